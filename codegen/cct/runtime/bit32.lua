@@ -0,0 +1,89 @@
+-- CC: Tweaked has the Lua 5.2 `bit32`, which lacks the bit counting
+-- functions from Luau that the runtime relies on
+local bit32 = (function()
+	local native = bit32
+	local bit32 = {}
+
+	for name, func in pairs(native) do
+		bit32[name] = func
+	end
+
+	local bit_and = native.band
+	local bit_lshift = native.lshift
+	local bit_rshift = native.rshift
+
+	function bit32.countlz(num)
+		num = bit_and(num, 0xFFFFFFFF)
+
+		if num == 0 then
+			return 32
+		end
+
+		local count = 0
+
+		if bit_and(num, 0xFFFF0000) == 0 then
+			count = count + 16
+			num = bit_lshift(num, 16)
+		end
+
+		if bit_and(num, 0xFF000000) == 0 then
+			count = count + 8
+			num = bit_lshift(num, 8)
+		end
+
+		if bit_and(num, 0xF0000000) == 0 then
+			count = count + 4
+			num = bit_lshift(num, 4)
+		end
+
+		if bit_and(num, 0xC0000000) == 0 then
+			count = count + 2
+			num = bit_lshift(num, 2)
+		end
+
+		if bit_and(num, 0x80000000) == 0 then
+			count = count + 1
+		end
+
+		return count
+	end
+
+	function bit32.countrz(num)
+		num = bit_and(num, 0xFFFFFFFF)
+
+		if num == 0 then
+			return 32
+		end
+
+		local count = 0
+
+		if bit_and(num, 0xFFFF) == 0 then
+			count = count + 16
+			num = bit_rshift(num, 16)
+		end
+
+		if bit_and(num, 0xFF) == 0 then
+			count = count + 8
+			num = bit_rshift(num, 8)
+		end
+
+		if bit_and(num, 0xF) == 0 then
+			count = count + 4
+			num = bit_rshift(num, 4)
+		end
+
+		if bit_and(num, 0x3) == 0 then
+			count = count + 2
+			num = bit_rshift(num, 2)
+		end
+
+		if bit_and(num, 0x1) == 0 then
+			count = count + 1
+		end
+
+		return count
+	end
+
+	return bit32
+end)()
+
@@ -0,0 +1,29 @@
+-- Computers are stopped when they run too long without yielding, so loops
+-- call in here and yield through the event queue once a second has passed
+local rt_yield = (function()
+	local CHECK_INTERVAL = 1024
+	local YIELD_INTERVAL = 1
+
+	local count = 0
+	local last = os.clock()
+
+	return function()
+		count = count + 1
+
+		if count < CHECK_INTERVAL then
+			return
+		end
+
+		count = 0
+
+		if os.clock() - last < YIELD_INTERVAL then
+			return
+		end
+
+		os.queueEvent("wasm_yield")
+		os.pullEvent("wasm_yield")
+
+		last = os.clock()
+	end
+end)()
+
@@ -0,0 +1,20 @@
+use std::io::{Result, Write};
+
+use wasm_ast::module::Module;
+
+fn do_runtime(lock: &mut dyn Write) -> Result<()> {
+	let runtime = codegen_cct::RUNTIME;
+
+	writeln!(lock, "{runtime}")
+}
+
+fn do_module(data: &[u8], lock: &mut dyn Write) -> Result<()> {
+	let wasm = Module::try_from_data(data).unwrap();
+
+	do_runtime(lock)?;
+	codegen_cct::from_module_untyped(&wasm, lock)
+}
+
+fn main() -> Result<()> {
+	codegen_core::run_command("wasm2cct", do_module)
+}
@@ -0,0 +1,22 @@
+// CC: Tweaked runs the Lua 5.1 output as is, only needing the `bit32` gaps
+// filled and loops that yield before the computer is stopped for hogging time
+pub static RUNTIME: &str = concat!(
+	include_str!("../runtime/bit32.lua"),
+	include_str!("../runtime/yield.lua"),
+	include_str!("../../lua51/runtime/buffer.lua"),
+	include_str!("../../lua51/runtime/runtime.lua"),
+);
+pub static RUNTIME_WORDS: &str = concat!(
+	include_str!("../runtime/bit32.lua"),
+	include_str!("../runtime/yield.lua"),
+	include_str!("../../lua51/runtime/buffer.lua"),
+	include_str!("../../lua51/runtime/buffer_words.lua"),
+	include_str!("../../lua51/runtime/runtime.lua"),
+);
+
+pub use translator::{
+	from_inst_list, from_module_custom, from_module_mapped, from_module_split, from_module_typed,
+	from_module_untyped, write_self_test,
+};
+
+mod translator;
@@ -0,0 +1,83 @@
+use std::io::{Result, Write};
+
+use codegen_core::{Mapping, Options, Split};
+use codegen_lua51::Lua51;
+use wasm_ast::module::{Module, TypeInfo};
+use wasmparser::Operator;
+
+const CC_TWEAKED: Lua51 = Lua51 {
+	has_loop_yield: true,
+};
+
+/// # Errors
+/// Returns `Err` if writing to `Write` failed.
+pub fn from_inst_list(code: &[Operator], type_info: &TypeInfo, w: &mut dyn Write) -> Result<()> {
+	codegen_core::from_inst_list(&CC_TWEAKED, code, type_info, w)
+}
+
+/// # Errors
+/// Returns `Err` if writing to `Write` failed.
+pub fn from_module_typed(wasm: &Module, type_info: &TypeInfo, w: &mut dyn Write) -> Result<()> {
+	codegen_core::from_module_typed(&CC_TWEAKED, wasm, type_info, w)
+}
+
+/// Translates the module, also embedding the contents of every custom section
+/// named in `name_list` as a string in the `custom_list` of the instance.
+///
+/// # Errors
+/// Returns `Err` if writing to `Write` failed.
+pub fn from_module_custom(
+	wasm: &Module,
+	type_info: &TypeInfo,
+	name_list: &[&str],
+	options: &Options,
+	w: &mut dyn Write,
+) -> Result<()> {
+	codegen_core::from_module_custom(&CC_TWEAKED, wasm, type_info, name_list, options, w)
+}
+
+/// Translates the module as in [`from_module_custom`], also returning the
+/// line that each function and statement starts at for a source map.
+///
+/// # Errors
+/// Returns `Err` if writing to `Write` failed.
+pub fn from_module_mapped(
+	wasm: &Module,
+	type_info: &TypeInfo,
+	name_list: &[&str],
+	options: &Options,
+	w: &mut dyn Write,
+) -> Result<Vec<Mapping>> {
+	codegen_core::from_module_mapped(&CC_TWEAKED, wasm, type_info, name_list, options, w)
+}
+
+/// Translates the module with its functions moved out to chunks, which are
+/// returned to be written where the `import` of `split` loads them from.
+///
+/// # Errors
+/// Returns `Err` if writing to `Write` failed.
+pub fn from_module_split(
+	wasm: &Module,
+	type_info: &TypeInfo,
+	name_list: &[&str],
+	options: &Options,
+	split: &Split,
+	w: &mut dyn Write,
+) -> Result<Vec<Vec<u8>>> {
+	codegen_core::from_module_split(&CC_TWEAKED, wasm, type_info, name_list, options, split, w)
+}
+
+/// # Errors
+/// Returns `Err` if writing to `Write` failed.
+pub fn from_module_untyped(wasm: &Module, w: &mut dyn Write) -> Result<()> {
+	codegen_core::from_module_untyped(&CC_TWEAKED, wasm, w)
+}
+
+/// Writes a script that checks the runtime against edge cases, as described
+/// in [`codegen_core::write_self_test`], to be run after the runtime.
+///
+/// # Errors
+/// Returns `Err` if writing to `Write` failed.
+pub fn write_self_test(w: &mut dyn Write) -> Result<()> {
+	codegen_core::write_self_test(&CC_TWEAKED, w)
+}
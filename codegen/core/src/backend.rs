@@ -0,0 +1,424 @@
+use std::io::{Result, Write};
+
+use wasm_ast::{
+	module::Module,
+	node::{BinOpType, CmpOpType, Expression, FuncData, UnOpType, Value},
+};
+
+use crate::options::Options;
+
+/// The dialect specific half of a translation. The driver in this crate
+/// handles imports, exports, and the init code, and calls back into the
+/// backend for everything that is written differently per target. It is
+/// shared between the threads that write functions in parallel.
+pub trait Backend: Sync {
+	/// Writes a function from its `function` header to the closing `end`,
+	/// drawing from `FUEL` on entry and on every loop iteration when the
+	/// `fuel` of `options` is set, and counting itself into `DEPTH` when its
+	/// `max_depth` is.
+	///
+	/// # Errors
+	/// Returns `Err` if writing to `Write` failed.
+	fn write_function(&self, func: &FuncData, options: &Options, w: &mut dyn Write) -> Result<()>;
+
+	/// Writes a function as in [`Backend::write_function`], but with a
+	/// [`write_mark`](crate::write_mark) before each statement for building a
+	/// source map. Backends that do not mark statements are mapped only by
+	/// where their functions start.
+	///
+	/// # Errors
+	/// Returns `Err` if writing to `Write` failed.
+	fn write_function_marked(
+		&self,
+		func: &FuncData,
+		options: &Options,
+		w: &mut dyn Write,
+	) -> Result<()> {
+		self.write_function(func, options, w)
+	}
+
+	/// Writes a standalone expression, such as the value of a constant.
+	///
+	/// # Errors
+	/// Returns `Err` if writing to `Write` failed.
+	fn write_expression(&self, expr: &Expression, w: &mut dyn Write) -> Result<()>;
+
+	/// Names the runtime operation behind a unary operator.
+	fn un_op_name(&self, op: UnOpType) -> (&'static str, &'static str);
+
+	/// Names the runtime operation behind a binary operator, or `None` if
+	/// it is written as a symbol instead.
+	fn bin_op_name(&self, op: BinOpType) -> Option<(&'static str, &'static str)>;
+
+	/// Names the runtime operation behind a comparison, or `None` if it is
+	/// written as a symbol instead.
+	fn cmp_op_name(&self, op: CmpOpType) -> Option<(&'static str, &'static str)>;
+
+	/// Names the runtime constructor behind a constant, or `None` if it is
+	/// written as a literal instead.
+	fn value_name(&self, _value: Value) -> Option<(&'static str, &'static str)> {
+		None
+	}
+
+	/// Binds a runtime operation named by the backend, or a `load` or
+	/// `store` of a memory access type, to a local.
+	///
+	/// # Errors
+	/// Returns `Err` if writing to `Write` failed.
+	fn write_local_operation(&self, _head: &str, _tail: &str, _w: &mut dyn Write) -> Result<()> {
+		Ok(())
+	}
+
+	/// Declares the local that caches the memory at `index`.
+	///
+	/// # Errors
+	/// Returns `Err` if writing to `Write` failed.
+	fn write_memory_local(&self, index: usize, w: &mut dyn Write) -> Result<()> {
+		writeln!(w, "local memory_at_{index}")
+	}
+
+	/// Starts a chunk, which takes the `env` of an instance and defines
+	/// functions over it, with everything up to the parameters.
+	///
+	/// # Errors
+	/// Returns `Err` if writing to `Write` failed.
+	fn write_chunk_start(&self, w: &mut dyn Write) -> Result<()> {
+		writeln!(w, "function(env)")
+	}
+
+	/// Declares the local that a chunk reads `name` into from its `env`.
+	///
+	/// # Errors
+	/// Returns `Err` if writing to `Write` failed.
+	fn write_env_local(&self, name: &str, w: &mut dyn Write) -> Result<()> {
+		writeln!(w, "\tlocal {name} = env.{name}")
+	}
+
+	/// Starts the declaration of the `env` that an instance hands to chunks,
+	/// which the fields of it follow.
+	///
+	/// # Errors
+	/// Returns `Err` if writing to `Write` failed.
+	fn write_env_start(&self, w: &mut dyn Write) -> Result<()> {
+		writeln!(w, "local env = {{")
+	}
+
+	/// Starts the declaration of the jump tables of a function, which the
+	/// list of them follows.
+	///
+	/// # Errors
+	/// Returns `Err` if writing to `Write` failed.
+	fn write_jump_table_local(&self, w: &mut dyn Write) -> Result<()> {
+		write!(w, "local br_map = ")
+	}
+
+	/// Writes the name of a function in the runtime.
+	///
+	/// # Errors
+	/// Returns `Err` if writing to `Write` failed.
+	fn write_runtime_name(&self, head: &str, tail: &str, w: &mut dyn Write) -> Result<()> {
+		write!(w, "rt.{head}.{tail}")
+	}
+
+	/// Writes the runtime table that traps are raised through, which also
+	/// takes how the instance wants them raised.
+	///
+	/// # Errors
+	/// Returns `Err` if writing to `Write` failed.
+	fn write_trap_table(&self, w: &mut dyn Write) -> Result<()> {
+		write!(w, "rt.trap")
+	}
+
+	/// Writes the runtime table that traced runtimes pass watched accesses
+	/// through, which also stands in for the globals of the instance.
+	///
+	/// # Errors
+	/// Returns `Err` if writing to `Write` failed.
+	fn write_trace_table(&self, w: &mut dyn Write) -> Result<()> {
+		write!(w, "rt.trace")
+	}
+
+	/// Writes anything needed before the module level arrays are declared.
+	///
+	/// # Errors
+	/// Returns `Err` if writing to `Write` failed.
+	fn write_header(&self, _w: &mut dyn Write) -> Result<()> {
+		Ok(())
+	}
+
+	/// Writes anything needed after the function that instantiates the module.
+	///
+	/// # Errors
+	/// Returns `Err` if writing to `Write` failed.
+	fn write_footer(&self, _w: &mut dyn Write) -> Result<()> {
+		Ok(())
+	}
+
+	/// Declares the `FUEL` table that metered functions count down, starting
+	/// with `budget` left.
+	///
+	/// # Errors
+	/// Returns `Err` if writing to `Write` failed.
+	fn write_fuel_state(&self, budget: u64, w: &mut dyn Write) -> Result<()> {
+		writeln!(w, "local FUEL = {{ left = {budget} }}")
+	}
+
+	/// Declares the `DEPTH` table that depth limited functions count their
+	/// calls in, and `guard_depth`, which wraps an exported function so that a
+	/// call from the host failing puts the count back to where it started.
+	///
+	/// # Errors
+	/// Returns `Err` if writing to `Write` failed.
+	fn write_depth_state(&self, w: &mut dyn Write) -> Result<()> {
+		writeln!(w, "local DEPTH = {{ now = 0 }}")?;
+		writeln!(w, "local function leave_depth(depth, success, ...)")?;
+		writeln!(w, "\tif not success then")?;
+		writeln!(w, "\t\tDEPTH.now = depth")?;
+		writeln!(w, "\t\terror((...), 0)")?;
+		writeln!(w, "\tend")?;
+		writeln!(w, "\treturn ...")?;
+		writeln!(w, "end")?;
+		writeln!(w, "local function guard_depth(func)")?;
+		writeln!(w, "\treturn function(...)")?;
+		writeln!(w, "\t\treturn leave_depth(DEPTH.now, pcall(func, ...))")?;
+		writeln!(w, "\tend")?;
+		writeln!(w, "end")
+	}
+
+	/// Declares the `YIELD` table that yielding loops count down, starting at
+	/// and reset to `every` iterations.
+	///
+	/// # Errors
+	/// Returns `Err` if writing to `Write` failed.
+	fn write_yield_state(&self, every: u32, w: &mut dyn Write) -> Result<()> {
+		writeln!(w, "local YIELD = {{ left = {every}, every = {every} }}")
+	}
+
+	/// Declares `call_async`, which calls an export of an instance in its
+	/// own coroutine and returns a task that finishes once the export does.
+	/// Imports suspend the export by yielding a function, which is called with
+	/// another that resumes it with the results of the import.
+	///
+	/// # Errors
+	/// Returns `Err` if writing to `Write` failed.
+	fn write_async_state(&self, w: &mut dyn Write) -> Result<()> {
+		writeln!(w, "local function call_async(instance, name, ...)")?;
+		writeln!(w, "\tlocal func = instance.func_list[name]")?;
+		writeln!(w, "\tif not func then")?;
+		writeln!(
+			w,
+			"\t\terror(\"no exported function named \" .. tostring(name), 2)"
+		)?;
+		writeln!(w, "\tend")?;
+		writeln!(w, "\tlocal thread = coroutine.create(func)")?;
+		writeln!(w, "\tlocal task = {{ done = false }}")?;
+		writeln!(w, "\tlocal function step(success, ...)")?;
+		writeln!(
+			w,
+			"\t\tif success and coroutine.status(thread) ~= \"dead\" then"
+		)?;
+		writeln!(w, "\t\t\tlocal start = ...")?;
+		writeln!(w, "\t\t\tif type(start) ~= \"function\" then")?;
+		writeln!(w, "\t\t\t\treturn step(coroutine.resume(thread))")?;
+		writeln!(w, "\t\t\tend")?;
+		writeln!(w, "\t\t\treturn start(function(...)")?;
+		writeln!(w, "\t\t\t\tstep(coroutine.resume(thread, ...))")?;
+		writeln!(w, "\t\t\tend)")?;
+		writeln!(w, "\t\tend")?;
+		writeln!(w, "\t\ttask.done = true")?;
+		writeln!(w, "\t\ttask.success = success")?;
+		writeln!(w, "\t\ttask.results = {{ ... }}")?;
+		writeln!(w, "\t\ttask.count = select(\"#\", ...)")?;
+		writeln!(w, "\t\tif task.callback then")?;
+		writeln!(w, "\t\t\ttask.callback(success, ...)")?;
+		writeln!(w, "\t\tend")?;
+		writeln!(w, "\tend")?;
+		writeln!(w, "\tfunction task.on_done(self, callback)")?;
+		writeln!(w, "\t\tif self.done then")?;
+		writeln!(w, "\t\t\tlocal unpack = table.unpack or unpack")?;
+		writeln!(
+			w,
+			"\t\t\tcallback(self.success, unpack(self.results, 1, self.count))"
+		)?;
+		writeln!(w, "\t\telse")?;
+		writeln!(w, "\t\t\tself.callback = callback")?;
+		writeln!(w, "\t\tend")?;
+		writeln!(w, "\tend")?;
+		writeln!(w, "\tstep(coroutine.resume(thread, ...))")?;
+		writeln!(w, "\treturn task")?;
+		writeln!(w, "end")
+	}
+
+	/// Declares the `PROFILE` table that profiled functions count their calls
+	/// and time in, with `wrap`, which makes a function do so, and `dump`,
+	/// which lists the functions called by the time spent in them. The time of
+	/// a function leaves out that of the calls it makes, which is kept in
+	/// `inner` while it runs.
+	///
+	/// # Errors
+	/// Returns `Err` if writing to `Write` failed.
+	fn write_profile_state(&self, w: &mut dyn Write) -> Result<()> {
+		writeln!(
+			w,
+			"local PROFILE = {{ count = {{}}, time = {{}}, name = {{}}, inner = 0 }}"
+		)?;
+		writeln!(w, "function PROFILE.leave(index, start, outer, ...)")?;
+		writeln!(w, "\tlocal spent = os.clock() - start")?;
+		writeln!(
+			w,
+			"\tPROFILE.time[index] = PROFILE.time[index] + (spent - PROFILE.inner)"
+		)?;
+		writeln!(w, "\tPROFILE.inner = outer + spent")?;
+		writeln!(w, "\treturn ...")?;
+		writeln!(w, "end")?;
+		writeln!(w, "function PROFILE.wrap(index, func)")?;
+		writeln!(w, "\tPROFILE.count[index] = 0")?;
+		writeln!(w, "\tPROFILE.time[index] = 0")?;
+		writeln!(w, "\treturn function(...)")?;
+		writeln!(w, "\t\tlocal start, outer = os.clock(), PROFILE.inner")?;
+		writeln!(w, "\t\tPROFILE.inner = 0")?;
+		writeln!(w, "\t\tPROFILE.count[index] = PROFILE.count[index] + 1")?;
+		writeln!(
+			w,
+			"\t\treturn PROFILE.leave(index, start, outer, func(...))"
+		)?;
+		writeln!(w, "\tend")?;
+		writeln!(w, "end")?;
+		writeln!(w, "function PROFILE.dump()")?;
+		writeln!(w, "\tlocal list = {{}}")?;
+		writeln!(w, "\tfor index, count in pairs(PROFILE.count) do")?;
+		writeln!(w, "\t\tif count ~= 0 then")?;
+		writeln!(
+			w,
+			"\t\t\tlist[#list + 1] = {{ index = index, name = PROFILE.name[index], count = count, time = PROFILE.time[index] }}"
+		)?;
+		writeln!(w, "\t\tend")?;
+		writeln!(w, "\tend")?;
+		writeln!(
+			w,
+			"\ttable.sort(list, function(a, b) return a.time > b.time end)"
+		)?;
+		writeln!(w, "\treturn list")?;
+		writeln!(w, "end")
+	}
+
+	/// Declares the `COVERAGE` table of block counters, with `new`, which
+	/// makes the zeroed counters of a function, and `dump`, which lists every
+	/// block of the functions defined with its `func`, `block`, and `count`.
+	///
+	/// # Errors
+	/// Returns `Err` if writing to `Write` failed.
+	fn write_coverage_state(&self, w: &mut dyn Write) -> Result<()> {
+		writeln!(w, "local COVERAGE = {{ hit = {{}}, len = {{}} }}")?;
+		writeln!(w, "function COVERAGE.new(index, len)")?;
+		writeln!(w, "\tlocal hit = {{}}")?;
+		writeln!(w, "\tfor block = 0, len - 1 do")?;
+		writeln!(w, "\t\thit[block] = 0")?;
+		writeln!(w, "\tend")?;
+		writeln!(w, "\tCOVERAGE.hit[index] = hit")?;
+		writeln!(w, "\tCOVERAGE.len[index] = len")?;
+		writeln!(w, "\treturn hit")?;
+		writeln!(w, "end")?;
+		writeln!(w, "function COVERAGE.dump()")?;
+		writeln!(w, "\tlocal list = {{}}")?;
+		writeln!(w, "\tfor index, hit in pairs(COVERAGE.hit) do")?;
+		writeln!(w, "\t\tfor block = 0, COVERAGE.len[index] - 1 do")?;
+		writeln!(
+			w,
+			"\t\t\tlist[#list + 1] = {{ func = index, block = block, count = hit[block] }}"
+		)?;
+		writeln!(w, "\t\tend")?;
+		writeln!(w, "\tend")?;
+		writeln!(w, "\ttable.sort(list, function(a, b)")?;
+		writeln!(w, "\t\tif a.func ~= b.func then")?;
+		writeln!(w, "\t\t\treturn a.func < b.func")?;
+		writeln!(w, "\t\tend")?;
+		writeln!(w, "\t\treturn a.block < b.block")?;
+		writeln!(w, "\tend)")?;
+		writeln!(w, "\treturn list")?;
+		writeln!(w, "end")
+	}
+
+	/// Declares one of the module level arrays, such as `FUNC_LIST`.
+	///
+	/// # Errors
+	/// Returns `Err` if writing to `Write` failed.
+	fn write_named_array(&self, name: &str, len: usize, w: &mut dyn Write) -> Result<()> {
+		if len == 0 {
+			return Ok(());
+		}
+
+		writeln!(w, "local {name} = {{}}")
+	}
+
+	/// Writes a quoted string literal holding arbitrary bytes, escaped into
+	/// one buffer that is written at once.
+	///
+	/// # Errors
+	/// Returns `Err` if writing to `Write` failed.
+	fn write_string(&self, data: &[u8], w: &mut dyn Write) -> Result<()> {
+		let mut buffer = Vec::with_capacity(data.len() + 2);
+
+		buffer.push(b'"');
+		buffer.extend(data.escape_ascii());
+		buffer.push(b'"');
+
+		w.write_all(&buffer)
+	}
+
+	/// Copies the 1 based `data` array into `target` starting at `offset`
+	/// when an active element segment is applied.
+	///
+	/// # Errors
+	/// Returns `Err` if writing to `Write` failed.
+	fn write_element_copy(&self, w: &mut dyn Write) -> Result<()> {
+		writeln!(w, "\t\ttable.move(data, 1, data.n, offset, target)")
+	}
+
+	/// Writes the runtime that the instance carries as `rt`, before the
+	/// export lists are built.
+	///
+	/// # Errors
+	/// Returns `Err` if writing to `Write` failed.
+	fn write_export_runtime(&self, w: &mut dyn Write) -> Result<()> {
+		writeln!(
+			w,
+			"\t\t-- `rt.string.read(memory, addr, len)`, `rt.string.read_cstr(memory, addr)`, and"
+		)?;
+		writeln!(
+			w,
+			"\t\t-- `rt.string.write(memory, addr, data)` exchange strings with a memory"
+		)?;
+		writeln!(w, "\t\trt = rt,")
+	}
+
+	/// Opens the function that instantiates the module, which takes the
+	/// imports as `wasm`, how to raise traps as `on_trap`, and any instance to
+	/// adopt the state of as `previous`.
+	///
+	/// # Errors
+	/// Returns `Err` if writing to `Write` failed.
+	fn write_instance_start(&self, _wasm: &Module, w: &mut dyn Write) -> Result<()> {
+		writeln!(w, "return function(wasm, on_trap, previous)")
+	}
+
+	/// Closes the table of exports returned by the instance.
+	///
+	/// # Errors
+	/// Returns `Err` if writing to `Write` failed.
+	fn write_instance_end(&self, w: &mut dyn Write) -> Result<()> {
+		writeln!(w, "\t}}")
+	}
+
+	/// Whether the runtime carries `rt.wasi`, which imports from
+	/// `wasi_snapshot_preview1` fall back to when the host does not pass them.
+	fn has_wasi(&self) -> bool {
+		false
+	}
+
+	/// Whether `i64` values are plain numbers, in which case offsets into a
+	/// 64-bit memory are used without conversion.
+	fn has_native_i64(&self) -> bool {
+		false
+	}
+}
@@ -0,0 +1,90 @@
+use wasm_ast::node::{CmpOpType, Expression, Select, Value};
+
+/// A `select` on a comparison between its own two operands, which picks the
+/// least or the greatest of them and is written as `math.min` or `math.max`.
+pub struct Bound<'a> {
+	is_least: bool,
+	lhs: &'a Expression,
+	rhs: &'a Expression,
+}
+
+impl<'a> Bound<'a> {
+	/// Returns `min` or `max`, after the function of `math` that picks the
+	/// same operand.
+	#[must_use]
+	pub const fn name(&self) -> &'static str {
+		if self.is_least {
+			"min"
+		} else {
+			"max"
+		}
+	}
+
+	#[must_use]
+	pub const fn lhs(&self) -> &'a Expression {
+		self.lhs
+	}
+
+	#[must_use]
+	pub const fn rhs(&self) -> &'a Expression {
+		self.rhs
+	}
+}
+
+// Operands are only matched when reading them twice is sure to give the same
+// value, as both the comparison and the arm read them
+fn is_same(lhs: &Expression, rhs: &Expression) -> bool {
+	match (lhs, rhs) {
+		(Expression::GetLocal(lhs), Expression::GetLocal(rhs)) => lhs.var() == rhs.var(),
+		(Expression::GetTemporary(lhs), Expression::GetTemporary(rhs)) => lhs.var() == rhs.var(),
+		(Expression::Value(Value::I32(lhs)), Expression::Value(Value::I32(rhs))) => lhs == rhs,
+		(Expression::Value(Value::I64(lhs)), Expression::Value(Value::I64(rhs))) => lhs == rhs,
+		_ => false,
+	}
+}
+
+// Returns whether the comparison is signed, is on `i64` values, and holds when
+// its left operand is the least
+const fn get_order(op_type: CmpOpType) -> Option<(bool, bool, bool)> {
+	let order = match op_type {
+		CmpOpType::LtS_I32 | CmpOpType::LeS_I32 => (true, false, true),
+		CmpOpType::LtU_I32 | CmpOpType::LeU_I32 => (false, false, true),
+		CmpOpType::GtS_I32 | CmpOpType::GeS_I32 => (true, false, false),
+		CmpOpType::GtU_I32 | CmpOpType::GeU_I32 => (false, false, false),
+		CmpOpType::LtS_I64 | CmpOpType::LeS_I64 => (true, true, true),
+		CmpOpType::GtS_I64 | CmpOpType::GeS_I64 => (true, true, false),
+		_ => return None,
+	};
+
+	Some(order)
+}
+
+/// Finds the operands of a `select` that picks the least or the greatest of
+/// the two integers that its condition compares. Floats are left out as the
+/// runtimes disagree on which operand `math.min` gives back for a NaN. Targets
+/// that keep `i32` values signed only take signed comparisons and the others
+/// only unsigned ones, and only those with `i64` values that `math.min` can
+/// compare take `i64` comparisons, which are then signed.
+#[must_use]
+pub fn find_bound(select: &Select, is_signed: bool, has_integer: bool) -> Option<Bound<'_>> {
+	let Expression::CmpOp(cmp_op) = select.condition() else {
+		return None;
+	};
+
+	let (signed, is_i64, is_left_least) = get_order(cmp_op.op_type())?;
+
+	if (is_i64 && !has_integer) || (!is_i64 && signed != is_signed) {
+		return None;
+	}
+
+	let (lhs, rhs) = (cmp_op.lhs(), cmp_op.rhs());
+	let is_least = if is_same(select.on_true(), lhs) && is_same(select.on_false(), rhs) {
+		is_left_least
+	} else if is_same(select.on_true(), rhs) && is_same(select.on_false(), lhs) {
+		!is_left_least
+	} else {
+		return None;
+	};
+
+	Some(Bound { is_least, lhs, rhs })
+}
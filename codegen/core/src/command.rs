@@ -0,0 +1,46 @@
+#[cfg(feature = "wat")]
+use std::io::Error;
+use std::io::{BufWriter, ErrorKind, Result, Write};
+
+// Text modules are only understood when built with the `wat` feature,
+// binary modules are passed through either way
+#[cfg(feature = "wat")]
+fn read_source(path: String) -> Result<Vec<u8>> {
+	wat::parse_file(path).map_err(|e| Error::new(ErrorKind::InvalidData, e))
+}
+
+#[cfg(not(feature = "wat"))]
+fn read_source(path: String) -> Result<Vec<u8>> {
+	std::fs::read(path)
+}
+
+fn load_arg_source(name: &str) -> Result<Vec<u8>> {
+	let mut arguments = std::env::args();
+	let path = arguments.next().unwrap_or_else(|| name.to_string());
+
+	arguments.next().map_or_else(
+		|| {
+			eprintln!("usage: {path} <file>\n");
+
+			Err(ErrorKind::NotFound.into())
+		},
+		read_source,
+	)
+}
+
+/// Runs the `wasm2*` utility called `name`, passing the module read from the
+/// file given as its first argument to `write` along with standard output.
+/// Standard output is flushed at every line break, so the many small writes
+/// are gathered before they reach it.
+///
+/// # Errors
+///
+/// Returns an `io::Error` if no file is given, it cannot be read, or writing
+/// the output fails.
+pub fn run_command(name: &str, write: fn(&[u8], &mut dyn Write) -> Result<()>) -> Result<()> {
+	let data = load_arg_source(name)?;
+	let lock = &mut BufWriter::new(std::io::stdout().lock());
+
+	write(&data, lock)?;
+	lock.flush()
+}
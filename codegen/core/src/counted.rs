@@ -0,0 +1,279 @@
+use wasm_ast::{
+	node::{
+		BinOpType, Block, CmpOpType, Expression, LabelType, Local, SetLocal, Statement, Terminator,
+		Value,
+	},
+	visit::{Driver, Visitor},
+};
+
+/// A loop that ends by stepping a local by a constant and branching back
+/// while it has not passed a bound, which is written as a numeric `for`
+/// over the rest of its body.
+pub struct Counted<'a> {
+	var: Local,
+	step: i32,
+	bound: &'a Expression,
+	offset: i32,
+	num_statement: usize,
+}
+
+impl<'a> Counted<'a> {
+	/// Returns the local that is stepped.
+	#[must_use]
+	pub const fn var(&self) -> Local {
+		self.var
+	}
+
+	/// Returns the constant that the local is stepped by.
+	#[must_use]
+	pub const fn step(&self) -> i32 {
+		self.step
+	}
+
+	/// Returns the bound, which is a constant or a local that the body
+	/// never sets.
+	#[must_use]
+	pub const fn bound(&self) -> &'a Expression {
+		self.bound
+	}
+
+	/// Returns what is added to the bound to get the last value that the
+	/// comparison lets through.
+	#[must_use]
+	pub const fn offset(&self) -> i32 {
+		self.offset
+	}
+
+	/// Returns the number of statements before the step.
+	#[must_use]
+	pub const fn num_statement(&self) -> usize {
+		self.num_statement
+	}
+}
+
+struct SetLocalList {
+	var_list: [usize; 2],
+	is_set: bool,
+}
+
+impl Visitor for SetLocalList {
+	fn visit_set_local(&mut self, set_local: &SetLocal) {
+		self.is_set |= self.var_list.contains(&set_local.var().var());
+	}
+}
+
+fn get_step(stat: &Statement) -> Option<(Local, i32)> {
+	let Statement::SetLocal(set_local) = stat else {
+		return None;
+	};
+
+	let Expression::BinOp(bin_op) = set_local.value() else {
+		return None;
+	};
+
+	let (Expression::GetLocal(var), Expression::Value(Value::I32(value))) =
+		(bin_op.lhs(), bin_op.rhs())
+	else {
+		return None;
+	};
+
+	let step = match bin_op.op_type() {
+		BinOpType::Add_I32 => *value,
+		BinOpType::Sub_I32 => value.checked_neg()?,
+		_ => return None,
+	};
+
+	(var.var() == set_local.var().var() && step != 0).then_some((*var, step))
+}
+
+// Returns whether the comparison is signed, holds below the bound, and holds
+// at the bound
+const fn get_order(op_type: CmpOpType) -> Option<(bool, bool, bool)> {
+	let order = match op_type {
+		CmpOpType::LtS_I32 => (true, true, false),
+		CmpOpType::LtU_I32 => (false, true, false),
+		CmpOpType::LeS_I32 => (true, true, true),
+		CmpOpType::LeU_I32 => (false, true, true),
+		CmpOpType::GtS_I32 => (true, false, false),
+		CmpOpType::GtU_I32 => (false, false, false),
+		CmpOpType::GeS_I32 => (true, false, true),
+		CmpOpType::GeU_I32 => (false, false, true),
+		_ => return None,
+	};
+
+	Some(order)
+}
+
+fn has_branch_out_of_block(block: &Block, depth: usize) -> bool {
+	let depth = depth + 1;
+	let has_last = match block.last() {
+		Some(Terminator::Br(v)) => v.target() >= depth,
+		Some(Terminator::BrTable(v)) => std::iter::once(v.default())
+			.chain(v.data().iter().copied())
+			.any(|v| v.target() >= depth),
+		_ => false,
+	};
+
+	has_last || has_branch_out(block.code(), depth)
+}
+
+// Branches from `depth` blocks into the body leave it when they target at
+// least as many, as do delegated exceptions
+fn has_branch_out(code: &[Statement], depth: usize) -> bool {
+	code.iter().any(|stat| match stat {
+		Statement::Block(v) => has_branch_out_of_block(v, depth),
+		Statement::BrIf(v) => v.target().target() >= depth,
+		Statement::If(v) => {
+			has_branch_out_of_block(v.on_true(), depth)
+				|| v.on_false()
+					.is_some_and(|v| has_branch_out_of_block(v, depth))
+		}
+		Statement::Try(v) => {
+			v.delegate().is_some()
+				|| has_branch_out_of_block(v.code(), depth)
+				|| v.catch_list()
+					.iter()
+					.any(|v| has_branch_out_of_block(v.code(), depth))
+		}
+		_ => false,
+	})
+}
+
+/// Finds the step and bound of a loop whose body ends by stepping a local by
+/// a constant and branching back while a comparison to a bound holds. Targets
+/// that keep `i32` values signed only take signed comparisons and the others
+/// only unsigned ones, and those without `goto` take no body with a branch
+/// that leaves it, as a `break` would leave the `for` in its place.
+#[must_use]
+pub fn find_counted(block: &Block, is_signed: bool, has_goto: bool) -> Option<Counted<'_>> {
+	if block.label_type() != Some(LabelType::Backward) {
+		return None;
+	}
+
+	let [body @ .., step, Statement::BrIf(branch)] = block.code() else {
+		return None;
+	};
+
+	let (var, step) = get_step(step)?;
+	let target = branch.target();
+
+	if body.is_empty() || target.target() != 0 || !target.align().is_aligned() {
+		return None;
+	}
+
+	let Expression::CmpOp(cmp_op) = branch.condition() else {
+		return None;
+	};
+
+	let (signed, below, inclusive) = get_order(cmp_op.op_type())?;
+	let (bound, below) = match (cmp_op.lhs(), cmp_op.rhs()) {
+		(Expression::GetLocal(lhs), rhs) if lhs.var() == var.var() => (rhs, below),
+		(lhs, Expression::GetLocal(rhs)) if rhs.var() == var.var() => (lhs, !below),
+		_ => return None,
+	};
+
+	let bound_var = match bound {
+		Expression::Value(Value::I32(_)) => var.var(),
+		Expression::GetLocal(v) if v.var() != var.var() => v.var(),
+		_ => return None,
+	};
+
+	if signed != is_signed || below != (step > 0) {
+		return None;
+	}
+
+	let mut visitor = SetLocalList {
+		var_list: [var.var(), bound_var],
+		is_set: false,
+	};
+
+	for stat in body {
+		stat.accept(&mut visitor);
+	}
+
+	if visitor.is_set || (!has_goto && has_branch_out(body, 0)) {
+		return None;
+	}
+
+	let offset = match (below, inclusive) {
+		(_, true) => 0,
+		(true, false) => -1,
+		(false, false) => 1,
+	};
+
+	Some(Counted {
+		var,
+		step,
+		bound,
+		offset,
+		num_statement: body.len(),
+	})
+}
+
+#[cfg(test)]
+mod test {
+	use wasm_ast::{
+		factory::Factory,
+		module::{Module, TypeInfo},
+		node::{Block, Expression, FuncData, Statement},
+	};
+
+	// Builds a function that steps its first parameter through a loop
+	// ending in `stop`, after the body given
+	fn build_loop(body: &str, stop: &str) -> FuncData {
+		let text = format!(
+			"(module
+				(func (param i32 i32) (local i32)
+					loop
+						{body}
+						local.get 0 i32.const 1 i32.add local.set 0
+						local.get 0 local.get 1 {stop} br_if 0
+					end
+				)
+			)"
+		);
+
+		let data = wat::parse_str(text).unwrap();
+		let wasm = Module::try_from_data(&data).unwrap();
+		let type_info = TypeInfo::from_module(&wasm);
+		let mut factory = Factory::from_type_info(&type_info);
+
+		factory.create_indexed(0, &wasm.code_section()[0]).unwrap()
+	}
+
+	fn find_loop(func: &FuncData) -> &Block {
+		let [Statement::Block(block)] = func.code().code() else {
+			panic!("the function should be a single loop");
+		};
+
+		block
+	}
+
+	#[test]
+	fn count_up_to_local() {
+		let func = build_loop("local.get 2 i32.const 3 i32.add local.set 2", "i32.lt_s");
+		let counted = super::find_counted(find_loop(&func), true, true).unwrap();
+
+		assert_eq!(counted.var().var(), 0);
+		assert_eq!(counted.step(), 1);
+		assert_eq!(counted.offset(), -1);
+		assert_eq!(counted.num_statement(), 1);
+		assert!(matches!(counted.bound(), Expression::GetLocal(v) if v.var() == 1));
+	}
+
+	#[test]
+	fn skip_body_setting_bound() {
+		let func = build_loop("local.get 2 local.set 1", "i32.lt_s");
+
+		assert!(super::find_counted(find_loop(&func), true, true).is_none());
+	}
+
+	#[test]
+	fn skip_unsigned_when_signed() {
+		let func = build_loop("local.get 2 i32.const 3 i32.add local.set 2", "i32.lt_u");
+		let block = find_loop(&func);
+
+		assert!(super::find_counted(block, true, true).is_none());
+		assert!(super::find_counted(block, false, true).is_some());
+	}
+}
@@ -0,0 +1,24 @@
+use wasm_ast::{
+	node::{Block, FuncData},
+	visit::{Driver, Visitor},
+};
+
+struct Visit {
+	count: usize,
+}
+
+impl Visitor for Visit {
+	fn visit_block(&mut self, _: &Block) {
+		self.count += 1;
+	}
+}
+
+/// Counts the blocks that a covered function numbers as it is written,
+/// including the one of its body.
+pub fn count_blocks(ast: &FuncData) -> usize {
+	let mut visit = Visit { count: 0 };
+
+	ast.accept(&mut visit);
+
+	visit.count
+}
@@ -0,0 +1,111 @@
+use std::io::{Result, Write};
+
+use wasm_ast::node::{BinOpType, CmpOpType};
+use wasmparser::ValType;
+
+use crate::backend::Backend;
+
+/// How branches out of a block are written, which decides how blocks and
+/// loops are laid out.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum BranchStyle {
+	/// Blocks end with a label and loops start with one, which branches
+	/// `goto` directly.
+	Goto,
+	/// Every block is a loop that branches `break` out of, or `continue` when
+	/// it is a loop, with `desired` set to the level of targets further out.
+	Continue,
+	/// As with [`BranchStyle::Continue`], but without `continue`, so loops run
+	/// their body inside a `repeat` that is left to go around again.
+	Repeat,
+}
+
+/// The syntax of a Lua dialect, which the function writer of this crate
+/// lowers statements and expressions through. Backends that write functions
+/// with [`write_function`](crate::write_function) implement it along with
+/// [`Backend`], which names the runtime operations.
+pub trait Dialect: Backend {
+	/// Returns how branches are written.
+	fn branch_style(&self) -> BranchStyle;
+
+	/// Whether `i32` values are kept signed, rather than unsigned.
+	fn is_signed(&self) -> bool;
+
+	/// Whether adding, subtracting, and multiplying `i32` values by small
+	/// constants is written natively and wrapped by the `to_signed` of the
+	/// header.
+	fn has_native_i32(&self) -> bool {
+		false
+	}
+
+	/// Whether numbers are integers that wrap on overflow, so that `i32`
+	/// operators given a symbol keep their low 32 bits exact however far they
+	/// carry and are wrapped back into range natively where they are used.
+	fn has_wrapping_integers(&self) -> bool {
+		false
+	}
+
+	/// Whether unsigned comparisons are all written through the less than
+	/// named by [`Backend::cmp_op_name`], with its operands swapped or its
+	/// result negated for the others.
+	fn has_unsigned_less(&self) -> bool {
+		false
+	}
+
+	/// Whether `if` expressions are available, which unlike `and` and `or`
+	/// hold values that are `nil`.
+	fn has_if_expression(&self) -> bool {
+		false
+	}
+
+	/// Returns the function called at the start of every iteration of a loop,
+	/// for hosts that stop code which runs too long without yielding. Loops
+	/// that call it are never written as numeric `for` loops.
+	fn loop_call(&self) -> Option<&'static str> {
+		None
+	}
+
+	/// Returns the symbol of a binary operator, or `None` if it is written
+	/// as a call to the operation named by [`Backend::bin_op_name`] instead.
+	fn bin_op_symbol(&self, op: BinOpType) -> Option<&'static str>;
+
+	/// Returns the symbol of a comparison, or `None` if it is written as a
+	/// call to the operation named by [`Backend::cmp_op_name`] instead.
+	fn cmp_op_symbol(&self, op: CmpOpType) -> Option<&'static str>;
+
+	/// Writes a constant `i64`.
+	///
+	/// # Errors
+	/// Returns `Err` if writing to `Write` failed.
+	fn write_i64(&self, number: i64, w: &mut dyn Write) -> Result<()>;
+
+	/// Writes the runtime table that holds related functions, such as those
+	/// of `exception` or `simd`.
+	///
+	/// # Errors
+	/// Returns `Err` if writing to `Write` failed.
+	fn write_runtime_table(&self, name: &str, w: &mut dyn Write) -> Result<()> {
+		write!(w, "rt.{name}")
+	}
+
+	/// Writes the name of the `load` or `store` of a memory access type.
+	///
+	/// # Errors
+	/// Returns `Err` if writing to `Write` failed.
+	fn write_access_name(&self, head: &str, tail: &str, w: &mut dyn Write) -> Result<()> {
+		write!(w, "{head}_{tail}")
+	}
+
+	/// Writes the size in pages of the memory at `index`.
+	///
+	/// # Errors
+	/// Returns `Err` if writing to `Write` failed.
+	fn write_memory_size(&self, index: usize, w: &mut dyn Write) -> Result<()> {
+		write!(w, "memory_at_{index}.min")
+	}
+
+	/// Returns the annotation written after parameters and locals of a type.
+	fn type_annotation(&self, _ty: ValType) -> &'static str {
+		""
+	}
+}
@@ -1,13 +1,16 @@
 use std::collections::HashMap;
 
 use wasm_ast::{
-	node::{Br, BrIf, BrTable, FuncData},
+	node::{Block, Br, BrIf, BrTable, FuncData, LabelType},
 	visit::{Driver, Visitor},
 };
 
+use crate::dialect::BranchStyle;
+
 struct Visit {
 	br_map: HashMap<usize, usize>,
 	has_branch: bool,
+	is_loop_branch: bool,
 }
 
 impl Visit {
@@ -19,6 +22,12 @@ impl Visit {
 }
 
 impl Visitor for Visit {
+	fn visit_block(&mut self, stat: &Block) {
+		if self.is_loop_branch && stat.label_type() == Some(LabelType::Backward) {
+			self.has_branch = true;
+		}
+	}
+
 	fn visit_br(&mut self, stat: Br) {
 		self.set_branch(stat);
 	}
@@ -45,10 +54,13 @@ impl Visitor for Visit {
 	}
 }
 
-pub fn visit(ast: &FuncData) -> (HashMap<usize, usize>, bool) {
+// Numbers the jump tables of a function, and finds whether any branch has to
+// go through `desired`, which loops without `continue` always do
+pub fn visit(ast: &FuncData, style: BranchStyle) -> (HashMap<usize, usize>, bool) {
 	let mut visit = Visit {
 		br_map: HashMap::new(),
 		has_branch: false,
+		is_loop_branch: style == BranchStyle::Repeat,
 	};
 
 	ast.accept(&mut visit);
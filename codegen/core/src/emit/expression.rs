@@ -0,0 +1,701 @@
+use std::{
+	io::{Result, Write},
+	num::FpCategory,
+};
+
+use wasm_ast::node::{
+	BinOp, BinOpType, CmpOp, CmpOpType, Expression, GetGlobal, LoadAt, Local, MemorySize,
+	RefAsNonNull, RefFunc, RefIsNull, RefNull, Select, SimdOp, TableGet, TableSize, Temporary,
+	UnOp, Value,
+};
+use wasmparser::ValType;
+
+use crate::{
+	bound::find_bound,
+	float::{is_exact_decimal, write_hex_float},
+	into_string::IntoName,
+};
+
+use super::manager::{write_separated, Driver, Manager};
+
+// Returns how a choice between two values opens, continues, and goes on to
+// the other value, which is only written as `a and b or c` without `if`
+// expressions when `b` is never `false` or `nil`
+fn get_choice(mng: &Manager) -> [&'static str; 3] {
+	if mng.dialect().has_if_expression() {
+		["(if ", " then ", " else "]
+	} else {
+		["(", " and ", " or "]
+	}
+}
+
+pub fn write_i32(number: i32, mng: &Manager, w: &mut dyn Write) -> Result<()> {
+	if mng.dialect().is_signed() {
+		write!(w, "{number}")
+	} else {
+		write!(w, "{}", number as u32)
+	}
+}
+
+// Floats are written in decimal where that is exact, and otherwise in
+// hexadecimal or built from their bits through the operation the dialect
+// names for them, which NaNs always are so that their sign and payload are kept
+fn write_f32(number: f32, mng: &Manager, w: &mut dyn Write) -> Result<()> {
+	if let Some((head, tail)) = mng.dialect().value_name(Value::F32(number)) {
+		write!(w, "{head}_{tail}(")?;
+		write_i32(number.to_bits() as i32, mng, w)?;
+		return write!(w, ")");
+	}
+
+	write_f64(number.into(), mng, w)
+}
+
+fn write_f64(number: f64, mng: &Manager, w: &mut dyn Write) -> Result<()> {
+	if let Some((head, tail)) = mng.dialect().value_name(Value::F64(number)) {
+		write!(w, "{head}_{tail}(")?;
+		mng.dialect().write_i64(number.to_bits() as i64, w)?;
+		return write!(w, ")");
+	}
+
+	match (number.classify(), number.is_sign_negative()) {
+		(FpCategory::Infinite, true) => write!(w, "-math.huge"),
+		(FpCategory::Infinite, false) => write!(w, "math.huge"),
+		_ if is_exact_decimal(number) => write!(w, "{number:e}"),
+		_ => write_hex_float(number, w),
+	}
+}
+
+fn write_v128(number: u128, mng: &Manager, w: &mut dyn Write) -> Result<()> {
+	mng.dialect().write_runtime_table("simd", w)?;
+
+	if number == 0 {
+		return write!(w, ".ZERO");
+	}
+
+	let list = number.to_le_bytes();
+
+	write!(w, ".from_u32(")?;
+	write_separated(
+		list.chunks_exact(4),
+		|v, w| write!(w, "{}", u32::from_le_bytes(v.try_into().unwrap())),
+		w,
+	)?;
+	write!(w, ")")
+}
+
+// Values of `i32` are kept unsigned by some dialects, but those handed in
+// from outside may be negative, so operands that are not constants are
+// wrapped into range before they are ordered as unsigned
+fn write_unsigned(expr: &Expression, mng: &mut Manager, w: &mut dyn Write) -> Result<()> {
+	if mng.dialect().is_signed() || matches!(expr, Expression::Value(Value::I32(_))) {
+		return expr.write(mng, w);
+	}
+
+	write!(w, "(")?;
+	expr.write(mng, w)?;
+	write!(w, " % 4294967296)")
+}
+
+impl Driver for Select {
+	fn write(&self, mng: &mut Manager, w: &mut dyn Write) -> Result<()> {
+		let dialect = mng.dialect();
+
+		if let Some(bound) = find_bound(self, dialect.is_signed(), dialect.has_native_i64()) {
+			write!(w, "math.{}(", bound.name())?;
+			write_unsigned(bound.lhs(), mng, w)?;
+			write!(w, ", ")?;
+			write_unsigned(bound.rhs(), mng, w)?;
+			return write!(w, ")");
+		}
+
+		// `a and b or c` falls through when `b` is `nil`, so references go through the runtime
+		if !dialect.has_if_expression() && matches!(self.ty(), Some(ValType::Ref(_))) {
+			dialect.write_runtime_name("reference", "select", w)?;
+			write!(w, "(")?;
+			self.condition().write(mng, w)?;
+			write!(w, ", ")?;
+			self.on_true().write(mng, w)?;
+			write!(w, ", ")?;
+			self.on_false().write(mng, w)?;
+			return write!(w, ")");
+		}
+
+		let [open, then, other] = get_choice(mng);
+
+		write!(w, "{open}")?;
+		Condition(self.condition()).write(mng, w)?;
+		write!(w, "{then}")?;
+		self.on_true().write(mng, w)?;
+		write!(w, "{other}")?;
+		self.on_false().write(mng, w)?;
+		write!(w, ")")
+	}
+}
+
+impl Driver for RefNull {
+	fn write(&self, _: &mut Manager, w: &mut dyn Write) -> Result<()> {
+		write!(w, "nil")
+	}
+}
+
+impl Driver for RefFunc {
+	fn write(&self, _: &mut Manager, w: &mut dyn Write) -> Result<()> {
+		write!(w, "FUNC_LIST[{}]", self.function())
+	}
+}
+
+impl Driver for RefIsNull {
+	fn write(&self, mng: &mut Manager, w: &mut dyn Write) -> Result<()> {
+		let [open, then, other] = get_choice(mng);
+
+		write!(w, "{open}")?;
+		self.reference().write(mng, w)?;
+		write!(w, " == nil{then}1{other}0)")
+	}
+}
+
+impl Driver for RefAsNonNull {
+	fn write(&self, mng: &mut Manager, w: &mut dyn Write) -> Result<()> {
+		mng.dialect()
+			.write_runtime_name("reference", "as_non_null", w)?;
+		write!(w, "(")?;
+		self.reference().write(mng, w)?;
+		write!(w, ")")
+	}
+}
+
+impl Driver for Temporary {
+	fn write(&self, mng: &mut Manager, w: &mut dyn Write) -> Result<()> {
+		let var = self.var();
+
+		if let Some(slot) = mng.frame().temporary(var) {
+			write!(w, "frame[{}]", slot + 1)
+		} else {
+			write!(w, "reg_{var}")
+		}
+	}
+}
+
+impl Driver for Local {
+	fn write(&self, mng: &mut Manager, w: &mut dyn Write) -> Result<()> {
+		let var = self.var();
+
+		if let Some(slot) = mng.frame().local(var) {
+			write!(w, "frame[{}]", slot + 1)
+		} else {
+			write!(w, "loc_{var}")
+		}
+	}
+}
+
+impl Driver for GetGlobal {
+	fn write(&self, _mng: &mut Manager, w: &mut dyn Write) -> Result<()> {
+		write!(w, "GLOBAL_LIST[{}].value", self.var())
+	}
+}
+
+impl Driver for LoadAt {
+	fn write(&self, mng: &mut Manager, w: &mut dyn Write) -> Result<()> {
+		let name = self.load_type().into_name();
+		let memory = self.memory();
+
+		mng.dialect().write_access_name("load", name, w)?;
+		write!(w, "(memory_at_{memory}, ")?;
+		self.pointer().write(mng, w)?;
+
+		if self.offset() != 0 {
+			write!(w, " + {}", self.offset())?;
+		}
+
+		write!(w, ")")
+	}
+}
+
+impl Driver for MemorySize {
+	fn write(&self, mng: &mut Manager, w: &mut dyn Write) -> Result<()> {
+		let memory = self.memory();
+
+		if !self.is_64() {
+			return mng.dialect().write_memory_size(memory, w);
+		}
+
+		mng.dialect()
+			.write_runtime_name("allocator", "size_64", w)?;
+		write!(w, "(memory_at_{memory})")
+	}
+}
+
+impl Driver for TableGet {
+	fn write(&self, mng: &mut Manager, w: &mut dyn Write) -> Result<()> {
+		mng.dialect().write_runtime_name("table", "get", w)?;
+		write!(w, "(TABLE_LIST[{}], ", self.table())?;
+		self.index().write(mng, w)?;
+		write!(w, ")")
+	}
+}
+
+impl Driver for TableSize {
+	fn write(&self, _mng: &mut Manager, w: &mut dyn Write) -> Result<()> {
+		write!(w, "TABLE_LIST[{}].min", self.table())
+	}
+}
+
+impl Driver for Value {
+	fn write(&self, mng: &mut Manager, w: &mut dyn Write) -> Result<()> {
+		match self {
+			Self::I32(i) => write_i32(*i, mng, w),
+			Self::I64(i) => mng.dialect().write_i64(*i, w),
+			Self::F32(f) => write_f32(*f, mng, w),
+			Self::F64(f) => write_f64(*f, mng, w),
+			Self::V128(v) => write_v128(*v, mng, w),
+		}
+	}
+}
+
+impl Driver for UnOp {
+	fn write(&self, mng: &mut Manager, w: &mut dyn Write) -> Result<()> {
+		let (a, b) = mng.dialect().un_op_name(self.op_type());
+
+		write!(w, "{a}_{b}(")?;
+		self.rhs().write(mng, w)?;
+		write!(w, ")")
+	}
+}
+
+// Doubles hold every `i32` sum exactly, so adding, subtracting, and multiplying
+// by a small constant are written natively and wrapped by `bit.tobit`, which is
+// exact up to 51 bits. Operands that flow straight into another of them, or
+// into a `bit` function that wraps its arguments itself, are left unwrapped as
+// long as the bound on their magnitude stays within that.
+const MAX_BITS: u32 = 51;
+const MAX_FACTOR_BITS: u32 = MAX_BITS - 31;
+
+// Returns the least number of bits `n` where the magnitude is at most `2^n`
+const fn get_magnitude_bits(value: i32) -> u32 {
+	match value.unsigned_abs() {
+		0 | 1 => 0,
+		magnitude => u32::BITS - (magnitude - 1).leading_zeros(),
+	}
+}
+
+fn get_factor(bin_op: &BinOp) -> Option<(&Expression, u32)> {
+	let (lhs, rhs) = (bin_op.lhs(), bin_op.rhs());
+	let (other, value) = match (lhs, rhs) {
+		(_, Expression::Value(Value::I32(value))) => (lhs, *value),
+		(Expression::Value(Value::I32(value)), _) => (rhs, *value),
+		_ => return None,
+	};
+
+	let bits = get_magnitude_bits(value);
+
+	(bits <= MAX_FACTOR_BITS).then_some((other, bits))
+}
+
+fn get_native_bits(bin_op: &BinOp) -> Option<u32> {
+	match bin_op.op_type() {
+		BinOpType::Add_I32 | BinOpType::Sub_I32 => {
+			let lhs = get_operand_bits(bin_op.lhs(), MAX_BITS - 1);
+			let rhs = get_operand_bits(bin_op.rhs(), MAX_BITS - 1);
+
+			Some(lhs.max(rhs) + 1)
+		}
+		BinOpType::Mul_I32 => {
+			let (other, bits) = get_factor(bin_op)?;
+
+			Some(get_operand_bits(other, MAX_BITS - bits) + bits)
+		}
+		_ => None,
+	}
+}
+
+fn get_unwrapped(expr: &Expression, limit: u32) -> Option<(&BinOp, u32)> {
+	let Expression::BinOp(bin_op) = expr else {
+		return None;
+	};
+
+	get_native_bits(bin_op)
+		.filter(|&bits| bits <= limit)
+		.map(|bits| (bin_op, bits))
+}
+
+fn get_operand_bits(expr: &Expression, limit: u32) -> u32 {
+	match expr {
+		Expression::Value(Value::I32(value)) => get_magnitude_bits(*value),
+		_ => get_unwrapped(expr, limit).map_or(31, |v| v.1),
+	}
+}
+
+fn write_operand(
+	expr: &Expression,
+	limit: u32,
+	mng: &mut Manager,
+	w: &mut dyn Write,
+) -> Result<()> {
+	match get_unwrapped(expr, limit) {
+		Some((bin_op, _)) => write_native(bin_op, mng, w),
+		None => expr.write(mng, w),
+	}
+}
+
+fn write_native(bin_op: &BinOp, mng: &mut Manager, w: &mut dyn Write) -> Result<()> {
+	let (symbol, limit) = match bin_op.op_type() {
+		BinOpType::Add_I32 => ("+", MAX_BITS - 1),
+		BinOpType::Sub_I32 => ("-", MAX_BITS - 1),
+		_ => ("*", MAX_BITS - get_factor(bin_op).unwrap().1),
+	};
+
+	write!(w, "(")?;
+	write_operand(bin_op.lhs(), limit, mng, w)?;
+	write!(w, " {symbol} ")?;
+	write_operand(bin_op.rhs(), limit, mng, w)?;
+	write!(w, ")")
+}
+
+const fn is_bit_function(op_type: BinOpType) -> bool {
+	matches!(
+		op_type,
+		BinOpType::And_I32
+			| BinOpType::Or_I32
+			| BinOpType::Xor_I32
+			| BinOpType::Shl_I32
+			| BinOpType::ShrS_I32
+			| BinOpType::ShrU_I32
+			| BinOpType::Rotl_I32
+			| BinOpType::Rotr_I32
+	)
+}
+
+// Integers that wrap on overflow keep the low 32 bits of sums, differences,
+// products, and shifts exact however far they carry, so operands that are one
+// of them are left unwrapped. Only the result is wrapped, by clearing the bits
+// above 32 and extending the sign bit over them.
+const fn is_integer(op_type: BinOpType) -> bool {
+	matches!(
+		op_type,
+		BinOpType::Add_I32
+			| BinOpType::Sub_I32
+			| BinOpType::Mul_I32
+			| BinOpType::Shl_I32
+			| BinOpType::ShrU_I32
+	)
+}
+
+fn write_integer_operand(expr: &Expression, mng: &mut Manager, w: &mut dyn Write) -> Result<()> {
+	match expr {
+		Expression::BinOp(bin_op) if is_integer(bin_op.op_type()) => {
+			write!(w, "(")?;
+			write_integer(bin_op, mng, w)?;
+			write!(w, ")")
+		}
+		_ => expr.write(mng, w),
+	}
+}
+
+fn write_shift_count(expr: &Expression, mng: &mut Manager, w: &mut dyn Write) -> Result<()> {
+	if let Expression::Value(Value::I32(count)) = expr {
+		return write!(w, "{}", count & 31);
+	}
+
+	write!(w, "(")?;
+	write_integer_operand(expr, mng, w)?;
+	write!(w, " & 31)")
+}
+
+fn write_integer(bin_op: &BinOp, mng: &mut Manager, w: &mut dyn Write) -> Result<()> {
+	let symbol = mng.dialect().bin_op_symbol(bin_op.op_type()).unwrap();
+
+	if bin_op.op_type() == BinOpType::ShrU_I32 {
+		write!(w, "(")?;
+		write_integer_operand(bin_op.lhs(), mng, w)?;
+		write!(w, " & 0xFFFFFFFF)")?;
+	} else {
+		write_integer_operand(bin_op.lhs(), mng, w)?;
+	}
+
+	write!(w, " {symbol} ")?;
+
+	if matches!(bin_op.op_type(), BinOpType::Shl_I32 | BinOpType::ShrU_I32) {
+		write_shift_count(bin_op.rhs(), mng, w)
+	} else {
+		write_integer_operand(bin_op.rhs(), mng, w)
+	}
+}
+
+impl Driver for BinOp {
+	fn write(&self, mng: &mut Manager, w: &mut dyn Write) -> Result<()> {
+		let dialect = mng.dialect();
+		let is_native = dialect.has_native_i32();
+
+		if dialect.has_wrapping_integers() && is_integer(self.op_type()) {
+			write!(w, "((")?;
+			write_integer(self, mng, w)?;
+
+			return write!(w, " & 0xFFFFFFFF ~ 0x80000000) - 0x80000000)");
+		}
+
+		// Arithmetic shifts of values kept signed are floor divisions by a power of two
+		if dialect.has_wrapping_integers() && self.op_type() == BinOpType::ShrS_I32 {
+			let symbol = dialect.bin_op_symbol(self.op_type()).unwrap();
+
+			write!(w, "(")?;
+			self.lhs().write(mng, w)?;
+			write!(w, " {symbol} (1 << ")?;
+			write_shift_count(self.rhs(), mng, w)?;
+			return write!(w, "))");
+		}
+
+		if is_native && get_native_bits(self).is_some() {
+			write!(w, "to_signed")?;
+
+			return write_native(self, mng, w);
+		}
+
+		if let Some(symbol) = dialect.bin_op_symbol(self.op_type()) {
+			write!(w, "(")?;
+			self.lhs().write(mng, w)?;
+			write!(w, " {symbol} ")?;
+
+			// Native shifts by 64 or more give zero instead of wrapping the count
+			match (self.op_type(), self.rhs()) {
+				(
+					BinOpType::Shl_I64 | BinOpType::ShrU_I64,
+					Expression::Value(Value::I64(count)),
+				) => {
+					write!(w, "{}", count & 63)?;
+				}
+				(BinOpType::Shl_I64 | BinOpType::ShrU_I64, rhs) => {
+					write!(w, "(")?;
+					rhs.write(mng, w)?;
+					write!(w, " & 63)")?;
+				}
+				(_, rhs) => rhs.write(mng, w)?,
+			}
+
+			return write!(w, ")");
+		}
+
+		let (head, tail) = dialect.bin_op_name(self.op_type()).unwrap();
+
+		write!(w, "{head}_{tail}(")?;
+
+		if is_native && is_bit_function(self.op_type()) {
+			write_operand(self.lhs(), MAX_BITS, mng, w)?;
+			write!(w, ", ")?;
+			write_operand(self.rhs(), MAX_BITS, mng, w)?;
+		} else {
+			self.lhs().write(mng, w)?;
+			write!(w, ", ")?;
+			self.rhs().write(mng, w)?;
+		}
+
+		write!(w, ")")
+	}
+}
+
+const fn is_unsigned(op_type: CmpOpType) -> bool {
+	matches!(
+		op_type,
+		CmpOpType::LtU_I32 | CmpOpType::GtU_I32 | CmpOpType::LeU_I32 | CmpOpType::GeU_I32
+	)
+}
+
+// Returns whether an unsigned comparison swaps its operands and negates the
+// result when written through a less than, or `None` if it is not unsigned
+const fn get_less_order(op_type: CmpOpType) -> Option<(bool, bool)> {
+	let order = match op_type {
+		CmpOpType::LtU_I32 | CmpOpType::LtU_I64 => (false, false),
+		CmpOpType::GtU_I32 | CmpOpType::GtU_I64 => (true, false),
+		CmpOpType::LeU_I32 | CmpOpType::LeU_I64 => (true, true),
+		CmpOpType::GeU_I32 | CmpOpType::GeU_I64 => (false, true),
+		_ => return None,
+	};
+
+	Some(order)
+}
+
+struct CmpOpBoolean<'a>(&'a CmpOp);
+
+impl Driver for CmpOpBoolean<'_> {
+	fn write(&self, mng: &mut Manager, w: &mut dyn Write) -> Result<()> {
+		let cmp = self.0;
+		let dialect = mng.dialect();
+
+		if let Some((is_swapped, is_negated)) = dialect
+			.has_unsigned_less()
+			.then(|| get_less_order(cmp.op_type()))
+			.flatten()
+		{
+			let (head, tail) = dialect.cmp_op_name(cmp.op_type()).unwrap();
+			let (lhs, rhs) = if is_swapped {
+				(cmp.rhs(), cmp.lhs())
+			} else {
+				(cmp.lhs(), cmp.rhs())
+			};
+
+			if is_negated {
+				write!(w, "not ")?;
+			}
+
+			write!(w, "{head}_{tail}(")?;
+			lhs.write(mng, w)?;
+			write!(w, ", ")?;
+			rhs.write(mng, w)?;
+			return write!(w, ")");
+		}
+
+		if let Some(symbol) = dialect.cmp_op_symbol(cmp.op_type()) {
+			if is_unsigned(cmp.op_type()) {
+				write_unsigned(cmp.lhs(), mng, w)?;
+				write!(w, " {symbol} ")?;
+				write_unsigned(cmp.rhs(), mng, w)
+			} else {
+				cmp.lhs().write(mng, w)?;
+				write!(w, " {symbol} ")?;
+				cmp.rhs().write(mng, w)
+			}
+		} else {
+			let (head, tail) = dialect.cmp_op_name(cmp.op_type()).unwrap();
+
+			write!(w, "{head}_{tail}(")?;
+			cmp.lhs().write(mng, w)?;
+			write!(w, ", ")?;
+			cmp.rhs().write(mng, w)?;
+			write!(w, ")")
+		}
+	}
+}
+
+impl Driver for CmpOp {
+	fn write(&self, mng: &mut Manager, w: &mut dyn Write) -> Result<()> {
+		let [open, then, other] = get_choice(mng);
+
+		write!(w, "{open}")?;
+		CmpOpBoolean(self).write(mng, w)?;
+		write!(w, "{then}1{other}0)")
+	}
+}
+
+// Expressions that are only ever `0` or `1` are written as conditions
+// without going through an integer
+fn is_boolean(expr: &Expression) -> bool {
+	match expr {
+		Expression::Value(Value::I32(0 | 1)) | Expression::CmpOp(_) => true,
+		Expression::BinOp(v) => {
+			matches!(v.op_type(), BinOpType::And_I32 | BinOpType::Or_I32)
+				&& is_boolean(v.lhs())
+				&& is_boolean(v.rhs())
+		}
+		Expression::Select(v) => is_boolean(v.on_true()) && is_boolean(v.on_false()),
+		_ => false,
+	}
+}
+
+// `eqz` and `~= 0` of a condition are its negation and itself
+fn get_tested(cmp: &CmpOp) -> Option<(&Expression, bool)> {
+	let is_negated = match cmp.op_type() {
+		CmpOpType::Eq_I32 => true,
+		CmpOpType::Ne_I32 => false,
+		_ => return None,
+	};
+
+	let is_zero = matches!(cmp.rhs(), Expression::Value(Value::I32(0)));
+
+	(is_zero && is_boolean(cmp.lhs())).then_some((cmp.lhs(), is_negated))
+}
+
+pub struct Condition<'a>(pub &'a Expression);
+
+impl Driver for Condition<'_> {
+	fn write(&self, mng: &mut Manager, w: &mut dyn Write) -> Result<()> {
+		match self.0 {
+			Expression::Value(Value::I32(value)) => write!(w, "{}", *value != 0),
+			Expression::CmpOp(node) => match get_tested(node) {
+				Some((lhs, true)) => {
+					write!(w, "not (")?;
+					Condition(lhs).write(mng, w)?;
+					write!(w, ")")
+				}
+				Some((lhs, false)) => Condition(lhs).write(mng, w),
+				None => CmpOpBoolean(node).write(mng, w),
+			},
+			// `and` and `or` skip their right side, so it must not trap
+			Expression::BinOp(node) if is_boolean(self.0) && node.rhs().is_pure() => {
+				let symbol = if matches!(node.op_type(), BinOpType::And_I32) {
+					"and"
+				} else {
+					"or"
+				};
+
+				write!(w, "(")?;
+				Condition(node.lhs()).write(mng, w)?;
+				write!(w, " {symbol} ")?;
+				Condition(node.rhs()).write(mng, w)?;
+				write!(w, ")")
+			}
+			Expression::Select(node) if is_boolean(self.0) && mng.dialect().has_if_expression() => {
+				write!(w, "(if ")?;
+				Condition(node.condition()).write(mng, w)?;
+				write!(w, " then ")?;
+				Condition(node.on_true()).write(mng, w)?;
+				write!(w, " else ")?;
+				Condition(node.on_false()).write(mng, w)?;
+				write!(w, ")")
+			}
+			// The condition is tested twice, so it must not have an effect
+			Expression::Select(node) if is_boolean(self.0) && node.condition().is_pure() => {
+				write!(w, "(")?;
+				Condition(node.condition()).write(mng, w)?;
+				write!(w, " and ")?;
+				Condition(node.on_true()).write(mng, w)?;
+				write!(w, " or not (")?;
+				Condition(node.condition()).write(mng, w)?;
+				write!(w, ") and ")?;
+				Condition(node.on_false()).write(mng, w)?;
+				write!(w, ")")
+			}
+			_ => {
+				self.0.write(mng, w)?;
+				write!(w, " ~= 0")
+			}
+		}
+	}
+}
+
+impl Driver for SimdOp {
+	fn write(&self, mng: &mut Manager, w: &mut dyn Write) -> Result<()> {
+		mng.dialect().write_runtime_table("simd", w)?;
+		write!(w, ".{}(", self.op_type().name())?;
+		self.param_list().write(mng, w)?;
+		write!(w, ")")
+	}
+}
+
+impl Driver for Expression {
+	fn write(&self, mng: &mut Manager, w: &mut dyn Write) -> Result<()> {
+		match self {
+			Self::Select(e) => e.write(mng, w),
+			Self::GetTemporary(e) => e.write(mng, w),
+			Self::GetLocal(e) => e.write(mng, w),
+			Self::GetGlobal(e) => e.write(mng, w),
+			Self::LoadAt(e) => e.write(mng, w),
+			Self::MemorySize(e) => e.write(mng, w),
+			Self::TableGet(e) => e.write(mng, w),
+			Self::TableSize(e) => e.write(mng, w),
+			Self::Value(e) => e.write(mng, w),
+			Self::RefNull(e) => e.write(mng, w),
+			Self::RefFunc(e) => e.write(mng, w),
+			Self::RefIsNull(e) => e.write(mng, w),
+			Self::RefAsNonNull(e) => e.write(mng, w),
+			Self::UnOp(e) => e.write(mng, w),
+			Self::BinOp(e) => e.write(mng, w),
+			Self::CmpOp(e) => e.write(mng, w),
+			Self::SimdOp(e) => e.write(mng, w),
+		}
+	}
+}
+
+impl Driver for &[Expression] {
+	fn write(&self, mng: &mut Manager, w: &mut dyn Write) -> Result<()> {
+		write_separated(self.iter(), |e, w| e.write(mng, w), w)
+	}
+}
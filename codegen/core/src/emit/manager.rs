@@ -0,0 +1,220 @@
+use std::{
+	collections::HashMap,
+	io::{Result, Write},
+};
+
+use wasm_ast::node::{BrTable, FuncData, LabelType};
+
+use crate::{
+	dialect::{BranchStyle, Dialect},
+	frame::Frame,
+	localize::localize,
+};
+
+use super::br_target;
+
+pub struct Manager<'a> {
+	dialect: &'a dyn Dialect,
+	table_map: HashMap<usize, usize>,
+	has_branch: bool,
+	frame: Frame,
+	num_label: usize,
+	label_list: Vec<usize>,
+	label_type_list: Vec<Option<LabelType>>,
+	closure_list: Vec<(usize, Vec<usize>)>,
+	indentation: usize,
+	is_marked: bool,
+	is_metered: bool,
+	is_yielding: bool,
+	next_block: Option<usize>,
+	max_depth: Option<u32>,
+}
+
+impl<'a> Manager<'a> {
+	pub fn empty(dialect: &'a dyn Dialect) -> Self {
+		Self {
+			dialect,
+			table_map: HashMap::new(),
+			has_branch: false,
+			frame: Frame::default(),
+			num_label: 0,
+			label_list: Vec::new(),
+			label_type_list: Vec::new(),
+			closure_list: Vec::new(),
+			indentation: 0,
+			is_marked: false,
+			is_metered: false,
+			is_yielding: false,
+			next_block: None,
+			max_depth: None,
+		}
+	}
+
+	pub fn function<D: Dialect>(ast: &FuncData, dialect: &'a D) -> Self {
+		let (upvalues, memories) = localize(dialect, ast);
+		let (table_map, has_branch) = br_target::visit(ast, dialect.branch_style());
+		let frame = Frame::new(ast, upvalues.len() + memories.len());
+
+		Self {
+			table_map,
+			has_branch,
+			frame,
+			..Self::empty(dialect)
+		}
+	}
+
+	pub fn dialect(&self) -> &'a dyn Dialect {
+		self.dialect
+	}
+
+	pub fn branch_style(&self) -> BranchStyle {
+		self.dialect.branch_style()
+	}
+
+	pub fn mark_statements(&mut self) {
+		self.is_marked = true;
+	}
+
+	pub const fn is_marked(&self) -> bool {
+		self.is_marked
+	}
+
+	pub fn meter_fuel(&mut self) {
+		self.is_metered = true;
+	}
+
+	pub const fn is_metered(&self) -> bool {
+		self.is_metered
+	}
+
+	pub fn yield_loops(&mut self) {
+		self.is_yielding = true;
+	}
+
+	pub const fn is_yielding(&self) -> bool {
+		self.is_yielding
+	}
+
+	pub fn cover_blocks(&mut self) {
+		self.next_block = Some(0);
+	}
+
+	// Blocks are numbered in the order they are written, which is the order
+	// that the block visitor of the core counts them in too
+	pub fn next_block(&mut self) -> Option<usize> {
+		let id = self.next_block?;
+
+		self.next_block = Some(id + 1);
+
+		Some(id)
+	}
+
+	pub fn limit_depth(&mut self, limit: u32) {
+		self.max_depth = Some(limit);
+	}
+
+	pub const fn max_depth(&self) -> Option<u32> {
+		self.max_depth
+	}
+
+	pub fn get_table_index(&self, table: &BrTable) -> usize {
+		let id = std::ptr::from_ref(table) as usize;
+
+		self.table_map[&id]
+	}
+
+	pub fn has_table(&self) -> bool {
+		!self.table_map.is_empty()
+	}
+
+	pub const fn has_branch(&self) -> bool {
+		self.has_branch
+	}
+
+	pub const fn frame(&self) -> &Frame {
+		&self.frame
+	}
+
+	pub fn label_list(&self) -> &[usize] {
+		&self.label_list
+	}
+
+	pub fn label_type_list(&self) -> &[Option<LabelType>] {
+		&self.label_type_list
+	}
+
+	pub fn push_label(&mut self, label_type: Option<LabelType>) -> usize {
+		self.label_list.push(self.num_label);
+		self.label_type_list.push(label_type);
+		self.num_label += 1;
+
+		self.num_label - 1
+	}
+
+	pub fn pop_label(&mut self) {
+		self.label_list.pop().unwrap();
+		self.label_type_list.pop().unwrap();
+	}
+
+	pub fn push_closure(&mut self) {
+		self.closure_list.push((self.label_list.len(), Vec::new()));
+	}
+
+	pub fn pop_closure(&mut self) -> Vec<usize> {
+		self.closure_list.pop().unwrap().1
+	}
+
+	pub fn is_closure_start(&self) -> bool {
+		self.closure_list.last().map(|v| v.0) == Some(self.label_list.len())
+	}
+
+	// Labels outside of the current closure cannot be jumped to directly,
+	// so the closure returns them to be dispatched by its caller
+	pub fn try_escape_label(&mut self, index: usize) -> bool {
+		let Some((start, escape_list)) = self.closure_list.last_mut() else {
+			return false;
+		};
+
+		if index >= *start {
+			return false;
+		}
+
+		if !escape_list.contains(&index) {
+			escape_list.push(index);
+		}
+
+		true
+	}
+
+	pub const fn indentation(&self) -> usize {
+		self.indentation
+	}
+
+	pub fn indent(&mut self) {
+		self.indentation += 1;
+	}
+
+	pub fn dedent(&mut self) {
+		self.indentation -= 1;
+	}
+}
+
+pub trait Driver {
+	fn write(&self, mng: &mut Manager, w: &mut dyn Write) -> Result<()>;
+}
+
+pub fn write_separated<I, T, M>(mut iter: I, mut func: M, w: &mut dyn Write) -> Result<()>
+where
+	M: FnMut(T, &mut dyn Write) -> Result<()>,
+	I: Iterator<Item = T>,
+{
+	match iter.next() {
+		Some(first) => func(first, w)?,
+		None => return Ok(()),
+	}
+
+	iter.try_for_each(|v| {
+		write!(w, ", ")?;
+		func(v, w)
+	})
+}
@@ -0,0 +1,104 @@
+use std::io::{Result, Write};
+
+use wasm_ast::node::{Expression, FuncData};
+
+use crate::{dialect::Dialect, options::Options};
+
+use self::manager::{Driver, Manager};
+
+macro_rules! indentation {
+	($mng:tt, $w:tt) => {{
+		let mut iter = 0..$mng.indentation();
+
+		iter.try_for_each(|_| $w.write_all(b"\t"))
+	}};
+}
+
+macro_rules! indented {
+	($mng:tt, $w:tt, $($args:tt)*) => {{
+		indentation!($mng, $w)?;
+		write!($w, $($args)*)
+	}};
+}
+
+macro_rules! line {
+	($mng:tt, $w:tt, $($args:tt)*) => {{
+		indentation!($mng, $w)?;
+		writeln!($w, $($args)*)
+	}};
+}
+
+mod br_target;
+mod expression;
+mod manager;
+mod statement;
+
+fn write_function_with(
+	mut mng: Manager,
+	func: &FuncData,
+	options: &Options,
+	w: &mut dyn Write,
+) -> Result<()> {
+	if options.fuel.is_some() {
+		mng.meter_fuel();
+	}
+
+	if options.yield_every.is_some() {
+		mng.yield_loops();
+	}
+
+	if options.coverage {
+		mng.cover_blocks();
+	}
+
+	if let Some(limit) = options.max_depth {
+		mng.limit_depth(limit);
+	}
+
+	func.write(&mut mng, w)
+}
+
+/// Writes a function in a dialect, as [`Backend::write_function`] does.
+///
+/// # Errors
+/// Returns `Err` if writing to `Write` failed.
+///
+/// [`Backend::write_function`]: crate::Backend::write_function
+pub fn write_function<D: Dialect>(
+	dialect: &D,
+	func: &FuncData,
+	options: &Options,
+	w: &mut dyn Write,
+) -> Result<()> {
+	write_function_with(Manager::function(func, dialect), func, options, w)
+}
+
+/// Writes a function in a dialect, as [`Backend::write_function_marked`]
+/// does.
+///
+/// # Errors
+/// Returns `Err` if writing to `Write` failed.
+///
+/// [`Backend::write_function_marked`]: crate::Backend::write_function_marked
+pub fn write_function_marked<D: Dialect>(
+	dialect: &D,
+	func: &FuncData,
+	options: &Options,
+	w: &mut dyn Write,
+) -> Result<()> {
+	let mut mng = Manager::function(func, dialect);
+
+	mng.mark_statements();
+	write_function_with(mng, func, options, w)
+}
+
+/// Writes a standalone expression in a dialect, as
+/// [`Backend::write_expression`] does.
+///
+/// # Errors
+/// Returns `Err` if writing to `Write` failed.
+///
+/// [`Backend::write_expression`]: crate::Backend::write_expression
+pub fn write_expression(dialect: &dyn Dialect, expr: &Expression, w: &mut dyn Write) -> Result<()> {
+	expr.write(&mut Manager::empty(dialect), w)
+}
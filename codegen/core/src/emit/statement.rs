@@ -0,0 +1,1029 @@
+use std::{
+	io::{Result, Write},
+	ops::Range,
+};
+
+use wasm_ast::node::{
+	AtomicNotify, AtomicWait, Block, Br, BrIf, BrTable, Call, CallIndirect, CallRef, Catch,
+	DataDrop, ElemDrop, FuncData, If, LabelType, MemoryCopy, MemoryFill, MemoryGrow, MemoryInit,
+	ResultList, Rethrow, SetGlobal, SetLocal, SetTemporary, Statement, StoreAt, TableCopy,
+	TableFill, TableGrow, TableInit, TableSet, Terminator, Throw, Try, Value,
+};
+use wasmparser::ValType;
+
+use crate::{
+	counted::{find_counted, Counted},
+	dialect::BranchStyle,
+	into_string::IntoName,
+	source_map::write_mark,
+};
+
+use super::{
+	expression::Condition,
+	manager::{write_separated, Driver, Manager},
+};
+
+impl Driver for ResultList {
+	fn write(&self, mng: &mut Manager, w: &mut dyn Write) -> Result<()> {
+		write_separated(self.iter(), |t, w| t.write(mng, w), w)
+	}
+}
+
+fn write_goto(index: usize, mng: &mut Manager, w: &mut dyn Write) -> Result<()> {
+	let level = mng.label_list()[index];
+
+	if mng.try_escape_label(index) {
+		line!(mng, w, "do return {level} end")
+	} else {
+		line!(mng, w, "goto continue_at_{level}")
+	}
+}
+
+// Without `goto` every block is a loop, so branches `break` out of them with
+// `desired` set to the level of the target unless it is the innermost block,
+// which loops go around again through `continue` where there is one
+fn write_break(target: usize, mng: &mut Manager, w: &mut dyn Write) -> Result<()> {
+	let is_loop = mng.label_type_list().last() == Some(&Some(LabelType::Backward));
+
+	if target == 0 && !is_loop {
+		line!(mng, w, "break")
+	} else if target == 0 && mng.branch_style() == BranchStyle::Continue {
+		line!(mng, w, "continue")
+	} else {
+		let level = mng.label_list().len() - 1 - target;
+
+		line!(mng, w, "desired = {level}")?;
+		line!(mng, w, "break")
+	}
+}
+
+impl Driver for Br {
+	fn write(&self, mng: &mut Manager, w: &mut dyn Write) -> Result<()> {
+		let index = mng.label_list().len() - 1 - self.target();
+
+		if !self.align().is_aligned() {
+			indentation!(mng, w)?;
+			self.align().new_range().write(mng, w)?;
+			write!(w, " = ")?;
+			self.align().old_range().write(mng, w)?;
+			writeln!(w)?;
+		}
+
+		if mng.branch_style() == BranchStyle::Goto {
+			write_goto(index, mng, w)
+		} else {
+			write_break(self.target(), mng, w)
+		}
+	}
+}
+
+fn to_ordered_table(list: &[Br], default: Br) -> Vec<Br> {
+	let mut data: Vec<_> = list
+		.iter()
+		.copied()
+		.chain(std::iter::once(default))
+		.collect();
+
+	data.sort_by_key(|v| v.target());
+	data.dedup_by_key(|v| v.target());
+	data
+}
+
+fn write_search_layer(
+	range: Range<usize>,
+	list: &[Br],
+	mng: &mut Manager,
+	w: &mut dyn Write,
+) -> Result<()> {
+	if range.len() == 1 {
+		return list[range.start].write(mng, w);
+	}
+
+	let center = range.start + range.len() / 2;
+	let br = list[center];
+
+	if range.start != center {
+		line!(mng, w, "if temp < {} then", br.target())?;
+		mng.indent();
+		write_search_layer(range.start..center, list, mng, w)?;
+		mng.dedent();
+		indented!(mng, w, "else")?;
+	}
+
+	if range.end != center + 1 {
+		writeln!(w, "if temp > {} then", br.target())?;
+		mng.indent();
+		write_search_layer(center + 1..range.end, list, mng, w)?;
+		mng.dedent();
+		indented!(mng, w, "else")?;
+	}
+
+	writeln!(w)?;
+	mng.indent();
+	br.write(mng, w)?;
+	mng.dedent();
+	line!(mng, w, "end")
+}
+
+fn write_table_lookup(table: &BrTable, mng: &mut Manager, w: &mut dyn Write) -> Result<()> {
+	let id = mng.get_table_index(table);
+
+	indented!(mng, w, "temp = br_map[{id}][")?;
+	table.condition().write(mng, w)?;
+	writeln!(w, "] or {}", table.default().target())
+}
+
+impl Driver for BrTable {
+	fn write(&self, mng: &mut Manager, w: &mut dyn Write) -> Result<()> {
+		if self.data().is_empty() {
+			// Our condition should be pure so we probably don't need
+			// to emit it in this case.
+			return self.default().write(mng, w);
+		}
+
+		// `BrTable` is optimized by first mapping all indices to targets through
+		// a Lua table built once beside the function; this reduces the size of the
+		// code generated as duplicate entries don't need checking. Then, for speed,
+		// a binary search is done for the target and the appropriate jump is performed.
+		let list = to_ordered_table(self.data(), self.default());
+
+		write_table_lookup(self, mng, w)?;
+		write_search_layer(0..list.len(), &list, mng, w)
+	}
+}
+
+impl Driver for Throw {
+	fn write(&self, mng: &mut Manager, w: &mut dyn Write) -> Result<()> {
+		indentation!(mng, w)?;
+		mng.dialect().write_runtime_table("exception", w)?;
+		write!(w, ".throw(TAG_LIST[{}]", self.tag())?;
+
+		if !self.param_list().is_empty() {
+			write!(w, ", ")?;
+			self.param_list().write(mng, w)?;
+		}
+
+		writeln!(w, ")")
+	}
+}
+
+impl Driver for Rethrow {
+	fn write(&self, mng: &mut Manager, w: &mut dyn Write) -> Result<()> {
+		let level = mng.label_list().len() - 1 - self.target();
+
+		line!(mng, w, "error(exception_{level}, 0)")
+	}
+}
+
+impl Driver for Terminator {
+	fn write(&self, mng: &mut Manager, w: &mut dyn Write) -> Result<()> {
+		match self {
+			Self::Unreachable => {
+				indentation!(mng, w)?;
+				mng.dialect().write_trap_table(w)?;
+				writeln!(w, r#".raise("unreachable", "out of code bounds")"#)
+			}
+			Self::Br(s) => s.write(mng, w),
+			Self::BrTable(s) => s.write(mng, w),
+			Self::Throw(s) => s.write(mng, w),
+			Self::Rethrow(s) => s.write(mng, w),
+		}
+	}
+}
+
+// Metered functions count down on entry and on every loop iteration
+fn write_fuel_use(mng: &Manager, w: &mut dyn Write) -> Result<()> {
+	line!(mng, w, "FUEL.left = FUEL.left - 1")?;
+	line!(mng, w, "if FUEL.left < 0 then use_fuel() end")
+}
+
+// Yielding loops count down on every iteration and hand control back to the
+// host once the count runs out
+fn write_yield_use(mng: &Manager, w: &mut dyn Write) -> Result<()> {
+	line!(mng, w, "YIELD.left = YIELD.left - 1")?;
+	line!(mng, w, "if YIELD.left <= 0 then use_yield() end")
+}
+
+// Depth limited functions count themselves into `DEPTH` on entry and back
+// out before they return, so runaway recursion traps instead
+fn write_depth_enter(limit: u32, mng: &Manager, w: &mut dyn Write) -> Result<()> {
+	line!(mng, w, "local depth = DEPTH.now + 1")?;
+	line!(mng, w, "if depth > {limit} then exhaust_stack() end")?;
+	line!(mng, w, "DEPTH.now = depth")
+}
+
+fn write_loop_start(mng: &Manager, w: &mut dyn Write) -> Result<()> {
+	if mng.is_metered() {
+		write_fuel_use(mng, w)?;
+	}
+
+	if mng.is_yielding() {
+		write_yield_use(mng, w)?;
+	}
+
+	if let Some(name) = mng.dialect().loop_call() {
+		line!(mng, w, "{name}()")?;
+	}
+
+	Ok(())
+}
+
+// Counted loops run their body in a numeric `for` that starts from the local
+// as it is, so the step and branch after it only go around again when the
+// loop is continued or the local wraps around
+fn write_counted_start(counted: &Counted, mng: &mut Manager, w: &mut dyn Write) -> Result<()> {
+	let var = counted.var();
+	let name = if counted.step() > 0 { "max" } else { "min" };
+	let offset = match counted.offset() {
+		-1 => " - 1",
+		1 => " + 1",
+		_ => "",
+	};
+
+	indented!(mng, w, "for counter = ")?;
+	var.write(mng, w)?;
+	write!(w, ", math.{name}(")?;
+	var.write(mng, w)?;
+	write!(w, ", ")?;
+	counted.bound().write(mng, w)?;
+	write!(w, "{offset})")?;
+
+	if counted.step() != 1 {
+		write!(w, ", {}", counted.step())?;
+	}
+
+	writeln!(w, " do")?;
+	mng.indent();
+	indentation!(mng, w)?;
+	var.write(mng, w)?;
+	writeln!(w, " = counter")
+}
+
+// Each statement is preceded by a mark of its instruction when the function
+// is being written for a source map
+fn write_statement_list(
+	block: &Block,
+	range: Range<usize>,
+	mng: &mut Manager,
+	w: &mut dyn Write,
+) -> Result<()> {
+	let code = &block.code()[range.clone()];
+	let offset_list = &block.offset_list()[range];
+
+	for (stat, &offset) in code.iter().zip(offset_list) {
+		if mng.is_marked() {
+			write_mark(offset, w)?;
+		}
+
+		stat.write(mng, w)?;
+	}
+
+	Ok(())
+}
+
+// Covered blocks count every entry, which a counted loop makes on every run of
+// its `for`
+fn write_code(block: &Block, mng: &mut Manager, w: &mut dyn Write) -> Result<()> {
+	// Metered and yielding loops have to count every iteration themselves
+	let is_plain = mng.dialect().loop_call().is_none();
+	let counted = if !mng.is_metered() && !mng.is_yielding() && is_plain {
+		let is_signed = mng.dialect().is_signed();
+		let has_goto = mng.branch_style() == BranchStyle::Goto;
+
+		find_counted(block, is_signed, has_goto)
+	} else {
+		None
+	};
+
+	let len = counted
+		.as_ref()
+		.map_or(block.code().len(), Counted::num_statement);
+
+	if let Some(counted) = &counted {
+		write_counted_start(counted, mng, w)?;
+	}
+
+	if let Some(id) = mng.next_block() {
+		line!(mng, w, "coverage[{id}] = coverage[{id}] + 1")?;
+	}
+
+	write_statement_list(block, 0..len, mng, w)?;
+
+	if counted.is_some() {
+		mng.dedent();
+		line!(mng, w, "end")?;
+	}
+
+	write_statement_list(block, len..block.code().len(), mng, w)
+}
+
+fn write_inner_block(block: &Block, mng: &mut Manager, w: &mut dyn Write) -> Result<()> {
+	write_code(block, mng, w)?;
+
+	if let Some(v) = block.last() {
+		v.write(mng, w)?;
+	}
+
+	Ok(())
+}
+
+fn write_br_parent(mng: &mut Manager, w: &mut dyn Write) -> Result<()> {
+	if !mng.has_branch() || mng.label_type_list().iter().all(Option::is_none) {
+		return Ok(());
+	}
+
+	line!(mng, w, "if desired then")?;
+	mng.indent();
+
+	// Loops without `continue` resolve their own level after leaving the
+	// inner `repeat`
+	match *mng.label_type_list().last().unwrap() {
+		Some(LabelType::Backward) if mng.branch_style() == BranchStyle::Continue => {
+			let level = mng.label_list().len() - 1;
+
+			line!(mng, w, "if desired == {level} then")?;
+			mng.indent();
+			line!(mng, w, "desired = nil")?;
+			line!(mng, w, "continue")?;
+			mng.dedent();
+			line!(mng, w, "end")?;
+		}
+		Some(LabelType::Forward) => {
+			let level = mng.label_list().len() - 1;
+
+			line!(mng, w, "if desired == {level} then")?;
+			mng.indent();
+			line!(mng, w, "desired = nil")?;
+			mng.dedent();
+			line!(mng, w, "end")?;
+		}
+		_ => {}
+	}
+
+	line!(mng, w, "break")?;
+
+	mng.dedent();
+	line!(mng, w, "end")
+}
+
+fn write_block_without_goto(block: &Block, mng: &mut Manager, w: &mut dyn Write) -> Result<()> {
+	mng.push_label(block.label_type());
+
+	line!(mng, w, "while true do")?;
+	mng.indent();
+
+	// Without `continue` either, loops run their body inside a `repeat`
+	// that is left with `desired` set to the loop's own level
+	let is_loop = block.label_type() == Some(LabelType::Backward);
+	let is_repeat = is_loop && mng.branch_style() == BranchStyle::Repeat;
+
+	if is_loop {
+		write_loop_start(mng, w)?;
+	}
+
+	if is_repeat {
+		line!(mng, w, "repeat")?;
+		mng.indent();
+	}
+
+	write_code(block, mng, w)?;
+
+	match block.last() {
+		Some(v) => v.write(mng, w)?,
+		None => line!(mng, w, "break")?,
+	}
+
+	if is_repeat {
+		let level = mng.label_list().len() - 1;
+
+		mng.dedent();
+		line!(mng, w, "until true")?;
+		line!(mng, w, "if desired ~= {level} then")?;
+		mng.indent();
+		line!(mng, w, "break")?;
+		mng.dedent();
+		line!(mng, w, "end")?;
+		line!(mng, w, "desired = nil")?;
+	}
+
+	mng.dedent();
+	line!(mng, w, "end")?;
+
+	mng.pop_label();
+
+	// Pending branches leave a protected closure through `desired`
+	// and get resolved by the `Try` around it
+	if mng.is_closure_start() {
+		return Ok(());
+	}
+
+	write_br_parent(mng, w)
+}
+
+impl Driver for Block {
+	fn write(&self, mng: &mut Manager, w: &mut dyn Write) -> Result<()> {
+		if mng.branch_style() != BranchStyle::Goto {
+			return write_block_without_goto(self, mng, w);
+		}
+
+		let label = mng.push_label(self.label_type());
+
+		match self.label_type() {
+			Some(LabelType::Forward) => {
+				write_inner_block(self, mng, w)?;
+				line!(mng, w, "::continue_at_{label}::")?;
+			}
+			Some(LabelType::Backward) => {
+				line!(mng, w, "::continue_at_{label}::")?;
+				line!(mng, w, "while true do")?;
+				mng.indent();
+				write_loop_start(mng, w)?;
+				write_inner_block(self, mng, w)?;
+
+				if self.last().is_none() {
+					line!(mng, w, "break")?;
+				}
+
+				mng.dedent();
+				line!(mng, w, "end")?;
+			}
+			None => write_inner_block(self, mng, w)?,
+		}
+
+		mng.pop_label();
+
+		Ok(())
+	}
+}
+
+impl Driver for BrIf {
+	fn write(&self, mng: &mut Manager, w: &mut dyn Write) -> Result<()> {
+		indented!(mng, w, "if ")?;
+		Condition(self.condition()).write(mng, w)?;
+		writeln!(w, " then")?;
+		mng.indent();
+		self.target().write(mng, w)?;
+		mng.dedent();
+		line!(mng, w, "end")
+	}
+}
+
+impl Driver for If {
+	fn write(&self, mng: &mut Manager, w: &mut dyn Write) -> Result<()> {
+		// Arms hinted as unlikely are swapped so the likely one falls through
+		let (on_true, on_false, flip) = match self.on_false() {
+			Some(v) if self.hint() == Some(false) => (v, Some(self.on_true()), true),
+			v => (self.on_true(), v, false),
+		};
+
+		indented!(mng, w, "if ")?;
+
+		if flip {
+			write!(w, "not (")?;
+			Condition(self.condition()).write(mng, w)?;
+			write!(w, ")")?;
+		} else {
+			Condition(self.condition()).write(mng, w)?;
+		}
+
+		writeln!(w, " then")?;
+
+		mng.indent();
+		on_true.write(mng, w)?;
+		mng.dedent();
+
+		if let Some(v) = on_false {
+			line!(mng, w, "else")?;
+			mng.indent();
+			v.write(mng, w)?;
+			mng.dedent();
+		}
+
+		line!(mng, w, "end")
+	}
+}
+
+fn write_catch_list(
+	list: &[Catch],
+	level: usize,
+	mng: &mut Manager,
+	w: &mut dyn Write,
+) -> Result<()> {
+	indented!(mng, w, "if not ")?;
+	mng.dialect().write_runtime_table("exception", w)?;
+	writeln!(w, ".accept(exception_{level}) then")?;
+	mng.indent();
+	line!(mng, w, "error(exception_{level}, 0)")?;
+	mng.dedent();
+
+	for catch in list {
+		match catch.tag() {
+			Some(tag) => line!(
+				mng,
+				w,
+				"elseif exception_{level}.tag == TAG_LIST[{tag}] then"
+			)?,
+			None => line!(mng, w, "else")?,
+		}
+
+		mng.indent();
+
+		if !catch.result_list().is_empty() {
+			let len = catch.result_list().iter().count();
+
+			indentation!(mng, w)?;
+			catch.result_list().write(mng, w)?;
+			write!(w, " = ")?;
+			write_separated(1..=len, |i, w| write!(w, "exception_{level}[{i}]"), w)?;
+			writeln!(w)?;
+		}
+
+		catch.code().write(mng, w)?;
+		mng.dedent();
+	}
+
+	if list.last().is_some_and(|v| v.tag().is_some()) {
+		line!(mng, w, "else")?;
+		mng.indent();
+		line!(mng, w, "error(exception_{level}, 0)")?;
+		mng.dedent();
+	}
+
+	line!(mng, w, "end")
+}
+
+impl Driver for Try {
+	fn write(&self, mng: &mut Manager, w: &mut dyn Write) -> Result<()> {
+		let level = mng.label_list().len();
+
+		line!(mng, w, "do")?;
+		mng.indent();
+		line!(
+			mng,
+			w,
+			"local success, exception_{level} = pcall(function()"
+		)?;
+		mng.indent();
+		mng.push_closure();
+		self.code().write(mng, w)?;
+
+		let escape_list = mng.pop_closure();
+
+		mng.dedent();
+		line!(mng, w, "end)")?;
+		line!(mng, w, "if not success then")?;
+		mng.indent();
+
+		// Calls unwound by the exception never counted themselves back out
+		if mng.max_depth().is_some() {
+			line!(mng, w, "DEPTH.now = depth")?;
+		}
+
+		if self.catch_list().is_empty() {
+			let skip = self.delegate().unwrap_or_default();
+
+			indentation!(mng, w)?;
+			mng.dialect().write_runtime_table("exception", w)?;
+			writeln!(w, ".delegate(exception_{level}, {skip})")?;
+		} else {
+			write_catch_list(self.catch_list(), level, mng, w)?;
+		}
+
+		mng.dedent();
+
+		// Branches out of the protected code come back as the label to go to
+		for index in escape_list {
+			let label = mng.label_list()[index];
+
+			line!(mng, w, "elseif exception_{level} == {label} then")?;
+			mng.indent();
+			write_goto(index, mng, w)?;
+			mng.dedent();
+		}
+
+		line!(mng, w, "end")?;
+		mng.dedent();
+		line!(mng, w, "end")?;
+
+		if mng.branch_style() != BranchStyle::Goto {
+			write_br_parent(mng, w)?;
+		}
+
+		Ok(())
+	}
+}
+
+impl Driver for Call {
+	fn write(&self, mng: &mut Manager, w: &mut dyn Write) -> Result<()> {
+		if !self.result_list().is_empty() {
+			self.result_list().write(mng, w)?;
+			write!(w, " = ")?;
+		}
+
+		write!(w, "FUNC_LIST[{}](", self.function())?;
+		self.param_list().write(mng, w)?;
+		write!(w, ")")
+	}
+}
+
+impl Driver for CallIndirect {
+	fn write(&self, mng: &mut Manager, w: &mut dyn Write) -> Result<()> {
+		if !self.result_list().is_empty() {
+			self.result_list().write(mng, w)?;
+			write!(w, " = ")?;
+		}
+
+		write!(w, "TABLE_LIST[{}].data[", self.table())?;
+		self.index().write(mng, w)?;
+		write!(w, "](")?;
+		self.param_list().write(mng, w)?;
+		write!(w, ")")
+	}
+}
+
+impl Driver for CallRef {
+	fn write(&self, mng: &mut Manager, w: &mut dyn Write) -> Result<()> {
+		if !self.result_list().is_empty() {
+			self.result_list().write(mng, w)?;
+			write!(w, " = ")?;
+		}
+
+		self.function().write(mng, w)?;
+		write!(w, "(")?;
+		self.param_list().write(mng, w)?;
+		write!(w, ")")
+	}
+}
+
+impl Driver for SetTemporary {
+	fn write(&self, mng: &mut Manager, w: &mut dyn Write) -> Result<()> {
+		self.var().write(mng, w)?;
+		write!(w, " = ")?;
+		self.value().write(mng, w)
+	}
+}
+
+impl Driver for SetLocal {
+	fn write(&self, mng: &mut Manager, w: &mut dyn Write) -> Result<()> {
+		self.var().write(mng, w)?;
+		write!(w, " = ")?;
+		self.value().write(mng, w)
+	}
+}
+
+impl Driver for SetGlobal {
+	fn write(&self, mng: &mut Manager, w: &mut dyn Write) -> Result<()> {
+		write!(w, "GLOBAL_LIST[{}].value = ", self.var())?;
+		self.value().write(mng, w)
+	}
+}
+
+impl Driver for StoreAt {
+	fn write(&self, mng: &mut Manager, w: &mut dyn Write) -> Result<()> {
+		let name = self.store_type().into_name();
+		let memory = self.memory();
+
+		mng.dialect().write_access_name("store", name, w)?;
+		write!(w, "(memory_at_{memory}, ")?;
+
+		self.pointer().write(mng, w)?;
+
+		if self.offset() != 0 {
+			write!(w, " + {}", self.offset())?;
+		}
+
+		write!(w, ", ")?;
+		self.value().write(mng, w)?;
+		write!(w, ")")
+	}
+}
+
+impl Driver for MemoryGrow {
+	fn write(&self, mng: &mut Manager, w: &mut dyn Write) -> Result<()> {
+		let memory = self.memory();
+
+		let name = if self.is_64() { "grow_64" } else { "grow" };
+
+		self.result().write(mng, w)?;
+		write!(w, " = ")?;
+		mng.dialect().write_runtime_name("allocator", name, w)?;
+		write!(w, "(memory_at_{memory}, ")?;
+		self.size().write(mng, w)?;
+		write!(w, ")")
+	}
+}
+
+impl Driver for MemoryCopy {
+	fn write(&self, mng: &mut Manager, w: &mut dyn Write) -> Result<()> {
+		let memory_1 = self.destination().memory();
+		let memory_2 = self.source().memory();
+
+		mng.dialect().write_runtime_name("store", "copy", w)?;
+		write!(w, "(memory_at_{memory_1}, ")?;
+		self.destination().pointer().write(mng, w)?;
+		write!(w, ", memory_at_{memory_2}, ")?;
+		self.source().pointer().write(mng, w)?;
+		write!(w, ", ")?;
+		self.size().write(mng, w)?;
+		write!(w, ")")
+	}
+}
+
+impl Driver for MemoryFill {
+	fn write(&self, mng: &mut Manager, w: &mut dyn Write) -> Result<()> {
+		let memory = self.destination().memory();
+
+		mng.dialect().write_runtime_name("store", "fill", w)?;
+		write!(w, "(memory_at_{memory}, ")?;
+		self.destination().pointer().write(mng, w)?;
+		write!(w, ", ")?;
+		self.size().write(mng, w)?;
+		write!(w, ", ")?;
+		self.value().write(mng, w)?;
+		write!(w, ")")
+	}
+}
+
+impl Driver for MemoryInit {
+	fn write(&self, mng: &mut Manager, w: &mut dyn Write) -> Result<()> {
+		let memory = self.destination().memory();
+		let data = self.data();
+
+		mng.dialect().write_runtime_name("store", "init", w)?;
+		write!(w, "(memory_at_{memory}, ")?;
+		self.destination().pointer().write(mng, w)?;
+		write!(w, ", DATA_LIST[{data}], ")?;
+		self.offset().write(mng, w)?;
+		write!(w, ", ")?;
+		self.size().write(mng, w)?;
+		write!(w, ")")
+	}
+}
+
+impl Driver for DataDrop {
+	fn write(&self, _mng: &mut Manager, w: &mut dyn Write) -> Result<()> {
+		write!(w, "DATA_LIST[{}] = nil", self.data())
+	}
+}
+
+impl Driver for TableInit {
+	fn write(&self, mng: &mut Manager, w: &mut dyn Write) -> Result<()> {
+		let table = self.table();
+		let element = self.element();
+
+		mng.dialect().write_runtime_name("table", "init", w)?;
+		write!(w, "(TABLE_LIST[{table}], ")?;
+		self.destination().write(mng, w)?;
+		write!(w, ", ELEM_LIST[{element}], ")?;
+		self.offset().write(mng, w)?;
+		write!(w, ", ")?;
+		self.size().write(mng, w)?;
+		write!(w, ")")
+	}
+}
+
+impl Driver for ElemDrop {
+	fn write(&self, _mng: &mut Manager, w: &mut dyn Write) -> Result<()> {
+		write!(w, "ELEM_LIST[{}] = nil", self.element())
+	}
+}
+
+impl Driver for TableSet {
+	fn write(&self, mng: &mut Manager, w: &mut dyn Write) -> Result<()> {
+		mng.dialect().write_runtime_name("table", "set", w)?;
+		write!(w, "(TABLE_LIST[{}], ", self.table())?;
+		self.index().write(mng, w)?;
+		write!(w, ", ")?;
+		self.value().write(mng, w)?;
+		write!(w, ")")
+	}
+}
+
+impl Driver for TableGrow {
+	fn write(&self, mng: &mut Manager, w: &mut dyn Write) -> Result<()> {
+		let table = self.table();
+
+		self.result().write(mng, w)?;
+		write!(w, " = ")?;
+		mng.dialect().write_runtime_name("table", "grow", w)?;
+		write!(w, "(TABLE_LIST[{table}], ")?;
+		self.size().write(mng, w)?;
+		write!(w, ", ")?;
+		self.value().write(mng, w)?;
+		write!(w, ")")
+	}
+}
+
+impl Driver for TableCopy {
+	fn write(&self, mng: &mut Manager, w: &mut dyn Write) -> Result<()> {
+		let table_1 = self.destination().table();
+		let table_2 = self.source().table();
+
+		mng.dialect().write_runtime_name("table", "copy", w)?;
+		write!(w, "(TABLE_LIST[{table_1}], ")?;
+		self.destination().index().write(mng, w)?;
+		write!(w, ", TABLE_LIST[{table_2}], ")?;
+		self.source().index().write(mng, w)?;
+		write!(w, ", ")?;
+		self.size().write(mng, w)?;
+		write!(w, ")")
+	}
+}
+
+impl Driver for TableFill {
+	fn write(&self, mng: &mut Manager, w: &mut dyn Write) -> Result<()> {
+		let table = self.destination().table();
+
+		mng.dialect().write_runtime_name("table", "fill", w)?;
+		write!(w, "(TABLE_LIST[{table}], ")?;
+		self.destination().index().write(mng, w)?;
+		write!(w, ", ")?;
+		self.size().write(mng, w)?;
+		write!(w, ", ")?;
+		self.value().write(mng, w)?;
+		write!(w, ")")
+	}
+}
+
+impl Driver for AtomicWait {
+	fn write(&self, mng: &mut Manager, w: &mut dyn Write) -> Result<()> {
+		self.result().write(mng, w)?;
+		write!(w, " = ")?;
+		mng.dialect().write_runtime_name("atomic", "wait", w)?;
+		write!(w, "(")?;
+		self.value().write(mng, w)?;
+		write!(w, ", ")?;
+		self.expected().write(mng, w)?;
+		write!(w, ", ")?;
+		self.timeout().write(mng, w)?;
+		write!(w, ")")
+	}
+}
+
+impl Driver for AtomicNotify {
+	fn write(&self, mng: &mut Manager, w: &mut dyn Write) -> Result<()> {
+		let memory = self.memory();
+
+		self.result().write(mng, w)?;
+		write!(w, " = ")?;
+		mng.dialect().write_runtime_name("atomic", "notify", w)?;
+		write!(w, "(memory_at_{memory}, ")?;
+		self.pointer().write(mng, w)?;
+		write!(w, ", ")?;
+		self.count().write(mng, w)?;
+		write!(w, ")")
+	}
+}
+
+fn write_stat(stat: &dyn Driver, mng: &mut Manager, w: &mut dyn Write) -> Result<()> {
+	indentation!(mng, w)?;
+	stat.write(mng, w)?;
+	writeln!(w)
+}
+
+impl Driver for Statement {
+	fn write(&self, mng: &mut Manager, w: &mut dyn Write) -> Result<()> {
+		match self {
+			Self::Block(s) => s.write(mng, w),
+			Self::BrIf(s) => s.write(mng, w),
+			Self::If(s) => s.write(mng, w),
+			Self::Try(s) => s.write(mng, w),
+			Self::Call(s) => write_stat(s, mng, w),
+			Self::CallIndirect(s) => write_stat(s, mng, w),
+			Self::CallRef(s) => write_stat(s, mng, w),
+			Self::SetTemporary(s) => write_stat(s, mng, w),
+			Self::SetLocal(s) => write_stat(s, mng, w),
+			Self::SetGlobal(s) => write_stat(s, mng, w),
+			Self::StoreAt(s) => write_stat(s, mng, w),
+			Self::MemoryGrow(s) => write_stat(s, mng, w),
+			Self::MemoryCopy(s) => write_stat(s, mng, w),
+			Self::MemoryFill(s) => write_stat(s, mng, w),
+			Self::MemoryInit(s) => write_stat(s, mng, w),
+			Self::DataDrop(s) => write_stat(s, mng, w),
+			Self::TableInit(s) => write_stat(s, mng, w),
+			Self::ElemDrop(s) => write_stat(s, mng, w),
+			Self::TableSet(s) => write_stat(s, mng, w),
+			Self::TableGrow(s) => write_stat(s, mng, w),
+			Self::TableCopy(s) => write_stat(s, mng, w),
+			Self::TableFill(s) => write_stat(s, mng, w),
+			Self::AtomicWait(s) => write_stat(s, mng, w),
+			Self::AtomicNotify(s) => write_stat(s, mng, w),
+		}
+	}
+}
+
+fn write_parameter_list(ast: &FuncData, mng: &Manager, w: &mut dyn Write) -> Result<()> {
+	let dialect = mng.dialect();
+	let param_list = ast.param_data().iter().enumerate();
+
+	write!(w, "function(")?;
+	write_separated(
+		param_list,
+		|(i, &typ), w| write!(w, "loc_{i}{}", dialect.type_annotation(typ)),
+		w,
+	)?;
+	writeln!(w, ")")
+}
+
+fn write_zero(typ: ValType, mng: &mut Manager, w: &mut dyn Write) -> Result<()> {
+	match typ {
+		ValType::F32 | ValType::F64 => write!(w, "0.0"),
+		ValType::I64 => Value::I64(0).write(mng, w),
+		ValType::V128 => Value::V128(0).write(mng, w),
+		ValType::Ref(_) => write!(w, "nil"),
+		_ => write!(w, "0"),
+	}
+}
+
+fn write_variable_list(ast: &FuncData, mng: &mut Manager, w: &mut dyn Write) -> Result<()> {
+	let mut init_list: Vec<_> = (0..ast.num_param())
+		.filter(|&i| mng.frame().local(i).is_some())
+		.map(|i| (i, None))
+		.collect();
+
+	for (i, typ) in ast.local_data().iter().copied().enumerate() {
+		let index = ast.num_param() + i;
+
+		if mng.frame().local(index).is_some() {
+			init_list.push((index, Some(typ)));
+		} else {
+			let annotation = mng.dialect().type_annotation(typ);
+
+			indented!(mng, w, "local loc_{index}{annotation} = ")?;
+			write_zero(typ, mng, w)?;
+			writeln!(w)?;
+		}
+	}
+
+	for i in 0..ast.num_stack() {
+		if mng.frame().temporary(i).is_none() {
+			line!(mng, w, "local reg_{i}")?;
+		}
+	}
+
+	// Locals come first in the table, starting from the parameters moved into
+	// it, and the `nil` of each temporary after them still makes room for it
+	if !mng.frame().is_empty() {
+		indented!(mng, w, "local frame = {{ ")?;
+
+		for &(index, typ) in &init_list {
+			match typ {
+				Some(typ) => write_zero(typ, mng, w)?,
+				None => write!(w, "loc_{index}")?,
+			}
+
+			write!(w, ", ")?;
+		}
+
+		for _ in init_list.len()..mng.frame().len() {
+			write!(w, "nil, ")?;
+		}
+
+		writeln!(w, "}}")?;
+	}
+
+	Ok(())
+}
+
+impl Driver for FuncData {
+	fn write(&self, mng: &mut Manager, w: &mut dyn Write) -> Result<()> {
+		mng.indent();
+
+		write_parameter_list(self, mng, w)?;
+		write_variable_list(self, mng, w)?;
+
+		if mng.branch_style() != BranchStyle::Goto && mng.has_branch() {
+			line!(mng, w, "local desired")?;
+		}
+
+		if mng.has_table() {
+			line!(mng, w, "local temp")?;
+		}
+
+		if let Some(limit) = mng.max_depth() {
+			write_depth_enter(limit, mng, w)?;
+		}
+
+		if mng.is_metered() {
+			write_fuel_use(mng, w)?;
+		}
+
+		self.code().write(mng, w)?;
+
+		if mng.max_depth().is_some() {
+			line!(mng, w, "DEPTH.now = depth - 1")?;
+		}
+
+		if self.num_result() != 0 {
+			indented!(mng, w, "return ")?;
+
+			ResultList::new(0, self.num_result()).write(mng, w)?;
+
+			writeln!(w)?;
+		}
+
+		mng.dedent();
+
+		line!(mng, w, "end")
+	}
+}
@@ -0,0 +1,53 @@
+use std::io::{Result, Write};
+
+// Finite floats have at most this many significant decimal digits
+const MAX_DIGIT_COUNT: usize = 800;
+
+/// Returns whether the shortest decimal that reads back as a float is also
+/// exactly its value, so that any parser reads it back without rounding.
+#[must_use]
+pub fn is_exact_decimal(number: f64) -> bool {
+	let shortest = format!("{number:e}");
+	let exact = format!("{number:.MAX_DIGIT_COUNT$e}");
+
+	let (shortest, _) = shortest.split_once('e').unwrap();
+	let (exact, _) = exact.split_once('e').unwrap();
+
+	exact.trim_end_matches('0').trim_end_matches('.') == shortest
+}
+
+/// Writes a finite float as a hexadecimal literal with a binary exponent,
+/// which holds its bits exactly.
+///
+/// # Errors
+/// Returns `Err` if writing to `Write` failed.
+pub fn write_hex_float(number: f64, w: &mut dyn Write) -> Result<()> {
+	const FRACTION_LEN: u32 = 52;
+
+	let bits = number.to_bits();
+	let sign = if number.is_sign_negative() { "-" } else { "" };
+	let biased = i64::try_from((bits >> FRACTION_LEN) & 0x7FF).unwrap();
+	let mut fraction = bits & ((1 << FRACTION_LEN) - 1);
+
+	// Subnormals have no implicit leading one and the least exponent
+	let (lead, exponent) = if biased == 0 {
+		(0, -1022)
+	} else {
+		(1, biased - 1023)
+	};
+
+	if fraction == 0 {
+		return write!(w, "{sign}0x{lead}p{exponent:+}");
+	}
+
+	let mut len = FRACTION_LEN / 4;
+
+	while fraction & 0xF == 0 {
+		fraction >>= 4;
+		len -= 1;
+	}
+
+	let len = len as usize;
+
+	write!(w, "{sign}0x{lead}.{fraction:0len$x}p{exponent:+}")
+}
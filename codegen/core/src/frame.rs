@@ -0,0 +1,255 @@
+use std::{cmp::Reverse, collections::BTreeSet};
+
+use wasm_ast::{
+	node::{Block, FuncData, LabelType, Local, SetLocal, SetTemporary, Statement, Temporary},
+	visit::{Driver, Visitor},
+};
+
+const MAX_LOCAL_COUNT: usize = 180;
+
+// Protected code runs in a closure, which Lua 5.1 and LuaJIT let hold no more
+// than 60 upvalues, some of which go to the tables of the instance and the
+// state of the function around it
+const MAX_UPVALUE_COUNT: usize = 60;
+const RESERVED_UPVALUE_COUNT: usize = 16;
+
+// Uses in a loop count for this many times as much for each loop around them,
+// as they are likely to run as many times more
+const LOOP_WEIGHT: usize = 8;
+
+fn add_weight(count: &mut usize, weight: usize) {
+	*count = count.saturating_add(weight);
+}
+
+// Variables are keyed by whether they are temporaries and then their index,
+// which is also the order they are laid out in the table
+type Key = (bool, usize);
+
+#[derive(Default)]
+struct Capture {
+	key_set: BTreeSet<Key>,
+}
+
+impl Visitor for Capture {
+	fn visit_get_temporary(&mut self, v: Temporary) {
+		self.key_set.insert((true, v.var()));
+	}
+
+	fn visit_get_local(&mut self, v: Local) {
+		self.key_set.insert((false, v.var()));
+	}
+
+	fn visit_set_temporary(&mut self, v: &SetTemporary) {
+		self.key_set.insert((true, v.var().var()));
+	}
+
+	fn visit_set_local(&mut self, v: &SetLocal) {
+		self.key_set.insert((false, v.var().var()));
+	}
+}
+
+struct Count {
+	local_list: Vec<usize>,
+	temporary_list: Vec<usize>,
+	closure_list: Vec<BTreeSet<Key>>,
+	weight: usize,
+}
+
+impl Count {
+	fn new(ast: &FuncData) -> Self {
+		Self {
+			local_list: vec![0; ast.num_param() + ast.local_data().len()],
+			temporary_list: vec![0; ast.num_stack()],
+			closure_list: Vec::new(),
+			weight: 1,
+		}
+	}
+
+	// Walks the blocks by hand rather than through the visitor so that each
+	// loop knows the weight of the code around it, and uses count for
+	// `LOOP_WEIGHT` times as much for every loop they are nested in
+	fn count_block(&mut self, block: &Block) {
+		let weight = self.weight;
+
+		if block.label_type() == Some(LabelType::Backward) {
+			self.weight = weight.saturating_mul(LOOP_WEIGHT);
+		}
+
+		for v in block.code() {
+			match v {
+				Statement::Block(v) => self.count_block(v),
+				Statement::If(v) => {
+					v.condition().accept(self);
+
+					self.count_block(v.on_true());
+
+					if let Some(v) = v.on_false() {
+						self.count_block(v);
+					}
+				}
+				Statement::Try(v) => {
+					let mut capture = Capture::default();
+
+					v.code().accept(&mut capture);
+
+					self.closure_list.push(capture.key_set);
+					self.count_block(v.code());
+
+					for v in v.catch_list() {
+						self.count_block(v.code());
+					}
+				}
+				_ => v.accept(self),
+			}
+		}
+
+		if let Some(v) = block.last() {
+			v.accept(self);
+		}
+
+		self.weight = weight;
+	}
+
+	fn weight_of(&self, key: Key) -> usize {
+		match key {
+			(true, i) => self.temporary_list[i],
+			(false, i) => self.local_list[i],
+		}
+	}
+}
+
+impl Visitor for Count {
+	fn visit_get_temporary(&mut self, v: Temporary) {
+		add_weight(&mut self.temporary_list[v.var()], self.weight);
+	}
+
+	fn visit_get_local(&mut self, v: Local) {
+		add_weight(&mut self.local_list[v.var()], self.weight);
+	}
+
+	fn visit_set_temporary(&mut self, v: &SetTemporary) {
+		add_weight(&mut self.temporary_list[v.var().var()], self.weight);
+	}
+
+	fn visit_set_local(&mut self, v: &SetLocal) {
+		add_weight(&mut self.local_list[v.var().var()], self.weight);
+	}
+}
+
+/// Where the locals and temporaries of a function are kept, which is in
+/// Lua locals for as many as the limit on them allows, and in the slots of a
+/// `frame` table for the rest. Parameters are kept in locals, and the others
+/// that are used the most, with uses in loops weighed higher, are given the
+/// remaining ones. Protected code that would capture too many upvalues has
+/// the variables it uses the least moved to the table too, parameters
+/// included.
+#[derive(Default)]
+pub struct Frame {
+	local_list: Vec<Option<usize>>,
+	temporary_list: Vec<Option<usize>>,
+	len: usize,
+}
+
+impl Frame {
+	/// Lays out the variables of a function that already holds `upvalues`
+	/// locals of its own.
+	#[must_use]
+	pub fn new(ast: &FuncData, upvalues: usize) -> Self {
+		let mut count = Count::new(ast);
+
+		count.count_block(ast.code());
+
+		let available = MAX_LOCAL_COUNT
+			.saturating_sub(upvalues)
+			.saturating_sub(ast.num_param());
+
+		// Ties go to temporaries and then to the lowest index
+		let mut order: Vec<_> = count
+			.temporary_list
+			.iter()
+			.enumerate()
+			.map(|(i, &n)| (Reverse(n), false, i))
+			.chain(
+				count
+					.local_list
+					.iter()
+					.enumerate()
+					.skip(ast.num_param())
+					.map(|(i, &n)| (Reverse(n), true, i)),
+			)
+			.collect();
+
+		order.sort_unstable();
+
+		let mut frame = Self {
+			local_list: vec![None; count.local_list.len()],
+			temporary_list: vec![None; count.temporary_list.len()],
+			len: 0,
+		};
+
+		let mut spilled: BTreeSet<Key> = order
+			.into_iter()
+			.skip(available)
+			.map(|(_, is_local, i)| (!is_local, i))
+			.collect();
+
+		let captured = MAX_UPVALUE_COUNT
+			.saturating_sub(RESERVED_UPVALUE_COUNT)
+			.saturating_sub(upvalues);
+
+		for key_set in &count.closure_list {
+			let mut kept: Vec<_> = key_set
+				.iter()
+				.filter(|key| !spilled.contains(key))
+				.map(|&key| (count.weight_of(key), key))
+				.collect();
+
+			let Some(excess) = kept.len().checked_sub(captured) else {
+				continue;
+			};
+
+			kept.sort_unstable();
+			spilled.extend(kept.into_iter().take(excess).map(|v| v.1));
+		}
+
+		// Locals come first so that the table starts with their values
+		for (is_temporary, i) in spilled {
+			let list = if is_temporary {
+				&mut frame.temporary_list
+			} else {
+				&mut frame.local_list
+			};
+
+			list[i] = Some(frame.len);
+			frame.len += 1;
+		}
+
+		frame
+	}
+
+	/// Returns the slot of the table that holds a local, counted from 0, or
+	/// `None` when it is kept in a Lua local.
+	#[must_use]
+	pub fn local(&self, var: usize) -> Option<usize> {
+		self.local_list.get(var).copied().flatten()
+	}
+
+	/// Returns the slot of the table that holds a temporary, counted from 0,
+	/// or `None` when it is kept in a Lua local.
+	#[must_use]
+	pub fn temporary(&self, var: usize) -> Option<usize> {
+		self.temporary_list.get(var).copied().flatten()
+	}
+
+	/// Returns the slots in the table, which is only needed when there are
+	/// any.
+	#[must_use]
+	pub const fn len(&self) -> usize {
+		self.len
+	}
+
+	#[must_use]
+	pub const fn is_empty(&self) -> bool {
+		self.len == 0
+	}
+}
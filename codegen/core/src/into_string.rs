@@ -0,0 +1,47 @@
+use wasm_ast::node::{LoadType, StoreType};
+
+/// Names the memory access types the same way in every dialect, matching
+/// the `load` and `store` functions of the runtimes.
+pub trait IntoName {
+	#[must_use]
+	fn into_name(self) -> &'static str;
+}
+
+impl IntoName for LoadType {
+	fn into_name(self) -> &'static str {
+		match self {
+			Self::I32 => "i32",
+			Self::I64 => "i64",
+			Self::F32 => "f32",
+			Self::F64 => "f64",
+			Self::I32_I8 => "i32_i8",
+			Self::I32_U8 => "i32_u8",
+			Self::I32_I16 => "i32_i16",
+			Self::I32_U16 => "i32_u16",
+			Self::I64_I8 => "i64_i8",
+			Self::I64_U8 => "i64_u8",
+			Self::I64_I16 => "i64_i16",
+			Self::I64_U16 => "i64_u16",
+			Self::I64_I32 => "i64_i32",
+			Self::I64_U32 => "i64_u32",
+			Self::V128 => "v128",
+		}
+	}
+}
+
+impl IntoName for StoreType {
+	fn into_name(self) -> &'static str {
+		match self {
+			Self::I32 => "i32",
+			Self::I64 => "i64",
+			Self::F32 => "f32",
+			Self::F64 => "f64",
+			Self::I32_N8 => "i32_n8",
+			Self::I32_N16 => "i32_n16",
+			Self::I64_N8 => "i64_n8",
+			Self::I64_N16 => "i64_n16",
+			Self::I64_N32 => "i64_n32",
+			Self::V128 => "v128",
+		}
+	}
+}
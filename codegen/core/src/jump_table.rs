@@ -0,0 +1,51 @@
+use std::io::{Result, Write};
+
+use wasm_ast::{
+	node::{BrTable, FuncData},
+	visit::{Driver, Visitor},
+};
+
+struct Visit {
+	target_list: Vec<Vec<usize>>,
+}
+
+impl Visitor for Visit {
+	fn visit_br_table(&mut self, table: &BrTable) {
+		if table.data().is_empty() {
+			return;
+		}
+
+		let list = table.data().iter().map(|v| v.target()).collect();
+
+		self.target_list.push(list);
+	}
+}
+
+/// Finds the targets of every `br_table` with entries in a function, in the
+/// order that the backends number them from 1 as they are written.
+pub fn find_jump_table_list(ast: &FuncData) -> Vec<Vec<usize>> {
+	let mut visit = Visit {
+		target_list: Vec::new(),
+	};
+
+	ast.accept(&mut visit);
+
+	visit.target_list
+}
+
+/// Writes the jump tables as a list of their targets by index on one line.
+pub fn write_jump_table_list(list: &[Vec<usize>], w: &mut dyn Write) -> Result<()> {
+	write!(w, "{{ ")?;
+
+	for data in list {
+		let mut iter = data.iter();
+
+		write!(w, "{{ [0] = {}, ", iter.next().unwrap())?;
+
+		iter.try_for_each(|v| write!(w, "{v}, "))?;
+
+		write!(w, "}}, ")?;
+	}
+
+	writeln!(w, "}}")
+}
@@ -0,0 +1,40 @@
+pub use backend::Backend;
+pub use bound::{find_bound, Bound};
+pub use command::run_command;
+pub use counted::{find_counted, Counted};
+pub use dialect::{BranchStyle, Dialect};
+pub use emit::{write_expression, write_function, write_function_marked};
+pub use float::{is_exact_decimal, write_hex_float};
+pub use frame::Frame;
+pub use into_string::IntoName;
+pub use localize::localize;
+pub use minify::minify;
+pub use options::Options;
+pub use runtime::{build_runtime_name_list, write_runtime_binding, write_runtime_table};
+pub use self_test::write_self_test;
+pub use shorten::shorten;
+pub use source_map::{write_mark, write_source_map, Mapping};
+pub use translator::{
+	from_inst_list, from_module_custom, from_module_mapped, from_module_split, from_module_typed,
+	from_module_untyped, Split,
+};
+
+mod backend;
+mod bound;
+mod command;
+mod counted;
+mod coverage;
+mod dialect;
+mod emit;
+mod float;
+mod frame;
+mod into_string;
+mod jump_table;
+mod localize;
+mod minify;
+mod options;
+mod runtime;
+mod self_test;
+mod shorten;
+mod source_map;
+mod translator;
@@ -0,0 +1,118 @@
+use std::collections::BTreeSet;
+
+use wasm_ast::{
+	node::{
+		AtomicNotify, BinOp, CmpOp, FuncData, LoadAt, MemoryCopy, MemoryFill, MemoryGrow,
+		MemoryInit, MemorySize, StoreAt, UnOp, Value,
+	},
+	visit::{Driver, Visitor},
+};
+
+use wasmparser::ValType;
+
+use crate::{backend::Backend, into_string::IntoName};
+
+struct Visit<'a> {
+	backend: &'a dyn Backend,
+	local_set: BTreeSet<(&'static str, &'static str)>,
+	memory_set: BTreeSet<usize>,
+}
+
+impl Visitor for Visit<'_> {
+	fn visit_load_at(&mut self, v: &LoadAt) {
+		let name = v.load_type().into_name();
+
+		self.memory_set.insert(v.memory());
+		self.local_set.insert(("load", name));
+	}
+
+	fn visit_store_at(&mut self, v: &StoreAt) {
+		let name = v.store_type().into_name();
+
+		self.memory_set.insert(v.memory());
+		self.local_set.insert(("store", name));
+	}
+
+	// Doubles built from their bits take them as an `i64`, which may itself be
+	// built by the runtime
+	fn visit_value(&mut self, v: Value) {
+		let Some(name) = self.backend.value_name(v) else {
+			return;
+		};
+
+		self.local_set.insert(name);
+
+		if let Value::F64(v) = v {
+			self.visit_value(Value::I64(v.to_bits() as i64));
+		}
+	}
+
+	fn visit_un_op(&mut self, v: &UnOp) {
+		let name = self.backend.un_op_name(v.op_type());
+
+		self.local_set.insert(name);
+	}
+
+	fn visit_bin_op(&mut self, v: &BinOp) {
+		if let Some(name) = self.backend.bin_op_name(v.op_type()) {
+			self.local_set.insert(name);
+		}
+	}
+
+	fn visit_cmp_op(&mut self, v: &CmpOp) {
+		if let Some(name) = self.backend.cmp_op_name(v.op_type()) {
+			self.local_set.insert(name);
+		}
+	}
+
+	fn visit_memory_size(&mut self, m: &MemorySize) {
+		self.memory_set.insert(m.memory());
+	}
+
+	fn visit_memory_grow(&mut self, m: &MemoryGrow) {
+		self.memory_set.insert(m.memory());
+	}
+
+	fn visit_memory_copy(&mut self, m: &MemoryCopy) {
+		self.memory_set.insert(m.destination().memory());
+		self.memory_set.insert(m.source().memory());
+	}
+
+	fn visit_memory_fill(&mut self, m: &MemoryFill) {
+		self.memory_set.insert(m.destination().memory());
+	}
+
+	fn visit_memory_init(&mut self, m: &MemoryInit) {
+		self.memory_set.insert(m.destination().memory());
+	}
+
+	fn visit_atomic_notify(&mut self, m: &AtomicNotify) {
+		self.memory_set.insert(m.memory());
+	}
+}
+
+/// Finds the runtime operations and memories used by a function, so they
+/// can be bound to locals ahead of the function list or counted against the
+/// upvalues it may hold.
+#[must_use]
+pub fn localize(
+	backend: &dyn Backend,
+	ast: &FuncData,
+) -> (BTreeSet<(&'static str, &'static str)>, BTreeSet<usize>) {
+	let mut visit = Visit {
+		backend,
+		local_set: BTreeSet::new(),
+		memory_set: BTreeSet::new(),
+	};
+
+	// Locals start out as zero, which may itself be built by the runtime
+	if ast.local_data().contains(&ValType::I64) {
+		if let Some(name) = backend.value_name(Value::I64(0)) {
+			visit.local_set.insert(name);
+		}
+	}
+
+	ast.accept(&mut visit);
+
+	(visit.local_set, visit.memory_set)
+}
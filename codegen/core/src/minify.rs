@@ -0,0 +1,220 @@
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Gap {
+	None,
+	Space,
+	Line,
+}
+
+pub(crate) const fn is_word(byte: u8) -> bool {
+	byte.is_ascii_alphanumeric() || byte == b'_' || byte >= 0x80
+}
+
+// Returns the level of a long bracket such as `[==[` at the start of `data`
+pub(crate) fn get_bracket_level(data: &[u8]) -> Option<usize> {
+	let rest = data.strip_prefix(b"[")?;
+	let level = rest.iter().take_while(|&&v| v == b'=').count();
+
+	(rest.get(level) == Some(&b'[')).then_some(level)
+}
+
+// Returns the length of the long bracket at the start of `data`, or all of it
+// if the closing bracket is missing
+pub(crate) fn get_bracket_len(data: &[u8], level: usize) -> usize {
+	let mut close = vec![b'='; level + 2];
+
+	close[0] = b']';
+	close[level + 1] = b']';
+
+	data.windows(close.len())
+		.skip(level + 2)
+		.position(|v| v == close)
+		.map_or(data.len(), |v| v + level + 2 + close.len())
+}
+
+// Returns the length of the quoted string at the start of `data`, or all of it
+// if the closing quote is missing
+pub(crate) fn get_quoted_len(data: &[u8]) -> usize {
+	let quote = data[0];
+	let mut iter = data.iter().enumerate().skip(1);
+
+	while let Some((i, &byte)) = iter.next() {
+		if byte == b'\\' {
+			iter.next();
+		} else if byte == quote {
+			return i + 1;
+		}
+	}
+
+	data.len()
+}
+
+pub(crate) fn get_line_len(data: &[u8]) -> usize {
+	data.iter().position(|&v| v == b'\n').unwrap_or(data.len())
+}
+
+struct Minifier {
+	result: Vec<u8>,
+	gap: Gap,
+	is_number: bool,
+}
+
+impl Minifier {
+	// Whitespace between two tokens can only be dropped if joining them does
+	// not form a longer token, such as two names, `--`, `..`, or `[[`
+	fn needs_space(&self, next: u8) -> bool {
+		let Some(&last) = self.result.last() else {
+			return false;
+		};
+
+		let is_joined = match (last, next) {
+			(b'-', b'-')
+			| (b'[', b'[' | b'=')
+			| (b'.', b'.')
+			| (b'=' | b'~' | b'<' | b'>', b'=')
+			| (b'<', b'<')
+			| (b'>', b'>')
+			| (b'/', b'/')
+			| (b':', b':') => true,
+			(b'.', _) => next.is_ascii_digit(),
+			(b'e' | b'E' | b'p' | b'P', b'+' | b'-') => self.is_number,
+			(_, b'.') => self.is_number,
+			_ => false,
+		};
+
+		is_joined || (is_word(last) && is_word(next))
+	}
+
+	// A line starting with `(` is kept on its own line, as joining it to the
+	// one before would turn the statements into a call
+	fn write_gap(&mut self, next: u8) {
+		match self.gap {
+			Gap::None => {}
+			Gap::Line if next == b'(' => self.result.push(b'\n'),
+			Gap::Space | Gap::Line => {
+				if self.needs_space(next) {
+					self.result.push(b' ');
+				}
+			}
+		}
+
+		self.gap = Gap::None;
+	}
+
+	fn write_byte(&mut self, byte: u8) {
+		self.write_gap(byte);
+
+		if !is_word(byte) {
+			self.is_number = false;
+		} else if !self.result.last().copied().is_some_and(is_word) {
+			self.is_number = byte.is_ascii_digit();
+		}
+
+		self.result.push(byte);
+	}
+
+	fn write_verbatim(&mut self, data: &[u8]) {
+		self.write_gap(data[0]);
+		self.result.extend_from_slice(data);
+		self.is_number = false;
+	}
+
+	// Directives such as `--!optimize 2` are comments that change how Luau
+	// compiles the script, so they are kept on a line of their own
+	fn write_directive(&mut self, data: &[u8]) {
+		if self.result.last().is_some_and(|&v| v != b'\n') {
+			self.result.push(b'\n');
+		}
+
+		self.result.extend_from_slice(data);
+		self.result.push(b'\n');
+		self.gap = Gap::None;
+		self.is_number = false;
+	}
+
+	fn add_gap(&mut self, gap: Gap) {
+		self.gap = self.gap.max(gap);
+	}
+
+	// Returns the length of the comment at the start of `data`
+	fn skip_comment(&mut self, data: &[u8]) -> usize {
+		if let Some(level) = get_bracket_level(&data[2..]) {
+			let len = get_bracket_len(&data[2..], level) + 2;
+			let gap = if data[..len].contains(&b'\n') {
+				Gap::Line
+			} else {
+				Gap::Space
+			};
+
+			self.add_gap(gap);
+
+			len
+		} else if data[2..].starts_with(b"!") {
+			let len = get_line_len(data);
+
+			self.write_directive(&data[..len]);
+
+			(len + 1).min(data.len())
+		} else {
+			self.add_gap(Gap::Space);
+
+			get_line_len(data)
+		}
+	}
+
+	fn write_source(&mut self, source: &[u8]) {
+		let mut i = 0;
+
+		while let Some(&byte) = source.get(i) {
+			let rest = &source[i..];
+
+			i += match byte {
+				b' ' | b'\t' | b'\r' => {
+					self.add_gap(Gap::Space);
+
+					1
+				}
+				b'\n' => {
+					self.add_gap(Gap::Line);
+
+					1
+				}
+				b'-' if rest.starts_with(b"--") => self.skip_comment(rest),
+				b'"' | b'\'' => {
+					let len = get_quoted_len(rest);
+
+					self.write_verbatim(&rest[..len]);
+
+					len
+				}
+				b'[' if get_bracket_level(rest).is_some() => {
+					let level = get_bracket_level(rest).unwrap();
+					let len = get_bracket_len(rest, level);
+
+					self.write_verbatim(&rest[..len]);
+
+					len
+				}
+				_ => {
+					self.write_byte(byte);
+
+					1
+				}
+			};
+		}
+	}
+}
+
+/// Strips the comments and every whitespace byte that is not needed to
+/// separate two tokens from Lua source, for targets that limit the size of
+/// a script. Strings and Luau `--!` directives are kept as they are.
+#[must_use]
+pub fn minify(source: &[u8]) -> Vec<u8> {
+	let mut minifier = Minifier {
+		result: Vec::with_capacity(source.len()),
+		gap: Gap::None,
+		is_number: false,
+	};
+
+	minifier.write_source(source);
+	minifier.result
+}
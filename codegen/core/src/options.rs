@@ -0,0 +1,43 @@
+use wasm_ast::optimize::Level;
+
+/// Settings shared by every backend for how a module is translated.
+#[derive(Clone, Copy, Default)]
+pub struct Options {
+	/// How far each function is optimized before it is lowered.
+	pub level: Level,
+	/// Meters functions against a budget of this many function entries and
+	/// loop iterations, which traps or calls a handler once spent.
+	pub fuel: Option<u64>,
+	/// Calls the yield handler, or yields the running coroutine without one,
+	/// once every this many loop iterations.
+	pub yield_every: Option<u32>,
+	/// Traps once this many calls are active at once, instead of leaving deep
+	/// recursion to overflow the stack of the host.
+	pub max_depth: Option<u32>,
+	/// Defines every function as a local named after it in the name section,
+	/// so that tracebacks on Luau show its name.
+	pub debug_names: bool,
+	/// Passes the globals of the instance through the runtime trace table and
+	/// exposes it as `trace`, for runtimes built with tracing.
+	pub trace: bool,
+	/// Counts the calls to every function and the time spent in them, which
+	/// the instance exposes as `profile`.
+	pub profile: bool,
+	/// Counts every entry into each block of every function, which the
+	/// instance exposes as `coverage`.
+	pub coverage: bool,
+	/// Adds `call_async` to the instance, which calls an export in a coroutine
+	/// that imports can suspend until the host resumes them.
+	pub call_async: bool,
+	/// Writes data segments as text in base 85 that the runtime decodes as
+	/// the module starts, instead of escaping every byte that is not printable.
+	pub compact_data: bool,
+	/// Leaves out the functions that are not reached from the exports, the
+	/// start function, or a table that can be read, and the element segments
+	/// that only fill tables that nothing reads.
+	pub tree_shake: bool,
+	/// Moves constants added to addresses into the offset of loads and
+	/// stores, which is only correct for modules whose addresses never wrap
+	/// around past 4 GiB.
+	pub fold_offsets: bool,
+}
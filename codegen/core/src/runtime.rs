@@ -0,0 +1,63 @@
+use std::{
+	collections::BTreeSet,
+	io::{Result, Write},
+};
+
+/// Returns every top level local of a runtime, found by the `local` at the
+/// start of the line that declares it.
+#[must_use]
+pub fn build_runtime_name_list(runtime: &str) -> BTreeSet<&str> {
+	runtime
+		.lines()
+		.filter_map(|line| {
+			let line = line.strip_prefix("local ")?;
+			let line = line.strip_prefix("function ").unwrap_or(line);
+			let end = line.find(|v: char| !v.is_ascii_alphanumeric() && v != '_')?;
+
+			Some(&line[..end])
+		})
+		.collect()
+}
+
+/// Writes a runtime made of top level locals so that it can be loaded on its
+/// own, returning a table of everything it declares.
+///
+/// # Errors
+/// Returns `Err` if writing to `Write` failed.
+pub fn write_runtime_table(runtime: &str, w: &mut dyn Write) -> Result<()> {
+	writeln!(w, "{runtime}")?;
+	writeln!(w, "return {{")?;
+
+	for name in build_runtime_name_list(runtime) {
+		writeln!(w, "\t{name} = {name},")?;
+	}
+
+	writeln!(w, "}}")
+}
+
+/// Writes the translated `data` after loading the table written by
+/// [`write_runtime_table`] with the `import` expression. Only the parts of
+/// the runtime that `data` names are bound, keeping clear of the local limit.
+///
+/// # Errors
+/// Returns `Err` if writing to `Write` failed.
+pub fn write_runtime_binding(
+	runtime: &str,
+	import: &str,
+	data: &[u8],
+	w: &mut dyn Write,
+) -> Result<()> {
+	let used: BTreeSet<_> = data
+		.split(|v| !v.is_ascii_alphanumeric() && *v != b'_')
+		.collect();
+
+	writeln!(w, "local Runtime = {import}")?;
+
+	for name in build_runtime_name_list(runtime) {
+		if used.contains(name.as_bytes()) {
+			writeln!(w, "local {name} = Runtime.{name}")?;
+		}
+	}
+
+	w.write_all(data)
+}
@@ -0,0 +1,850 @@
+use std::{
+	collections::BTreeSet,
+	fmt::{Display, Formatter},
+	io::{Result, Write},
+};
+
+use wasm_ast::{
+	factory::Factory,
+	module::{Module, TypeInfo},
+};
+use wasmparser::Operator;
+
+use crate::{backend::Backend, localize::localize, options::Options};
+
+use self::{
+	Expect::{NaN32, NaN64, Trap},
+	Value::{F32, F64, I32, I64},
+};
+
+// The cases are built as functions of no module, so an empty one stands in
+const EMPTY_MODULE: &[u8] = b"\0asm\x01\0\0\0";
+
+const OVERFLOW: &str = "integer_overflow";
+const DIVIDE: &str = "integer_divide_by_zero";
+const CONVERSION: &str = "invalid_conversion_to_integer";
+
+// Floats are given by their bits so that the signs of zeros and the payloads
+// of NaNs are exact
+const F32_NAN: u32 = 0x7FC0_0000;
+const F32_INF: u32 = 0x7F80_0000;
+const F32_NEG_INF: u32 = 0xFF80_0000;
+const F32_NEG_ZERO: u32 = 0x8000_0000;
+const F32_ONE: u32 = 0x3F80_0000;
+const F32_NEG_ONE: u32 = 0xBF80_0000;
+const F32_TWO: u32 = 0x4000_0000;
+const F32_HALF: u32 = 0x3F00_0000;
+const F32_NEG_HALF: u32 = 0xBF00_0000;
+
+const F64_NAN: u64 = 0x7FF8_0000_0000_0000;
+const F64_INF: u64 = 0x7FF0_0000_0000_0000;
+const F64_NEG_INF: u64 = 0xFFF0_0000_0000_0000;
+const F64_NEG_ZERO: u64 = 0x8000_0000_0000_0000;
+const F64_ONE: u64 = 0x3FF0_0000_0000_0000;
+const F64_NEG_ONE: u64 = 0xBFF0_0000_0000_0000;
+const F64_TWO: u64 = 0x4000_0000_0000_0000;
+const F64_HALF: u64 = 0x3FE0_0000_0000_0000;
+const F64_NEG_HALF: u64 = 0xBFE0_0000_0000_0000;
+
+#[derive(Clone, Copy)]
+enum Value {
+	I32(i32),
+	I64(i64),
+	F32(u32),
+	F64(u64),
+}
+
+impl Value {
+	fn write_code(self, code: &mut Vec<Operator<'static>>) {
+		match self {
+			I32(value) => code.push(Operator::I32Const { value }),
+			I64(value) => code.push(Operator::I64Const { value }),
+			F32(bits) => code.extend([
+				Operator::I32Const { value: bits as i32 },
+				Operator::F32ReinterpretI32,
+			]),
+			F64(bits) => code.extend([
+				Operator::I64Const { value: bits as i64 },
+				Operator::F64ReinterpretI64,
+			]),
+		}
+	}
+}
+
+impl Display for Value {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		match *self {
+			I32(value) => value.fmt(f),
+			I64(value) => value.fmt(f),
+			F32(bits) => f32::from_bits(bits).fmt(f),
+			F64(bits) => f64::from_bits(bits).fmt(f),
+		}
+	}
+}
+
+#[derive(Clone, Copy)]
+enum Expect {
+	Exact(Value),
+	NaN32,
+	NaN64,
+	Trap(&'static str),
+}
+
+impl Expect {
+	// Results are checked by the module itself, with floats compared by their
+	// bits and any NaN being accepted where one is expected
+	fn write_code(self, code: &mut Vec<Operator<'static>>) {
+		match self {
+			Self::Exact(I32(value)) => {
+				code.extend([Operator::I32Const { value }, Operator::I32Eq]);
+			}
+			Self::Exact(I64(value)) => {
+				code.extend([Operator::I64Const { value }, Operator::I64Eq]);
+			}
+			Self::Exact(F32(bits)) => code.extend([
+				Operator::I32ReinterpretF32,
+				Operator::I32Const { value: bits as i32 },
+				Operator::I32Eq,
+			]),
+			Self::Exact(F64(bits)) => code.extend([
+				Operator::I64ReinterpretF64,
+				Operator::I64Const { value: bits as i64 },
+				Operator::I64Eq,
+			]),
+			NaN32 => code.extend([
+				Operator::I32ReinterpretF32,
+				Operator::I32Const { value: 0x7FFF_FFFF },
+				Operator::I32And,
+				Operator::I32Const { value: 0x7F80_0000 },
+				Operator::I32GtU,
+			]),
+			NaN64 => code.extend([
+				Operator::I64ReinterpretF64,
+				Operator::I64Const {
+					value: 0x7FFF_FFFF_FFFF_FFFF,
+				},
+				Operator::I64And,
+				Operator::I64Const {
+					value: 0x7FF0_0000_0000_0000,
+				},
+				Operator::I64GtU,
+			]),
+			Trap(_) => {}
+		}
+	}
+}
+
+impl From<Value> for Expect {
+	fn from(value: Value) -> Self {
+		Self::Exact(value)
+	}
+}
+
+impl Display for Expect {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::Exact(value) => write!(f, "is {value}"),
+			NaN32 | NaN64 => write!(f, "is NaN"),
+			Trap(kind) => write!(f, "traps with {kind}"),
+		}
+	}
+}
+
+struct Case {
+	op: Operator<'static>,
+	arg_list: Vec<Value>,
+	expect: Expect,
+}
+
+impl Case {
+	fn build_code(&self) -> Vec<Operator<'static>> {
+		let mut code = Vec::new();
+
+		for arg in &self.arg_list {
+			arg.write_code(&mut code);
+		}
+
+		code.push(self.op.clone());
+		self.expect.write_code(&mut code);
+		code.push(Operator::End);
+		code
+	}
+
+	fn build_name(&self) -> String {
+		let arg_list: Vec<_> = self.arg_list.iter().map(Value::to_string).collect();
+
+		format!("{:?}({}) {}", self.op, arg_list.join(", "), self.expect)
+	}
+}
+
+fn un(op: Operator<'static>, value: Value, expect: impl Into<Expect>) -> Case {
+	Case {
+		op,
+		arg_list: vec![value],
+		expect: expect.into(),
+	}
+}
+
+fn bin(op: Operator<'static>, lhs: Value, rhs: Value, expect: impl Into<Expect>) -> Case {
+	Case {
+		op,
+		arg_list: vec![lhs, rhs],
+		expect: expect.into(),
+	}
+}
+
+fn build_i32_list() -> Vec<Case> {
+	vec![
+		bin(Operator::I32Add, I32(i32::MAX), I32(1), I32(i32::MIN)),
+		bin(Operator::I32Sub, I32(i32::MIN), I32(1), I32(i32::MAX)),
+		bin(Operator::I32Mul, I32(0x1_0000), I32(0x1_0000), I32(0)),
+		bin(Operator::I32Mul, I32(i32::MAX), I32(i32::MAX), I32(1)),
+		bin(
+			Operator::I32Mul,
+			I32(0x1234_5678),
+			I32(0x09AB_CDEF),
+			I32(-498_937_336),
+		),
+		bin(Operator::I32DivS, I32(i32::MIN), I32(-1), Trap(OVERFLOW)),
+		bin(Operator::I32DivS, I32(1), I32(0), Trap(DIVIDE)),
+		bin(Operator::I32DivS, I32(i32::MIN), I32(0), Trap(DIVIDE)),
+		bin(Operator::I32DivS, I32(-7), I32(2), I32(-3)),
+		bin(Operator::I32DivS, I32(7), I32(-2), I32(-3)),
+		bin(Operator::I32DivU, I32(-1), I32(2), I32(i32::MAX)),
+		bin(Operator::I32DivU, I32(i32::MIN), I32(-1), I32(0)),
+		bin(Operator::I32DivU, I32(1), I32(0), Trap(DIVIDE)),
+		bin(Operator::I32RemS, I32(i32::MIN), I32(-1), I32(0)),
+		bin(Operator::I32RemS, I32(-7), I32(2), I32(-1)),
+		bin(Operator::I32RemS, I32(7), I32(-2), I32(1)),
+		bin(Operator::I32RemS, I32(1), I32(0), Trap(DIVIDE)),
+		bin(Operator::I32RemU, I32(-2), I32(3), I32(2)),
+		bin(Operator::I32RemU, I32(i32::MIN), I32(-1), I32(i32::MIN)),
+		bin(Operator::I32RemU, I32(1), I32(0), Trap(DIVIDE)),
+		bin(
+			Operator::I32And,
+			I32(-1),
+			I32(0x0F0F_0F0F),
+			I32(0x0F0F_0F0F),
+		),
+		bin(Operator::I32Or, I32(i32::MIN), I32(1), I32(i32::MIN + 1)),
+		bin(Operator::I32Xor, I32(-1), I32(i32::MAX), I32(i32::MIN)),
+		bin(Operator::I32Shl, I32(1), I32(31), I32(i32::MIN)),
+		bin(Operator::I32Shl, I32(1), I32(32), I32(1)),
+		bin(Operator::I32Shl, I32(1), I32(33), I32(2)),
+		bin(Operator::I32Shl, I32(1), I32(-1), I32(i32::MIN)),
+		bin(Operator::I32ShrS, I32(i32::MIN), I32(31), I32(-1)),
+		bin(Operator::I32ShrS, I32(i32::MIN), I32(32), I32(i32::MIN)),
+		bin(Operator::I32ShrS, I32(-1), I32(33), I32(-1)),
+		bin(Operator::I32ShrU, I32(i32::MIN), I32(31), I32(1)),
+		bin(Operator::I32ShrU, I32(i32::MIN), I32(32), I32(i32::MIN)),
+		bin(Operator::I32ShrU, I32(-1), I32(33), I32(i32::MAX)),
+		bin(Operator::I32Rotl, I32(i32::MIN + 1), I32(1), I32(3)),
+		bin(
+			Operator::I32Rotl,
+			I32(0x1234_5678),
+			I32(36),
+			I32(0x2345_6781),
+		),
+		bin(Operator::I32Rotr, I32(1), I32(1), I32(i32::MIN)),
+		bin(
+			Operator::I32Rotr,
+			I32(0x1234_5678),
+			I32(36),
+			I32(0x8123_4567_u32 as i32),
+		),
+		un(Operator::I32Clz, I32(0), I32(32)),
+		un(Operator::I32Clz, I32(1), I32(31)),
+		un(Operator::I32Clz, I32(-1), I32(0)),
+		un(Operator::I32Ctz, I32(0), I32(32)),
+		un(Operator::I32Ctz, I32(i32::MIN), I32(31)),
+		un(Operator::I32Popcnt, I32(-1), I32(32)),
+		un(Operator::I32Popcnt, I32(0x5555_5555), I32(16)),
+		un(Operator::I32Extend8S, I32(0x80), I32(-128)),
+		un(Operator::I32Extend8S, I32(0x17F), I32(127)),
+		un(Operator::I32Extend16S, I32(0x8000), I32(-32768)),
+		un(Operator::I32Eqz, I32(0), I32(1)),
+		un(Operator::I32Eqz, I32(i32::MIN), I32(0)),
+		bin(Operator::I32Eq, I32(-1), I32(-1), I32(1)),
+		bin(Operator::I32Ne, I32(-1), I32(i32::MAX), I32(1)),
+		bin(Operator::I32LtS, I32(-1), I32(0), I32(1)),
+		bin(Operator::I32LtU, I32(-1), I32(0), I32(0)),
+		bin(Operator::I32GtS, I32(-1), I32(0), I32(0)),
+		bin(Operator::I32GtU, I32(-1), I32(0), I32(1)),
+		bin(Operator::I32LeS, I32(i32::MIN), I32(i32::MAX), I32(1)),
+		bin(Operator::I32LeU, I32(i32::MIN), I32(i32::MAX), I32(0)),
+		bin(Operator::I32GeS, I32(i32::MIN), I32(i32::MAX), I32(0)),
+		bin(Operator::I32GeU, I32(i32::MIN), I32(i32::MAX), I32(1)),
+	]
+}
+
+fn build_i64_list() -> Vec<Case> {
+	vec![
+		bin(Operator::I64Add, I64(i64::MAX), I64(1), I64(i64::MIN)),
+		bin(
+			Operator::I64Add,
+			I64(0xFFFF_FFFF),
+			I64(1),
+			I64(0x1_0000_0000),
+		),
+		bin(Operator::I64Sub, I64(i64::MIN), I64(1), I64(i64::MAX)),
+		bin(Operator::I64Sub, I64(0), I64(1), I64(-1)),
+		bin(
+			Operator::I64Mul,
+			I64(0x1_0000_0000),
+			I64(0x1_0000_0000),
+			I64(0),
+		),
+		bin(Operator::I64Mul, I64(i64::MAX), I64(i64::MAX), I64(1)),
+		bin(
+			Operator::I64Mul,
+			I64(0x0123_4567_89AB_CDEF),
+			I64(0x0FED_CBA9_8765_4321),
+			I64(2_459_930_256_624_457_935),
+		),
+		bin(Operator::I64DivS, I64(i64::MIN), I64(-1), Trap(OVERFLOW)),
+		bin(Operator::I64DivS, I64(1), I64(0), Trap(DIVIDE)),
+		bin(Operator::I64DivS, I64(i64::MIN), I64(0), Trap(DIVIDE)),
+		bin(Operator::I64DivS, I64(-7), I64(2), I64(-3)),
+		bin(Operator::I64DivS, I64(i64::MIN), I64(2), I64(i64::MIN / 2)),
+		bin(Operator::I64DivU, I64(-1), I64(2), I64(i64::MAX)),
+		bin(
+			Operator::I64DivU,
+			I64(-1),
+			I64(0xFFFF_FFFF),
+			I64(0x1_0000_0001),
+		),
+		bin(Operator::I64DivU, I64(i64::MIN), I64(-1), I64(0)),
+		bin(Operator::I64DivU, I64(1), I64(0), Trap(DIVIDE)),
+		bin(Operator::I64RemS, I64(i64::MIN), I64(-1), I64(0)),
+		bin(Operator::I64RemS, I64(-7), I64(2), I64(-1)),
+		bin(Operator::I64RemS, I64(1), I64(0), Trap(DIVIDE)),
+		bin(Operator::I64RemU, I64(-1), I64(10), I64(5)),
+		bin(Operator::I64RemU, I64(i64::MIN), I64(-1), I64(i64::MIN)),
+		bin(Operator::I64RemU, I64(1), I64(0), Trap(DIVIDE)),
+		bin(
+			Operator::I64And,
+			I64(-1),
+			I64(0x0F0F_0F0F_0F0F_0F0F),
+			I64(0x0F0F_0F0F_0F0F_0F0F),
+		),
+		bin(Operator::I64Or, I64(i64::MIN), I64(1), I64(i64::MIN + 1)),
+		bin(Operator::I64Xor, I64(-1), I64(i64::MAX), I64(i64::MIN)),
+		bin(Operator::I64Shl, I64(1), I64(32), I64(0x1_0000_0000)),
+		bin(Operator::I64Shl, I64(1), I64(63), I64(i64::MIN)),
+		bin(Operator::I64Shl, I64(1), I64(64), I64(1)),
+		bin(Operator::I64Shl, I64(1), I64(65), I64(2)),
+		bin(Operator::I64ShrS, I64(i64::MIN), I64(63), I64(-1)),
+		bin(Operator::I64ShrS, I64(i64::MIN), I64(64), I64(i64::MIN)),
+		bin(Operator::I64ShrS, I64(i64::MIN), I64(32), I64(-0x8000_0000)),
+		bin(Operator::I64ShrU, I64(i64::MIN), I64(63), I64(1)),
+		bin(Operator::I64ShrU, I64(-1), I64(32), I64(0xFFFF_FFFF)),
+		bin(Operator::I64ShrU, I64(-1), I64(65), I64(i64::MAX)),
+		bin(Operator::I64Rotl, I64(i64::MIN + 1), I64(1), I64(3)),
+		bin(Operator::I64Rotl, I64(1), I64(65), I64(2)),
+		bin(Operator::I64Rotr, I64(1), I64(1), I64(i64::MIN)),
+		bin(
+			Operator::I64Rotr,
+			I64(0x0123_4567_89AB_CDEF),
+			I64(68),
+			I64(-1_147_797_409_030_816_546),
+		),
+		un(Operator::I64Clz, I64(0), I64(64)),
+		un(Operator::I64Clz, I64(1), I64(63)),
+		un(Operator::I64Clz, I64(0x1_0000_0000), I64(31)),
+		un(Operator::I64Ctz, I64(0), I64(64)),
+		un(Operator::I64Ctz, I64(i64::MIN), I64(63)),
+		un(Operator::I64Ctz, I64(0x1_0000_0000), I64(32)),
+		un(Operator::I64Popcnt, I64(-1), I64(64)),
+		un(Operator::I64Popcnt, I64(0x5555_5555_5555_5555), I64(32)),
+		un(Operator::I64Extend8S, I64(0x80), I64(-128)),
+		un(Operator::I64Extend16S, I64(0x8000), I64(-32768)),
+		un(Operator::I64Extend32S, I64(0x8000_0000), I64(-0x8000_0000)),
+		un(Operator::I64Extend32S, I64(0x1_7FFF_FFFF), I64(0x7FFF_FFFF)),
+		un(Operator::I64Eqz, I64(0x1_0000_0000), I32(0)),
+		un(Operator::I64Eqz, I64(0), I32(1)),
+		bin(Operator::I64Eq, I64(0x1_0000_0000), I64(0), I32(0)),
+		bin(Operator::I64Ne, I64(0x1_0000_0000), I64(0), I32(1)),
+		bin(Operator::I64LtS, I64(-1), I64(0), I32(1)),
+		bin(
+			Operator::I64LtS,
+			I64(0x1_0000_0000),
+			I64(0xFFFF_FFFF),
+			I32(0),
+		),
+		bin(Operator::I64LtU, I64(-1), I64(0), I32(0)),
+		bin(
+			Operator::I64LtU,
+			I64(0xFFFF_FFFF),
+			I64(0x1_0000_0000),
+			I32(1),
+		),
+		bin(Operator::I64GtS, I64(i64::MIN), I64(i64::MAX), I32(0)),
+		bin(Operator::I64GtU, I64(i64::MIN), I64(i64::MAX), I32(1)),
+		bin(Operator::I64LeS, I64(-1), I64(-1), I32(1)),
+		bin(Operator::I64LeU, I64(-1), I64(0), I32(0)),
+		bin(Operator::I64GeS, I64(-1), I64(0), I32(0)),
+		bin(Operator::I64GeU, I64(-1), I64(0), I32(1)),
+		un(Operator::I32WrapI64, I64(0x1_0000_0001), I32(1)),
+		un(Operator::I32WrapI64, I64(0x8000_0000), I32(i32::MIN)),
+		un(Operator::I32WrapI64, I64(-1), I32(-1)),
+		un(Operator::I64ExtendI32S, I32(-1), I64(-1)),
+		un(Operator::I64ExtendI32U, I32(-1), I64(0xFFFF_FFFF)),
+	]
+}
+
+fn build_f32_list() -> Vec<Case> {
+	vec![
+		bin(
+			Operator::F32Add,
+			F32(0x4B80_0000),
+			F32(F32_ONE),
+			F32(0x4B80_0000),
+		),
+		bin(
+			Operator::F32Add,
+			F32(F32_ONE),
+			F32(0x3400_0000),
+			F32(0x3F80_0001),
+		),
+		bin(Operator::F32Add, F32(F32_INF), F32(F32_NEG_INF), NaN32),
+		bin(
+			Operator::F32Sub,
+			F32(F32_NEG_ZERO),
+			F32(0),
+			F32(F32_NEG_ZERO),
+		),
+		bin(
+			Operator::F32Mul,
+			F32(0x7F7F_FFFF),
+			F32(F32_TWO),
+			F32(F32_INF),
+		),
+		bin(Operator::F32Mul, F32(0), F32(F32_INF), NaN32),
+		bin(
+			Operator::F32Div,
+			F32(F32_ONE),
+			F32(0x4040_0000),
+			F32(0x3EAA_AAAB),
+		),
+		bin(Operator::F32Div, F32(F32_ONE), F32(0), F32(F32_INF)),
+		bin(Operator::F32Div, F32(F32_NEG_ONE), F32(0), F32(F32_NEG_INF)),
+		bin(Operator::F32Div, F32(0), F32(0), NaN32),
+		bin(Operator::F32Min, F32(F32_NAN), F32(F32_ONE), NaN32),
+		bin(Operator::F32Min, F32(F32_ONE), F32(F32_NAN), NaN32),
+		bin(
+			Operator::F32Min,
+			F32(0),
+			F32(F32_NEG_ZERO),
+			F32(F32_NEG_ZERO),
+		),
+		bin(
+			Operator::F32Min,
+			F32(F32_NEG_ZERO),
+			F32(0),
+			F32(F32_NEG_ZERO),
+		),
+		bin(Operator::F32Max, F32(F32_NAN), F32(F32_ONE), NaN32),
+		bin(Operator::F32Max, F32(F32_ONE), F32(F32_NAN), NaN32),
+		bin(Operator::F32Max, F32(0), F32(F32_NEG_ZERO), F32(0)),
+		bin(Operator::F32Max, F32(F32_NEG_ZERO), F32(0), F32(0)),
+		bin(
+			Operator::F32Copysign,
+			F32(F32_ONE),
+			F32(F32_NEG_ZERO),
+			F32(F32_NEG_ONE),
+		),
+		bin(
+			Operator::F32Copysign,
+			F32(F32_NEG_ONE),
+			F32(0),
+			F32(F32_ONE),
+		),
+		un(Operator::F32Abs, F32(F32_NEG_ZERO), F32(0)),
+		un(Operator::F32Abs, F32(F32_NEG_INF), F32(F32_INF)),
+		un(Operator::F32Neg, F32(0), F32(F32_NEG_ZERO)),
+		un(Operator::F32Ceil, F32(F32_NEG_HALF), F32(F32_NEG_ZERO)),
+		un(Operator::F32Floor, F32(F32_NEG_HALF), F32(F32_NEG_ONE)),
+		un(Operator::F32Trunc, F32(0xBFC0_0000), F32(F32_NEG_ONE)),
+		un(Operator::F32Trunc, F32(F32_NEG_HALF), F32(F32_NEG_ZERO)),
+		un(Operator::F32Nearest, F32(F32_HALF), F32(0)),
+		un(Operator::F32Nearest, F32(0x3FC0_0000), F32(F32_TWO)),
+		un(Operator::F32Nearest, F32(0x4020_0000), F32(F32_TWO)),
+		un(Operator::F32Nearest, F32(0xC020_0000), F32(0xC000_0000)),
+		un(Operator::F32Nearest, F32(F32_NEG_HALF), F32(F32_NEG_ZERO)),
+		un(Operator::F32Sqrt, F32(0x4080_0000), F32(F32_TWO)),
+		un(Operator::F32Sqrt, F32(F32_NEG_ONE), NaN32),
+		un(Operator::F32Sqrt, F32(F32_NEG_ZERO), F32(F32_NEG_ZERO)),
+		bin(Operator::F32Eq, F32(F32_NAN), F32(F32_NAN), I32(0)),
+		bin(Operator::F32Eq, F32(0), F32(F32_NEG_ZERO), I32(1)),
+		bin(Operator::F32Ne, F32(F32_NAN), F32(F32_NAN), I32(1)),
+		bin(Operator::F32Lt, F32(F32_NAN), F32(F32_ONE), I32(0)),
+		bin(Operator::F32Lt, F32(F32_NEG_ZERO), F32(0), I32(0)),
+		bin(Operator::F32Gt, F32(F32_NAN), F32(F32_ONE), I32(0)),
+		bin(Operator::F32Le, F32(F32_NEG_ZERO), F32(0), I32(1)),
+		bin(Operator::F32Ge, F32(F32_ONE), F32(F32_NAN), I32(0)),
+	]
+}
+
+fn build_f64_list() -> Vec<Case> {
+	vec![
+		bin(
+			Operator::F64Add,
+			F64(0x4340_0000_0000_0000),
+			F64(F64_ONE),
+			F64(0x4340_0000_0000_0000),
+		),
+		bin(Operator::F64Add, F64(F64_INF), F64(F64_NEG_INF), NaN64),
+		bin(
+			Operator::F64Sub,
+			F64(F64_NEG_ZERO),
+			F64(0),
+			F64(F64_NEG_ZERO),
+		),
+		bin(
+			Operator::F64Mul,
+			F64(0x7FEF_FFFF_FFFF_FFFF),
+			F64(F64_TWO),
+			F64(F64_INF),
+		),
+		bin(Operator::F64Mul, F64(0), F64(F64_INF), NaN64),
+		bin(
+			Operator::F64Div,
+			F64(F64_ONE),
+			F64(0x4008_0000_0000_0000),
+			F64(0x3FD5_5555_5555_5555),
+		),
+		bin(Operator::F64Div, F64(F64_ONE), F64(0), F64(F64_INF)),
+		bin(Operator::F64Div, F64(F64_NEG_ONE), F64(0), F64(F64_NEG_INF)),
+		bin(Operator::F64Div, F64(0), F64(0), NaN64),
+		bin(Operator::F64Min, F64(F64_NAN), F64(F64_ONE), NaN64),
+		bin(Operator::F64Min, F64(F64_ONE), F64(F64_NAN), NaN64),
+		bin(
+			Operator::F64Min,
+			F64(0),
+			F64(F64_NEG_ZERO),
+			F64(F64_NEG_ZERO),
+		),
+		bin(
+			Operator::F64Min,
+			F64(F64_NEG_ZERO),
+			F64(0),
+			F64(F64_NEG_ZERO),
+		),
+		bin(Operator::F64Max, F64(F64_NAN), F64(F64_ONE), NaN64),
+		bin(Operator::F64Max, F64(F64_ONE), F64(F64_NAN), NaN64),
+		bin(Operator::F64Max, F64(0), F64(F64_NEG_ZERO), F64(0)),
+		bin(Operator::F64Max, F64(F64_NEG_ZERO), F64(0), F64(0)),
+		bin(
+			Operator::F64Copysign,
+			F64(F64_ONE),
+			F64(F64_NEG_ZERO),
+			F64(F64_NEG_ONE),
+		),
+		bin(
+			Operator::F64Copysign,
+			F64(F64_NEG_ONE),
+			F64(0),
+			F64(F64_ONE),
+		),
+		un(Operator::F64Abs, F64(F64_NEG_ZERO), F64(0)),
+		un(Operator::F64Abs, F64(F64_NEG_INF), F64(F64_INF)),
+		un(Operator::F64Neg, F64(0), F64(F64_NEG_ZERO)),
+		un(Operator::F64Ceil, F64(F64_NEG_HALF), F64(F64_NEG_ZERO)),
+		un(Operator::F64Floor, F64(F64_NEG_HALF), F64(F64_NEG_ONE)),
+		un(
+			Operator::F64Trunc,
+			F64(0xBFF8_0000_0000_0000),
+			F64(F64_NEG_ONE),
+		),
+		un(Operator::F64Trunc, F64(F64_NEG_HALF), F64(F64_NEG_ZERO)),
+		un(Operator::F64Nearest, F64(F64_HALF), F64(0)),
+		un(
+			Operator::F64Nearest,
+			F64(0x3FF8_0000_0000_0000),
+			F64(F64_TWO),
+		),
+		un(
+			Operator::F64Nearest,
+			F64(0x4004_0000_0000_0000),
+			F64(F64_TWO),
+		),
+		un(
+			Operator::F64Nearest,
+			F64(0xC004_0000_0000_0000),
+			F64(0xC000_0000_0000_0000),
+		),
+		un(Operator::F64Nearest, F64(F64_NEG_HALF), F64(F64_NEG_ZERO)),
+		un(Operator::F64Sqrt, F64(0x4010_0000_0000_0000), F64(F64_TWO)),
+		un(Operator::F64Sqrt, F64(F64_NEG_ONE), NaN64),
+		un(Operator::F64Sqrt, F64(F64_NEG_ZERO), F64(F64_NEG_ZERO)),
+		bin(Operator::F64Eq, F64(F64_NAN), F64(F64_NAN), I32(0)),
+		bin(Operator::F64Eq, F64(0), F64(F64_NEG_ZERO), I32(1)),
+		bin(Operator::F64Ne, F64(F64_NAN), F64(F64_NAN), I32(1)),
+		bin(Operator::F64Lt, F64(F64_NAN), F64(F64_ONE), I32(0)),
+		bin(Operator::F64Lt, F64(F64_NEG_ZERO), F64(0), I32(0)),
+		bin(Operator::F64Gt, F64(F64_NAN), F64(F64_ONE), I32(0)),
+		bin(Operator::F64Le, F64(F64_NEG_ZERO), F64(0), I32(1)),
+		bin(Operator::F64Ge, F64(F64_ONE), F64(F64_NAN), I32(0)),
+	]
+}
+
+fn build_conversion_list() -> Vec<Case> {
+	vec![
+		un(Operator::I32TruncF32S, F32(F32_NAN), Trap(CONVERSION)),
+		un(Operator::I32TruncF32S, F32(0x4F00_0000), Trap(CONVERSION)),
+		un(Operator::I32TruncF32S, F32(0xCF00_0000), I32(i32::MIN)),
+		un(Operator::I32TruncF32S, F32(0xBFC0_0000), I32(-1)),
+		un(Operator::I32TruncF32U, F32(F32_NEG_ONE), Trap(CONVERSION)),
+		un(Operator::I32TruncF32U, F32(0xBF66_6666), I32(0)),
+		un(Operator::I32TruncF32U, F32(0x4F7F_FFFF), I32(-256)),
+		un(Operator::I32TruncF32U, F32(0x4F80_0000), Trap(CONVERSION)),
+		un(
+			Operator::I32TruncF64S,
+			F64(0x41DF_FFFF_FFF9_999A),
+			I32(i32::MAX),
+		),
+		un(
+			Operator::I32TruncF64S,
+			F64(0xC1E0_0000_001C_CCCD),
+			I32(i32::MIN),
+		),
+		un(
+			Operator::I32TruncF64S,
+			F64(0x41E0_0000_0000_0000),
+			Trap(CONVERSION),
+		),
+		un(Operator::I32TruncF64S, F64(F64_INF), Trap(CONVERSION)),
+		un(Operator::I32TruncF64U, F64(0x41EF_FFFF_FFFC_CCCD), I32(-1)),
+		un(
+			Operator::I32TruncF64U,
+			F64(0x41F0_0000_0000_0000),
+			Trap(CONVERSION),
+		),
+		un(Operator::I32TruncF64U, F64(F64_NAN), Trap(CONVERSION)),
+		un(Operator::I64TruncF32S, F32(0x5F00_0000), Trap(CONVERSION)),
+		un(Operator::I64TruncF32S, F32(0xDF00_0000), I64(i64::MIN)),
+		un(
+			Operator::I64TruncF32U,
+			F32(0x5F7F_FFFF),
+			I64(-1_099_511_627_776),
+		),
+		un(Operator::I64TruncF32U, F32(F32_NEG_ONE), Trap(CONVERSION)),
+		un(
+			Operator::I64TruncF64S,
+			F64(0x43E0_0000_0000_0000),
+			Trap(CONVERSION),
+		),
+		un(
+			Operator::I64TruncF64S,
+			F64(0xC3E0_0000_0000_0000),
+			I64(i64::MIN),
+		),
+		un(Operator::I64TruncF64S, F64(F64_NEG_HALF), I64(0)),
+		un(
+			Operator::I64TruncF64U,
+			F64(0x43EF_FFFF_FFFF_FFFF),
+			I64(-2048),
+		),
+		un(
+			Operator::I64TruncF64U,
+			F64(0x43F0_0000_0000_0000),
+			Trap(CONVERSION),
+		),
+		un(Operator::I64TruncF64U, F64(F64_NEG_ONE), Trap(CONVERSION)),
+		un(Operator::I32TruncSatF32S, F32(F32_NAN), I32(0)),
+		un(Operator::I32TruncSatF32S, F32(F32_INF), I32(i32::MAX)),
+		un(Operator::I32TruncSatF32S, F32(F32_NEG_INF), I32(i32::MIN)),
+		un(Operator::I32TruncSatF32U, F32(F32_NEG_ONE), I32(0)),
+		un(Operator::I32TruncSatF32U, F32(F32_INF), I32(-1)),
+		un(
+			Operator::I32TruncSatF64S,
+			F64(0x41E0_0000_0000_0000),
+			I32(i32::MAX),
+		),
+		un(
+			Operator::I32TruncSatF64U,
+			F64(0x41F0_0000_0000_0000),
+			I32(-1),
+		),
+		un(Operator::I32TruncSatF64U, F64(F64_NAN), I32(0)),
+		un(Operator::I64TruncSatF32S, F32(0x5F00_0000), I64(i64::MAX)),
+		un(Operator::I64TruncSatF32U, F32(F32_NEG_INF), I64(0)),
+		un(Operator::I64TruncSatF64S, F64(F64_NAN), I64(0)),
+		un(
+			Operator::I64TruncSatF64S,
+			F64(0x43E0_0000_0000_0000),
+			I64(i64::MAX),
+		),
+		un(Operator::I64TruncSatF64S, F64(F64_NEG_INF), I64(i64::MIN)),
+		un(
+			Operator::I64TruncSatF64U,
+			F64(0x43F0_0000_0000_0000),
+			I64(-1),
+		),
+		un(Operator::I64TruncSatF64U, F64(F64_NEG_ONE), I64(0)),
+		un(Operator::F32ConvertI32S, I32(i32::MAX), F32(0x4F00_0000)),
+		un(Operator::F32ConvertI32S, I32(16_777_217), F32(0x4B80_0000)),
+		un(Operator::F32ConvertI32U, I32(-1), F32(0x4F80_0000)),
+		un(Operator::F32ConvertI64S, I64(i64::MAX), F32(0x5F00_0000)),
+		un(Operator::F32ConvertI64S, I64(-1), F32(F32_NEG_ONE)),
+		un(Operator::F32ConvertI64U, I64(-1), F32(0x5F80_0000)),
+		un(
+			Operator::F64ConvertI32S,
+			I32(i32::MIN),
+			F64(0xC1E0_0000_0000_0000),
+		),
+		un(
+			Operator::F64ConvertI32U,
+			I32(-1),
+			F64(0x41EF_FFFF_FFE0_0000),
+		),
+		un(
+			Operator::F64ConvertI64S,
+			I64(i64::MAX),
+			F64(0x43E0_0000_0000_0000),
+		),
+		un(
+			Operator::F64ConvertI64S,
+			I64(9_007_199_254_740_993),
+			F64(0x4340_0000_0000_0000),
+		),
+		un(
+			Operator::F64ConvertI64U,
+			I64(-1),
+			F64(0x43F0_0000_0000_0000),
+		),
+		un(
+			Operator::F32DemoteF64,
+			F64(0x3FF0_0000_0100_0000),
+			F32(F32_ONE),
+		),
+		un(
+			Operator::F32DemoteF64,
+			F64(0x7E37_E43C_8800_759C),
+			F32(F32_INF),
+		),
+		un(Operator::F32DemoteF64, F64(F64_NAN), NaN32),
+		un(
+			Operator::F64PromoteF32,
+			F32(0x3EAA_AAAB),
+			F64(0x3FD5_5555_6000_0000),
+		),
+		un(
+			Operator::F64PromoteF32,
+			F32(F32_NEG_ZERO),
+			F64(F64_NEG_ZERO),
+		),
+		un(
+			Operator::I32ReinterpretF32,
+			F32(F32_NEG_ZERO),
+			I32(i32::MIN),
+		),
+		un(
+			Operator::I64ReinterpretF64,
+			F64(F64_NEG_ZERO),
+			I64(i64::MIN),
+		),
+	]
+}
+
+fn build_case_list() -> Vec<Case> {
+	let mut case_list = build_i32_list();
+
+	case_list.extend(build_i64_list());
+	case_list.extend(build_f32_list());
+	case_list.extend(build_f64_list());
+	case_list.extend(build_conversion_list());
+	case_list
+}
+
+// The driver is a function of its own so that its locals are not counted
+// against the top level of runtimes made of locals
+fn write_driver(backend: &dyn Backend, w: &mut dyn Write) -> Result<()> {
+	writeln!(w, "local function run_case_list()")?;
+	write!(w, "\tlocal trap = ")?;
+	backend.write_trap_table(w)?;
+	writeln!(w)?;
+	writeln!(w, "\tlocal failed = 0")?;
+	writeln!(w, "\ttrap.configure(\"value\")")?;
+	writeln!(w, "\tfor _, case in ipairs(CASE_LIST) do")?;
+	writeln!(w, "\t\tlocal success, result = pcall(case.func)")?;
+	writeln!(w, "\t\tlocal passed")?;
+	writeln!(w, "\t\tif case.trap then")?;
+	writeln!(
+		w,
+		"\t\t\tpassed = not success and type(result) == \"table\" and result.kind == case.trap"
+	)?;
+	writeln!(w, "\t\telse")?;
+	writeln!(w, "\t\t\tpassed = success and result == 1")?;
+	writeln!(w, "\t\tend")?;
+	writeln!(w, "\t\tif not passed then")?;
+	writeln!(w, "\t\t\tfailed = failed + 1")?;
+	writeln!(w, "\t\t\tif success then")?;
+	writeln!(w, "\t\t\t\tprint(\"failed: \" .. case.name)")?;
+	writeln!(w, "\t\t\telse")?;
+	writeln!(
+		w,
+		"\t\t\t\tprint(\"failed: \" .. case.name .. \", raised \" .. tostring(result))"
+	)?;
+	writeln!(w, "\t\t\tend")?;
+	writeln!(w, "\t\tend")?;
+	writeln!(w, "\tend")?;
+	writeln!(w, "\ttrap.configure(nil)")?;
+	writeln!(
+		w,
+		"\tprint((#CASE_LIST - failed) .. \" of \" .. #CASE_LIST .. \" checks passed\")"
+	)?;
+	writeln!(w, "\tif failed ~= 0 then")?;
+	writeln!(w, "\t\terror(failed .. \" checks failed\", 0)")?;
+	writeln!(w, "\tend")?;
+	writeln!(w, "end")?;
+	writeln!(w, "run_case_list()")
+}
+
+/// Writes a script that checks the runtime operations of a backend against
+/// edge cases where hosts are known to differ, such as dividing the least
+/// integer by -1, the minimum of a NaN, and shifting by 32 or more. Each case
+/// is translated as the function it would be in a module, and the script
+/// prints those that fail before raising an error naming how many did. It
+/// expects the runtime to be written before it.
+///
+/// # Errors
+/// Returns `Err` if writing to `Write` failed.
+pub fn write_self_test(backend: &dyn Backend, w: &mut dyn Write) -> Result<()> {
+	let wasm = Module::try_from_data(EMPTY_MODULE).unwrap();
+	let type_info = TypeInfo::from_module(&wasm);
+	let mut builder = Factory::from_type_info(&type_info);
+
+	let case_list = build_case_list();
+	let func_list: Vec<_> = case_list
+		.iter()
+		.map(|v| builder.create_anonymous(&v.build_code()))
+		.collect();
+
+	let mut loc_set = BTreeSet::new();
+
+	for func in &func_list {
+		loc_set.extend(localize(backend, func).0);
+	}
+
+	for loc in loc_set {
+		backend.write_local_operation(loc.0, loc.1, w)?;
+	}
+
+	writeln!(w, "local CASE_LIST = {{}}")?;
+
+	for (i, (case, func)) in case_list.iter().zip(&func_list).enumerate() {
+		write!(w, "CASE_LIST[{}] = {{ name = ", i + 1)?;
+		backend.write_string(case.build_name().as_bytes(), w)?;
+
+		if let Trap(kind) = case.expect {
+			write!(w, ", trap = \"{kind}\"")?;
+		}
+
+		write!(w, ", func = ")?;
+		backend.write_function(func, &Options::default(), w)?;
+		writeln!(w, "}}")?;
+	}
+
+	write_driver(backend, w)
+}
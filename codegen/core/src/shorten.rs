@@ -0,0 +1,273 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::minify::{get_bracket_len, get_bracket_level, get_line_len, get_quoted_len, is_word};
+
+const KEYWORD_LIST: [&[u8]; 23] = [
+	b"and",
+	b"break",
+	b"continue",
+	b"do",
+	b"else",
+	b"elseif",
+	b"end",
+	b"false",
+	b"for",
+	b"function",
+	b"goto",
+	b"if",
+	b"in",
+	b"local",
+	b"nil",
+	b"not",
+	b"or",
+	b"repeat",
+	b"return",
+	b"then",
+	b"true",
+	b"until",
+	b"while",
+];
+
+const SYMBOL_LIST: [&[u8]; 8] = [b"...", b"..", b"==", b"~=", b"<=", b">=", b"::", b"//"];
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Kind {
+	Blank,
+	Name,
+	Literal,
+	Symbol,
+}
+
+struct Token<'a> {
+	kind: Kind,
+	data: &'a [u8],
+	is_line_start: bool,
+	is_variable: bool,
+}
+
+fn get_token_len(data: &[u8]) -> (Kind, usize) {
+	match data[0] {
+		b' ' | b'\t' | b'\r' | b'\n' => (Kind::Blank, 1),
+		b'-' if data.starts_with(b"--") => {
+			let len = get_bracket_level(&data[2..]).map_or_else(
+				|| get_line_len(data),
+				|v| get_bracket_len(&data[2..], v) + 2,
+			);
+
+			(Kind::Blank, len)
+		}
+		b'"' | b'\'' => (Kind::Literal, get_quoted_len(data)),
+		b'[' if get_bracket_level(data).is_some() => {
+			let level = get_bracket_level(data).unwrap();
+
+			(Kind::Literal, get_bracket_len(data, level))
+		}
+		byte if is_word(byte) => {
+			let len = data.iter().take_while(|&&v| is_word(v)).count();
+			let kind = if byte.is_ascii_digit() {
+				Kind::Literal
+			} else {
+				Kind::Name
+			};
+
+			(kind, len)
+		}
+		_ => {
+			let len = SYMBOL_LIST
+				.iter()
+				.find(|v| data.starts_with(v))
+				.map_or(1, |v| v.len());
+
+			(Kind::Symbol, len)
+		}
+	}
+}
+
+// Names after a `.` or `:` are fields and names before the `=` of a table
+// constructor are keys, neither of which refer to a variable
+fn mark_variables(list: &mut [Token]) {
+	let mut bracket_list = Vec::new();
+	let mut last: Option<&[u8]> = None;
+	let index_list: Vec<_> = (0..list.len())
+		.filter(|&i| list[i].kind != Kind::Blank)
+		.collect();
+
+	for (i, &index) in index_list.iter().enumerate() {
+		let token = &list[index];
+
+		match token.data {
+			b"(" | b"[" | b"{" => bracket_list.push(token.data[0]),
+			b")" | b"]" | b"}" => {
+				bracket_list.pop();
+			}
+			_ => {}
+		}
+
+		let is_field = matches!(last, Some(b"." | b":"));
+		let is_key = bracket_list.last() == Some(&b'{')
+			&& index_list.get(i + 1).is_some_and(|&v| list[v].data == b"=");
+
+		last = Some(token.data);
+		list[index].is_variable = token.kind == Kind::Name && !is_field && !is_key;
+	}
+}
+
+fn load_token_list(source: &[u8]) -> Vec<Token<'_>> {
+	let mut list = Vec::new();
+	let mut i = 0;
+
+	while i < source.len() {
+		let (kind, len) = get_token_len(&source[i..]);
+
+		list.push(Token {
+			kind,
+			data: &source[i..i + len],
+			is_line_start: i == 0 || source[i - 1] == b'\n',
+			is_variable: false,
+		});
+
+		i += len;
+	}
+
+	mark_variables(&mut list);
+
+	list
+}
+
+fn is_numbered(name: &[u8], prefix: &[u8]) -> bool {
+	name.strip_prefix(prefix)
+		.is_some_and(|v| !v.is_empty() && v.iter().all(u8::is_ascii_digit))
+}
+
+// Returns the names declared by a `local` that starts a line, which are the
+// ones at the top level of the generated code
+fn load_top_level_names<'a>(list: &[Token<'a>], result: &mut BTreeSet<&'a [u8]>) {
+	let mut iter = list
+		.iter()
+		.filter(|v| v.kind != Kind::Blank)
+		.map(|v| (v.data, v.is_line_start))
+		.peekable();
+
+	while let Some((data, is_line_start)) = iter.next() {
+		if data != b"local" || !is_line_start {
+			continue;
+		}
+
+		let name = match iter.next() {
+			Some((b"function", _)) => iter.next(),
+			name => name,
+		};
+
+		// Teal declarations such as `local record Exports` are types
+		if let Some((name, _)) = name {
+			let is_type = matches!(name, b"record" | b"enum" | b"type" | b"interface")
+				&& iter.peek().is_some_and(|v| is_word(v.0[0]));
+
+			if !is_type {
+				result.insert(name);
+			}
+		}
+	}
+}
+
+struct NameGenerator<'a> {
+	used_set: BTreeSet<&'a [u8]>,
+	index: usize,
+}
+
+impl NameGenerator<'_> {
+	fn to_name(mut index: usize) -> Vec<u8> {
+		const START: &[u8] = b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ_";
+		const REST: &[u8] = b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ_0123456789";
+
+		let mut name = vec![START[index % START.len()]];
+
+		index /= START.len();
+
+		while index != 0 {
+			index -= 1;
+			name.push(REST[index % REST.len()]);
+			index /= REST.len();
+		}
+
+		name
+	}
+
+	fn next_name(&mut self) -> Vec<u8> {
+		loop {
+			let name = Self::to_name(self.index);
+
+			self.index += 1;
+
+			if !self.used_set.contains(&name[..]) {
+				return name;
+			}
+		}
+	}
+}
+
+/// Renames the locals, registers, module level arrays, and runtime functions
+/// of a translation to the shortest names not already in use, for targets
+/// that limit the size of a script. The `runtime` and `module` are returned
+/// joined, as the names they share must be renamed together. Exports are
+/// keyed by strings and keep their names.
+#[must_use]
+pub fn shorten(runtime: &[u8], module: &[u8]) -> Vec<u8> {
+	let runtime_list = load_token_list(runtime);
+	let module_list = load_token_list(module);
+	let mut name_set = BTreeSet::new();
+
+	load_top_level_names(&runtime_list, &mut name_set);
+	name_set.retain(|v| v.starts_with(b"rt_"));
+	load_top_level_names(&module_list, &mut name_set);
+
+	name_set.extend(
+		module_list
+			.iter()
+			.filter(|v| {
+				v.is_variable && (is_numbered(v.data, b"loc_") || is_numbered(v.data, b"reg_"))
+			})
+			.map(|v| v.data),
+	);
+
+	let mut count_map: BTreeMap<&[u8], usize> = BTreeMap::new();
+
+	for token in runtime_list.iter().chain(&module_list) {
+		if token.is_variable && name_set.contains(token.data) {
+			*count_map.entry(token.data).or_default() += 1;
+		}
+	}
+
+	let mut count_list: Vec<_> = count_map.into_iter().collect();
+
+	count_list.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+
+	let mut generator = NameGenerator {
+		used_set: runtime_list
+			.iter()
+			.chain(&module_list)
+			.filter(|v| v.kind == Kind::Name)
+			.map(|v| v.data)
+			.chain(KEYWORD_LIST)
+			.collect(),
+		index: 0,
+	};
+
+	let rename_map: BTreeMap<_, _> = count_list
+		.into_iter()
+		.map(|(name, _)| (name, generator.next_name()))
+		.collect();
+
+	let mut result = Vec::with_capacity(runtime.len() + module.len());
+
+	for token in runtime_list.iter().chain(&module_list) {
+		let data = rename_map
+			.get(token.data)
+			.filter(|_| token.is_variable)
+			.map_or(token.data, Vec::as_slice);
+
+		result.extend_from_slice(data);
+	}
+
+	result
+}
@@ -0,0 +1,111 @@
+use std::io::{Result, Write};
+
+// Marks are written on a line of their own and start with a byte that
+// generated code never contains, as strings escape it
+const MARK: u8 = 0;
+
+/// Ties a line of the generated code back to the instruction it came from.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Mapping {
+	/// The 1 based line in the generated code.
+	pub line: usize,
+	/// The index of the function in the function space of the module.
+	pub function: usize,
+	/// The offset of the instruction in the module.
+	pub offset: usize,
+}
+
+/// Writes a mark naming the instruction `offset` that the next line of a
+/// function comes from. Marks are stripped out by the driver, which records
+/// the line they stood before.
+///
+/// # Errors
+/// Returns `Err` if writing to `Write` failed.
+pub fn write_mark(offset: usize, w: &mut dyn Write) -> Result<()> {
+	w.write_all(&[MARK])?;
+	writeln!(w, "{offset}")
+}
+
+pub(crate) struct LineCounter<'a> {
+	inner: &'a mut dyn Write,
+	line: usize,
+}
+
+impl<'a> LineCounter<'a> {
+	pub(crate) fn new(inner: &'a mut dyn Write) -> Self {
+		Self { inner, line: 1 }
+	}
+
+	pub(crate) const fn line(&self) -> usize {
+		self.line
+	}
+
+	// Writes a function with its marks removed, recording each as a mapping
+	pub(crate) fn write_unmarked(
+		&mut self,
+		data: &[u8],
+		function: usize,
+		mapping_list: &mut Vec<Mapping>,
+	) -> Result<()> {
+		for line in data.split_inclusive(|&v| v == b'\n') {
+			let Some(rest) = line.strip_prefix(&[MARK]) else {
+				self.write_all(line)?;
+
+				continue;
+			};
+
+			let offset = String::from_utf8_lossy(rest).trim_end().parse().unwrap();
+
+			mapping_list.push(Mapping {
+				line: self.line,
+				function,
+				offset,
+			});
+		}
+
+		Ok(())
+	}
+}
+
+impl Write for LineCounter<'_> {
+	fn write(&mut self, buf: &[u8]) -> Result<usize> {
+		let len = self.inner.write(buf)?;
+
+		self.line += buf[..len].iter().filter(|&&v| v == b'\n').count();
+
+		Ok(len)
+	}
+
+	fn flush(&mut self) -> Result<()> {
+		self.inner.flush()
+	}
+}
+
+/// Writes the mappings as a JSON document, with the lines moved down by
+/// `line_offset` to account for anything written before the module.
+///
+/// # Errors
+/// Returns `Err` if writing to `Write` failed.
+pub fn write_source_map(
+	mapping_list: &[Mapping],
+	line_offset: usize,
+	w: &mut dyn Write,
+) -> Result<()> {
+	writeln!(w, "{{")?;
+	writeln!(w, "\t\"mappings\": [")?;
+
+	for (i, v) in mapping_list.iter().enumerate() {
+		let separator = if i + 1 == mapping_list.len() { "" } else { "," };
+
+		writeln!(
+			w,
+			"\t\t{{ \"line\": {}, \"function\": {}, \"offset\": {} }}{separator}",
+			v.line + line_offset,
+			v.function,
+			v.offset
+		)?;
+	}
+
+	writeln!(w, "\t]")?;
+	writeln!(w, "}}")
+}
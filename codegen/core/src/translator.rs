@@ -0,0 +1,1492 @@
+use std::{
+	collections::BTreeSet,
+	io::{Result, Write},
+};
+
+use wasm_ast::{
+	factory::Factory,
+	module::{External, Module, TypeInfo},
+	node::{FuncData, Statement},
+	optimize::{devirtualize, fold_offsets, hoist, inline, optimize},
+	shake::Reachable,
+};
+use wasmparser::{
+	ConstExpr, CustomSectionReader, Data, DataKind, Element, ElementItems, ElementKind, Export,
+	Import, Operator, OperatorsReader,
+};
+
+use crate::{
+	backend::Backend,
+	coverage::count_blocks,
+	jump_table::{find_jump_table_list, write_jump_table_list},
+	localize::localize,
+	options::Options,
+	runtime::build_runtime_name_list,
+	source_map::{LineCounter, Mapping},
+};
+
+// LuaJIT and Lua 5.1 refuse to load a function that captures more upvalues
+const UPVALUE_LIMIT: usize = 60;
+
+const KEYWORD_LIST: [&[u8]; 21] = [
+	b"and",
+	b"break",
+	b"do",
+	b"else",
+	b"elseif",
+	b"end",
+	b"false",
+	b"for",
+	b"function",
+	b"if",
+	b"in",
+	b"local",
+	b"nil",
+	b"not",
+	b"or",
+	b"repeat",
+	b"return",
+	b"then",
+	b"true",
+	b"until",
+	b"while",
+];
+
+// Functions declare their own locals, registers, and cached memories
+const LOCAL_PREFIX_LIST: [&[u8]; 3] = [b"loc_", b"reg_", b"memory_at_"];
+
+/// How [`from_module_split`] lays out the chunks that functions are moved to.
+pub struct Split<'a> {
+	/// The most bytes of functions a chunk holds, though a function larger
+	/// than this still gets a chunk of its own.
+	pub limit: usize,
+	/// The names declared before the module, such as the top level locals of
+	/// the runtime, which the chunks can bind.
+	pub name_list: BTreeSet<&'a str>,
+	/// Returns the expression that loads the chunk at an index.
+	pub import: &'a dyn Fn(usize) -> String,
+}
+
+trait AsIEName {
+	fn as_ie_name(&self) -> &str;
+}
+
+impl AsIEName for External {
+	fn as_ie_name(&self) -> &str {
+		match self {
+			Self::Func => "func_list",
+			Self::Table => "table_list",
+			Self::Memory => "memory_list",
+			Self::Global => "global_list",
+			Self::Tag => "tag_list",
+		}
+	}
+}
+
+fn reader_to_code(reader: OperatorsReader) -> Vec<Operator> {
+	let parsed: std::result::Result<_, _> = reader.into_iter().collect();
+
+	parsed.unwrap()
+}
+
+fn write_constant(
+	backend: &dyn Backend,
+	init: &ConstExpr,
+	type_info: &TypeInfo,
+	w: &mut dyn Write,
+) -> Result<()> {
+	let func = create_constant(init, type_info);
+
+	if let Some(Statement::SetTemporary(stat)) = func.code().code().last() {
+		backend.write_expression(stat.value(), w)
+	} else {
+		writeln!(w, r#"error("Valueless constant")"#)
+	}
+}
+
+const WASI_NAME: &str = "wasi_snapshot_preview1";
+
+fn write_import_of(
+	list: &[Import],
+	wanted: External,
+	has_wasi: bool,
+	w: &mut dyn Write,
+) -> Result<()> {
+	let lower = wanted.as_ie_name();
+	let upper = lower.to_uppercase();
+
+	for (i, Import { name, module, .. }) in list
+		.iter()
+		.filter(|v| External::from(v.ty) == wanted)
+		.enumerate()
+	{
+		write!(w, "\t")?;
+
+		if has_wasi && *module == WASI_NAME {
+			writeln!(w, r#"{upper}[{i}] = WASI.{lower}["{name}"]"#)?;
+		} else {
+			writeln!(w, r#"{upper}[{i}] = wasm["{module}"].{lower}["{name}"]"#)?;
+		}
+	}
+
+	Ok(())
+}
+
+fn write_export_of(
+	list: &[Export],
+	wanted: External,
+	is_guarded: bool,
+	w: &mut dyn Write,
+) -> Result<()> {
+	let lower = wanted.as_ie_name();
+	let upper = lower.to_uppercase();
+
+	writeln!(w, "\t\t{lower} = {{")?;
+
+	for Export { name, index, .. } in list.iter().filter(|v| External::from(v.kind) == wanted) {
+		write!(w, "\t\t\t")?;
+
+		if is_guarded {
+			writeln!(w, r#"["{name}"] = guard_depth({upper}[{index}]),"#)?;
+		} else {
+			writeln!(w, r#"["{name}"] = {upper}[{index}],"#)?;
+		}
+	}
+
+	writeln!(w, "\t\t}},")
+}
+
+// A reloaded module takes over the memories, tables, and globals that the
+// `previous` instance exported under the same name, once its own init code
+// has run so that the segments do not write over them
+fn write_adopt_list(wasm: &Module, w: &mut dyn Write) -> Result<()> {
+	let adopt_list: Vec<_> = wasm
+		.export_section()
+		.iter()
+		.filter_map(|v| {
+			let wanted = External::from(v.kind);
+			let index = usize::try_from(v.index).unwrap();
+
+			let is_adopted = matches!(
+				wanted,
+				External::Table | External::Memory | External::Global
+			) && index >= wasm.import_count(wanted);
+
+			is_adopted.then_some((wanted, v.name, index))
+		})
+		.collect();
+
+	if adopt_list.is_empty() {
+		return Ok(());
+	}
+
+	writeln!(w, "	if previous then")?;
+
+	for (wanted, name, index) in adopt_list {
+		let lower = wanted.as_ie_name();
+		let upper = lower.to_uppercase();
+
+		writeln!(
+			w,
+			r#"		{upper}[{index}] = previous.{lower}["{name}"] or {upper}[{index}]"#
+		)?;
+	}
+
+	writeln!(w, "	end")
+}
+
+// Functions imported from WASI are bound to `rt.wasi` unless the host
+// passed its own, and it reaches the module through the first memory
+fn write_wasi_binding(backend: &dyn Backend, wasm: &Module, w: &mut dyn Write) -> Result<bool> {
+	let has_wasi = backend.has_wasi()
+		&& wasm
+			.import_section()
+			.iter()
+			.any(|v| v.module == WASI_NAME && External::from(v.ty) == External::Func);
+
+	if !has_wasi {
+		return Ok(false);
+	}
+
+	let memory = if wasm.memory_space() == 0 {
+		"nil"
+	} else {
+		"MEMORY_LIST[0]"
+	};
+
+	write!(w, "\tlocal WASI = ")?;
+	backend.write_runtime_name("wasi", "bind", w)?;
+	writeln!(w, r#"(wasm["{WASI_NAME}"], function()"#)?;
+	writeln!(w, "\t\treturn {memory}")?;
+	writeln!(w, "\tend)")?;
+
+	Ok(true)
+}
+
+fn write_import_list(backend: &dyn Backend, wasm: &Module, w: &mut dyn Write) -> Result<()> {
+	let list = wasm.import_section();
+	let has_wasi = write_wasi_binding(backend, wasm, w)?;
+
+	write_import_of(list, External::Func, has_wasi, w)?;
+	write_import_of(list, External::Table, false, w)?;
+	write_import_of(list, External::Memory, false, w)?;
+	write_import_of(list, External::Global, false, w)?;
+	write_import_of(list, External::Tag, false, w)
+}
+
+// Exported functions are guarded when depth limited, as the host may catch a
+// trap that left calls counted in `DEPTH`
+fn write_export_list(
+	backend: &dyn Backend,
+	list: &[Export],
+	options: &Options,
+	w: &mut dyn Write,
+) -> Result<()> {
+	let is_guarded = options.max_depth.is_some();
+
+	backend.write_export_runtime(w)?;
+	write_export_of(list, External::Func, is_guarded, w)?;
+	write_export_of(list, External::Table, false, w)?;
+	write_export_of(list, External::Memory, false, w)?;
+	write_export_of(list, External::Global, false, w)?;
+	write_export_of(list, External::Tag, false, w)
+}
+
+fn write_custom_list(
+	backend: &dyn Backend,
+	list: &[CustomSectionReader],
+	name_list: &[&str],
+	w: &mut dyn Write,
+) -> Result<()> {
+	if name_list.is_empty() {
+		return Ok(());
+	}
+
+	writeln!(w, "\t\tcustom_list = {{")?;
+
+	for custom in list.iter().filter(|v| name_list.contains(&v.name())) {
+		let name = custom.name();
+
+		write!(w, "\t\t\t")?;
+		write!(w, r#"["{name}"] = "#)?;
+		backend.write_string(custom.data(), w)?;
+		writeln!(w, ",")?;
+	}
+
+	writeln!(w, "\t\t}},")
+}
+
+fn write_table_list(wasm: &Module, w: &mut dyn Write) -> Result<()> {
+	let offset = wasm.import_count(External::Table);
+	let table = wasm.table_section();
+
+	for (i, table) in table.iter().enumerate() {
+		let index = offset + i;
+		let min = table.ty.initial;
+		let max = table.ty.maximum.unwrap_or(0xFFFF);
+
+		writeln!(
+			w,
+			"\tTABLE_LIST[{index}] = {{ min = {min}, max = {max}, data = {{}} }}"
+		)?;
+	}
+
+	Ok(())
+}
+
+// Memories indexed by `i64` may span the whole of it, which is `2^48` pages
+// of 64 KiB, and still fits in a double exactly
+const MAX_PAGE_COUNT_64: u64 = 1 << 48;
+
+fn write_memory_list(backend: &dyn Backend, wasm: &Module, w: &mut dyn Write) -> Result<()> {
+	let offset = wasm.import_count(External::Memory);
+	let memory = wasm.memory_section();
+
+	for (i, ty) in memory.iter().enumerate() {
+		let index = offset + i;
+		let min = ty.initial;
+		let max = ty.maximum.unwrap_or(if ty.memory64 {
+			MAX_PAGE_COUNT_64
+		} else {
+			0xFFFF
+		});
+
+		write!(w, "\tMEMORY_LIST[{index}] = ")?;
+		backend.write_runtime_name("allocator", "new", w)?;
+		writeln!(w, "({min}, {max})")?;
+	}
+
+	Ok(())
+}
+
+fn write_tag_list(wasm: &Module, w: &mut dyn Write) -> Result<()> {
+	let offset = wasm.import_count(External::Tag);
+	let tag = wasm.tag_section();
+
+	for index in offset..offset + tag.len() {
+		writeln!(w, "\tTAG_LIST[{index}] = {{}}")?;
+	}
+
+	Ok(())
+}
+
+fn write_global_list(
+	backend: &dyn Backend,
+	wasm: &Module,
+	type_info: &TypeInfo,
+	w: &mut dyn Write,
+) -> Result<()> {
+	let offset = wasm.import_count(External::Global);
+	let global = wasm.global_section();
+
+	for (i, global) in global.iter().enumerate() {
+		let index = offset + i;
+
+		write!(w, "\tGLOBAL_LIST[{index}] = {{ value = ")?;
+		write_constant(backend, &global.init_expr, type_info, w)?;
+		writeln!(w, " }}")?;
+	}
+
+	Ok(())
+}
+
+fn write_element_items(
+	backend: &dyn Backend,
+	element: &Element,
+	type_info: &TypeInfo,
+	w: &mut dyn Write,
+) -> Result<()> {
+	write!(w, "{{ ")?;
+
+	let len = match element.items.clone() {
+		ElementItems::Functions(functions) => {
+			let len = functions.count();
+
+			for index in functions {
+				let index = index.unwrap();
+				write!(w, "FUNC_LIST[{index}], ")?;
+			}
+
+			len
+		}
+		ElementItems::Expressions(expressions) => {
+			let len = expressions.count();
+
+			for init in expressions {
+				let init = init.unwrap();
+				write_constant(backend, &init, type_info, w)?;
+				write!(w, ", ")?;
+			}
+
+			len
+		}
+	};
+
+	write!(w, "n = {len} }}")
+}
+
+fn write_element_list(
+	backend: &dyn Backend,
+	list: &[Element],
+	reachable: &Reachable,
+	type_info: &TypeInfo,
+	w: &mut dyn Write,
+) -> Result<()> {
+	for (i, element) in list.iter().enumerate() {
+		if !reachable.is_element_used(i) {
+			continue;
+		}
+
+		match element.kind {
+			ElementKind::Passive => {
+				write!(w, "\tELEM_LIST[{i}] = ")?;
+				write_element_items(backend, element, type_info, w)?;
+				writeln!(w)?;
+			}
+			ElementKind::Declared => {}
+			ElementKind::Active {
+				table_index,
+				offset_expr,
+			} => {
+				let index = table_index.unwrap_or(0);
+
+				writeln!(w, "\tdo")?;
+				writeln!(w, "\t\tlocal target = TABLE_LIST[{index}].data")?;
+				write!(w, "\t\tlocal offset = ")?;
+
+				write_constant(backend, &offset_expr, type_info, w)?;
+
+				writeln!(w)?;
+				write!(w, "\t\tlocal data = ")?;
+				write_element_items(backend, element, type_info, w)?;
+				writeln!(w)?;
+
+				backend.write_element_copy(w)?;
+				writeln!(w, "\tend")?;
+			}
+		}
+	}
+
+	Ok(())
+}
+
+// Every 4 bytes, read as a little endian word, become 5 digits in base 85
+// written from `#` up with `\` skipped, so that none of them are escaped, or
+// `z` when the word is zero. The whole segment is encoded into one buffer
+// before it is written.
+fn write_encoded(data: &[u8], w: &mut dyn Write) -> Result<()> {
+	let mut buffer = Vec::with_capacity(data.len().div_ceil(4) * 5);
+
+	for chunk in data.chunks(4) {
+		let mut word = [0; 4];
+
+		word[..chunk.len()].copy_from_slice(chunk);
+
+		let mut value = u32::from_le_bytes(word);
+
+		if value == 0 {
+			buffer.push(b'z');
+
+			continue;
+		}
+
+		let mut digit_list = [0; 5];
+
+		for digit in digit_list.iter_mut().rev() {
+			let rem = u8::try_from(value % 85).unwrap();
+
+			*digit = if rem < 57 { b'#' + rem } else { b'$' + rem };
+			value /= 85;
+		}
+
+		buffer.extend_from_slice(&digit_list);
+	}
+
+	w.write_all(&buffer)
+}
+
+fn write_data(
+	backend: &dyn Backend,
+	data: &[u8],
+	options: &Options,
+	w: &mut dyn Write,
+) -> Result<()> {
+	if !options.compact_data {
+		return backend.write_string(data, w);
+	}
+
+	backend.write_runtime_name("data", "decode", w)?;
+	write!(w, "(\"")?;
+	write_encoded(data, w)?;
+	write!(w, "\", {})", data.len())
+}
+
+fn write_data_list(
+	backend: &dyn Backend,
+	list: &[Data],
+	type_info: &TypeInfo,
+	options: &Options,
+	w: &mut dyn Write,
+) -> Result<()> {
+	for (i, data) in list.iter().enumerate() {
+		match data.kind {
+			DataKind::Passive => {
+				write!(w, "\tDATA_LIST[{i}] = ")?;
+				write_data(backend, data.data, options, w)?;
+				writeln!(w)?;
+			}
+			DataKind::Active {
+				memory_index,
+				offset_expr,
+			} => {
+				let memory = memory_index.try_into().unwrap();
+
+				write!(w, "\t")?;
+				backend.write_runtime_name("store", "string", w)?;
+				write!(w, "(MEMORY_LIST[{memory}], ")?;
+
+				if type_info.is_memory_64(memory) && !backend.has_native_i64() {
+					backend.write_runtime_name("convert", "f64_u64", w)?;
+					write!(w, "(")?;
+					write_constant(backend, &offset_expr, type_info, w)?;
+					write!(w, ")")?;
+				} else {
+					write_constant(backend, &offset_expr, type_info, w)?;
+				}
+
+				write!(w, ",")?;
+				write_data(backend, data.data, options, w)?;
+				writeln!(w, ")")?;
+			}
+		}
+	}
+
+	Ok(())
+}
+
+fn optimize_func(func: &mut FuncData, options: &Options) {
+	optimize(func, options.level);
+
+	if options.fold_offsets {
+		fold_offsets(func);
+	}
+}
+
+fn optimize_func_list(func_list: &mut [FuncData], options: &Options) {
+	#[cfg(feature = "parallel")]
+	{
+		use rayon::prelude::*;
+
+		func_list
+			.par_iter_mut()
+			.for_each(|func| optimize_func(func, options));
+	}
+
+	#[cfg(not(feature = "parallel"))]
+	for func in func_list {
+		optimize_func(func, options);
+	}
+}
+
+fn build_func_list(wasm: &Module, type_info: &TypeInfo, options: &Options) -> Vec<FuncData> {
+	let offset = wasm.import_count(External::Func);
+	let mut builder = Factory::from_type_info(type_info);
+
+	let mut func_list: Vec<_> = wasm
+		.code_section()
+		.iter()
+		.enumerate()
+		.map(|f| builder.create_indexed(f.0 + offset, f.1).unwrap())
+		.collect();
+
+	devirtualize(wasm, &mut func_list, options.level);
+	inline(&mut func_list, offset, options.level);
+	hoist(wasm, &mut func_list, options.level);
+
+	optimize_func_list(&mut func_list, options);
+
+	func_list
+}
+
+fn build_reachable(wasm: &Module, func_list: &[FuncData], options: &Options) -> Reachable {
+	if options.tree_shake {
+		Reachable::from_module(wasm, func_list)
+	} else {
+		Reachable::all(wasm)
+	}
+}
+
+fn create_constant(init: &ConstExpr, type_info: &TypeInfo) -> FuncData {
+	let code = reader_to_code(init.get_operators_reader());
+
+	Factory::from_type_info(type_info).create_anonymous(&code)
+}
+
+// Extended constant expressions can use operations that need localizing
+fn build_constant_list(wasm: &Module, type_info: &TypeInfo) -> Vec<FuncData> {
+	let global_list = wasm.global_section().iter().map(|v| v.init_expr);
+	let element_list = wasm.element_section().iter().filter_map(|v| match v.kind {
+		ElementKind::Active { offset_expr, .. } => Some(offset_expr),
+		_ => None,
+	});
+	let data_list = wasm.data_section().iter().filter_map(|v| match v.kind {
+		DataKind::Active { offset_expr, .. } => Some(offset_expr),
+		DataKind::Passive => None,
+	});
+
+	global_list
+		.chain(element_list)
+		.chain(data_list)
+		.map(|init| create_constant(&init, type_info))
+		.collect()
+}
+
+// Runtime functions are localized once for the module, while the memories
+// they are used with are declared by each instance
+fn write_localize_used(
+	backend: &dyn Backend,
+	wasm: &Module,
+	func_list: &[FuncData],
+	reachable: &Reachable,
+	constant_list: &[FuncData],
+	w: &mut dyn Write,
+) -> Result<BTreeSet<usize>> {
+	let offset = wasm.import_count(External::Func);
+	let mut loc_set = BTreeSet::new();
+	let mut mem_set = BTreeSet::new();
+
+	for (loc, mem) in func_list
+		.iter()
+		.enumerate()
+		.filter(|v| reachable.is_function_used(offset + v.0))
+		.map(|v| v.1)
+		.chain(constant_list)
+		.map(|v| localize(backend, v))
+	{
+		loc_set.extend(loc);
+		mem_set.extend(mem);
+	}
+
+	for loc in loc_set {
+		backend.write_local_operation(loc.0, loc.1, w)?;
+	}
+
+	Ok(mem_set)
+}
+
+fn write_func_start(wasm: &Module, index: u32, w: &mut dyn Write) -> Result<()> {
+	write!(w, "FUNC_LIST[{index}] = ")?;
+
+	wasm.name_section()
+		.get(&index)
+		.map_or_else(|| Ok(()), |name| write!(w, "--[[ {name} ]] "))
+}
+
+// Names from the name section can hold any character, and are prefixed so
+// that they never shadow a name the function uses
+fn build_debug_name(wasm: &Module, index: u32) -> String {
+	let name: String = wasm.name_section().get(&index).map_or_else(
+		|| index.to_string(),
+		|name| {
+			name.chars()
+				.map(|v| if v.is_ascii_alphanumeric() { v } else { '_' })
+				.collect()
+		},
+	);
+
+	format!("wasm_{name}")
+}
+
+// Scoped functions start this many lines below their `do`
+fn get_func_line_offset(ast: &FuncData, options: &Options) -> usize {
+	let has_jump_table = !find_jump_table_list(ast).is_empty();
+
+	usize::from(options.debug_names || options.coverage || has_jump_table)
+		+ usize::from(options.coverage)
+		+ usize::from(has_jump_table)
+}
+
+// Functions are assigned as they are written, or with `debug_names` are first
+// defined as a local named after them, which Luau shows in tracebacks and
+// `debug.info` in place of an anonymous function. Profiled functions are
+// wrapped on the same line so that the source map still lines up, and covered
+// functions get their block counters as a local scoped to them. The jump
+// tables of `br_table` are built once beside the function in the same way,
+// rather than on every call.
+fn write_func(
+	backend: &dyn Backend,
+	wasm: &Module,
+	index: u32,
+	ast: &FuncData,
+	data: &[u8],
+	options: &Options,
+	w: &mut dyn Write,
+) -> Result<()> {
+	let jump_table_list = find_jump_table_list(ast);
+	let is_scoped = options.debug_names || options.coverage || !jump_table_list.is_empty();
+
+	if is_scoped {
+		writeln!(w, "do")?;
+	}
+
+	if !jump_table_list.is_empty() {
+		backend.write_jump_table_local(w)?;
+		write_jump_table_list(&jump_table_list, w)?;
+	}
+
+	if options.coverage {
+		let len = count_blocks(ast);
+
+		writeln!(w, "local coverage = COVERAGE.new({index}, {len})")?;
+	}
+
+	if options.debug_names {
+		let name = build_debug_name(wasm, index);
+		let rest = data
+			.strip_prefix(b"function")
+			.expect("functions are written from their header");
+
+		write!(w, "local function {name}")?;
+		w.write_all(rest)?;
+
+		if options.profile {
+			writeln!(w, "FUNC_LIST[{index}] = PROFILE.wrap({index}, {name})")?;
+		} else {
+			writeln!(w, "FUNC_LIST[{index}] = {name}")?;
+		}
+	} else if options.profile {
+		let rest = data
+			.strip_suffix(b"\n")
+			.expect("functions end with a line break");
+
+		write_func_start(wasm, index, w)?;
+		write!(w, "PROFILE.wrap({index}, ")?;
+		w.write_all(rest)?;
+		writeln!(w, ")")?;
+	} else {
+		write_func_start(wasm, index, w)?;
+		w.write_all(data)?;
+	}
+
+	if is_scoped {
+		writeln!(w, "end")?;
+	}
+
+	Ok(())
+}
+
+// Functions are written independently of each other, so with the `parallel`
+// feature each is written into its own buffer across threads before they are
+// joined back in order. Those that cannot be reached are left empty.
+fn build_func_data_list<F>(
+	wasm: &Module,
+	func_list: &[FuncData],
+	reachable: &Reachable,
+	write: F,
+) -> Result<Vec<Vec<u8>>>
+where
+	F: Fn(u32, &FuncData) -> Result<Vec<u8>> + Sync,
+{
+	#[cfg(feature = "parallel")]
+	use rayon::prelude::*;
+
+	#[cfg(feature = "parallel")]
+	let iter = func_list.par_iter().enumerate();
+
+	#[cfg(not(feature = "parallel"))]
+	let iter = func_list.iter().enumerate();
+
+	let offset = wasm.import_count(External::Func);
+
+	iter.map(|(i, v)| {
+		let index = offset + i;
+
+		if reachable.is_function_used(index) {
+			write(index.try_into().unwrap(), v)
+		} else {
+			Ok(Vec::new())
+		}
+	})
+	.collect()
+}
+
+fn write_func_list(
+	backend: &dyn Backend,
+	wasm: &Module,
+	func_list: &[FuncData],
+	reachable: &Reachable,
+	options: &Options,
+	chunk: &mut ChunkWriter,
+	w: &mut LineCounter,
+) -> Result<usize> {
+	let data_list = build_func_data_list(wasm, func_list, reachable, |index, v| {
+		let mut body = Vec::new();
+		let mut data = Vec::new();
+
+		backend.write_function(v, options, &mut body)?;
+		write_func(backend, wasm, index, v, &body, options, &mut data)?;
+
+		Ok(data)
+	})?;
+
+	chunk.write_list(&data_list, w, &mut |w, i| w.write_all(&data_list[i]))
+}
+
+fn write_func_list_mapped(
+	backend: &dyn Backend,
+	wasm: &Module,
+	func_list: &[FuncData],
+	reachable: &Reachable,
+	options: &Options,
+	chunk: &mut ChunkWriter,
+	w: &mut LineCounter,
+) -> Result<(usize, Vec<Mapping>)> {
+	let offset = wasm.import_count(External::Func);
+	let data_list = build_func_data_list(wasm, func_list, reachable, |index, v| {
+		let mut body = Vec::new();
+		let mut data = Vec::new();
+
+		backend.write_function_marked(v, options, &mut body)?;
+		write_func(backend, wasm, index, v, &body, options, &mut data)?;
+
+		Ok(data)
+	})?;
+
+	let mut mapping_list = Vec::new();
+	let len = chunk.write_list(&data_list, w, &mut |w, i| {
+		let index = offset + i;
+
+		mapping_list.push(Mapping {
+			line: w.line() + get_func_line_offset(&func_list[i], options),
+			function: index,
+			offset: wasm.code_section()[i].range().start,
+		});
+
+		w.write_unmarked(&data_list[i], index, &mut mapping_list)
+	})?;
+
+	Ok((len, mapping_list))
+}
+
+fn write_named_array_list(backend: &dyn Backend, wasm: &Module, w: &mut dyn Write) -> Result<()> {
+	backend.write_named_array("FUNC_LIST", wasm.function_space(), w)?;
+	backend.write_named_array("TABLE_LIST", wasm.table_space(), w)?;
+	backend.write_named_array("MEMORY_LIST", wasm.memory_space(), w)?;
+	backend.write_named_array("GLOBAL_LIST", wasm.global_space(), w)?;
+	backend.write_named_array("TAG_LIST", wasm.tag_space(), w)?;
+	backend.write_named_array("ELEM_LIST", wasm.element_section().len(), w)?;
+	backend.write_named_array("DATA_LIST", wasm.data_section().len(), w)
+}
+
+// Metered functions count `FUEL` down and call in here once it runs out,
+// which traps unless a handler hands back more
+fn write_fuel_state(backend: &dyn Backend, options: &Options, w: &mut dyn Write) -> Result<()> {
+	let Some(budget) = options.fuel else {
+		return Ok(());
+	};
+
+	backend.write_fuel_state(budget, w)?;
+	writeln!(w, "local function use_fuel()")?;
+	writeln!(w, "\tlocal handler = FUEL.handler")?;
+	writeln!(w, "\tlocal left = handler and handler() or 0")?;
+	writeln!(w, "\tif left <= 0 then")?;
+	write!(w, "\t\t")?;
+	backend.write_trap_table(w)?;
+	writeln!(w, ".raise(\"out_of_fuel\", \"out of fuel\")")?;
+	writeln!(w, "\tend")?;
+	writeln!(w, "\tFUEL.left = left - 1")?;
+	writeln!(w, "end")
+}
+
+// Covered functions make their block counters in `COVERAGE` as they are
+// defined, which `dump` lists by function and block
+fn write_coverage_state(backend: &dyn Backend, options: &Options, w: &mut dyn Write) -> Result<()> {
+	if !options.coverage {
+		return Ok(());
+	}
+
+	backend.write_coverage_state(w)
+}
+
+// Asynchronous calls drive an export in a coroutine, resuming it whenever
+// the import that suspended it hands back its results
+fn write_async_state(backend: &dyn Backend, options: &Options, w: &mut dyn Write) -> Result<()> {
+	if !options.call_async {
+		return Ok(());
+	}
+
+	backend.write_async_state(w)
+}
+
+// Profiles are listed with the names of functions from the name section
+fn write_profile_state(
+	backend: &dyn Backend,
+	wasm: &Module,
+	options: &Options,
+	w: &mut dyn Write,
+) -> Result<()> {
+	if !options.profile {
+		return Ok(());
+	}
+
+	let mut name_list: Vec<_> = wasm.name_section().iter().collect();
+
+	name_list.sort_unstable();
+	backend.write_profile_state(w)?;
+
+	for (index, name) in name_list {
+		write!(w, "PROFILE.name[{index}] = ")?;
+		backend.write_string(name.as_bytes(), w)?;
+		writeln!(w)?;
+	}
+
+	Ok(())
+}
+
+// Yielding loops call in here once `YIELD` is counted down, which hands
+// control back to the host through its handler or the running coroutine
+fn write_yield_state(backend: &dyn Backend, options: &Options, w: &mut dyn Write) -> Result<()> {
+	let Some(every) = options.yield_every else {
+		return Ok(());
+	};
+
+	backend.write_yield_state(every, w)?;
+	writeln!(w, "local function use_yield()")?;
+	writeln!(w, "\tYIELD.left = YIELD.every")?;
+	writeln!(w, "\tlocal handler = YIELD.handler")?;
+	writeln!(w, "\tif handler then")?;
+	writeln!(w, "\t\thandler()")?;
+	writeln!(w, "\telse")?;
+	writeln!(w, "\t\tcoroutine.yield()")?;
+	writeln!(w, "\tend")?;
+	writeln!(w, "end")
+}
+
+// Depth limited functions call in here once they go over the limit
+fn write_depth_state(backend: &dyn Backend, options: &Options, w: &mut dyn Write) -> Result<()> {
+	if options.max_depth.is_none() {
+		return Ok(());
+	}
+
+	backend.write_depth_state(w)?;
+	writeln!(w, "local function exhaust_stack()")?;
+	write!(w, "\t")?;
+	backend.write_trap_table(w)?;
+	writeln!(
+		w,
+		".raise(\"call_stack_exhausted\", \"call stack exhausted\")"
+	)?;
+	writeln!(w, "end")
+}
+
+// Functions are packed in order until the next one would go over the limit
+fn build_chunk_body_list(
+	backend: &dyn Backend,
+	wasm: &Module,
+	func_list: &[FuncData],
+	reachable: &Reachable,
+	options: &Options,
+	limit: usize,
+) -> Result<Vec<Vec<u8>>> {
+	let data_list = build_func_data_list(wasm, func_list, reachable, |index, v| {
+		let mut body = Vec::new();
+		let mut data = Vec::new();
+
+		backend.write_function(v, options, &mut body)?;
+		write_func(backend, wasm, index, v, &body, options, &mut data)?;
+
+		Ok(data)
+	})?;
+
+	let mut body_list: Vec<Vec<u8>> = Vec::new();
+
+	for mut data in data_list.into_iter().filter(|v| !v.is_empty()) {
+		match body_list.last_mut() {
+			Some(last) if last.len() + data.len() <= limit => last.append(&mut data),
+			_ => body_list.push(data),
+		}
+	}
+
+	Ok(body_list)
+}
+
+// Names a chunk mentions, which are split on anything that cannot be part of
+// one and so also hold keywords, fields, and numbers
+fn build_used_set(body: &[u8]) -> BTreeSet<&[u8]> {
+	body.split(|v| !v.is_ascii_alphanumeric() && *v != b'_')
+		.collect()
+}
+
+// Counts the names a chunk mentions that neither it nor the instance
+// declares, which are at least as many as it captures as upvalues
+fn count_captured(used: &BTreeSet<&[u8]>, name_set: &BTreeSet<&str>) -> usize {
+	used.iter()
+		.filter(|v| {
+			v.first()
+				.is_some_and(|v| v.is_ascii_alphabetic() || *v == b'_')
+		})
+		.filter(|v| !KEYWORD_LIST.contains(v))
+		.filter(|v| !LOCAL_PREFIX_LIST.iter().any(|p| v.starts_with(p)))
+		.filter(|v| !std::str::from_utf8(v).is_ok_and(|v| name_set.contains(v)))
+		.count()
+}
+
+// Chunks define the functions of a module. Each is a function that takes the
+// arrays and state of an instance from its `env` and defines functions over
+// them, so that every instance gets its own, and hands back another that
+// refreshes its cached memories once the instance has made or adopted them.
+struct ChunkWriter<'a, 'b> {
+	backend: &'b dyn Backend,
+	name_set: &'b BTreeSet<&'a str>,
+	mem_set: &'b BTreeSet<usize>,
+	env_set: BTreeSet<&'a str>,
+}
+
+impl<'a, 'b> ChunkWriter<'a, 'b> {
+	fn new(
+		backend: &'b dyn Backend,
+		name_set: &'b BTreeSet<&'a str>,
+		mem_set: &'b BTreeSet<usize>,
+	) -> Self {
+		Self {
+			backend,
+			name_set,
+			mem_set,
+			env_set: BTreeSet::new(),
+		}
+	}
+
+	fn write_start(&mut self, used: &BTreeSet<&[u8]>, w: &mut dyn Write) -> Result<Vec<usize>> {
+		let mem_list: Vec<_> = self
+			.mem_set
+			.iter()
+			.copied()
+			.filter(|v| used.contains(format!("memory_at_{v}").as_bytes()))
+			.collect();
+
+		self.backend.write_chunk_start(w)?;
+
+		for &name in self.name_set {
+			let is_used =
+				used.contains(name.as_bytes()) || (name == "MEMORY_LIST" && !mem_list.is_empty());
+
+			if is_used {
+				self.backend.write_env_local(name, w)?;
+				self.env_set.insert(name);
+			}
+		}
+
+		for &mem in &mem_list {
+			write!(w, "\t")?;
+			self.backend.write_memory_local(mem, w)?;
+		}
+
+		Ok(mem_list)
+	}
+
+	fn write_end(mem_list: &[usize], w: &mut dyn Write) -> Result<()> {
+		writeln!(w, "\treturn function()")?;
+
+		for mem in mem_list {
+			writeln!(w, "\t\tmemory_at_{mem} = MEMORY_LIST[{mem}]")?;
+		}
+
+		writeln!(w, "\tend")?;
+		writeln!(w, "end")
+	}
+
+	// Chunks moved out to their own script are loaded as they are
+	fn write_body(&mut self, body: &[u8]) -> Result<Vec<u8>> {
+		let mut w = Vec::new();
+
+		write!(w, "return ")?;
+
+		let mem_list = self.write_start(&build_used_set(body), &mut w)?;
+
+		w.extend_from_slice(body);
+		Self::write_end(&mem_list, &mut w)?;
+
+		Ok(w)
+	}
+
+	// Chunks kept in the script capture the runtime names they use from it,
+	// so functions are packed into them in order until the names the next
+	// one mentions would take the chunk over the limit of upvalues
+	fn write_list(
+		&mut self,
+		data_list: &[Vec<u8>],
+		w: &mut LineCounter,
+		write_data: &mut dyn FnMut(&mut LineCounter, usize) -> Result<()>,
+	) -> Result<usize> {
+		let mut group_list: Vec<(Vec<usize>, BTreeSet<&[u8]>)> = Vec::new();
+
+		for (i, data) in data_list.iter().enumerate() {
+			if data.is_empty() {
+				continue;
+			}
+
+			let used = build_used_set(data);
+
+			match group_list.last_mut() {
+				Some((list, last))
+					if count_captured(&last.union(&used).copied().collect(), self.name_set)
+						<= UPVALUE_LIMIT =>
+				{
+					list.push(i);
+					last.extend(used);
+				}
+				_ => group_list.push((vec![i], used)),
+			}
+		}
+
+		self.backend
+			.write_named_array("CHUNK_LIST", group_list.len(), w)?;
+
+		for (i, (list, used)) in group_list.iter().enumerate() {
+			write!(w, "CHUNK_LIST[{i}] = ")?;
+
+			let mem_list = self.write_start(used, w)?;
+
+			for &index in list {
+				write_data(w, index)?;
+			}
+
+			Self::write_end(&mem_list, w)?;
+		}
+
+		Ok(group_list.len())
+	}
+}
+
+// Every instance declares its own arrays and state, which are then handed to
+// the chunks in `env` to define its own functions over
+fn write_instance_state(
+	backend: &dyn Backend,
+	wasm: &Module,
+	options: &Options,
+	w: &mut dyn Write,
+) -> Result<()> {
+	backend.write_instance_start(wasm, w)?;
+	write_named_array_list(backend, wasm, w)?;
+	write_fuel_state(backend, options, w)?;
+	write_yield_state(backend, options, w)?;
+	write_depth_state(backend, options, w)?;
+	write_profile_state(backend, wasm, options, w)?;
+	write_coverage_state(backend, options, w)?;
+	write_async_state(backend, options, w)
+}
+
+// The `env` of an instance holds the arrays and state that chunks read, and,
+// for chunks moved out to their own script, the runtime names in `CHUNK_ENV`
+fn write_chunk_env(
+	backend: &dyn Backend,
+	env_set: &BTreeSet<&str>,
+	len: usize,
+	is_split: bool,
+	w: &mut dyn Write,
+) -> Result<()> {
+	let Some(last) = len.checked_sub(1) else {
+		return Ok(());
+	};
+
+	backend.write_env_start(w)?;
+
+	for name in env_set {
+		writeln!(w, "\t{name} = {name},")?;
+	}
+
+	writeln!(w, "}}")?;
+
+	if is_split {
+		writeln!(w, "for name, value in pairs(CHUNK_ENV) do")?;
+		writeln!(w, "\tenv[name] = value")?;
+		writeln!(w, "end")?;
+	}
+
+	backend.write_named_array("REFRESH_LIST", len, w)?;
+	writeln!(w, "for index = 0, {last} do")?;
+	writeln!(w, "\tREFRESH_LIST[index] = CHUNK_LIST[index](env)")?;
+	writeln!(w, "end")
+}
+
+// Globals are swapped for tables that stand in for them once every one has
+// been made or adopted, so that the ones exported are traced as well
+fn write_trace_list(backend: &dyn Backend, wasm: &Module, w: &mut dyn Write) -> Result<()> {
+	if wasm.global_space() == 0 {
+		return Ok(());
+	}
+
+	writeln!(w, "\tfor index, global in pairs(GLOBAL_LIST) do")?;
+	write!(w, "\t\tGLOBAL_LIST[index] = ")?;
+	backend.write_trace_table(w)?;
+	writeln!(w, ".global(index, global)")?;
+	writeln!(w, "\tend")
+}
+
+// Defined functions are registered so that a trap raised in one can name it
+fn write_trap_register(backend: &dyn Backend, wasm: &Module, w: &mut dyn Write) -> Result<()> {
+	let offset = wasm.import_count(External::Func);
+	let len = wasm.function_space();
+
+	if offset == len {
+		return Ok(());
+	}
+
+	write!(w, "\t")?;
+	backend.write_trap_table(w)?;
+	writeln!(w, ".register(FUNC_LIST, {offset}, {})", len - 1)
+}
+
+#[allow(clippy::too_many_arguments)]
+fn write_module_start(
+	backend: &dyn Backend,
+	wasm: &Module,
+	reachable: &Reachable,
+	type_info: &TypeInfo,
+	name_list: &[&str],
+	options: &Options,
+	chunk_len: usize,
+	w: &mut dyn Write,
+) -> Result<()> {
+	writeln!(w, "local function run_init_code()")?;
+	write_table_list(wasm, w)?;
+	write_memory_list(backend, wasm, w)?;
+	write_tag_list(wasm, w)?;
+	write_global_list(backend, wasm, type_info, w)?;
+	write_element_list(backend, wasm.element_section(), reachable, type_info, w)?;
+	write_data_list(backend, wasm.data_section(), type_info, options, w)?;
+	writeln!(w, "end")?;
+
+	write!(w, "\t")?;
+	backend.write_trap_table(w)?;
+	writeln!(w, ".configure(on_trap)")?;
+	write_trap_register(backend, wasm, w)?;
+	write_import_list(backend, wasm, w)?;
+	writeln!(w, "\trun_init_code()")?;
+	write_adopt_list(wasm, w)?;
+
+	if options.trace {
+		write_trace_list(backend, wasm, w)?;
+	}
+
+	if let Some(last) = chunk_len.checked_sub(1) {
+		writeln!(w, "\tfor index = 0, {last} do")?;
+		writeln!(w, "\t\tREFRESH_LIST[index]()")?;
+		writeln!(w, "\tend")?;
+	}
+
+	if let Some(start) = wasm.start_section() {
+		writeln!(w, "\tFUNC_LIST[{start}]()")?;
+	}
+
+	writeln!(w, "\treturn {{")?;
+	write_export_list(backend, wasm.export_section(), options, w)?;
+	write_custom_list(backend, wasm.custom_section(), name_list, w)?;
+
+	if options.fuel.is_some() {
+		writeln!(w, "\t\tfuel = FUEL,")?;
+	}
+
+	if options.yield_every.is_some() {
+		writeln!(w, "\t\tyield = YIELD,")?;
+	}
+
+	if options.profile {
+		writeln!(w, "\t\tprofile = PROFILE,")?;
+	}
+
+	if options.coverage {
+		writeln!(w, "\t\tcoverage = COVERAGE,")?;
+	}
+
+	if options.call_async {
+		writeln!(w, "\t\tcall_async = call_async,")?;
+	}
+
+	if options.trace {
+		write!(w, "\t\ttrace = ")?;
+		backend.write_trace_table(w)?;
+		writeln!(w, ",")?;
+	}
+
+	backend.write_instance_end(w)?;
+	writeln!(w, "end")?;
+	backend.write_footer(w)
+}
+
+/// # Errors
+/// Returns `Err` if writing to `Write` failed.
+pub fn from_inst_list(
+	backend: &dyn Backend,
+	code: &[Operator],
+	type_info: &TypeInfo,
+	w: &mut dyn Write,
+) -> Result<()> {
+	let ast = Factory::from_type_info(type_info).create_anonymous(code);
+
+	backend.write_function(&ast, &Options::default(), w)
+}
+
+/// # Errors
+/// Returns `Err` if writing to `Write` failed.
+pub fn from_module_typed(
+	backend: &dyn Backend,
+	wasm: &Module,
+	type_info: &TypeInfo,
+	w: &mut dyn Write,
+) -> Result<()> {
+	from_module_custom(backend, wasm, type_info, &[], &Options::default(), w)
+}
+
+/// Translates the module, also embedding the contents of every custom section
+/// named in `name_list` as a string in the `custom_list` of the instance.
+/// Functions are optimized as far as the `level` of `options` asks, and
+/// metered against its `fuel`, which the instance exposes as `fuel`. Calls
+/// nested deeper than its `max_depth` trap.
+///
+/// # Errors
+/// Returns `Err` if writing to `Write` failed.
+pub fn from_module_custom(
+	backend: &dyn Backend,
+	wasm: &Module,
+	type_info: &TypeInfo,
+	name_list: &[&str],
+	options: &Options,
+	w: &mut dyn Write,
+) -> Result<()> {
+	let func_list = build_func_list(wasm, type_info, options);
+	let reachable = build_reachable(wasm, &func_list, options);
+	let constant_list = build_constant_list(wasm, type_info);
+	let mut w = LineCounter::new(w);
+	let mem_set = write_localize_used(
+		backend,
+		wasm,
+		&func_list,
+		&reachable,
+		&constant_list,
+		&mut w,
+	)?;
+
+	backend.write_header(&mut w)?;
+
+	let mut state = Vec::new();
+
+	write_instance_state(backend, wasm, options, &mut state)?;
+
+	let state_text = String::from_utf8_lossy(&state);
+	let name_set = build_runtime_name_list(&state_text);
+	let mut chunk = ChunkWriter::new(backend, &name_set, &mem_set);
+	let chunk_len = write_func_list(
+		backend, wasm, &func_list, &reachable, options, &mut chunk, &mut w,
+	)?;
+
+	w.write_all(&state)?;
+	write_chunk_env(backend, &chunk.env_set, chunk_len, false, &mut w)?;
+	write_module_start(
+		backend, wasm, &reachable, type_info, name_list, options, chunk_len, &mut w,
+	)
+}
+
+/// Translates the module as in [`from_module_custom`], also returning where
+/// each function starts and, for backends that mark them, where each of their
+/// statements starts. Lines are counted from the start of `w`.
+///
+/// # Errors
+/// Returns `Err` if writing to `Write` failed.
+pub fn from_module_mapped(
+	backend: &dyn Backend,
+	wasm: &Module,
+	type_info: &TypeInfo,
+	name_list: &[&str],
+	options: &Options,
+	w: &mut dyn Write,
+) -> Result<Vec<Mapping>> {
+	let func_list = build_func_list(wasm, type_info, options);
+	let reachable = build_reachable(wasm, &func_list, options);
+	let constant_list = build_constant_list(wasm, type_info);
+	let mut w = LineCounter::new(w);
+	let mem_set = write_localize_used(
+		backend,
+		wasm,
+		&func_list,
+		&reachable,
+		&constant_list,
+		&mut w,
+	)?;
+
+	backend.write_header(&mut w)?;
+
+	let mut state = Vec::new();
+
+	write_instance_state(backend, wasm, options, &mut state)?;
+
+	let state_text = String::from_utf8_lossy(&state);
+	let name_set = build_runtime_name_list(&state_text);
+	let mut chunk = ChunkWriter::new(backend, &name_set, &mem_set);
+	let (chunk_len, mapping_list) = write_func_list_mapped(
+		backend, wasm, &func_list, &reachable, options, &mut chunk, &mut w,
+	)?;
+
+	w.write_all(&state)?;
+	write_chunk_env(backend, &chunk.env_set, chunk_len, false, &mut w)?;
+	write_module_start(
+		backend, wasm, &reachable, type_info, name_list, options, chunk_len, &mut w,
+	)?;
+
+	Ok(mapping_list)
+}
+
+/// Translates the module as in [`from_module_custom`], but moves the
+/// functions out to chunks, for hosts that limit the size of a script. Each
+/// chunk returns a function that takes the names it needs from the `env` of
+/// an instance and hands back another that refreshes its cached memories
+/// once the instance has made them. The chunks are returned to be written wherever the
+/// `import` of `split` loads them from.
+///
+/// # Errors
+/// Returns `Err` if writing to `Write` failed.
+pub fn from_module_split(
+	backend: &dyn Backend,
+	wasm: &Module,
+	type_info: &TypeInfo,
+	name_list: &[&str],
+	options: &Options,
+	split: &Split,
+	w: &mut dyn Write,
+) -> Result<Vec<Vec<u8>>> {
+	let func_list = build_func_list(wasm, type_info, options);
+	let reachable = build_reachable(wasm, &func_list, options);
+	let constant_list = build_constant_list(wasm, type_info);
+	let mut head = Vec::new();
+	let mem_set = write_localize_used(
+		backend,
+		wasm,
+		&func_list,
+		&reachable,
+		&constant_list,
+		&mut head,
+	)?;
+
+	backend.write_header(&mut head)?;
+
+	let mut state = Vec::new();
+
+	write_instance_state(backend, wasm, options, &mut state)?;
+
+	// Chunks take the runtime names they use from `env` as well, since they
+	// cannot capture them from the script that loads them
+	let head_text = String::from_utf8_lossy(&head);
+	let state_text = String::from_utf8_lossy(&state);
+	let name_set = build_runtime_name_list(&state_text);
+	let mut chunk_name_set = split.name_list.clone();
+
+	chunk_name_set.extend(build_runtime_name_list(&head_text));
+	chunk_name_set.extend(name_set.iter().copied());
+
+	let mut chunk = ChunkWriter::new(backend, &chunk_name_set, &mem_set);
+	let chunk_list =
+		build_chunk_body_list(backend, wasm, &func_list, &reachable, options, split.limit)?
+			.iter()
+			.map(|body| chunk.write_body(body))
+			.collect::<Result<Vec<_>>>()?;
+
+	let (env_set, shared_set): (BTreeSet<_>, BTreeSet<_>) =
+		chunk.env_set.iter().partition(|v| name_set.contains(*v));
+
+	w.write_all(&head)?;
+	writeln!(w, "local CHUNK_ENV = {{")?;
+
+	for name in shared_set {
+		writeln!(w, "\t{name} = {name},")?;
+	}
+
+	writeln!(w, "}}")?;
+	backend.write_named_array("CHUNK_LIST", chunk_list.len(), w)?;
+
+	for i in 0..chunk_list.len() {
+		writeln!(w, "CHUNK_LIST[{i}] = {}", (split.import)(i))?;
+	}
+
+	w.write_all(&state)?;
+	write_chunk_env(backend, &env_set, chunk_list.len(), true, w)?;
+	write_module_start(
+		backend,
+		wasm,
+		&reachable,
+		type_info,
+		name_list,
+		options,
+		chunk_list.len(),
+		w,
+	)?;
+
+	Ok(chunk_list)
+}
+
+/// # Errors
+/// Returns `Err` if writing to `Write` failed.
+pub fn from_module_untyped(backend: &dyn Backend, wasm: &Module, w: &mut dyn Write) -> Result<()> {
+	let type_info = TypeInfo::from_module(wasm);
+
+	from_module_typed(backend, wasm, &type_info, w)
+}
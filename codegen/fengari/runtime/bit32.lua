@@ -0,0 +1,166 @@
+-- Fengari has no `bit32`, but its integers are 32 bits wide, so the native
+-- operators already behave like `i32` and only the unsigned values used by
+-- the runtime need moving in and out of the signed range
+local bit32 = (function()
+	local bit32 = {}
+
+	local math_floor = math.floor
+	local math_ceil = math.ceil
+
+	local MOD = 2 ^ 32
+	local SIGN = 2 ^ 31
+
+	local function to_u32(num)
+		if num < 0 then
+			num = math_ceil(num)
+		else
+			num = math_floor(num)
+		end
+
+		return num % MOD
+	end
+
+	local function to_i32(num)
+		num = to_u32(num)
+
+		if num >= SIGN then
+			num = num - MOD
+		end
+
+		return num
+	end
+
+	local function from_i32(num)
+		return num % MOD
+	end
+
+	function bit32.band(...)
+		local result = -1
+
+		for i = 1, select("#", ...) do
+			result = result & to_i32((select(i, ...)))
+		end
+
+		return from_i32(result)
+	end
+
+	function bit32.bor(...)
+		local result = 0
+
+		for i = 1, select("#", ...) do
+			result = result | to_i32((select(i, ...)))
+		end
+
+		return from_i32(result)
+	end
+
+	function bit32.bxor(...)
+		local result = 0
+
+		for i = 1, select("#", ...) do
+			result = result ~ to_i32((select(i, ...)))
+		end
+
+		return from_i32(result)
+	end
+
+	function bit32.bnot(num)
+		return from_i32(~to_i32(num))
+	end
+
+	function bit32.lshift(num, count)
+		if count < 0 then
+			return bit32.rshift(num, -count)
+		elseif count >= 32 then
+			return 0
+		end
+
+		return from_i32(to_i32(num) << count)
+	end
+
+	function bit32.rshift(num, count)
+		if count < 0 then
+			return bit32.lshift(num, -count)
+		elseif count >= 32 then
+			return 0
+		end
+
+		return from_i32(to_i32(num) >> count)
+	end
+
+	function bit32.arshift(num, count)
+		num = to_i32(num)
+
+		if num >= 0 or count <= 0 then
+			return bit32.rshift(num, count)
+		elseif count >= 32 then
+			return MOD - 1
+		end
+
+		return from_i32(~(~num >> count))
+	end
+
+	function bit32.extract(num, field, width)
+		width = width or 1
+
+		return from_i32((to_i32(num) >> field) & ((1 << width) - 1))
+	end
+
+	function bit32.replace(num, value, field, width)
+		width = width or 1
+
+		local mask = ((1 << width) - 1) << field
+		local old = to_i32(num) & ~mask
+		local new = (to_i32(value) << field) & mask
+
+		return from_i32(old | new)
+	end
+
+	function bit32.lrotate(num, count)
+		count = count % 32
+		num = to_i32(num)
+
+		return from_i32((num << count) | (num >> (32 - count)))
+	end
+
+	function bit32.rrotate(num, count)
+		return bit32.lrotate(num, -count)
+	end
+
+	function bit32.countlz(num)
+		num = to_u32(num)
+
+		if num == 0 then
+			return 32
+		end
+
+		local count = 0
+
+		while num < SIGN do
+			num = num * 2
+			count = count + 1
+		end
+
+		return count
+	end
+
+	function bit32.countrz(num)
+		num = to_u32(num)
+
+		if num == 0 then
+			return 32
+		end
+
+		local count = 0
+
+		while num % 2 == 0 do
+			num = num / 2
+			count = count + 1
+		end
+
+		return count
+	end
+
+	return bit32
+end)()
+
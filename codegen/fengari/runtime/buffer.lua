@@ -0,0 +1,155 @@
+-- Buffers in Fengari are `DataView`s over an `ArrayBuffer` from the `js`
+-- library, so memory is shared with JavaScript and every byte access is
+-- done by the host
+local buffer = (function()
+	local buffer = {}
+
+	local js = require("js")
+
+	local js_new = js.new
+	local ArrayBuffer = js.global.ArrayBuffer
+	local DataView = js.global.DataView
+	local Uint8Array = js.global.Uint8Array
+
+	local string_byte = string.byte
+	local string_char = string.char
+	local table_concat = table.concat
+
+	local function check_bounds(data, offset, size)
+		if offset < 0 or offset + size > data.byteLength then
+			error("buffer access out of bounds", 3)
+		end
+	end
+
+	local function as_bytes(data, offset, count)
+		return js_new(Uint8Array, data.buffer, offset, count)
+	end
+
+	function buffer.create(len)
+		return js_new(DataView, js_new(ArrayBuffer, len))
+	end
+
+	function buffer.len(data)
+		return data.byteLength
+	end
+
+	function buffer.fromstring(str)
+		local data = buffer.create(#str)
+
+		for i = 1, #str do
+			data:setUint8(i - 1, string_byte(str, i))
+		end
+
+		return data
+	end
+
+	function buffer.tostring(data)
+		local list = {}
+
+		for i = 0, data.byteLength - 1 do
+			list[i + 1] = string_char(data:getUint8(i))
+		end
+
+		return table_concat(list)
+	end
+
+	function buffer.copy(target, target_offset, source, source_offset, count)
+		source_offset = source_offset or 0
+		count = count or source.byteLength - source_offset
+
+		check_bounds(target, target_offset, count)
+		check_bounds(source, source_offset, count)
+
+		-- `set` copies through a temporary when both views share a buffer
+		as_bytes(target, target_offset, count):set(as_bytes(source, source_offset, count))
+	end
+
+	function buffer.fill(data, offset, value, count)
+		count = count or data.byteLength - offset
+
+		check_bounds(data, offset, count)
+
+		as_bytes(data, offset, count):fill(value % 256)
+	end
+
+	function buffer.readu8(data, offset)
+		check_bounds(data, offset, 1)
+
+		return data:getUint8(offset)
+	end
+
+	function buffer.readi8(data, offset)
+		check_bounds(data, offset, 1)
+
+		return data:getInt8(offset)
+	end
+
+	function buffer.readu16(data, offset)
+		check_bounds(data, offset, 2)
+
+		return data:getUint16(offset, true)
+	end
+
+	function buffer.readi16(data, offset)
+		check_bounds(data, offset, 2)
+
+		return data:getInt16(offset, true)
+	end
+
+	function buffer.readu32(data, offset)
+		check_bounds(data, offset, 4)
+
+		return data:getUint32(offset, true)
+	end
+
+	function buffer.readi32(data, offset)
+		check_bounds(data, offset, 4)
+
+		return data:getInt32(offset, true)
+	end
+
+	function buffer.readf32(data, offset)
+		check_bounds(data, offset, 4)
+
+		return data:getFloat32(offset, true)
+	end
+
+	function buffer.readf64(data, offset)
+		check_bounds(data, offset, 8)
+
+		return data:getFloat64(offset, true)
+	end
+
+	function buffer.writeu8(data, offset, value)
+		check_bounds(data, offset, 1)
+
+		data:setUint8(offset, value)
+	end
+
+	function buffer.writeu16(data, offset, value)
+		check_bounds(data, offset, 2)
+
+		data:setUint16(offset, value, true)
+	end
+
+	function buffer.writeu32(data, offset, value)
+		check_bounds(data, offset, 4)
+
+		data:setUint32(offset, value, true)
+	end
+
+	function buffer.writef32(data, offset, value)
+		check_bounds(data, offset, 4)
+
+		data:setFloat32(offset, value, true)
+	end
+
+	function buffer.writef64(data, offset, value)
+		check_bounds(data, offset, 8)
+
+		data:setFloat64(offset, value, true)
+	end
+
+	return buffer
+end)()
+
@@ -0,0 +1,26 @@
+-- Rounding in Lua 5.3 gives back integers, which Fengari keeps in 32 bits
+-- that wrap on overflow, so the runtime sees a `math` where rounding keeps
+-- producing floats with the sign of zero intact
+local math = (function()
+	local math_floor = math.floor
+	local math_ceil = math.ceil
+
+	local function to_float(num, result)
+		if result == 0 and (num < 0 or 1 / num < 0) then
+			return -0.0
+		end
+
+		return result + 0.0
+	end
+
+	local function floor(num)
+		return to_float(num, math_floor(num))
+	end
+
+	local function ceil(num)
+		return to_float(num, math_ceil(num))
+	end
+
+	return setmetatable({ floor = floor, ceil = ceil }, { __index = math })
+end)()
+
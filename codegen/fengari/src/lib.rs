@@ -0,0 +1,13 @@
+// Fengari runs the Lua 5.1 output as is, so only the parts of the runtime
+// that meet its 32 bit integers and its JavaScript host are replaced
+pub static RUNTIME: &str = concat!(
+	include_str!("../runtime/math.lua"),
+	include_str!("../runtime/bit32.lua"),
+	include_str!("../runtime/buffer.lua"),
+	include_str!("../../lua51/runtime/runtime.lua"),
+);
+
+pub use codegen_lua51::{
+	from_inst_list, from_module_custom, from_module_mapped, from_module_split, from_module_typed,
+	from_module_untyped, write_self_test,
+};
@@ -0,0 +1,129 @@
+-- Garry's Mod only exposes the LuaJIT `bit` library, whose results are
+-- signed and whose shift counts wrap, so `bit32` is rebuilt on top of it
+local bit32 = (function()
+	local bit32 = {}
+
+	local bit_tobit = bit.tobit
+	local bit_band = bit.band
+	local bit_bor = bit.bor
+	local bit_bxor = bit.bxor
+	local bit_bnot = bit.bnot
+	local bit_lshift = bit.lshift
+	local bit_rshift = bit.rshift
+	local bit_arshift = bit.arshift
+	local bit_rol = bit.rol
+	local bit_ror = bit.ror
+
+	local MOD = 0x100000000
+
+	local function to_u32(num)
+		return bit_tobit(num) % MOD
+	end
+
+	function bit32.band(...)
+		return bit_band(...) % MOD
+	end
+
+	function bit32.bor(...)
+		return bit_bor(...) % MOD
+	end
+
+	function bit32.bxor(...)
+		return bit_bxor(...) % MOD
+	end
+
+	function bit32.bnot(num)
+		return bit_bnot(num) % MOD
+	end
+
+	function bit32.lshift(num, count)
+		if count < 0 then
+			return bit32.rshift(num, -count)
+		elseif count >= 32 then
+			return 0
+		end
+
+		return bit_lshift(num, count) % MOD
+	end
+
+	function bit32.rshift(num, count)
+		if count < 0 then
+			return bit32.lshift(num, -count)
+		elseif count >= 32 then
+			return 0
+		end
+
+		return bit_rshift(num, count) % MOD
+	end
+
+	function bit32.arshift(num, count)
+		if count < 0 then
+			return bit32.lshift(num, -count)
+		elseif count >= 32 then
+			count = 31
+		end
+
+		return bit_arshift(num, count) % MOD
+	end
+
+	function bit32.extract(num, field, width)
+		width = width or 1
+
+		return bit_band(bit_rshift(num, field), 2 ^ width - 1) % MOD
+	end
+
+	function bit32.replace(num, value, field, width)
+		width = width or 1
+
+		local mask = bit_lshift(2 ^ width - 1, field)
+		local old = bit_band(num, bit_bnot(mask))
+		local new = bit_band(bit_lshift(value, field), mask)
+
+		return bit_bor(old, new) % MOD
+	end
+
+	function bit32.lrotate(num, count)
+		return bit_rol(num, count) % MOD
+	end
+
+	function bit32.rrotate(num, count)
+		return bit_ror(num, count) % MOD
+	end
+
+	function bit32.countlz(num)
+		num = to_u32(num)
+
+		if num == 0 then
+			return 32
+		end
+
+		local count = 0
+
+		while num < 0x80000000 do
+			num = num * 2
+			count = count + 1
+		end
+
+		return count
+	end
+
+	function bit32.countrz(num)
+		num = to_u32(num)
+
+		if num == 0 then
+			return 32
+		end
+
+		local count = 0
+
+		while num % 2 == 0 do
+			num = num / 2
+			count = count + 1
+		end
+
+		return count
+	end
+
+	return bit32
+end)()
+
@@ -0,0 +1,18 @@
+// Only the bit library differs from plain Lua 5.1, so the translator and the
+// rest of the runtime are shared with it
+pub static RUNTIME: &str = concat!(
+	include_str!("../runtime/bit32.lua"),
+	include_str!("../../lua51/runtime/buffer.lua"),
+	include_str!("../../lua51/runtime/runtime.lua"),
+);
+pub static RUNTIME_WORDS: &str = concat!(
+	include_str!("../runtime/bit32.lua"),
+	include_str!("../../lua51/runtime/buffer.lua"),
+	include_str!("../../lua51/runtime/buffer_words.lua"),
+	include_str!("../../lua51/runtime/runtime.lua"),
+);
+
+pub use codegen_lua51::{
+	from_inst_list, from_module_custom, from_module_mapped, from_module_split, from_module_typed,
+	from_module_untyped, write_self_test,
+};
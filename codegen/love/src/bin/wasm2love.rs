@@ -0,0 +1,22 @@
+use std::io::{Result, Write};
+
+use wasm_ast::module::Module;
+
+fn do_runtime(lock: &mut dyn Write) -> Result<()> {
+	let runtime = codegen_love::RUNTIME;
+
+	writeln!(lock, "local rt = (function()")?;
+	writeln!(lock, "{runtime}")?;
+	writeln!(lock, "end)()")
+}
+
+fn do_module(data: &[u8], lock: &mut dyn Write) -> Result<()> {
+	let wasm = Module::try_from_data(data).unwrap();
+
+	do_runtime(lock)?;
+	codegen_love::from_module_untyped(&wasm, lock)
+}
+
+fn main() -> Result<()> {
+	codegen_core::run_command("wasm2love", do_module)
+}
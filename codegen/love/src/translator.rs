@@ -0,0 +1,146 @@
+use std::io::{Result, Write};
+
+use codegen_core::{Backend, Mapping, Options, Split};
+use codegen_luajit::LuaJIT;
+use wasm_ast::{
+	module::{Module, TypeInfo},
+	node::{BinOpType, CmpOpType, Expression, FuncData, UnOpType, Value},
+};
+use wasmparser::Operator;
+
+// The LuaJIT builds shipped with LÖVE and Defold do not expose `table.new`,
+// so the module level arrays are plain tables. The file is loaded with the
+// engine's own `require` and instantiated through `new`, which keeps the
+// generated code free of anything that has to be edited per project
+struct Love;
+
+const LUA_JIT: LuaJIT = LuaJIT { no_goto: false };
+
+impl Backend for Love {
+	fn write_function(&self, func: &FuncData, options: &Options, w: &mut dyn Write) -> Result<()> {
+		LUA_JIT.write_function(func, options, w)
+	}
+
+	fn write_function_marked(
+		&self,
+		func: &FuncData,
+		options: &Options,
+		w: &mut dyn Write,
+	) -> Result<()> {
+		LUA_JIT.write_function_marked(func, options, w)
+	}
+
+	fn write_expression(&self, expr: &Expression, w: &mut dyn Write) -> Result<()> {
+		LUA_JIT.write_expression(expr, w)
+	}
+
+	fn un_op_name(&self, op: UnOpType) -> (&'static str, &'static str) {
+		LUA_JIT.un_op_name(op)
+	}
+
+	fn bin_op_name(&self, op: BinOpType) -> Option<(&'static str, &'static str)> {
+		LUA_JIT.bin_op_name(op)
+	}
+
+	fn cmp_op_name(&self, op: CmpOpType) -> Option<(&'static str, &'static str)> {
+		LUA_JIT.cmp_op_name(op)
+	}
+
+	fn value_name(&self, value: Value) -> Option<(&'static str, &'static str)> {
+		LUA_JIT.value_name(value)
+	}
+
+	fn write_local_operation(&self, head: &str, tail: &str, w: &mut dyn Write) -> Result<()> {
+		LUA_JIT.write_local_operation(head, tail, w)
+	}
+
+	fn has_wasi(&self) -> bool {
+		LUA_JIT.has_wasi()
+	}
+
+	fn write_header(&self, w: &mut dyn Write) -> Result<()> {
+		writeln!(w, "local to_signed = bit.tobit")
+	}
+
+	fn write_instance_start(&self, _wasm: &Module, w: &mut dyn Write) -> Result<()> {
+		writeln!(w, "local M = {{}}")?;
+		writeln!(w, "function M.new(wasm, on_trap, previous)")
+	}
+
+	fn write_footer(&self, w: &mut dyn Write) -> Result<()> {
+		writeln!(w, "return M")
+	}
+}
+
+/// # Errors
+/// Returns `Err` if writing to `Write` failed.
+pub fn from_inst_list(code: &[Operator], type_info: &TypeInfo, w: &mut dyn Write) -> Result<()> {
+	codegen_core::from_inst_list(&Love, code, type_info, w)
+}
+
+/// # Errors
+/// Returns `Err` if writing to `Write` failed.
+pub fn from_module_typed(wasm: &Module, type_info: &TypeInfo, w: &mut dyn Write) -> Result<()> {
+	codegen_core::from_module_typed(&Love, wasm, type_info, w)
+}
+
+/// Translates the module, also embedding the contents of every custom section
+/// named in `name_list` as a string in the `custom_list` of the instance.
+///
+/// # Errors
+/// Returns `Err` if writing to `Write` failed.
+pub fn from_module_custom(
+	wasm: &Module,
+	type_info: &TypeInfo,
+	name_list: &[&str],
+	options: &Options,
+	w: &mut dyn Write,
+) -> Result<()> {
+	codegen_core::from_module_custom(&Love, wasm, type_info, name_list, options, w)
+}
+
+/// Translates the module as in [`from_module_custom`], also returning the
+/// line that each function and statement starts at for a source map.
+///
+/// # Errors
+/// Returns `Err` if writing to `Write` failed.
+pub fn from_module_mapped(
+	wasm: &Module,
+	type_info: &TypeInfo,
+	name_list: &[&str],
+	options: &Options,
+	w: &mut dyn Write,
+) -> Result<Vec<Mapping>> {
+	codegen_core::from_module_mapped(&Love, wasm, type_info, name_list, options, w)
+}
+
+/// Translates the module with its functions moved out to chunks, which are
+/// returned to be written where the `import` of `split` loads them from.
+///
+/// # Errors
+/// Returns `Err` if writing to `Write` failed.
+pub fn from_module_split(
+	wasm: &Module,
+	type_info: &TypeInfo,
+	name_list: &[&str],
+	options: &Options,
+	split: &Split,
+	w: &mut dyn Write,
+) -> Result<Vec<Vec<u8>>> {
+	codegen_core::from_module_split(&Love, wasm, type_info, name_list, options, split, w)
+}
+
+/// # Errors
+/// Returns `Err` if writing to `Write` failed.
+pub fn from_module_untyped(wasm: &Module, w: &mut dyn Write) -> Result<()> {
+	codegen_core::from_module_untyped(&Love, wasm, w)
+}
+
+/// Writes a script that checks the runtime against edge cases, as described
+/// in [`codegen_core::write_self_test`], to be run after the runtime.
+///
+/// # Errors
+/// Returns `Err` if writing to `Write` failed.
+pub fn write_self_test(w: &mut dyn Write) -> Result<()> {
+	codegen_core::write_self_test(&Love, w)
+}
@@ -0,0 +1,183 @@
+-- Lua 5.1 has no `bit32`, so it is emulated with plain arithmetic on
+-- doubles, which are exact for all 32 bit integers
+local bit32 = (function()
+	local bit32 = {}
+
+	local math_floor = math.floor
+	local math_ceil = math.ceil
+
+	local MOD = 0x100000000
+
+	local function to_u32(num)
+		if num < 0 then
+			num = math_ceil(num)
+		else
+			num = math_floor(num)
+		end
+
+		return num % MOD
+	end
+
+	local function create_nibble_op(rule)
+		local lookup = {}
+
+		for a = 0, 15 do
+			for b = 0, 15 do
+				local x, y = a, b
+				local result = 0
+
+				for i = 0, 3 do
+					local bit_x, bit_y = x % 2, y % 2
+
+					result = result + rule(bit_x, bit_y) * 2 ^ i
+					x = (x - bit_x) / 2
+					y = (y - bit_y) / 2
+				end
+
+				lookup[a * 16 + b] = result
+			end
+		end
+
+		local function apply(lhs, rhs)
+			local result = 0
+			local shift = 1
+
+			for _ = 1, 8 do
+				local a, b = lhs % 16, rhs % 16
+
+				result = result + lookup[a * 16 + b] * shift
+				lhs = (lhs - a) / 16
+				rhs = (rhs - b) / 16
+				shift = shift * 16
+			end
+
+			return result
+		end
+
+		return function(identity)
+			return function(...)
+				local result = identity
+
+				for i = 1, select("#", ...) do
+					result = apply(result, to_u32((select(i, ...))))
+				end
+
+				return result
+			end
+		end
+	end
+
+	bit32.band = create_nibble_op(function(x, y)
+		return x * y
+	end)(MOD - 1)
+
+	bit32.bor = create_nibble_op(function(x, y)
+		return x + y - x * y
+	end)(0)
+
+	bit32.bxor = create_nibble_op(function(x, y)
+		return (x + y) % 2
+	end)(0)
+
+	function bit32.bnot(num)
+		return MOD - 1 - to_u32(num)
+	end
+
+	function bit32.lshift(num, count)
+		if count < 0 then
+			return bit32.rshift(num, -count)
+		elseif count >= 32 then
+			return 0
+		end
+
+		return (to_u32(num) % 2 ^ (32 - count)) * 2 ^ count
+	end
+
+	function bit32.rshift(num, count)
+		if count < 0 then
+			return bit32.lshift(num, -count)
+		elseif count >= 32 then
+			return 0
+		end
+
+		return math_floor(to_u32(num) / 2 ^ count)
+	end
+
+	function bit32.arshift(num, count)
+		num = to_u32(num)
+
+		if num < 0x80000000 or count <= 0 then
+			return bit32.rshift(num, count)
+		elseif count >= 32 then
+			return MOD - 1
+		end
+
+		return math_floor(num / 2 ^ count) + (MOD - 2 ^ (32 - count))
+	end
+
+	function bit32.extract(num, field, width)
+		width = width or 1
+
+		return math_floor(to_u32(num) / 2 ^ field) % 2 ^ width
+	end
+
+	function bit32.replace(num, value, field, width)
+		width = width or 1
+
+		local old = bit32.extract(num, field, width)
+		local new = to_u32(value) % 2 ^ width
+
+		return to_u32(num) + (new - old) * 2 ^ field
+	end
+
+	function bit32.lrotate(num, count)
+		count = count % 32
+		num = to_u32(num)
+
+		local high = math_floor(num / 2 ^ (32 - count))
+		local low = (num % 2 ^ (32 - count)) * 2 ^ count
+
+		return low + high
+	end
+
+	function bit32.rrotate(num, count)
+		return bit32.lrotate(num, -count)
+	end
+
+	function bit32.countlz(num)
+		num = to_u32(num)
+
+		if num == 0 then
+			return 32
+		end
+
+		local count = 0
+
+		while num < 0x80000000 do
+			num = num * 2
+			count = count + 1
+		end
+
+		return count
+	end
+
+	function bit32.countrz(num)
+		num = to_u32(num)
+
+		if num == 0 then
+			return 32
+		end
+
+		local count = 0
+
+		while num % 2 == 0 do
+			num = num / 2
+			count = count + 1
+		end
+
+		return count
+	end
+
+	return bit32
+end)()
+
@@ -0,0 +1,288 @@
+-- Lua 5.1 has no `buffer` either, so buffers are tables of bytes indexed
+-- from 0 with the length in `len`, where missing entries are zero
+local buffer = (function()
+	local buffer = {}
+
+	local math_floor = math.floor
+	local math_frexp = math.frexp
+	local math_ldexp = math.ldexp
+	local math_huge = math.huge
+
+	local string_byte = string.byte
+	local string_char = string.char
+
+	local function check_bounds(data, offset, size)
+		if offset < 0 or offset + size > data.len then
+			error("buffer access out of bounds", 3)
+		end
+	end
+
+	local function read_unsigned(data, offset, size)
+		check_bounds(data, offset, size)
+
+		local result = 0
+
+		for i = size - 1, 0, -1 do
+			result = result * 256 + (data[offset + i] or 0)
+		end
+
+		return result
+	end
+
+	local function read_signed(data, offset, size)
+		local result = read_unsigned(data, offset, size)
+		local limit = 2 ^ (size * 8)
+
+		if result >= limit / 2 then
+			result = result - limit
+		end
+
+		return result
+	end
+
+	local function write_unsigned(data, offset, size, value)
+		check_bounds(data, offset, size)
+
+		value = math_floor(value) % 2 ^ (size * 8)
+
+		for i = 0, size - 1 do
+			local byte = value % 256
+
+			data[offset + i] = byte
+			value = (value - byte) / 256
+		end
+	end
+
+	local function round_even(num)
+		local result = math_floor(num)
+		local rest = num - result
+
+		if rest > 0.5 or (rest == 0.5 and result % 2 == 1) then
+			result = result + 1
+		end
+
+		return result
+	end
+
+	-- Plain Lua cannot see the sign or payload of a NaN, so every NaN is written
+	-- as the quiet one and reinterprets of other NaNs do not keep their bits
+	local function into_f32_bits(num)
+		if num ~= num then
+			return 0x7FC00000
+		end
+
+		local sign = 0
+
+		if num < 0 or (num == 0 and 1 / num < 0) then
+			sign = 0x80000000
+			num = -num
+		end
+
+		if num == math_huge then
+			return sign + 0x7F800000
+		elseif num == 0 then
+			return sign
+		end
+
+		local fraction, exponent = math_frexp(num)
+		local biased = exponent + 126
+
+		if biased <= 0 then
+			return sign + round_even(num * 2 ^ 149)
+		end
+
+		local mantissa = round_even((fraction * 2 - 1) * 2 ^ 23)
+
+		if mantissa == 2 ^ 23 then
+			mantissa = 0
+			biased = biased + 1
+		end
+
+		if biased >= 255 then
+			return sign + 0x7F800000
+		end
+
+		return sign + biased * 2 ^ 23 + mantissa
+	end
+
+	local function from_f32_bits(bits)
+		local sign = bits >= 0x80000000 and -1 or 1
+		local biased = math_floor(bits / 2 ^ 23) % 256
+		local mantissa = bits % 2 ^ 23
+
+		if biased == 255 then
+			return mantissa == 0 and sign * math_huge or 0 / 0
+		elseif biased == 0 then
+			return sign * math_ldexp(mantissa, -149)
+		end
+
+		return sign * math_ldexp(mantissa + 2 ^ 23, biased - 150)
+	end
+
+	local function into_f64_bits(num)
+		if num ~= num then
+			return 0, 0x7FF80000
+		end
+
+		local sign = 0
+
+		if num < 0 or (num == 0 and 1 / num < 0) then
+			sign = 0x80000000
+			num = -num
+		end
+
+		if num == math_huge then
+			return 0, sign + 0x7FF00000
+		elseif num == 0 then
+			return 0, sign
+		end
+
+		local fraction, exponent = math_frexp(num)
+		local biased = exponent + 1022
+		local mantissa
+
+		if biased <= 0 then
+			biased = 0
+			mantissa = num * 2 ^ 1022 * 2 ^ 52
+		else
+			mantissa = (fraction * 2 - 1) * 2 ^ 52
+		end
+
+		local low = mantissa % 0x100000000
+		local high = (mantissa - low) / 0x100000000
+
+		return low, sign + biased * 2 ^ 20 + high
+	end
+
+	local function from_f64_bits(low, high)
+		local sign = high >= 0x80000000 and -1 or 1
+		local biased = math_floor(high / 2 ^ 20) % 2048
+		local mantissa = (high % 2 ^ 20) * 0x100000000 + low
+
+		if biased == 2047 then
+			return mantissa == 0 and sign * math_huge or 0 / 0
+		elseif biased == 0 then
+			return sign * math_ldexp(mantissa, -1074)
+		end
+
+		return sign * math_ldexp(mantissa + 2 ^ 52, biased - 1075)
+	end
+
+	function buffer.create(len)
+		return { len = len }
+	end
+
+	function buffer.len(data)
+		return data.len
+	end
+
+	function buffer.fromstring(str)
+		local data = { len = #str }
+
+		for i = 1, #str do
+			data[i - 1] = string_byte(str, i)
+		end
+
+		return data
+	end
+
+	function buffer.tostring(data)
+		local list = {}
+
+		for i = 0, data.len - 1 do
+			list[i + 1] = string_char(data[i] or 0)
+		end
+
+		return table.concat(list)
+	end
+
+	function buffer.copy(target, target_offset, source, source_offset, count)
+		source_offset = source_offset or 0
+		count = count or source.len - source_offset
+
+		check_bounds(target, target_offset, count)
+		check_bounds(source, source_offset, count)
+
+		if target == source and target_offset > source_offset then
+			for i = count - 1, 0, -1 do
+				target[target_offset + i] = source[source_offset + i]
+			end
+		else
+			for i = 0, count - 1 do
+				target[target_offset + i] = source[source_offset + i]
+			end
+		end
+	end
+
+	function buffer.fill(data, offset, value, count)
+		count = count or data.len - offset
+		value = value % 256
+
+		check_bounds(data, offset, count)
+
+		for i = offset, offset + count - 1 do
+			data[i] = value
+		end
+	end
+
+	function buffer.readu8(data, offset)
+		return read_unsigned(data, offset, 1)
+	end
+
+	function buffer.readi8(data, offset)
+		return read_signed(data, offset, 1)
+	end
+
+	function buffer.readu16(data, offset)
+		return read_unsigned(data, offset, 2)
+	end
+
+	function buffer.readi16(data, offset)
+		return read_signed(data, offset, 2)
+	end
+
+	function buffer.readu32(data, offset)
+		return read_unsigned(data, offset, 4)
+	end
+
+	function buffer.readi32(data, offset)
+		return read_signed(data, offset, 4)
+	end
+
+	function buffer.readf32(data, offset)
+		return from_f32_bits(read_unsigned(data, offset, 4))
+	end
+
+	function buffer.readf64(data, offset)
+		local low = read_unsigned(data, offset, 4)
+		local high = read_unsigned(data, offset + 4, 4)
+
+		return from_f64_bits(low, high)
+	end
+
+	function buffer.writeu8(data, offset, value)
+		write_unsigned(data, offset, 1, value)
+	end
+
+	function buffer.writeu16(data, offset, value)
+		write_unsigned(data, offset, 2, value)
+	end
+
+	function buffer.writeu32(data, offset, value)
+		write_unsigned(data, offset, 4, value)
+	end
+
+	function buffer.writef32(data, offset, value)
+		write_unsigned(data, offset, 4, into_f32_bits(value))
+	end
+
+	function buffer.writef64(data, offset, value)
+		local low, high = into_f64_bits(value)
+
+		write_unsigned(data, offset, 4, low)
+		write_unsigned(data, offset + 4, 4, high)
+	end
+
+	return buffer
+end)()
+
@@ -0,0 +1,248 @@
+-- Buffers can also be tables of little endian 32 bit words indexed from 0,
+-- so that accesses within one word are a single lookup, with floats going
+-- through the byte layout above
+buffer = (function(bytes)
+	local buffer = {}
+
+	local math_floor = math.floor
+
+	local string_byte = string.byte
+	local string_char = string.char
+	local string_sub = string.sub
+
+	local function check_bounds(data, offset, size)
+		if offset < 0 or offset + size > data.len then
+			error("buffer access out of bounds", 3)
+		end
+	end
+
+	local function read_byte(data, offset)
+		local word = data[math_floor(offset / 4)] or 0
+
+		return math_floor(word / 2 ^ (offset % 4 * 8)) % 256
+	end
+
+	local function write_byte(data, offset, value)
+		local index = math_floor(offset / 4)
+		local word = data[index] or 0
+		local shift = 2 ^ (offset % 4 * 8)
+		local old = math_floor(word / shift) % 256
+
+		data[index] = word + (value - old) * shift
+	end
+
+	local function read_unsigned(data, offset, size)
+		check_bounds(data, offset, size)
+
+		local rest = offset % 4
+
+		if rest + size <= 4 then
+			local word = data[(offset - rest) / 4] or 0
+
+			return math_floor(word / 2 ^ (rest * 8)) % 2 ^ (size * 8)
+		end
+
+		local result = 0
+
+		for i = size - 1, 0, -1 do
+			result = result * 256 + read_byte(data, offset + i)
+		end
+
+		return result
+	end
+
+	local function read_signed(data, offset, size)
+		local result = read_unsigned(data, offset, size)
+		local limit = 2 ^ (size * 8)
+
+		if result >= limit / 2 then
+			result = result - limit
+		end
+
+		return result
+	end
+
+	local function write_unsigned(data, offset, size, value)
+		check_bounds(data, offset, size)
+
+		value = math_floor(value) % 2 ^ (size * 8)
+
+		local rest = offset % 4
+
+		if rest + size <= 4 then
+			local index = (offset - rest) / 4
+			local word = data[index] or 0
+			local shift = 2 ^ (rest * 8)
+			local old = math_floor(word / shift) % 2 ^ (size * 8)
+
+			data[index] = word + (value - old) * shift
+
+			return
+		end
+
+		for i = 0, size - 1 do
+			local byte = value % 256
+
+			write_byte(data, offset + i, byte)
+			value = (value - byte) / 256
+		end
+	end
+
+	function buffer.create(len)
+		return { len = len }
+	end
+
+	function buffer.len(data)
+		return data.len
+	end
+
+	function buffer.fromstring(str)
+		local data = { len = #str }
+
+		for i = 1, #str, 4 do
+			local b_1, b_2, b_3, b_4 = string_byte(str, i, i + 3)
+
+			data[(i - 1) / 4] = b_1 + (b_2 or 0) * 0x100 + (b_3 or 0) * 0x10000 + (b_4 or 0) * 0x1000000
+		end
+
+		return data
+	end
+
+	function buffer.tostring(data)
+		local list = {}
+
+		for i = 0, math_floor((data.len + 3) / 4) - 1 do
+			local word = data[i] or 0
+			local b_1 = word % 256
+			local b_2 = math_floor(word / 0x100) % 256
+			local b_3 = math_floor(word / 0x10000) % 256
+			local b_4 = math_floor(word / 0x1000000)
+
+			list[i + 1] = string_char(b_1, b_2, b_3, b_4)
+		end
+
+		return string_sub(table.concat(list), 1, data.len)
+	end
+
+	function buffer.copy(target, target_offset, source, source_offset, count)
+		source_offset = source_offset or 0
+		count = count or source.len - source_offset
+
+		check_bounds(target, target_offset, count)
+		check_bounds(source, source_offset, count)
+
+		-- Whole words are moved as they are when both sides line up
+		if target_offset % 4 == 0 and source_offset % 4 == 0 and count % 4 == 0 then
+			local first_1 = target_offset / 4
+			local first_2 = source_offset / 4
+
+			if target == source and first_1 > first_2 then
+				for i = count / 4 - 1, 0, -1 do
+					target[first_1 + i] = source[first_2 + i]
+				end
+			else
+				for i = 0, count / 4 - 1 do
+					target[first_1 + i] = source[first_2 + i]
+				end
+			end
+		elseif target == source and target_offset > source_offset then
+			for i = count - 1, 0, -1 do
+				write_byte(target, target_offset + i, read_byte(source, source_offset + i))
+			end
+		else
+			for i = 0, count - 1 do
+				write_byte(target, target_offset + i, read_byte(source, source_offset + i))
+			end
+		end
+	end
+
+	function buffer.fill(data, offset, value, count)
+		count = count or data.len - offset
+		value = value % 256
+
+		check_bounds(data, offset, count)
+
+		local last = offset + count
+
+		while offset < last and offset % 4 ~= 0 do
+			write_byte(data, offset, value)
+			offset = offset + 1
+		end
+
+		local word = value * 0x1010101
+
+		while offset + 4 <= last do
+			data[offset / 4] = word
+			offset = offset + 4
+		end
+
+		while offset < last do
+			write_byte(data, offset, value)
+			offset = offset + 1
+		end
+	end
+
+	function buffer.readu8(data, offset)
+		return read_unsigned(data, offset, 1)
+	end
+
+	function buffer.readi8(data, offset)
+		return read_signed(data, offset, 1)
+	end
+
+	function buffer.readu16(data, offset)
+		return read_unsigned(data, offset, 2)
+	end
+
+	function buffer.readi16(data, offset)
+		return read_signed(data, offset, 2)
+	end
+
+	function buffer.readu32(data, offset)
+		return read_unsigned(data, offset, 4)
+	end
+
+	function buffer.readi32(data, offset)
+		return read_signed(data, offset, 4)
+	end
+
+	function buffer.writeu8(data, offset, value)
+		write_unsigned(data, offset, 1, value)
+	end
+
+	function buffer.writeu16(data, offset, value)
+		write_unsigned(data, offset, 2, value)
+	end
+
+	function buffer.writeu32(data, offset, value)
+		write_unsigned(data, offset, 4, value)
+	end
+
+	local scratch = bytes.create(8)
+
+	function buffer.readf32(data, offset)
+		bytes.writeu32(scratch, 0, read_unsigned(data, offset, 4))
+
+		return bytes.readf32(scratch, 0)
+	end
+
+	function buffer.readf64(data, offset)
+		bytes.writeu32(scratch, 0, read_unsigned(data, offset, 4))
+		bytes.writeu32(scratch, 4, read_unsigned(data, offset + 4, 4))
+
+		return bytes.readf64(scratch, 0)
+	end
+
+	function buffer.writef32(data, offset, value)
+		bytes.writef32(scratch, 0, value)
+		write_unsigned(data, offset, 4, bytes.readu32(scratch, 0))
+	end
+
+	function buffer.writef64(data, offset, value)
+		bytes.writef64(scratch, 0, value)
+		write_unsigned(data, offset, 4, bytes.readu32(scratch, 0))
+		write_unsigned(data, offset + 4, 4, bytes.readu32(scratch, 4))
+	end
+
+	return buffer
+end)(buffer)
@@ -0,0 +1,2368 @@
+-- Constants past 31 bits are written in decimal, since Lua 5.3 wraps hex
+-- literals that overflow an integer and Fengari integers are only 32 bits
+
+-- Functions are kept in tables named after the operation they perform, which
+-- are handed back together as `rt`. Everything lives inside a closure to stay
+-- clear of the local variable limit, leaving room for the operations that
+-- translated code binds to locals of its own
+local rt = (function()
+	local rt_add = {}
+	local rt_allocator = {}
+	local rt_atomic = {}
+	local rt_band = {}
+	local rt_bnot = {}
+	local rt_bor = {}
+	local rt_bxor = {}
+	local rt_clz = {}
+	local rt_convert = {}
+	local rt_copysign = {}
+	local rt_ctz = {}
+	local rt_data = {}
+	local rt_demote = {}
+	local rt_div = {}
+	local rt_eq = {}
+	local rt_extend = {}
+	local rt_ge = {}
+	local rt_gt = {}
+	local rt_i64 = {}
+	local rt_le = {}
+	local rt_load = {}
+	local rt_lt = {}
+	local rt_max = {}
+	local rt_min = {}
+	local rt_mul = {}
+	local rt_ne = {}
+	local rt_nearest = {}
+	local rt_neg = {}
+	local rt_popcnt = {}
+	local rt_promote = {}
+	local rt_reference = {}
+	local rt_reinterpret = {}
+	local rt_rem = {}
+	local rt_rotl = {}
+	local rt_rotr = {}
+	local rt_saturate = {}
+	local rt_shl = {}
+	local rt_shr = {}
+	local rt_sqrt = {}
+	local rt_store = {}
+	local rt_sub = {}
+	local rt_table = {}
+	local rt_truncate = {}
+	local rt_wrap = {}
+
+	local function no_op(num)
+		return num
+	end
+
+	rt_promote.f64_f32 = no_op
+	rt_convert.f64_u32 = no_op
+
+	-- Traps are raised as a table naming their kind, their message, and the index
+	-- of the function they were raised in, unless the instance is configured with
+	-- "message", which raises just their message, or with a handler whose result
+	-- is raised instead
+	local rt_trap = (function()
+		local trap = {}
+
+		local TRAP_MT = {
+			__tostring = function(data)
+				return data.info
+			end,
+		}
+
+		-- Functions are registered by every instance so that the innermost one
+		-- on the stack can be named when a trap is raised
+		local FUNC_INDEX = setmetatable({}, { __mode = "k" })
+
+		local debug_getinfo = debug and debug.getinfo
+
+		local on_trap = nil
+
+		function trap.configure(value)
+			on_trap = value
+		end
+
+		function trap.register(func_list, first, last)
+			for i = first, last do
+				local func = func_list[i]
+
+				if func then
+					FUNC_INDEX[func] = i
+				end
+			end
+		end
+
+		local function find_func()
+			if not debug_getinfo then
+				return nil
+			end
+
+			local level = 1
+
+			while true do
+				local info = debug_getinfo(level, "f")
+
+				if info == nil then
+					return nil
+				end
+
+				local index = FUNC_INDEX[info.func]
+
+				if index then
+					return index
+				end
+
+				level = level + 1
+			end
+		end
+
+		function trap.raise(kind, info)
+			if on_trap == "message" then
+				error(info, 0)
+			end
+
+			local func = find_func()
+
+			if on_trap == nil or on_trap == "value" then
+				error(setmetatable({ kind = kind, info = info, func = func }, TRAP_MT), 0)
+			else
+				error(on_trap(kind, info, func) or info, 0)
+			end
+		end
+
+		return trap
+	end)()
+
+	local function trap_unless(condition, kind, info)
+		if not condition then
+			rt_trap.raise(kind, info)
+		end
+	end
+
+	local bit_lshift = bit32.lshift
+	local bit_rshift = bit32.rshift
+	local bit_arshift = bit32.arshift
+
+	local bit_and = bit32.band
+	local bit_or = bit32.bor
+	local bit_xor = bit32.bxor
+	local bit_not = bit32.bnot
+
+	local bit_extract = bit32.extract
+	local bit_replace = bit32.replace
+
+	-- X: a[0 __21]
+	-- Y: a[22__31]
+	--  | b[0 __11]
+	-- Z: b[12__31]
+	local function constructor(x, y, z)
+		return { X = x, Y = y, Z = z }
+	end
+
+	rt_i64.ZERO = constructor(0, 0, 0)
+
+	function rt_i64.from_u32(data_1, data_2)
+		local x = bit_and(data_1, 0x3FFFFF)
+		local y = bit_and(data_2, 0x3FFFFF)
+		local z = bit_replace(bit_rshift(data_1, 22), bit_rshift(data_2, 22), 10, 10)
+
+		return constructor(x, y, z)
+	end
+
+	function rt_i64.is_zero(value)
+		return value.X == 0 and value.Y == 0 and value.Z == 0
+	end
+
+	local function load_d1(value)
+		return bit_replace(bit_and(value.X, 0x3FFFFF), value.Z, 22, 10)
+	end
+
+	local function load_d2(value)
+		return bit_replace(bit_and(value.Y, 0x3FFFFF), bit_rshift(value.Z, 10), 22, 10)
+	end
+
+	function rt_i64.into_u32(value)
+		local x, y, z = value.X, value.Y, value.Z
+		return bit_replace(bit_and(x, 0x3FFFFF), z, 22, 10), bit_replace(bit_and(y, 0x3FFFFF), bit_rshift(z, 10), 22, 10)
+	end
+
+	function rt_i64.from_u64(value)
+		return rt_i64.from_u32(bit_and(value % 4294967296), bit_and(value / 4294967296))
+	end
+
+	function rt_convert.f64_u64(value)
+		local value_1, value_2 = rt_i64.into_u32(value)
+		return value_1 + value_2 * 4294967296
+	end
+
+	rt_i64.ONE = rt_i64.from_u64(1)
+	local NUM_SIX_FOUR = rt_i64.from_u64(64)
+	local NUM_BIT_26 = rt_i64.from_u64(0x4000000)
+	local NUM_BIT_52 = rt_i64.from_u64(4503599627370496)
+
+	function rt_add.i64(lhs, rhs)
+		local lhs_1, lhs_2 = rt_i64.into_u32(lhs)
+		local rhs_1, rhs_2 = rt_i64.into_u32(rhs)
+		local data_1 = lhs_1 + rhs_1
+		local data_2 = lhs_2 + rhs_2
+
+		if data_1 >= 4294967296 then
+			data_1 = data_1 - 4294967296
+			data_2 = data_2 + 1
+		end
+
+		if data_2 >= 4294967296 then
+			data_2 = data_2 - 4294967296
+		end
+
+		return rt_i64.from_u32(data_1, data_2)
+	end
+
+	function rt_sub.i64(lhs, rhs)
+		local lhs_1, lhs_2 = rt_i64.into_u32(lhs)
+		local rhs_1, rhs_2 = rt_i64.into_u32(rhs)
+		local data_1 = lhs_1 - rhs_1
+		local data_2 = lhs_2 - rhs_2
+
+		if data_1 < 0 then
+			data_1 = data_1 + 4294967296
+			data_2 = data_2 - 1
+		end
+
+		if data_2 < 0 then
+			data_2 = data_2 + 4294967296
+		end
+
+		return rt_i64.from_u32(data_1, data_2)
+	end
+
+	function rt_lt.u64(lhs, rhs)
+		local data_l_2 = load_d2(lhs)
+		local data_r_2 = load_d2(rhs)
+
+		return data_l_2 < data_r_2 or (data_l_2 == data_r_2 and load_d1(lhs) < load_d1(rhs))
+	end
+
+	function rt_mul.i64(lhs, rhs)
+		if rt_i64.is_zero(lhs) or rt_i64.is_zero(rhs) then
+			return rt_i64.ZERO
+		elseif rt_lt.u64(lhs, NUM_BIT_26) and rt_lt.u64(rhs, NUM_BIT_26) then
+			return rt_i64.from_u64(load_d1(lhs) * load_d1(rhs))
+		end
+
+		-- Divide each long into 4 chunks of 16 bits, and then add up 4x4 products_
+		-- We can skip products that would overflow_
+		local lhs_1, lhs_2 = rt_i64.into_u32(lhs)
+		local rhs_1, rhs_2 = rt_i64.into_u32(rhs)
+
+		local a48 = bit_rshift(lhs_2, 16)
+		local a32 = bit_and(lhs_2, 0xFFFF)
+		local a16 = bit_rshift(lhs_1, 16)
+		local a00 = bit_and(lhs_1, 0xFFFF)
+
+		local b48 = bit_rshift(rhs_2, 16)
+		local b32 = bit_and(rhs_2, 0xFFFF)
+		local b16 = bit_rshift(rhs_1, 16)
+		local b00 = bit_and(rhs_1, 0xFFFF)
+
+		local c00 = a00 * b00
+		local c16 = bit_rshift(c00, 16)
+
+		c00 = bit_and(c00, 0xFFFF)
+		c16 = c16 + a16 * b00
+
+		local c32 = bit_rshift(c16, 16)
+
+		c16 = bit_and(c16, 0xFFFF)
+		c16 = c16 + a00 * b16
+		c32 = c32 + bit_rshift(c16, 16)
+		c16 = bit_and(c16, 0xFFFF)
+		c32 = c32 + a32 * b00
+
+		local c48 = bit_rshift(c32, 16)
+
+		c32 = bit_and(c32, 0xFFFF)
+		c32 = c32 + a16 * b16
+		c48 = c48 + bit_rshift(c32, 16)
+		c32 = bit_and(c32, 0xFFFF)
+		c32 = c32 + a00 * b32
+		c48 = c48 + bit_rshift(c32, 16)
+		c32 = bit_and(c32, 0xFFFF)
+		c48 = c48 + a48 * b00 + a32 * b16 + a16 * b32 + a00 * b48
+		c48 = bit_and(c48, 0xFFFF)
+
+		local data_1 = bit_replace(c00, c16, 16, 16)
+		local data_2 = bit_replace(c32, c48, 16, 16)
+
+		return rt_i64.from_u32(data_1, data_2)
+	end
+
+	function rt_bor.i64(lhs, rhs)
+		local x = bit_or(lhs.X, rhs.X)
+		local y = bit_or(lhs.Y, rhs.Y)
+		local z = bit_or(lhs.Z, rhs.Z)
+
+		return constructor(x, y, z)
+	end
+
+	function rt_shl.i64(lhs, rhs)
+		local count = rhs.X % 64
+
+		if count == 0 then
+			return lhs
+		elseif count < 32 then
+			local pad = 32 - count
+			local lhs_1, lhs_2 = rt_i64.into_u32(lhs)
+
+			local data_1 = bit_lshift(lhs_1, count)
+			local data_2 = bit_replace(bit_rshift(lhs_1, pad), lhs_2, count, pad)
+
+			return rt_i64.from_u32(data_1, data_2)
+		else
+			local lhs_1 = load_d1(lhs)
+
+			return rt_i64.from_u32(0, bit_lshift(lhs_1, count - 32))
+		end
+	end
+
+	function rt_div.u64(lhs, rhs)
+		if rt_i64.is_zero(rhs) then
+			rt_trap.raise("integer_divide_by_zero", "division by zero")
+		elseif rt_i64.is_zero(lhs) then
+			return rt_i64.ZERO, rt_i64.ZERO
+		elseif rt_lt.u64(lhs, NUM_BIT_52) and rt_lt.u64(rhs, NUM_BIT_52) then
+			local lhs_u = rt_convert.f64_u64(lhs)
+			local rhs_u = rt_convert.f64_u64(rhs)
+
+			return rt_i64.from_u64(lhs_u / rhs_u), rt_i64.from_u64(lhs_u % rhs_u)
+		end
+
+		local quotient = rt_i64.ZERO
+		local remainder = rt_i64.ZERO
+
+		local num_1, num_2 = rt_i64.into_u32(lhs)
+
+		for i = 63, 0, -1 do
+			local rem_1, rem_2 = rt_i64.into_u32(rt_shl.i64(remainder, rt_i64.ONE))
+
+			if i > 31 then
+				rem_1 = bit_or(rem_1, bit_extract(num_2, i - 32, 1))
+			else
+				rem_1 = bit_or(rem_1, bit_extract(num_1, i, 1))
+			end
+
+			remainder = rt_i64.from_u32(rem_1, rem_2)
+
+			if not rt_lt.u64(remainder, rhs) then
+				remainder = rt_sub.i64(remainder, rhs)
+				quotient = rt_bor.i64(quotient, rt_shl.i64(rt_i64.ONE, rt_i64.from_u32(i, 0)))
+			end
+		end
+
+		return quotient, remainder
+	end
+
+	function rt_i64.is_negative(value)
+		return value.Z >= 0x80000
+	end
+
+	function rt_i64.negate(value)
+		local value_1, value_2 = rt_i64.into_u32(value)
+		local data_1 = bit_not(value_1) + 1
+		local data_2 = bit_not(value_2)
+
+		if data_1 >= 4294967296 then
+			data_1 = data_1 - 4294967296
+			data_2 = data_2 + 1
+		end
+
+		if data_2 >= 4294967296 then
+			data_2 = data_2 - 4294967296
+		end
+
+		return rt_i64.from_u32(data_1, data_2)
+	end
+
+	function rt_div.i64(lhs, rhs)
+		local left_negative = rt_i64.is_negative(lhs)
+		local right_negative = rt_i64.is_negative(rhs)
+
+		if left_negative then
+			lhs = rt_i64.negate(lhs)
+		end
+
+		if right_negative then
+			rhs = rt_i64.negate(rhs)
+		end
+
+		local quotient, remainder = rt_div.u64(lhs, rhs)
+
+		if left_negative ~= right_negative then
+			quotient = rt_i64.negate(quotient)
+		elseif rt_i64.is_negative(quotient) then
+			-- Only the least value divided by -1 leaves a quotient past the
+			-- greatest one
+			rt_trap.raise("integer_overflow", "integer overflow")
+		end
+
+		if left_negative then
+			remainder = rt_i64.negate(remainder)
+		end
+
+		return quotient, remainder
+	end
+
+	function rt_band.i64(lhs, rhs)
+		local x = bit_and(lhs.X, rhs.X)
+		local y = bit_and(lhs.Y, rhs.Y)
+		local z = bit_and(lhs.Z, rhs.Z)
+
+		return constructor(x, y, z)
+	end
+
+	function rt_bnot.i64(value)
+		local x = bit_and(bit_not(value.X), 0xFFFFFF)
+		local y = bit_and(bit_not(value.Y), 0xFFFFFF)
+		local z = bit_and(bit_not(value.Z), 0xFFFFFF)
+
+		return constructor(x, y, z)
+	end
+
+	function rt_bxor.i64(lhs, rhs)
+		local x = bit_xor(lhs.X, rhs.X)
+		local y = bit_xor(lhs.Y, rhs.Y)
+		local z = bit_xor(lhs.Z, rhs.Z)
+
+		return constructor(x, y, z)
+	end
+
+	function rt_shr.u64(lhs, rhs)
+		local count = rhs.X % 64
+
+		if count == 0 then
+			return lhs
+		elseif count < 32 then
+			local lhs_1, lhs_2 = rt_i64.into_u32(lhs)
+
+			local data_1 = bit_replace(bit_rshift(lhs_1, count), lhs_2, 32 - count, count)
+			local data_2 = bit_rshift(lhs_2, count)
+
+			return rt_i64.from_u32(data_1, data_2)
+		else
+			local lhs_2 = load_d2(lhs)
+
+			return rt_i64.from_u32(bit_rshift(lhs_2, count - 32), 0)
+		end
+	end
+
+	function rt_shr.i64(lhs, rhs)
+		local count = rhs.X % 64
+
+		if count == 0 then
+			return lhs
+		elseif count < 32 then
+			local lhs_1, lhs_2 = rt_i64.into_u32(lhs)
+
+			local data_1 = bit_replace(bit_rshift(lhs_1, count), lhs_2, 32 - count, count)
+			local data_2 = bit_arshift(lhs_2, count)
+
+			return rt_i64.from_u32(data_1, data_2)
+		else
+			local lhs_2 = load_d2(lhs)
+
+			local data_1 = bit_arshift(lhs_2, count - 32)
+			local data_2 = lhs_2 >= 2147483648 and 4294967295 or 0
+
+			return rt_i64.from_u32(data_1, data_2)
+		end
+	end
+
+	function rt_rotl.i64(lhs, rhs)
+		if rt_i64.is_zero(rhs) then
+			return lhs
+		else
+			local data_1 = rt_shl.i64(lhs, rhs)
+			local data_2 = rt_shr.u64(lhs, rt_sub.i64(NUM_SIX_FOUR, rhs))
+
+			return rt_bor.i64(data_1, data_2)
+		end
+	end
+
+	function rt_rotr.i64(lhs, rhs)
+		if rt_i64.is_zero(rhs) then
+			return lhs
+		else
+			local data_1 = rt_shr.u64(lhs, rhs)
+			local data_2 = rt_shl.i64(lhs, rt_sub.i64(NUM_SIX_FOUR, rhs))
+
+			return rt_bor.i64(data_1, data_2)
+		end
+	end
+
+	function rt_eq.i64(lhs, rhs)
+		return lhs.X == rhs.X and lhs.Y == rhs.Y and lhs.Z == rhs.Z
+	end
+
+	function rt_gt.u64(lhs, rhs)
+		local data_l_2 = load_d2(lhs)
+		local data_r_2 = load_d2(rhs)
+
+		return data_l_2 > data_r_2 or (data_l_2 == data_r_2 and load_d1(lhs) > load_d1(rhs))
+	end
+
+	function rt_lt.i64(lhs, rhs)
+		local neg_a = rt_i64.is_negative(lhs)
+		local neg_b = rt_i64.is_negative(rhs)
+
+		if neg_a and not neg_b then
+			return true
+		elseif not neg_a and neg_b then
+			return false
+		else
+			return rt_i64.is_negative(rt_sub.i64(lhs, rhs))
+		end
+	end
+
+	function rt_gt.i64(lhs, rhs)
+		local neg_a = rt_i64.is_negative(lhs)
+		local neg_b = rt_i64.is_negative(rhs)
+
+		if neg_a and not neg_b then
+			return false
+		elseif not neg_a and neg_b then
+			return true
+		else
+			return rt_i64.is_negative(rt_sub.i64(rhs, lhs))
+		end
+	end
+
+	function rt_convert.f64_i32(num)
+		return bit_xor(num, 2147483648) - 2147483648
+	end
+
+
+	local math_abs = math.abs
+	local math_modf = math.modf
+	local math_min = math.min
+	local math_max = math.max
+
+	function rt_add.i32(lhs, rhs)
+		return bit_or(lhs + rhs, 0)
+	end
+
+	function rt_sub.i32(lhs, rhs)
+		return bit_or(lhs - rhs, 0)
+	end
+
+	function rt_mul.i32(lhs, rhs)
+		if (lhs + rhs) < 0x8000000 then
+			return bit_or(lhs * rhs, 0)
+		else
+			local a16 = bit_rshift(lhs, 16)
+			local a00 = bit_and(lhs, 0xFFFF)
+			local b16 = bit_rshift(rhs, 16)
+			local b00 = bit_and(rhs, 0xFFFF)
+
+			local c00 = a00 * b00
+			local c16 = a16 * b00 + a00 * b16
+
+			return bit_or(c00 + bit_lshift(c16, 16), 0)
+		end
+	end
+
+	function rt_div.i32(lhs, rhs)
+		trap_unless(rhs ~= 0, "integer_divide_by_zero", "division by zero")
+
+		lhs = rt_convert.f64_i32(lhs)
+		rhs = rt_convert.f64_i32(rhs)
+
+		trap_unless(lhs ~= -2147483648 or rhs ~= -1, "integer_overflow", "integer overflow")
+
+		return bit_or(math_modf(lhs / rhs), 0)
+	end
+
+	function rt_div.u32(lhs, rhs)
+		trap_unless(rhs ~= 0, "integer_divide_by_zero", "division by zero")
+
+		return bit_or(math_modf(lhs / rhs), 0)
+	end
+
+	function rt_rem.u32(lhs, rhs)
+		trap_unless(rhs ~= 0, "integer_divide_by_zero", "division by zero")
+
+		return lhs % rhs
+	end
+
+	function rt_rem.i32(lhs, rhs)
+		trap_unless(rhs ~= 0, "integer_divide_by_zero", "division by zero")
+
+		lhs = rt_convert.f64_i32(lhs)
+		rhs = rt_convert.f64_i32(rhs)
+
+		return bit_or(math.fmod(lhs, rhs), 0)
+	end
+
+	function rt_rem.i64(lhs, rhs)
+		local left_negative = rt_i64.is_negative(lhs)
+
+		if left_negative then
+			lhs = rt_i64.negate(lhs)
+		end
+
+		if rt_i64.is_negative(rhs) then
+			rhs = rt_i64.negate(rhs)
+		end
+
+		local _, remainder = rt_div.u64(lhs, rhs)
+
+		if left_negative then
+			remainder = rt_i64.negate(remainder)
+		end
+
+		return remainder
+	end
+
+	function rt_rem.u64(lhs, rhs)
+		local _, remainder = rt_div.u64(lhs, rhs)
+
+		return remainder
+	end
+
+	function rt_neg.f64(num)
+		return -num
+	end
+
+	rt_neg.f32 = rt_neg.f64
+
+	function rt_min.f64(lhs, rhs)
+		if rhs == rhs then
+			return math_min(lhs, rhs)
+		else
+			return rhs
+		end
+	end
+
+	rt_min.f32 = rt_min.f64
+
+	function rt_max.f64(lhs, rhs)
+		if rhs == rhs then
+			return math_max(lhs, rhs)
+		else
+			return rhs
+		end
+	end
+
+	rt_max.f32 = rt_max.f64
+
+	local buffer_create = buffer.create
+
+	local RE_INSTANCE = buffer_create(8)
+
+	local buffer_write_f64 = buffer.writef64
+	local buffer_read_i8 = buffer.readi8
+
+	function rt_copysign.f64(lhs, rhs)
+		buffer_write_f64(RE_INSTANCE, 0, rhs)
+
+		if buffer_read_i8(RE_INSTANCE, 7) >= 0 then
+			return (math_abs(lhs))
+		else
+			return -math_abs(lhs)
+		end
+	end
+
+	rt_copysign.f32 = rt_copysign.f64
+
+	-- Halfway cases are first rounded away from zero, and then back towards it
+	-- when that made them odd
+	function rt_nearest.f32(num)
+		local result
+
+		if num < 0 then
+			result = -math.floor(0.5 - num)
+		else
+			result = math.floor(num + 0.5)
+		end
+
+		if (math_abs(num) + 0.5) % 2 ~= 1 then
+			return result
+		elseif result > 0 then
+			return result - 1
+		else
+			return result + 1
+		end
+	end
+
+	rt_nearest.f64 = rt_nearest.f32
+
+	local bit_countlz = bit32.countlz
+	local bit_countrz = bit32.countrz
+
+	function rt_popcnt.i32(num)
+		num = num - bit_and(bit_rshift(num, 1), 0x55555555)
+		num = bit_and(num, 0x33333333) + bit_and(bit_rshift(num, 2), 0x33333333)
+		num = bit_and((num + bit_rshift(num, 4)), 0x0F0F0F0F)
+		num = num + bit_rshift(num, 8)
+		num = num + bit_rshift(num, 16)
+		return bit_and(num, 0x0000003F)
+	end
+
+	function rt_clz.i64(num)
+		local data_1, data_2 = rt_i64.into_u32(num)
+		local temp
+
+		if data_2 == 0 then
+			temp = bit_countlz(data_1) + 32
+		else
+			temp = bit_countlz(data_2)
+		end
+
+		return rt_i64.from_u32(temp, 0)
+	end
+
+	function rt_ctz.i64(num)
+		local data_1, data_2 = rt_i64.into_u32(num)
+		local temp
+
+		if data_1 == 0 then
+			temp = bit_countrz(data_2) + 32
+		else
+			temp = bit_countrz(data_1)
+		end
+
+		return rt_i64.from_u32(temp, 0)
+	end
+
+	function rt_popcnt.i64(num)
+		local data_1, data_2 = rt_i64.into_u32(num)
+		local temp = rt_popcnt.i32(data_1) + rt_popcnt.i32(data_2)
+
+		return rt_i64.from_u32(temp, 0)
+	end
+
+	function rt_le.i32(lhs, rhs)
+		return rt_convert.f64_i32(lhs) <= rt_convert.f64_i32(rhs)
+	end
+
+	function rt_lt.i32(lhs, rhs)
+		return rt_convert.f64_i32(lhs) < rt_convert.f64_i32(rhs)
+	end
+
+	function rt_ge.i32(lhs, rhs)
+		return rt_convert.f64_i32(lhs) >= rt_convert.f64_i32(rhs)
+	end
+
+	function rt_gt.i32(lhs, rhs)
+		return rt_convert.f64_i32(lhs) > rt_convert.f64_i32(rhs)
+	end
+
+	function rt_ne.i64(lhs, rhs)
+		return not rt_eq.i64(lhs, rhs)
+	end
+
+	function rt_le.i64(lhs, rhs)
+		return rt_lt.i64(lhs, rhs) or rt_eq.i64(lhs, rhs)
+	end
+
+	function rt_le.u64(lhs, rhs)
+		return rt_lt.u64(lhs, rhs) or rt_eq.i64(lhs, rhs)
+	end
+
+	function rt_ge.i64(lhs, rhs)
+		return rt_gt.i64(lhs, rhs) or rt_eq.i64(lhs, rhs)
+	end
+
+	function rt_ge.u64(lhs, rhs)
+		return rt_gt.u64(lhs, rhs) or rt_eq.i64(lhs, rhs)
+	end
+
+	function rt_shl.i32(lhs, rhs)
+		return bit_lshift(lhs, rhs % 32)
+	end
+
+	function rt_shr.u32(lhs, rhs)
+		return bit_rshift(lhs, rhs % 32)
+	end
+
+	function rt_shr.i32(lhs, rhs)
+		return bit_arshift(lhs, rhs % 32)
+	end
+
+	function rt_rotl.i32(lhs, rhs)
+		return bit32.lrotate(lhs, rhs % 32)
+	end
+
+	function rt_rotr.i32(lhs, rhs)
+		return bit32.rrotate(lhs, rhs % 32)
+	end
+
+	local math_ceil = math.ceil
+	local math_floor = math.floor
+
+	local function math_clamp(num, min, max)
+		return math_min(math_max(num, min), max)
+	end
+
+	local NUM_MIN_I64 = rt_i64.from_u32(0, 2147483648)
+	local NUM_MAX_I64 = rt_i64.from_u32(4294967295, 0x7FFFFFFF)
+	local NUM_MAX_U64 = rt_i64.from_u32(4294967295, 4294967295)
+
+	function rt_truncate.f64(num)
+		if num >= 0 then
+			return math_floor(num)
+		else
+			return math_ceil(num)
+		end
+	end
+
+	rt_truncate.u32_f64 = rt_truncate.f64
+	rt_truncate.u32_f32 = rt_truncate.f64
+	rt_truncate.f32 = rt_truncate.f64
+
+	function rt_wrap.i32_i64(num)
+		local data_1, _ = rt_i64.into_u32(num)
+
+		return data_1
+	end
+
+	function rt_truncate.i32_f64(num)
+		return bit_or(rt_truncate.f64(num), 0)
+	end
+
+	rt_truncate.i32_f32 = rt_truncate.i32_f64
+
+	function rt_truncate.i64_f64(num)
+		if num < 0 then
+			local temp = rt_i64.from_u64(-num)
+
+			return rt_i64.negate(temp)
+		else
+			return rt_i64.from_u64(num)
+		end
+	end
+
+	rt_truncate.i64_f32 = rt_truncate.i64_f64
+
+	function rt_truncate.u64_f64(num)
+		if num <= 0 then
+			return rt_i64.ZERO
+		else
+			return rt_i64.from_u64(math_floor(num))
+		end
+	end
+
+	rt_truncate.u64_f32 = rt_truncate.u64_f64
+
+	-- NaN passes every bound it is compared with, so it is turned to zero first
+	function rt_saturate.i32_f64(num)
+		if num ~= num then
+			return 0
+		end
+
+		local temp = math_clamp(rt_truncate.f64(num), -2147483648, 0x7FFFFFFF)
+
+		return bit_or(temp, 0)
+	end
+
+	rt_saturate.i32_f32 = rt_saturate.i32_f64
+
+	function rt_saturate.u32_f64(num)
+		if num ~= num then
+			return 0
+		end
+
+		return math_clamp(rt_truncate.f64(num), 0, 4294967295)
+	end
+
+	rt_saturate.u32_f32 = rt_saturate.u32_f64
+
+	function rt_saturate.i64_f64(num)
+		if num ~= num then
+			return rt_i64.ZERO
+		elseif num >= 2 ^ 63 - 1 then
+			return NUM_MAX_I64
+		elseif num <= -2 ^ 63 then
+			return NUM_MIN_I64
+		else
+			return rt_truncate.i64_f64(num)
+		end
+	end
+
+	rt_saturate.i64_f32 = rt_saturate.i64_f64
+
+	function rt_saturate.u64_f64(num)
+		if num ~= num then
+			return rt_i64.ZERO
+		elseif num >= 2 ^ 64 then
+			return NUM_MAX_U64
+		elseif num <= 0 then
+			return rt_i64.ZERO
+		else
+			return rt_truncate.i64_f64(num)
+		end
+	end
+
+	rt_saturate.u64_f32 = rt_saturate.u64_f64
+
+	function rt_extend.i32_n8(num)
+		num = bit_and(num, 0xFF)
+
+		if num >= 0x80 then
+			return bit_or(num - 0x100, 0)
+		else
+			return num
+		end
+	end
+
+	function rt_extend.i32_n16(num)
+		num = bit_and(num, 0xFFFF)
+
+		if num >= 0x8000 then
+			return bit_or(num - 0x10000, 0)
+		else
+			return num
+		end
+	end
+
+	function rt_extend.i64_n8(num)
+		local data_1, _ = rt_i64.into_u32(num)
+
+		data_1 = bit_and(data_1, 0xFF)
+
+		if data_1 >= 0x80 then
+			local temp = rt_i64.from_u32(-data_1 + 0x100, 0)
+
+			return rt_i64.negate(temp)
+		else
+			return rt_i64.from_u32(data_1, 0)
+		end
+	end
+
+	function rt_extend.i64_n16(num)
+		local data_1, _ = rt_i64.into_u32(num)
+
+		data_1 = bit_and(data_1, 0xFFFF)
+
+		if data_1 >= 0x8000 then
+			local temp = rt_i64.from_u32(-data_1 + 0x10000, 0)
+
+			return rt_i64.negate(temp)
+		else
+			return rt_i64.from_u32(data_1, 0)
+		end
+	end
+
+	function rt_extend.i64_n32(num)
+		local data_1, _ = rt_i64.into_u32(num)
+
+		if data_1 >= 2147483648 then
+			local temp = rt_i64.from_u32(-data_1 + 4294967296, 0)
+
+			return rt_i64.negate(temp)
+		else
+			return rt_i64.from_u32(data_1, 0)
+		end
+	end
+
+	function rt_extend.i64_i32(num)
+		if num >= 2147483648 then
+			local temp = rt_i64.from_u32(-num + 4294967296, 0)
+
+			return rt_i64.negate(temp)
+		else
+			return rt_i64.from_u32(num, 0)
+		end
+	end
+
+	function rt_extend.i64_u32(num)
+		return rt_i64.from_u32(num, 0)
+	end
+
+	function rt_convert.f64_i64(num)
+		if rt_i64.is_negative(num) then
+			local temp = rt_i64.negate(num)
+
+			return -rt_convert.f64_u64(temp)
+		else
+			return rt_convert.f64_u64(num)
+		end
+	end
+
+	local buffer_read_f32 = buffer.readf32
+	local buffer_read_f64 = buffer.readf64
+	local buffer_read_u32 = buffer.readu32
+
+	local buffer_write_f32 = buffer.writef32
+	local buffer_write_u32 = buffer.writeu32
+
+	-- Converting between floats and doubles quiets signaling NaNs, so the payload
+	-- of a NaN is moved between the two by hand, which only keeps it where the
+	-- `buffer` in use can read the bits of a NaN, as the Lua 5.1 one cannot
+	function rt_reinterpret.i32_f32(num)
+		if num ~= num then
+			buffer_write_f64(RE_INSTANCE, 0, num)
+
+			local data_1 = buffer_read_u32(RE_INSTANCE, 0)
+			local data_2 = buffer_read_u32(RE_INSTANCE, 4)
+			local payload = bit_replace(bit_rshift(data_1, 29), data_2, 3, 20)
+
+			if payload ~= 0 then
+				return bit_or(bit_and(data_2, 0x80000000), 0x7F800000, payload)
+			end
+		end
+
+		buffer_write_f32(RE_INSTANCE, 0, num)
+
+		return buffer_read_u32(RE_INSTANCE, 0)
+	end
+
+	function rt_reinterpret.i64_f64(num)
+		buffer_write_f64(RE_INSTANCE, 0, num)
+
+		local data_1 = buffer_read_u32(RE_INSTANCE, 0)
+		local data_2 = buffer_read_u32(RE_INSTANCE, 4)
+
+		return rt_i64.from_u32(data_1, data_2)
+	end
+
+	function rt_reinterpret.f32_i32(num)
+		if bit_and(num, 0x7F800000) == 0x7F800000 and bit_and(num, 0x7FFFFF) ~= 0 then
+			local data_2 = bit_or(bit_and(num, 0x80000000), 0x7FF00000, bit_rshift(bit_and(num, 0x7FFFFF), 3))
+
+			buffer_write_u32(RE_INSTANCE, 0, bit_lshift(num, 29))
+			buffer_write_u32(RE_INSTANCE, 4, data_2)
+
+			return buffer_read_f64(RE_INSTANCE, 0)
+		end
+
+		buffer_write_u32(RE_INSTANCE, 0, num)
+
+		return buffer_read_f32(RE_INSTANCE, 0)
+	end
+
+	function rt_reinterpret.f64_i64(num)
+		local data_1, data_2 = rt_i64.into_u32(num)
+
+		buffer_write_u32(RE_INSTANCE, 0, data_1)
+		buffer_write_u32(RE_INSTANCE, 4, data_2)
+
+		return buffer_read_f64(RE_INSTANCE, 0)
+	end
+
+	local math_sqrt = math.sqrt
+
+	-- Doubles hold more than twice the bits of a float, so rounding the exact
+	-- result of an operation on two floats to a double and then to a float gives
+	-- the same float as rounding it once
+	function rt_demote.f32_f64(num)
+		buffer_write_f32(RE_INSTANCE, 0, num)
+
+		return buffer_read_f32(RE_INSTANCE, 0)
+	end
+
+	rt_convert.f32_u32 = rt_demote.f32_f64
+
+	function rt_add.f32(lhs, rhs)
+		return rt_demote.f32_f64(lhs + rhs)
+	end
+
+	function rt_sub.f32(lhs, rhs)
+		return rt_demote.f32_f64(lhs - rhs)
+	end
+
+	function rt_mul.f32(lhs, rhs)
+		return rt_demote.f32_f64(lhs * rhs)
+	end
+
+	function rt_div.f32(lhs, rhs)
+		return rt_demote.f32_f64(lhs / rhs)
+	end
+
+	function rt_sqrt.f32(num)
+		return rt_demote.f32_f64(math_sqrt(num))
+	end
+
+	function rt_convert.f32_i32(num)
+		return rt_demote.f32_f64(rt_convert.f64_i32(num))
+	end
+
+	-- Values past 2^53 would round twice on their way to a float, so the bits
+	-- a double cannot hold are folded into one that only keeps them from
+	-- looking like a tie
+	function rt_convert.f32_u64(num)
+		local data_1, data_2 = rt_i64.into_u32(num)
+
+		if data_2 >= 0x200000 then
+			local sticky = data_1 % 0x800 ~= 0 and 0x800 or 0
+
+			data_1 = data_1 - data_1 % 0x800 + sticky
+		end
+
+		return rt_demote.f32_f64(data_1 + data_2 * 4294967296)
+	end
+
+	function rt_convert.f32_i64(num)
+		if rt_i64.is_negative(num) then
+			local temp = rt_i64.negate(num)
+
+			return -rt_convert.f32_u64(temp)
+		else
+			return rt_convert.f32_u64(num)
+		end
+	end
+
+	local string_sub = string.sub
+
+	local buffer_to_string = buffer.tostring
+	local buffer_from_string = buffer.fromstring
+
+	local buffer_len = buffer.len
+	local buffer_copy = buffer.copy
+	local buffer_fill = buffer.fill
+
+	local buffer_read_u8 = buffer.readu8
+	local buffer_read_i16 = buffer.readi16
+	local buffer_read_u16 = buffer.readu16
+	local buffer_read_i32 = buffer.readi32
+
+	local buffer_write_u8 = buffer.writeu8
+	local buffer_write_u16 = buffer.writeu16
+
+	function rt_load.i32_i8(memory, addr)
+		return bit_or(buffer_read_i8(memory.data, addr), 0)
+	end
+
+	function rt_load.i32_u8(memory, addr)
+		return buffer_read_u8(memory.data, addr)
+	end
+
+	function rt_load.i32_i16(memory, addr)
+		return bit_or(buffer_read_i16(memory.data, addr), 0)
+	end
+
+	function rt_load.i32_u16(memory, addr)
+		return buffer_read_u16(memory.data, addr)
+	end
+
+	function rt_load.i32(memory, addr)
+		return buffer_read_u32(memory.data, addr)
+	end
+
+	function rt_load.i64_i8(memory, addr)
+		local value = buffer_read_i8(memory.data, addr)
+
+		if value >= 0 then
+			return rt_i64.from_u32(value, 0)
+		else
+			return rt_i64.from_u32(value + 4294967296, 4294967295)
+		end
+	end
+
+	function rt_load.i64_u8(memory, addr)
+		return rt_i64.from_u32(buffer_read_u8(memory.data, addr), 0)
+	end
+
+	function rt_load.i64_i16(memory, addr)
+		local value = buffer_read_i16(memory.data, addr)
+
+		if value >= 0 then
+			return rt_i64.from_u32(value, 0)
+		else
+			return rt_i64.from_u32(value + 4294967296, 4294967295)
+		end
+	end
+
+	function rt_load.i64_u16(memory, addr)
+		return rt_i64.from_u32(buffer_read_u16(memory.data, addr), 0)
+	end
+
+	function rt_load.i64_i32(memory, addr)
+		local value = buffer_read_i32(memory.data, addr)
+
+		if value >= 0 then
+			return rt_i64.from_u32(value, 0)
+		else
+			return rt_i64.from_u32(value + 4294967296, 4294967295)
+		end
+	end
+
+	function rt_load.i64_u32(memory, addr)
+		return rt_i64.from_u32(buffer_read_u32(memory.data, addr), 0)
+	end
+
+	function rt_load.i64(memory, addr)
+		local data = memory.data
+		local value_1 = buffer_read_u32(data, addr)
+		local value_2 = buffer_read_u32(data, addr + 4)
+
+		return rt_i64.from_u32(value_1, value_2)
+	end
+
+	function rt_load.f32(memory, addr)
+		return buffer_read_f32(memory.data, addr)
+	end
+
+	function rt_load.f64(memory, addr)
+		return buffer_read_f64(memory.data, addr)
+	end
+
+	function rt_load.v128(memory, addr)
+		local value = buffer_create(16)
+
+		buffer_copy(value, 0, memory.data, addr, 16)
+
+		return value
+	end
+
+	function rt_load.string(memory, addr, len)
+		local temp = buffer_create(len)
+
+		buffer_copy(temp, 0, memory.data, addr, len)
+
+		return buffer_to_string(temp)
+	end
+
+	function rt_store.i32_n8(memory, addr, value)
+		buffer_write_u8(memory.data, addr, value)
+	end
+
+	function rt_store.i32_n16(memory, addr, value)
+		buffer_write_u16(memory.data, addr, value)
+	end
+
+	function rt_store.i32(memory, addr, value)
+		buffer_write_u32(memory.data, addr, value)
+	end
+
+	function rt_store.i64_n8(memory, addr, value)
+		local value_1, _ = rt_i64.into_u32(value)
+
+		buffer_write_u8(memory.data, addr, value_1)
+	end
+
+	function rt_store.i64_n16(memory, addr, value)
+		local value_1, _ = rt_i64.into_u32(value)
+
+		buffer_write_u16(memory.data, addr, value_1)
+	end
+
+	function rt_store.i64_n32(memory, addr, value)
+		local value_1, _ = rt_i64.into_u32(value)
+
+		buffer_write_u32(memory.data, addr, value_1)
+	end
+
+	function rt_store.i64(memory, addr, value)
+		local data = memory.data
+		local value_1, value_2 = rt_i64.into_u32(value)
+
+		buffer_write_u32(data, addr, value_1)
+		buffer_write_u32(data, addr + 4, value_2)
+	end
+
+	function rt_store.f32(memory, addr, value)
+		buffer_write_f32(memory.data, addr, value)
+	end
+
+	function rt_store.f64(memory, addr, value)
+		buffer_write_f64(memory.data, addr, value)
+	end
+
+	function rt_store.v128(memory, addr, value)
+		buffer_copy(memory.data, addr, value, 0, 16)
+	end
+
+	function rt_store.string(memory, addr, data, len)
+		local content = data
+
+		if len and len ~= #data then
+			content = string_sub(data, 1, len)
+		end
+
+		local temp = buffer_from_string(content)
+
+		buffer_copy(memory.data, addr, temp)
+	end
+
+	function rt_store.copy(memory_1, addr_1, memory_2, addr_2, len)
+		buffer_copy(memory_1.data, addr_1, memory_2.data, addr_2, len)
+	end
+
+	function rt_store.fill(memory, addr, len, value)
+		buffer_fill(memory.data, addr, value, len)
+	end
+
+	function rt_store.init(memory, addr, data, offset, len)
+		local temp = buffer_from_string(data or "")
+
+		buffer_copy(memory.data, addr, temp, offset, len)
+	end
+
+	-- Every 4 bytes, read as a little endian word, are written as 5 digits in
+	-- base 85 from `#` up with `\` skipped, or as `z` when the word is zero
+	function rt_data.decode(text, len)
+		local temp = buffer_create(math_ceil(len / 4) * 4)
+		local offset = 0
+		local position = 1
+
+		while position <= #text do
+			local value = 0
+
+			if string.byte(text, position) == 122 then
+				position = position + 1
+			else
+				for i = position, position + 4 do
+					local digit = string.byte(text, i) - 35
+
+					if digit > 57 then
+						digit = digit - 1
+					end
+
+					value = value * 85 + digit
+				end
+
+				position = position + 5
+			end
+
+			buffer_write_u32(temp, offset, value)
+			offset = offset + 4
+		end
+
+		return string_sub(buffer_to_string(temp), 1, len)
+	end
+
+	local WASM_PAGE_SIZE = 65536
+
+	function rt_allocator.new(min, max)
+		return { max = max, data = buffer_create(min * WASM_PAGE_SIZE) }
+	end
+
+	function rt_allocator.size(memory)
+		return buffer_len(memory.data) / WASM_PAGE_SIZE
+	end
+
+	-- Failing to make the larger buffer is a failed grow rather than an error
+	function rt_allocator.grow(memory, num)
+		local old = rt_allocator.size(memory)
+		local new = old + num
+
+		if new > memory.max then
+			return 4294967295
+		end
+
+		local success, reallocated = pcall(buffer_create, new * WASM_PAGE_SIZE)
+
+		if not success then
+			return 4294967295
+		end
+
+		buffer_copy(reallocated, 0, memory.data)
+
+		memory.data = reallocated
+
+		return old
+	end
+
+	-- Memories indexed by `i64` count their pages in `i64` too, with a failed
+	-- grow giving back all ones
+	function rt_allocator.size_64(memory)
+		return rt_i64.from_u64(rt_allocator.size(memory))
+	end
+
+	function rt_allocator.grow_64(memory, num)
+		local old = rt_allocator.grow(memory, rt_convert.f64_u64(num))
+
+		if old == 4294967295 then
+			return rt_i64.from_u32(4294967295, 4294967295)
+		end
+
+		return rt_i64.from_u64(old)
+	end
+
+	local function table_move(source, first, last, offset, target)
+		if target == source and offset > first then
+			for i = last - first, 0, -1 do
+				target[offset + i] = source[first + i]
+			end
+		else
+			for i = 0, last - first do
+				target[offset + i] = source[first + i]
+			end
+		end
+	end
+
+	function rt_table.init(target, addr, data, offset, len)
+		local count = data and data.n or 0
+
+		trap_unless(offset + len <= count, "out_of_bounds_table_access", "out of bounds table access")
+		trap_unless(addr + len <= target.min, "out_of_bounds_table_access", "out of bounds table access")
+
+		table_move(data or target.data, offset + 1, offset + len, addr, target.data)
+	end
+
+	function rt_table.get(target, index)
+		trap_unless(index < target.min, "out_of_bounds_table_access", "out of bounds table access")
+
+		return target.data[index]
+	end
+
+	function rt_table.set(target, index, value)
+		trap_unless(index < target.min, "out_of_bounds_table_access", "out of bounds table access")
+
+		target.data[index] = value
+	end
+
+	function rt_table.grow(target, num, value)
+		local old = target.min
+		local new = old + num
+
+		if new > target.max then
+			return 4294967295
+		end
+
+		for i = old, new - 1 do
+			target.data[i] = value
+		end
+
+		target.min = new
+
+		return old
+	end
+
+	function rt_table.copy(target_1, addr_1, target_2, addr_2, len)
+		trap_unless(addr_1 + len <= target_1.min, "out_of_bounds_table_access", "out of bounds table access")
+		trap_unless(addr_2 + len <= target_2.min, "out_of_bounds_table_access", "out of bounds table access")
+
+		table_move(target_2.data, addr_2, addr_2 + len - 1, addr_1, target_1.data)
+	end
+
+	function rt_table.fill(target, addr, len, value)
+		trap_unless(addr + len <= target.min, "out_of_bounds_table_access", "out of bounds table access")
+
+		for i = addr, addr + len - 1 do
+			target.data[i] = value
+		end
+	end
+
+	-- Snapshots of memories are their raw bytes, so their length gives the number
+	-- of pages to restore, while those of tables hold the references themselves
+	-- and only restore into the same Lua state
+	local rt_snapshot = (function()
+		local memory = {}
+		local wasm_table = {}
+		local global = {}
+
+		function memory.save(target)
+			return buffer_to_string(target.data)
+		end
+
+		function memory.load(target, data)
+			local len = #data
+
+			assert(len % WASM_PAGE_SIZE == 0 and len / WASM_PAGE_SIZE <= target.max, "snapshot does not fit the memory")
+
+			target.data = buffer_from_string(data)
+		end
+
+		function wasm_table.save(target)
+			local data = {}
+
+			table_move(target.data, 0, target.min - 1, 0, data)
+
+			return { min = target.min, data = data }
+		end
+
+		function wasm_table.load(target, saved)
+			assert(saved.min <= target.max, "snapshot does not fit the table")
+
+			for i = saved.min, target.min - 1 do
+				target.data[i] = nil
+			end
+
+			table_move(saved.data, 0, saved.min - 1, 0, target.data)
+			target.min = saved.min
+		end
+
+		function global.save(target)
+			return target.value
+		end
+
+		function global.load(target, value)
+			target.value = value
+		end
+
+		return { memory = memory, table = wasm_table, global = global }
+	end)()
+
+	rt_table.save = rt_snapshot.table.save
+	rt_table.load = rt_snapshot.table.load
+
+	-- Strings are exchanged with host code as their raw bytes, with C strings
+	-- read up to their first zero byte, and every access checked against the
+	-- size of the memory
+	local rt_string = (function()
+		local wasm_string = {}
+
+		local function check_bounds(memory, addr, len)
+			local inside = addr >= 0 and addr + len <= buffer_len(memory.data)
+
+			trap_unless(inside, "out_of_bounds_memory_access", "out of bounds memory access")
+		end
+
+		function wasm_string.read(memory, addr, len)
+			check_bounds(memory, addr, len)
+
+			return rt_load.string(memory, addr, len)
+		end
+
+		function wasm_string.read_cstr(memory, addr)
+			local data = memory.data
+			local last = buffer_len(data)
+			local len = 0
+
+			while addr >= 0 and addr + len < last and buffer_read_u8(data, addr + len) ~= 0 do
+				len = len + 1
+			end
+
+			check_bounds(memory, addr, len + 1)
+
+			return rt_load.string(memory, addr, len)
+		end
+
+		function wasm_string.write(memory, addr, data)
+			check_bounds(memory, addr, #data)
+			rt_store.string(memory, addr, data)
+		end
+
+		return wasm_string
+	end)()
+
+	-- There is only ever one thread, so nothing can wake a waiter
+	function rt_atomic.wait(value, expected, timeout)
+		if value ~= expected then
+			return 1
+		end
+
+		trap_unless(not rt_lt.i64(timeout, rt_i64.ZERO), "blocking_wait", "wait would block forever")
+
+		return 2
+	end
+
+	function rt_atomic.notify(memory, addr, count)
+		return 0
+	end
+
+	function rt_reference.select(condition, on_true, on_false)
+		if condition ~= 0 then
+			return on_true
+		end
+
+		return on_false
+	end
+
+	function rt_reference.as_non_null(value)
+		trap_unless(value ~= nil, "null_reference", "null reference")
+
+		return value
+	end
+
+	-- Traps never carry this metatable, so only tables that do are
+	-- exceptions that a `catch_all` is allowed to handle
+	local rt_exception = (function()
+		local exception = {}
+
+		local EXCEPTION_MT = {}
+
+		function exception.throw(tag, ...)
+			local data = setmetatable({ ... }, EXCEPTION_MT)
+
+			data.tag = tag
+			data.skip = 0
+
+			error(data, 0)
+		end
+
+		-- Delegated exceptions must first pass through `skip` handlers
+		function exception.accept(data)
+			if getmetatable(data) ~= EXCEPTION_MT then
+				return false
+			elseif data.skip ~= 0 then
+				data.skip = data.skip - 1
+
+				return false
+			end
+
+			return true
+		end
+
+		function exception.delegate(data, skip)
+			if exception.accept(data) then
+				data.skip = skip
+			end
+
+			error(data, 0)
+		end
+
+		return exception
+	end)()
+
+	-- Vectors are 16 byte buffers that are never modified after creation.
+	-- Everything lives inside a closure to stay clear of the local variable limit.
+	local rt_simd = (function()
+		local simd = {}
+
+		local math_sqrt = math.sqrt
+
+		local function write_u8(data, offset, value)
+			buffer_write_u8(data, offset, bit_and(value, 0xFF))
+		end
+
+		local function write_u16(data, offset, value)
+			buffer_write_u16(data, offset, bit_and(value, 0xFFFF))
+		end
+
+		local function write_u32(data, offset, value)
+			buffer_write_u32(data, offset, bit_or(value, 0))
+		end
+
+		local function read_i64(data, offset)
+			return rt_i64.from_u32(buffer_read_u32(data, offset), buffer_read_u32(data, offset + 4))
+		end
+
+		local function write_i64(data, offset, value)
+			local value_1, value_2 = rt_i64.into_u32(value)
+
+			buffer_write_u32(data, offset, value_1)
+			buffer_write_u32(data, offset + 4, value_2)
+		end
+
+		local function shape(read, write, size)
+			return { read = read, write = write, size = size, len = 16 / size }
+		end
+
+		local I8 = shape(buffer_read_i8, write_u8, 1)
+		local U8 = shape(buffer_read_u8, write_u8, 1)
+		local I16 = shape(buffer_read_i16, write_u16, 2)
+		local U16 = shape(buffer_read_u16, write_u16, 2)
+		local I32 = shape(buffer_read_i32, write_u32, 4)
+		local U32 = shape(buffer_read_u32, write_u32, 4)
+		local I64 = shape(read_i64, write_i64, 8)
+		local F32 = shape(buffer_read_f32, buffer_write_f32, 4)
+		local F64 = shape(buffer_read_f64, buffer_write_f64, 8)
+
+		simd.ZERO = buffer_create(16)
+
+		local function map_unary(lane, func)
+			local read, write, size = lane.read, lane.write, lane.size
+
+			return function(value)
+				local result = buffer_create(16)
+
+				for i = 0, 15, size do
+					write(result, i, func(read(value, i)))
+				end
+
+				return result
+			end
+		end
+
+		local function map_binary(lane, func)
+			local read, write, size = lane.read, lane.write, lane.size
+
+			return function(lhs, rhs)
+				local result = buffer_create(16)
+
+				for i = 0, 15, size do
+					write(result, i, func(read(lhs, i), read(rhs, i)))
+				end
+
+				return result
+			end
+		end
+
+		local function map_convert(lane, output, len, offset, func)
+			local read, write = lane.read, output.write
+			local size_1, size_2 = lane.size, output.size
+
+			return function(value)
+				local result = buffer_create(16)
+
+				for i = 0, len - 1 do
+					write(result, i * size_2, func(read(value, (i + offset) * size_1)))
+				end
+
+				return result
+			end
+		end
+
+		local function map_compare(lane, func)
+			local read, size = lane.read, lane.size
+
+			return function(lhs, rhs)
+				local result = buffer_create(16)
+
+				for i = 0, 15, size do
+					if func(read(lhs, i), read(rhs, i)) then
+						buffer_fill(result, i, 0xFF, size)
+					end
+				end
+
+				return result
+			end
+		end
+
+		local function map_shift(lane, func)
+			local read, write, size = lane.read, lane.write, lane.size
+			local bits = size * 8
+
+			return function(value, count)
+				local result = buffer_create(16)
+
+				count = count % bits
+
+				for i = 0, 15, size do
+					write(result, i, func(read(value, i), count))
+				end
+
+				return result
+			end
+		end
+
+		local function map_narrow(lane, output, low, high)
+			local read, write = lane.read, output.write
+			local size_1, size_2, len = lane.size, output.size, lane.len
+
+			return function(lhs, rhs)
+				local result = buffer_create(16)
+
+				for i = 0, len - 1 do
+					write(result, i * size_2, math_clamp(read(lhs, i * size_1), low, high))
+					write(result, (i + len) * size_2, math_clamp(read(rhs, i * size_1), low, high))
+				end
+
+				return result
+			end
+		end
+
+		local function map_pairwise(lane, output)
+			local read, write = lane.read, output.write
+			local size_1, size_2 = lane.size, output.size
+
+			return function(value)
+				local result = buffer_create(16)
+
+				for i = 0, output.len - 1 do
+					local offset = i * 2 * size_1
+
+					write(result, i * size_2, read(value, offset) + read(value, offset + size_1))
+				end
+
+				return result
+			end
+		end
+
+		local function map_extend_mul(lane, output, offset, func)
+			local read, write = lane.read, output.write
+			local size_1, size_2 = lane.size, output.size
+
+			return function(lhs, rhs)
+				local result = buffer_create(16)
+
+				for i = 0, output.len - 1 do
+					local index = (i + offset) * size_1
+
+					write(result, i * size_2, func(read(lhs, index), read(rhs, index)))
+				end
+
+				return result
+			end
+		end
+
+		local function extract_lane(lane, func)
+			local read, size = lane.read, lane.size
+
+			return function(value, index)
+				return func(read(value, index * size))
+			end
+		end
+
+		local function replace_lane(lane)
+			local write, size = lane.write, lane.size
+
+			return function(value, num, index)
+				local result = buffer_create(16)
+
+				buffer_copy(result, 0, value, 0, 16)
+				write(result, index * size, num)
+
+				return result
+			end
+		end
+
+		local function splat(lane)
+			local write, size = lane.write, lane.size
+
+			return function(num)
+				local result = buffer_create(16)
+
+				for i = 0, 15, size do
+					write(result, i, num)
+				end
+
+				return result
+			end
+		end
+
+		local function all_true(lane)
+			local read, size = lane.read, lane.size
+
+			return function(value)
+				for i = 0, 15, size do
+					if read(value, i) == 0 then
+						return 0
+					end
+				end
+
+				return 1
+			end
+		end
+
+		local function bitmask(lane)
+			local read, size = lane.read, lane.size
+
+			return function(value)
+				local result = 0
+
+				for i = 0, lane.len - 1 do
+					if read(value, i * size) < 0 then
+						result = bit_or(result, bit_lshift(1, i))
+					end
+				end
+
+				return result
+			end
+		end
+
+		local function to_u32(num)
+			return bit_or(num, 0)
+		end
+
+		local function is_eq(lhs, rhs)
+			return lhs == rhs
+		end
+
+		local function is_ne(lhs, rhs)
+			return lhs ~= rhs
+		end
+
+		local function is_lt(lhs, rhs)
+			return lhs < rhs
+		end
+
+		local function is_gt(lhs, rhs)
+			return lhs > rhs
+		end
+
+		local function is_le(lhs, rhs)
+			return lhs <= rhs
+		end
+
+		local function is_ge(lhs, rhs)
+			return lhs >= rhs
+		end
+
+		local function add(lhs, rhs)
+			return lhs + rhs
+		end
+
+		local function sub(lhs, rhs)
+			return lhs - rhs
+		end
+
+		local function mul(lhs, rhs)
+			return lhs * rhs
+		end
+
+		local function div(lhs, rhs)
+			return lhs / rhs
+		end
+
+		local function average(lhs, rhs)
+			return bit_rshift(lhs + rhs + 1, 1)
+		end
+
+		local function p_min(lhs, rhs)
+			if rhs < lhs then
+				return rhs
+			else
+				return lhs
+			end
+		end
+
+		local function p_max(lhs, rhs)
+			if lhs < rhs then
+				return rhs
+			else
+				return lhs
+			end
+		end
+
+		local function add_saturate(low, high)
+			return function(lhs, rhs)
+				return math_clamp(lhs + rhs, low, high)
+			end
+		end
+
+		local function sub_saturate(low, high)
+			return function(lhs, rhs)
+				return math_clamp(lhs - rhs, low, high)
+			end
+		end
+
+		local function truncate_saturate(func)
+			return function(num)
+				if num ~= num then
+					return 0
+				else
+					return func(num)
+				end
+			end
+		end
+
+		local function shift_i64(func)
+			return function(lhs, rhs)
+				return func(lhs, rt_i64.from_u32(rhs, 0))
+			end
+		end
+
+		local function abs_i64(num)
+			if rt_i64.is_negative(num) then
+				return rt_i64.negate(num)
+			else
+				return num
+			end
+		end
+
+		local function mul_i64_i32(lhs, rhs)
+			return rt_mul.i64(rt_extend.i64_i32(lhs), rt_extend.i64_i32(rhs))
+		end
+
+		local function mul_i64_u32(lhs, rhs)
+			return rt_mul.i64(rt_extend.i64_u32(lhs), rt_extend.i64_u32(rhs))
+		end
+
+		local saturate_i32 = truncate_saturate(rt_saturate.i32_f64)
+		local saturate_u32 = truncate_saturate(rt_saturate.u32_f64)
+
+		function simd.from_u32(data_1, data_2, data_3, data_4)
+			local result = buffer_create(16)
+
+			buffer_write_u32(result, 0, data_1)
+			buffer_write_u32(result, 4, data_2)
+			buffer_write_u32(result, 8, data_3)
+			buffer_write_u32(result, 12, data_4)
+
+			return result
+		end
+
+		function simd.i8x16_shuffle(lhs, rhs, lanes)
+			local result = buffer_create(16)
+
+			for i = 0, 15 do
+				local index = buffer_read_u8(lanes, i)
+
+				if index < 16 then
+					buffer_write_u8(result, i, buffer_read_u8(lhs, index))
+				else
+					buffer_write_u8(result, i, buffer_read_u8(rhs, index - 16))
+				end
+			end
+
+			return result
+		end
+
+		function simd.i8x16_swizzle(lhs, rhs)
+			local result = buffer_create(16)
+
+			for i = 0, 15 do
+				local index = buffer_read_u8(rhs, i)
+
+				if index < 16 then
+					buffer_write_u8(result, i, buffer_read_u8(lhs, index))
+				end
+			end
+
+			return result
+		end
+
+		simd.i8x16_extract_lane_s = extract_lane(I8, to_u32)
+		simd.i8x16_extract_lane_u = extract_lane(U8, no_op)
+		simd.i16x8_extract_lane_s = extract_lane(I16, to_u32)
+		simd.i16x8_extract_lane_u = extract_lane(U16, no_op)
+		simd.i32x4_extract_lane = extract_lane(U32, no_op)
+		simd.i64x2_extract_lane = extract_lane(I64, no_op)
+		simd.f32x4_extract_lane = extract_lane(F32, no_op)
+		simd.f64x2_extract_lane = extract_lane(F64, no_op)
+
+		simd.i8x16_replace_lane = replace_lane(U8)
+		simd.i16x8_replace_lane = replace_lane(U16)
+		simd.i32x4_replace_lane = replace_lane(U32)
+		simd.i64x2_replace_lane = replace_lane(I64)
+		simd.f32x4_replace_lane = replace_lane(F32)
+		simd.f64x2_replace_lane = replace_lane(F64)
+
+		simd.i8x16_splat = splat(U8)
+		simd.i16x8_splat = splat(U16)
+		simd.i32x4_splat = splat(U32)
+		simd.i64x2_splat = splat(I64)
+		simd.f32x4_splat = splat(F32)
+		simd.f64x2_splat = splat(F64)
+
+		simd.i8x16_eq = map_compare(U8, is_eq)
+		simd.i8x16_ne = map_compare(U8, is_ne)
+		simd.i8x16_lt_s = map_compare(I8, is_lt)
+		simd.i8x16_lt_u = map_compare(U8, is_lt)
+		simd.i8x16_gt_s = map_compare(I8, is_gt)
+		simd.i8x16_gt_u = map_compare(U8, is_gt)
+		simd.i8x16_le_s = map_compare(I8, is_le)
+		simd.i8x16_le_u = map_compare(U8, is_le)
+		simd.i8x16_ge_s = map_compare(I8, is_ge)
+		simd.i8x16_ge_u = map_compare(U8, is_ge)
+
+		simd.i16x8_eq = map_compare(U16, is_eq)
+		simd.i16x8_ne = map_compare(U16, is_ne)
+		simd.i16x8_lt_s = map_compare(I16, is_lt)
+		simd.i16x8_lt_u = map_compare(U16, is_lt)
+		simd.i16x8_gt_s = map_compare(I16, is_gt)
+		simd.i16x8_gt_u = map_compare(U16, is_gt)
+		simd.i16x8_le_s = map_compare(I16, is_le)
+		simd.i16x8_le_u = map_compare(U16, is_le)
+		simd.i16x8_ge_s = map_compare(I16, is_ge)
+		simd.i16x8_ge_u = map_compare(U16, is_ge)
+
+		simd.i32x4_eq = map_compare(U32, is_eq)
+		simd.i32x4_ne = map_compare(U32, is_ne)
+		simd.i32x4_lt_s = map_compare(I32, is_lt)
+		simd.i32x4_lt_u = map_compare(U32, is_lt)
+		simd.i32x4_gt_s = map_compare(I32, is_gt)
+		simd.i32x4_gt_u = map_compare(U32, is_gt)
+		simd.i32x4_le_s = map_compare(I32, is_le)
+		simd.i32x4_le_u = map_compare(U32, is_le)
+		simd.i32x4_ge_s = map_compare(I32, is_ge)
+		simd.i32x4_ge_u = map_compare(U32, is_ge)
+
+		simd.i64x2_eq = map_compare(I64, rt_eq.i64)
+		simd.i64x2_ne = map_compare(I64, rt_ne.i64)
+		simd.i64x2_lt_s = map_compare(I64, rt_lt.i64)
+		simd.i64x2_gt_s = map_compare(I64, rt_gt.i64)
+		simd.i64x2_le_s = map_compare(I64, rt_le.i64)
+		simd.i64x2_ge_s = map_compare(I64, rt_ge.i64)
+
+		simd.f32x4_eq = map_compare(F32, is_eq)
+		simd.f32x4_ne = map_compare(F32, is_ne)
+		simd.f32x4_lt = map_compare(F32, is_lt)
+		simd.f32x4_gt = map_compare(F32, is_gt)
+		simd.f32x4_le = map_compare(F32, is_le)
+		simd.f32x4_ge = map_compare(F32, is_ge)
+
+		simd.f64x2_eq = map_compare(F64, is_eq)
+		simd.f64x2_ne = map_compare(F64, is_ne)
+		simd.f64x2_lt = map_compare(F64, is_lt)
+		simd.f64x2_gt = map_compare(F64, is_gt)
+		simd.f64x2_le = map_compare(F64, is_le)
+		simd.f64x2_ge = map_compare(F64, is_ge)
+
+		simd.v128_not = map_unary(U32, bit_not)
+		simd.v128_and = map_binary(U32, bit_and)
+		simd.v128_or = map_binary(U32, bit_or)
+		simd.v128_xor = map_binary(U32, bit_xor)
+
+		simd.v128_andnot = map_binary(U32, function(lhs, rhs)
+			return bit_and(lhs, bit_not(rhs))
+		end)
+
+		function simd.v128_bitselect(lhs, rhs, mask)
+			local result = buffer_create(16)
+
+			for i = 0, 15, 4 do
+				local bits = buffer_read_u32(mask, i)
+				local data_1 = bit_and(buffer_read_u32(lhs, i), bits)
+				local data_2 = bit_and(buffer_read_u32(rhs, i), bit_not(bits))
+
+				buffer_write_u32(result, i, bit_or(data_1, data_2))
+			end
+
+			return result
+		end
+
+		function simd.v128_any_true(value)
+			for i = 0, 15, 4 do
+				if buffer_read_u32(value, i) ~= 0 then
+					return 1
+				end
+			end
+
+			return 0
+		end
+
+		simd.i8x16_abs = map_unary(I8, math_abs)
+		simd.i8x16_neg = map_unary(I8, rt_neg.f64)
+		simd.i8x16_popcnt = map_unary(U8, rt_popcnt.i32)
+		simd.i8x16_all_true = all_true(U8)
+		simd.i8x16_bitmask = bitmask(I8)
+		simd.i8x16_narrow_i16x8_s = map_narrow(I16, I8, -0x80, 0x7F)
+		simd.i8x16_narrow_i16x8_u = map_narrow(I16, U8, 0, 0xFF)
+		simd.i8x16_shl = map_shift(U8, bit_lshift)
+		simd.i8x16_shr_s = map_shift(I8, bit_arshift)
+		simd.i8x16_shr_u = map_shift(U8, bit_rshift)
+		simd.i8x16_add = map_binary(U8, add)
+		simd.i8x16_add_sat_s = map_binary(I8, add_saturate(-0x80, 0x7F))
+		simd.i8x16_add_sat_u = map_binary(U8, add_saturate(0, 0xFF))
+		simd.i8x16_sub = map_binary(U8, sub)
+		simd.i8x16_sub_sat_s = map_binary(I8, sub_saturate(-0x80, 0x7F))
+		simd.i8x16_sub_sat_u = map_binary(U8, sub_saturate(0, 0xFF))
+		simd.i8x16_min_s = map_binary(I8, math_min)
+		simd.i8x16_min_u = map_binary(U8, math_min)
+		simd.i8x16_max_s = map_binary(I8, math_max)
+		simd.i8x16_max_u = map_binary(U8, math_max)
+		simd.i8x16_avgr_u = map_binary(U8, average)
+
+		simd.i16x8_extadd_pairwise_i8x16_s = map_pairwise(I8, I16)
+		simd.i16x8_extadd_pairwise_i8x16_u = map_pairwise(U8, I16)
+		simd.i16x8_abs = map_unary(I16, math_abs)
+		simd.i16x8_neg = map_unary(I16, rt_neg.f64)
+
+		simd.i16x8_q15mulr_sat_s = map_binary(I16, function(lhs, rhs)
+			return math_clamp(bit_arshift(lhs * rhs + 0x4000, 15), -0x8000, 0x7FFF)
+		end)
+
+		simd.i16x8_all_true = all_true(U16)
+		simd.i16x8_bitmask = bitmask(I16)
+		simd.i16x8_narrow_i32x4_s = map_narrow(I32, I16, -0x8000, 0x7FFF)
+		simd.i16x8_narrow_i32x4_u = map_narrow(I32, U16, 0, 0xFFFF)
+		simd.i16x8_extend_low_i8x16_s = map_convert(I8, I16, 8, 0, no_op)
+		simd.i16x8_extend_high_i8x16_s = map_convert(I8, I16, 8, 8, no_op)
+		simd.i16x8_extend_low_i8x16_u = map_convert(U8, I16, 8, 0, no_op)
+		simd.i16x8_extend_high_i8x16_u = map_convert(U8, I16, 8, 8, no_op)
+		simd.i16x8_shl = map_shift(U16, bit_lshift)
+		simd.i16x8_shr_s = map_shift(I16, bit_arshift)
+		simd.i16x8_shr_u = map_shift(U16, bit_rshift)
+		simd.i16x8_add = map_binary(U16, add)
+		simd.i16x8_add_sat_s = map_binary(I16, add_saturate(-0x8000, 0x7FFF))
+		simd.i16x8_add_sat_u = map_binary(U16, add_saturate(0, 0xFFFF))
+		simd.i16x8_sub = map_binary(U16, sub)
+		simd.i16x8_sub_sat_s = map_binary(I16, sub_saturate(-0x8000, 0x7FFF))
+		simd.i16x8_sub_sat_u = map_binary(U16, sub_saturate(0, 0xFFFF))
+		simd.i16x8_mul = map_binary(U16, mul)
+		simd.i16x8_min_s = map_binary(I16, math_min)
+		simd.i16x8_min_u = map_binary(U16, math_min)
+		simd.i16x8_max_s = map_binary(I16, math_max)
+		simd.i16x8_max_u = map_binary(U16, math_max)
+		simd.i16x8_avgr_u = map_binary(U16, average)
+		simd.i16x8_extmul_low_i8x16_s = map_extend_mul(I8, I16, 0, mul)
+		simd.i16x8_extmul_high_i8x16_s = map_extend_mul(I8, I16, 8, mul)
+		simd.i16x8_extmul_low_i8x16_u = map_extend_mul(U8, I16, 0, mul)
+		simd.i16x8_extmul_high_i8x16_u = map_extend_mul(U8, I16, 8, mul)
+
+		simd.i32x4_extadd_pairwise_i16x8_s = map_pairwise(I16, I32)
+		simd.i32x4_extadd_pairwise_i16x8_u = map_pairwise(U16, I32)
+		simd.i32x4_abs = map_unary(I32, math_abs)
+		simd.i32x4_neg = map_unary(I32, rt_neg.f64)
+		simd.i32x4_all_true = all_true(U32)
+		simd.i32x4_bitmask = bitmask(I32)
+		simd.i32x4_extend_low_i16x8_s = map_convert(I16, I32, 4, 0, no_op)
+		simd.i32x4_extend_high_i16x8_s = map_convert(I16, I32, 4, 4, no_op)
+		simd.i32x4_extend_low_i16x8_u = map_convert(U16, I32, 4, 0, no_op)
+		simd.i32x4_extend_high_i16x8_u = map_convert(U16, I32, 4, 4, no_op)
+		simd.i32x4_shl = map_shift(U32, bit_lshift)
+		simd.i32x4_shr_s = map_shift(U32, bit_arshift)
+		simd.i32x4_shr_u = map_shift(U32, bit_rshift)
+		simd.i32x4_add = map_binary(U32, rt_add.i32)
+		simd.i32x4_sub = map_binary(U32, rt_sub.i32)
+		simd.i32x4_mul = map_binary(U32, rt_mul.i32)
+		simd.i32x4_min_s = map_binary(I32, math_min)
+		simd.i32x4_min_u = map_binary(U32, math_min)
+		simd.i32x4_max_s = map_binary(I32, math_max)
+		simd.i32x4_max_u = map_binary(U32, math_max)
+
+		function simd.i32x4_dot_i16x8_s(lhs, rhs)
+			local result = buffer_create(16)
+
+			for i = 0, 15, 4 do
+				local data_1 = buffer_read_i16(lhs, i) * buffer_read_i16(rhs, i)
+				local data_2 = buffer_read_i16(lhs, i + 2) * buffer_read_i16(rhs, i + 2)
+
+				write_u32(result, i, data_1 + data_2)
+			end
+
+			return result
+		end
+
+		simd.i32x4_extmul_low_i16x8_s = map_extend_mul(I16, I32, 0, mul)
+		simd.i32x4_extmul_high_i16x8_s = map_extend_mul(I16, I32, 4, mul)
+		simd.i32x4_extmul_low_i16x8_u = map_extend_mul(U16, U32, 0, mul)
+		simd.i32x4_extmul_high_i16x8_u = map_extend_mul(U16, U32, 4, mul)
+
+		function simd.i64x2_all_true(value)
+			if rt_i64.is_zero(read_i64(value, 0)) or rt_i64.is_zero(read_i64(value, 8)) then
+				return 0
+			else
+				return 1
+			end
+		end
+
+		function simd.i64x2_bitmask(value)
+			local result = 0
+
+			if buffer_read_i32(value, 4) < 0 then
+				result = result + 1
+			end
+
+			if buffer_read_i32(value, 12) < 0 then
+				result = result + 2
+			end
+
+			return result
+		end
+
+		simd.i64x2_abs = map_unary(I64, abs_i64)
+		simd.i64x2_neg = map_unary(I64, rt_i64.negate)
+		simd.i64x2_extend_low_i32x4_s = map_convert(U32, I64, 2, 0, rt_extend.i64_i32)
+		simd.i64x2_extend_high_i32x4_s = map_convert(U32, I64, 2, 2, rt_extend.i64_i32)
+		simd.i64x2_extend_low_i32x4_u = map_convert(U32, I64, 2, 0, rt_extend.i64_u32)
+		simd.i64x2_extend_high_i32x4_u = map_convert(U32, I64, 2, 2, rt_extend.i64_u32)
+		simd.i64x2_shl = map_shift(I64, shift_i64(rt_shl.i64))
+		simd.i64x2_shr_s = map_shift(I64, shift_i64(rt_shr.i64))
+		simd.i64x2_shr_u = map_shift(I64, shift_i64(rt_shr.u64))
+		simd.i64x2_add = map_binary(I64, rt_add.i64)
+		simd.i64x2_sub = map_binary(I64, rt_sub.i64)
+		simd.i64x2_mul = map_binary(I64, rt_mul.i64)
+		simd.i64x2_extmul_low_i32x4_s = map_extend_mul(U32, I64, 0, mul_i64_i32)
+		simd.i64x2_extmul_high_i32x4_s = map_extend_mul(U32, I64, 2, mul_i64_i32)
+		simd.i64x2_extmul_low_i32x4_u = map_extend_mul(U32, I64, 0, mul_i64_u32)
+		simd.i64x2_extmul_high_i32x4_u = map_extend_mul(U32, I64, 2, mul_i64_u32)
+
+		simd.f32x4_ceil = map_unary(F32, math_ceil)
+		simd.f32x4_floor = map_unary(F32, math_floor)
+		simd.f32x4_trunc = map_unary(F32, rt_truncate.f64)
+		simd.f32x4_nearest = map_unary(F32, rt_nearest.f32)
+		simd.f32x4_abs = map_unary(F32, math_abs)
+		simd.f32x4_neg = map_unary(F32, rt_neg.f64)
+		simd.f32x4_sqrt = map_unary(F32, math_sqrt)
+		simd.f32x4_add = map_binary(F32, add)
+		simd.f32x4_sub = map_binary(F32, sub)
+		simd.f32x4_mul = map_binary(F32, mul)
+		simd.f32x4_div = map_binary(F32, div)
+		simd.f32x4_min = map_binary(F32, rt_min.f64)
+		simd.f32x4_max = map_binary(F32, rt_max.f64)
+		simd.f32x4_pmin = map_binary(F32, p_min)
+		simd.f32x4_pmax = map_binary(F32, p_max)
+
+		simd.f64x2_ceil = map_unary(F64, math_ceil)
+		simd.f64x2_floor = map_unary(F64, math_floor)
+		simd.f64x2_trunc = map_unary(F64, rt_truncate.f64)
+		simd.f64x2_nearest = map_unary(F64, rt_nearest.f32)
+		simd.f64x2_abs = map_unary(F64, math_abs)
+		simd.f64x2_neg = map_unary(F64, rt_neg.f64)
+		simd.f64x2_sqrt = map_unary(F64, math_sqrt)
+		simd.f64x2_add = map_binary(F64, add)
+		simd.f64x2_sub = map_binary(F64, sub)
+		simd.f64x2_mul = map_binary(F64, mul)
+		simd.f64x2_div = map_binary(F64, div)
+		simd.f64x2_min = map_binary(F64, rt_min.f64)
+		simd.f64x2_max = map_binary(F64, rt_max.f64)
+		simd.f64x2_pmin = map_binary(F64, p_min)
+		simd.f64x2_pmax = map_binary(F64, p_max)
+
+		simd.i32x4_trunc_sat_f32x4_s = map_convert(F32, U32, 4, 0, saturate_i32)
+		simd.i32x4_trunc_sat_f32x4_u = map_convert(F32, U32, 4, 0, saturate_u32)
+		simd.f32x4_convert_i32x4_s = map_convert(I32, F32, 4, 0, no_op)
+		simd.f32x4_convert_i32x4_u = map_convert(U32, F32, 4, 0, no_op)
+		simd.i32x4_trunc_sat_f64x2_s_zero = map_convert(F64, U32, 2, 0, saturate_i32)
+		simd.i32x4_trunc_sat_f64x2_u_zero = map_convert(F64, U32, 2, 0, saturate_u32)
+		simd.f64x2_convert_low_i32x4_s = map_convert(I32, F64, 2, 0, no_op)
+		simd.f64x2_convert_low_i32x4_u = map_convert(U32, F64, 2, 0, no_op)
+		simd.f32x4_demote_f64x2_zero = map_convert(F64, F32, 2, 0, no_op)
+		simd.f64x2_promote_low_f32x4 = map_convert(F32, F64, 2, 0, no_op)
+
+		return simd
+	end)()
+
+	-- The same operations under the names that the Luau runtime gives them
+	rt_i64.add = rt_add.i64
+	rt_i64.bit_and = rt_band.i64
+	rt_i64.bit_not = rt_bnot.i64
+	rt_i64.bit_or = rt_bor.i64
+	rt_i64.bit_xor = rt_bxor.i64
+	rt_i64.divide_signed = rt_div.i64
+	rt_i64.divide_unsigned = rt_div.u64
+	rt_i64.into_u64 = rt_convert.f64_u64
+	rt_i64.is_equal = rt_eq.i64
+	rt_i64.is_greater_signed = rt_gt.i64
+	rt_i64.is_greater_unsigned = rt_gt.u64
+	rt_i64.is_less_signed = rt_lt.i64
+	rt_i64.is_less_unsigned = rt_lt.u64
+	rt_i64.multiply = rt_mul.i64
+	rt_i64.rotate_left = rt_rotl.i64
+	rt_i64.rotate_right = rt_rotr.i64
+	rt_i64.shift_left = rt_shl.i64
+	rt_i64.shift_right_signed = rt_shr.i64
+	rt_i64.shift_right_unsigned = rt_shr.u64
+	rt_i64.subtract = rt_sub.i64
+
+	return {
+		add = rt_add,
+		allocator = rt_allocator,
+		atomic = rt_atomic,
+		band = rt_band,
+		bnot = rt_bnot,
+		bor = rt_bor,
+		bxor = rt_bxor,
+		clz = rt_clz,
+		convert = rt_convert,
+		copysign = rt_copysign,
+		ctz = rt_ctz,
+		data = rt_data,
+		demote = rt_demote,
+		div = rt_div,
+		eq = rt_eq,
+		extend = rt_extend,
+		ge = rt_ge,
+		gt = rt_gt,
+		i64 = rt_i64,
+		le = rt_le,
+		load = rt_load,
+		lt = rt_lt,
+		max = rt_max,
+		min = rt_min,
+		mul = rt_mul,
+		ne = rt_ne,
+		nearest = rt_nearest,
+		neg = rt_neg,
+		popcnt = rt_popcnt,
+		promote = rt_promote,
+		reference = rt_reference,
+		reinterpret = rt_reinterpret,
+		rem = rt_rem,
+		rotl = rt_rotl,
+		rotr = rt_rotr,
+		saturate = rt_saturate,
+		shl = rt_shl,
+		shr = rt_shr,
+		sqrt = rt_sqrt,
+		store = rt_store,
+		sub = rt_sub,
+		table = rt_table,
+		truncate = rt_truncate,
+		wrap = rt_wrap,
+		memory = rt_snapshot.memory,
+		global = rt_snapshot.global,
+		-- `read(memory, addr, len)`, `read_cstr(memory, addr)`, and
+		-- `write(memory, addr, data)` exchange strings with a memory
+		string = rt_string,
+		exception = rt_exception,
+		trap = rt_trap,
+		simd = rt_simd,
+	}
+end)()
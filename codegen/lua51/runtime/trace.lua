@@ -0,0 +1,161 @@
+
+-- Tracing rebinds the loads and stores of the runtime before it so that every
+-- access to a watched range of memory, and to a watched global, is passed to
+-- `hook`, which prints them until it is replaced
+local rt_trace = (function()
+	local trace = {}
+
+	local range_list = {}
+	local global_set = {}
+
+	-- Globals are traced through a table standing in for them, which keeps
+	-- the one it stands in for so that adopting it does not trace twice
+	local SOURCE_MAP = setmetatable({}, { __mode = "k" })
+
+	local function is_watched(addr, size)
+		for _, range in ipairs(range_list) do
+			if addr <= range[2] and addr + size > range[1] then
+				return true
+			end
+		end
+
+		return false
+	end
+
+	function trace.hook(kind, addr, size, value)
+		print(kind, addr, size, value)
+	end
+
+	function trace.watch(first, last)
+		table.insert(range_list, { first, last })
+	end
+
+	function trace.unwatch(first, last)
+		for i = #range_list, 1, -1 do
+			local range = range_list[i]
+
+			if range[1] == first and range[2] == last then
+				table.remove(range_list, i)
+			end
+		end
+	end
+
+	function trace.watch_global(index)
+		global_set[index] = true
+	end
+
+	function trace.unwatch_global(index)
+		global_set[index] = nil
+	end
+
+	function trace.load(size, func)
+		return function(memory, addr)
+			local value = func(memory, addr)
+
+			if is_watched(addr, size) then
+				trace.hook("load", addr, size, value)
+			end
+
+			return value
+		end
+	end
+
+	function trace.store(size, func)
+		return function(memory, addr, value)
+			if is_watched(addr, size) then
+				trace.hook("store", addr, size, value)
+			end
+
+			func(memory, addr, value)
+		end
+	end
+
+	-- Bulk operations report the whole range they write, with the source
+	-- address of a copy and the byte of a fill as their value
+	function trace.copy(func)
+		return function(memory_1, addr_1, memory_2, addr_2, len)
+			if is_watched(addr_1, len) then
+				trace.hook("copy", addr_1, len, addr_2)
+			end
+
+			func(memory_1, addr_1, memory_2, addr_2, len)
+		end
+	end
+
+	function trace.fill(func)
+		return function(memory, addr, len, value)
+			if is_watched(addr, len) then
+				trace.hook("fill", addr, len, value)
+			end
+
+			func(memory, addr, len, value)
+		end
+	end
+
+	function trace.init(func)
+		return function(memory, addr, data, offset, len)
+			if is_watched(addr, len) then
+				trace.hook("init", addr, len, nil)
+			end
+
+			func(memory, addr, data, offset, len)
+		end
+	end
+
+	function trace.global(index, global)
+		local source = SOURCE_MAP[global] or global
+		local proxy = setmetatable({}, {
+			__index = function(_, key)
+				local value = source[key]
+
+				if global_set[index] then
+					trace.hook("get", index, 0, value)
+				end
+
+				return value
+			end,
+			__newindex = function(_, key, value)
+				if global_set[index] then
+					trace.hook("set", index, 0, value)
+				end
+
+				source[key] = value
+			end,
+		})
+
+		SOURCE_MAP[proxy] = source
+
+		return proxy
+	end
+
+	return trace
+end)()
+
+rt.load.i32_i8 = rt_trace.load(1, rt.load.i32_i8)
+rt.load.i32_u8 = rt_trace.load(1, rt.load.i32_u8)
+rt.load.i32_i16 = rt_trace.load(2, rt.load.i32_i16)
+rt.load.i32_u16 = rt_trace.load(2, rt.load.i32_u16)
+rt.load.i32 = rt_trace.load(4, rt.load.i32)
+rt.load.i64_i8 = rt_trace.load(1, rt.load.i64_i8)
+rt.load.i64_u8 = rt_trace.load(1, rt.load.i64_u8)
+rt.load.i64_i16 = rt_trace.load(2, rt.load.i64_i16)
+rt.load.i64_u16 = rt_trace.load(2, rt.load.i64_u16)
+rt.load.i64_i32 = rt_trace.load(4, rt.load.i64_i32)
+rt.load.i64_u32 = rt_trace.load(4, rt.load.i64_u32)
+rt.load.i64 = rt_trace.load(8, rt.load.i64)
+rt.load.f32 = rt_trace.load(4, rt.load.f32)
+rt.load.f64 = rt_trace.load(8, rt.load.f64)
+rt.load.v128 = rt_trace.load(16, rt.load.v128)
+rt.store.i32_n8 = rt_trace.store(1, rt.store.i32_n8)
+rt.store.i32_n16 = rt_trace.store(2, rt.store.i32_n16)
+rt.store.i32 = rt_trace.store(4, rt.store.i32)
+rt.store.i64_n8 = rt_trace.store(1, rt.store.i64_n8)
+rt.store.i64_n16 = rt_trace.store(2, rt.store.i64_n16)
+rt.store.i64_n32 = rt_trace.store(4, rt.store.i64_n32)
+rt.store.i64 = rt_trace.store(8, rt.store.i64)
+rt.store.f32 = rt_trace.store(4, rt.store.f32)
+rt.store.f64 = rt_trace.store(8, rt.store.f64)
+rt.store.v128 = rt_trace.store(16, rt.store.v128)
+rt.store.copy = rt_trace.copy(rt.store.copy)
+rt.store.fill = rt_trace.fill(rt.store.fill)
+rt.store.init = rt_trace.init(rt.store.init)
@@ -0,0 +1,25 @@
+pub static RUNTIME: &str = concat!(
+	include_str!("../runtime/bit32.lua"),
+	include_str!("../runtime/buffer.lua"),
+	include_str!("../runtime/runtime.lua"),
+);
+/// The runtime with memory laid out as a table of 32 bit words rather than of
+/// bytes, which makes most accesses a single lookup at the cost of splitting
+/// words for single bytes.
+pub static RUNTIME_WORDS: &str = concat!(
+	include_str!("../runtime/bit32.lua"),
+	include_str!("../runtime/buffer.lua"),
+	include_str!("../runtime/buffer_words.lua"),
+	include_str!("../runtime/runtime.lua"),
+);
+/// Appended to any of the runtimes to pass the loads, stores, and globals it
+/// is told to watch to a hook, for finding where memory is corrupted.
+pub static TRACE: &str = include_str!("../runtime/trace.lua");
+
+pub use translator::{
+	from_inst_list, from_module_custom, from_module_mapped, from_module_split, from_module_typed,
+	from_module_untyped, write_self_test, Lua51,
+};
+
+mod analyzer;
+mod translator;
@@ -0,0 +1,234 @@
+use std::io::{Result, Write};
+
+use codegen_core::{is_exact_decimal, Backend, BranchStyle, Dialect, Mapping, Options, Split};
+use wasm_ast::{
+	module::{Module, TypeInfo},
+	node::{BinOpType, CmpOpType, Expression, FuncData, UnOpType, Value},
+};
+use wasmparser::Operator;
+
+use crate::analyzer::into_string::{IntoNameTuple, TryIntoSymbol};
+
+// Lua 5.1 has no hexadecimal escapes, so every byte that is not plainly
+// printable is written as a zero padded decimal escape
+fn escape(data: &[u8]) -> Vec<u8> {
+	let mut buffer = Vec::with_capacity(data.len() * 2 + 2);
+
+	buffer.push(b'"');
+
+	for &byte in data {
+		let is_plain = byte == b' ' || byte.is_ascii_graphic() && !matches!(byte, b'"' | b'\\');
+
+		if is_plain {
+			buffer.push(byte);
+		} else {
+			buffer.extend_from_slice(&[
+				b'\\',
+				b'0' + byte / 100,
+				b'0' + byte / 10 % 10,
+				b'0' + byte % 10,
+			]);
+		}
+	}
+
+	buffer.push(b'"');
+	buffer
+}
+
+/// Lowers functions to Lua 5.1, which dialects that run on it can reuse.
+pub struct Lua51 {
+	/// Calls `rt_yield` on every loop iteration, for hosts that stop code
+	/// which runs for too long without yielding.
+	pub has_loop_yield: bool,
+}
+
+const LUA_51: Lua51 = Lua51 {
+	has_loop_yield: false,
+};
+
+impl Backend for Lua51 {
+	fn write_function(&self, func: &FuncData, options: &Options, w: &mut dyn Write) -> Result<()> {
+		codegen_core::write_function(self, func, options, w)
+	}
+
+	fn write_function_marked(
+		&self,
+		func: &FuncData,
+		options: &Options,
+		w: &mut dyn Write,
+	) -> Result<()> {
+		codegen_core::write_function_marked(self, func, options, w)
+	}
+
+	fn write_expression(&self, expr: &Expression, w: &mut dyn Write) -> Result<()> {
+		codegen_core::write_expression(self, expr, w)
+	}
+
+	fn un_op_name(&self, op: UnOpType) -> (&'static str, &'static str) {
+		op.into_name_tuple()
+	}
+
+	fn bin_op_name(&self, op: BinOpType) -> Option<(&'static str, &'static str)> {
+		op.try_into_symbol().is_none().then(|| op.into_name_tuple())
+	}
+
+	fn cmp_op_name(&self, op: CmpOpType) -> Option<(&'static str, &'static str)> {
+		op.try_into_symbol().is_none().then(|| op.into_name_tuple())
+	}
+
+	fn value_name(&self, value: Value) -> Option<(&'static str, &'static str)> {
+		let name = match value {
+			Value::I64(0) => ("i64", "ZERO"),
+			Value::I64(1) => ("i64", "ONE"),
+			Value::I64(_) => ("i64", "from_u32"),
+			Value::F32(v) if v.is_nan() || (v.is_finite() && !is_exact_decimal(v.into())) => {
+				("reinterpret", "f32_i32")
+			}
+			Value::F64(v) if v.is_nan() || (v.is_finite() && !is_exact_decimal(v)) => {
+				("reinterpret", "f64_i64")
+			}
+			_ => return None,
+		};
+
+		Some(name)
+	}
+
+	// Bitwise operations on `i32` come from the `bit32` that the runtime builds
+	// for its host, and everything else from the `rt` it hands back
+	fn write_local_operation(&self, head: &str, tail: &str, w: &mut dyn Write) -> Result<()> {
+		match head {
+			"bit" => writeln!(w, "local bit_{tail} = bit32.{tail}"),
+			"math" => writeln!(w, "local math_{tail} = math.{tail}"),
+			_ => writeln!(w, "local {head}_{tail} = rt.{head}.{tail}"),
+		}
+	}
+
+	fn write_trace_table(&self, w: &mut dyn Write) -> Result<()> {
+		write!(w, "rt_trace")
+	}
+
+	fn write_string(&self, data: &[u8], w: &mut dyn Write) -> Result<()> {
+		w.write_all(&escape(data))
+	}
+
+	fn write_element_copy(&self, w: &mut dyn Write) -> Result<()> {
+		writeln!(w, "\t\tfor i = 1, data.n do")?;
+		writeln!(w, "\t\t\ttarget[offset + i - 1] = data[i]")?;
+		writeln!(w, "\t\tend")
+	}
+}
+
+// Values of `i32` are kept unsigned as in Luau, but without `continue` or
+// `if` expressions, so loops repeat their body and choices use `and` and `or`
+impl Dialect for Lua51 {
+	fn branch_style(&self) -> BranchStyle {
+		BranchStyle::Repeat
+	}
+
+	fn is_signed(&self) -> bool {
+		false
+	}
+
+	fn loop_call(&self) -> Option<&'static str> {
+		self.has_loop_yield.then_some("rt_yield")
+	}
+
+	fn bin_op_symbol(&self, op: BinOpType) -> Option<&'static str> {
+		op.try_into_symbol()
+	}
+
+	fn cmp_op_symbol(&self, op: CmpOpType) -> Option<&'static str> {
+		op.try_into_symbol()
+	}
+
+	fn write_i64(&self, number: i64, w: &mut dyn Write) -> Result<()> {
+		match number {
+			0 => write!(w, "i64_ZERO"),
+			1 => write!(w, "i64_ONE"),
+			_ => {
+				let list = number.to_ne_bytes();
+				let a = u32::from_ne_bytes(list[0..4].try_into().unwrap());
+				let b = u32::from_ne_bytes(list[4..8].try_into().unwrap());
+
+				write!(w, "i64_from_u32({a}, {b})")
+			}
+		}
+	}
+
+	fn write_memory_size(&self, index: usize, w: &mut dyn Write) -> Result<()> {
+		write!(w, "rt.allocator.size(memory_at_{index})")
+	}
+}
+
+/// # Errors
+/// Returns `Err` if writing to `Write` failed.
+pub fn from_inst_list(code: &[Operator], type_info: &TypeInfo, w: &mut dyn Write) -> Result<()> {
+	codegen_core::from_inst_list(&LUA_51, code, type_info, w)
+}
+
+/// # Errors
+/// Returns `Err` if writing to `Write` failed.
+pub fn from_module_typed(wasm: &Module, type_info: &TypeInfo, w: &mut dyn Write) -> Result<()> {
+	codegen_core::from_module_typed(&LUA_51, wasm, type_info, w)
+}
+
+/// Translates the module, also embedding the contents of every custom section
+/// named in `name_list` as a string in the `custom_list` of the instance.
+///
+/// # Errors
+/// Returns `Err` if writing to `Write` failed.
+pub fn from_module_custom(
+	wasm: &Module,
+	type_info: &TypeInfo,
+	name_list: &[&str],
+	options: &Options,
+	w: &mut dyn Write,
+) -> Result<()> {
+	codegen_core::from_module_custom(&LUA_51, wasm, type_info, name_list, options, w)
+}
+
+/// Translates the module as in [`from_module_custom`], also returning the
+/// line that each function and statement starts at for a source map.
+///
+/// # Errors
+/// Returns `Err` if writing to `Write` failed.
+pub fn from_module_mapped(
+	wasm: &Module,
+	type_info: &TypeInfo,
+	name_list: &[&str],
+	options: &Options,
+	w: &mut dyn Write,
+) -> Result<Vec<Mapping>> {
+	codegen_core::from_module_mapped(&LUA_51, wasm, type_info, name_list, options, w)
+}
+
+/// Translates the module with its functions moved out to chunks, which are
+/// returned to be written where the `import` of `split` loads them from.
+///
+/// # Errors
+/// Returns `Err` if writing to `Write` failed.
+pub fn from_module_split(
+	wasm: &Module,
+	type_info: &TypeInfo,
+	name_list: &[&str],
+	options: &Options,
+	split: &Split,
+	w: &mut dyn Write,
+) -> Result<Vec<Vec<u8>>> {
+	codegen_core::from_module_split(&LUA_51, wasm, type_info, name_list, options, split, w)
+}
+
+/// # Errors
+/// Returns `Err` if writing to `Write` failed.
+pub fn from_module_untyped(wasm: &Module, w: &mut dyn Write) -> Result<()> {
+	codegen_core::from_module_untyped(&LUA_51, wasm, w)
+}
+
+/// Writes a script that checks the runtime against edge cases, as described
+/// in [`codegen_core::write_self_test`], to be run after the runtime.
+///
+/// # Errors
+/// Returns `Err` if writing to `Write` failed.
+pub fn write_self_test(w: &mut dyn Write) -> Result<()> {
+	codegen_core::write_self_test(&LUA_51, w)
+}
@@ -0,0 +1,1910 @@
+local module = {}
+
+local math_ceil = math.ceil
+local math_floor = math.floor
+local math_ult = math.ult
+
+local string_pack = string.pack
+local string_unpack = string.unpack
+
+local NUM_MIN_I64 = math.mininteger
+local NUM_MAX_I64 = math.maxinteger
+
+-- Values of `i32` are kept sign extended in native integers, so anything
+-- that can carry past 32 bits is wrapped back into range
+local function to_signed(num)
+	return ((num + 0x80000000) & 0xFFFFFFFF) - 0x80000000
+end
+
+local function truncate_f64(num)
+	if num >= 0 then
+		return (math_floor(num))
+	else
+		return (math_ceil(num))
+	end
+end
+
+-- Rounding gives back integers whenever it can, but float operations must
+-- keep producing floats with the sign of zero intact
+local function to_float(num, result)
+	if result == 0 and (num < 0 or 1 / num < 0) then
+		return -0.0
+	end
+
+	return result + 0.0
+end
+
+-- Doubles hold more than twice the bits of a float, so rounding the exact
+-- result of an operation on two floats to a double and then to a float gives
+-- the same float as rounding it once
+local function round_f32(num)
+	return (string_unpack("<f", string_pack("<f", num)))
+end
+
+-- Traps are raised as a table naming their kind, their message, and the index
+-- of the function they were raised in, unless the instance is configured with
+-- "message", which raises just their message, or with a handler whose result
+-- is raised instead
+local trap = {}
+
+do
+	local TRAP_MT = {
+		__tostring = function(data)
+			return data.info
+		end,
+	}
+
+	-- Functions are registered by every instance so that the innermost one
+	-- on the stack can be named when a trap is raised
+	local FUNC_INDEX = setmetatable({}, { __mode = "k" })
+
+	local debug_getinfo = debug and debug.getinfo
+
+	local on_trap = nil
+
+	function trap.configure(value)
+		on_trap = value
+	end
+
+	function trap.register(func_list, first, last)
+		for i = first, last do
+			local func = func_list[i]
+
+			if func then
+				FUNC_INDEX[func] = i
+			end
+		end
+	end
+
+	local function find_func()
+		if not debug_getinfo then
+			return nil
+		end
+
+		local level = 1
+
+		while true do
+			local info = debug_getinfo(level, "f")
+
+			if info == nil then
+				return nil
+			end
+
+			local index = FUNC_INDEX[info.func]
+
+			if index then
+				return index
+			end
+
+			level = level + 1
+		end
+	end
+
+	function trap.raise(kind, info)
+		if on_trap == "message" then
+			error(info, 0)
+		end
+
+		local func = find_func()
+
+		if on_trap == nil or on_trap == "value" then
+			error(setmetatable({ kind = kind, info = info, func = func }, TRAP_MT), 0)
+		else
+			error(on_trap(kind, info, func) or info, 0)
+		end
+	end
+
+	module.trap = trap
+end
+
+local function trap_unless(condition, kind, info)
+	if not condition then
+		trap.raise(kind, info)
+	end
+end
+
+do
+	local add = {}
+	local sub = {}
+	local mul = {}
+	local div = {}
+	local rem = {}
+	local neg = {}
+	local min = {}
+	local max = {}
+	local copysign = {}
+	local nearest = {}
+	local ceil = {}
+	local floor = {}
+	local sqrt = {}
+
+	local math_abs = math.abs
+	local math_fmod = math.fmod
+	local math_min = math.min
+	local math_max = math.max
+	local math_sqrt = math.sqrt
+
+	local function round(num)
+		if num >= 0 then
+			return (math_floor(num + 0.5))
+		else
+			return (math_ceil(num - 0.5))
+		end
+	end
+
+	-- Floor division rounds towards negative infinity, so inexact negative
+	-- quotients are moved one step back towards zero
+	local function div_truncate(lhs, rhs)
+		local result = lhs // rhs
+
+		if result < 0 and result * rhs ~= lhs then
+			result = result + 1
+		end
+
+		return result
+	end
+
+	function add.i32(lhs, rhs)
+		return (to_signed(lhs + rhs))
+	end
+
+	function sub.i32(lhs, rhs)
+		return (to_signed(lhs - rhs))
+	end
+
+	function mul.i32(lhs, rhs)
+		return (to_signed(lhs * rhs))
+	end
+
+	function div.i32(lhs, rhs)
+		trap_unless(rhs ~= 0, "integer_divide_by_zero", "division by zero")
+		trap_unless(lhs ~= -0x80000000 or rhs ~= -1, "integer_overflow", "integer overflow")
+
+		return (div_truncate(lhs, rhs))
+	end
+
+	function div.u32(lhs, rhs)
+		trap_unless(rhs ~= 0, "integer_divide_by_zero", "division by zero")
+
+		return (to_signed((lhs & 0xFFFFFFFF) // (rhs & 0xFFFFFFFF)))
+	end
+
+	function rem.u32(lhs, rhs)
+		trap_unless(rhs ~= 0, "integer_divide_by_zero", "division by zero")
+
+		return (to_signed((lhs & 0xFFFFFFFF) % (rhs & 0xFFFFFFFF)))
+	end
+
+	function div.i64(lhs, rhs)
+		trap_unless(rhs ~= 0, "integer_divide_by_zero", "division by zero")
+		trap_unless(lhs ~= NUM_MIN_I64 or rhs ~= -1, "integer_overflow", "integer overflow")
+
+		return (div_truncate(lhs, rhs))
+	end
+
+	function rem.i32(lhs, rhs)
+		trap_unless(rhs ~= 0, "integer_divide_by_zero", "division by zero")
+
+		return (math_fmod(lhs, rhs))
+	end
+
+	rem.i64 = rem.i32
+
+	-- The dividend is halved first so it stays positive, then the quotient
+	-- is corrected using what remains
+	function div.u64(lhs, rhs)
+		trap_unless(rhs ~= 0, "integer_divide_by_zero", "division by zero")
+
+		if rhs < 0 then
+			if math_ult(lhs, rhs) then
+				return 0
+			else
+				return 1
+			end
+		end
+
+		local result = ((lhs >> 1) // rhs) << 1
+
+		if not math_ult(lhs - result * rhs, rhs) then
+			result = result + 1
+		end
+
+		return result
+	end
+
+	function rem.u64(lhs, rhs)
+		return lhs - div.u64(lhs, rhs) * rhs
+	end
+
+	function add.f32(lhs, rhs)
+		return round_f32(lhs + rhs)
+	end
+
+	function sub.f32(lhs, rhs)
+		return round_f32(lhs - rhs)
+	end
+
+	function mul.f32(lhs, rhs)
+		return round_f32(lhs * rhs)
+	end
+
+	function div.f32(lhs, rhs)
+		return round_f32(lhs / rhs)
+	end
+
+	function sqrt.f32(num)
+		return round_f32(math_sqrt(num))
+	end
+
+	function neg.f32(num)
+		return -num
+	end
+
+	function min.f32(lhs, rhs)
+		if lhs ~= lhs then
+			return lhs
+		elseif rhs ~= rhs then
+			return rhs
+		else
+			return (math_min(lhs, rhs))
+		end
+	end
+
+	function max.f32(lhs, rhs)
+		if lhs ~= lhs then
+			return lhs
+		elseif rhs ~= rhs then
+			return rhs
+		else
+			return (math_max(lhs, rhs))
+		end
+	end
+
+	function copysign.f32(lhs, rhs)
+		local bits = string_unpack("<i8", string_pack("<d", rhs))
+
+		if bits >= 0 then
+			return (math_abs(lhs))
+		else
+			return -math_abs(lhs)
+		end
+	end
+
+	function nearest.f32(num)
+		local result = round(num)
+
+		if (math_abs(num) + 0.5) % 2 == 1 then
+			if result >= 0 then
+				result = result - 1
+			else
+				result = result + 1
+			end
+		end
+
+		return to_float(num, result)
+	end
+
+	function ceil.f32(num)
+		return to_float(num, math_ceil(num))
+	end
+
+	function floor.f32(num)
+		return to_float(num, math_floor(num))
+	end
+
+	neg.f64 = neg.f32
+	min.f64 = min.f32
+	max.f64 = max.f32
+	copysign.f64 = copysign.f32
+	nearest.f64 = nearest.f32
+	ceil.f64 = ceil.f32
+	floor.f64 = floor.f32
+
+	module.add = add
+	module.sub = sub
+	module.mul = mul
+	module.div = div
+	module.rem = rem
+	module.min = min
+	module.max = max
+	module.neg = neg
+	module.copysign = copysign
+	module.nearest = nearest
+	module.ceil = ceil
+	module.floor = floor
+	module.sqrt = sqrt
+end
+
+do
+	local clz = {}
+	local ctz = {}
+	local popcnt = {}
+
+	function clz.i64(num)
+		if num == 0 then
+			return 64
+		end
+
+		local count = 0
+
+		if num >> 32 == 0 then
+			num = num << 32
+			count = count + 32
+		end
+
+		if num >> 48 == 0 then
+			num = num << 16
+			count = count + 16
+		end
+
+		if num >> 56 == 0 then
+			num = num << 8
+			count = count + 8
+		end
+
+		if num >> 60 == 0 then
+			num = num << 4
+			count = count + 4
+		end
+
+		if num >> 62 == 0 then
+			num = num << 2
+			count = count + 2
+		end
+
+		if num >> 63 == 0 then
+			count = count + 1
+		end
+
+		return count
+	end
+
+	function ctz.i64(num)
+		if num == 0 then
+			return 64
+		end
+
+		local count = 0
+
+		if num << 32 == 0 then
+			num = num >> 32
+			count = count + 32
+		end
+
+		if num << 48 == 0 then
+			num = num >> 16
+			count = count + 16
+		end
+
+		if num << 56 == 0 then
+			num = num >> 8
+			count = count + 8
+		end
+
+		if num << 60 == 0 then
+			num = num >> 4
+			count = count + 4
+		end
+
+		if num << 62 == 0 then
+			num = num >> 2
+			count = count + 2
+		end
+
+		if num << 63 == 0 then
+			count = count + 1
+		end
+
+		return count
+	end
+
+	function popcnt.i64(num)
+		local count = 0
+
+		while num ~= 0 do
+			num = num & (num - 1)
+			count = count + 1
+		end
+
+		return count
+	end
+
+	function clz.i32(num)
+		return clz.i64(num & 0xFFFFFFFF) - 32
+	end
+
+	function ctz.i32(num)
+		if num == 0 then
+			return 32
+		end
+
+		return ctz.i64(num)
+	end
+
+	function popcnt.i32(num)
+		return popcnt.i64(num & 0xFFFFFFFF)
+	end
+
+	module.clz = clz
+	module.ctz = ctz
+	module.popcnt = popcnt
+end
+
+do
+	local le = {}
+	local lt = {}
+	local ge = {}
+	local gt = {}
+
+	function le.u32(lhs, rhs)
+		return lhs & 0xFFFFFFFF <= rhs & 0xFFFFFFFF
+	end
+
+	function lt.u32(lhs, rhs)
+		return lhs & 0xFFFFFFFF < rhs & 0xFFFFFFFF
+	end
+
+	function ge.u32(lhs, rhs)
+		return lhs & 0xFFFFFFFF >= rhs & 0xFFFFFFFF
+	end
+
+	function gt.u32(lhs, rhs)
+		return lhs & 0xFFFFFFFF > rhs & 0xFFFFFFFF
+	end
+
+	function le.u64(lhs, rhs)
+		return not math_ult(rhs, lhs)
+	end
+
+	lt.u64 = math_ult
+
+	function ge.u64(lhs, rhs)
+		return not math_ult(lhs, rhs)
+	end
+
+	function gt.u64(lhs, rhs)
+		return math_ult(rhs, lhs)
+	end
+
+	module.le = le
+	module.lt = lt
+	module.ge = ge
+	module.gt = gt
+end
+
+do
+	local shl = {}
+	local shr = {}
+	local rotl = {}
+	local rotr = {}
+
+	-- Native right shifts are logical, so negative values are flipped
+	-- around them to drag the sign bit along
+	local function arithmetic_shift(num, count)
+		if num >= 0 then
+			return num >> count
+		else
+			return ~(~num >> count)
+		end
+	end
+
+	function shl.i32(lhs, rhs)
+		return (to_signed(lhs << (rhs & 31)))
+	end
+
+	function shr.i32(lhs, rhs)
+		return arithmetic_shift(lhs, rhs & 31)
+	end
+
+	function shr.u32(lhs, rhs)
+		return (to_signed((lhs & 0xFFFFFFFF) >> (rhs & 31)))
+	end
+
+	function shr.i64(lhs, rhs)
+		return arithmetic_shift(lhs, rhs & 63)
+	end
+
+	function rotl.i32(lhs, rhs)
+		local num = lhs & 0xFFFFFFFF
+		local count = rhs & 31
+
+		return (to_signed(num << count | num >> (32 - count)))
+	end
+
+	function rotr.i32(lhs, rhs)
+		return rotl.i32(lhs, -rhs)
+	end
+
+	function rotl.i64(lhs, rhs)
+		local count = rhs & 63
+
+		return lhs << count | lhs >> (64 - count)
+	end
+
+	function rotr.i64(lhs, rhs)
+		return rotl.i64(lhs, -rhs)
+	end
+
+	module.shl = shl
+	module.shr = shr
+	module.rotl = rotl
+	module.rotr = rotr
+end
+
+do
+	local wrap = {}
+	local truncate = {}
+	local saturate = {}
+	local extend = {}
+	local convert = {}
+	local promote = {}
+	local demote = {}
+	local reinterpret = {}
+
+	wrap.i32_i64 = to_signed
+
+	function truncate.i32_f32(num)
+		trap_unless(num > -0x80000001 and num < 0x80000000, "invalid_conversion_to_integer", "invalid conversion to integer")
+
+		return (truncate_f64(num))
+	end
+
+	truncate.i32_f64 = truncate.i32_f32
+
+	function truncate.u32_f32(num)
+		trap_unless(num > -1 and num < 0x100000000, "invalid_conversion_to_integer", "invalid conversion to integer")
+
+		return (to_signed(truncate_f64(num)))
+	end
+
+	truncate.u32_f64 = truncate.u32_f32
+
+	function truncate.i64_f32(num)
+		trap_unless(num >= -2 ^ 63 and num < 2 ^ 63, "invalid_conversion_to_integer", "invalid conversion to integer")
+
+		return (truncate_f64(num))
+	end
+
+	truncate.i64_f64 = truncate.i64_f32
+
+	function truncate.u64_f32(num)
+		trap_unless(num > -1 and num < 2 ^ 64, "invalid_conversion_to_integer", "invalid conversion to integer")
+
+		if num >= 2 ^ 63 then
+			return (truncate_f64(num - 2 ^ 64))
+		else
+			return (truncate_f64(num))
+		end
+	end
+
+	truncate.u64_f64 = truncate.u64_f32
+
+	function truncate.f32(num)
+		return to_float(num, truncate_f64(num))
+	end
+
+	truncate.f64 = truncate.f32
+
+	function saturate.i32_f32(num)
+		if num <= -0x80000000 then
+			return -0x80000000
+		elseif num >= 0x7FFFFFFF then
+			return 0x7FFFFFFF
+		elseif num ~= num then
+			return 0
+		else
+			return (truncate_f64(num))
+		end
+	end
+
+	saturate.i32_f64 = saturate.i32_f32
+
+	function saturate.u32_f32(num)
+		if num <= 0 or num ~= num then
+			return 0
+		elseif num >= 0xFFFFFFFF then
+			return -1
+		else
+			return (to_signed(truncate_f64(num)))
+		end
+	end
+
+	saturate.u32_f64 = saturate.u32_f32
+
+	function saturate.i64_f32(num)
+		if num >= 2 ^ 63 then
+			return NUM_MAX_I64
+		elseif num <= -2 ^ 63 then
+			return NUM_MIN_I64
+		elseif num ~= num then
+			return 0
+		else
+			return (truncate_f64(num))
+		end
+	end
+
+	saturate.i64_f64 = saturate.i64_f32
+
+	function saturate.u64_f32(num)
+		if num >= 2 ^ 64 then
+			return -1
+		elseif num <= 0 or num ~= num then
+			return 0
+		elseif num >= 2 ^ 63 then
+			return (truncate_f64(num - 2 ^ 64))
+		else
+			return (truncate_f64(num))
+		end
+	end
+
+	saturate.u64_f64 = saturate.u64_f32
+
+	function extend.i32_n8(num)
+		return ((num & 0xFF) ~ 0x80) - 0x80
+	end
+
+	function extend.i32_n16(num)
+		return ((num & 0xFFFF) ~ 0x8000) - 0x8000
+	end
+
+	extend.i64_n8 = extend.i32_n8
+	extend.i64_n16 = extend.i32_n16
+	extend.i64_n32 = to_signed
+
+	function extend.i64_i32(num)
+		return num
+	end
+
+	function extend.i64_u32(num)
+		return num & 0xFFFFFFFF
+	end
+
+	function convert.f64_i32(num)
+		return num + 0.0
+	end
+
+	function convert.f64_u32(num)
+		return (num & 0xFFFFFFFF) + 0.0
+	end
+
+	-- Halving keeps the lowest bit around, so rounding to a float is unchanged
+	function convert.f64_u64(num)
+		if num >= 0 then
+			return num + 0.0
+		else
+			return ((num >> 1) | (num & 1)) * 2.0
+		end
+	end
+
+	convert.f64_i64 = convert.f64_i32
+
+	function convert.f32_i32(num)
+		return round_f32(num + 0.0)
+	end
+
+	function convert.f32_u32(num)
+		return round_f32((num & 0xFFFFFFFF) + 0.0)
+	end
+
+	-- Values past 2^53 would round twice on their way to a float, so the bits
+	-- a double cannot hold are folded into one that only keeps them from
+	-- looking like a tie
+	function convert.f32_u64(num)
+		if math_ult(0x1FFFFFFFFFFFFF, num) then
+			num = (num & ~0x7FF) | ((num & 0x7FF) ~= 0 and 0x800 or 0)
+		end
+
+		return round_f32(convert.f64_u64(num))
+	end
+
+	function convert.f32_i64(num)
+		if num < 0 then
+			return -convert.f32_u64(-num)
+		else
+			return convert.f32_u64(num)
+		end
+	end
+
+	demote.f32_f64 = round_f32
+
+	function promote.f64_f32(num)
+		return num
+	end
+
+	-- Converting between floats and doubles quiets signaling NaNs, so the
+	-- payload of a NaN is moved between the two by hand
+	function reinterpret.i32_f32(num)
+		if num ~= num then
+			local bits = string_unpack("<i8", string_pack("<d", num))
+			local payload = (bits >> 29) & 0x7FFFFF
+
+			if payload ~= 0 then
+				return to_signed(((bits >> 32) & 0x80000000) | 0x7F800000 | payload)
+			end
+		end
+
+		return (string_unpack("<i4", string_pack("<f", num)))
+	end
+
+	function reinterpret.i64_f64(num)
+		return (string_unpack("<i8", string_pack("<d", num)))
+	end
+
+	function reinterpret.f32_i32(num)
+		if num & 0x7F800000 == 0x7F800000 and num & 0x7FFFFF ~= 0 then
+			local bits = ((num & 0x80000000) << 32) | 0x7FF0000000000000 | ((num & 0x7FFFFF) << 29)
+
+			return (string_unpack("<d", string_pack("<i8", bits)))
+		end
+
+		return (string_unpack("<f", string_pack("<i4", num)))
+	end
+
+	function reinterpret.f64_i64(num)
+		return (string_unpack("<d", string_pack("<i8", num)))
+	end
+
+	module.wrap = wrap
+	module.truncate = truncate
+	module.saturate = saturate
+	module.extend = extend
+	module.convert = convert
+	module.demote = demote
+	module.promote = promote
+	module.reinterpret = reinterpret
+end
+
+do
+	local load = {}
+	local store = {}
+	local allocator = {}
+	local wasm_memory = {}
+	local wasm_string = {}
+
+	local string_byte = string.byte
+	local string_char = string.char
+	local string_sub = string.sub
+	local table_move = table.move
+	local table_unpack = table.unpack
+
+	local WASM_PAGE_SIZE = 65536
+
+	-- Memory is a sparse table of bytes, so untouched addresses read as zero
+	local DATA_MT = {
+		__index = function()
+			return 0
+		end,
+	}
+
+	local function read(memory, addr, len)
+		return string_char(table_unpack(memory.data, addr, addr + len - 1))
+	end
+
+	local function write(memory, addr, data)
+		local bytes = memory.data
+
+		for i = 1, #data do
+			bytes[addr + i - 1] = string_byte(data, i)
+		end
+	end
+
+	-- Integers are put together from their bytes and taken apart again with
+	-- shifts, so only floats go through strings to reach their bits
+	local function load_u16(memory, addr)
+		local data = memory.data
+
+		return data[addr] | data[addr + 1] << 8
+	end
+
+	local function load_u32(memory, addr)
+		local data = memory.data
+
+		return data[addr] | data[addr + 1] << 8 | data[addr + 2] << 16 | data[addr + 3] << 24
+	end
+
+	function load.i32_i8(memory, addr)
+		return (memory.data[addr] ~ 0x80) - 0x80
+	end
+
+	function load.i32_u8(memory, addr)
+		return memory.data[addr]
+	end
+
+	function load.i32_i16(memory, addr)
+		return (load_u16(memory, addr) ~ 0x8000) - 0x8000
+	end
+
+	load.i32_u16 = load_u16
+
+	function load.i32(memory, addr)
+		return (load_u32(memory, addr) ~ 0x80000000) - 0x80000000
+	end
+
+	load.i64_i8 = load.i32_i8
+	load.i64_u8 = load.i32_u8
+	load.i64_i16 = load.i32_i16
+	load.i64_u16 = load_u16
+	load.i64_i32 = load.i32
+	load.i64_u32 = load_u32
+
+	function load.i64(memory, addr)
+		return load_u32(memory, addr) | load_u32(memory, addr + 4) << 32
+	end
+
+	function load.f32(memory, addr)
+		return (string_unpack("<f", string_pack("<I4", load_u32(memory, addr))))
+	end
+
+	function load.f64(memory, addr)
+		return (string_unpack("<d", string_pack("<i8", load.i64(memory, addr))))
+	end
+
+	function load.v128(memory, addr)
+		return read(memory, addr, 16)
+	end
+
+	load.string = read
+
+	function store.i32_n8(memory, addr, value)
+		memory.data[addr] = value & 0xFF
+	end
+
+	function store.i32_n16(memory, addr, value)
+		local data = memory.data
+
+		data[addr] = value & 0xFF
+		data[addr + 1] = value >> 8 & 0xFF
+	end
+
+	function store.i32(memory, addr, value)
+		local data = memory.data
+
+		data[addr] = value & 0xFF
+		data[addr + 1] = value >> 8 & 0xFF
+		data[addr + 2] = value >> 16 & 0xFF
+		data[addr + 3] = value >> 24 & 0xFF
+	end
+
+	store.i64_n8 = store.i32_n8
+	store.i64_n16 = store.i32_n16
+	store.i64_n32 = store.i32
+
+	function store.i64(memory, addr, value)
+		store.i32(memory, addr, value)
+		store.i32(memory, addr + 4, value >> 32)
+	end
+
+	function store.f32(memory, addr, value)
+		store.i32(memory, addr, string_unpack("<I4", string_pack("<f", value)))
+	end
+
+	function store.f64(memory, addr, value)
+		store.i64(memory, addr, string_unpack("<i8", string_pack("<d", value)))
+	end
+
+	store.v128 = write
+
+	function store.string(memory, addr, data, len)
+		write(memory, addr, string_sub(data, 1, len or #data))
+	end
+
+	function store.copy(memory_1, addr_1, memory_2, addr_2, len)
+		table_move(memory_2.data, addr_2, addr_2 + len - 1, addr_1, memory_1.data)
+	end
+
+	function store.fill(memory, addr, len, value)
+		local bytes = memory.data
+
+		value = value & 0xFF
+
+		for i = addr, addr + len - 1 do
+			bytes[i] = value
+		end
+	end
+
+	function store.init(memory, addr, data, offset, len)
+		data = data or ""
+
+		trap_unless(offset + len <= #data, "out_of_bounds_memory_access", "out of bounds memory access")
+		trap_unless(addr + len <= memory.min * WASM_PAGE_SIZE, "out_of_bounds_memory_access", "out of bounds memory access")
+
+		write(memory, addr, string_sub(data, offset + 1, offset + len))
+	end
+
+	function allocator.new(min, max)
+		return { min = min, max = max, data = setmetatable({}, DATA_MT) }
+	end
+
+	-- Sizes are unsigned, so a negative one asks for more than any maximum
+	function allocator.grow(memory, num)
+		local old = memory.min
+		local new = old + num
+
+		if num < 0 or new > memory.max then
+			return -1
+		else
+			memory.min = new
+
+			return old
+		end
+	end
+
+	-- Integers are 64 bits wide already, so memories indexed by `i64` count
+	-- their pages the same way
+	function allocator.size_64(memory)
+		return memory.min
+	end
+
+	allocator.grow_64 = allocator.grow
+
+	-- Snapshots are the raw bytes of the memory, so their length gives the
+	-- number of pages to restore
+	function wasm_memory.save(memory)
+		local list = {}
+
+		for addr = 0, memory.min * WASM_PAGE_SIZE - 1, 4096 do
+			list[#list + 1] = read(memory, addr, 4096)
+		end
+
+		return table.concat(list)
+	end
+
+	function wasm_memory.load(memory, data)
+		local len = #data
+		local num = len // WASM_PAGE_SIZE
+
+		assert(len % WASM_PAGE_SIZE == 0 and num <= memory.max, "snapshot does not fit the memory")
+
+		local bytes = setmetatable({}, DATA_MT)
+
+		for i = 1, len do
+			local value = string_byte(data, i)
+
+			if value ~= 0 then
+				bytes[i - 1] = value
+			end
+		end
+
+		memory.data = bytes
+		memory.min = num
+	end
+
+	-- Strings are exchanged with host code as their raw bytes, with C strings
+	-- read up to their first zero byte, and every access checked against the
+	-- size of the memory
+	local function check_bounds(memory, addr, len)
+		local inside = addr >= 0 and addr + len <= memory.min * WASM_PAGE_SIZE
+
+		trap_unless(inside, "out_of_bounds_memory_access", "out of bounds memory access")
+	end
+
+	function wasm_string.read(memory, addr, len)
+		check_bounds(memory, addr, len)
+
+		return read(memory, addr, len)
+	end
+
+	function wasm_string.read_cstr(memory, addr)
+		local bytes = memory.data
+		local last = memory.min * WASM_PAGE_SIZE
+		local len = 0
+
+		while addr >= 0 and addr + len < last and bytes[addr + len] ~= 0 do
+			len = len + 1
+		end
+
+		check_bounds(memory, addr, len + 1)
+
+		return read(memory, addr, len)
+	end
+
+	function wasm_string.write(memory, addr, data)
+		check_bounds(memory, addr, #data)
+		write(memory, addr, data)
+	end
+
+	module.load = load
+	module.store = store
+	module.allocator = allocator
+	module.memory = wasm_memory
+	module.string = wasm_string
+end
+
+do
+	local data = {}
+
+	local string_byte = string.byte
+	local string_pack = string.pack
+	local string_sub = string.sub
+	local table_concat = table.concat
+
+	-- Every 4 bytes, read as a little endian word, are written as 5 digits in
+	-- base 85 from `#` up with `\` skipped, or as `z` when the word is zero
+	function data.decode(text, len)
+		local list = {}
+		local position = 1
+
+		while position <= #text do
+			local value = 0
+
+			if string_byte(text, position) == 122 then
+				position = position + 1
+			else
+				for i = position, position + 4 do
+					local digit = string_byte(text, i) - 35
+
+					if digit > 57 then
+						digit = digit - 1
+					end
+
+					value = value * 85 + digit
+				end
+
+				position = position + 5
+			end
+
+			list[#list + 1] = string_pack("<I4", value)
+		end
+
+		return string_sub(table_concat(list), 1, len)
+	end
+
+	module.data = data
+end
+
+do
+	local wasm_table = {}
+
+	local table_move = table.move
+
+	function wasm_table.init(target, addr, data, offset, len)
+		local count = data and data.n or 0
+
+		trap_unless(offset + len <= count, "out_of_bounds_table_access", "out of bounds table access")
+		trap_unless(addr + len <= target.min, "out_of_bounds_table_access", "out of bounds table access")
+
+		table_move(data or target.data, offset + 1, offset + len, addr, target.data)
+	end
+
+	function wasm_table.get(target, index)
+		trap_unless(index < target.min, "out_of_bounds_table_access", "out of bounds table access")
+
+		return target.data[index]
+	end
+
+	function wasm_table.set(target, index, value)
+		trap_unless(index < target.min, "out_of_bounds_table_access", "out of bounds table access")
+
+		target.data[index] = value
+	end
+
+	function wasm_table.grow(target, num, value)
+		local old = target.min
+		local new = old + num
+
+		if new > target.max then
+			return -1
+		end
+
+		for i = old, new - 1 do
+			target.data[i] = value
+		end
+
+		target.min = new
+
+		return old
+	end
+
+	function wasm_table.copy(target_1, addr_1, target_2, addr_2, len)
+		trap_unless(addr_1 + len <= target_1.min, "out_of_bounds_table_access", "out of bounds table access")
+		trap_unless(addr_2 + len <= target_2.min, "out_of_bounds_table_access", "out of bounds table access")
+
+		table_move(target_2.data, addr_2, addr_2 + len - 1, addr_1, target_1.data)
+	end
+
+	function wasm_table.fill(target, addr, len, value)
+		trap_unless(addr + len <= target.min, "out_of_bounds_table_access", "out of bounds table access")
+
+		for i = addr, addr + len - 1 do
+			target.data[i] = value
+		end
+	end
+
+	-- Snapshots hold the references themselves, so they only restore into
+	-- the same Lua state
+	function wasm_table.save(target)
+		return { min = target.min, data = table_move(target.data, 0, target.min - 1, 0, {}) }
+	end
+
+	function wasm_table.load(target, saved)
+		assert(saved.min <= target.max, "snapshot does not fit the table")
+
+		for i = saved.min, target.min - 1 do
+			target.data[i] = nil
+		end
+
+		table_move(saved.data, 0, saved.min - 1, 0, target.data)
+		target.min = saved.min
+	end
+
+	module.table = wasm_table
+end
+
+do
+	local global = {}
+
+	function global.save(target)
+		return target.value
+	end
+
+	function global.load(target, value)
+		target.value = value
+	end
+
+	module.global = global
+end
+
+do
+	local reference = {}
+
+	function reference.select(condition, on_true, on_false)
+		if condition ~= 0 then
+			return on_true
+		else
+			return on_false
+		end
+	end
+
+	function reference.as_non_null(value)
+		trap_unless(value ~= nil, "null_reference", "null reference")
+
+		return value
+	end
+
+	module.reference = reference
+end
+
+do
+	local atomic = {}
+
+	-- There is only ever one thread, so nothing can wake a waiter
+	function atomic.wait(value, expected, timeout)
+		if value ~= expected then
+			return 1
+		end
+
+		trap_unless(timeout >= 0, "blocking_wait", "wait would block forever")
+
+		return 2
+	end
+
+	function atomic.notify(memory, addr, count)
+		return 0
+	end
+
+	module.atomic = atomic
+end
+
+do
+	local exception = {}
+
+	-- Traps never carry this metatable, so only tables that do are
+	-- exceptions that a `catch_all` is allowed to handle
+	local EXCEPTION_MT = {}
+
+	function exception.throw(tag, ...)
+		local data = setmetatable({ ... }, EXCEPTION_MT)
+
+		data.tag = tag
+		data.skip = 0
+
+		error(data, 0)
+	end
+
+	-- Delegated exceptions must first pass through `skip` handlers
+	function exception.accept(data)
+		if getmetatable(data) ~= EXCEPTION_MT then
+			return false
+		elseif data.skip ~= 0 then
+			data.skip = data.skip - 1
+
+			return false
+		end
+
+		return true
+	end
+
+	function exception.delegate(data, skip)
+		if exception.accept(data) then
+			data.skip = skip
+		end
+
+		error(data, 0)
+	end
+
+	module.exception = exception
+end
+
+do
+	local simd = {}
+
+	local math_abs = math.abs
+	local math_min = math.min
+	local math_max = math.max
+	local math_sqrt = math.sqrt
+	local string_rep = string.rep
+	local table_unpack = table.unpack
+
+	local function lane_of(len, read, write, mask)
+		return {
+			len = len,
+			read = "<" .. string_rep(read, len),
+			write = "<" .. string_rep(write, len),
+			mask = mask,
+		}
+	end
+
+	-- Vectors are immutable strings of 16 bytes, which are unpacked into
+	-- lanes for every operation and packed again afterwards
+	local LANE_LIST = {
+		i8 = lane_of(16, "i1", "I1", 0xFF),
+		u8 = lane_of(16, "I1", "I1", 0xFF),
+		i16 = lane_of(8, "i2", "I2", 0xFFFF),
+		u16 = lane_of(8, "I2", "I2", 0xFFFF),
+		i32 = lane_of(4, "i4", "I4", 0xFFFFFFFF),
+		u32 = lane_of(4, "I4", "I4", 0xFFFFFFFF),
+		i64 = lane_of(2, "i8", "i8", -1),
+		u64 = lane_of(2, "I8", "i8", -1),
+		f32 = lane_of(4, "f", "f"),
+		f64 = lane_of(2, "d", "d"),
+	}
+
+	simd.ZERO = string_rep("\0", 16)
+
+	local function lanes_of(value, field)
+		local lane = LANE_LIST[field]
+		local list = { string_unpack(lane.read, value) }
+		local result = {}
+
+		for i = 0, lane.len - 1 do
+			result[i] = list[i + 1]
+		end
+
+		return result
+	end
+
+	-- Lanes that were never set are zero, like a freshly made vector
+	local function from_lanes(field, list)
+		local lane = LANE_LIST[field]
+		local mask = lane.mask
+		local data = {}
+
+		for i = 0, lane.len - 1 do
+			local num = list[i] or 0
+
+			if mask then
+				num = num & mask
+			end
+
+			data[i + 1] = num
+		end
+
+		return (string_pack(lane.write, table_unpack(data, 1, lane.len)))
+	end
+
+	local function clamp(num, low, high)
+		return (math_min(math_max(num, low), high))
+	end
+
+	local function map_unary(field, len, func)
+		return function(value)
+			local list, out = lanes_of(value, field), {}
+
+			for i = 0, len - 1 do
+				out[i] = func(list[i])
+			end
+
+			return from_lanes(field, out)
+		end
+	end
+
+	local function map_binary(field, len, func)
+		return function(lhs, rhs)
+			local list_1, list_2, out = lanes_of(lhs, field), lanes_of(rhs, field), {}
+
+			for i = 0, len - 1 do
+				out[i] = func(list_1[i], list_2[i])
+			end
+
+			return from_lanes(field, out)
+		end
+	end
+
+	local function map_convert(field, output, len, offset, func)
+		return function(value)
+			local list, out = lanes_of(value, field), {}
+
+			for i = 0, len - 1 do
+				out[i] = func(list[i + offset])
+			end
+
+			return from_lanes(output, out)
+		end
+	end
+
+	local function map_compare(field, output, len, func)
+		return function(lhs, rhs)
+			local list_1, list_2, out = lanes_of(lhs, field), lanes_of(rhs, field), {}
+
+			for i = 0, len - 1 do
+				if func(list_1[i], list_2[i]) then
+					out[i] = -1
+				end
+			end
+
+			return from_lanes(output, out)
+		end
+	end
+
+	local function map_shift(field, len, bits, func)
+		return function(value, count)
+			local list, out = lanes_of(value, field), {}
+
+			count = count & (bits - 1)
+
+			for i = 0, len - 1 do
+				out[i] = func(list[i], count)
+			end
+
+			return from_lanes(field, out)
+		end
+	end
+
+	local function map_narrow(field, output, len, low, high)
+		return function(lhs, rhs)
+			local list_1, list_2, out = lanes_of(lhs, field), lanes_of(rhs, field), {}
+
+			for i = 0, len - 1 do
+				out[i] = clamp(list_1[i], low, high)
+				out[i + len] = clamp(list_2[i], low, high)
+			end
+
+			return from_lanes(output, out)
+		end
+	end
+
+	local function map_pairwise(field, output, len)
+		return function(value)
+			local list, out = lanes_of(value, field), {}
+
+			for i = 0, len - 1 do
+				out[i] = list[i * 2] + list[i * 2 + 1]
+			end
+
+			return from_lanes(output, out)
+		end
+	end
+
+	local function map_extend_mul(field, output, len, offset)
+		return function(lhs, rhs)
+			local list_1, list_2, out = lanes_of(lhs, field), lanes_of(rhs, field), {}
+
+			for i = 0, len - 1 do
+				out[i] = list_1[i + offset] * list_2[i + offset]
+			end
+
+			return from_lanes(output, out)
+		end
+	end
+
+	local function extract_lane(field)
+		return function(value, lane)
+			return lanes_of(value, field)[lane]
+		end
+	end
+
+	local function replace_lane(field)
+		return function(value, num, lane)
+			local list = lanes_of(value, field)
+
+			list[lane] = num
+
+			return from_lanes(field, list)
+		end
+	end
+
+	local function splat(field, len)
+		return function(num)
+			local out = {}
+
+			for i = 0, len - 1 do
+				out[i] = num
+			end
+
+			return from_lanes(field, out)
+		end
+	end
+
+	local function all_true(field, len)
+		return function(value)
+			local list = lanes_of(value, field)
+
+			for i = 0, len - 1 do
+				if list[i] == 0 then
+					return 0
+				end
+			end
+
+			return 1
+		end
+	end
+
+	local function bitmask(field, len)
+		return function(value)
+			local list = lanes_of(value, field)
+			local result = 0
+
+			for i = 0, len - 1 do
+				if list[i] < 0 then
+					result = result | (1 << i)
+				end
+			end
+
+			return result
+		end
+	end
+
+	local function number_of(num)
+		return num
+	end
+
+	local function is_eq(lhs, rhs)
+		return lhs == rhs
+	end
+
+	local function is_ne(lhs, rhs)
+		return lhs ~= rhs
+	end
+
+	local function is_lt(lhs, rhs)
+		return lhs < rhs
+	end
+
+	local function is_gt(lhs, rhs)
+		return lhs > rhs
+	end
+
+	local function is_le(lhs, rhs)
+		return lhs <= rhs
+	end
+
+	local function is_ge(lhs, rhs)
+		return lhs >= rhs
+	end
+
+	local function bit_not(num)
+		return ~num
+	end
+
+	local function bit_and(lhs, rhs)
+		return lhs & rhs
+	end
+
+	local function bit_or(lhs, rhs)
+		return lhs | rhs
+	end
+
+	local function bit_xor(lhs, rhs)
+		return lhs ~ rhs
+	end
+
+	local function bit_lshift(num, count)
+		return num << count
+	end
+
+	local function bit_rshift(num, count)
+		return num >> count
+	end
+
+	local function bit_arshift(num, count)
+		return module.shr.i64(num, count)
+	end
+
+	local function add(lhs, rhs)
+		return lhs + rhs
+	end
+
+	local function sub(lhs, rhs)
+		return lhs - rhs
+	end
+
+	local function mul(lhs, rhs)
+		return lhs * rhs
+	end
+
+	local function div(lhs, rhs)
+		return lhs / rhs
+	end
+
+	local function neg(num)
+		return -num
+	end
+
+	local function abs(num)
+		if num < 0 then
+			return -num
+		else
+			return num
+		end
+	end
+
+	local function average(lhs, rhs)
+		return (lhs + rhs + 1) >> 1
+	end
+
+	local function p_min(lhs, rhs)
+		if rhs < lhs then
+			return rhs
+		else
+			return lhs
+		end
+	end
+
+	local function p_max(lhs, rhs)
+		if lhs < rhs then
+			return rhs
+		else
+			return lhs
+		end
+	end
+
+	local function popcnt(num)
+		local result = 0
+
+		while num ~= 0 do
+			result = result + (num & 1)
+			num = num >> 1
+		end
+
+		return result
+	end
+
+	local function add_saturate(low, high)
+		return function(lhs, rhs)
+			return clamp(lhs + rhs, low, high)
+		end
+	end
+
+	local function sub_saturate(low, high)
+		return function(lhs, rhs)
+			return clamp(lhs - rhs, low, high)
+		end
+	end
+
+	local saturate_i32 = module.saturate.i32_f64
+	local saturate_u32 = module.saturate.u32_f64
+
+	function simd.from_u32(data_1, data_2, data_3, data_4)
+		return (string_pack("<I4I4I4I4", data_1, data_2, data_3, data_4))
+	end
+
+	function simd.i8x16_shuffle(lhs, rhs, lanes)
+		local list_1, list_2, index, out = lanes_of(lhs, "u8"), lanes_of(rhs, "u8"), lanes_of(lanes, "u8"), {}
+
+		for i = 0, 15 do
+			local lane = index[i]
+
+			if lane < 16 then
+				out[i] = list_1[lane]
+			else
+				out[i] = list_2[lane - 16]
+			end
+		end
+
+		return from_lanes("u8", out)
+	end
+
+	function simd.i8x16_swizzle(lhs, rhs)
+		local list, index, out = lanes_of(lhs, "u8"), lanes_of(rhs, "u8"), {}
+
+		for i = 0, 15 do
+			local lane = index[i]
+
+			if lane < 16 then
+				out[i] = list[lane]
+			end
+		end
+
+		return from_lanes("u8", out)
+	end
+
+	simd.i8x16_extract_lane_s = extract_lane("i8")
+	simd.i8x16_extract_lane_u = extract_lane("u8")
+	simd.i16x8_extract_lane_s = extract_lane("i16")
+	simd.i16x8_extract_lane_u = extract_lane("u16")
+	simd.i32x4_extract_lane = extract_lane("i32")
+	simd.i64x2_extract_lane = extract_lane("i64")
+	simd.f32x4_extract_lane = extract_lane("f32")
+	simd.f64x2_extract_lane = extract_lane("f64")
+
+	simd.i8x16_replace_lane = replace_lane("i8")
+	simd.i16x8_replace_lane = replace_lane("i16")
+	simd.i32x4_replace_lane = replace_lane("i32")
+	simd.i64x2_replace_lane = replace_lane("i64")
+	simd.f32x4_replace_lane = replace_lane("f32")
+	simd.f64x2_replace_lane = replace_lane("f64")
+
+	simd.i8x16_splat = splat("i8", 16)
+	simd.i16x8_splat = splat("i16", 8)
+	simd.i32x4_splat = splat("i32", 4)
+	simd.i64x2_splat = splat("i64", 2)
+	simd.f32x4_splat = splat("f32", 4)
+	simd.f64x2_splat = splat("f64", 2)
+
+	simd.i8x16_eq = map_compare("i8", "i8", 16, is_eq)
+	simd.i8x16_ne = map_compare("i8", "i8", 16, is_ne)
+	simd.i8x16_lt_s = map_compare("i8", "i8", 16, is_lt)
+	simd.i8x16_lt_u = map_compare("u8", "i8", 16, is_lt)
+	simd.i8x16_gt_s = map_compare("i8", "i8", 16, is_gt)
+	simd.i8x16_gt_u = map_compare("u8", "i8", 16, is_gt)
+	simd.i8x16_le_s = map_compare("i8", "i8", 16, is_le)
+	simd.i8x16_le_u = map_compare("u8", "i8", 16, is_le)
+	simd.i8x16_ge_s = map_compare("i8", "i8", 16, is_ge)
+	simd.i8x16_ge_u = map_compare("u8", "i8", 16, is_ge)
+
+	simd.i16x8_eq = map_compare("i16", "i16", 8, is_eq)
+	simd.i16x8_ne = map_compare("i16", "i16", 8, is_ne)
+	simd.i16x8_lt_s = map_compare("i16", "i16", 8, is_lt)
+	simd.i16x8_lt_u = map_compare("u16", "i16", 8, is_lt)
+	simd.i16x8_gt_s = map_compare("i16", "i16", 8, is_gt)
+	simd.i16x8_gt_u = map_compare("u16", "i16", 8, is_gt)
+	simd.i16x8_le_s = map_compare("i16", "i16", 8, is_le)
+	simd.i16x8_le_u = map_compare("u16", "i16", 8, is_le)
+	simd.i16x8_ge_s = map_compare("i16", "i16", 8, is_ge)
+	simd.i16x8_ge_u = map_compare("u16", "i16", 8, is_ge)
+
+	simd.i32x4_eq = map_compare("i32", "i32", 4, is_eq)
+	simd.i32x4_ne = map_compare("i32", "i32", 4, is_ne)
+	simd.i32x4_lt_s = map_compare("i32", "i32", 4, is_lt)
+	simd.i32x4_lt_u = map_compare("u32", "i32", 4, is_lt)
+	simd.i32x4_gt_s = map_compare("i32", "i32", 4, is_gt)
+	simd.i32x4_gt_u = map_compare("u32", "i32", 4, is_gt)
+	simd.i32x4_le_s = map_compare("i32", "i32", 4, is_le)
+	simd.i32x4_le_u = map_compare("u32", "i32", 4, is_le)
+	simd.i32x4_ge_s = map_compare("i32", "i32", 4, is_ge)
+	simd.i32x4_ge_u = map_compare("u32", "i32", 4, is_ge)
+
+	simd.i64x2_eq = map_compare("i64", "i64", 2, is_eq)
+	simd.i64x2_ne = map_compare("i64", "i64", 2, is_ne)
+	simd.i64x2_lt_s = map_compare("i64", "i64", 2, is_lt)
+	simd.i64x2_gt_s = map_compare("i64", "i64", 2, is_gt)
+	simd.i64x2_le_s = map_compare("i64", "i64", 2, is_le)
+	simd.i64x2_ge_s = map_compare("i64", "i64", 2, is_ge)
+
+	simd.f32x4_eq = map_compare("f32", "i32", 4, is_eq)
+	simd.f32x4_ne = map_compare("f32", "i32", 4, is_ne)
+	simd.f32x4_lt = map_compare("f32", "i32", 4, is_lt)
+	simd.f32x4_gt = map_compare("f32", "i32", 4, is_gt)
+	simd.f32x4_le = map_compare("f32", "i32", 4, is_le)
+	simd.f32x4_ge = map_compare("f32", "i32", 4, is_ge)
+
+	simd.f64x2_eq = map_compare("f64", "i64", 2, is_eq)
+	simd.f64x2_ne = map_compare("f64", "i64", 2, is_ne)
+	simd.f64x2_lt = map_compare("f64", "i64", 2, is_lt)
+	simd.f64x2_gt = map_compare("f64", "i64", 2, is_gt)
+	simd.f64x2_le = map_compare("f64", "i64", 2, is_le)
+	simd.f64x2_ge = map_compare("f64", "i64", 2, is_ge)
+
+	simd.v128_not = map_unary("i64", 2, bit_not)
+	simd.v128_and = map_binary("i64", 2, bit_and)
+	simd.v128_or = map_binary("i64", 2, bit_or)
+	simd.v128_xor = map_binary("i64", 2, bit_xor)
+
+	simd.v128_andnot = map_binary("i64", 2, function(lhs, rhs)
+		return lhs & ~rhs
+	end)
+
+	function simd.v128_bitselect(lhs, rhs, mask)
+		local list_1, list_2, list_3, out = lanes_of(lhs, "i64"), lanes_of(rhs, "i64"), lanes_of(mask, "i64"), {}
+
+		for i = 0, 1 do
+			local bits = list_3[i]
+
+			out[i] = (list_1[i] & bits) | (list_2[i] & ~bits)
+		end
+
+		return from_lanes("i64", out)
+	end
+
+	function simd.v128_any_true(value)
+		if value ~= simd.ZERO then
+			return 1
+		else
+			return 0
+		end
+	end
+
+	simd.i8x16_abs = map_unary("i8", 16, abs)
+	simd.i8x16_neg = map_unary("i8", 16, neg)
+	simd.i8x16_popcnt = map_unary("u8", 16, popcnt)
+	simd.i8x16_all_true = all_true("u8", 16)
+	simd.i8x16_bitmask = bitmask("i8", 16)
+	simd.i8x16_narrow_i16x8_s = map_narrow("i16", "i8", 8, -0x80, 0x7F)
+	simd.i8x16_narrow_i16x8_u = map_narrow("i16", "u8", 8, 0, 0xFF)
+	simd.i8x16_shl = map_shift("i8", 16, 8, bit_lshift)
+	simd.i8x16_shr_s = map_shift("i8", 16, 8, bit_arshift)
+	simd.i8x16_shr_u = map_shift("u8", 16, 8, bit_rshift)
+	simd.i8x16_add = map_binary("i8", 16, add)
+	simd.i8x16_add_sat_s = map_binary("i8", 16, add_saturate(-0x80, 0x7F))
+	simd.i8x16_add_sat_u = map_binary("u8", 16, add_saturate(0, 0xFF))
+	simd.i8x16_sub = map_binary("i8", 16, sub)
+	simd.i8x16_sub_sat_s = map_binary("i8", 16, sub_saturate(-0x80, 0x7F))
+	simd.i8x16_sub_sat_u = map_binary("u8", 16, sub_saturate(0, 0xFF))
+	simd.i8x16_min_s = map_binary("i8", 16, math_min)
+	simd.i8x16_min_u = map_binary("u8", 16, math_min)
+	simd.i8x16_max_s = map_binary("i8", 16, math_max)
+	simd.i8x16_max_u = map_binary("u8", 16, math_max)
+	simd.i8x16_avgr_u = map_binary("u8", 16, average)
+
+	simd.i16x8_extadd_pairwise_i8x16_s = map_pairwise("i8", "i16", 8)
+	simd.i16x8_extadd_pairwise_i8x16_u = map_pairwise("u8", "i16", 8)
+	simd.i16x8_abs = map_unary("i16", 8, abs)
+	simd.i16x8_neg = map_unary("i16", 8, neg)
+
+	simd.i16x8_q15mulr_sat_s = map_binary("i16", 8, function(lhs, rhs)
+		return clamp(bit_arshift(lhs * rhs + 0x4000, 15), -0x8000, 0x7FFF)
+	end)
+
+	simd.i16x8_all_true = all_true("u16", 8)
+	simd.i16x8_bitmask = bitmask("i16", 8)
+	simd.i16x8_narrow_i32x4_s = map_narrow("i32", "i16", 4, -0x8000, 0x7FFF)
+	simd.i16x8_narrow_i32x4_u = map_narrow("i32", "u16", 4, 0, 0xFFFF)
+	simd.i16x8_extend_low_i8x16_s = map_convert("i8", "i16", 8, 0, number_of)
+	simd.i16x8_extend_high_i8x16_s = map_convert("i8", "i16", 8, 8, number_of)
+	simd.i16x8_extend_low_i8x16_u = map_convert("u8", "i16", 8, 0, number_of)
+	simd.i16x8_extend_high_i8x16_u = map_convert("u8", "i16", 8, 8, number_of)
+	simd.i16x8_shl = map_shift("i16", 8, 16, bit_lshift)
+	simd.i16x8_shr_s = map_shift("i16", 8, 16, bit_arshift)
+	simd.i16x8_shr_u = map_shift("u16", 8, 16, bit_rshift)
+	simd.i16x8_add = map_binary("i16", 8, add)
+	simd.i16x8_add_sat_s = map_binary("i16", 8, add_saturate(-0x8000, 0x7FFF))
+	simd.i16x8_add_sat_u = map_binary("u16", 8, add_saturate(0, 0xFFFF))
+	simd.i16x8_sub = map_binary("i16", 8, sub)
+	simd.i16x8_sub_sat_s = map_binary("i16", 8, sub_saturate(-0x8000, 0x7FFF))
+	simd.i16x8_sub_sat_u = map_binary("u16", 8, sub_saturate(0, 0xFFFF))
+	simd.i16x8_mul = map_binary("i16", 8, mul)
+	simd.i16x8_min_s = map_binary("i16", 8, math_min)
+	simd.i16x8_min_u = map_binary("u16", 8, math_min)
+	simd.i16x8_max_s = map_binary("i16", 8, math_max)
+	simd.i16x8_max_u = map_binary("u16", 8, math_max)
+	simd.i16x8_avgr_u = map_binary("u16", 8, average)
+	simd.i16x8_extmul_low_i8x16_s = map_extend_mul("i8", "i16", 8, 0)
+	simd.i16x8_extmul_high_i8x16_s = map_extend_mul("i8", "i16", 8, 8)
+	simd.i16x8_extmul_low_i8x16_u = map_extend_mul("u8", "i16", 8, 0)
+	simd.i16x8_extmul_high_i8x16_u = map_extend_mul("u8", "i16", 8, 8)
+
+	simd.i32x4_extadd_pairwise_i16x8_s = map_pairwise("i16", "i32", 4)
+	simd.i32x4_extadd_pairwise_i16x8_u = map_pairwise("u16", "i32", 4)
+	simd.i32x4_abs = map_unary("i32", 4, abs)
+	simd.i32x4_neg = map_unary("i32", 4, neg)
+	simd.i32x4_all_true = all_true("u32", 4)
+	simd.i32x4_bitmask = bitmask("i32", 4)
+	simd.i32x4_extend_low_i16x8_s = map_convert("i16", "i32", 4, 0, number_of)
+	simd.i32x4_extend_high_i16x8_s = map_convert("i16", "i32", 4, 4, number_of)
+	simd.i32x4_extend_low_i16x8_u = map_convert("u16", "i32", 4, 0, number_of)
+	simd.i32x4_extend_high_i16x8_u = map_convert("u16", "i32", 4, 4, number_of)
+	simd.i32x4_shl = map_shift("i32", 4, 32, bit_lshift)
+	simd.i32x4_shr_s = map_shift("i32", 4, 32, bit_arshift)
+	simd.i32x4_shr_u = map_shift("u32", 4, 32, bit_rshift)
+	simd.i32x4_add = map_binary("i32", 4, add)
+	simd.i32x4_sub = map_binary("i32", 4, sub)
+	simd.i32x4_mul = map_binary("i32", 4, mul)
+	simd.i32x4_min_s = map_binary("i32", 4, math_min)
+	simd.i32x4_min_u = map_binary("u32", 4, math_min)
+	simd.i32x4_max_s = map_binary("i32", 4, math_max)
+	simd.i32x4_max_u = map_binary("u32", 4, math_max)
+
+	function simd.i32x4_dot_i16x8_s(lhs, rhs)
+		local list_1, list_2, out = lanes_of(lhs, "i16"), lanes_of(rhs, "i16"), {}
+
+		for i = 0, 3 do
+			local a = list_1[i * 2] * list_2[i * 2]
+			local b = list_1[i * 2 + 1] * list_2[i * 2 + 1]
+
+			out[i] = a + b
+		end
+
+		return from_lanes("i32", out)
+	end
+
+	simd.i32x4_extmul_low_i16x8_s = map_extend_mul("i16", "i32", 4, 0)
+	simd.i32x4_extmul_high_i16x8_s = map_extend_mul("i16", "i32", 4, 4)
+	simd.i32x4_extmul_low_i16x8_u = map_extend_mul("u16", "u32", 4, 0)
+	simd.i32x4_extmul_high_i16x8_u = map_extend_mul("u16", "u32", 4, 4)
+
+	simd.i64x2_abs = map_unary("i64", 2, abs)
+	simd.i64x2_neg = map_unary("i64", 2, neg)
+	simd.i64x2_all_true = all_true("u64", 2)
+	simd.i64x2_bitmask = bitmask("i64", 2)
+	simd.i64x2_extend_low_i32x4_s = map_convert("i32", "i64", 2, 0, number_of)
+	simd.i64x2_extend_high_i32x4_s = map_convert("i32", "i64", 2, 2, number_of)
+	simd.i64x2_extend_low_i32x4_u = map_convert("u32", "i64", 2, 0, number_of)
+	simd.i64x2_extend_high_i32x4_u = map_convert("u32", "i64", 2, 2, number_of)
+	simd.i64x2_shl = map_shift("i64", 2, 64, bit_lshift)
+	simd.i64x2_shr_s = map_shift("i64", 2, 64, bit_arshift)
+	simd.i64x2_shr_u = map_shift("i64", 2, 64, bit_rshift)
+	simd.i64x2_add = map_binary("i64", 2, add)
+	simd.i64x2_sub = map_binary("i64", 2, sub)
+	simd.i64x2_mul = map_binary("i64", 2, mul)
+	simd.i64x2_extmul_low_i32x4_s = map_extend_mul("i32", "i64", 2, 0)
+	simd.i64x2_extmul_high_i32x4_s = map_extend_mul("i32", "i64", 2, 2)
+	simd.i64x2_extmul_low_i32x4_u = map_extend_mul("u32", "u64", 2, 0)
+	simd.i64x2_extmul_high_i32x4_u = map_extend_mul("u32", "u64", 2, 2)
+
+	simd.f32x4_ceil = map_unary("f32", 4, module.ceil.f32)
+	simd.f32x4_floor = map_unary("f32", 4, module.floor.f32)
+	simd.f32x4_trunc = map_unary("f32", 4, module.truncate.f32)
+	simd.f32x4_nearest = map_unary("f32", 4, module.nearest.f32)
+	simd.f32x4_abs = map_unary("f32", 4, math_abs)
+	simd.f32x4_neg = map_unary("f32", 4, neg)
+	simd.f32x4_sqrt = map_unary("f32", 4, math_sqrt)
+	simd.f32x4_add = map_binary("f32", 4, add)
+	simd.f32x4_sub = map_binary("f32", 4, sub)
+	simd.f32x4_mul = map_binary("f32", 4, mul)
+	simd.f32x4_div = map_binary("f32", 4, div)
+	simd.f32x4_min = map_binary("f32", 4, module.min.f32)
+	simd.f32x4_max = map_binary("f32", 4, module.max.f32)
+	simd.f32x4_pmin = map_binary("f32", 4, p_min)
+	simd.f32x4_pmax = map_binary("f32", 4, p_max)
+
+	simd.f64x2_ceil = map_unary("f64", 2, module.ceil.f64)
+	simd.f64x2_floor = map_unary("f64", 2, module.floor.f64)
+	simd.f64x2_trunc = map_unary("f64", 2, module.truncate.f64)
+	simd.f64x2_nearest = map_unary("f64", 2, module.nearest.f64)
+	simd.f64x2_abs = map_unary("f64", 2, math_abs)
+	simd.f64x2_neg = map_unary("f64", 2, neg)
+	simd.f64x2_sqrt = map_unary("f64", 2, math_sqrt)
+	simd.f64x2_add = map_binary("f64", 2, add)
+	simd.f64x2_sub = map_binary("f64", 2, sub)
+	simd.f64x2_mul = map_binary("f64", 2, mul)
+	simd.f64x2_div = map_binary("f64", 2, div)
+	simd.f64x2_min = map_binary("f64", 2, module.min.f64)
+	simd.f64x2_max = map_binary("f64", 2, module.max.f64)
+	simd.f64x2_pmin = map_binary("f64", 2, p_min)
+	simd.f64x2_pmax = map_binary("f64", 2, p_max)
+
+	simd.i32x4_trunc_sat_f32x4_s = map_convert("f32", "i32", 4, 0, saturate_i32)
+	simd.i32x4_trunc_sat_f32x4_u = map_convert("f32", "i32", 4, 0, saturate_u32)
+	simd.f32x4_convert_i32x4_s = map_convert("i32", "f32", 4, 0, number_of)
+	simd.f32x4_convert_i32x4_u = map_convert("u32", "f32", 4, 0, number_of)
+	simd.i32x4_trunc_sat_f64x2_s_zero = map_convert("f64", "i32", 2, 0, saturate_i32)
+	simd.i32x4_trunc_sat_f64x2_u_zero = map_convert("f64", "i32", 2, 0, saturate_u32)
+	simd.f64x2_convert_low_i32x4_s = map_convert("i32", "f64", 2, 0, number_of)
+	simd.f64x2_convert_low_i32x4_u = map_convert("u32", "f64", 2, 0, number_of)
+	simd.f32x4_demote_f64x2_zero = map_convert("f64", "f32", 2, 0, number_of)
+	simd.f64x2_promote_low_f32x4 = map_convert("f32", "f64", 2, 0, number_of)
+
+	module.simd = simd
+end
+
+return module
@@ -0,0 +1 @@
+pub mod into_string;
@@ -0,0 +1,180 @@
+use std::io::{Result, Write};
+
+use codegen_core::{Backend, BranchStyle, Dialect, Mapping, Options, Split};
+use wasm_ast::{
+	module::{Module, TypeInfo},
+	node::{BinOpType, CmpOpType, Expression, FuncData, UnOpType, Value},
+};
+use wasmparser::Operator;
+
+use crate::analyzer::into_string::{IntoNameTuple, TryIntoSymbol};
+
+/// Lowers functions to Lua 5.4, which dialects that compile to it can reuse.
+pub struct Lua54;
+
+impl Backend for Lua54 {
+	fn write_function(&self, func: &FuncData, options: &Options, w: &mut dyn Write) -> Result<()> {
+		codegen_core::write_function(self, func, options, w)
+	}
+
+	fn write_function_marked(
+		&self,
+		func: &FuncData,
+		options: &Options,
+		w: &mut dyn Write,
+	) -> Result<()> {
+		codegen_core::write_function_marked(self, func, options, w)
+	}
+
+	fn write_expression(&self, expr: &Expression, w: &mut dyn Write) -> Result<()> {
+		codegen_core::write_expression(self, expr, w)
+	}
+
+	fn un_op_name(&self, op: UnOpType) -> (&'static str, &'static str) {
+		op.into_name_tuple()
+	}
+
+	fn bin_op_name(&self, op: BinOpType) -> Option<(&'static str, &'static str)> {
+		op.try_into_symbol().is_none().then(|| op.into_name_tuple())
+	}
+
+	fn cmp_op_name(&self, op: CmpOpType) -> Option<(&'static str, &'static str)> {
+		op.try_into_symbol().is_none().then(|| op.into_name_tuple())
+	}
+
+	fn value_name(&self, value: Value) -> Option<(&'static str, &'static str)> {
+		let name = match value {
+			Value::F32(v) if v.is_nan() => ("reinterpret", "f32_i32"),
+			Value::F64(v) if v.is_nan() => ("reinterpret", "f64_i64"),
+			_ => return None,
+		};
+
+		Some(name)
+	}
+
+	fn write_local_operation(&self, head: &str, tail: &str, w: &mut dyn Write) -> Result<()> {
+		write!(w, "local {head}_{tail} = ")?;
+
+		match (head, tail) {
+			("abs", _) | ("sqrt", "f64") => write!(w, "math.{head}"),
+			("math", _) => write!(w, "math.{tail}"),
+			_ => write!(w, "rt.{head}.{tail}"),
+		}?;
+
+		writeln!(w)
+	}
+
+	fn has_native_i64(&self) -> bool {
+		true
+	}
+}
+
+// Integers are native, so `i64` is written as is and `i32` is kept signed like
+// in LuaJIT, but wrapped inline rather than by the runtime
+impl Dialect for Lua54 {
+	fn branch_style(&self) -> BranchStyle {
+		BranchStyle::Goto
+	}
+
+	fn is_signed(&self) -> bool {
+		true
+	}
+
+	fn has_wrapping_integers(&self) -> bool {
+		true
+	}
+
+	fn has_unsigned_less(&self) -> bool {
+		true
+	}
+
+	fn bin_op_symbol(&self, op: BinOpType) -> Option<&'static str> {
+		op.try_into_symbol()
+	}
+
+	fn cmp_op_symbol(&self, op: CmpOpType) -> Option<&'static str> {
+		op.try_into_symbol()
+	}
+
+	fn write_i64(&self, number: i64, w: &mut dyn Write) -> Result<()> {
+		// The magnitude of the smallest integer would be read as a float
+		if number == i64::MIN {
+			write!(w, "math.mininteger")
+		} else {
+			write!(w, "{number}")
+		}
+	}
+}
+
+/// # Errors
+/// Returns `Err` if writing to `Write` failed.
+pub fn from_inst_list(code: &[Operator], type_info: &TypeInfo, w: &mut dyn Write) -> Result<()> {
+	codegen_core::from_inst_list(&Lua54, code, type_info, w)
+}
+
+/// # Errors
+/// Returns `Err` if writing to `Write` failed.
+pub fn from_module_typed(wasm: &Module, type_info: &TypeInfo, w: &mut dyn Write) -> Result<()> {
+	codegen_core::from_module_typed(&Lua54, wasm, type_info, w)
+}
+
+/// Translates the module, also embedding the contents of every custom section
+/// named in `name_list` as a string in the `custom_list` of the instance.
+///
+/// # Errors
+/// Returns `Err` if writing to `Write` failed.
+pub fn from_module_custom(
+	wasm: &Module,
+	type_info: &TypeInfo,
+	name_list: &[&str],
+	options: &Options,
+	w: &mut dyn Write,
+) -> Result<()> {
+	codegen_core::from_module_custom(&Lua54, wasm, type_info, name_list, options, w)
+}
+
+/// Translates the module as in [`from_module_custom`], also returning the
+/// line that each function and statement starts at for a source map.
+///
+/// # Errors
+/// Returns `Err` if writing to `Write` failed.
+pub fn from_module_mapped(
+	wasm: &Module,
+	type_info: &TypeInfo,
+	name_list: &[&str],
+	options: &Options,
+	w: &mut dyn Write,
+) -> Result<Vec<Mapping>> {
+	codegen_core::from_module_mapped(&Lua54, wasm, type_info, name_list, options, w)
+}
+
+/// Translates the module with its functions moved out to chunks, which are
+/// returned to be written where the `import` of `split` loads them from.
+///
+/// # Errors
+/// Returns `Err` if writing to `Write` failed.
+pub fn from_module_split(
+	wasm: &Module,
+	type_info: &TypeInfo,
+	name_list: &[&str],
+	options: &Options,
+	split: &Split,
+	w: &mut dyn Write,
+) -> Result<Vec<Vec<u8>>> {
+	codegen_core::from_module_split(&Lua54, wasm, type_info, name_list, options, split, w)
+}
+
+/// # Errors
+/// Returns `Err` if writing to `Write` failed.
+pub fn from_module_untyped(wasm: &Module, w: &mut dyn Write) -> Result<()> {
+	codegen_core::from_module_untyped(&Lua54, wasm, w)
+}
+
+/// Writes a script that checks the runtime against edge cases, as described
+/// in [`codegen_core::write_self_test`], to be run after the runtime.
+///
+/// # Errors
+/// Returns `Err` if writing to `Write` failed.
+pub fn write_self_test(w: &mut dyn Write) -> Result<()> {
+	codegen_core::write_self_test(&Lua54, w)
+}
@@ -23,6 +23,105 @@ local function truncate_f64(num)
 	end
 end
 
+local round_f32
+
+do
+	local RE_INSTANCE = ffi.new([[union {
+		float f32;
+	}]])
+
+	-- Doubles hold more than twice the bits of a float, so rounding the
+	-- exact result of an operation on two floats to a double and then to a
+	-- float gives the same float as rounding it once
+	function round_f32(num)
+		RE_INSTANCE.f32 = num
+
+		return RE_INSTANCE.f32
+	end
+end
+
+-- Traps are raised as a table naming their kind, their message, and the index
+-- of the function they were raised in, unless the instance is configured with
+-- "message", which raises just their message, or with a handler whose result
+-- is raised instead
+local trap = {}
+
+do
+	local TRAP_MT = {
+		__tostring = function(data)
+			return data.info
+		end,
+	}
+
+	-- Functions are registered by every instance so that the innermost one
+	-- on the stack can be named when a trap is raised
+	local FUNC_INDEX = setmetatable({}, { __mode = "k" })
+
+	local debug_getinfo = debug and debug.getinfo
+
+	local on_trap = nil
+
+	function trap.configure(value)
+		on_trap = value
+	end
+
+	function trap.register(func_list, first, last)
+		for i = first, last do
+			local func = func_list[i]
+
+			if func then
+				FUNC_INDEX[func] = i
+			end
+		end
+	end
+
+	local function find_func()
+		if not debug_getinfo then
+			return nil
+		end
+
+		local level = 1
+
+		while true do
+			local info = debug_getinfo(level, "f")
+
+			if info == nil then
+				return nil
+			end
+
+			local index = FUNC_INDEX[info.func]
+
+			if index then
+				return index
+			end
+
+			level = level + 1
+		end
+	end
+
+	function trap.raise(kind, info)
+		if on_trap == "message" then
+			error(info, 0)
+		end
+
+		local func = find_func()
+
+		if on_trap == nil or on_trap == "value" then
+			error(setmetatable({ kind = kind, info = info, func = func }, TRAP_MT), 0)
+		else
+			error(on_trap(kind, info, func) or info, 0)
+		end
+	end
+
+	module.trap = trap
+end
+
+local function trap_unless(condition, kind, info)
+	if not condition then
+		trap.raise(kind, info)
+	end
+end
+
 do
 	local add = {}
 	local sub = {}
@@ -34,10 +133,15 @@ do
 	local max = {}
 	local copysign = {}
 	local nearest = {}
+	local sqrt = {}
 
 	local math_abs = math.abs
+	local math_fmod = math.fmod
 	local math_min = math.min
 	local math_max = math.max
+	local math_sqrt = math.sqrt
+
+	local NUM_MIN_I64 = bit.lshift(NUM_ONE, 63)
 
 	local RE_INSTANCE = ffi.new([[union {
 		double f64;
@@ -65,13 +169,14 @@ do
 	end
 
 	function div.i32(lhs, rhs)
-		assert(rhs ~= 0, "division by zero")
+		trap_unless(rhs ~= 0, "integer_divide_by_zero", "division by zero")
+		trap_unless(lhs ~= -0x80000000 or rhs ~= -1, "integer_overflow", "integer overflow")
 
 		return (truncate_f64(lhs / rhs))
 	end
 
 	function div.u32(lhs, rhs)
-		assert(rhs ~= 0, "division by zero")
+		trap_unless(rhs ~= 0, "integer_divide_by_zero", "division by zero")
 
 		lhs = to_number(u32(lhs))
 		rhs = to_number(u32(rhs))
@@ -80,7 +185,7 @@ do
 	end
 
 	function rem.u32(lhs, rhs)
-		assert(rhs ~= 0, "division by zero")
+		trap_unless(rhs ~= 0, "integer_divide_by_zero", "division by zero")
 
 		lhs = to_number(u32(lhs))
 		rhs = to_number(u32(rhs))
@@ -88,18 +193,57 @@ do
 		return (to_signed(lhs % rhs))
 	end
 
+	function rem.i32(lhs, rhs)
+		trap_unless(rhs ~= 0, "integer_divide_by_zero", "division by zero")
+
+		return (to_signed(math_fmod(lhs, rhs)))
+	end
+
+	function div.i64(lhs, rhs)
+		trap_unless(rhs ~= 0, "integer_divide_by_zero", "division by zero")
+		trap_unless(lhs ~= NUM_MIN_I64 or rhs ~= -1, "integer_overflow", "integer overflow")
+
+		return lhs / rhs
+	end
+
+	function rem.i64(lhs, rhs)
+		trap_unless(rhs ~= 0, "integer_divide_by_zero", "division by zero")
+
+		return lhs % rhs
+	end
+
 	function div.u64(lhs, rhs)
-		assert(rhs ~= 0, "division by zero")
+		trap_unless(rhs ~= 0, "integer_divide_by_zero", "division by zero")
 
 		return (i64(u64(lhs) / u64(rhs)))
 	end
 
 	function rem.u64(lhs, rhs)
-		assert(rhs ~= 0, "division by zero")
+		trap_unless(rhs ~= 0, "integer_divide_by_zero", "division by zero")
 
 		return (i64(u64(lhs) % u64(rhs)))
 	end
 
+	function add.f32(lhs, rhs)
+		return round_f32(lhs + rhs)
+	end
+
+	function sub.f32(lhs, rhs)
+		return round_f32(lhs - rhs)
+	end
+
+	function mul.f32(lhs, rhs)
+		return round_f32(lhs * rhs)
+	end
+
+	function div.f32(lhs, rhs)
+		return round_f32(lhs / rhs)
+	end
+
+	function sqrt.f32(num)
+		return round_f32(math_sqrt(num))
+	end
+
 	function neg.f32(num)
 		return -num
 	end
@@ -164,6 +308,7 @@ do
 	module.neg = neg
 	module.copysign = copysign
 	module.nearest = nearest
+	module.sqrt = sqrt
 end
 
 do
@@ -407,6 +552,7 @@ do
 	local NUM_MIN_I64 = bit.lshift(NUM_ONE, 63)
 	local NUM_MAX_I64 = bit.bnot(NUM_MIN_I64)
 	local NUM_MAX_U64 = bit.bnot(NUM_ZERO)
+	local NUM_MAX_SAFE = bit.lshift(u64(1), 53)
 
 	-- This would surely be an issue in a multi-thread environment...
 	-- ... thankfully this isn't one.
@@ -415,8 +561,25 @@ do
 		int64_t i64;
 		float f32;
 		double f64;
+		struct { int32_t a32, b32; };
 	}]])
 
+	-- LuaJIT keeps the type of every other value in the payload of negative
+	-- NaNs past this, so those lose their payload instead of becoming one
+	local NUM_MIN_TAG = -0x70000
+	local NUM_NAN_HIGH = -0x80000
+
+	local function read_f64()
+		local high = RE_INSTANCE.b32
+
+		if high < 0 and high >= NUM_MIN_TAG then
+			RE_INSTANCE.a32 = 0
+			RE_INSTANCE.b32 = NUM_NAN_HIGH
+		end
+
+		return RE_INSTANCE.f64
+	end
+
 	function wrap.i32_i64(num)
 		RE_INSTANCE.i64 = num
 
@@ -553,28 +716,70 @@ do
 	end
 
 	function convert.f32_i32(num)
-		return num
+		return round_f32(num)
 	end
 
 	function convert.f32_u32(num)
-		return (to_number(u32(num)))
+		return round_f32(to_number(u32(num)))
 	end
 
+	-- Values past 2^53 would round twice on their way to a float, so the bits
+	-- a double cannot hold are folded into one that only keeps them from
+	-- looking like a tie
 	function convert.f32_u64(num)
-		return (to_number(u64(num)))
+		num = u64(num)
+
+		if num >= NUM_MAX_SAFE then
+			local sticky = bit_and(num, 0x7FF) ~= 0 and 0x800 or 0
+
+			num = bit.bor(bit_and(num, bit.bnot(0x7FF * NUM_ONE)), sticky)
+		end
+
+		return round_f32(to_number(num))
 	end
 
-	convert.f64_i32 = convert.f32_i32
-	convert.f64_u32 = convert.f32_u32
-	convert.f64_u64 = convert.f32_u64
+	function convert.f32_i64(num)
+		if num < 0 then
+			return -convert.f32_u64(-num)
+		else
+			return convert.f32_u64(num)
+		end
+	end
 
-	function demote.f32_f64(num)
+	function convert.f64_i32(num)
 		return num
 	end
 
-	promote.f64_f32 = demote.f32_f64
+	function convert.f64_u32(num)
+		return (to_number(u32(num)))
+	end
+
+	function convert.f64_u64(num)
+		return (to_number(u64(num)))
+	end
+
+	demote.f32_f64 = round_f32
+
+	function promote.f64_f32(num)
+		return num
+	end
 
+	-- Converting between floats and doubles quiets signaling NaNs, so the
+	-- payload of a NaN is moved between the two by hand
 	function reinterpret.i32_f32(num)
+		if num ~= num then
+			RE_INSTANCE.f64 = num
+
+			local data_1 = bit.rshift(RE_INSTANCE.a32, 29)
+			local data_2 = bit.lshift(bit_and(RE_INSTANCE.b32, 0xFFFFF), 3)
+
+			if data_1 ~= 0 or data_2 ~= 0 then
+				local sign = bit_and(RE_INSTANCE.b32, 0x80000000)
+
+				return (bit.bor(sign, 0x7F800000, data_2, data_1))
+			end
+		end
+
 		RE_INSTANCE.f32 = num
 
 		return RE_INSTANCE.i32
@@ -587,6 +792,15 @@ do
 	end
 
 	function reinterpret.f32_i32(num)
+		if bit_and(num, 0x7F800000) == 0x7F800000 and bit_and(num, 0x7FFFFF) ~= 0 then
+			local sign = bit_and(num, 0x80000000)
+
+			RE_INSTANCE.a32 = bit.lshift(num, 29)
+			RE_INSTANCE.b32 = bit.bor(sign, 0x7FF00000, bit.rshift(bit_and(num, 0x7FFFFF), 3))
+
+			return read_f64()
+		end
+
 		RE_INSTANCE.i32 = num
 
 		return RE_INSTANCE.f32
@@ -595,7 +809,7 @@ do
 	function reinterpret.f64_i64(num)
 		RE_INSTANCE.i64 = num
 
-		return RE_INSTANCE.f64
+		return read_f64()
 	end
 
 	module.wrap = wrap
@@ -612,36 +826,59 @@ do
 	local load = {}
 	local store = {}
 	local allocator = {}
-
-	ffi.cdef([[
-	union Any {
-		int8_t i8;
-		int16_t i16;
-		int32_t i32;
-		int64_t i64;
-
-		uint8_t u8;
-		uint16_t u16;
-		uint32_t u32;
-		uint64_t u64;
-
-		float f32;
-		double f64;
-	};
-
-	struct Memory {
-		uint32_t min;
-		uint32_t max;
-		union Any *data;
-	};
-
-	void *calloc(size_t num, size_t size);
-	void *realloc(void *ptr, size_t size);
-	void free(void *ptr);
-	]])
+	local wasm_memory = {}
+	local wasm_string = {}
+
+	-- Types can only be declared once per Lua state, so they are skipped when
+	-- another module, such as one loaded earlier by the same OpenResty worker,
+	-- has already declared them
+	if not pcall(ffi.typeof, "struct Memory") then
+		ffi.cdef([[
+		union Any {
+			int8_t i8;
+			int16_t i16;
+			int32_t i32;
+			int64_t i64;
+
+			uint8_t u8;
+			uint16_t u16;
+			uint32_t u32;
+			uint64_t u64;
+
+			float f32;
+			double f64;
+		};
+
+		struct Memory {
+			double min;
+			double max;
+			union Any *data;
+		};
+
+		union V128 {
+			int8_t i8[16];
+			int16_t i16[8];
+			int32_t i32[4];
+			int64_t i64[2];
+
+			uint8_t u8[16];
+			uint16_t u16[8];
+			uint32_t u32[4];
+			uint64_t u64[2];
+
+			float f32[4];
+			double f64[2];
+		};
+
+		void *calloc(size_t num, size_t size);
+		void *realloc(void *ptr, size_t size);
+		void free(void *ptr);
+		]])
+	end
 
 	local alias_t = ffi.typeof("uint8_t *")
 	local any_t = ffi.typeof("union Any *")
+	local v128_t = ffi.typeof("union V128")
 	local cast = ffi.cast
 
 	local function by_offset(pointer, offset)
@@ -706,6 +943,14 @@ do
 		return by_offset(memory.data, addr).f64
 	end
 
+	function load.v128(memory, addr)
+		local value = v128_t()
+
+		ffi.copy(value, by_offset(memory.data, addr), 16)
+
+		return value
+	end
+
 	function load.string(memory, addr, len)
 		local start = cast(alias_t, memory.data) + addr
 
@@ -748,6 +993,10 @@ do
 		by_offset(memory.data, addr).f64 = value
 	end
 
+	function store.v128(memory, addr, value)
+		ffi.copy(by_offset(memory.data, addr), value, 16)
+	end
+
 	function store.string(memory, addr, data, len)
 		local start = by_offset(memory.data, addr)
 
@@ -769,16 +1018,33 @@ do
 
 	local WASM_PAGE_SIZE = 65536
 
+	function store.init(memory, addr, data, offset, len)
+		data = data or ""
+
+		trap_unless(offset + len <= #data, "out_of_bounds_memory_access", "out of bounds memory access")
+		trap_unless(addr + len <= memory.min * WASM_PAGE_SIZE, "out_of_bounds_memory_access", "out of bounds memory access")
+
+		local start = by_offset(memory.data, addr)
+
+		ffi.copy(start, string.sub(data, offset + 1, offset + len), len)
+	end
+
 	local function finalizer(memory)
 		ffi.C.free(memory.data)
 	end
 
+	-- The old block is kept when `realloc` fails, so the memory is unchanged
 	local function grow_unchecked(memory, old, new)
-		memory.data = ffi.C.realloc(memory.data, new)
+		local data = ffi.C.realloc(memory.data, new)
 
-		assert(memory.data ~= nil, "failed to reallocate")
+		if data == nil then
+			return false
+		end
 
+		memory.data = data
 		ffi.fill(by_offset(memory.data, old), new - old, 0)
+
+		return true
 	end
 
 	function allocator.new(min, max)
@@ -799,19 +1065,1381 @@ do
 		local old = memory.min
 		local new = old + num
 
-		if new > memory.max then
+		-- Sizes are unsigned, so a negative one asks for more than any maximum
+		if num < 0 or new > memory.max then
+			return -1
+		elseif not grow_unchecked(memory, old * WASM_PAGE_SIZE, new * WASM_PAGE_SIZE) then
 			return -1
 		else
-			grow_unchecked(memory, old * WASM_PAGE_SIZE, new * WASM_PAGE_SIZE)
 			memory.min = new
 
 			return old
 		end
 	end
 
+	-- Memories indexed by `i64` count their pages in `i64` too
+	function allocator.size_64(memory)
+		return i64(memory.min)
+	end
+
+	function allocator.grow_64(memory, num)
+		return i64(allocator.grow(memory, tonumber(num)))
+	end
+
+	-- Snapshots are the raw bytes of the memory, so their length gives the
+	-- number of pages to restore
+	function wasm_memory.save(memory)
+		return ffi.string(memory.data, memory.min * WASM_PAGE_SIZE)
+	end
+
+	function wasm_memory.load(memory, data)
+		local len = #data
+		local num = len / WASM_PAGE_SIZE
+
+		assert(len % WASM_PAGE_SIZE == 0 and num <= memory.max, "snapshot does not fit the memory")
+
+		memory.data = ffi.C.realloc(memory.data, math.max(len, 1))
+
+		assert(memory.data ~= nil, "failed to reallocate")
+
+		ffi.copy(memory.data, data, len)
+		memory.min = num
+	end
+
+	-- Strings are exchanged with host code as their raw bytes, with C strings
+	-- read up to their first zero byte, and every access checked against the
+	-- size of the memory
+	local function check_bounds(memory, addr, len)
+		local inside = addr >= 0 and addr + len <= memory.min * WASM_PAGE_SIZE
+
+		trap_unless(inside, "out_of_bounds_memory_access", "out of bounds memory access")
+	end
+
+	function wasm_string.read(memory, addr, len)
+		check_bounds(memory, addr, len)
+
+		return load.string(memory, addr, len)
+	end
+
+	function wasm_string.read_cstr(memory, addr)
+		local bytes = cast(alias_t, memory.data)
+		local last = memory.min * WASM_PAGE_SIZE
+		local len = 0
+
+		while addr >= 0 and addr + len < last and bytes[addr + len] ~= 0 do
+			len = len + 1
+		end
+
+		check_bounds(memory, addr, len + 1)
+
+		return load.string(memory, addr, len)
+	end
+
+	function wasm_string.write(memory, addr, data)
+		check_bounds(memory, addr, #data)
+		store.string(memory, addr, data)
+	end
+
 	module.load = load
 	module.store = store
 	module.allocator = allocator
+	module.memory = wasm_memory
+	module.string = wasm_string
+end
+
+do
+	local data = {}
+
+	local string_byte = string.byte
+
+	-- Every 4 bytes, read as a little endian word, are written as 5 digits in
+	-- base 85 from `#` up with `\` skipped, or as `z` when the word is zero
+	function data.decode(text, len)
+		local word_list = ffi.new("uint32_t[?]", math_ceil(len / 4))
+		local index = 0
+		local position = 1
+
+		while position <= #text do
+			local value = 0
+
+			if string_byte(text, position) == 122 then
+				position = position + 1
+			else
+				for i = position, position + 4 do
+					local digit = string_byte(text, i) - 35
+
+					if digit > 57 then
+						digit = digit - 1
+					end
+
+					value = value * 85 + digit
+				end
+
+				position = position + 5
+			end
+
+			word_list[index] = value
+			index = index + 1
+		end
+
+		return ffi.string(word_list, len)
+	end
+
+	module.data = data
+end
+
+do
+	local wasm_table = {}
+
+	local table_move = table.move
+
+	function wasm_table.init(target, addr, data, offset, len)
+		local count = data and data.n or 0
+
+		trap_unless(offset + len <= count, "out_of_bounds_table_access", "out of bounds table access")
+		trap_unless(addr + len <= target.min, "out_of_bounds_table_access", "out of bounds table access")
+
+		table_move(data or target.data, offset + 1, offset + len, addr, target.data)
+	end
+
+	function wasm_table.get(target, index)
+		trap_unless(index < target.min, "out_of_bounds_table_access", "out of bounds table access")
+
+		return target.data[index]
+	end
+
+	function wasm_table.set(target, index, value)
+		trap_unless(index < target.min, "out_of_bounds_table_access", "out of bounds table access")
+
+		target.data[index] = value
+	end
+
+	function wasm_table.grow(target, num, value)
+		local old = target.min
+		local new = old + num
+
+		if new > target.max then
+			return -1
+		end
+
+		for i = old, new - 1 do
+			target.data[i] = value
+		end
+
+		target.min = new
+
+		return old
+	end
+
+	function wasm_table.copy(target_1, addr_1, target_2, addr_2, len)
+		trap_unless(addr_1 + len <= target_1.min, "out_of_bounds_table_access", "out of bounds table access")
+		trap_unless(addr_2 + len <= target_2.min, "out_of_bounds_table_access", "out of bounds table access")
+
+		table_move(target_2.data, addr_2, addr_2 + len - 1, addr_1, target_1.data)
+	end
+
+	function wasm_table.fill(target, addr, len, value)
+		trap_unless(addr + len <= target.min, "out_of_bounds_table_access", "out of bounds table access")
+
+		for i = addr, addr + len - 1 do
+			target.data[i] = value
+		end
+	end
+
+	-- Snapshots hold the references themselves, so they only restore into
+	-- the same Lua state
+	function wasm_table.save(target)
+		return { min = target.min, data = table_move(target.data, 0, target.min - 1, 0, {}) }
+	end
+
+	function wasm_table.load(target, saved)
+		assert(saved.min <= target.max, "snapshot does not fit the table")
+
+		for i = saved.min, target.min - 1 do
+			target.data[i] = nil
+		end
+
+		table_move(saved.data, 0, saved.min - 1, 0, target.data)
+		target.min = saved.min
+	end
+
+	module.table = wasm_table
+end
+
+do
+	local global = {}
+
+	function global.save(target)
+		return target.value
+	end
+
+	function global.load(target, value)
+		target.value = value
+	end
+
+	module.global = global
+end
+
+do
+	local reference = {}
+
+	function reference.select(condition, on_true, on_false)
+		if condition ~= 0 then
+			return on_true
+		else
+			return on_false
+		end
+	end
+
+	function reference.as_non_null(value)
+		trap_unless(value ~= nil, "null_reference", "null reference")
+
+		return value
+	end
+
+	module.reference = reference
+end
+
+do
+	local atomic = {}
+
+	-- There is only ever one thread, so nothing can wake a waiter
+	function atomic.wait(value, expected, timeout)
+		if value ~= expected then
+			return 1
+		end
+
+		trap_unless(timeout >= 0, "blocking_wait", "wait would block forever")
+
+		return 2
+	end
+
+	function atomic.notify(memory, addr, count)
+		return 0
+	end
+
+	module.atomic = atomic
+end
+
+do
+	local exception = {}
+
+	-- Traps never carry this metatable, so only tables that do are
+	-- exceptions that a `catch_all` is allowed to handle
+	local EXCEPTION_MT = {}
+
+	function exception.throw(tag, ...)
+		local data = setmetatable({ ... }, EXCEPTION_MT)
+
+		data.tag = tag
+		data.skip = 0
+
+		error(data, 0)
+	end
+
+	-- Delegated exceptions must first pass through `skip` handlers
+	function exception.accept(data)
+		if getmetatable(data) ~= EXCEPTION_MT then
+			return false
+		elseif data.skip ~= 0 then
+			data.skip = data.skip - 1
+
+			return false
+		end
+
+		return true
+	end
+
+	function exception.delegate(data, skip)
+		if exception.accept(data) then
+			data.skip = skip
+		end
+
+		error(data, 0)
+	end
+
+	module.exception = exception
+end
+
+do
+	local simd = {}
+
+	local bit_and = bit.band
+	local bit_or = bit.bor
+	local bit_xor = bit.bxor
+	local bit_not = bit.bnot
+	local bit_lshift = bit.lshift
+	local bit_rshift = bit.rshift
+	local bit_arshift = bit.arshift
+
+	local math_abs = math.abs
+	local math_min = math.min
+	local math_max = math.max
+	local math_sqrt = math.sqrt
+
+	local v128_t = ffi.typeof("union V128")
+
+	-- Vectors are never modified after creation, so they can be freely shared
+	simd.ZERO = v128_t()
+
+	local function copy_of(value)
+		local result = v128_t()
+
+		ffi.copy(result, value, 16)
+
+		return result
+	end
+
+	local function clamp(num, low, high)
+		return (math_min(math_max(num, low), high))
+	end
+
+	local function map_unary(field, len, func)
+		return function(value)
+			local result = v128_t()
+			local list, out = value[field], result[field]
+
+			for i = 0, len - 1 do
+				out[i] = func(list[i])
+			end
+
+			return result
+		end
+	end
+
+	local function map_binary(field, len, func)
+		return function(lhs, rhs)
+			local result = v128_t()
+			local list_1, list_2, out = lhs[field], rhs[field], result[field]
+
+			for i = 0, len - 1 do
+				out[i] = func(list_1[i], list_2[i])
+			end
+
+			return result
+		end
+	end
+
+	local function map_convert(field, output, len, offset, func)
+		return function(value)
+			local result = v128_t()
+			local list, out = value[field], result[output]
+
+			for i = 0, len - 1 do
+				out[i] = func(list[i + offset])
+			end
+
+			return result
+		end
+	end
+
+	local function map_compare(field, output, len, func)
+		return function(lhs, rhs)
+			local result = v128_t()
+			local list_1, list_2, out = lhs[field], rhs[field], result[output]
+
+			for i = 0, len - 1 do
+				if func(list_1[i], list_2[i]) then
+					out[i] = -1
+				end
+			end
+
+			return result
+		end
+	end
+
+	local function map_shift(field, len, bits, func)
+		return function(value, count)
+			local result = v128_t()
+			local list, out = value[field], result[field]
+
+			count = bit_and(count, bits - 1)
+
+			for i = 0, len - 1 do
+				out[i] = func(list[i], count)
+			end
+
+			return result
+		end
+	end
+
+	local function map_narrow(field, output, len, low, high)
+		return function(lhs, rhs)
+			local result = v128_t()
+			local list_1, list_2, out = lhs[field], rhs[field], result[output]
+
+			for i = 0, len - 1 do
+				out[i] = clamp(list_1[i], low, high)
+				out[i + len] = clamp(list_2[i], low, high)
+			end
+
+			return result
+		end
+	end
+
+	local function map_pairwise(field, output, len)
+		return function(value)
+			local result = v128_t()
+			local list, out = value[field], result[output]
+
+			for i = 0, len - 1 do
+				out[i] = list[i * 2] + list[i * 2 + 1]
+			end
+
+			return result
+		end
+	end
+
+	local function map_extend_mul(field, output, len, offset, cast)
+		return function(lhs, rhs)
+			local result = v128_t()
+			local list_1, list_2, out = lhs[field], rhs[field], result[output]
+
+			for i = 0, len - 1 do
+				out[i] = cast(list_1[i + offset]) * list_2[i + offset]
+			end
+
+			return result
+		end
+	end
+
+	local function extract_lane(field)
+		return function(value, lane)
+			return value[field][lane]
+		end
+	end
+
+	local function replace_lane(field)
+		return function(value, num, lane)
+			local result = copy_of(value)
+
+			result[field][lane] = num
+
+			return result
+		end
+	end
+
+	local function splat(field, len)
+		return function(num)
+			local result = v128_t()
+			local out = result[field]
+
+			for i = 0, len - 1 do
+				out[i] = num
+			end
+
+			return result
+		end
+	end
+
+	local function all_true(field, len)
+		return function(value)
+			local list = value[field]
+
+			for i = 0, len - 1 do
+				if list[i] == 0 then
+					return 0
+				end
+			end
+
+			return 1
+		end
+	end
+
+	local function bitmask(field, len)
+		return function(value)
+			local list = value[field]
+			local result = 0
+
+			for i = 0, len - 1 do
+				if list[i] < 0 then
+					result = bit_or(result, bit_lshift(1, i))
+				end
+			end
+
+			return result
+		end
+	end
+
+	local function number_of(num)
+		return num
+	end
+
+	local function is_eq(lhs, rhs)
+		return lhs == rhs
+	end
+
+	local function is_ne(lhs, rhs)
+		return lhs ~= rhs
+	end
+
+	local function is_lt(lhs, rhs)
+		return lhs < rhs
+	end
+
+	local function is_gt(lhs, rhs)
+		return lhs > rhs
+	end
+
+	local function is_le(lhs, rhs)
+		return lhs <= rhs
+	end
+
+	local function is_ge(lhs, rhs)
+		return lhs >= rhs
+	end
+
+	local function add(lhs, rhs)
+		return lhs + rhs
+	end
+
+	local function sub(lhs, rhs)
+		return lhs - rhs
+	end
+
+	local function mul(lhs, rhs)
+		return lhs * rhs
+	end
+
+	local function div(lhs, rhs)
+		return lhs / rhs
+	end
+
+	local function neg(num)
+		return -num
+	end
+
+	local function abs(num)
+		if num < 0 then
+			return -num
+		else
+			return num
+		end
+	end
+
+	local function average(lhs, rhs)
+		return (bit_rshift(lhs + rhs + 1, 1))
+	end
+
+	local function p_min(lhs, rhs)
+		if rhs < lhs then
+			return rhs
+		else
+			return lhs
+		end
+	end
+
+	local function p_max(lhs, rhs)
+		if lhs < rhs then
+			return rhs
+		else
+			return lhs
+		end
+	end
+
+	local function popcnt(num)
+		local result = 0
+
+		while num ~= 0 do
+			result = result + bit_and(num, 1)
+			num = bit_rshift(num, 1)
+		end
+
+		return result
+	end
+
+	local function add_saturate(low, high)
+		return function(lhs, rhs)
+			return clamp(lhs + rhs, low, high)
+		end
+	end
+
+	local function sub_saturate(low, high)
+		return function(lhs, rhs)
+			return clamp(lhs - rhs, low, high)
+		end
+	end
+
+	local function truncate_saturate(func)
+		return function(num)
+			if num ~= num then
+				return 0
+			else
+				return func(num)
+			end
+		end
+	end
+
+	local saturate_i32 = truncate_saturate(module.saturate.i32_f64)
+	local saturate_u32 = truncate_saturate(module.saturate.u32_f64)
+
+	function simd.from_u32(data_1, data_2, data_3, data_4)
+		local result = v128_t()
+		local out = result.u32
+
+		out[0] = data_1
+		out[1] = data_2
+		out[2] = data_3
+		out[3] = data_4
+
+		return result
+	end
+
+	function simd.i8x16_shuffle(lhs, rhs, lanes)
+		local result = v128_t()
+		local list_1, list_2, index, out = lhs.u8, rhs.u8, lanes.u8, result.u8
+
+		for i = 0, 15 do
+			local lane = index[i]
+
+			if lane < 16 then
+				out[i] = list_1[lane]
+			else
+				out[i] = list_2[lane - 16]
+			end
+		end
+
+		return result
+	end
+
+	function simd.i8x16_swizzle(lhs, rhs)
+		local result = v128_t()
+		local list, index, out = lhs.u8, rhs.u8, result.u8
+
+		for i = 0, 15 do
+			local lane = index[i]
+
+			if lane < 16 then
+				out[i] = list[lane]
+			end
+		end
+
+		return result
+	end
+
+	simd.i8x16_extract_lane_s = extract_lane("i8")
+	simd.i8x16_extract_lane_u = extract_lane("u8")
+	simd.i16x8_extract_lane_s = extract_lane("i16")
+	simd.i16x8_extract_lane_u = extract_lane("u16")
+	simd.i32x4_extract_lane = extract_lane("i32")
+	simd.i64x2_extract_lane = extract_lane("i64")
+	simd.f32x4_extract_lane = extract_lane("f32")
+	simd.f64x2_extract_lane = extract_lane("f64")
+
+	simd.i8x16_replace_lane = replace_lane("i8")
+	simd.i16x8_replace_lane = replace_lane("i16")
+	simd.i32x4_replace_lane = replace_lane("i32")
+	simd.i64x2_replace_lane = replace_lane("i64")
+	simd.f32x4_replace_lane = replace_lane("f32")
+	simd.f64x2_replace_lane = replace_lane("f64")
+
+	simd.i8x16_splat = splat("i8", 16)
+	simd.i16x8_splat = splat("i16", 8)
+	simd.i32x4_splat = splat("i32", 4)
+	simd.i64x2_splat = splat("i64", 2)
+	simd.f32x4_splat = splat("f32", 4)
+	simd.f64x2_splat = splat("f64", 2)
+
+	simd.i8x16_eq = map_compare("i8", "i8", 16, is_eq)
+	simd.i8x16_ne = map_compare("i8", "i8", 16, is_ne)
+	simd.i8x16_lt_s = map_compare("i8", "i8", 16, is_lt)
+	simd.i8x16_lt_u = map_compare("u8", "i8", 16, is_lt)
+	simd.i8x16_gt_s = map_compare("i8", "i8", 16, is_gt)
+	simd.i8x16_gt_u = map_compare("u8", "i8", 16, is_gt)
+	simd.i8x16_le_s = map_compare("i8", "i8", 16, is_le)
+	simd.i8x16_le_u = map_compare("u8", "i8", 16, is_le)
+	simd.i8x16_ge_s = map_compare("i8", "i8", 16, is_ge)
+	simd.i8x16_ge_u = map_compare("u8", "i8", 16, is_ge)
+
+	simd.i16x8_eq = map_compare("i16", "i16", 8, is_eq)
+	simd.i16x8_ne = map_compare("i16", "i16", 8, is_ne)
+	simd.i16x8_lt_s = map_compare("i16", "i16", 8, is_lt)
+	simd.i16x8_lt_u = map_compare("u16", "i16", 8, is_lt)
+	simd.i16x8_gt_s = map_compare("i16", "i16", 8, is_gt)
+	simd.i16x8_gt_u = map_compare("u16", "i16", 8, is_gt)
+	simd.i16x8_le_s = map_compare("i16", "i16", 8, is_le)
+	simd.i16x8_le_u = map_compare("u16", "i16", 8, is_le)
+	simd.i16x8_ge_s = map_compare("i16", "i16", 8, is_ge)
+	simd.i16x8_ge_u = map_compare("u16", "i16", 8, is_ge)
+
+	simd.i32x4_eq = map_compare("i32", "i32", 4, is_eq)
+	simd.i32x4_ne = map_compare("i32", "i32", 4, is_ne)
+	simd.i32x4_lt_s = map_compare("i32", "i32", 4, is_lt)
+	simd.i32x4_lt_u = map_compare("u32", "i32", 4, is_lt)
+	simd.i32x4_gt_s = map_compare("i32", "i32", 4, is_gt)
+	simd.i32x4_gt_u = map_compare("u32", "i32", 4, is_gt)
+	simd.i32x4_le_s = map_compare("i32", "i32", 4, is_le)
+	simd.i32x4_le_u = map_compare("u32", "i32", 4, is_le)
+	simd.i32x4_ge_s = map_compare("i32", "i32", 4, is_ge)
+	simd.i32x4_ge_u = map_compare("u32", "i32", 4, is_ge)
+
+	simd.i64x2_eq = map_compare("i64", "i64", 2, is_eq)
+	simd.i64x2_ne = map_compare("i64", "i64", 2, is_ne)
+	simd.i64x2_lt_s = map_compare("i64", "i64", 2, is_lt)
+	simd.i64x2_gt_s = map_compare("i64", "i64", 2, is_gt)
+	simd.i64x2_le_s = map_compare("i64", "i64", 2, is_le)
+	simd.i64x2_ge_s = map_compare("i64", "i64", 2, is_ge)
+
+	simd.f32x4_eq = map_compare("f32", "i32", 4, is_eq)
+	simd.f32x4_ne = map_compare("f32", "i32", 4, is_ne)
+	simd.f32x4_lt = map_compare("f32", "i32", 4, is_lt)
+	simd.f32x4_gt = map_compare("f32", "i32", 4, is_gt)
+	simd.f32x4_le = map_compare("f32", "i32", 4, is_le)
+	simd.f32x4_ge = map_compare("f32", "i32", 4, is_ge)
+
+	simd.f64x2_eq = map_compare("f64", "i64", 2, is_eq)
+	simd.f64x2_ne = map_compare("f64", "i64", 2, is_ne)
+	simd.f64x2_lt = map_compare("f64", "i64", 2, is_lt)
+	simd.f64x2_gt = map_compare("f64", "i64", 2, is_gt)
+	simd.f64x2_le = map_compare("f64", "i64", 2, is_le)
+	simd.f64x2_ge = map_compare("f64", "i64", 2, is_ge)
+
+	simd.v128_not = map_unary("i32", 4, bit_not)
+	simd.v128_and = map_binary("i32", 4, bit_and)
+	simd.v128_or = map_binary("i32", 4, bit_or)
+	simd.v128_xor = map_binary("i32", 4, bit_xor)
+
+	simd.v128_andnot = map_binary("i32", 4, function(lhs, rhs)
+		return (bit_and(lhs, bit_not(rhs)))
+	end)
+
+	function simd.v128_bitselect(lhs, rhs, mask)
+		local result = v128_t()
+		local list_1, list_2, list_3, out = lhs.i32, rhs.i32, mask.i32, result.i32
+
+		for i = 0, 3 do
+			local bits = list_3[i]
+
+			out[i] = bit_or(bit_and(list_1[i], bits), bit_and(list_2[i], bit_not(bits)))
+		end
+
+		return result
+	end
+
+	function simd.v128_any_true(value)
+		local list = value.i32
+
+		if list[0] ~= 0 or list[1] ~= 0 or list[2] ~= 0 or list[3] ~= 0 then
+			return 1
+		else
+			return 0
+		end
+	end
+
+	simd.i8x16_abs = map_unary("i8", 16, abs)
+	simd.i8x16_neg = map_unary("i8", 16, neg)
+	simd.i8x16_popcnt = map_unary("u8", 16, popcnt)
+	simd.i8x16_all_true = all_true("u8", 16)
+	simd.i8x16_bitmask = bitmask("i8", 16)
+	simd.i8x16_narrow_i16x8_s = map_narrow("i16", "i8", 8, -0x80, 0x7F)
+	simd.i8x16_narrow_i16x8_u = map_narrow("i16", "u8", 8, 0, 0xFF)
+	simd.i8x16_shl = map_shift("i8", 16, 8, bit_lshift)
+	simd.i8x16_shr_s = map_shift("i8", 16, 8, bit_arshift)
+	simd.i8x16_shr_u = map_shift("u8", 16, 8, bit_rshift)
+	simd.i8x16_add = map_binary("i8", 16, add)
+	simd.i8x16_add_sat_s = map_binary("i8", 16, add_saturate(-0x80, 0x7F))
+	simd.i8x16_add_sat_u = map_binary("u8", 16, add_saturate(0, 0xFF))
+	simd.i8x16_sub = map_binary("i8", 16, sub)
+	simd.i8x16_sub_sat_s = map_binary("i8", 16, sub_saturate(-0x80, 0x7F))
+	simd.i8x16_sub_sat_u = map_binary("u8", 16, sub_saturate(0, 0xFF))
+	simd.i8x16_min_s = map_binary("i8", 16, math_min)
+	simd.i8x16_min_u = map_binary("u8", 16, math_min)
+	simd.i8x16_max_s = map_binary("i8", 16, math_max)
+	simd.i8x16_max_u = map_binary("u8", 16, math_max)
+	simd.i8x16_avgr_u = map_binary("u8", 16, average)
+
+	simd.i16x8_extadd_pairwise_i8x16_s = map_pairwise("i8", "i16", 8)
+	simd.i16x8_extadd_pairwise_i8x16_u = map_pairwise("u8", "i16", 8)
+	simd.i16x8_abs = map_unary("i16", 8, abs)
+	simd.i16x8_neg = map_unary("i16", 8, neg)
+
+	simd.i16x8_q15mulr_sat_s = map_binary("i16", 8, function(lhs, rhs)
+		return clamp(bit_arshift(lhs * rhs + 0x4000, 15), -0x8000, 0x7FFF)
+	end)
+
+	simd.i16x8_all_true = all_true("u16", 8)
+	simd.i16x8_bitmask = bitmask("i16", 8)
+	simd.i16x8_narrow_i32x4_s = map_narrow("i32", "i16", 4, -0x8000, 0x7FFF)
+	simd.i16x8_narrow_i32x4_u = map_narrow("i32", "u16", 4, 0, 0xFFFF)
+	simd.i16x8_extend_low_i8x16_s = map_convert("i8", "i16", 8, 0, number_of)
+	simd.i16x8_extend_high_i8x16_s = map_convert("i8", "i16", 8, 8, number_of)
+	simd.i16x8_extend_low_i8x16_u = map_convert("u8", "i16", 8, 0, number_of)
+	simd.i16x8_extend_high_i8x16_u = map_convert("u8", "i16", 8, 8, number_of)
+	simd.i16x8_shl = map_shift("i16", 8, 16, bit_lshift)
+	simd.i16x8_shr_s = map_shift("i16", 8, 16, bit_arshift)
+	simd.i16x8_shr_u = map_shift("u16", 8, 16, bit_rshift)
+	simd.i16x8_add = map_binary("i16", 8, add)
+	simd.i16x8_add_sat_s = map_binary("i16", 8, add_saturate(-0x8000, 0x7FFF))
+	simd.i16x8_add_sat_u = map_binary("u16", 8, add_saturate(0, 0xFFFF))
+	simd.i16x8_sub = map_binary("i16", 8, sub)
+	simd.i16x8_sub_sat_s = map_binary("i16", 8, sub_saturate(-0x8000, 0x7FFF))
+	simd.i16x8_sub_sat_u = map_binary("u16", 8, sub_saturate(0, 0xFFFF))
+	simd.i16x8_mul = map_binary("i16", 8, mul)
+	simd.i16x8_min_s = map_binary("i16", 8, math_min)
+	simd.i16x8_min_u = map_binary("u16", 8, math_min)
+	simd.i16x8_max_s = map_binary("i16", 8, math_max)
+	simd.i16x8_max_u = map_binary("u16", 8, math_max)
+	simd.i16x8_avgr_u = map_binary("u16", 8, average)
+	simd.i16x8_extmul_low_i8x16_s = map_extend_mul("i8", "i16", 8, 0, number_of)
+	simd.i16x8_extmul_high_i8x16_s = map_extend_mul("i8", "i16", 8, 8, number_of)
+	simd.i16x8_extmul_low_i8x16_u = map_extend_mul("u8", "i16", 8, 0, number_of)
+	simd.i16x8_extmul_high_i8x16_u = map_extend_mul("u8", "i16", 8, 8, number_of)
+
+	simd.i32x4_extadd_pairwise_i16x8_s = map_pairwise("i16", "i32", 4)
+	simd.i32x4_extadd_pairwise_i16x8_u = map_pairwise("u16", "i32", 4)
+	simd.i32x4_abs = map_unary("i32", 4, abs)
+	simd.i32x4_neg = map_unary("i32", 4, neg)
+	simd.i32x4_all_true = all_true("u32", 4)
+	simd.i32x4_bitmask = bitmask("i32", 4)
+	simd.i32x4_extend_low_i16x8_s = map_convert("i16", "i32", 4, 0, number_of)
+	simd.i32x4_extend_high_i16x8_s = map_convert("i16", "i32", 4, 4, number_of)
+	simd.i32x4_extend_low_i16x8_u = map_convert("u16", "i32", 4, 0, number_of)
+	simd.i32x4_extend_high_i16x8_u = map_convert("u16", "i32", 4, 4, number_of)
+	simd.i32x4_shl = map_shift("i32", 4, 32, bit_lshift)
+	simd.i32x4_shr_s = map_shift("i32", 4, 32, bit_arshift)
+	simd.i32x4_shr_u = map_shift("i32", 4, 32, bit_rshift)
+	simd.i32x4_add = map_binary("i32", 4, module.add.i32)
+	simd.i32x4_sub = map_binary("i32", 4, module.sub.i32)
+	simd.i32x4_mul = map_binary("i32", 4, module.mul.i32)
+	simd.i32x4_min_s = map_binary("i32", 4, math_min)
+	simd.i32x4_min_u = map_binary("u32", 4, math_min)
+	simd.i32x4_max_s = map_binary("i32", 4, math_max)
+	simd.i32x4_max_u = map_binary("u32", 4, math_max)
+
+	function simd.i32x4_dot_i16x8_s(lhs, rhs)
+		local result = v128_t()
+		local list_1, list_2, out = lhs.i16, rhs.i16, result.i32
+
+		for i = 0, 3 do
+			local a = list_1[i * 2] * list_2[i * 2]
+			local b = list_1[i * 2 + 1] * list_2[i * 2 + 1]
+
+			out[i] = to_signed(a + b)
+		end
+
+		return result
+	end
+
+	simd.i32x4_extmul_low_i16x8_s = map_extend_mul("i16", "i32", 4, 0, number_of)
+	simd.i32x4_extmul_high_i16x8_s = map_extend_mul("i16", "i32", 4, 4, number_of)
+	simd.i32x4_extmul_low_i16x8_u = map_extend_mul("u16", "u32", 4, 0, number_of)
+	simd.i32x4_extmul_high_i16x8_u = map_extend_mul("u16", "u32", 4, 4, number_of)
+
+	simd.i64x2_abs = map_unary("i64", 2, abs)
+	simd.i64x2_neg = map_unary("i64", 2, neg)
+	simd.i64x2_all_true = all_true("u64", 2)
+	simd.i64x2_bitmask = bitmask("i64", 2)
+	simd.i64x2_extend_low_i32x4_s = map_convert("i32", "i64", 2, 0, number_of)
+	simd.i64x2_extend_high_i32x4_s = map_convert("i32", "i64", 2, 2, number_of)
+	simd.i64x2_extend_low_i32x4_u = map_convert("u32", "i64", 2, 0, number_of)
+	simd.i64x2_extend_high_i32x4_u = map_convert("u32", "i64", 2, 2, number_of)
+	simd.i64x2_shl = map_shift("i64", 2, 64, bit_lshift)
+	simd.i64x2_shr_s = map_shift("i64", 2, 64, bit_arshift)
+	simd.i64x2_shr_u = map_shift("i64", 2, 64, bit_rshift)
+	simd.i64x2_add = map_binary("i64", 2, add)
+	simd.i64x2_sub = map_binary("i64", 2, sub)
+	simd.i64x2_mul = map_binary("i64", 2, mul)
+	simd.i64x2_extmul_low_i32x4_s = map_extend_mul("i32", "i64", 2, 0, i64)
+	simd.i64x2_extmul_high_i32x4_s = map_extend_mul("i32", "i64", 2, 2, i64)
+	simd.i64x2_extmul_low_i32x4_u = map_extend_mul("u32", "u64", 2, 0, u64)
+	simd.i64x2_extmul_high_i32x4_u = map_extend_mul("u32", "u64", 2, 2, u64)
+
+	simd.f32x4_ceil = map_unary("f32", 4, math_ceil)
+	simd.f32x4_floor = map_unary("f32", 4, math_floor)
+	simd.f32x4_trunc = map_unary("f32", 4, truncate_f64)
+	simd.f32x4_nearest = map_unary("f32", 4, module.nearest.f32)
+	simd.f32x4_abs = map_unary("f32", 4, math_abs)
+	simd.f32x4_neg = map_unary("f32", 4, neg)
+	simd.f32x4_sqrt = map_unary("f32", 4, math_sqrt)
+	simd.f32x4_add = map_binary("f32", 4, add)
+	simd.f32x4_sub = map_binary("f32", 4, sub)
+	simd.f32x4_mul = map_binary("f32", 4, mul)
+	simd.f32x4_div = map_binary("f32", 4, div)
+	simd.f32x4_min = map_binary("f32", 4, module.min.f32)
+	simd.f32x4_max = map_binary("f32", 4, module.max.f32)
+	simd.f32x4_pmin = map_binary("f32", 4, p_min)
+	simd.f32x4_pmax = map_binary("f32", 4, p_max)
+
+	simd.f64x2_ceil = map_unary("f64", 2, math_ceil)
+	simd.f64x2_floor = map_unary("f64", 2, math_floor)
+	simd.f64x2_trunc = map_unary("f64", 2, truncate_f64)
+	simd.f64x2_nearest = map_unary("f64", 2, module.nearest.f64)
+	simd.f64x2_abs = map_unary("f64", 2, math_abs)
+	simd.f64x2_neg = map_unary("f64", 2, neg)
+	simd.f64x2_sqrt = map_unary("f64", 2, math_sqrt)
+	simd.f64x2_add = map_binary("f64", 2, add)
+	simd.f64x2_sub = map_binary("f64", 2, sub)
+	simd.f64x2_mul = map_binary("f64", 2, mul)
+	simd.f64x2_div = map_binary("f64", 2, div)
+	simd.f64x2_min = map_binary("f64", 2, module.min.f64)
+	simd.f64x2_max = map_binary("f64", 2, module.max.f64)
+	simd.f64x2_pmin = map_binary("f64", 2, p_min)
+	simd.f64x2_pmax = map_binary("f64", 2, p_max)
+
+	simd.i32x4_trunc_sat_f32x4_s = map_convert("f32", "i32", 4, 0, saturate_i32)
+	simd.i32x4_trunc_sat_f32x4_u = map_convert("f32", "i32", 4, 0, saturate_u32)
+	simd.f32x4_convert_i32x4_s = map_convert("i32", "f32", 4, 0, number_of)
+	simd.f32x4_convert_i32x4_u = map_convert("u32", "f32", 4, 0, number_of)
+	simd.i32x4_trunc_sat_f64x2_s_zero = map_convert("f64", "i32", 2, 0, saturate_i32)
+	simd.i32x4_trunc_sat_f64x2_u_zero = map_convert("f64", "i32", 2, 0, saturate_u32)
+	simd.f64x2_convert_low_i32x4_s = map_convert("i32", "f64", 2, 0, number_of)
+	simd.f64x2_convert_low_i32x4_u = map_convert("u32", "f64", 2, 0, number_of)
+	simd.f32x4_demote_f64x2_zero = map_convert("f64", "f32", 2, 0, number_of)
+	simd.f64x2_promote_low_f32x4 = map_convert("f32", "f64", 2, 0, number_of)
+
+	module.simd = simd
+end
+
+-- Modules importing `wasi_snapshot_preview1` fall back to this when the host
+-- passes no `func_list` for it, taking the `args`, `env`, `files` and stdio
+-- from whatever table the host passed there instead
+do
+	local wasi = {}
+
+	local load = module.load
+	local store = module.store
+
+	local math_random = math.random
+	local string_char = string.char
+	local table_concat = table.concat
+
+	local ERRNO_SUCCESS = 0
+	local ERRNO_BADF = 8
+	local ERRNO_INVAL = 28
+	local ERRNO_ISDIR = 31
+	local ERRNO_NOENT = 44
+	local ERRNO_NOSYS = 52
+	local ERRNO_SPIPE = 70
+
+	local FILETYPE_CHARACTER_DEVICE = 2
+	local FILETYPE_DIRECTORY = 3
+	local FILETYPE_REGULAR_FILE = 4
+
+	local OFLAGS_CREAT = 1
+	local OFLAGS_TRUNC = 8
+	local FDFLAGS_APPEND = 1
+
+	local PREOPEN_FD = 3
+
+	-- Calls to anything not written here are reported as unsupported
+	local NOSYS_MT = {
+		__index = function()
+			return function()
+				return ERRNO_NOSYS
+			end
+		end,
+	}
+
+	-- `proc_exit` unwinds the instance with this, naming the exit code
+	local EXIT_MT = {
+		__tostring = function(data)
+			return "exited with code " .. data.code
+		end,
+	}
+
+	local function build_env_list(env)
+		local list = {}
+
+		for name, value in pairs(env) do
+			list[#list + 1] = name .. "=" .. value
+		end
+
+		table.sort(list)
+
+		return list
+	end
+
+	local function write_list_sizes(memory, list, count_addr, size_addr)
+		local size = 0
+
+		for _, value in ipairs(list) do
+			size = size + #value + 1
+		end
+
+		store.i32(memory, count_addr, #list)
+		store.i32(memory, size_addr, size)
+
+		return ERRNO_SUCCESS
+	end
+
+	-- Entries are written back to back with a null after each, and a pointer
+	-- to every one of them goes in the list
+	local function write_list(memory, list, list_addr, data_addr)
+		for i, value in ipairs(list) do
+			store.i32(memory, list_addr + (i - 1) * 4, data_addr)
+			store.string(memory, data_addr, value .. "\0")
+
+			data_addr = data_addr + #value + 1
+		end
+
+		return ERRNO_SUCCESS
+	end
+
+	local function gather(memory, iovs, iovs_len)
+		local list = {}
+
+		for i = 0, iovs_len - 1 do
+			local addr = load.i32(memory, iovs + i * 8)
+			local len = load.i32(memory, iovs + i * 8 + 4)
+
+			list[i + 1] = load.string(memory, addr, len)
+		end
+
+		return table_concat(list)
+	end
+
+	local function scatter(memory, iovs, iovs_len, data)
+		local offset = 0
+
+		for i = 0, iovs_len - 1 do
+			local addr = load.i32(memory, iovs + i * 8)
+			local len = load.i32(memory, iovs + i * 8 + 4)
+			local part = string.sub(data, offset + 1, offset + len)
+
+			store.string(memory, addr, part)
+			offset = offset + #part
+
+			if #part < len then
+				break
+			end
+		end
+
+		return offset
+	end
+
+	local function get_iovs_len(memory, iovs, iovs_len)
+		local len = 0
+
+		for i = 0, iovs_len - 1 do
+			len = len + load.i32(memory, iovs + i * 8 + 4)
+		end
+
+		return len
+	end
+
+	local function get_path(memory, path, path_len)
+		local name = load.string(memory, path, path_len)
+
+		return (string.gsub(name, "^[%./]+", ""))
+	end
+
+	function wasi.bind(host, get_memory)
+		if host ~= nil and host.func_list ~= nil then
+			return host
+		end
+
+		local config = host or {}
+		local args = config.args or {}
+		local env_list = build_env_list(config.env or {})
+
+		local stdin = config.stdin or function(len)
+			return io.stdin:read(len) or ""
+		end
+
+		local stdout = config.stdout or function(data)
+			io.stdout:write(data)
+		end
+
+		local stderr = config.stderr or function(data)
+			io.stderr:write(data)
+		end
+
+		-- Files are strings keyed by their path in the only preopened
+		-- directory, named `/`, kept in the table the host passed so that it
+		-- can read back what was written
+		local file_map = config.files or {}
+
+		local fd_list = {
+			[0] = { read = stdin },
+			[1] = { write = stdout },
+			[2] = { write = stderr },
+			[PREOPEN_FD] = { is_dir = true },
+		}
+
+		local func_list = {}
+
+		function func_list.args_sizes_get(count_addr, size_addr)
+			return write_list_sizes(get_memory(), args, count_addr, size_addr)
+		end
+
+		function func_list.args_get(list_addr, data_addr)
+			return write_list(get_memory(), args, list_addr, data_addr)
+		end
+
+		function func_list.environ_sizes_get(count_addr, size_addr)
+			return write_list_sizes(get_memory(), env_list, count_addr, size_addr)
+		end
+
+		function func_list.environ_get(list_addr, data_addr)
+			return write_list(get_memory(), env_list, list_addr, data_addr)
+		end
+
+		function func_list.clock_res_get(_, addr)
+			store.i64(get_memory(), addr, i64(1000))
+
+			return ERRNO_SUCCESS
+		end
+
+		-- The realtime clock only has seconds, and the others count the
+		-- processor time used
+		function func_list.clock_time_get(id, _, addr)
+			local time
+
+			if id == 0 then
+				time = i64(os.time()) * 1000000000
+			else
+				time = i64(os.clock() * 1000000000)
+			end
+
+			store.i64(get_memory(), addr, time)
+
+			return ERRNO_SUCCESS
+		end
+
+		function func_list.random_get(addr, len)
+			local list = {}
+
+			for i = 1, len do
+				list[i] = string_char(math_random(0, 255))
+			end
+
+			store.string(get_memory(), addr, table_concat(list))
+
+			return ERRNO_SUCCESS
+		end
+
+		function func_list.proc_exit(code)
+			error(setmetatable({ code = code }, EXIT_MT), 0)
+		end
+
+		function func_list.sched_yield()
+			return ERRNO_SUCCESS
+		end
+
+		function func_list.fd_write(fd, iovs, iovs_len, written_addr)
+			local entry = fd_list[fd]
+
+			if entry == nil then
+				return ERRNO_BADF
+			elseif entry.is_dir then
+				return ERRNO_ISDIR
+			end
+
+			local memory = get_memory()
+			local data = gather(memory, iovs, iovs_len)
+
+			if entry.write then
+				entry.write(data)
+			else
+				local file = file_map[entry.name] or ""
+				local offset = entry.is_append and #file or entry.offset
+				local head = string.sub(file, 1, offset)
+
+				head = head .. string.rep("\0", offset - #head)
+				file_map[entry.name] = head .. data .. string.sub(file, offset + #data + 1)
+				entry.offset = offset + #data
+			end
+
+			store.i32(memory, written_addr, #data)
+
+			return ERRNO_SUCCESS
+		end
+
+		function func_list.fd_read(fd, iovs, iovs_len, read_addr)
+			local entry = fd_list[fd]
+
+			if entry == nil then
+				return ERRNO_BADF
+			elseif entry.is_dir then
+				return ERRNO_ISDIR
+			end
+
+			local memory = get_memory()
+			local len = get_iovs_len(memory, iovs, iovs_len)
+			local data
+
+			if entry.read then
+				data = entry.read(len)
+			else
+				data = string.sub(file_map[entry.name] or "", entry.offset + 1, entry.offset + len)
+				entry.offset = entry.offset + #data
+			end
+
+			store.i32(memory, read_addr, scatter(memory, iovs, iovs_len, data))
+
+			return ERRNO_SUCCESS
+		end
+
+		function func_list.fd_seek(fd, offset, whence, result_addr)
+			local entry = fd_list[fd]
+
+			if entry == nil then
+				return ERRNO_BADF
+			elseif entry.name == nil then
+				return ERRNO_SPIPE
+			end
+
+			local base
+
+			if whence == 0 then
+				base = 0
+			elseif whence == 1 then
+				base = entry.offset
+			elseif whence == 2 then
+				base = #(file_map[entry.name] or "")
+			else
+				return ERRNO_INVAL
+			end
+
+			local position = base + tonumber(offset)
+
+			if position < 0 then
+				return ERRNO_INVAL
+			end
+
+			entry.offset = position
+			store.i64(get_memory(), result_addr, i64(position))
+
+			return ERRNO_SUCCESS
+		end
+
+		function func_list.fd_close(fd)
+			if fd_list[fd] == nil then
+				return ERRNO_BADF
+			end
+
+			fd_list[fd] = nil
+
+			return ERRNO_SUCCESS
+		end
+
+		function func_list.fd_fdstat_get(fd, addr)
+			local entry = fd_list[fd]
+
+			if entry == nil then
+				return ERRNO_BADF
+			end
+
+			local memory = get_memory()
+			local filetype = FILETYPE_REGULAR_FILE
+
+			if entry.is_dir then
+				filetype = FILETYPE_DIRECTORY
+			elseif entry.name == nil then
+				filetype = FILETYPE_CHARACTER_DEVICE
+			end
+
+			store.fill(memory, addr, 24, 0)
+			store.i32_n8(memory, addr, filetype)
+			store.i64(memory, addr + 8, i64(-1))
+			store.i64(memory, addr + 16, i64(-1))
+
+			return ERRNO_SUCCESS
+		end
+
+		function func_list.fd_filestat_get(fd, addr)
+			local entry = fd_list[fd]
+
+			if entry == nil then
+				return ERRNO_BADF
+			end
+
+			local memory = get_memory()
+
+			store.fill(memory, addr, 64, 0)
+
+			if entry.name then
+				store.i32_n8(memory, addr + 16, FILETYPE_REGULAR_FILE)
+				store.i64(memory, addr + 32, i64(#(file_map[entry.name] or "")))
+			elseif entry.is_dir then
+				store.i32_n8(memory, addr + 16, FILETYPE_DIRECTORY)
+			else
+				store.i32_n8(memory, addr + 16, FILETYPE_CHARACTER_DEVICE)
+			end
+
+			store.i64(memory, addr + 24, i64(1))
+
+			return ERRNO_SUCCESS
+		end
+
+		function func_list.fd_prestat_get(fd, addr)
+			if fd ~= PREOPEN_FD then
+				return ERRNO_BADF
+			end
+
+			local memory = get_memory()
+
+			store.i32(memory, addr, 0)
+			store.i32(memory, addr + 4, 1)
+
+			return ERRNO_SUCCESS
+		end
+
+		function func_list.fd_prestat_dir_name(fd, addr, len)
+			if fd ~= PREOPEN_FD then
+				return ERRNO_BADF
+			end
+
+			store.string(get_memory(), addr, "/", len)
+
+			return ERRNO_SUCCESS
+		end
+
+		function func_list.path_open(fd, _, path, path_len, oflags, _, _, fdflags, fd_addr)
+			if fd ~= PREOPEN_FD then
+				return ERRNO_BADF
+			end
+
+			local memory = get_memory()
+			local name = get_path(memory, path, path_len)
+
+			if file_map[name] == nil then
+				if bit.band(oflags, OFLAGS_CREAT) == 0 then
+					return ERRNO_NOENT
+				end
+
+				file_map[name] = ""
+			elseif bit.band(oflags, OFLAGS_TRUNC) ~= 0 then
+				file_map[name] = ""
+			end
+
+			local new = PREOPEN_FD + 1
+
+			while fd_list[new] ~= nil do
+				new = new + 1
+			end
+
+			fd_list[new] = {
+				name = name,
+				offset = 0,
+				is_append = bit.band(fdflags, FDFLAGS_APPEND) ~= 0,
+			}
+
+			store.i32(memory, fd_addr, new)
+
+			return ERRNO_SUCCESS
+		end
+
+		function func_list.path_unlink_file(fd, path, path_len)
+			if fd ~= PREOPEN_FD then
+				return ERRNO_BADF
+			end
+
+			local name = get_path(get_memory(), path, path_len)
+
+			if file_map[name] == nil then
+				return ERRNO_NOENT
+			end
+
+			file_map[name] = nil
+
+			return ERRNO_SUCCESS
+		end
+
+		return { func_list = setmetatable(func_list, NOSYS_MT) }
+	end
+
+	module.wasi = wasi
 end
 
 return module
@@ -1,3 +1 @@
-pub mod br_table;
 pub mod into_string;
-pub mod localize;
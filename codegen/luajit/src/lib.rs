@@ -1,7 +1,9 @@
 pub static RUNTIME: &str = include_str!("../runtime/runtime.lua");
 
-pub use translator::{from_inst_list, from_module_typed, from_module_untyped};
+pub use translator::{
+	from_inst_list, from_module_custom, from_module_mapped, from_module_split, from_module_typed,
+	from_module_untyped, write_self_test, LuaJIT,
+};
 
 mod analyzer;
-mod backend;
 mod translator;
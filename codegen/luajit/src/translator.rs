@@ -1,342 +1,202 @@
-use std::{
-	collections::BTreeSet,
-	io::{Result, Write},
-};
+use std::io::{Result, Write};
 
+use codegen_core::{Backend, BranchStyle, Dialect, Mapping, Options, Split};
 use wasm_ast::{
-	factory::Factory,
-	module::{External, Module, TypeInfo},
-	node::{FuncData, Statement},
-};
-use wasmparser::{
-	ConstExpr, Data, DataKind, Element, ElementItems, ElementKind, Export, Import, Operator,
-	OperatorsReader,
+	module::{Module, TypeInfo},
+	node::{BinOpType, CmpOpType, Expression, FuncData, UnOpType, Value},
 };
+use wasmparser::Operator;
 
-use crate::{
-	analyzer::localize,
-	backend::manager::{Driver, Manager},
-};
-
-trait AsIEName {
-	fn as_ie_name(&self) -> &str;
-}
-
-impl AsIEName for External {
-	fn as_ie_name(&self) -> &str {
-		match self {
-			Self::Func => "func_list",
-			Self::Table => "table_list",
-			Self::Memory => "memory_list",
-			Self::Global => "global_list",
-			Self::Tag => unimplemented!(),
-		}
-	}
-}
-
-fn reader_to_code(reader: OperatorsReader) -> Vec<Operator> {
-	let parsed: std::result::Result<_, _> = reader.into_iter().collect();
+use crate::analyzer::into_string::{IntoNameTuple, TryIntoSymbol};
 
-	parsed.unwrap()
+/// Lowers functions to LuaJIT, which dialects that run on it can reuse.
+pub struct LuaJIT {
+	/// Lowers branches to nested loops and a `desired` level instead of
+	/// `goto`, for embeddings where it is disabled.
+	pub no_goto: bool,
 }
 
-fn write_named_array(name: &str, len: usize, w: &mut dyn Write) -> Result<()> {
-	let Some(len) = len.checked_sub(1) else { return Ok(()) };
+const LUA_JIT: LuaJIT = LuaJIT { no_goto: false };
 
-	writeln!(w, "local {name} = table_new({len}, 1)")
-}
-
-fn write_constant(init: &ConstExpr, type_info: &TypeInfo, w: &mut dyn Write) -> Result<()> {
-	let code = reader_to_code(init.get_operators_reader());
-	let func = Factory::from_type_info(type_info).create_anonymous(&code);
-
-	if let Some(Statement::SetTemporary(stat)) = func.code().code().last() {
-		stat.value().write(&mut Manager::empty(), w)
-	} else {
-		writeln!(w, r#"error("Valueless constant")"#)
+impl Backend for LuaJIT {
+	fn write_function(&self, func: &FuncData, options: &Options, w: &mut dyn Write) -> Result<()> {
+		codegen_core::write_function(self, func, options, w)
 	}
-}
 
-fn write_import_of(list: &[Import], wanted: External, w: &mut dyn Write) -> Result<()> {
-	let lower = wanted.as_ie_name();
-	let upper = lower.to_uppercase();
-
-	for (i, Import { name, module, .. }) in list
-		.iter()
-		.filter(|v| External::from(v.ty) == wanted)
-		.enumerate()
-	{
-		write!(w, "\t")?;
-		writeln!(w, r#"{upper}[{i}] = wasm["{module}"].{lower}["{name}"]"#)?;
+	fn write_function_marked(
+		&self,
+		func: &FuncData,
+		options: &Options,
+		w: &mut dyn Write,
+	) -> Result<()> {
+		codegen_core::write_function_marked(self, func, options, w)
 	}
 
-	Ok(())
-}
-
-fn write_export_of(list: &[Export], wanted: External, w: &mut dyn Write) -> Result<()> {
-	let lower = wanted.as_ie_name();
-	let upper = lower.to_uppercase();
-
-	writeln!(w, "\t\t{lower} = {{")?;
-
-	for Export { name, index, .. } in list.iter().filter(|v| External::from(v.kind) == wanted) {
-		write!(w, "\t\t\t")?;
-		writeln!(w, r#"["{name}"] = {upper}[{index}],"#)?;
+	fn write_expression(&self, expr: &Expression, w: &mut dyn Write) -> Result<()> {
+		codegen_core::write_expression(self, expr, w)
 	}
 
-	writeln!(w, "\t\t}},")
-}
-
-fn write_import_list(list: &[Import], w: &mut dyn Write) -> Result<()> {
-	write_import_of(list, External::Func, w)?;
-	write_import_of(list, External::Table, w)?;
-	write_import_of(list, External::Memory, w)?;
-	write_import_of(list, External::Global, w)
-}
-
-fn write_export_list(list: &[Export], w: &mut dyn Write) -> Result<()> {
-	write_export_of(list, External::Func, w)?;
-	write_export_of(list, External::Table, w)?;
-	write_export_of(list, External::Memory, w)?;
-	write_export_of(list, External::Global, w)
-}
-
-fn write_table_list(wasm: &Module, w: &mut dyn Write) -> Result<()> {
-	let offset = wasm.import_count(External::Table);
-	let table = wasm.table_section();
-
-	for (i, table) in table.iter().enumerate() {
-		let index = offset + i;
-		let min = table.ty.initial;
-		let max = table.ty.maximum.unwrap_or(0xFFFF);
-
-		writeln!(
-			w,
-			"\tTABLE_LIST[{index}] = {{ min = {min}, max = {max}, data = {{}} }}"
-		)?;
+	fn un_op_name(&self, op: UnOpType) -> (&'static str, &'static str) {
+		op.into_name_tuple()
 	}
 
-	Ok(())
-}
-
-fn write_memory_list(wasm: &Module, w: &mut dyn Write) -> Result<()> {
-	let offset = wasm.import_count(External::Memory);
-	let memory = wasm.memory_section();
+	// Adding and subtracting `i32` values is written natively through the
+	// `to_signed` of the header
+	fn bin_op_name(&self, op: BinOpType) -> Option<(&'static str, &'static str)> {
+		let is_native = matches!(op, BinOpType::Add_I32 | BinOpType::Sub_I32);
 
-	for (i, ty) in memory.iter().enumerate() {
-		let index = offset + i;
-		let min = ty.initial;
-		let max = ty.maximum.unwrap_or(0xFFFF);
-
-		writeln!(w, "\tMEMORY_LIST[{index}] = rt.allocator.new({min}, {max})")?;
+		(!is_native && op.try_into_symbol().is_none()).then(|| op.into_name_tuple())
 	}
 
-	Ok(())
-}
-
-fn write_global_list(wasm: &Module, type_info: &TypeInfo, w: &mut dyn Write) -> Result<()> {
-	let offset = wasm.import_count(External::Global);
-	let global = wasm.global_section();
-
-	for (i, global) in global.iter().enumerate() {
-		let index = offset + i;
-
-		write!(w, "\tGLOBAL_LIST[{index}] = {{ value = ")?;
-		write_constant(&global.init_expr, type_info, w)?;
-		writeln!(w, " }}")?;
+	fn cmp_op_name(&self, op: CmpOpType) -> Option<(&'static str, &'static str)> {
+		op.try_into_symbol().is_none().then(|| op.into_name_tuple())
 	}
 
-	Ok(())
-}
-
-fn write_element_list(list: &[Element], type_info: &TypeInfo, w: &mut dyn Write) -> Result<()> {
-	for element in list {
-		let ElementKind::Active { table_index: index, offset_expr: init } = element.kind else {
-			unimplemented!("passive elements not supported")
+	fn value_name(&self, value: Value) -> Option<(&'static str, &'static str)> {
+		let name = match value {
+			Value::F32(v) if v.is_nan() => ("reinterpret", "f32_i32"),
+			Value::F64(v) if v.is_nan() => ("reinterpret", "f64_i64"),
+			_ => return None,
 		};
 
-		let index = index.unwrap_or(0);
-
-		writeln!(w, "\tdo")?;
-		writeln!(w, "\t\tlocal target = TABLE_LIST[{index}].data")?;
-		write!(w, "\t\tlocal offset = ")?;
-
-		write_constant(&init, type_info, w)?;
-
-		writeln!(w)?;
-		write!(w, "\t\tlocal data = {{ ")?;
-
-		match element.items.clone() {
-			ElementItems::Functions(functions) => {
-				for index in functions {
-					let index = index.unwrap();
-					write!(w, "FUNC_LIST[{index}],")?;
-				}
-			}
-			ElementItems::Expressions(expressions) => {
-				for init in expressions {
-					let init = init.unwrap();
-					write_constant(&init, type_info, w)?;
-				}
-			}
-		}
+		Some(name)
+	}
 
-		writeln!(w, " }}")?;
-		writeln!(w, "\t\ttable.move(data, 1, #data, offset, target)")?;
-		writeln!(w, "\tend")?;
+	fn write_local_operation(&self, head: &str, tail: &str, w: &mut dyn Write) -> Result<()> {
+		write!(w, "local {head}_{tail} = ")?;
+
+		match (head, tail) {
+			("abs" | "ceil" | "floor", _) | ("sqrt", "f64") => write!(w, "math.{head}"),
+			("band" | "bor" | "bxor" | "bnot", _) => write!(w, "bit.{head}"),
+			("shl", _) => write!(w, "bit.lshift"),
+			("shr", "i32" | "i64") => write!(w, "bit.arshift"),
+			("shr", "u32" | "u64") => write!(w, "bit.rshift"),
+			("rotl", _) => write!(w, "bit.rol"),
+			("rotr", _) => write!(w, "bit.ror"),
+			("convert", "f64_i64") => write!(w, "tonumber"),
+			_ => write!(w, "rt.{head}.{tail}"),
+		}?;
+
+		writeln!(w)
 	}
 
-	Ok(())
-}
+	fn write_header(&self, w: &mut dyn Write) -> Result<()> {
+		writeln!(w, "local table_new = require(\"table.new\")")?;
+		writeln!(w, "local to_signed = bit.tobit")
+	}
 
-fn write_data_list(list: &[Data], type_info: &TypeInfo, w: &mut dyn Write) -> Result<()> {
-	for data in list {
-		let (index, init) = match data.kind {
-			DataKind::Passive => unimplemented!("passive data not supported"),
-			DataKind::Active {
-				memory_index,
-				offset_expr,
-			} => (memory_index, offset_expr),
+	fn write_named_array(&self, name: &str, len: usize, w: &mut dyn Write) -> Result<()> {
+		let Some(len) = len.checked_sub(1) else {
+			return Ok(());
 		};
 
-		write!(w, "\trt.store.string(MEMORY_LIST[{index}], ")?;
-		write_constant(&init, type_info, w)?;
-		writeln!(w, r#","{}")"#, data.data.escape_ascii())?;
+		writeln!(w, "local {name} = table_new({len}, 1)")
 	}
 
-	Ok(())
-}
-
-fn build_func_list(wasm: &Module, type_info: &TypeInfo) -> Vec<FuncData> {
-	let offset = wasm.import_count(External::Func);
-	let mut builder = Factory::from_type_info(type_info);
-
-	wasm.code_section()
-		.iter()
-		.enumerate()
-		.map(|f| builder.create_indexed(f.0 + offset, f.1).unwrap())
-		.collect()
+	fn has_wasi(&self) -> bool {
+		true
+	}
 }
 
-fn write_local_operation(head: &str, tail: &str, w: &mut dyn Write) -> Result<()> {
-	write!(w, "local {head}_{tail} = ")?;
-
-	match (head, tail) {
-		("abs" | "ceil" | "floor" | "sqrt", _) => write!(w, "math.{head}"),
-		("rem", "i32") => write!(w, "math.fmod"),
-		("band" | "bor" | "bxor" | "bnot", _) => write!(w, "bit.{head}"),
-		("shl", _) => write!(w, "bit.lshift"),
-		("shr", "i32" | "i64") => write!(w, "bit.arshift"),
-		("shr", "u32" | "u64") => write!(w, "bit.rshift"),
-		("rotl", _) => write!(w, "bit.rol"),
-		("rotr", _) => write!(w, "bit.ror"),
-		("convert", "f32_i64" | "f64_i64") => write!(w, "tonumber"),
-		_ => write!(w, "rt.{head}.{tail}"),
-	}?;
-
-	writeln!(w)
-}
+impl Dialect for LuaJIT {
+	fn branch_style(&self) -> BranchStyle {
+		if self.no_goto {
+			BranchStyle::Repeat
+		} else {
+			BranchStyle::Goto
+		}
+	}
 
-fn write_localize_used(func_list: &[FuncData], w: &mut dyn Write) -> Result<BTreeSet<usize>> {
-	let mut loc_set = BTreeSet::new();
-	let mut mem_set = BTreeSet::new();
+	fn is_signed(&self) -> bool {
+		true
+	}
 
-	for (loc, mem) in func_list.iter().map(localize::visit) {
-		loc_set.extend(loc);
-		mem_set.extend(mem);
+	fn has_native_i32(&self) -> bool {
+		true
 	}
 
-	for loc in loc_set {
-		write_local_operation(loc.0, loc.1, w)?;
+	fn bin_op_symbol(&self, op: BinOpType) -> Option<&'static str> {
+		op.try_into_symbol()
 	}
 
-	for mem in &mem_set {
-		writeln!(w, "local memory_at_{mem}")?;
+	fn cmp_op_symbol(&self, op: CmpOpType) -> Option<&'static str> {
+		op.try_into_symbol()
 	}
 
-	Ok(mem_set)
+	fn write_i64(&self, number: i64, w: &mut dyn Write) -> Result<()> {
+		write!(w, "{number}LL")
+	}
 }
 
-fn write_func_start(wasm: &Module, index: u32, w: &mut dyn Write) -> Result<()> {
-	write!(w, "FUNC_LIST[{index}] = ")?;
-
-	wasm.name_section()
-		.get(&index)
-		.map_or_else(|| Ok(()), |name| write!(w, "--[[ {name} ]] "))
+/// # Errors
+/// Returns `Err` if writing to `Write` failed.
+pub fn from_inst_list(code: &[Operator], type_info: &TypeInfo, w: &mut dyn Write) -> Result<()> {
+	codegen_core::from_inst_list(&LUA_JIT, code, type_info, w)
 }
 
-fn write_func_list(wasm: &Module, func_list: &[FuncData], w: &mut dyn Write) -> Result<()> {
-	let offset = wasm.import_count(External::Func);
-
-	func_list.iter().enumerate().try_for_each(|(i, v)| {
-		let index = (offset + i).try_into().unwrap();
-
-		write_func_start(wasm, index, w)?;
-
-		v.write(&mut Manager::function(v), w)
-	})
+/// # Errors
+/// Returns `Err` if writing to `Write` failed.
+pub fn from_module_typed(wasm: &Module, type_info: &TypeInfo, w: &mut dyn Write) -> Result<()> {
+	codegen_core::from_module_typed(&LUA_JIT, wasm, type_info, w)
 }
 
-fn write_module_start(
+/// Translates the module, also embedding the contents of every custom section
+/// named in `name_list` as a string in the `custom_list` of the instance.
+///
+/// # Errors
+/// Returns `Err` if writing to `Write` failed.
+pub fn from_module_custom(
 	wasm: &Module,
 	type_info: &TypeInfo,
-	mem_set: &BTreeSet<usize>,
+	name_list: &[&str],
+	options: &Options,
 	w: &mut dyn Write,
 ) -> Result<()> {
-	writeln!(w, "local function run_init_code()")?;
-	write_table_list(wasm, w)?;
-	write_memory_list(wasm, w)?;
-	write_global_list(wasm, type_info, w)?;
-	write_element_list(wasm.element_section(), type_info, w)?;
-	write_data_list(wasm.data_section(), type_info, w)?;
-	writeln!(w, "end")?;
-
-	writeln!(w, "return function(wasm)")?;
-	write_import_list(wasm.import_section(), w)?;
-	writeln!(w, "\trun_init_code()")?;
-
-	for mem in mem_set {
-		writeln!(w, "\tmemory_at_{mem} = MEMORY_LIST[{mem}]")?;
-	}
-
-	if let Some(start) = wasm.start_section() {
-		writeln!(w, "\tFUNC_LIST[{start}]()")?;
-	}
-
-	writeln!(w, "\treturn {{")?;
-	write_export_list(wasm.export_section(), w)?;
-	writeln!(w, "\t}}")?;
-	writeln!(w, "end")
+	codegen_core::from_module_custom(&LUA_JIT, wasm, type_info, name_list, options, w)
 }
 
+/// Translates the module as in [`from_module_custom`], also returning the
+/// line that each function and statement starts at for a source map.
+///
 /// # Errors
 /// Returns `Err` if writing to `Write` failed.
-pub fn from_inst_list(code: &[Operator], type_info: &TypeInfo, w: &mut dyn Write) -> Result<()> {
-	let ast = Factory::from_type_info(type_info).create_anonymous(code);
-
-	ast.write(&mut Manager::function(&ast), w)
+pub fn from_module_mapped(
+	wasm: &Module,
+	type_info: &TypeInfo,
+	name_list: &[&str],
+	options: &Options,
+	w: &mut dyn Write,
+) -> Result<Vec<Mapping>> {
+	codegen_core::from_module_mapped(&LUA_JIT, wasm, type_info, name_list, options, w)
 }
 
+/// Translates the module with its functions moved out to chunks, which are
+/// returned to be written where the `import` of `split` loads them from.
+///
 /// # Errors
 /// Returns `Err` if writing to `Write` failed.
-pub fn from_module_typed(wasm: &Module, type_info: &TypeInfo, w: &mut dyn Write) -> Result<()> {
-	let func_list = build_func_list(wasm, type_info);
-	let mem_set = write_localize_used(&func_list, w)?;
-
-	writeln!(w, "local table_new = require(\"table.new\")")?;
-	write_named_array("FUNC_LIST", wasm.function_space(), w)?;
-	write_named_array("TABLE_LIST", wasm.table_space(), w)?;
-	write_named_array("MEMORY_LIST", wasm.memory_space(), w)?;
-	write_named_array("GLOBAL_LIST", wasm.global_space(), w)?;
-
-	write_func_list(wasm, &func_list, w)?;
-	write_module_start(wasm, type_info, &mem_set, w)
+pub fn from_module_split(
+	wasm: &Module,
+	type_info: &TypeInfo,
+	name_list: &[&str],
+	options: &Options,
+	split: &Split,
+	w: &mut dyn Write,
+) -> Result<Vec<Vec<u8>>> {
+	codegen_core::from_module_split(&LUA_JIT, wasm, type_info, name_list, options, split, w)
 }
 
 /// # Errors
 /// Returns `Err` if writing to `Write` failed.
 pub fn from_module_untyped(wasm: &Module, w: &mut dyn Write) -> Result<()> {
-	let type_info = TypeInfo::from_module(wasm);
+	codegen_core::from_module_untyped(&LUA_JIT, wasm, w)
+}
 
-	from_module_typed(wasm, &type_info, w)
+/// Writes a script that checks the runtime against edge cases, as described
+/// in [`codegen_core::write_self_test`], to be run after the runtime.
+///
+/// # Errors
+/// Returns `Err` if writing to `Write` failed.
+pub fn write_self_test(w: &mut dyn Write) -> Result<()> {
+	codegen_core::write_self_test(&LUA_JIT, w)
 }
@@ -1,46 +1,4 @@
-use wasm_ast::node::{BinOpType, CmpOpType, LoadType, StoreType, UnOpType};
-
-pub trait IntoName {
-	#[must_use]
-	fn into_name(self) -> &'static str;
-}
-
-impl IntoName for LoadType {
-	fn into_name(self) -> &'static str {
-		match self {
-			Self::I32 => "i32",
-			Self::I64 => "i64",
-			Self::F32 => "f32",
-			Self::F64 => "f64",
-			Self::I32_I8 => "i32_i8",
-			Self::I32_U8 => "i32_u8",
-			Self::I32_I16 => "i32_i16",
-			Self::I32_U16 => "i32_u16",
-			Self::I64_I8 => "i64_i8",
-			Self::I64_U8 => "i64_u8",
-			Self::I64_I16 => "i64_i16",
-			Self::I64_U16 => "i64_u16",
-			Self::I64_I32 => "i64_i32",
-			Self::I64_U32 => "i64_u32",
-		}
-	}
-}
-
-impl IntoName for StoreType {
-	fn into_name(self) -> &'static str {
-		match self {
-			Self::I32 => "i32",
-			Self::I64 => "i64",
-			Self::F32 => "f32",
-			Self::F64 => "f64",
-			Self::I32_N8 => "i32_n8",
-			Self::I32_N16 => "i32_n16",
-			Self::I64_N8 => "i64_n8",
-			Self::I64_N16 => "i64_n16",
-			Self::I64_N32 => "i64_n32",
-		}
-	}
-}
+use wasm_ast::node::{BinOpType, CmpOpType, UnOpType};
 
 pub trait IntoNameTuple {
 	#[must_use]
@@ -62,7 +20,7 @@ impl IntoNameTuple for UnOpType {
 			Self::Floor_F32 => ("math", "floor"),
 			Self::Truncate_F32 => ("rt_truncate", "f32"),
 			Self::Nearest_F32 => ("rt_nearest", "f32"),
-			Self::Sqrt_F32 => ("math", "sqrt"),
+			Self::Sqrt_F32 => ("rt_sqrt", "f32"),
 			Self::Abs_F64 => ("math", "abs"),
 			Self::Neg_F64 => ("rt_neg", "f64"),
 			Self::Ceil_F64 => ("math", "ceil"),
@@ -94,11 +52,11 @@ impl IntoNameTuple for UnOpType {
 			Self::Extend_I64_N32 => ("rt_extend", "i64_n32"),
 			Self::Extend_I64_I32 => ("rt_extend", "i64_i32"),
 			Self::Extend_I64_U32 => ("rt_extend", "i64_u32"),
-			Self::Convert_F32_I32 => ("rt_convert", "f64_i32"),
-			Self::Convert_F32_U32 => ("no", "op"),
-			Self::Convert_F32_I64 => ("rt_convert", "f64_i64"),
-			Self::Convert_F32_U64 => ("rt_convert", "f64_u64"),
-			Self::Demote_F32_F64 => ("no", "op"),
+			Self::Convert_F32_I32 => ("rt_convert", "f32_i32"),
+			Self::Convert_F32_U32 => ("rt_round", "f32"),
+			Self::Convert_F32_I64 => ("rt_convert", "f32_i64"),
+			Self::Convert_F32_U64 => ("rt_convert", "f32_u64"),
+			Self::Demote_F32_F64 => ("rt_round", "f32"),
 			Self::Convert_F64_I32 => ("rt_convert", "f64_i32"),
 			Self::Convert_F64_U32 => ("no", "op"),
 			Self::Convert_F64_I64 => ("rt_convert", "f64_i64"),
@@ -210,11 +168,10 @@ pub trait TryIntoSymbol {
 impl TryIntoSymbol for BinOpType {
 	fn try_into_symbol(self) -> Option<&'static str> {
 		let result = match self {
-			Self::Add_F32 | Self::Add_F64 => "+",
-			Self::Sub_F32 | Self::Sub_F64 => "-",
-			Self::Mul_F32 | Self::Mul_F64 => "*",
-			Self::Div_F32 | Self::Div_F64 => "/",
-			Self::RemU_I32 => "%",
+			Self::Add_F64 => "+",
+			Self::Sub_F64 => "-",
+			Self::Mul_F64 => "*",
+			Self::Div_F64 => "/",
 			_ => return None,
 		};
 
@@ -1,3 +1 @@
-pub mod br_target;
 pub mod into_string;
-pub mod localize;
@@ -1,4 +0,0 @@
-pub mod manager;
-
-mod expression;
-mod statement;
@@ -1,34 +1,70 @@
-use std::io::{ErrorKind, Result, Write};
+use std::io::{Result, Write};
 
 use wasm_ast::module::Module;
 
-fn load_arg_source() -> Result<Vec<u8>> {
-	let mut arguments = std::env::args();
-	let path = arguments.next().unwrap_or_else(|| "wasm2luau".to_string());
+fn do_directives(lock: &mut dyn Write) -> Result<()> {
+	if cfg!(feature = "native") {
+		writeln!(lock, "--!native")?;
+	}
 
-	arguments.next().map_or_else(
-		|| {
-			eprintln!("usage: {path} <file>\n");
-
-			Err(ErrorKind::NotFound.into())
-		},
-		std::fs::read,
-	)
+	writeln!(lock, "--!optimize 2")
 }
 
 fn do_runtime(lock: &mut dyn Write) -> Result<()> {
 	let runtime = codegen_luau::RUNTIME;
 
-	writeln!(lock, "--!optimize 2")?;
+	do_directives(lock)?;
 	writeln!(lock, "{runtime}")
 }
 
-fn main() -> Result<()> {
-	let data = load_arg_source()?;
-	let wasm = Module::try_from_data(&data).unwrap();
+// A `]]>` inside the source would end the section early, so it is split
+// across two of them
+fn do_script(name: &str, source: &[u8], lock: &mut dyn Write) -> Result<()> {
+	let source = String::from_utf8_lossy(source).replace("]]>", "]]]]><![CDATA[>");
+
+	writeln!(lock, "<Properties>")?;
+	writeln!(lock, "<string name=\"Name\">{name}</string>")?;
+	writeln!(
+		lock,
+		"<ProtectedString name=\"Source\"><![CDATA[{source}]]></ProtectedString>"
+	)?;
+	writeln!(lock, "</Properties>")
+}
+
+// Roblox models are written as XML, holding the translated `ModuleScript`
+// with the runtime as its `Runtime` child so the file can be dropped in as is
+fn do_model(source: &[u8], lock: &mut dyn Write) -> Result<()> {
+	let mut runtime = Vec::new();
+
+	do_directives(&mut runtime)?;
+	codegen_luau::write_runtime_script(&mut runtime)?;
+
+	writeln!(lock, "<roblox version=\"4\">")?;
+	writeln!(lock, "<Item class=\"ModuleScript\" referent=\"RBX0\">")?;
+	do_script("Module", source, lock)?;
+	writeln!(lock, "<Item class=\"ModuleScript\" referent=\"RBX1\">")?;
+	do_script("Runtime", &runtime, lock)?;
+	writeln!(lock, "</Item>")?;
+	writeln!(lock, "</Item>")?;
+	writeln!(lock, "</roblox>")
+}
+
+fn do_module(data: &[u8], lock: &mut dyn Write) -> Result<()> {
+	let wasm = Module::try_from_data(data).unwrap();
+
+	if cfg!(feature = "module_script") {
+		let mut source = Vec::new();
+
+		do_directives(&mut source)?;
+		codegen_luau::script_from_module_untyped(&wasm, &mut source)?;
 
-	let lock = &mut std::io::stdout().lock();
+		return do_model(&source, lock);
+	}
 
 	do_runtime(lock)?;
 	codegen_luau::from_module_untyped(&wasm, lock)
 }
+
+fn main() -> Result<()> {
+	codegen_core::run_command("wasm2luau", do_module)
+}
@@ -1,8 +1,31 @@
-pub static RUNTIME: &str = include_str!("../runtime/runtime.luau");
+/// The runtime with every `i64` packed in a `Vector3`, or as [`RUNTIME_PAIR`]
+/// does when the `vector` feature is turned off.
+#[cfg(feature = "vector")]
+pub static RUNTIME: &str = concat!(
+	include_str!("../runtime/bit.luau"),
+	include_str!("../runtime/i64_vector.luau"),
+	include_str!("../runtime/runtime.luau"),
+);
+#[cfg(not(feature = "vector"))]
+pub static RUNTIME: &str = RUNTIME_PAIR;
+/// The runtime with every `i64` as a table of its two 32 bit halves, which
+/// makes arithmetic on them skip the unpacking at the cost of allocating a
+/// table for each result.
+pub static RUNTIME_PAIR: &str = concat!(
+	include_str!("../runtime/bit.luau"),
+	include_str!("../runtime/i64_pair.luau"),
+	include_str!("../runtime/runtime.luau"),
+);
 pub static EXPORT_RUNTIME: &str = include_str!("../runtime/export_runtime.luau");
+/// Appended to either runtime to pass the loads, stores, and globals it is
+/// told to watch to a hook, for finding where memory is corrupted.
+pub static TRACE: &str = include_str!("../runtime/trace.luau");
 
-pub use translator::{from_inst_list, from_module_typed, from_module_untyped};
+pub use translator::{
+	from_inst_list, from_module_custom, from_module_mapped, from_module_split, from_module_typed,
+	from_module_untyped, script_from_module_imported, script_from_module_untyped,
+	write_runtime_script, write_self_test,
+};
 
 mod analyzer;
-mod backend;
 mod translator;
@@ -1,340 +1,352 @@
-use std::{
-	collections::BTreeSet,
-	io::{Result, Write},
-};
+use std::io::{Result, Write};
 
+use codegen_core::{is_exact_decimal, Backend, BranchStyle, Dialect, Mapping, Options, Split};
 use wasm_ast::{
-	factory::Factory,
-	module::{External, Module, TypeInfo},
-	node::{FuncData, Statement},
-};
-use wasmparser::{
-	ConstExpr, Data, DataKind, Element, ElementItems, ElementKind, Export, Import, Operator,
-	OperatorsReader, ValType,
+	module::{Module, TypeInfo},
+	node::{BinOpType, CmpOpType, Expression, FuncData, UnOpType, Value},
 };
+use wasmparser::{Operator, ValType};
 
-use crate::{
-	analyzer::localize,
-	backend::manager::{Driver, Manager},
-};
+use crate::analyzer::into_string::{IntoNameTuple, TryIntoSymbol};
 
-trait AsIEName {
-	fn as_ie_name(&self) -> &str;
-}
+struct Luau;
 
-impl AsIEName for External {
-	fn as_ie_name(&self) -> &str {
-		match self {
-			Self::Func => "func_list",
-			Self::Table => "table_list",
-			Self::Memory => "memory_list",
-			Self::Global => "global_list",
-			Self::Tag => unimplemented!(),
-		}
+impl Backend for Luau {
+	fn write_function(&self, func: &FuncData, options: &Options, w: &mut dyn Write) -> Result<()> {
+		codegen_core::write_function(self, func, options, w)
 	}
-}
-
-fn reader_to_code(reader: OperatorsReader) -> Vec<Operator> {
-	let parsed: std::result::Result<_, _> = reader.into_iter().collect();
 
-	parsed.unwrap()
-}
+	fn write_function_marked(
+		&self,
+		func: &FuncData,
+		options: &Options,
+		w: &mut dyn Write,
+	) -> Result<()> {
+		codegen_core::write_function_marked(self, func, options, w)
+	}
 
-fn write_named_array(name: &str, len: usize, w: &mut dyn Write) -> Result<()> {
-	let Some(len) = len.checked_sub(1) else {
-		return Ok(());
-	};
+	fn write_expression(&self, expr: &Expression, w: &mut dyn Write) -> Result<()> {
+		codegen_core::write_expression(self, expr, w)
+	}
 
-	writeln!(w, "local {name} = table.create({len})")
-}
+	fn un_op_name(&self, op: UnOpType) -> (&'static str, &'static str) {
+		op.into_name_tuple()
+	}
 
-fn write_constant(init: &ConstExpr, type_info: &TypeInfo, w: &mut dyn Write) -> Result<()> {
-	let code = reader_to_code(init.get_operators_reader());
-	let func = Factory::from_type_info(type_info).create_anonymous(&code);
+	fn bin_op_name(&self, op: BinOpType) -> Option<(&'static str, &'static str)> {
+		op.try_into_symbol().is_none().then(|| op.into_name_tuple())
+	}
 
-	if let Some(Statement::SetTemporary(stat)) = func.code().code().last() {
-		stat.value().write(&mut Manager::empty(), w)
-	} else {
-		writeln!(w, r#"error("Valueless constant")"#)
+	fn cmp_op_name(&self, op: CmpOpType) -> Option<(&'static str, &'static str)> {
+		op.try_into_symbol().is_none().then(|| op.into_name_tuple())
 	}
-}
 
-fn write_import_of(list: &[Import], wanted: External, w: &mut dyn Write) -> Result<()> {
-	let lower = wanted.as_ie_name();
-	let upper = lower.to_uppercase();
+	fn value_name(&self, value: Value) -> Option<(&'static str, &'static str)> {
+		let name = match value {
+			Value::I64(0) => ("rt_i64", "ZERO"),
+			Value::I64(1) => ("rt_i64", "ONE"),
+			Value::I64(_) => ("rt_i64", "from_u32"),
+			Value::F32(v) if v.is_nan() || (v.is_finite() && !is_exact_decimal(v.into())) => {
+				("rt_reinterpret", "f32_i32")
+			}
+			Value::F64(v) if v.is_nan() || (v.is_finite() && !is_exact_decimal(v)) => {
+				("rt_reinterpret", "f64_i64")
+			}
+			_ => return None,
+		};
 
-	for (i, Import { name, module, .. }) in list
-		.iter()
-		.filter(|v| External::from(v.ty) == wanted)
-		.enumerate()
-	{
-		write!(w, "\t")?;
-		writeln!(w, r#"{upper}[{i}] = wasm["{module}"].{lower}["{name}"]"#)?;
+		Some(name)
 	}
 
-	Ok(())
-}
+	fn write_runtime_name(&self, head: &str, tail: &str, w: &mut dyn Write) -> Result<()> {
+		write!(w, "rt_{head}_{tail}")
+	}
 
-fn write_export_of(list: &[Export], wanted: External, w: &mut dyn Write) -> Result<()> {
-	let lower = wanted.as_ie_name();
-	let upper = lower.to_uppercase();
+	fn write_trap_table(&self, w: &mut dyn Write) -> Result<()> {
+		write!(w, "rt_trap")
+	}
 
-	writeln!(w, "\t\t{lower} = {{")?;
+	fn write_trace_table(&self, w: &mut dyn Write) -> Result<()> {
+		write!(w, "rt_trace")
+	}
 
-	for Export { name, index, .. } in list.iter().filter(|v| External::from(v.kind) == wanted) {
-		write!(w, "\t\t\t")?;
-		writeln!(w, r#"["{name}"] = {upper}[{index}],"#)?;
+	fn write_memory_local(&self, index: usize, w: &mut dyn Write) -> Result<()> {
+		if cfg!(feature = "native") {
+			writeln!(
+				w,
+				"local memory_at_{index}: {{ max: number, data: buffer }}"
+			)
+		} else {
+			writeln!(w, "local memory_at_{index}")
+		}
 	}
 
-	writeln!(w, "\t\t}},")
-}
+	fn write_jump_table_local(&self, w: &mut dyn Write) -> Result<()> {
+		if cfg!(feature = "native") {
+			write!(w, "local br_map: {{ {{ [number]: number }} }} = ")
+		} else {
+			write!(w, "local br_map = ")
+		}
+	}
 
-fn write_import_list(list: &[Import], w: &mut dyn Write) -> Result<()> {
-	write_import_of(list, External::Func, w)?;
-	write_import_of(list, External::Table, w)?;
-	write_import_of(list, External::Memory, w)?;
-	write_import_of(list, External::Global, w)
-}
+	fn write_named_array(&self, name: &str, len: usize, w: &mut dyn Write) -> Result<()> {
+		let Some(len) = len.checked_sub(1) else {
+			return Ok(());
+		};
 
-fn write_export_list(list: &[Export], w: &mut dyn Write) -> Result<()> {
-	writeln!(w, "{}", crate::EXPORT_RUNTIME)?;
-	write_export_of(list, External::Func, w)?;
-	write_export_of(list, External::Table, w)?;
-	write_export_of(list, External::Memory, w)?;
-	write_export_of(list, External::Global, w)
+		writeln!(w, "local {name} = table.create({len})")
+	}
+
+	fn write_export_runtime(&self, w: &mut dyn Write) -> Result<()> {
+		writeln!(w, "{}", crate::EXPORT_RUNTIME)
+	}
 }
 
-fn write_table_list(wasm: &Module, w: &mut dyn Write) -> Result<()> {
-	let offset = wasm.import_count(External::Table);
-	let table = wasm.table_section();
+// Values of `i32` are kept unsigned, and anything without a symbol, which
+// includes every operation on `i64`, goes through the runtime
+impl Dialect for Luau {
+	fn branch_style(&self) -> BranchStyle {
+		BranchStyle::Continue
+	}
 
-	for (i, table) in table.iter().enumerate() {
-		let index = offset + i;
-		let min = table.ty.initial;
-		let max = table.ty.maximum.unwrap_or(0xFFFF);
+	fn is_signed(&self) -> bool {
+		false
+	}
 
-		writeln!(
-			w,
-			"\tTABLE_LIST[{index}] = {{ min = {min}, max = {max}, data = {{}} }}"
-		)?;
+	fn has_if_expression(&self) -> bool {
+		true
 	}
 
-	Ok(())
-}
+	fn bin_op_symbol(&self, op: BinOpType) -> Option<&'static str> {
+		op.try_into_symbol()
+	}
 
-fn write_memory_list(wasm: &Module, w: &mut dyn Write) -> Result<()> {
-	let offset = wasm.import_count(External::Memory);
-	let memory = wasm.memory_section();
+	fn cmp_op_symbol(&self, op: CmpOpType) -> Option<&'static str> {
+		op.try_into_symbol()
+	}
 
-	for (i, ty) in memory.iter().enumerate() {
-		let index = offset + i;
-		let min = ty.initial;
-		let max = ty.maximum.unwrap_or(0xFFFF);
+	fn write_i64(&self, number: i64, w: &mut dyn Write) -> Result<()> {
+		match number {
+			0 => write!(w, "rt_i64_ZERO"),
+			1 => write!(w, "rt_i64_ONE"),
+			_ => {
+				let list = number.to_ne_bytes();
+				let a = u32::from_ne_bytes(list[0..4].try_into().unwrap());
+				let b = u32::from_ne_bytes(list[4..8].try_into().unwrap());
 
-		writeln!(w, "\tMEMORY_LIST[{index}] = rt_allocator_new({min}, {max})")?;
+				write!(w, "rt_i64_from_u32({a}, {b})")
+			}
+		}
 	}
 
-	Ok(())
-}
-
-fn write_global_list(wasm: &Module, type_info: &TypeInfo, w: &mut dyn Write) -> Result<()> {
-	let offset = wasm.import_count(External::Global);
-	let global = wasm.global_section();
+	fn write_runtime_table(&self, name: &str, w: &mut dyn Write) -> Result<()> {
+		write!(w, "rt_{name}")
+	}
 
-	for (i, global) in global.iter().enumerate() {
-		let index = offset + i;
+	fn write_access_name(&self, head: &str, tail: &str, w: &mut dyn Write) -> Result<()> {
+		write!(w, "rt_{head}_{tail}")
+	}
 
-		write!(w, "\tGLOBAL_LIST[{index}] = {{ value = ")?;
-		write_constant(&global.init_expr, type_info, w)?;
-		writeln!(w, " }}")?;
+	fn write_memory_size(&self, index: usize, w: &mut dyn Write) -> Result<()> {
+		write!(w, "rt_allocator_size(memory_at_{index})")
 	}
 
-	Ok(())
-}
+	// Luau's native code generator specializes functions on the types of their
+	// parameters and locals, so they are only annotated when targeting it
+	fn type_annotation(&self, ty: ValType) -> &'static str {
+		if !cfg!(feature = "native") {
+			return "";
+		}
 
-fn write_element_list(list: &[Element], type_info: &TypeInfo, w: &mut dyn Write) -> Result<()> {
-	for element in list {
-		let ElementKind::Active {
-			table_index: index,
-			offset_expr: init,
-		} = element.kind
-		else {
-			unimplemented!("passive elements not supported")
-		};
+		match ty {
+			ValType::I32 | ValType::F32 | ValType::F64 => ": number",
+			ValType::I64 if cfg!(feature = "vector") => ": Vector3",
+			ValType::I64 => ": { number }",
+			ValType::V128 => ": buffer",
+			ValType::Ref(_) => ": any",
+		}
+	}
+}
 
-		let index = index.unwrap_or(0);
+const RUNTIME_IMPORT: &str = "require(script.Runtime)";
 
-		writeln!(w, "\tdo")?;
-		writeln!(w, "\t\tlocal target = TABLE_LIST[{index}].data")?;
-		write!(w, "\t\tlocal offset = ")?;
+// Roblox loads the output as a `ModuleScript`, which hands back a table with
+// `new` instead of the instantiating function itself
+struct ModuleScript;
 
-		write_constant(&init, type_info, w)?;
+impl Backend for ModuleScript {
+	fn write_function(&self, func: &FuncData, options: &Options, w: &mut dyn Write) -> Result<()> {
+		Luau.write_function(func, options, w)
+	}
 
-		writeln!(w)?;
-		write!(w, "\t\tlocal data = {{ ")?;
+	fn write_function_marked(
+		&self,
+		func: &FuncData,
+		options: &Options,
+		w: &mut dyn Write,
+	) -> Result<()> {
+		Luau.write_function_marked(func, options, w)
+	}
 
-		match element.items.clone() {
-			ElementItems::Functions(functions) => {
-				for index in functions {
-					let index = index.unwrap();
-					write!(w, "FUNC_LIST[{index}],")?;
-				}
-			}
-			ElementItems::Expressions(expressions) => {
-				for init in expressions {
-					let init = init.unwrap();
-					write_constant(&init, type_info, w)?;
-				}
-			}
-		}
+	fn write_expression(&self, expr: &Expression, w: &mut dyn Write) -> Result<()> {
+		Luau.write_expression(expr, w)
+	}
 
-		writeln!(w, " }}")?;
-		writeln!(w, "\t\ttable.move(data, 1, #data, offset, target)")?;
-		writeln!(w, "\tend")?;
+	fn un_op_name(&self, op: UnOpType) -> (&'static str, &'static str) {
+		Luau.un_op_name(op)
 	}
 
-	Ok(())
-}
+	fn bin_op_name(&self, op: BinOpType) -> Option<(&'static str, &'static str)> {
+		Luau.bin_op_name(op)
+	}
 
-fn write_data_list(list: &[Data], type_info: &TypeInfo, w: &mut dyn Write) -> Result<()> {
-	for data in list {
-		let (index, init) = match data.kind {
-			DataKind::Passive => unimplemented!("passive data not supported"),
-			DataKind::Active {
-				memory_index,
-				offset_expr,
-			} => (memory_index, offset_expr),
-		};
+	fn cmp_op_name(&self, op: CmpOpType) -> Option<(&'static str, &'static str)> {
+		Luau.cmp_op_name(op)
+	}
 
-		write!(w, "\trt_store_string(MEMORY_LIST[{index}], ")?;
-		write_constant(&init, type_info, w)?;
-		writeln!(w, r#","{}")"#, data.data.escape_ascii())?;
+	fn value_name(&self, value: Value) -> Option<(&'static str, &'static str)> {
+		Luau.value_name(value)
 	}
 
-	Ok(())
-}
+	fn write_runtime_name(&self, head: &str, tail: &str, w: &mut dyn Write) -> Result<()> {
+		Luau.write_runtime_name(head, tail, w)
+	}
 
-fn build_func_list(wasm: &Module, type_info: &TypeInfo) -> Vec<FuncData> {
-	let offset = wasm.import_count(External::Func);
-	let mut builder = Factory::from_type_info(type_info);
+	fn write_trap_table(&self, w: &mut dyn Write) -> Result<()> {
+		Luau.write_trap_table(w)
+	}
 
-	wasm.code_section()
-		.iter()
-		.enumerate()
-		.map(|f| builder.create_indexed(f.0 + offset, f.1).unwrap())
-		.collect()
-}
+	fn write_trace_table(&self, w: &mut dyn Write) -> Result<()> {
+		Luau.write_trace_table(w)
+	}
 
-fn write_localize_used(
-	wasm: &Module,
-	func_list: &[FuncData],
-	w: &mut dyn Write,
-) -> Result<BTreeSet<usize>> {
-	let mut loc_set = BTreeSet::new();
-	let mut mem_set = BTreeSet::new();
+	fn write_memory_local(&self, index: usize, w: &mut dyn Write) -> Result<()> {
+		Luau.write_memory_local(index, w)
+	}
 
-	let has_global_i64 = wasm
-		.global_section()
-		.iter()
-		.any(|g| g.ty.content_type == ValType::I64);
+	fn write_jump_table_local(&self, w: &mut dyn Write) -> Result<()> {
+		Luau.write_jump_table_local(w)
+	}
 
-	if has_global_i64 {
-		loc_set.insert(("i64", "ZERO"));
-		loc_set.insert(("i64", "ONE"));
-		loc_set.insert(("i64", "from_u32"));
+	fn write_named_array(&self, name: &str, len: usize, w: &mut dyn Write) -> Result<()> {
+		Luau.write_named_array(name, len, w)
 	}
 
-	for (loc, mem) in func_list.iter().map(localize::visit) {
-		loc_set.extend(loc);
-		mem_set.extend(mem);
+	fn write_export_runtime(&self, w: &mut dyn Write) -> Result<()> {
+		Luau.write_export_runtime(w)
 	}
 
-	for mem in &mem_set {
-		writeln!(w, "local memory_at_{mem}")?;
+	fn write_instance_start(&self, _wasm: &Module, w: &mut dyn Write) -> Result<()> {
+		writeln!(w, "return {{")?;
+		writeln!(w, "new = function(wasm, on_trap, previous)")
 	}
 
-	Ok(mem_set)
+	fn write_footer(&self, w: &mut dyn Write) -> Result<()> {
+		writeln!(w, "}}")
+	}
 }
 
-fn write_func_start(wasm: &Module, index: u32, w: &mut dyn Write) -> Result<()> {
-	write!(w, "FUNC_LIST[{index}] = ")?;
-
-	wasm.name_section()
-		.get(&index)
-		.map_or_else(|| Ok(()), |name| write!(w, "--[[ {name} ]] "))
+/// Writes the runtime as the source of the `Runtime` child of a translated
+/// `ModuleScript`, returning a table of everything it declares.
+///
+/// # Errors
+/// Returns `Err` if writing to `Write` failed.
+pub fn write_runtime_script(w: &mut dyn Write) -> Result<()> {
+	codegen_core::write_runtime_table(crate::RUNTIME, w)
 }
 
-fn write_func_list(wasm: &Module, func_list: &[FuncData], w: &mut dyn Write) -> Result<()> {
-	let offset = wasm.import_count(External::Func);
-
-	func_list.iter().enumerate().try_for_each(|(i, v)| {
-		let index = (offset + i).try_into().unwrap();
-
-		write_func_start(wasm, index, w)?;
+/// # Errors
+/// Returns `Err` if writing to `Write` failed.
+pub fn from_inst_list(code: &[Operator], type_info: &TypeInfo, w: &mut dyn Write) -> Result<()> {
+	codegen_core::from_inst_list(&Luau, code, type_info, w)
+}
 
-		v.write(&mut Manager::function(v), w)
-	})
+/// # Errors
+/// Returns `Err` if writing to `Write` failed.
+pub fn from_module_typed(wasm: &Module, type_info: &TypeInfo, w: &mut dyn Write) -> Result<()> {
+	codegen_core::from_module_typed(&Luau, wasm, type_info, w)
 }
 
-fn write_module_start(
+/// Translates the module, also embedding the contents of every custom section
+/// named in `name_list` as a string in the `custom_list` of the instance.
+///
+/// # Errors
+/// Returns `Err` if writing to `Write` failed.
+pub fn from_module_custom(
 	wasm: &Module,
 	type_info: &TypeInfo,
-	mem_set: &BTreeSet<usize>,
+	name_list: &[&str],
+	options: &Options,
 	w: &mut dyn Write,
 ) -> Result<()> {
-	writeln!(w, "local function run_init_code()")?;
-	write_table_list(wasm, w)?;
-	write_memory_list(wasm, w)?;
-	write_global_list(wasm, type_info, w)?;
-	write_element_list(wasm.element_section(), type_info, w)?;
-	write_data_list(wasm.data_section(), type_info, w)?;
-	writeln!(w, "end")?;
-
-	writeln!(w, "return function(wasm)")?;
-	write_import_list(wasm.import_section(), w)?;
-	writeln!(w, "\trun_init_code()")?;
-
-	for mem in mem_set {
-		writeln!(w, "\tmemory_at_{mem} = MEMORY_LIST[{mem}]")?;
-	}
-
-	if let Some(start) = wasm.start_section() {
-		writeln!(w, "\tFUNC_LIST[{start}]()")?;
-	}
-
-	writeln!(w, "\treturn {{")?;
-	write_export_list(wasm.export_section(), w)?;
-	writeln!(w, "\t}}")?;
-	writeln!(w, "end")
+	codegen_core::from_module_custom(&Luau, wasm, type_info, name_list, options, w)
 }
 
+/// Translates the module as in [`from_module_custom`], also returning the
+/// line that each function and statement starts at for a source map.
+///
 /// # Errors
 /// Returns `Err` if writing to `Write` failed.
-pub fn from_inst_list(code: &[Operator], type_info: &TypeInfo, w: &mut dyn Write) -> Result<()> {
-	let ast = Factory::from_type_info(type_info).create_anonymous(code);
+pub fn from_module_mapped(
+	wasm: &Module,
+	type_info: &TypeInfo,
+	name_list: &[&str],
+	options: &Options,
+	w: &mut dyn Write,
+) -> Result<Vec<Mapping>> {
+	codegen_core::from_module_mapped(&Luau, wasm, type_info, name_list, options, w)
+}
 
-	ast.write(&mut Manager::function(&ast), w)
+/// Translates the module with its functions moved out to chunks, which are
+/// returned to be written where the `import` of `split` loads them from.
+///
+/// # Errors
+/// Returns `Err` if writing to `Write` failed.
+pub fn from_module_split(
+	wasm: &Module,
+	type_info: &TypeInfo,
+	name_list: &[&str],
+	options: &Options,
+	split: &Split,
+	w: &mut dyn Write,
+) -> Result<Vec<Vec<u8>>> {
+	codegen_core::from_module_split(&Luau, wasm, type_info, name_list, options, split, w)
 }
 
 /// # Errors
 /// Returns `Err` if writing to `Write` failed.
-pub fn from_module_typed(wasm: &Module, type_info: &TypeInfo, w: &mut dyn Write) -> Result<()> {
-	let func_list = build_func_list(wasm, type_info);
-	let mem_set = write_localize_used(wasm, &func_list, w)?;
+pub fn from_module_untyped(wasm: &Module, w: &mut dyn Write) -> Result<()> {
+	codegen_core::from_module_untyped(&Luau, wasm, w)
+}
 
-	write_named_array("FUNC_LIST", wasm.function_space(), w)?;
-	write_named_array("TABLE_LIST", wasm.table_space(), w)?;
-	write_named_array("MEMORY_LIST", wasm.memory_space(), w)?;
-	write_named_array("GLOBAL_LIST", wasm.global_space(), w)?;
+/// Writes a script that checks the runtime against edge cases, as described
+/// in [`codegen_core::write_self_test`], to be run after the runtime.
+///
+/// # Errors
+/// Returns `Err` if writing to `Write` failed.
+pub fn write_self_test(w: &mut dyn Write) -> Result<()> {
+	codegen_core::write_self_test(&Luau, w)
+}
 
-	write_func_list(wasm, &func_list, w)?;
-	write_module_start(wasm, type_info, &mem_set, w)
+/// Translates the module as the source of a `ModuleScript` returning
+/// `{ new = function(wasm) ... end }`, with the runtime required from the
+/// `Runtime` child written by [`write_runtime_script`].
+///
+/// # Errors
+/// Returns `Err` if writing to `Write` failed.
+pub fn script_from_module_untyped(wasm: &Module, w: &mut dyn Write) -> Result<()> {
+	script_from_module_imported(wasm, RUNTIME_IMPORT, w)
 }
 
+/// Translates the module as in [`script_from_module_untyped`], but loads the
+/// runtime with the `import` expression instead, such as
+/// `require(game.ReplicatedStorage.Runtime)`.
+///
 /// # Errors
 /// Returns `Err` if writing to `Write` failed.
-pub fn from_module_untyped(wasm: &Module, w: &mut dyn Write) -> Result<()> {
-	let type_info = TypeInfo::from_module(wasm);
+pub fn script_from_module_imported(wasm: &Module, import: &str, w: &mut dyn Write) -> Result<()> {
+	let mut data = Vec::new();
 
-	from_module_typed(wasm, &type_info, w)
+	codegen_core::from_module_untyped(&ModuleScript, wasm, &mut data)?;
+	codegen_core::write_runtime_binding(crate::RUNTIME, import, &data, w)
 }
@@ -0,0 +1,10 @@
+// OpenResty runs on LuaJIT, so the runtime and the function bodies are shared
+// with it and only the module packaging differs
+pub static RUNTIME: &str = codegen_luajit::RUNTIME;
+
+pub use translator::{
+	from_inst_list, from_module_custom, from_module_mapped, from_module_split, from_module_typed,
+	from_module_untyped, write_self_test,
+};
+
+mod translator;
@@ -0,0 +1,9 @@
+// Teal compiles to Lua 5.4, so the runtime and the function bodies are shared
+// with it and only the module level declarations carry type annotations
+pub static RUNTIME: &str = codegen_lua54::RUNTIME;
+
+pub use translator::{
+	from_inst_list, from_module_custom, from_module_mapped, from_module_typed, from_module_untyped,
+};
+
+mod translator;
@@ -0,0 +1,520 @@
+use std::io::{Result, Write};
+
+use codegen_core::{Backend, Mapping, Options};
+use codegen_lua54::Lua54;
+use wasm_ast::{
+	module::{External, Module, TypeInfo},
+	node::{BinOpType, CmpOpType, Expression, FuncData, UnOpType, Value},
+};
+use wasmparser::{FuncType, Operator, Type, TypeRef, ValType};
+
+const FUNCTION_TYPE: &str = "function(...: any): any...";
+
+const KEYWORD_LIST: [&str; 22] = [
+	"and", "break", "do", "else", "elseif", "end", "false", "for", "function", "goto", "if", "in",
+	"local", "nil", "not", "or", "repeat", "return", "then", "true", "until", "while",
+];
+
+fn is_identifier(name: &str) -> bool {
+	let mut chars = name.chars();
+	let is_start = chars
+		.next()
+		.is_some_and(|v| v.is_ascii_alphabetic() || v == '_');
+
+	is_start
+		&& chars.all(|v| v.is_ascii_alphanumeric() || v == '_')
+		&& !KEYWORD_LIST.contains(&name)
+}
+
+const fn type_to_name(ty: ValType) -> &'static str {
+	match ty {
+		ValType::I32 | ValType::I64 => "integer",
+		ValType::F32 | ValType::F64 => "number",
+		ValType::V128 => "string",
+		ValType::Ref(_) => "any",
+	}
+}
+
+fn write_type_list(list: &[ValType], w: &mut dyn Write) -> Result<()> {
+	for (i, &ty) in list.iter().enumerate() {
+		if i != 0 {
+			write!(w, ", ")?;
+		}
+
+		write!(w, "{}", type_to_name(ty))?;
+	}
+
+	Ok(())
+}
+
+fn write_func_type(ty: &FuncType, w: &mut dyn Write) -> Result<()> {
+	write!(w, "function(")?;
+	write_type_list(ty.params(), w)?;
+	write!(w, ")")?;
+
+	match ty.results() {
+		[] => Ok(()),
+		[result] => write!(w, ": {}", type_to_name(*result)),
+		list => {
+			write!(w, ": (")?;
+			write_type_list(list, w)?;
+			write!(w, ")")
+		}
+	}
+}
+
+fn build_func_type_list<'a>(wasm: &'a Module) -> Vec<&'a FuncType> {
+	let import_list = wasm.import_section().iter().filter_map(|v| match v.ty {
+		TypeRef::Func(index) => Some(index),
+		_ => None,
+	});
+
+	import_list
+		.chain(wasm.func_section().iter().copied())
+		.map(|index| {
+			let index = usize::try_from(index).unwrap();
+			let Type::Func(ty) = &wasm.type_section()[index] else {
+				unreachable!("type at func index must be a func type");
+			};
+
+			ty
+		})
+		.collect()
+}
+
+// Exports with names that are not identifiers cannot be record fields, so
+// the function exports fall back to a plain map when any are present
+fn write_func_export_record(wasm: &Module, w: &mut dyn Write) -> Result<bool> {
+	let mut export_list = wasm
+		.export_section()
+		.iter()
+		.filter(|v| External::from(v.kind) == External::Func);
+
+	if !export_list.clone().all(|v| is_identifier(v.name)) {
+		return Ok(false);
+	}
+
+	let type_list = build_func_type_list(wasm);
+
+	writeln!(w, "local record FuncExports")?;
+
+	export_list.try_for_each(|v| {
+		let index = usize::try_from(v.index).unwrap();
+
+		write!(w, "\t{}: ", v.name)?;
+		write_func_type(type_list[index], w)?;
+		writeln!(w)
+	})?;
+
+	writeln!(w, "end")?;
+
+	Ok(true)
+}
+
+fn write_export_record(wasm: &Module, w: &mut dyn Write) -> Result<()> {
+	let is_record = write_func_export_record(wasm, w)?;
+
+	writeln!(w, "local record Exports")?;
+
+	if is_record {
+		writeln!(w, "\tfunc_list: FuncExports")?;
+	} else {
+		writeln!(w, "\tfunc_list: {{string: {FUNCTION_TYPE}}}")?;
+	}
+
+	writeln!(w, "\ttable_list: {{string: any}}")?;
+	writeln!(w, "\tmemory_list: {{string: any}}")?;
+	writeln!(w, "\tglobal_list: {{string: any}}")?;
+	writeln!(w, "\ttag_list: {{string: any}}")?;
+	writeln!(w, "\tcustom_list: {{string: string}}")?;
+	writeln!(w, "\trt: any")?;
+	writeln!(w, "\tfuel: any")?;
+	writeln!(w, "\tyield: any")?;
+	writeln!(w, "\tprofile: any")?;
+	writeln!(w, "\tcoverage: any")?;
+	writeln!(w, "\tcall_async: any")?;
+	writeln!(w, "end")
+}
+
+struct Teal;
+
+impl Backend for Teal {
+	fn write_function(&self, func: &FuncData, options: &Options, w: &mut dyn Write) -> Result<()> {
+		Lua54.write_function(func, options, w)
+	}
+
+	fn write_function_marked(
+		&self,
+		func: &FuncData,
+		options: &Options,
+		w: &mut dyn Write,
+	) -> Result<()> {
+		Lua54.write_function_marked(func, options, w)
+	}
+
+	fn write_expression(&self, expr: &Expression, w: &mut dyn Write) -> Result<()> {
+		Lua54.write_expression(expr, w)
+	}
+
+	fn un_op_name(&self, op: UnOpType) -> (&'static str, &'static str) {
+		Lua54.un_op_name(op)
+	}
+
+	fn bin_op_name(&self, op: BinOpType) -> Option<(&'static str, &'static str)> {
+		Lua54.bin_op_name(op)
+	}
+
+	fn cmp_op_name(&self, op: CmpOpType) -> Option<(&'static str, &'static str)> {
+		Lua54.cmp_op_name(op)
+	}
+
+	fn value_name(&self, value: Value) -> Option<(&'static str, &'static str)> {
+		Lua54.value_name(value)
+	}
+
+	fn write_local_operation(&self, head: &str, tail: &str, w: &mut dyn Write) -> Result<()> {
+		Lua54.write_local_operation(head, tail, w)
+	}
+
+	fn write_memory_local(&self, index: usize, w: &mut dyn Write) -> Result<()> {
+		writeln!(w, "local memory_at_{index}: any")
+	}
+
+	fn write_jump_table_local(&self, w: &mut dyn Write) -> Result<()> {
+		write!(w, "local br_map: {{{{integer: integer}}}} = ")
+	}
+
+	fn write_chunk_start(&self, w: &mut dyn Write) -> Result<()> {
+		writeln!(w, "function(env: {{string: any}}): function()")
+	}
+
+	// The state that functions use is cast to maps of the fields they read,
+	// since its records are declared by the instance
+	fn write_env_local(&self, name: &str, w: &mut dyn Write) -> Result<()> {
+		let ty = match name {
+			"FUNC_LIST" => format!("{{integer: {FUNCTION_TYPE}}}"),
+			"DATA_LIST" => "{integer: string}".to_string(),
+			"FUEL" | "YIELD" | "DEPTH" => "{string: integer}".to_string(),
+			"use_fuel" | "use_yield" | "exhaust_stack" => "function()".to_string(),
+			"PROFILE" => format!("{{string: function(integer, {FUNCTION_TYPE}): {FUNCTION_TYPE}}}"),
+			"COVERAGE" => "{string: function(integer, integer): {integer: integer}}".to_string(),
+			_ => "{integer: any}".to_string(),
+		};
+
+		writeln!(w, "\tlocal {name} = env.{name} as {ty}")
+	}
+
+	fn write_env_start(&self, w: &mut dyn Write) -> Result<()> {
+		writeln!(w, "local env: {{string: any}} = {{")
+	}
+
+	fn write_fuel_state(&self, budget: u64, w: &mut dyn Write) -> Result<()> {
+		writeln!(w, "local record Fuel")?;
+		writeln!(w, "\tleft: integer")?;
+		writeln!(w, "\thandler: function(): integer")?;
+		writeln!(w, "end")?;
+		writeln!(w, "local FUEL: Fuel = {{ left = {budget} }}")
+	}
+
+	fn write_yield_state(&self, every: u32, w: &mut dyn Write) -> Result<()> {
+		writeln!(w, "local record Yield")?;
+		writeln!(w, "\tleft: integer")?;
+		writeln!(w, "\tevery: integer")?;
+		writeln!(w, "\thandler: function()")?;
+		writeln!(w, "end")?;
+		writeln!(
+			w,
+			"local YIELD: Yield = {{ left = {every}, every = {every} }}"
+		)
+	}
+
+	fn write_depth_state(&self, w: &mut dyn Write) -> Result<()> {
+		writeln!(w, "local record Depth")?;
+		writeln!(w, "\tnow: integer")?;
+		writeln!(w, "end")?;
+		writeln!(w, "local DEPTH: Depth = {{ now = 0 }}")?;
+		writeln!(
+			w,
+			"local function leave_depth(depth: integer, success: boolean, ...: any): any..."
+		)?;
+		writeln!(w, "\tif not success then")?;
+		writeln!(w, "\t\tDEPTH.now = depth")?;
+		writeln!(w, "\t\terror((...), 0)")?;
+		writeln!(w, "\tend")?;
+		writeln!(w, "\treturn ...")?;
+		writeln!(w, "end")?;
+		writeln!(
+			w,
+			"local function guard_depth(func: {FUNCTION_TYPE}): {FUNCTION_TYPE}"
+		)?;
+		writeln!(w, "\treturn function(...: any): any...")?;
+		writeln!(w, "\t\treturn leave_depth(DEPTH.now, pcall(func, ...))")?;
+		writeln!(w, "\tend")?;
+		writeln!(w, "end")
+	}
+
+	fn write_profile_state(&self, w: &mut dyn Write) -> Result<()> {
+		writeln!(w, "local record Entry")?;
+		writeln!(w, "\tindex: integer")?;
+		writeln!(w, "\tname: string")?;
+		writeln!(w, "\tcount: integer")?;
+		writeln!(w, "\ttime: number")?;
+		writeln!(w, "end")?;
+		writeln!(w, "local record Profile")?;
+		writeln!(w, "\tcount: {{integer: integer}}")?;
+		writeln!(w, "\ttime: {{integer: number}}")?;
+		writeln!(w, "\tname: {{integer: string}}")?;
+		writeln!(w, "\tinner: number")?;
+		writeln!(
+			w,
+			"\tleave: function(integer, number, number, ...: any): any..."
+		)?;
+		writeln!(
+			w,
+			"\twrap: function(integer, {FUNCTION_TYPE}): {FUNCTION_TYPE}"
+		)?;
+		writeln!(w, "\tdump: function(): {{Entry}}")?;
+		writeln!(w, "end")?;
+		writeln!(
+			w,
+			"local PROFILE: Profile = {{ count = {{}}, time = {{}}, name = {{}}, inner = 0 }}"
+		)?;
+		writeln!(
+			w,
+			"function PROFILE.leave(index: integer, start: number, outer: number, ...: any): any..."
+		)?;
+		writeln!(w, "\tlocal spent = os.clock() - start")?;
+		writeln!(
+			w,
+			"\tPROFILE.time[index] = PROFILE.time[index] + (spent - PROFILE.inner)"
+		)?;
+		writeln!(w, "\tPROFILE.inner = outer + spent")?;
+		writeln!(w, "\treturn ...")?;
+		writeln!(w, "end")?;
+		writeln!(
+			w,
+			"function PROFILE.wrap(index: integer, func: {FUNCTION_TYPE}): {FUNCTION_TYPE}"
+		)?;
+		writeln!(w, "\tPROFILE.count[index] = 0")?;
+		writeln!(w, "\tPROFILE.time[index] = 0")?;
+		writeln!(w, "\treturn function(...: any): any...")?;
+		writeln!(w, "\t\tlocal start, outer = os.clock(), PROFILE.inner")?;
+		writeln!(w, "\t\tPROFILE.inner = 0")?;
+		writeln!(w, "\t\tPROFILE.count[index] = PROFILE.count[index] + 1")?;
+		writeln!(
+			w,
+			"\t\treturn PROFILE.leave(index, start, outer, func(...))"
+		)?;
+		writeln!(w, "\tend")?;
+		writeln!(w, "end")?;
+		writeln!(w, "function PROFILE.dump(): {{Entry}}")?;
+		writeln!(w, "\tlocal list: {{Entry}} = {{}}")?;
+		writeln!(w, "\tfor index, count in pairs(PROFILE.count) do")?;
+		writeln!(w, "\t\tif count ~= 0 then")?;
+		writeln!(
+			w,
+			"\t\t\tlist[#list + 1] = {{ index = index, name = PROFILE.name[index], count = count, time = PROFILE.time[index] }}"
+		)?;
+		writeln!(w, "\t\tend")?;
+		writeln!(w, "\tend")?;
+		writeln!(
+			w,
+			"\ttable.sort(list, function(a: Entry, b: Entry): boolean return a.time > b.time end)"
+		)?;
+		writeln!(w, "\treturn list")?;
+		writeln!(w, "end")
+	}
+
+	fn write_coverage_state(&self, w: &mut dyn Write) -> Result<()> {
+		writeln!(w, "local record Hit")?;
+		writeln!(w, "\tfunc: integer")?;
+		writeln!(w, "\tblock: integer")?;
+		writeln!(w, "\tcount: integer")?;
+		writeln!(w, "end")?;
+		writeln!(w, "local record Coverage")?;
+		writeln!(w, "\thit: {{integer: {{integer: integer}}}}")?;
+		writeln!(w, "\tlen: {{integer: integer}}")?;
+		writeln!(w, "\tnew: function(integer, integer): {{integer: integer}}")?;
+		writeln!(w, "\tdump: function(): {{Hit}}")?;
+		writeln!(w, "end")?;
+		writeln!(w, "local COVERAGE: Coverage = {{ hit = {{}}, len = {{}} }}")?;
+		writeln!(
+			w,
+			"function COVERAGE.new(index: integer, len: integer): {{integer: integer}}"
+		)?;
+		writeln!(w, "\tlocal hit: {{integer: integer}} = {{}}")?;
+		writeln!(w, "\tfor block = 0, len - 1 do")?;
+		writeln!(w, "\t\thit[block] = 0")?;
+		writeln!(w, "\tend")?;
+		writeln!(w, "\tCOVERAGE.hit[index] = hit")?;
+		writeln!(w, "\tCOVERAGE.len[index] = len")?;
+		writeln!(w, "\treturn hit")?;
+		writeln!(w, "end")?;
+		writeln!(w, "function COVERAGE.dump(): {{Hit}}")?;
+		writeln!(w, "\tlocal list: {{Hit}} = {{}}")?;
+		writeln!(w, "\tfor index, hit in pairs(COVERAGE.hit) do")?;
+		writeln!(w, "\t\tfor block = 0, COVERAGE.len[index] - 1 do")?;
+		writeln!(
+			w,
+			"\t\t\tlist[#list + 1] = {{ func = index, block = block, count = hit[block] }}"
+		)?;
+		writeln!(w, "\t\tend")?;
+		writeln!(w, "\tend")?;
+		writeln!(w, "\ttable.sort(list, function(a: Hit, b: Hit): boolean")?;
+		writeln!(w, "\t\tif a.func ~= b.func then")?;
+		writeln!(w, "\t\t\treturn a.func < b.func")?;
+		writeln!(w, "\t\tend")?;
+		writeln!(w, "\t\treturn a.block < b.block")?;
+		writeln!(w, "\tend)")?;
+		writeln!(w, "\treturn list")?;
+		writeln!(w, "end")
+	}
+
+	fn write_async_state(&self, w: &mut dyn Write) -> Result<()> {
+		writeln!(w, "local record Task")?;
+		writeln!(w, "\tdone: boolean")?;
+		writeln!(w, "\tsuccess: boolean")?;
+		writeln!(w, "\tresults: {{any}}")?;
+		writeln!(w, "\tcount: integer")?;
+		writeln!(w, "\tcallback: function(boolean, ...: any)")?;
+		writeln!(w, "\ton_done: function(Task, function(boolean, ...: any))")?;
+		writeln!(w, "end")?;
+		writeln!(
+			w,
+			"local function call_async(instance: any, name: string, ...: any): Task"
+		)?;
+		writeln!(
+			w,
+			"\tlocal func = instance.func_list[name] as {FUNCTION_TYPE}"
+		)?;
+		writeln!(w, "\tif not func then")?;
+		writeln!(
+			w,
+			"\t\terror(\"no exported function named \" .. tostring(name), 2)"
+		)?;
+		writeln!(w, "\tend")?;
+		writeln!(w, "\tlocal thread = coroutine.create(func)")?;
+		writeln!(w, "\tlocal task: Task = {{ done = false }}")?;
+		writeln!(w, "\tlocal step: function(boolean, ...: any)")?;
+		writeln!(w, "\tstep = function(success: boolean, ...: any)")?;
+		writeln!(
+			w,
+			"\t\tif success and coroutine.status(thread) ~= \"dead\" then"
+		)?;
+		writeln!(w, "\t\t\tlocal start = ...")?;
+		writeln!(w, "\t\t\tif type(start) ~= \"function\" then")?;
+		writeln!(w, "\t\t\t\treturn step(coroutine.resume(thread))")?;
+		writeln!(w, "\t\t\tend")?;
+		writeln!(
+			w,
+			"\t\t\treturn (start as function(function(...: any)))(function(...: any)"
+		)?;
+		writeln!(w, "\t\t\t\tstep(coroutine.resume(thread, ...))")?;
+		writeln!(w, "\t\t\tend)")?;
+		writeln!(w, "\t\tend")?;
+		writeln!(w, "\t\ttask.done = true")?;
+		writeln!(w, "\t\ttask.success = success")?;
+		writeln!(w, "\t\ttask.results = {{ ... }}")?;
+		writeln!(w, "\t\ttask.count = select(\"#\", ...)")?;
+		writeln!(w, "\t\tif task.callback then")?;
+		writeln!(w, "\t\t\ttask.callback(success, ...)")?;
+		writeln!(w, "\t\tend")?;
+		writeln!(w, "\tend")?;
+		writeln!(
+			w,
+			"\ttask.on_done = function(self: Task, callback: function(boolean, ...: any))"
+		)?;
+		writeln!(w, "\t\tif self.done then")?;
+		writeln!(
+			w,
+			"\t\t\tcallback(self.success, table.unpack(self.results, 1, self.count))"
+		)?;
+		writeln!(w, "\t\telse")?;
+		writeln!(w, "\t\t\tself.callback = callback")?;
+		writeln!(w, "\t\tend")?;
+		writeln!(w, "\tend")?;
+		writeln!(w, "\tstep(coroutine.resume(thread, ...))")?;
+		writeln!(w, "\treturn task")?;
+		writeln!(w, "end")
+	}
+
+	fn write_named_array(&self, name: &str, len: usize, w: &mut dyn Write) -> Result<()> {
+		if len == 0 {
+			return Ok(());
+		}
+
+		let ty = match name {
+			"FUNC_LIST" => FUNCTION_TYPE,
+			"DATA_LIST" => "string",
+			"CHUNK_LIST" => "function({string: any}): function()",
+			"REFRESH_LIST" => "function()",
+			_ => "any",
+		};
+
+		writeln!(w, "local {name}: {{integer: {ty}}} = {{}}")
+	}
+
+	fn write_instance_start(&self, wasm: &Module, w: &mut dyn Write) -> Result<()> {
+		write_export_record(wasm, w)?;
+		writeln!(
+			w,
+			"return function(wasm: any, on_trap: any, previous: any): Exports"
+		)
+	}
+
+	fn write_instance_end(&self, w: &mut dyn Write) -> Result<()> {
+		writeln!(w, "\t}} as Exports")
+	}
+
+	fn has_native_i64(&self) -> bool {
+		Lua54.has_native_i64()
+	}
+}
+
+/// # Errors
+/// Returns `Err` if writing to `Write` failed.
+pub fn from_inst_list(code: &[Operator], type_info: &TypeInfo, w: &mut dyn Write) -> Result<()> {
+	codegen_core::from_inst_list(&Teal, code, type_info, w)
+}
+
+/// # Errors
+/// Returns `Err` if writing to `Write` failed.
+pub fn from_module_typed(wasm: &Module, type_info: &TypeInfo, w: &mut dyn Write) -> Result<()> {
+	codegen_core::from_module_typed(&Teal, wasm, type_info, w)
+}
+
+/// Translates the module, also embedding the contents of every custom section
+/// named in `name_list` as a string in the `custom_list` of the instance.
+///
+/// # Errors
+/// Returns `Err` if writing to `Write` failed.
+pub fn from_module_custom(
+	wasm: &Module,
+	type_info: &TypeInfo,
+	name_list: &[&str],
+	options: &Options,
+	w: &mut dyn Write,
+) -> Result<()> {
+	codegen_core::from_module_custom(&Teal, wasm, type_info, name_list, options, w)
+}
+
+/// Translates the module as in [`from_module_custom`], also returning the
+/// line that each function and statement starts at for a source map.
+///
+/// # Errors
+/// Returns `Err` if writing to `Write` failed.
+pub fn from_module_mapped(
+	wasm: &Module,
+	type_info: &TypeInfo,
+	name_list: &[&str],
+	options: &Options,
+	w: &mut dyn Write,
+) -> Result<Vec<Mapping>> {
+	codegen_core::from_module_mapped(&Teal, wasm, type_info, name_list, options, w)
+}
+
+/// # Errors
+/// Returns `Err` if writing to `Write` failed.
+pub fn from_module_untyped(wasm: &Module, w: &mut dyn Write) -> Result<()> {
+	codegen_core::from_module_untyped(&Teal, wasm, w)
+}
@@ -0,0 +1,46 @@
+use std::{
+	io::{Error, Result},
+	path::PathBuf,
+	process::Command,
+};
+
+static BENCHMARK: &str = include_str!("luau_i64.luau");
+
+// Outside of Roblox there is no `Vector3`, but the built in `vector` is the
+// same type under another name
+static VECTOR3: &str = "Vector3 = Vector3 or { new = vector.create }";
+
+fn executable() -> String {
+	std::env::var("LUAU_PATH").unwrap_or_else(|_| "luau".to_string())
+}
+
+fn run_with(name: &str, runtime: &str) -> Result<()> {
+	let temp = PathBuf::from(env!("CARGO_TARGET_TMPDIR"))
+		.join(name)
+		.with_extension("luau");
+
+	std::fs::write(
+		&temp,
+		format!("--!optimize 2\n{VECTOR3}\n{runtime}\n{BENCHMARK}"),
+	)?;
+
+	let result = Command::new(executable()).arg(&temp).output()?;
+
+	if !result.status.success() {
+		let data = String::from_utf8_lossy(&result.stderr);
+
+		return Err(Error::other(data.into_owned()));
+	}
+
+	println!("{name}");
+	print!("{}", String::from_utf8_lossy(&result.stdout));
+
+	Ok(())
+}
+
+// Compares the `i64` representations of the Luau runtime on the same
+// operations, which should also give the same results
+fn main() -> Result<()> {
+	run_with("vector", codegen_luau::RUNTIME)?;
+	run_with("pair", codegen_luau::RUNTIME_PAIR)
+}
@@ -3,6 +3,7 @@ use std::{
 	path::PathBuf,
 };
 
+use codegen_core::Options;
 use wasm_ast::module::{Module, TypeInfo};
 use wast::{
 	core::{WastArgCore, WastRetCore},
@@ -14,6 +15,7 @@ use target::{get_name_from_id, Target};
 mod target;
 
 static ASSERTION: &str = include_str!("luajit_assert.lua");
+static MANY_RUNTIME_NAMES: &str = include_str!("many_runtime_names.wast");
 
 struct LuaJIT;
 
@@ -142,11 +144,16 @@ impl Target for LuaJIT {
 		writeln!(w, "{ASSERTION}")
 	}
 
-	fn write_module(data: &Module, name: Option<&str>, w: &mut dyn Write) -> Result<()> {
+	fn write_module(
+		data: &Module,
+		name: Option<&str>,
+		options: &Options,
+		w: &mut dyn Write,
+	) -> Result<()> {
 		let type_info = TypeInfo::from_module(data);
 
 		writeln!(w, r#"loaded["temp"] = (function()"#)?;
-		codegen_luajit::from_module_typed(data, &type_info, w)?;
+		codegen_luajit::from_module_custom(data, &type_info, &[], options, w)?;
 		writeln!(w, "end)()(linked)")?;
 
 		if let Some(name) = name {
@@ -229,5 +236,16 @@ fn translate_file(path: PathBuf) {
 
 	let source = std::fs::read_to_string(path).unwrap();
 
-	LuaJIT::test(name, &source).unwrap();
+	for level in target::LEVEL_LIST {
+		LuaJIT::test(name, &source, level).unwrap();
+	}
+}
+
+// Functions capture the runtime names they use as upvalues, which LuaJIT
+// limits to 60 for any one function however many the module uses
+#[test]
+fn translate_many_runtime_names() {
+	for level in target::LEVEL_LIST {
+		LuaJIT::test("many_runtime_names.wast", MANY_RUNTIME_NAMES, level).unwrap();
+	}
 }
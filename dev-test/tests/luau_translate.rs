@@ -3,6 +3,7 @@ use std::{
 	path::PathBuf,
 };
 
+use codegen_core::Options;
 use wasm_ast::module::{Module, TypeInfo};
 use wast::{
 	core::{WastArgCore, WastRetCore},
@@ -153,11 +154,16 @@ impl Target for Luau {
 		writeln!(w, "{ASSERTION}")
 	}
 
-	fn write_module(data: &Module, name: Option<&str>, w: &mut dyn Write) -> Result<()> {
+	fn write_module(
+		data: &Module,
+		name: Option<&str>,
+		options: &Options,
+		w: &mut dyn Write,
+	) -> Result<()> {
 		let type_info = TypeInfo::from_module(data);
 
 		writeln!(w, r#"loaded["temp"] = (function()"#)?;
-		codegen_luau::from_module_typed(data, &type_info, w)?;
+		codegen_luau::from_module_custom(data, &type_info, &[], options, w)?;
 		writeln!(w, "end)()(linked)")?;
 
 		if let Some(name) = name {
@@ -240,5 +246,7 @@ fn translate_file(path: PathBuf) {
 
 	let source = std::fs::read_to_string(path).unwrap();
 
-	Luau::test(name, &source).unwrap();
+	for level in target::LEVEL_LIST {
+		Luau::test(name, &source, level).unwrap();
+	}
 }
@@ -0,0 +1,99 @@
+(module
+	(func (export "f0") (param i64 i64) (result i64) local.get 0 local.get 1 i64.add)
+	(func (export "f1") (param i64 i64) (result i64) local.get 0 local.get 1 i64.sub)
+	(func (export "f2") (param i64 i64) (result i64) local.get 0 local.get 1 i64.mul)
+	(func (export "f3") (param i64 i64) (result i64) local.get 0 local.get 1 i64.div_s)
+	(func (export "f4") (param i64 i64) (result i64) local.get 0 local.get 1 i64.div_u)
+	(func (export "f5") (param i64 i64) (result i64) local.get 0 local.get 1 i64.rem_s)
+	(func (export "f6") (param i64 i64) (result i64) local.get 0 local.get 1 i64.rem_u)
+	(func (export "f7") (param i64 i64) (result i64) local.get 0 local.get 1 i64.and)
+	(func (export "f8") (param i64 i64) (result i64) local.get 0 local.get 1 i64.or)
+	(func (export "f9") (param i64 i64) (result i64) local.get 0 local.get 1 i64.xor)
+	(func (export "f10") (param i64 i64) (result i64) local.get 0 local.get 1 i64.shl)
+	(func (export "f11") (param i64 i64) (result i64) local.get 0 local.get 1 i64.shr_s)
+	(func (export "f12") (param i64 i64) (result i64) local.get 0 local.get 1 i64.shr_u)
+	(func (export "f13") (param i64 i64) (result i64) local.get 0 local.get 1 i64.rotl)
+	(func (export "f14") (param i64 i64) (result i64) local.get 0 local.get 1 i64.rotr)
+	(func (export "f15") (param i64 i64) (result i32) local.get 0 local.get 1 i64.eq)
+	(func (export "f16") (param i64 i64) (result i32) local.get 0 local.get 1 i64.ne)
+	(func (export "f17") (param i64 i64) (result i32) local.get 0 local.get 1 i64.lt_s)
+	(func (export "f18") (param i64 i64) (result i32) local.get 0 local.get 1 i64.lt_u)
+	(func (export "f19") (param i64 i64) (result i32) local.get 0 local.get 1 i64.gt_s)
+	(func (export "f20") (param i64 i64) (result i32) local.get 0 local.get 1 i64.gt_u)
+	(func (export "f21") (param i64 i64) (result i32) local.get 0 local.get 1 i64.le_s)
+	(func (export "f22") (param i64 i64) (result i32) local.get 0 local.get 1 i64.le_u)
+	(func (export "f23") (param i64 i64) (result i32) local.get 0 local.get 1 i64.ge_s)
+	(func (export "f24") (param i64 i64) (result i32) local.get 0 local.get 1 i64.ge_u)
+	(func (export "f25") (param i64) (result i64) local.get 0 i64.clz)
+	(func (export "f26") (param i64) (result i64) local.get 0 i64.ctz)
+	(func (export "f27") (param i64) (result i64) local.get 0 i64.popcnt)
+	(func (export "f28") (param i64) (result i64) local.get 0 i64.extend8_s)
+	(func (export "f29") (param i64) (result i64) local.get 0 i64.extend16_s)
+	(func (export "f30") (param i64) (result i64) local.get 0 i64.extend32_s)
+	(func (export "f31") (param f32 f32) (result f32) local.get 0 local.get 1 f32.add)
+	(func (export "f32") (param f32 f32) (result f32) local.get 0 local.get 1 f32.sub)
+	(func (export "f33") (param f32 f32) (result f32) local.get 0 local.get 1 f32.mul)
+	(func (export "f34") (param f32 f32) (result f32) local.get 0 local.get 1 f32.div)
+	(func (export "f35") (param f32 f32) (result f32) local.get 0 local.get 1 f32.min)
+	(func (export "f36") (param f32 f32) (result f32) local.get 0 local.get 1 f32.max)
+	(func (export "f37") (param f32 f32) (result f32) local.get 0 local.get 1 f32.copysign)
+	(func (export "f38") (param f32) (result f32) local.get 0 f32.abs)
+	(func (export "f39") (param f32) (result f32) local.get 0 f32.neg)
+	(func (export "f40") (param f32) (result f32) local.get 0 f32.ceil)
+	(func (export "f41") (param f32) (result f32) local.get 0 f32.floor)
+	(func (export "f42") (param f32) (result f32) local.get 0 f32.trunc)
+	(func (export "f43") (param f32) (result f32) local.get 0 f32.nearest)
+	(func (export "f44") (param f32) (result f32) local.get 0 f32.sqrt)
+	(func (export "f45") (param f64 f64) (result f64) local.get 0 local.get 1 f64.add)
+	(func (export "f46") (param f64 f64) (result f64) local.get 0 local.get 1 f64.sub)
+	(func (export "f47") (param f64 f64) (result f64) local.get 0 local.get 1 f64.mul)
+	(func (export "f48") (param f64 f64) (result f64) local.get 0 local.get 1 f64.div)
+	(func (export "f49") (param f64 f64) (result f64) local.get 0 local.get 1 f64.min)
+	(func (export "f50") (param f64 f64) (result f64) local.get 0 local.get 1 f64.max)
+	(func (export "f51") (param f64 f64) (result f64) local.get 0 local.get 1 f64.copysign)
+	(func (export "f52") (param f64) (result f64) local.get 0 f64.abs)
+	(func (export "f53") (param f64) (result f64) local.get 0 f64.neg)
+	(func (export "f54") (param f64) (result f64) local.get 0 f64.ceil)
+	(func (export "f55") (param f64) (result f64) local.get 0 f64.floor)
+	(func (export "f56") (param f64) (result f64) local.get 0 f64.trunc)
+	(func (export "f57") (param f64) (result f64) local.get 0 f64.nearest)
+	(func (export "f58") (param f64) (result f64) local.get 0 f64.sqrt)
+	(func (export "f59") (param f32) (result i32) local.get 0 i32.trunc_f32_s)
+	(func (export "f60") (param f32) (result i32) local.get 0 i32.trunc_f32_u)
+	(func (export "f61") (param f64) (result i32) local.get 0 i32.trunc_f64_s)
+	(func (export "f62") (param f64) (result i32) local.get 0 i32.trunc_f64_u)
+	(func (export "f63") (param f32) (result i64) local.get 0 i64.trunc_f32_s)
+	(func (export "f64") (param f32) (result i64) local.get 0 i64.trunc_f32_u)
+	(func (export "f65") (param f64) (result i64) local.get 0 i64.trunc_f64_s)
+	(func (export "f66") (param f64) (result i64) local.get 0 i64.trunc_f64_u)
+	(func (export "f67") (param f64) (result i32) local.get 0 i32.trunc_sat_f64_s)
+	(func (export "f68") (param f64) (result i32) local.get 0 i32.trunc_sat_f64_u)
+	(func (export "f69") (param f64) (result i64) local.get 0 i64.trunc_sat_f64_s)
+	(func (export "f70") (param f64) (result i64) local.get 0 i64.trunc_sat_f64_u)
+	(func (export "f71") (param i32) (result i32) local.get 0 i32.clz)
+	(func (export "f72") (param i32) (result i32) local.get 0 i32.ctz)
+	(func (export "f73") (param i32) (result i32) local.get 0 i32.popcnt)
+	(func (export "f74") (param i32 i32) (result i32) local.get 0 local.get 1 i32.rotl)
+	(func (export "f75") (param i32 i32) (result i32) local.get 0 local.get 1 i32.rotr)
+	(func (export "f76") (param i32 i32) (result i32) local.get 0 local.get 1 i32.div_s)
+	(func (export "f77") (param i32 i32) (result i32) local.get 0 local.get 1 i32.div_u)
+	(func (export "f78") (param i32 i32) (result i32) local.get 0 local.get 1 i32.rem_s)
+	(func (export "f79") (param i32 i32) (result i32) local.get 0 local.get 1 i32.rem_u)
+	(func (export "f80") (param i32 i32) (result i32) local.get 0 local.get 1 i32.mul)
+	(func (export "f81") (param i32 i32) (result i32) local.get 0 local.get 1 i32.shr_u)
+	(func (export "f82") (param i32 i32) (result i32) local.get 0 local.get 1 i32.shr_s)
+	(func (export "f83") (param i32 i32) (result i32) local.get 0 local.get 1 i32.shl)
+	(func (export "ld") (param i32) (result i64) local.get 0 i64.load local.get 0 f64.load i64.trunc_sat_f64_s i64.add local.get 0 i32.load8_s i64.extend_i32_s i64.add local.get 0 i64.load32_u i64.add)
+	(memory 1)
+)
+
+(assert_return (invoke "f0" (i64.const 1) (i64.const 2)) (i64.const 3))
+(assert_return (invoke "f3" (i64.const -7) (i64.const 2)) (i64.const -3))
+(assert_return (invoke "f12" (i64.const -1) (i64.const 60)) (i64.const 15))
+(assert_return (invoke "f31" (f32.const 1.5) (f32.const 2.25)) (f32.const 3.75))
+(assert_return (invoke "f49" (f64.const 1) (f64.const 2)) (f64.const 1))
+(assert_return (invoke "f61" (f64.const -1.5)) (i32.const -1))
+(assert_return (invoke "f71" (i32.const 1)) (i32.const 31))
+(assert_return (invoke "f81" (i32.const -1) (i32.const 28)) (i32.const 15))
+(assert_return (invoke "f83" (i32.const 1) (i32.const 31)) (i32.const -2147483648))
+(assert_return (invoke "ld" (i32.const 0)) (i64.const 0))
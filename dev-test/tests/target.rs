@@ -5,7 +5,8 @@ use std::{
 	process::Command,
 };
 
-use wasm_ast::module::Module as AstModule;
+use codegen_core::Options;
+use wasm_ast::{module::Module as AstModule, optimize::Level};
 use wast::{
 	core::Module as WaModule, parser::ParseBuffer, token::Id, QuoteWat, Wast, WastDirective,
 	WastExecute, WastInvoke, WastRet, Wat,
@@ -57,6 +58,18 @@ pub fn get_name_from_id(id: Option<Id>) -> &str {
 	id.as_ref().map_or("temp", Id::name)
 }
 
+/// The levels every test file is translated and run at, as the `-O0`, `-O1`,
+/// and `-O2` of the command line.
+pub static LEVEL_LIST: [Level; 3] = [Level::Direct, Level::Cleanup, Level::Full];
+
+const fn get_level_name(level: Level) -> &'static str {
+	match level {
+		Level::Direct => "O0",
+		Level::Cleanup => "O1",
+		Level::Full => "O2",
+	}
+}
+
 #[allow(clippy::missing_errors_doc)]
 pub trait Target: Sized {
 	fn executable() -> String;
@@ -77,9 +90,14 @@ pub trait Target: Sized {
 
 	fn write_runtime(w: &mut dyn Write) -> Result<()>;
 
-	fn write_module(data: &AstModule, name: Option<&str>, w: &mut dyn Write) -> Result<()>;
+	fn write_module(
+		data: &AstModule,
+		name: Option<&str>,
+		options: &Options,
+		w: &mut dyn Write,
+	) -> Result<()>;
 
-	fn write_variant(variant: WastDirective, w: &mut dyn Write) -> Result<()> {
+	fn write_variant(variant: WastDirective, options: &Options, w: &mut dyn Write) -> Result<()> {
 		match variant {
 			WastDirective::Wat(data) => {
 				let mut ast = try_into_ast_module(data).expect("Must be a module");
@@ -88,7 +106,7 @@ pub trait Target: Sized {
 				let data = AstModule::try_from_data(&bytes).unwrap();
 				let name = ast.id.as_ref().map(Id::name);
 
-				Self::write_module(&data, name, w)?;
+				Self::write_module(&data, name, options, w)?;
 			}
 			WastDirective::Register { name, module, .. } => {
 				let pre = get_name_from_id(module);
@@ -127,7 +145,7 @@ pub trait Target: Sized {
 		}
 	}
 
-	fn run_generation(source: &str) -> Result<Vec<u8>> {
+	fn run_generation(source: &str, options: &Options) -> Result<Vec<u8>> {
 		let lexed = ParseBuffer::new(source).expect("Failed to tokenize");
 		let parsed: Wast = wast::parser::parse(&lexed).unwrap();
 
@@ -136,17 +154,23 @@ pub trait Target: Sized {
 		Self::write_runtime(&mut data)?;
 
 		for variant in parsed.directives {
-			Self::write_variant(variant, &mut data)?;
+			Self::write_variant(variant, options, &mut data)?;
 		}
 
 		Ok(data)
 	}
 
-	fn test(name: &str, source: &str) -> Result<()> {
-		let data = Self::run_generation(source)?;
+	fn test(name: &str, source: &str, level: Level) -> Result<()> {
+		let options = Options {
+			level,
+			..Options::default()
+		};
+
+		let data = Self::run_generation(source, &options)?;
+		let extension = format!("{}.wast.lua", get_level_name(level));
 		let temp = PathBuf::from(env!("CARGO_TARGET_TMPDIR"))
 			.join(name)
-			.with_extension("wast.lua");
+			.with_extension(extension);
 
 		std::fs::write(&temp, data)?;
 		Self::run_command(&temp)
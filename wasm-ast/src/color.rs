@@ -0,0 +1,338 @@
+use crate::{
+	node::{Align, Block, Expression, FuncData, LabelType, ResultList, Statement, Terminator},
+	visit::{get_child_list, get_expression_list},
+};
+
+// Temporaries that branches, calls, and handlers move as a range, along with
+// the results of the function, keep their place, while every other one lives
+// from its first to its last use with loops widening that to all of the loop
+struct Liveness {
+	position: usize,
+	interval_list: Vec<Option<(usize, usize)>>,
+	is_fixed_list: Vec<bool>,
+}
+
+impl Liveness {
+	fn new(num_stack: usize, num_result: usize) -> Self {
+		let mut is_fixed_list = vec![false; num_stack];
+
+		is_fixed_list[..num_result.min(num_stack)].fill(true);
+
+		Self {
+			position: 0,
+			interval_list: vec![None; num_stack],
+			is_fixed_list,
+		}
+	}
+
+	fn mention(&mut self, var: usize) {
+		let position = self.position;
+		let interval = self.interval_list[var].get_or_insert((position, position));
+
+		interval.1 = position;
+	}
+
+	fn fix_result_list(&mut self, list: ResultList) {
+		for var in list.iter() {
+			self.is_fixed_list[var.var] = true;
+		}
+	}
+
+	// Branches that carry nothing may sit above every temporary in use
+	fn fix_align(&mut self, align: Align) {
+		self.fix_result_list(align.new_range());
+		self.fix_result_list(align.old_range());
+	}
+
+	fn widen(&mut self, start: usize, end: usize) {
+		for (first, last) in self.interval_list.iter_mut().flatten() {
+			if *first <= end && *last >= start {
+				*first = (*first).min(start);
+				*last = (*last).max(end);
+			}
+		}
+	}
+
+	fn visit_expression(&mut self, expr: &mut Expression) {
+		if let Expression::GetTemporary(v) = expr {
+			self.mention(v.var);
+		}
+
+		for child in get_child_list(expr) {
+			self.visit_expression(child);
+		}
+	}
+
+	fn visit_statement(&mut self, stat: &mut Statement) {
+		self.position += 1;
+
+		let start = self.position;
+
+		for expr in get_expression_list(stat) {
+			self.visit_expression(expr);
+		}
+
+		match stat {
+			Statement::Block(v) => {
+				self.visit_block(v);
+
+				if v.label_type == Some(LabelType::Backward) {
+					self.widen(start, self.position);
+				}
+			}
+			Statement::If(v) => {
+				self.visit_block(&mut v.on_true);
+
+				if let Some(on_false) = &mut v.on_false {
+					self.visit_block(on_false);
+				}
+			}
+			Statement::Try(v) => {
+				self.visit_block(&mut v.code);
+
+				for catch in &mut v.catch_list {
+					self.fix_result_list(catch.result_list);
+					self.visit_block(&mut catch.code);
+				}
+			}
+			Statement::BrIf(v) => self.fix_align(v.target.align),
+			Statement::Call(v) => self.fix_result_list(v.result_list),
+			Statement::CallIndirect(v) => self.fix_result_list(v.result_list),
+			Statement::CallRef(v) => self.fix_result_list(v.result_list),
+			Statement::SetTemporary(v) => self.mention(v.var.var),
+			Statement::MemoryGrow(v) => self.mention(v.result.var),
+			Statement::TableGrow(v) => self.mention(v.result.var),
+			Statement::AtomicWait(v) => self.mention(v.result.var),
+			Statement::AtomicNotify(v) => self.mention(v.result.var),
+			_ => {}
+		}
+	}
+
+	fn visit_terminator(&mut self, last: &mut Terminator) {
+		self.position += 1;
+
+		match last {
+			Terminator::Br(v) => self.fix_align(v.align),
+			Terminator::BrTable(v) => {
+				self.visit_expression(&mut v.condition);
+
+				for target in v.data.iter().chain(std::iter::once(&v.default)) {
+					self.fix_align(target.align);
+				}
+			}
+			Terminator::Throw(v) => {
+				for param in &mut v.param_list {
+					self.visit_expression(param);
+				}
+			}
+			Terminator::Unreachable | Terminator::Rethrow(_) => {}
+		}
+	}
+
+	fn visit_block(&mut self, block: &mut Block) {
+		for stat in &mut block.code {
+			self.visit_statement(stat);
+		}
+
+		if let Some(last) = &mut block.last {
+			self.visit_terminator(last);
+		}
+	}
+
+	// Temporaries are taken in the order they start, each given the lowest
+	// place that is not fixed and not held by one alive at its first use
+	fn find_color_list(&self) -> Vec<usize> {
+		let mut color_list: Vec<usize> = (0..self.interval_list.len()).collect();
+		let mut order_list: Vec<_> = self
+			.interval_list
+			.iter()
+			.enumerate()
+			.filter(|&(var, _)| !self.is_fixed_list[var])
+			.filter_map(|(var, interval)| interval.map(|interval| (interval, var)))
+			.collect();
+
+		order_list.sort_unstable();
+
+		let mut held_list: Vec<Option<usize>> = vec![None; self.interval_list.len()];
+
+		for ((first, last), var) in order_list {
+			let color = (0..held_list.len())
+				.find(|&color| {
+					!self.is_fixed_list[color] && held_list[color].map_or(true, |end| end < first)
+				})
+				.unwrap();
+
+			held_list[color] = Some(last);
+			color_list[var] = color;
+		}
+
+		color_list
+	}
+}
+
+fn rename_expression(expr: &mut Expression, color_list: &[usize]) {
+	if let Expression::GetTemporary(v) = expr {
+		v.var = color_list[v.var];
+	}
+
+	for child in get_child_list(expr) {
+		rename_expression(child, color_list);
+	}
+}
+
+fn rename_statement(stat: &mut Statement, color_list: &[usize]) {
+	for expr in get_expression_list(stat) {
+		rename_expression(expr, color_list);
+	}
+
+	let var = match stat {
+		Statement::Block(v) => return rename_block(v, color_list),
+		Statement::If(v) => {
+			rename_block(&mut v.on_true, color_list);
+
+			if let Some(on_false) = &mut v.on_false {
+				rename_block(on_false, color_list);
+			}
+
+			return;
+		}
+		Statement::Try(v) => {
+			rename_block(&mut v.code, color_list);
+
+			for catch in &mut v.catch_list {
+				rename_block(&mut catch.code, color_list);
+			}
+
+			return;
+		}
+		Statement::SetTemporary(v) => &mut v.var.var,
+		Statement::MemoryGrow(v) => &mut v.result.var,
+		Statement::TableGrow(v) => &mut v.result.var,
+		Statement::AtomicWait(v) => &mut v.result.var,
+		Statement::AtomicNotify(v) => &mut v.result.var,
+		_ => return,
+	};
+
+	*var = color_list[*var];
+}
+
+fn rename_block(block: &mut Block, color_list: &[usize]) {
+	for stat in &mut block.code {
+		rename_statement(stat, color_list);
+	}
+
+	match block.last.as_deref_mut() {
+		Some(Terminator::BrTable(v)) => rename_expression(&mut v.condition, color_list),
+		Some(Terminator::Throw(v)) => {
+			for param in &mut v.param_list {
+				rename_expression(param, color_list);
+			}
+		}
+		_ => {}
+	}
+}
+
+/// Moves temporaries that are never alive at the same time into the same
+/// place, so that functions need fewer of them.
+pub fn recolor(func: &mut FuncData) {
+	let mut liveness = Liveness::new(func.num_stack, func.num_result);
+
+	liveness.visit_block(&mut func.code);
+
+	let color_list = liveness.find_color_list();
+	let is_used =
+		|var: &usize| liveness.interval_list[*var].is_some() || liveness.is_fixed_list[*var];
+	let num_stack = (0..func.num_stack)
+		.filter(is_used)
+		.map(|var| color_list[var] + 1)
+		.max()
+		.unwrap_or(0);
+
+	rename_block(&mut func.code, &color_list);
+
+	func.num_stack = num_stack;
+}
+
+#[cfg(test)]
+mod test {
+	use crate::node::{
+		BinOp, BinOpType, Block, Expression, FuncData, Local, SetLocal, SetTemporary, Statement,
+		Temporary, Value,
+	};
+
+	fn get_temporary(var: usize) -> Box<Expression> {
+		Expression::GetTemporary(Temporary { var }).into()
+	}
+
+	fn set_temporary(var: usize, value: i32) -> Statement {
+		Statement::SetTemporary(SetTemporary {
+			var: Temporary { var },
+			value: Expression::Value(Value::I32(value)).into(),
+		})
+	}
+
+	fn set_local(value: Box<Expression>) -> Statement {
+		Statement::SetLocal(SetLocal {
+			var: Local { var: 0 },
+			value,
+		})
+	}
+
+	fn recolor(code: Vec<Statement>) -> FuncData {
+		let mut func = FuncData {
+			local_data: Vec::new(),
+			param_data: Vec::new(),
+			num_result: 0,
+			num_stack: 2,
+			code: Block {
+				label_type: None,
+				offset_list: vec![0; code.len()],
+				code,
+				last: None,
+			},
+		};
+
+		super::recolor(&mut func);
+
+		func
+	}
+
+	#[test]
+	fn share_place_after_last_use() {
+		let func = recolor(vec![
+			set_temporary(0, 1),
+			set_local(get_temporary(0)),
+			set_temporary(1, 2),
+			set_local(get_temporary(1)),
+		]);
+
+		assert_eq!(func.num_stack, 1);
+		assert!(matches!(
+			func.code.code.as_slice(),
+			[_, _, Statement::SetTemporary(v), Statement::SetLocal(w)]
+				if v.var.var == 0 && matches!(*w.value, Expression::GetTemporary(Temporary { var: 0 }))
+		));
+	}
+
+	#[test]
+	fn keep_places_alive_at_once() {
+		let sum = Expression::BinOp(BinOp {
+			op_type: BinOpType::Add_I32,
+			lhs: get_temporary(0),
+			rhs: get_temporary(1),
+		});
+
+		let func = recolor(vec![
+			set_temporary(0, 1),
+			set_temporary(1, 2),
+			set_local(sum.into()),
+		]);
+
+		assert_eq!(func.num_stack, 2);
+		assert!(matches!(
+			func.code.code.as_slice(),
+			[Statement::SetTemporary(a), Statement::SetTemporary(b), _]
+				if a.var.var == 0 && b.var.var == 1
+		));
+	}
+}
@@ -0,0 +1,289 @@
+use crate::{
+	node::{Block, Expression, FuncData, Local, ResultList, Statement, Temporary, Terminator},
+	visit::{get_block_list, get_child_list, get_expression_list, Driver, Visitor},
+};
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Var {
+	Local(usize),
+	Temporary(usize),
+}
+
+impl Var {
+	const fn from_expression(expr: &Expression) -> Option<Self> {
+		match expr {
+			Expression::GetLocal(v) => Some(Self::Local(v.var)),
+			Expression::GetTemporary(v) => Some(Self::Temporary(v.var)),
+			_ => None,
+		}
+	}
+
+	const fn into_expression(self) -> Expression {
+		match self {
+			Self::Local(var) => Expression::GetLocal(Local { var }),
+			Self::Temporary(var) => Expression::GetTemporary(Temporary { var }),
+		}
+	}
+}
+
+// Each copy is a variable that holds the same value as the one it was set
+// from, for as long as neither of them is set again
+#[derive(Default)]
+struct CopyList {
+	list: Vec<(Var, Var)>,
+}
+
+impl CopyList {
+	fn find(&self, var: Var) -> Option<Var> {
+		self.list
+			.iter()
+			.find_map(|&(copy, source)| (copy == var).then_some(source))
+	}
+
+	fn kill(&mut self, var: Var) {
+		self.list
+			.retain(|&(copy, source)| copy != var && source != var);
+	}
+
+	fn kill_result_list(&mut self, list: ResultList) {
+		for var in list.iter() {
+			self.kill(Var::Temporary(var.var));
+		}
+	}
+
+	fn replace(&self, expr: &mut Expression) {
+		if let Some(source) = Var::from_expression(expr).and_then(|var| self.find(var)) {
+			*expr = source.into_expression();
+
+			return;
+		}
+
+		for child in get_child_list(expr) {
+			self.replace(child);
+		}
+	}
+
+	// Setting a variable to one it already holds the value of is dropped, and
+	// otherwise what it is set to replaces what it held before
+	fn is_set_redundant(&mut self, var: Var, value: &Expression) -> bool {
+		let source = Var::from_expression(value);
+
+		if source == Some(var) || (source.is_some() && self.find(var) == source) {
+			return true;
+		}
+
+		self.kill(var);
+
+		if let Some(source) = source {
+			self.list.push((var, source));
+		}
+
+		false
+	}
+}
+
+pub(crate) fn get_result_list(stat: &Statement) -> Option<ResultList> {
+	let single = |var: Temporary| ResultList::new(var.var, var.var + 1);
+
+	match stat {
+		Statement::Call(v) => Some(v.result_list),
+		Statement::CallIndirect(v) => Some(v.result_list),
+		Statement::CallRef(v) => Some(v.result_list),
+		Statement::MemoryGrow(v) => Some(single(v.result)),
+		Statement::TableGrow(v) => Some(single(v.result)),
+		Statement::AtomicWait(v) => Some(single(v.result)),
+		Statement::AtomicNotify(v) => Some(single(v.result)),
+		_ => None,
+	}
+}
+
+// Copies are only followed within the code of one block, so nested code starts
+// without any and nothing is known after it, as it may be entered or left by
+// branches from elsewhere
+fn propagate_block(block: &mut Block) {
+	let code = std::mem::take(&mut block.code);
+	let offset_list = std::mem::take(&mut block.offset_list);
+	let mut copy_list = CopyList::default();
+
+	for (mut stat, offset) in code.into_iter().zip(offset_list) {
+		for expr in get_expression_list(&mut stat) {
+			copy_list.replace(expr);
+		}
+
+		for nested in get_block_list(&mut stat) {
+			propagate_block(nested);
+		}
+
+		let is_redundant = match &stat {
+			Statement::SetLocal(v) => copy_list.is_set_redundant(Var::Local(v.var.var), &v.value),
+			Statement::SetTemporary(v) => {
+				copy_list.is_set_redundant(Var::Temporary(v.var.var), &v.value)
+			}
+			Statement::Block(_) | Statement::If(_) | Statement::Try(_) => {
+				copy_list.list.clear();
+
+				false
+			}
+			stat => {
+				if let Some(list) = get_result_list(stat) {
+					copy_list.kill_result_list(list);
+				}
+
+				false
+			}
+		};
+
+		if !is_redundant {
+			block.code.push(stat);
+			block.offset_list.push(offset);
+		}
+	}
+
+	match block.last.as_deref_mut() {
+		Some(Terminator::BrTable(v)) => copy_list.replace(&mut v.condition),
+		Some(Terminator::Throw(v)) => {
+			for param in &mut v.param_list {
+				copy_list.replace(param);
+			}
+		}
+		_ => {}
+	}
+}
+
+struct ReadList {
+	is_read_list: Vec<bool>,
+}
+
+impl Visitor for ReadList {
+	fn visit_get_local(&mut self, local: Local) {
+		self.is_read_list[local.var] = true;
+	}
+}
+
+// The code of the function itself runs at most once and in order, so a copy
+// there into a local that nothing after it reads is never used
+fn remove_unread_copy(func: &mut FuncData) {
+	let num_local = func.param_data.len() + func.local_data.len();
+	let mut read_list = ReadList {
+		is_read_list: vec![false; num_local],
+	};
+
+	if let Some(last) = func.code.last() {
+		last.accept(&mut read_list);
+	}
+
+	let mut is_kept_list = vec![true; func.code.code.len()];
+
+	for (stat, is_kept) in func.code.code.iter().zip(&mut is_kept_list).rev() {
+		if let Statement::SetLocal(v) = stat {
+			if Var::from_expression(&v.value).is_some() && !read_list.is_read_list[v.var.var] {
+				*is_kept = false;
+
+				continue;
+			}
+		}
+
+		stat.accept(&mut read_list);
+	}
+
+	let code = std::mem::take(&mut func.code.code);
+	let offset_list = std::mem::take(&mut func.code.offset_list);
+
+	for ((stat, offset), is_kept) in code.into_iter().zip(offset_list).zip(is_kept_list) {
+		if is_kept {
+			func.code.code.push(stat);
+			func.code.offset_list.push(offset);
+		}
+	}
+}
+
+/// Reads the variable that a local or temporary was copied from in its place
+/// while both still hold the same value, dropping the copies that then set a
+/// variable to the value it already holds and those into locals that are
+/// not read again.
+pub fn propagate(func: &mut FuncData) {
+	propagate_block(&mut func.code);
+	remove_unread_copy(func);
+}
+
+#[cfg(test)]
+mod test {
+	use crate::node::{
+		BinOp, BinOpType, Block, Expression, FuncData, Local, SetLocal, Statement, Value,
+	};
+
+	fn get_local(var: usize) -> Box<Expression> {
+		Expression::GetLocal(Local { var }).into()
+	}
+
+	fn set_local(var: usize, value: Box<Expression>) -> Statement {
+		Statement::SetLocal(SetLocal {
+			var: Local { var },
+			value,
+		})
+	}
+
+	fn propagate(code: Vec<Statement>) -> FuncData {
+		let mut func = FuncData {
+			local_data: Vec::new(),
+			param_data: vec![wasmparser::ValType::I32; 3],
+			num_result: 0,
+			num_stack: 0,
+			code: Block {
+				label_type: None,
+				offset_list: vec![0; code.len()],
+				code,
+				last: None,
+			},
+		};
+
+		super::propagate(&mut func);
+
+		func
+	}
+
+	fn add_one(var: usize) -> Box<Expression> {
+		Expression::BinOp(BinOp {
+			op_type: BinOpType::Add_I32,
+			lhs: get_local(var),
+			rhs: Expression::Value(Value::I32(1)).into(),
+		})
+		.into()
+	}
+
+	#[test]
+	fn read_source_of_copy() {
+		let func = propagate(vec![set_local(1, get_local(0)), set_local(2, add_one(1))]);
+
+		// The copy is no longer read, so it is dropped too
+		let [Statement::SetLocal(v)] = func.code.code.as_slice() else {
+			panic!("only the sum should be left");
+		};
+
+		assert_eq!(v.var.var, 2);
+		assert!(matches!(
+			v.value.as_ref(),
+			Expression::BinOp(v) if matches!(*v.lhs, Expression::GetLocal(Local { var: 0 }))
+		));
+	}
+
+	#[test]
+	fn keep_copy_of_changed_source() {
+		let func = propagate(vec![
+			set_local(1, get_local(0)),
+			set_local(0, Expression::Value(Value::I32(5)).into()),
+			set_local(2, add_one(1)),
+		]);
+
+		let [Statement::SetLocal(_), Statement::SetLocal(_), Statement::SetLocal(v)] =
+			func.code.code.as_slice()
+		else {
+			panic!("every statement should be kept");
+		};
+
+		assert!(matches!(
+			v.value.as_ref(),
+			Expression::BinOp(v) if matches!(*v.lhs, Expression::GetLocal(Local { var: 1 }))
+		));
+	}
+}
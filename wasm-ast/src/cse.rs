@@ -0,0 +1,324 @@
+use crate::{
+	node::{Block, Expression, FuncData, LoadType, SetTemporary, Statement, Temporary, Value},
+	visit::{get_child_list, get_expression_list},
+};
+
+// Pointers are only compared when they are a plain read or a constant
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Operand {
+	Local(usize),
+	Temporary(usize),
+	I32(i32),
+	I64(i64),
+}
+
+impl Operand {
+	const fn from_expression(expr: &Expression) -> Option<Self> {
+		let operand = match expr {
+			Expression::GetLocal(v) => Self::Local(v.var),
+			Expression::GetTemporary(v) => Self::Temporary(v.var),
+			Expression::Value(Value::I32(v)) => Self::I32(*v),
+			Expression::Value(Value::I64(v)) => Self::I64(*v),
+			_ => return None,
+		};
+
+		Some(operand)
+	}
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Read {
+	Global(usize),
+	Load {
+		load_type: LoadType,
+		memory: usize,
+		offset: u64,
+		pointer: Operand,
+	},
+}
+
+impl Read {
+	fn from_expression(expr: &Expression) -> Option<Self> {
+		match expr {
+			Expression::GetGlobal(v) => Some(Self::Global(v.var)),
+			Expression::LoadAt(v) => {
+				Operand::from_expression(&v.pointer).map(|pointer| Self::Load {
+					load_type: v.load_type,
+					memory: v.memory,
+					offset: v.offset,
+					pointer,
+				})
+			}
+			_ => None,
+		}
+	}
+
+	fn is_changed_by(self, change: Change) -> bool {
+		match (self, change) {
+			(_, Change::All) => true,
+			(Self::Global(var), Change::Global(other)) => var == other,
+			(Self::Load { memory, .. }, Change::Memory(other)) => memory == other,
+			(Self::Load { pointer, .. }, Change::Local(var)) => pointer == Operand::Local(var),
+			(Self::Load { pointer, .. }, Change::Temporary(var)) => {
+				pointer == Operand::Temporary(var)
+			}
+			_ => false,
+		}
+	}
+}
+
+// What a statement changes once its expressions have been evaluated, where
+// anything that calls out, runs nested code, or writes a result changes all
+#[derive(Clone, Copy)]
+enum Change {
+	None,
+	Local(usize),
+	Temporary(usize),
+	Global(usize),
+	Memory(usize),
+	All,
+}
+
+impl From<&Statement> for Change {
+	fn from(stat: &Statement) -> Self {
+		match stat {
+			Statement::BrIf(_)
+			| Statement::DataDrop(_)
+			| Statement::TableInit(_)
+			| Statement::ElemDrop(_)
+			| Statement::TableSet(_)
+			| Statement::TableCopy(_)
+			| Statement::TableFill(_) => Self::None,
+			Statement::SetLocal(v) => Self::Local(v.var.var),
+			Statement::SetTemporary(v) => Self::Temporary(v.var.var),
+			Statement::SetGlobal(v) => Self::Global(v.var),
+			Statement::StoreAt(v) => Self::Memory(v.memory),
+			Statement::MemoryCopy(v) => Self::Memory(v.destination.memory),
+			Statement::MemoryFill(v) => Self::Memory(v.destination.memory),
+			Statement::MemoryInit(v) => Self::Memory(v.destination.memory),
+			_ => Self::All,
+		}
+	}
+}
+
+fn for_each_read<F>(expr: &mut Expression, func: &mut F)
+where
+	F: FnMut(Read, &mut Expression),
+{
+	if let Some(read) = Read::from_expression(expr) {
+		return func(read, expr);
+	}
+
+	for child in get_child_list(expr) {
+		for_each_read(child, func);
+	}
+}
+
+struct Group {
+	read: Read,
+	count: usize,
+}
+
+// Reads are grouped with the earlier ones that nothing has changed since, and
+// every read is given the index of its group in the order they are made
+fn find_group_list(code: &mut [Statement]) -> (Vec<Group>, Vec<usize>) {
+	let mut group_list: Vec<Group> = Vec::new();
+	let mut read_list = Vec::new();
+	let mut live_list: Vec<usize> = Vec::new();
+
+	for stat in code {
+		for expr in get_expression_list(stat) {
+			for_each_read(expr, &mut |read, _| {
+				let index = live_list
+					.iter()
+					.copied()
+					.find(|&index| group_list[index].read == read)
+					.unwrap_or_else(|| {
+						group_list.push(Group { read, count: 0 });
+						live_list.push(group_list.len() - 1);
+
+						group_list.len() - 1
+					});
+
+				group_list[index].count += 1;
+				read_list.push(index);
+			});
+		}
+
+		let change = Change::from(&*stat);
+
+		live_list.retain(|&index| !group_list[index].read.is_changed_by(change));
+	}
+
+	(group_list, read_list)
+}
+
+fn eliminate_nested(stat: &mut Statement, num_stack: &mut usize) {
+	match stat {
+		Statement::Block(v) => eliminate_block(v, num_stack),
+		Statement::If(v) => {
+			eliminate_block(&mut v.on_true, num_stack);
+
+			if let Some(on_false) = &mut v.on_false {
+				eliminate_block(on_false, num_stack);
+			}
+		}
+		Statement::Try(v) => {
+			eliminate_block(&mut v.code, num_stack);
+
+			for catch in &mut v.catch_list {
+				eliminate_block(&mut catch.code, num_stack);
+			}
+		}
+		_ => {}
+	}
+}
+
+// The first read of a group made more than once is set to a new temporary
+// just before its statement, which every read of the group then uses
+fn eliminate_block(block: &mut Block, num_stack: &mut usize) {
+	for stat in &mut block.code {
+		eliminate_nested(stat, num_stack);
+	}
+
+	let (group_list, read_list) = find_group_list(&mut block.code);
+	let mut temporary_list: Vec<_> = group_list
+		.iter()
+		.map(|group| {
+			(group.count > 1).then(|| {
+				*num_stack += 1;
+
+				(
+					Temporary {
+						var: *num_stack - 1,
+					},
+					false,
+				)
+			})
+		})
+		.collect();
+
+	if temporary_list.iter().all(Option::is_none) {
+		return;
+	}
+
+	let code = std::mem::take(&mut block.code);
+	let offset_list = std::mem::take(&mut block.offset_list);
+	let mut read_list = read_list.into_iter();
+
+	for (mut stat, offset) in code.into_iter().zip(offset_list) {
+		for expr in get_expression_list(&mut stat) {
+			for_each_read(expr, &mut |_, expr| {
+				let index = read_list.next().unwrap();
+				let Some((var, is_set)) = &mut temporary_list[index] else {
+					return;
+				};
+
+				let value = std::mem::replace(expr, Expression::GetTemporary(*var));
+
+				if !*is_set {
+					*is_set = true;
+
+					block.code.push(Statement::SetTemporary(SetTemporary {
+						var: *var,
+						value: value.into(),
+					}));
+					block.offset_list.push(offset);
+				}
+			});
+		}
+
+		block.code.push(stat);
+		block.offset_list.push(offset);
+	}
+}
+
+/// Reads the same global or memory only once where nothing can change it
+/// between reads, keeping the value in a new temporary.
+pub fn eliminate(func: &mut FuncData) {
+	eliminate_block(&mut func.code, &mut func.num_stack);
+}
+
+#[cfg(test)]
+mod test {
+	use crate::node::{
+		Block, Expression, FuncData, GetGlobal, Local, SetGlobal, SetLocal, Statement, Temporary,
+		Value,
+	};
+
+	fn set_local(var: usize, value: Expression) -> Statement {
+		Statement::SetLocal(SetLocal {
+			var: Local { var },
+			value: value.into(),
+		})
+	}
+
+	fn get_global() -> Expression {
+		Expression::GetGlobal(GetGlobal { var: 0 })
+	}
+
+	fn eliminate(code: Vec<Statement>) -> FuncData {
+		let mut func = FuncData {
+			local_data: Vec::new(),
+			param_data: Vec::new(),
+			num_result: 0,
+			num_stack: 0,
+			code: Block {
+				label_type: None,
+				offset_list: vec![0; code.len()],
+				code,
+				last: None,
+			},
+		};
+
+		super::eliminate(&mut func);
+
+		func
+	}
+
+	#[test]
+	fn read_global_once() {
+		let func = eliminate(vec![set_local(0, get_global()), set_local(1, get_global())]);
+
+		assert_eq!(func.num_stack, 1);
+
+		let [Statement::SetTemporary(read), Statement::SetLocal(first), Statement::SetLocal(second)] =
+			func.code.code.as_slice()
+		else {
+			panic!("the global should be read into a temporary before its first use");
+		};
+
+		assert_eq!(read.var.var, 0);
+		assert!(matches!(*read.value, Expression::GetGlobal(_)));
+		assert!(matches!(
+			*first.value,
+			Expression::GetTemporary(Temporary { var: 0 })
+		));
+		assert!(matches!(
+			*second.value,
+			Expression::GetTemporary(Temporary { var: 0 })
+		));
+	}
+
+	#[test]
+	fn read_global_again_after_set() {
+		let set_global = Statement::SetGlobal(SetGlobal {
+			var: 0,
+			value: Expression::Value(Value::I32(1)).into(),
+		});
+
+		let func = eliminate(vec![
+			set_local(0, get_global()),
+			set_global,
+			set_local(1, get_global()),
+		]);
+
+		assert_eq!(func.num_stack, 0);
+		assert!(matches!(
+			func.code.code.as_slice(),
+			[Statement::SetLocal(a), Statement::SetGlobal(_), Statement::SetLocal(b)]
+				if matches!(*a.value, Expression::GetGlobal(_))
+					&& matches!(*b.value, Expression::GetGlobal(_))
+		));
+	}
+}
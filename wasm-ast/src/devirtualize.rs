@@ -0,0 +1,266 @@
+use wasmparser::{ConstExpr, ElementItems, ElementKind, ExternalKind, Operator, Type, TypeRef};
+
+use crate::{
+	module::{External, Module},
+	node::{
+		Block, Call, CallIndirect, Expression, FuncData, Statement, TableCopy, TableFill,
+		TableInit, TableSet, Value,
+	},
+	visit::{get_block_list, Driver, Visitor},
+};
+
+struct SetTableList<'a> {
+	content_list: &'a mut [Option<Vec<Option<usize>>>],
+}
+
+impl Visitor for SetTableList<'_> {
+	fn visit_table_init(&mut self, table_init: &TableInit) {
+		self.content_list[table_init.table] = None;
+	}
+
+	fn visit_table_set(&mut self, table_set: &TableSet) {
+		self.content_list[table_set.table] = None;
+	}
+
+	fn visit_table_copy(&mut self, table_copy: &TableCopy) {
+		self.content_list[table_copy.destination.table] = None;
+	}
+
+	fn visit_table_fill(&mut self, table_fill: &TableFill) {
+		self.content_list[table_fill.destination.table] = None;
+	}
+}
+
+fn get_offset(init: &ConstExpr) -> Option<usize> {
+	let mut reader = init.get_operators_reader();
+
+	let Ok(Operator::I32Const { value }) = reader.read() else {
+		return None;
+	};
+
+	if !matches!(reader.read(), Ok(Operator::End)) {
+		return None;
+	}
+
+	usize::try_from(value).ok()
+}
+
+fn get_function(init: &ConstExpr) -> Option<usize> {
+	match init.get_operators_reader().read() {
+		Ok(Operator::RefFunc { function_index }) => usize::try_from(function_index).ok(),
+		_ => None,
+	}
+}
+
+fn get_item_list(items: &ElementItems) -> Vec<Option<usize>> {
+	match items.clone() {
+		ElementItems::Functions(list) => list
+			.into_iter()
+			.map(|v| usize::try_from(v.unwrap()).ok())
+			.collect(),
+		ElementItems::Expressions(list) => list
+			.into_iter()
+			.map(|v| get_function(&v.unwrap()))
+			.collect(),
+	}
+}
+
+// Tables that are neither imported nor exported keep the functions that
+// active segments place at constant offsets, as long as no code sets, fills,
+// copies into, or initializes them; growing only adds entries past the end
+fn find_content_list(wasm: &Module, func_list: &[FuncData]) -> Vec<Option<Vec<Option<usize>>>> {
+	let offset = wasm.import_count(External::Table);
+	let mut content_list = vec![None; offset];
+
+	content_list.resize(wasm.table_space(), Some(Vec::new()));
+
+	for export in wasm.export_section() {
+		if export.kind == ExternalKind::Table {
+			content_list[usize::try_from(export.index).unwrap()] = None;
+		}
+	}
+
+	for element in wasm.element_section() {
+		let ElementKind::Active {
+			table_index,
+			offset_expr,
+		} = &element.kind
+		else {
+			continue;
+		};
+
+		let index = usize::try_from(table_index.unwrap_or_default()).unwrap();
+		let Some(content) = &mut content_list[index] else {
+			continue;
+		};
+
+		let Some(start) = get_offset(offset_expr) else {
+			content_list[index] = None;
+
+			continue;
+		};
+
+		let item_list = get_item_list(&element.items);
+		let end = start + item_list.len();
+
+		if content.len() < end {
+			content.resize(end, None);
+		}
+
+		content[start..end].copy_from_slice(&item_list);
+	}
+
+	let mut visitor = SetTableList {
+		content_list: &mut content_list,
+	};
+
+	for func in func_list {
+		func.accept(&mut visitor);
+	}
+
+	content_list
+}
+
+fn find_type_list(wasm: &Module) -> Vec<usize> {
+	let import_list = wasm.import_section().iter().filter_map(|v| match v.ty {
+		TypeRef::Func(ty) => Some(ty),
+		_ => None,
+	});
+
+	import_list
+		.chain(wasm.func_section().iter().copied())
+		.map(|v| usize::try_from(v).unwrap())
+		.collect()
+}
+
+struct Devirtualizer<'a> {
+	content_list: &'a [Option<Vec<Option<usize>>>],
+	type_list: &'a [usize],
+	type_section: &'a [Type],
+}
+
+impl Devirtualizer<'_> {
+	// Calls through an entry holding a function of another type trap, so
+	// they are left as they are
+	fn find_function(&self, call_indirect: &CallIndirect) -> Option<usize> {
+		let Expression::Value(Value::I32(index)) = call_indirect.index.as_ref() else {
+			return None;
+		};
+
+		let content = self.content_list[call_indirect.table].as_ref()?;
+		let function = (*content.get(usize::try_from(*index).ok()?)?)?;
+
+		let expected = &self.type_section[call_indirect.ty];
+		let found = &self.type_section[self.type_list[function]];
+
+		match (expected, found) {
+			(Type::Func(expected), Type::Func(found)) => (expected == found).then_some(function),
+			_ => None,
+		}
+	}
+
+	fn devirtualize_block(&self, block: &mut Block) {
+		for stat in &mut block.code {
+			if let Statement::CallIndirect(v) = stat {
+				if let Some(function) = self.find_function(v) {
+					let data = Call {
+						function,
+						param_list: std::mem::take(&mut v.param_list),
+						result_list: v.result_list,
+					};
+
+					*stat = Statement::Call(data);
+				}
+			}
+
+			for nested in get_block_list(stat) {
+				self.devirtualize_block(nested);
+			}
+		}
+	}
+}
+
+/// Replaces every `call_indirect` on a constant index into a table that
+/// never changes after the module starts with a direct call to the function
+/// that its segments place there.
+pub fn devirtualize(wasm: &Module, func_list: &mut [FuncData]) {
+	let content_list = find_content_list(wasm, func_list);
+
+	if content_list.iter().all(Option::is_none) {
+		return;
+	}
+
+	let type_list = find_type_list(wasm);
+	let devirtualizer = Devirtualizer {
+		content_list: &content_list,
+		type_list: &type_list,
+		type_section: wasm.type_section(),
+	};
+
+	for func in func_list {
+		devirtualizer.devirtualize_block(&mut func.code);
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use crate::{
+		factory::Factory,
+		module::{External, Module, TypeInfo},
+		node::{FuncData, Statement},
+	};
+
+	// Returns the code of the last function after the pass
+	fn devirtualize(text: &str) -> Vec<Statement> {
+		let data = wat::parse_str(text).unwrap();
+		let wasm = Module::try_from_data(&data).unwrap();
+		let type_info = TypeInfo::from_module(&wasm);
+		let offset = wasm.import_count(External::Func);
+		let mut factory = Factory::from_type_info(&type_info);
+		let mut func_list: Vec<FuncData> = wasm
+			.code_section()
+			.iter()
+			.enumerate()
+			.map(|(i, v)| factory.create_indexed(i + offset, v).unwrap())
+			.collect();
+
+		super::devirtualize(&wasm, &mut func_list);
+
+		func_list.pop().unwrap().code.code
+	}
+
+	#[test]
+	fn call_constant_entry_directly() {
+		let code = devirtualize(
+			"(module
+				(type $t (func (result i32)))
+				(table 2 funcref)
+				(elem (i32.const 0) $a $b)
+				(func $a (result i32) i32.const 1)
+				(func $b (result i32) i32.const 2)
+				(func (result i32) i32.const 1 call_indirect (type $t))
+			)",
+		);
+
+		assert!(matches!(
+			code.as_slice(),
+			[Statement::Call(v)] if v.function == 1
+		));
+	}
+
+	#[test]
+	fn keep_call_into_exported_table() {
+		let code = devirtualize(
+			"(module
+				(type $t (func (result i32)))
+				(table (export \"table\") 2 funcref)
+				(elem (i32.const 0) $a $b)
+				(func $a (result i32) i32.const 1)
+				(func $b (result i32) i32.const 2)
+				(func (result i32) i32.const 1 call_indirect (type $t))
+			)",
+		);
+
+		assert!(matches!(code.as_slice(), [Statement::CallIndirect(_)]));
+	}
+}
@@ -0,0 +1,453 @@
+use std::io::{Result, Write};
+
+use wasmparser::ValType;
+
+use crate::node::{
+	Align, Block, Br, Expression, FuncData, LabelType, MemoryArgument, ResultList, Statement,
+	TableArgument, Terminator, Value,
+};
+
+// Operation types are named after their variants, which already follow the
+// `operation_result_parameter` order used by the runtimes
+fn write_name(name: &dyn std::fmt::Debug, w: &mut dyn Write) -> Result<()> {
+	let name = format!("{name:?}").to_lowercase();
+
+	w.write_all(name.as_bytes())
+}
+
+fn write_indent(level: usize, w: &mut dyn Write) -> Result<()> {
+	(0..level).try_for_each(|_| w.write_all(b"\t"))
+}
+
+fn write_type_list(list: &[ValType], w: &mut dyn Write) -> Result<()> {
+	if list.is_empty() {
+		return write!(w, "none");
+	}
+
+	for (i, v) in list.iter().enumerate() {
+		if i != 0 {
+			write!(w, ", ")?;
+		}
+
+		write_name(v, w)?;
+	}
+
+	Ok(())
+}
+
+fn write_value(value: Value, w: &mut dyn Write) -> Result<()> {
+	match value {
+		Value::I32(v) => write!(w, "i32 {v}"),
+		Value::I64(v) => write!(w, "i64 {v}"),
+		Value::F32(v) => write!(w, "f32 {v:?}"),
+		Value::F64(v) => write!(w, "f64 {v:?}"),
+		Value::V128(v) => write!(w, "v128 0x{v:032X}"),
+	}
+}
+
+fn write_result_list(list: ResultList, w: &mut dyn Write) -> Result<()> {
+	for (i, v) in list.iter().enumerate() {
+		if i != 0 {
+			write!(w, ", ")?;
+		}
+
+		write!(w, "reg_{}", v.var())?;
+	}
+
+	Ok(())
+}
+
+fn write_call_of(name: &str, list: &[&Expression], w: &mut dyn Write) -> Result<()> {
+	write!(w, "{name}(")?;
+
+	for (i, v) in list.iter().enumerate() {
+		if i != 0 {
+			write!(w, ", ")?;
+		}
+
+		write_expression(v, w)?;
+	}
+
+	write!(w, ")")
+}
+
+fn write_pointer(pointer: &Expression, offset: u64, w: &mut dyn Write) -> Result<()> {
+	write_expression(pointer, w)?;
+
+	if offset != 0 {
+		write!(w, " + {offset}")?;
+	}
+
+	Ok(())
+}
+
+fn write_expression(expr: &Expression, w: &mut dyn Write) -> Result<()> {
+	match expr {
+		Expression::Select(v) => {
+			write_call_of("select", &[v.condition(), v.on_true(), v.on_false()], w)
+		}
+		Expression::GetTemporary(v) => write!(w, "reg_{}", v.var()),
+		Expression::GetLocal(v) => write!(w, "loc_{}", v.var()),
+		Expression::GetGlobal(v) => write!(w, "global_{}", v.var()),
+		Expression::LoadAt(v) => {
+			write!(w, "load_")?;
+			write_name(&v.load_type(), w)?;
+			write!(w, "(memory_{}, ", v.memory())?;
+			write_pointer(v.pointer(), v.offset(), w)?;
+			write!(w, ")")
+		}
+		Expression::MemorySize(v) => write!(w, "memory_size(memory_{})", v.memory()),
+		Expression::TableGet(v) => {
+			write!(w, "table_get(table_{}, ", v.table())?;
+			write_expression(v.index(), w)?;
+			write!(w, ")")
+		}
+		Expression::TableSize(v) => write!(w, "table_size(table_{})", v.table()),
+		Expression::Value(v) => write_value(*v, w),
+		Expression::RefNull(v) => {
+			write!(w, "ref_null(")?;
+			write_name(&v.heap_type(), w)?;
+			write!(w, ")")
+		}
+		Expression::RefFunc(v) => write!(w, "ref_func(func_{})", v.function()),
+		Expression::RefIsNull(v) => write_call_of("ref_is_null", &[v.reference()], w),
+		Expression::RefAsNonNull(v) => write_call_of("ref_as_non_null", &[v.reference()], w),
+		Expression::UnOp(v) => {
+			write_name(&v.op_type(), w)?;
+			write_call_of("", &[v.rhs()], w)
+		}
+		Expression::BinOp(v) => {
+			write_name(&v.op_type(), w)?;
+			write_call_of("", &[v.lhs(), v.rhs()], w)
+		}
+		Expression::CmpOp(v) => {
+			write_name(&v.op_type(), w)?;
+			write_call_of("", &[v.lhs(), v.rhs()], w)
+		}
+		Expression::SimdOp(v) => {
+			let list: Vec<_> = v.param_list().iter().collect();
+
+			write_call_of(v.op_type().name(), &list, w)
+		}
+	}
+}
+
+// Branches that move their results down the stack show the moves they make
+fn write_align(align: Align, w: &mut dyn Write) -> Result<()> {
+	if align.is_aligned() {
+		return Ok(());
+	}
+
+	write!(w, " with ")?;
+	write_result_list(align.new_range(), w)?;
+	write!(w, " = ")?;
+	write_result_list(align.old_range(), w)
+}
+
+fn write_br(br: Br, w: &mut dyn Write) -> Result<()> {
+	write!(w, "{}", br.target())?;
+	write_align(br.align(), w)
+}
+
+fn write_terminator(last: &Terminator, level: usize, w: &mut dyn Write) -> Result<()> {
+	write_indent(level, w)?;
+
+	match last {
+		Terminator::Unreachable => write!(w, "unreachable")?,
+		Terminator::Br(v) => {
+			write!(w, "br ")?;
+			write_br(*v, w)?;
+		}
+		Terminator::BrTable(v) => {
+			write_call_of("br_table", &[v.condition()], w)?;
+			writeln!(w)?;
+
+			for (i, br) in v.data().iter().enumerate() {
+				write_indent(level + 1, w)?;
+				write!(w, "{i} => br ")?;
+				write_br(*br, w)?;
+				writeln!(w)?;
+			}
+
+			write_indent(level + 1, w)?;
+			write!(w, "_ => br ")?;
+			write_br(v.default(), w)?;
+		}
+		Terminator::Throw(v) => {
+			let list: Vec<_> = v.param_list().iter().collect();
+
+			write_call_of(&format!("throw tag_{}", v.tag()), &list, w)?;
+		}
+		Terminator::Rethrow(v) => write!(w, "rethrow {}", v.target())?,
+	}
+
+	writeln!(w)
+}
+
+fn write_block(block: &Block, level: usize, w: &mut dyn Write) -> Result<()> {
+	let offset_list = block
+		.offset_list()
+		.iter()
+		.map(Some)
+		.chain(std::iter::repeat(None));
+
+	for (stat, offset) in block.code().iter().zip(offset_list) {
+		write_statement(stat, offset.copied(), level, w)?;
+	}
+
+	if let Some(last) = block.last() {
+		write_terminator(last, level, w)?;
+	}
+
+	Ok(())
+}
+
+fn write_memory_argument(data: &MemoryArgument, w: &mut dyn Write) -> Result<()> {
+	write!(w, "memory_{}, ", data.memory())?;
+	write_expression(data.pointer(), w)
+}
+
+fn write_table_argument(data: &TableArgument, w: &mut dyn Write) -> Result<()> {
+	write!(w, "table_{}, ", data.table())?;
+	write_expression(data.index(), w)
+}
+
+fn write_expression_list(list: &[&Expression], w: &mut dyn Write) -> Result<()> {
+	list.iter().try_for_each(|v| {
+		write!(w, ", ")?;
+		write_expression(v, w)
+	})
+}
+
+fn write_nested(name: &str, block: &Block, level: usize, w: &mut dyn Write) -> Result<()> {
+	writeln!(w, "{name} {{")?;
+	write_block(block, level + 1, w)?;
+	write_indent(level, w)?;
+	write!(w, "}}")
+}
+
+#[allow(clippy::too_many_lines)]
+fn write_statement(
+	stat: &Statement,
+	offset: Option<usize>,
+	level: usize,
+	w: &mut dyn Write,
+) -> Result<()> {
+	write_indent(level, w)?;
+
+	match stat {
+		Statement::Block(v) => {
+			let name = match v.label_type() {
+				Some(LabelType::Forward) => "block",
+				Some(LabelType::Backward) => "loop",
+				None => "do",
+			};
+
+			write_nested(name, v, level, w)?;
+		}
+		Statement::BrIf(v) => {
+			write_call_of("br_if", &[v.condition()], w)?;
+			write!(w, " ")?;
+			write_br(v.target(), w)?;
+		}
+		Statement::If(v) => {
+			write!(w, "if ")?;
+			write_expression(v.condition(), w)?;
+
+			match v.hint() {
+				Some(true) => write!(w, " likely")?,
+				Some(false) => write!(w, " unlikely")?,
+				None => {}
+			}
+
+			write_nested("", v.on_true(), level, w)?;
+
+			if let Some(on_false) = v.on_false() {
+				write_nested(" else", on_false, level, w)?;
+			}
+		}
+		Statement::Try(v) => {
+			write_nested("try", v.code(), level, w)?;
+
+			for catch in v.catch_list() {
+				match catch.tag() {
+					Some(tag) => write!(w, " catch tag_{tag}")?,
+					None => write!(w, " catch_all")?,
+				}
+
+				if !catch.result_list().is_empty() {
+					write!(w, " into ")?;
+					write_result_list(catch.result_list(), w)?;
+				}
+
+				write_nested("", catch.code(), level, w)?;
+			}
+
+			if let Some(delegate) = v.delegate() {
+				write!(w, " delegate {delegate}")?;
+			}
+		}
+		Statement::Call(v) => {
+			if !v.result_list().is_empty() {
+				write_result_list(v.result_list(), w)?;
+				write!(w, " = ")?;
+			}
+
+			let list: Vec<_> = v.param_list().iter().collect();
+
+			write_call_of(&format!("call func_{}", v.function()), &list, w)?;
+		}
+		Statement::CallIndirect(v) => {
+			if !v.result_list().is_empty() {
+				write_result_list(v.result_list(), w)?;
+				write!(w, " = ")?;
+			}
+
+			write!(w, "call_indirect(table_{}, ", v.table())?;
+			write_expression(v.index(), w)?;
+			write_expression_list(&v.param_list().iter().collect::<Vec<_>>(), w)?;
+			write!(w, ")")?;
+		}
+		Statement::CallRef(v) => {
+			if !v.result_list().is_empty() {
+				write_result_list(v.result_list(), w)?;
+				write!(w, " = ")?;
+			}
+
+			write!(w, "call_ref(")?;
+			write_expression(v.function(), w)?;
+			write_expression_list(&v.param_list().iter().collect::<Vec<_>>(), w)?;
+			write!(w, ")")?;
+		}
+		Statement::SetTemporary(v) => {
+			write!(w, "reg_{} = ", v.var().var())?;
+			write_expression(v.value(), w)?;
+		}
+		Statement::SetLocal(v) => {
+			write!(w, "loc_{} = ", v.var().var())?;
+			write_expression(v.value(), w)?;
+		}
+		Statement::SetGlobal(v) => {
+			write!(w, "global_{} = ", v.var())?;
+			write_expression(v.value(), w)?;
+		}
+		Statement::StoreAt(v) => {
+			write!(w, "store_")?;
+			write_name(&v.store_type(), w)?;
+			write!(w, "(memory_{}, ", v.memory())?;
+			write_pointer(v.pointer(), v.offset(), w)?;
+			write_expression_list(&[v.value()], w)?;
+			write!(w, ")")?;
+		}
+		Statement::MemoryGrow(v) => {
+			write!(
+				w,
+				"reg_{} = memory_grow(memory_{}, ",
+				v.result().var(),
+				v.memory()
+			)?;
+			write_expression(v.size(), w)?;
+			write!(w, ")")?;
+		}
+		Statement::MemoryCopy(v) => {
+			write!(w, "memory_copy(")?;
+			write_memory_argument(v.destination(), w)?;
+			write!(w, ", ")?;
+			write_memory_argument(v.source(), w)?;
+			write_expression_list(&[v.size()], w)?;
+			write!(w, ")")?;
+		}
+		Statement::MemoryFill(v) => {
+			write!(w, "memory_fill(")?;
+			write_memory_argument(v.destination(), w)?;
+			write_expression_list(&[v.size(), v.value()], w)?;
+			write!(w, ")")?;
+		}
+		Statement::MemoryInit(v) => {
+			write!(w, "memory_init(")?;
+			write_memory_argument(v.destination(), w)?;
+			write!(w, ", data_{}", v.data())?;
+			write_expression_list(&[v.offset(), v.size()], w)?;
+			write!(w, ")")?;
+		}
+		Statement::DataDrop(v) => write!(w, "data_drop(data_{})", v.data())?,
+		Statement::TableInit(v) => {
+			write!(w, "table_init(table_{}, ", v.table())?;
+			write_expression(v.destination(), w)?;
+			write!(w, ", elem_{}", v.element())?;
+			write_expression_list(&[v.offset(), v.size()], w)?;
+			write!(w, ")")?;
+		}
+		Statement::ElemDrop(v) => write!(w, "elem_drop(elem_{})", v.element())?,
+		Statement::TableSet(v) => {
+			write!(w, "table_set(table_{}, ", v.table())?;
+			write_expression(v.index(), w)?;
+			write_expression_list(&[v.value()], w)?;
+			write!(w, ")")?;
+		}
+		Statement::TableGrow(v) => {
+			write!(
+				w,
+				"reg_{} = table_grow(table_{}",
+				v.result().var(),
+				v.table()
+			)?;
+			write_expression_list(&[v.size(), v.value()], w)?;
+			write!(w, ")")?;
+		}
+		Statement::TableCopy(v) => {
+			write!(w, "table_copy(")?;
+			write_table_argument(v.destination(), w)?;
+			write!(w, ", ")?;
+			write_table_argument(v.source(), w)?;
+			write_expression_list(&[v.size()], w)?;
+			write!(w, ")")?;
+		}
+		Statement::TableFill(v) => {
+			write!(w, "table_fill(")?;
+			write_table_argument(v.destination(), w)?;
+			write_expression_list(&[v.size(), v.value()], w)?;
+			write!(w, ")")?;
+		}
+		Statement::AtomicWait(v) => {
+			write!(w, "reg_{} = ", v.result().var())?;
+			write_call_of("atomic_wait", &[v.value(), v.expected(), v.timeout()], w)?;
+		}
+		Statement::AtomicNotify(v) => {
+			write!(
+				w,
+				"reg_{} = atomic_notify(memory_{}",
+				v.result().var(),
+				v.memory()
+			)?;
+			write_expression_list(&[v.pointer(), v.count()], w)?;
+			write!(w, ")")?;
+		}
+	}
+
+	match offset {
+		Some(offset) => writeln!(w, " @ {offset:#x}"),
+		None => writeln!(w),
+	}
+}
+
+/// Writes the code of the function at `index` in a readable form, naming
+/// locals and temporaries as the generated code does and showing where each
+/// statement came from in the module, for reporting miscompilations.
+///
+/// # Errors
+/// Returns `Err` if writing to `Write` failed.
+pub fn write_func_data(index: usize, func: &FuncData, w: &mut dyn Write) -> Result<()> {
+	write!(w, "function {index} (params: ")?;
+	write_type_list(func.param_data(), w)?;
+	write!(w, "; locals: ")?;
+	write_type_list(func.local_data(), w)?;
+	writeln!(
+		w,
+		"; results: {}; temporaries: {})",
+		func.num_result(),
+		func.num_stack()
+	)?;
+
+	write_block(func.code(), 1, w)
+}
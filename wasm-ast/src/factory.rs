@@ -1,14 +1,19 @@
+use std::collections::HashMap;
+
 use wasmparser::{BlockType, FunctionBody, MemArg, Operator, Result};
 
 use crate::{
 	module::{read_checked, read_checked_locals, TypeInfo},
 	node::{
-		BinOp, BinOpType, Block, Br, BrIf, BrTable, Call, CallIndirect, CmpOp, CmpOpType,
-		Expression, FuncData, GetGlobal, If, LabelType, LoadAt, LoadType, Local, MemoryArgument,
-		MemoryCopy, MemoryFill, MemoryGrow, MemorySize, Select, SetGlobal, SetLocal, Statement,
-		StoreAt, StoreType, Terminator, UnOp, UnOpType, Value,
+		AtomicNotify, AtomicWait, BinOp, BinOpType, Block, Br, BrIf, BrTable, Call, CallIndirect,
+		CallRef, Catch, CmpOp, CmpOpType, DataDrop, ElemDrop, Expression, FuncData, GetGlobal, If,
+		LabelType, LoadAt, LoadType, Local, MemoryArgument, MemoryCopy, MemoryFill, MemoryGrow,
+		MemoryInit, MemorySize, RefAsNonNull, RefFunc, RefIsNull, RefNull, ResultList, Rethrow,
+		Select, SetGlobal, SetLocal, SetTemporary, SimdOp, SimdOpType, Statement, StoreAt,
+		StoreType, TableArgument, TableCopy, TableFill, TableGet, TableGrow, TableInit, TableSet,
+		TableSize, Temporary, Terminator, Throw, Try, UnOp, UnOpType, Value,
 	},
-	stack::{ReadGet, Stack},
+	stack::{is_deferrable, ReadGet, Stack},
 };
 
 #[derive(Clone, Copy)]
@@ -17,13 +22,35 @@ enum BlockVariant {
 	Backward,
 	If,
 	Else,
+	Try,
+	Catch(Option<usize>),
 }
 
 enum BlockData {
-	Forward { num_result: usize },
-	Backward { num_param: usize },
-	If { num_result: usize, ty: BlockType },
-	Else { num_result: usize },
+	Forward {
+		num_result: usize,
+	},
+	Backward {
+		num_param: usize,
+	},
+	If {
+		num_result: usize,
+		ty: BlockType,
+		hint: Option<bool>,
+	},
+	Else {
+		num_result: usize,
+	},
+	Try {
+		num_result: usize,
+		ty: BlockType,
+	},
+	Catch {
+		num_result: usize,
+		ty: BlockType,
+		tag: Option<usize>,
+		result_list: ResultList,
+	},
 }
 
 impl Default for BlockData {
@@ -35,22 +62,63 @@ impl Default for BlockData {
 impl From<BlockData> for LabelType {
 	fn from(data: BlockData) -> Self {
 		match data {
-			BlockData::Forward { .. } | BlockData::If { .. } | BlockData::Else { .. } => {
-				Self::Forward
-			}
+			BlockData::Forward { .. }
+			| BlockData::If { .. }
+			| BlockData::Else { .. }
+			| BlockData::Try { .. }
+			| BlockData::Catch { .. } => Self::Forward,
 			BlockData::Backward { .. } => Self::Backward,
 		}
 	}
 }
 
+fn mask_with<T: Into<Value>>(value: Expression, mask: T) -> Expression {
+	let mask = mask.into();
+	let op_type = match mask {
+		Value::I32(_) => BinOpType::And_I32,
+		Value::I64(_) => BinOpType::And_I64,
+		_ => unreachable!("masks are always integers"),
+	};
+
+	Expression::BinOp(BinOp {
+		op_type,
+		lhs: value.into(),
+		rhs: Expression::Value(mask).into(),
+	})
+}
+
+// Lane indices and shuffle masks are passed along as trailing constants
+fn get_simd_immediate(op: &Operator) -> Option<Value> {
+	match *op {
+		Operator::I8x16ExtractLaneS { lane }
+		| Operator::I8x16ExtractLaneU { lane }
+		| Operator::I8x16ReplaceLane { lane }
+		| Operator::I16x8ExtractLaneS { lane }
+		| Operator::I16x8ExtractLaneU { lane }
+		| Operator::I16x8ReplaceLane { lane }
+		| Operator::I32x4ExtractLane { lane }
+		| Operator::I32x4ReplaceLane { lane }
+		| Operator::I64x2ExtractLane { lane }
+		| Operator::I64x2ReplaceLane { lane }
+		| Operator::F32x4ExtractLane { lane }
+		| Operator::F32x4ReplaceLane { lane }
+		| Operator::F64x2ExtractLane { lane }
+		| Operator::F64x2ReplaceLane { lane } => Some(Value::I32(lane.into())),
+		Operator::I8x16Shuffle { lanes } => Some(u128::from_le_bytes(lanes).into()),
+		_ => None,
+	}
+}
+
 #[derive(Default)]
 struct StatList {
 	stack: Stack,
 	code: Vec<Statement>,
+	offset_list: Vec<usize>,
 	last: Option<Box<Terminator>>,
 
 	block_data: BlockData,
 	has_reference: bool,
+	start: usize,
 }
 
 impl StatList {
@@ -58,62 +126,52 @@ impl StatList {
 		Self::default()
 	}
 
-	fn leak_all(&mut self) {
-		self.stack.leak_into(&mut self.code, |_| true);
+	// Statements take the offset of the instruction that was being built when
+	// they were pushed, which is also the one that leaked them
+	fn fill_offset_list(&mut self, offset: usize) {
+		self.offset_list.resize(self.code.len(), offset);
 	}
 
-	fn leak_pre_call(&mut self) {
-		self.stack.leak_into(&mut self.code, |node| {
-			ReadGet::run(node, |_| false, |_| true, |_| true)
-		});
-	}
-
-	fn leak_local_write(&mut self, id: usize) {
-		self.stack.leak_into(&mut self.code, |node| {
-			ReadGet::run(node, |var| var.var() == id, |_| false, |_| false)
-		});
+	fn leak_all(&mut self) {
+		self.stack.leak_into(&mut self.code, |_, _| true);
 	}
 
-	fn leak_global_write(&mut self, id: usize) {
-		self.stack.leak_into(&mut self.code, |node| {
-			ReadGet::run(node, |_| false, |var| var.var() == id, |_| false)
-		});
-	}
+	// The top `num_top` values, which branches and blocks take from their
+	// temporaries, are always leaked, while those under them only are when
+	// they cannot wait
+	fn leak_under(&mut self, num_top: usize, can_wait: fn(&Expression) -> bool) {
+		let end = self.stack.len() - num_top;
 
-	fn leak_memory_write(&mut self, id: usize) {
-		self.stack.leak_into(&mut self.code, |node| {
-			ReadGet::run(node, |_| false, |_| false, |var| var.memory() == id)
-		});
+		self.stack
+			.leak_into(&mut self.code, |i, node| i >= end || !can_wait(node));
 	}
 
-	fn push_load(&mut self, load_type: LoadType, memarg: MemArg) {
-		let memory = memarg.memory.try_into().unwrap();
-		let offset = memarg.offset.try_into().unwrap();
-
-		let data = Expression::LoadAt(LoadAt {
-			load_type,
-			memory,
-			offset,
-			pointer: self.stack.pop().into(),
-		});
+	// Values left under a block being built are leaked in front of the
+	// statement holding it, which the arms after the first are already part
+	// of, except for the condition of an `if` as it is read before either arm
+	fn leak_pending<P>(&mut self, child: &BlockData, predicate: P, offset: usize)
+	where
+		P: Fn(&Expression) -> bool,
+	{
+		let (num_top, is_arm) = match child {
+			BlockData::If { .. } => (1, false),
+			BlockData::Else { .. } | BlockData::Catch { .. } => (0, true),
+			_ => (0, false),
+		};
 
-		self.stack.push(data);
-	}
+		let end = self.stack.len() - num_top;
+		let mut code = Vec::new();
 
-	fn add_store(&mut self, store_type: StoreType, memarg: MemArg) {
-		let memory = memarg.memory.try_into().unwrap();
-		let offset = memarg.offset.try_into().unwrap();
+		self.stack
+			.leak_into(&mut code, |i, node| i < end && predicate(node));
 
-		let data = Statement::StoreAt(StoreAt {
-			store_type,
-			memory,
-			offset,
-			value: self.stack.pop().into(),
-			pointer: self.stack.pop().into(),
-		});
+		let position = self.code.len() - usize::from(is_arm);
 
-		self.leak_memory_write(memory);
-		self.code.push(data);
+		self.offset_list.splice(
+			position..position,
+			std::iter::repeat(offset).take(code.len()),
+		);
+		self.code.splice(position..position, code);
 	}
 
 	fn push_constant<T: Into<Value>>(&mut self, value: T) {
@@ -151,6 +209,21 @@ impl StatList {
 		self.stack.push(data);
 	}
 
+	fn push_simd_op(&mut self, op_type: SimdOpType, immediate: Option<Value>) {
+		let mut param_list: Vec<_> = self.stack.pop_len(op_type.num_param()).collect();
+
+		if let Some(value) = immediate {
+			param_list.push(Expression::Value(value));
+		}
+
+		let data = Expression::SimdOp(SimdOp {
+			op_type,
+			param_list,
+		});
+
+		self.stack.push(data);
+	}
+
 	// Eqz is the only unary comparison so it's "emulated"
 	// using a constant operand
 	fn try_add_equal_zero(&mut self, op: &Operator) -> bool {
@@ -184,14 +257,20 @@ impl StatList {
 		} else if let Ok(op_type) = CmpOpType::try_from(op) {
 			self.push_cmp_op(op_type);
 
+			true
+		} else if let Ok(op_type) = SimdOpType::try_from(op) {
+			self.push_simd_op(op_type, get_simd_immediate(op));
+
 			true
 		} else {
 			self.try_add_equal_zero(op)
 		}
 	}
 
-	fn set_terminator(&mut self, term: Terminator) {
-		self.leak_all();
+	// Values under those that a branch takes are never read again, so only
+	// the ones that may trap or have an effect need to be evaluated
+	fn set_terminator(&mut self, term: Terminator, num_top: usize) {
+		self.leak_under(num_top, Expression::is_pure);
 		self.last = Some(term.into());
 	}
 }
@@ -203,6 +282,7 @@ impl From<StatList> for Block {
 		Self {
 			label_type,
 			code: stat.code,
+			offset_list: stat.offset_list,
 			last: stat.last,
 		}
 	}
@@ -215,6 +295,10 @@ pub struct Factory<'a> {
 	target: StatList,
 
 	nested_unreachable: usize,
+	offset: usize,
+
+	hint_list: HashMap<usize, bool>,
+	branch_hint: Option<bool>,
 }
 
 impl<'a> Factory<'a> {
@@ -225,17 +309,26 @@ impl<'a> Factory<'a> {
 			pending: Vec::new(),
 			target: StatList::new(),
 			nested_unreachable: 0,
+			offset: 0,
+			hint_list: HashMap::new(),
+			branch_hint: None,
 		}
 	}
 
+	/// Builds a function from a bare list of instructions, which are not
+	/// part of a module, so statements are given the index of their
+	/// instruction in place of an offset.
 	#[must_use]
 	pub fn create_anonymous(&mut self, list: &[Operator]) -> FuncData {
-		let data = self.build_stat_list(list, 1);
+		self.hint_list.clear();
+
+		let offset_list: Vec<_> = (0..list.len()).collect();
+		let data = self.build_stat_list(list, &offset_list, 1);
 
 		FuncData {
 			local_data: Vec::new(),
+			param_data: Vec::new(),
 			num_result: 1,
-			num_param: 0,
 			num_stack: data.stack.capacity,
 			code: data.into(),
 		}
@@ -245,37 +338,84 @@ impl<'a> Factory<'a> {
 	///
 	/// Returns an error if the function is malformed.
 	pub fn create_indexed(&mut self, index: usize, func: &FunctionBody) -> Result<FuncData> {
-		let code = read_checked(func.get_operators_reader()?)?;
+		let mut reader = func.get_operators_reader()?;
+
+		// Offsets into memories indexed by `i64` may take up to 64 bits
+		reader.allow_memarg64(true);
+
+		let list: Vec<_> = read_checked(reader.into_iter_with_offsets())?;
 		let local_data = read_checked_locals(func.get_locals_reader()?)?;
 
-		let (num_param, num_result) = self.type_info.by_func_index(index);
-		let data = self.build_stat_list(&code, num_result);
+		self.load_hint_list(index, func.range().start, &list);
+
+		let (code, offset_list): (Vec<_>, Vec<_>) = list.into_iter().unzip();
+
+		let param_data = self.type_info.by_func_index_params(index).to_vec();
+		let (_, num_result) = self.type_info.by_func_index(index);
+		let data = self.build_stat_list(&code, &offset_list, num_result);
 
 		Ok(FuncData {
 			local_data,
+			param_data,
 			num_result,
-			num_param,
 			num_stack: data.stack.capacity,
 			code: data.into(),
 		})
 	}
 
+	// Branch hints are given as body offsets, so they are mapped to the
+	// index of the operator they apply to ahead of building
+	fn load_hint_list(&mut self, index: usize, start: usize, list: &[(Operator, usize)]) {
+		self.hint_list.clear();
+
+		let Some(hint_list) = self.type_info.by_branch_hint(index) else {
+			return;
+		};
+
+		for (i, (_, offset)) in list.iter().enumerate() {
+			if let Some(&hint) = hint_list.get(&(offset - start)) {
+				self.hint_list.insert(i, hint);
+			}
+		}
+	}
+
 	fn start_block(&mut self, ty: BlockType, variant: BlockVariant) {
-		let (num_param, num_result) = self.type_info.by_block_type(ty);
+		let (mut num_param, num_result) = self.type_info.by_block_type(ty);
 		let mut old = std::mem::take(&mut self.target);
 
-		old.leak_all();
+		old.leak_under(num_param, is_deferrable);
+		old.fill_offset_list(self.offset);
 
+		self.target.start = self.offset;
 		self.target.block_data = match variant {
 			BlockVariant::Forward => BlockData::Forward { num_result },
 			BlockVariant::Backward => BlockData::Backward { num_param },
-			BlockVariant::If => BlockData::If { num_result, ty },
+			BlockVariant::If => BlockData::If {
+				num_result,
+				ty,
+				hint: self.branch_hint,
+			},
 			BlockVariant::Else => {
 				old.stack.pop_len(num_result).for_each(drop);
 				old.stack.push_temporaries(num_param);
 
 				BlockData::Else { num_result }
 			}
+			BlockVariant::Try => BlockData::Try { num_result, ty },
+			BlockVariant::Catch(tag) => {
+				num_param = tag.map_or(0, |tag| self.type_info.by_tag_index(tag));
+
+				old.stack.pop_len(num_result).for_each(drop);
+
+				let result_list = old.stack.push_temporaries(num_param);
+
+				BlockData::Catch {
+					num_result,
+					ty,
+					tag,
+					result_list,
+				}
+			}
 		};
 
 		self.target.stack = old.stack.split_last(num_param, num_result);
@@ -295,18 +435,51 @@ impl<'a> Factory<'a> {
 		self.start_block(ty, BlockVariant::Else);
 	}
 
+	fn start_catch(&mut self, tag: Option<usize>) {
+		let (BlockData::Try { ty, .. } | BlockData::Catch { ty, .. }) = self.target.block_data
+		else {
+			unreachable!()
+		};
+
+		self.target.leak_all();
+		self.end_block();
+		self.start_block(ty, BlockVariant::Catch(tag));
+	}
+
+	// Exceptions that escape a delegating `Try` must skip over the handlers
+	// of every `Try` between it and the label it delegates to
+	fn end_delegate(&mut self, depth: usize) {
+		let skip = self.pending[self.pending.len() - depth..]
+			.iter()
+			.filter(|v| matches!(v.block_data, BlockData::Try { .. }))
+			.count();
+
+		self.target.leak_all();
+		self.end_block();
+
+		let Statement::Try(last) = self.target.code.last_mut().unwrap() else {
+			unreachable!()
+		};
+
+		last.delegate = Some(skip);
+	}
+
 	fn end_block(&mut self) {
 		let old = self.pending.pop().unwrap();
-		let now = std::mem::replace(&mut self.target, old);
+		let mut now = std::mem::replace(&mut self.target, old);
+		let start = now.start;
+
+		now.fill_offset_list(self.offset);
 
-		self.target.stack.capacity = now.stack.capacity;
+		self.target.stack.capacity = self.target.stack.capacity.max(now.stack.capacity);
 
 		let stat = match now.block_data {
 			BlockData::Forward { .. } | BlockData::Backward { .. } => Statement::Block(now.into()),
-			BlockData::If { .. } => Statement::If(If {
+			BlockData::If { hint, .. } => Statement::If(If {
 				condition: self.target.stack.pop().into(),
 				on_true: Box::new(now.into()),
 				on_false: None,
+				hint,
 			}),
 			BlockData::Else { .. } => {
 				let Statement::If(last) = self.target.code.last_mut().unwrap() else {
@@ -315,11 +488,32 @@ impl<'a> Factory<'a> {
 
 				last.on_false = Some(Box::new(now.into()));
 
+				return;
+			}
+			BlockData::Try { .. } => Statement::Try(Try {
+				code: now.into(),
+				catch_list: Vec::new(),
+				delegate: None,
+			}),
+			BlockData::Catch {
+				tag, result_list, ..
+			} => {
+				let Statement::Try(last) = self.target.code.last_mut().unwrap() else {
+					unreachable!()
+				};
+
+				last.catch_list.push(Catch {
+					tag,
+					result_list,
+					code: now.into(),
+				});
+
 				return;
 			}
 		};
 
 		self.target.code.push(stat);
+		self.target.offset_list.push(start);
 	}
 
 	fn get_relative_block(&mut self, index: usize) -> &mut StatList {
@@ -338,7 +532,9 @@ impl<'a> Factory<'a> {
 		let result = match block.block_data {
 			BlockData::Forward { num_result }
 			| BlockData::If { num_result, .. }
-			| BlockData::Else { num_result } => num_result,
+			| BlockData::Else { num_result }
+			| BlockData::Try { num_result, .. }
+			| BlockData::Catch { num_result, .. } => num_result,
 			BlockData::Backward { num_param } => num_param,
 		};
 
@@ -349,11 +545,57 @@ impl<'a> Factory<'a> {
 		Br { target, align }
 	}
 
+	// Writes leak the values reading what they change from every block
+	// being built, as those under a nested block are left on its stack
+	fn leak_write<P>(&mut self, predicate: P)
+	where
+		P: Fn(&Expression) -> bool,
+	{
+		for i in 0..self.pending.len() {
+			let (list, rest) = self.pending[i..].split_first_mut().unwrap();
+			let child = rest.first().unwrap_or(&self.target);
+
+			list.leak_pending(&child.block_data, &predicate, self.offset);
+		}
+
+		self.target
+			.stack
+			.leak_into(&mut self.target.code, |_, node| predicate(node));
+	}
+
+	fn leak_pre_call(&mut self) {
+		self.leak_write(|node| ReadGet::run(node, |_| false, |_| true, |_| true, |_| true));
+	}
+
+	fn leak_local_write(&mut self, id: usize) {
+		self.leak_write(|node| {
+			ReadGet::run(node, |var| var.var() == id, |_| false, |_| false, |_| false)
+		});
+	}
+
+	fn leak_global_write(&mut self, id: usize) {
+		self.leak_write(|node| {
+			ReadGet::run(node, |_| false, |var| var.var() == id, |_| false, |_| false)
+		});
+	}
+
+	fn leak_memory_write(&mut self, id: usize) {
+		self.leak_write(|node| {
+			ReadGet::run(node, |_| false, |_| false, |var| var == id, |_| false)
+		});
+	}
+
+	fn leak_table_write(&mut self, id: usize) {
+		self.leak_write(|node| {
+			ReadGet::run(node, |_| false, |_| false, |_| false, |var| var == id)
+		});
+	}
+
 	fn add_call(&mut self, function: usize) {
 		let (num_param, num_result) = self.type_info.by_func_index(function);
 		let param_list = self.target.stack.pop_len(num_param).collect();
 
-		self.target.leak_pre_call();
+		self.leak_pre_call();
 
 		let result_list = self.target.stack.push_temporaries(num_result);
 
@@ -371,11 +613,12 @@ impl<'a> Factory<'a> {
 		let index = self.target.stack.pop().into();
 		let param_list = self.target.stack.pop_len(num_param).collect();
 
-		self.target.leak_pre_call();
+		self.leak_pre_call();
 
 		let result_list = self.target.stack.push_temporaries(num_result);
 
 		let data = Statement::CallIndirect(CallIndirect {
+			ty,
 			table,
 			index,
 			param_list,
@@ -385,10 +628,737 @@ impl<'a> Factory<'a> {
 		self.target.code.push(data);
 	}
 
+	fn add_call_ref(&mut self, ty: usize) {
+		let (num_param, num_result) = self.type_info.by_type_index(ty);
+		let function = match self.target.stack.pop() {
+			data @ Expression::RefAsNonNull(_) => data,
+			data => Expression::RefAsNonNull(RefAsNonNull {
+				reference: data.into(),
+			}),
+		};
+		let param_list = self.target.stack.pop_len(num_param).collect();
+
+		self.leak_pre_call();
+
+		let result_list = self.target.stack.push_temporaries(num_result);
+
+		let data = Statement::CallRef(CallRef {
+			function: function.into(),
+			param_list,
+			result_list,
+		});
+
+		self.target.code.push(data);
+	}
+
+	// Memories indexed by `i64` are still addressed with plain numbers, so
+	// any pointer or length into them is converted beforehand, while their
+	// page counts are taken and given back as `i64` by the runtime
+	fn to_address(&self, memory: usize, value: Expression) -> Box<Expression> {
+		if self.type_info.is_memory_64(memory) {
+			let data = Expression::UnOp(UnOp {
+				op_type: UnOpType::Convert_F64_U64,
+				rhs: value.into(),
+			});
+
+			data.into()
+		} else {
+			value.into()
+		}
+	}
+
+	fn push_load(&mut self, load_type: LoadType, memarg: MemArg) {
+		let memory = memarg.memory.try_into().unwrap();
+		let offset = memarg.offset;
+		let pointer = self.target.stack.pop();
+
+		let data = Expression::LoadAt(LoadAt {
+			load_type,
+			memory,
+			offset,
+			pointer: self.to_address(memory, pointer),
+		});
+
+		self.target.stack.push(data);
+	}
+
+	fn add_store(&mut self, store_type: StoreType, memarg: MemArg) {
+		let memory = memarg.memory.try_into().unwrap();
+		let offset = memarg.offset;
+		let value = self.target.stack.pop().into();
+		let pointer = self.target.stack.pop();
+
+		let data = Statement::StoreAt(StoreAt {
+			store_type,
+			memory,
+			offset,
+			value,
+			pointer: self.to_address(memory, pointer),
+		});
+
+		self.leak_memory_write(memory);
+		self.target.code.push(data);
+	}
+
+	fn push_load_extend(&mut self, op_type: SimdOpType, memarg: MemArg) {
+		self.push_load(LoadType::I64, memarg);
+		self.target.push_simd_op(SimdOpType::I64x2Splat, None);
+		self.target.push_simd_op(op_type, None);
+	}
+
+	fn push_load_splat(&mut self, load_type: LoadType, op_type: SimdOpType, memarg: MemArg) {
+		self.push_load(load_type, memarg);
+		self.target.push_simd_op(op_type, None);
+	}
+
+	fn push_load_lane(
+		&mut self,
+		load_type: LoadType,
+		op_type: SimdOpType,
+		memarg: MemArg,
+		lane: u8,
+	) {
+		let vector = self.target.stack.pop();
+
+		self.push_load(load_type, memarg);
+
+		let value = self.target.stack.pop();
+
+		self.target.stack.push(vector);
+		self.target.stack.push(value);
+		self.target
+			.push_simd_op(op_type, Some(Value::I32(lane.into())));
+	}
+
+	fn add_store_lane(
+		&mut self,
+		store_type: StoreType,
+		op_type: SimdOpType,
+		memarg: MemArg,
+		lane: u8,
+	) {
+		self.target
+			.push_simd_op(op_type, Some(Value::I32(lane.into())));
+		self.add_store(store_type, memarg);
+	}
+
+	// Vector loads and stores that do not move a whole vector are built
+	// from their scalar equivalents and some lane operations
+	fn try_add_simd_memory(&mut self, op: &Operator) -> bool {
+		match *op {
+			Operator::V128Load { memarg } => self.push_load(LoadType::V128, memarg),
+			Operator::V128Store { memarg } => self.add_store(StoreType::V128, memarg),
+			Operator::V128Load8x8S { memarg } => {
+				self.push_load_extend(SimdOpType::I16x8ExtendLowI8x16S, memarg);
+			}
+			Operator::V128Load8x8U { memarg } => {
+				self.push_load_extend(SimdOpType::I16x8ExtendLowI8x16U, memarg);
+			}
+			Operator::V128Load16x4S { memarg } => {
+				self.push_load_extend(SimdOpType::I32x4ExtendLowI16x8S, memarg);
+			}
+			Operator::V128Load16x4U { memarg } => {
+				self.push_load_extend(SimdOpType::I32x4ExtendLowI16x8U, memarg);
+			}
+			Operator::V128Load32x2S { memarg } => {
+				self.push_load_extend(SimdOpType::I64x2ExtendLowI32x4S, memarg);
+			}
+			Operator::V128Load32x2U { memarg } => {
+				self.push_load_extend(SimdOpType::I64x2ExtendLowI32x4U, memarg);
+			}
+			Operator::V128Load8Splat { memarg } => {
+				self.push_load_splat(LoadType::I32_U8, SimdOpType::I8x16Splat, memarg);
+			}
+			Operator::V128Load16Splat { memarg } => {
+				self.push_load_splat(LoadType::I32_U16, SimdOpType::I16x8Splat, memarg);
+			}
+			Operator::V128Load32Splat { memarg } => {
+				self.push_load_splat(LoadType::I32, SimdOpType::I32x4Splat, memarg);
+			}
+			Operator::V128Load64Splat { memarg } => {
+				self.push_load_splat(LoadType::I64, SimdOpType::I64x2Splat, memarg);
+			}
+			Operator::V128Load32Zero { memarg } => {
+				self.target.push_constant(0_u128);
+				self.push_load_lane(LoadType::I32, SimdOpType::I32x4ReplaceLane, memarg, 0);
+			}
+			Operator::V128Load64Zero { memarg } => {
+				self.target.push_constant(0_u128);
+				self.push_load_lane(LoadType::I64, SimdOpType::I64x2ReplaceLane, memarg, 0);
+			}
+			Operator::V128Load8Lane { memarg, lane } => {
+				self.push_load_lane(LoadType::I32_U8, SimdOpType::I8x16ReplaceLane, memarg, lane);
+			}
+			Operator::V128Load16Lane { memarg, lane } => {
+				self.push_load_lane(
+					LoadType::I32_U16,
+					SimdOpType::I16x8ReplaceLane,
+					memarg,
+					lane,
+				);
+			}
+			Operator::V128Load32Lane { memarg, lane } => {
+				self.push_load_lane(LoadType::I32, SimdOpType::I32x4ReplaceLane, memarg, lane);
+			}
+			Operator::V128Load64Lane { memarg, lane } => {
+				self.push_load_lane(LoadType::I64, SimdOpType::I64x2ReplaceLane, memarg, lane);
+			}
+			Operator::V128Store8Lane { memarg, lane } => {
+				self.add_store_lane(
+					StoreType::I32_N8,
+					SimdOpType::I8x16ExtractLaneU,
+					memarg,
+					lane,
+				);
+			}
+			Operator::V128Store16Lane { memarg, lane } => {
+				self.add_store_lane(
+					StoreType::I32_N16,
+					SimdOpType::I16x8ExtractLaneU,
+					memarg,
+					lane,
+				);
+			}
+			Operator::V128Store32Lane { memarg, lane } => {
+				self.add_store_lane(StoreType::I32, SimdOpType::I32x4ExtractLane, memarg, lane);
+			}
+			Operator::V128Store64Lane { memarg, lane } => {
+				self.add_store_lane(StoreType::I64, SimdOpType::I64x2ExtractLane, memarg, lane);
+			}
+			Operator::V128Const { value } => {
+				self.target
+					.push_constant(u128::from_le_bytes(*value.bytes()));
+			}
+			_ => return false,
+		}
+
+		true
+	}
+
+	// Atomic operations are lowered to their sequential equivalents,
+	// as the generated code only ever runs on a single thread
+	fn try_add_atomic(&mut self, op: &Operator) -> bool {
+		match *op {
+			Operator::I32AtomicLoad { memarg } => self.push_load(LoadType::I32, memarg),
+			Operator::I64AtomicLoad { memarg } => self.push_load(LoadType::I64, memarg),
+			Operator::I32AtomicLoad8U { memarg } => self.push_load(LoadType::I32_U8, memarg),
+			Operator::I32AtomicLoad16U { memarg } => self.push_load(LoadType::I32_U16, memarg),
+			Operator::I64AtomicLoad8U { memarg } => self.push_load(LoadType::I64_U8, memarg),
+			Operator::I64AtomicLoad16U { memarg } => self.push_load(LoadType::I64_U16, memarg),
+			Operator::I64AtomicLoad32U { memarg } => self.push_load(LoadType::I64_U32, memarg),
+			Operator::I32AtomicStore { memarg } => self.add_store(StoreType::I32, memarg),
+			Operator::I64AtomicStore { memarg } => self.add_store(StoreType::I64, memarg),
+			Operator::I32AtomicStore8 { memarg } => self.add_store(StoreType::I32_N8, memarg),
+			Operator::I32AtomicStore16 { memarg } => self.add_store(StoreType::I32_N16, memarg),
+			Operator::I64AtomicStore8 { memarg } => self.add_store(StoreType::I64_N8, memarg),
+			Operator::I64AtomicStore16 { memarg } => self.add_store(StoreType::I64_N16, memarg),
+			Operator::I64AtomicStore32 { memarg } => self.add_store(StoreType::I64_N32, memarg),
+			Operator::I32AtomicRmwAdd { memarg } => {
+				self.add_atomic_rmw(
+					Some(BinOpType::Add_I32),
+					LoadType::I32,
+					StoreType::I32,
+					memarg,
+				);
+			}
+			Operator::I32AtomicRmwSub { memarg } => {
+				self.add_atomic_rmw(
+					Some(BinOpType::Sub_I32),
+					LoadType::I32,
+					StoreType::I32,
+					memarg,
+				);
+			}
+			Operator::I32AtomicRmwAnd { memarg } => {
+				self.add_atomic_rmw(
+					Some(BinOpType::And_I32),
+					LoadType::I32,
+					StoreType::I32,
+					memarg,
+				);
+			}
+			Operator::I32AtomicRmwOr { memarg } => {
+				self.add_atomic_rmw(
+					Some(BinOpType::Or_I32),
+					LoadType::I32,
+					StoreType::I32,
+					memarg,
+				);
+			}
+			Operator::I32AtomicRmwXor { memarg } => {
+				self.add_atomic_rmw(
+					Some(BinOpType::Xor_I32),
+					LoadType::I32,
+					StoreType::I32,
+					memarg,
+				);
+			}
+			Operator::I32AtomicRmwXchg { memarg } => {
+				self.add_atomic_rmw(None, LoadType::I32, StoreType::I32, memarg);
+			}
+			Operator::I32AtomicRmw8AddU { memarg } => {
+				self.add_atomic_rmw(
+					Some(BinOpType::Add_I32),
+					LoadType::I32_U8,
+					StoreType::I32_N8,
+					memarg,
+				);
+			}
+			Operator::I32AtomicRmw8SubU { memarg } => {
+				self.add_atomic_rmw(
+					Some(BinOpType::Sub_I32),
+					LoadType::I32_U8,
+					StoreType::I32_N8,
+					memarg,
+				);
+			}
+			Operator::I32AtomicRmw8AndU { memarg } => {
+				self.add_atomic_rmw(
+					Some(BinOpType::And_I32),
+					LoadType::I32_U8,
+					StoreType::I32_N8,
+					memarg,
+				);
+			}
+			Operator::I32AtomicRmw8OrU { memarg } => {
+				self.add_atomic_rmw(
+					Some(BinOpType::Or_I32),
+					LoadType::I32_U8,
+					StoreType::I32_N8,
+					memarg,
+				);
+			}
+			Operator::I32AtomicRmw8XorU { memarg } => {
+				self.add_atomic_rmw(
+					Some(BinOpType::Xor_I32),
+					LoadType::I32_U8,
+					StoreType::I32_N8,
+					memarg,
+				);
+			}
+			Operator::I32AtomicRmw8XchgU { memarg } => {
+				self.add_atomic_rmw(None, LoadType::I32_U8, StoreType::I32_N8, memarg);
+			}
+			Operator::I32AtomicRmw16AddU { memarg } => {
+				self.add_atomic_rmw(
+					Some(BinOpType::Add_I32),
+					LoadType::I32_U16,
+					StoreType::I32_N16,
+					memarg,
+				);
+			}
+			Operator::I32AtomicRmw16SubU { memarg } => {
+				self.add_atomic_rmw(
+					Some(BinOpType::Sub_I32),
+					LoadType::I32_U16,
+					StoreType::I32_N16,
+					memarg,
+				);
+			}
+			Operator::I32AtomicRmw16AndU { memarg } => {
+				self.add_atomic_rmw(
+					Some(BinOpType::And_I32),
+					LoadType::I32_U16,
+					StoreType::I32_N16,
+					memarg,
+				);
+			}
+			Operator::I32AtomicRmw16OrU { memarg } => {
+				self.add_atomic_rmw(
+					Some(BinOpType::Or_I32),
+					LoadType::I32_U16,
+					StoreType::I32_N16,
+					memarg,
+				);
+			}
+			Operator::I32AtomicRmw16XorU { memarg } => {
+				self.add_atomic_rmw(
+					Some(BinOpType::Xor_I32),
+					LoadType::I32_U16,
+					StoreType::I32_N16,
+					memarg,
+				);
+			}
+			Operator::I32AtomicRmw16XchgU { memarg } => {
+				self.add_atomic_rmw(None, LoadType::I32_U16, StoreType::I32_N16, memarg);
+			}
+			Operator::I64AtomicRmwAdd { memarg } => {
+				self.add_atomic_rmw(
+					Some(BinOpType::Add_I64),
+					LoadType::I64,
+					StoreType::I64,
+					memarg,
+				);
+			}
+			Operator::I64AtomicRmwSub { memarg } => {
+				self.add_atomic_rmw(
+					Some(BinOpType::Sub_I64),
+					LoadType::I64,
+					StoreType::I64,
+					memarg,
+				);
+			}
+			Operator::I64AtomicRmwAnd { memarg } => {
+				self.add_atomic_rmw(
+					Some(BinOpType::And_I64),
+					LoadType::I64,
+					StoreType::I64,
+					memarg,
+				);
+			}
+			Operator::I64AtomicRmwOr { memarg } => {
+				self.add_atomic_rmw(
+					Some(BinOpType::Or_I64),
+					LoadType::I64,
+					StoreType::I64,
+					memarg,
+				);
+			}
+			Operator::I64AtomicRmwXor { memarg } => {
+				self.add_atomic_rmw(
+					Some(BinOpType::Xor_I64),
+					LoadType::I64,
+					StoreType::I64,
+					memarg,
+				);
+			}
+			Operator::I64AtomicRmwXchg { memarg } => {
+				self.add_atomic_rmw(None, LoadType::I64, StoreType::I64, memarg);
+			}
+			Operator::I64AtomicRmw8AddU { memarg } => {
+				self.add_atomic_rmw(
+					Some(BinOpType::Add_I64),
+					LoadType::I64_U8,
+					StoreType::I64_N8,
+					memarg,
+				);
+			}
+			Operator::I64AtomicRmw8SubU { memarg } => {
+				self.add_atomic_rmw(
+					Some(BinOpType::Sub_I64),
+					LoadType::I64_U8,
+					StoreType::I64_N8,
+					memarg,
+				);
+			}
+			Operator::I64AtomicRmw8AndU { memarg } => {
+				self.add_atomic_rmw(
+					Some(BinOpType::And_I64),
+					LoadType::I64_U8,
+					StoreType::I64_N8,
+					memarg,
+				);
+			}
+			Operator::I64AtomicRmw8OrU { memarg } => {
+				self.add_atomic_rmw(
+					Some(BinOpType::Or_I64),
+					LoadType::I64_U8,
+					StoreType::I64_N8,
+					memarg,
+				);
+			}
+			Operator::I64AtomicRmw8XorU { memarg } => {
+				self.add_atomic_rmw(
+					Some(BinOpType::Xor_I64),
+					LoadType::I64_U8,
+					StoreType::I64_N8,
+					memarg,
+				);
+			}
+			Operator::I64AtomicRmw8XchgU { memarg } => {
+				self.add_atomic_rmw(None, LoadType::I64_U8, StoreType::I64_N8, memarg);
+			}
+			Operator::I64AtomicRmw16AddU { memarg } => {
+				self.add_atomic_rmw(
+					Some(BinOpType::Add_I64),
+					LoadType::I64_U16,
+					StoreType::I64_N16,
+					memarg,
+				);
+			}
+			Operator::I64AtomicRmw16SubU { memarg } => {
+				self.add_atomic_rmw(
+					Some(BinOpType::Sub_I64),
+					LoadType::I64_U16,
+					StoreType::I64_N16,
+					memarg,
+				);
+			}
+			Operator::I64AtomicRmw16AndU { memarg } => {
+				self.add_atomic_rmw(
+					Some(BinOpType::And_I64),
+					LoadType::I64_U16,
+					StoreType::I64_N16,
+					memarg,
+				);
+			}
+			Operator::I64AtomicRmw16OrU { memarg } => {
+				self.add_atomic_rmw(
+					Some(BinOpType::Or_I64),
+					LoadType::I64_U16,
+					StoreType::I64_N16,
+					memarg,
+				);
+			}
+			Operator::I64AtomicRmw16XorU { memarg } => {
+				self.add_atomic_rmw(
+					Some(BinOpType::Xor_I64),
+					LoadType::I64_U16,
+					StoreType::I64_N16,
+					memarg,
+				);
+			}
+			Operator::I64AtomicRmw16XchgU { memarg } => {
+				self.add_atomic_rmw(None, LoadType::I64_U16, StoreType::I64_N16, memarg);
+			}
+			Operator::I64AtomicRmw32AddU { memarg } => {
+				self.add_atomic_rmw(
+					Some(BinOpType::Add_I64),
+					LoadType::I64_U32,
+					StoreType::I64_N32,
+					memarg,
+				);
+			}
+			Operator::I64AtomicRmw32SubU { memarg } => {
+				self.add_atomic_rmw(
+					Some(BinOpType::Sub_I64),
+					LoadType::I64_U32,
+					StoreType::I64_N32,
+					memarg,
+				);
+			}
+			Operator::I64AtomicRmw32AndU { memarg } => {
+				self.add_atomic_rmw(
+					Some(BinOpType::And_I64),
+					LoadType::I64_U32,
+					StoreType::I64_N32,
+					memarg,
+				);
+			}
+			Operator::I64AtomicRmw32OrU { memarg } => {
+				self.add_atomic_rmw(
+					Some(BinOpType::Or_I64),
+					LoadType::I64_U32,
+					StoreType::I64_N32,
+					memarg,
+				);
+			}
+			Operator::I64AtomicRmw32XorU { memarg } => {
+				self.add_atomic_rmw(
+					Some(BinOpType::Xor_I64),
+					LoadType::I64_U32,
+					StoreType::I64_N32,
+					memarg,
+				);
+			}
+			Operator::I64AtomicRmw32XchgU { memarg } => {
+				self.add_atomic_rmw(None, LoadType::I64_U32, StoreType::I64_N32, memarg);
+			}
+			Operator::I32AtomicRmwCmpxchg { memarg } => {
+				self.add_atomic_cmpxchg(LoadType::I32, StoreType::I32, memarg);
+			}
+			Operator::I32AtomicRmw8CmpxchgU { memarg } => {
+				self.add_atomic_cmpxchg(LoadType::I32_U8, StoreType::I32_N8, memarg);
+			}
+			Operator::I32AtomicRmw16CmpxchgU { memarg } => {
+				self.add_atomic_cmpxchg(LoadType::I32_U16, StoreType::I32_N16, memarg);
+			}
+			Operator::I64AtomicRmwCmpxchg { memarg } => {
+				self.add_atomic_cmpxchg(LoadType::I64, StoreType::I64, memarg);
+			}
+			Operator::I64AtomicRmw8CmpxchgU { memarg } => {
+				self.add_atomic_cmpxchg(LoadType::I64_U8, StoreType::I64_N8, memarg);
+			}
+			Operator::I64AtomicRmw16CmpxchgU { memarg } => {
+				self.add_atomic_cmpxchg(LoadType::I64_U16, StoreType::I64_N16, memarg);
+			}
+			Operator::I64AtomicRmw32CmpxchgU { memarg } => {
+				self.add_atomic_cmpxchg(LoadType::I64_U32, StoreType::I64_N32, memarg);
+			}
+			Operator::MemoryAtomicWait32 { memarg } => self.add_atomic_wait(LoadType::I32, memarg),
+			Operator::MemoryAtomicWait64 { memarg } => self.add_atomic_wait(LoadType::I64, memarg),
+			Operator::MemoryAtomicNotify { memarg } => self.add_atomic_notify(memarg),
+			Operator::AtomicFence => {}
+			_ => return false,
+		}
+
+		true
+	}
+
+	// Operands are leaked first so that they sit in their own temporaries,
+	// which can then be read again after the old value is loaded
+	fn add_atomic_rmw(
+		&mut self,
+		op_type: Option<BinOpType>,
+		load_type: LoadType,
+		store_type: StoreType,
+		memarg: MemArg,
+	) {
+		let memory = memarg.memory.try_into().unwrap();
+		let offset = memarg.offset;
+
+		self.target.leak_all();
+
+		let old = self.target.stack.push_temporary();
+		let value = Temporary { var: old.var() - 1 };
+		let pointer = Temporary { var: old.var() - 2 };
+
+		self.target.stack.pop_len(3).for_each(drop);
+
+		let load = Expression::LoadAt(LoadAt {
+			load_type,
+			memory,
+			offset,
+			pointer: self.to_address(memory, Expression::GetTemporary(pointer)),
+		});
+
+		self.target.code.push(Statement::SetTemporary(SetTemporary {
+			var: old,
+			value: load.into(),
+		}));
+
+		let value = match op_type {
+			Some(op_type) => Expression::BinOp(BinOp {
+				op_type,
+				lhs: Expression::GetTemporary(old).into(),
+				rhs: Expression::GetTemporary(value).into(),
+			}),
+			None => Expression::GetTemporary(value),
+		};
+
+		self.target.code.push(Statement::StoreAt(StoreAt {
+			store_type,
+			memory,
+			offset,
+			pointer: self.to_address(memory, Expression::GetTemporary(pointer)),
+			value: value.into(),
+		}));
+
+		let result = self.target.stack.push_temporary();
+
+		self.target.code.push(Statement::SetTemporary(SetTemporary {
+			var: result,
+			value: Expression::GetTemporary(old).into(),
+		}));
+	}
+
+	fn add_atomic_cmpxchg(&mut self, load_type: LoadType, store_type: StoreType, memarg: MemArg) {
+		let memory = memarg.memory.try_into().unwrap();
+		let offset = memarg.offset;
+
+		self.target.leak_all();
+
+		let old = self.target.stack.push_temporary();
+		let replacement = Temporary { var: old.var() - 1 };
+		let expected = Temporary { var: old.var() - 2 };
+		let pointer = Temporary { var: old.var() - 3 };
+
+		self.target.stack.pop_len(4).for_each(drop);
+
+		let load = Expression::LoadAt(LoadAt {
+			load_type,
+			memory,
+			offset,
+			pointer: self.to_address(memory, Expression::GetTemporary(pointer)),
+		});
+
+		self.target.code.push(Statement::SetTemporary(SetTemporary {
+			var: old,
+			value: load.into(),
+		}));
+
+		// The expected value is compared as if it was loaded with the same width
+		let expected = Expression::GetTemporary(expected);
+		let (op_type, expected) = match load_type {
+			LoadType::I32 => (CmpOpType::Eq_I32, expected),
+			LoadType::I64 => (CmpOpType::Eq_I64, expected),
+			LoadType::I32_U8 => (CmpOpType::Eq_I32, mask_with(expected, 0xFF_i32)),
+			LoadType::I32_U16 => (CmpOpType::Eq_I32, mask_with(expected, 0xFFFF_i32)),
+			LoadType::I64_U8 => (CmpOpType::Eq_I64, mask_with(expected, 0xFF_i64)),
+			LoadType::I64_U16 => (CmpOpType::Eq_I64, mask_with(expected, 0xFFFF_i64)),
+			LoadType::I64_U32 => (CmpOpType::Eq_I64, mask_with(expected, 0xFFFF_FFFF_i64)),
+			_ => unreachable!("atomic operations only load integers"),
+		};
+
+		let condition = Expression::CmpOp(CmpOp {
+			op_type,
+			lhs: Expression::GetTemporary(old).into(),
+			rhs: expected.into(),
+		});
+
+		let store = Statement::StoreAt(StoreAt {
+			store_type,
+			memory,
+			offset,
+			pointer: self.to_address(memory, Expression::GetTemporary(pointer)),
+			value: Expression::GetTemporary(replacement).into(),
+		});
+
+		let on_true = Block {
+			label_type: None,
+			code: vec![store],
+			offset_list: vec![self.offset],
+			last: None,
+		};
+
+		self.target.code.push(Statement::If(If {
+			condition: condition.into(),
+			on_true: on_true.into(),
+			on_false: None,
+			hint: None,
+		}));
+
+		let result = self.target.stack.push_temporary();
+
+		self.target.code.push(Statement::SetTemporary(SetTemporary {
+			var: result,
+			value: Expression::GetTemporary(old).into(),
+		}));
+	}
+
+	fn add_atomic_wait(&mut self, load_type: LoadType, memarg: MemArg) {
+		let timeout = self.target.stack.pop().into();
+		let expected = self.target.stack.pop().into();
+
+		self.push_load(load_type, memarg);
+
+		let value = self.target.stack.pop().into();
+		let result = self.target.stack.push_temporary();
+
+		let data = Statement::AtomicWait(AtomicWait {
+			result,
+			value,
+			expected,
+			timeout,
+		});
+
+		self.target.code.push(data);
+	}
+
+	fn add_atomic_notify(&mut self, memarg: MemArg) {
+		let memory = memarg.memory.try_into().unwrap();
+		let count = self.target.stack.pop().into();
+		let pointer = self.target.stack.pop();
+		let pointer = self.to_address(memory, pointer);
+		let result = self.target.stack.push_temporary();
+
+		let data = Statement::AtomicNotify(AtomicNotify {
+			memory,
+			result,
+			pointer,
+			count,
+		});
+
+		self.target.code.push(data);
+	}
+
 	#[cold]
 	fn drop_unreachable(&mut self, op: &Operator) {
 		match op {
-			Operator::Block { .. } | Operator::Loop { .. } | Operator::If { .. } => {
+			Operator::Block { .. }
+			| Operator::Loop { .. }
+			| Operator::If { .. }
+			| Operator::Try { .. } => {
 				self.nested_unreachable += 1;
 			}
 			Operator::Else if self.nested_unreachable == 1 => {
@@ -396,6 +1366,24 @@ impl<'a> Factory<'a> {
 
 				self.start_else();
 			}
+			Operator::Catch { tag_index } if self.nested_unreachable == 1 => {
+				self.nested_unreachable -= 1;
+
+				self.start_catch(Some((*tag_index).try_into().unwrap()));
+			}
+			Operator::CatchAll if self.nested_unreachable == 1 => {
+				self.nested_unreachable -= 1;
+
+				self.start_catch(None);
+			}
+			Operator::Delegate { relative_depth } if self.nested_unreachable == 1 => {
+				self.nested_unreachable -= 1;
+
+				self.end_delegate((*relative_depth).try_into().unwrap());
+			}
+			Operator::Delegate { .. } => {
+				self.nested_unreachable -= 1;
+			}
 			Operator::End if self.nested_unreachable == 1 => {
 				self.nested_unreachable -= 1;
 
@@ -410,7 +1398,10 @@ impl<'a> Factory<'a> {
 
 	#[allow(clippy::too_many_lines)]
 	fn add_instruction(&mut self, op: &Operator) {
-		if self.target.try_add_operation(op) {
+		if self.target.try_add_operation(op)
+			|| self.try_add_atomic(op)
+			|| self.try_add_simd_memory(op)
+		{
 			return;
 		}
 
@@ -418,7 +1409,7 @@ impl<'a> Factory<'a> {
 			Operator::Unreachable => {
 				self.nested_unreachable += 1;
 
-				self.target.set_terminator(Terminator::Unreachable);
+				self.target.set_terminator(Terminator::Unreachable, 0);
 			}
 			Operator::Nop => {}
 			Operator::Block { blockty } => {
@@ -440,22 +1431,51 @@ impl<'a> Factory<'a> {
 				self.target.leak_all();
 				self.end_block();
 			}
+			Operator::Try { blockty } => {
+				self.start_block(blockty, BlockVariant::Try);
+			}
+			Operator::Catch { tag_index } => {
+				self.start_catch(Some(tag_index.try_into().unwrap()));
+			}
+			Operator::CatchAll => {
+				self.start_catch(None);
+			}
+			Operator::Delegate { relative_depth } => {
+				self.end_delegate(relative_depth.try_into().unwrap());
+			}
+			Operator::Throw { tag_index } => {
+				let tag = tag_index.try_into().unwrap();
+				let num_param = self.type_info.by_tag_index(tag);
+				let param_list = self.target.stack.pop_len(num_param).collect();
+				let term = Terminator::Throw(Throw { tag, param_list });
+
+				self.target.set_terminator(term, 0);
+				self.nested_unreachable += 1;
+			}
+			Operator::Rethrow { relative_depth } => {
+				let target = relative_depth.try_into().unwrap();
+				let term = Terminator::Rethrow(Rethrow { target });
+
+				self.target.set_terminator(term, 0);
+				self.nested_unreachable += 1;
+			}
 			Operator::Br { relative_depth } => {
 				let target = relative_depth.try_into().unwrap();
-				let term = Terminator::Br(self.get_br_terminator(target));
+				let br = self.get_br_terminator(target);
 
-				self.target.set_terminator(term);
+				self.target
+					.set_terminator(Terminator::Br(br), br.align.length);
 				self.nested_unreachable += 1;
 			}
 			Operator::BrIf { relative_depth } => {
 				let target = relative_depth.try_into().unwrap();
-				let data = Statement::BrIf(BrIf {
-					condition: self.target.stack.pop().into(),
-					target: self.get_br_terminator(target),
-				});
+				let condition = self.target.stack.pop().into();
+				let target = self.get_br_terminator(target);
 
-				self.target.leak_all();
-				self.target.code.push(data);
+				self.target.leak_under(target.align.length, is_deferrable);
+				self.target
+					.code
+					.push(Statement::BrIf(BrIf { condition, target }));
 			}
 			Operator::BrTable { ref targets } => {
 				let condition = self.target.stack.pop().into();
@@ -466,6 +1486,7 @@ impl<'a> Factory<'a> {
 					.collect();
 
 				let default = self.get_br_terminator(targets.default().try_into().unwrap());
+				let num_top = default.align.length;
 
 				let term = Terminator::BrTable(BrTable {
 					condition,
@@ -473,14 +1494,15 @@ impl<'a> Factory<'a> {
 					default,
 				});
 
-				self.target.set_terminator(term);
+				self.target.set_terminator(term, num_top);
 				self.nested_unreachable += 1;
 			}
 			Operator::Return => {
 				let target = self.pending.len();
-				let term = Terminator::Br(self.get_br_terminator(target));
+				let br = self.get_br_terminator(target);
 
-				self.target.set_terminator(term);
+				self.target
+					.set_terminator(Terminator::Br(br), br.align.length);
 				self.nested_unreachable += 1;
 			}
 			Operator::Call { function_index } => {
@@ -498,11 +1520,27 @@ impl<'a> Factory<'a> {
 
 				self.add_call_indirect(type_index, table_index);
 			}
+			Operator::CallRef { type_index } => {
+				let type_index = type_index.try_into().unwrap();
+
+				self.add_call_ref(type_index);
+			}
 			Operator::Drop => {
 				self.target.stack.pop();
 			}
 			Operator::Select => {
 				let data = Expression::Select(Select {
+					ty: None,
+					condition: self.target.stack.pop().into(),
+					on_false: self.target.stack.pop().into(),
+					on_true: self.target.stack.pop().into(),
+				});
+
+				self.target.stack.push(data);
+			}
+			Operator::TypedSelect { ty } => {
+				let data = Expression::Select(Select {
+					ty: Some(ty),
 					condition: self.target.stack.pop().into(),
 					on_false: self.target.stack.pop().into(),
 					on_true: self.target.stack.pop().into(),
@@ -510,6 +1548,31 @@ impl<'a> Factory<'a> {
 
 				self.target.stack.push(data);
 			}
+			Operator::RefNull { hty } => {
+				let data = Expression::RefNull(RefNull { heap_type: hty });
+
+				self.target.stack.push(data);
+			}
+			Operator::RefIsNull => {
+				let data = Expression::RefIsNull(RefIsNull {
+					reference: self.target.stack.pop().into(),
+				});
+
+				self.target.stack.push(data);
+			}
+			Operator::RefAsNonNull => {
+				let data = Expression::RefAsNonNull(RefAsNonNull {
+					reference: self.target.stack.pop().into(),
+				});
+
+				self.target.stack.push(data);
+			}
+			Operator::RefFunc { function_index } => {
+				let function = function_index.try_into().unwrap();
+				let data = Expression::RefFunc(RefFunc { function });
+
+				self.target.stack.push(data);
+			}
 			Operator::LocalGet { local_index } => {
 				let var = local_index.try_into().unwrap();
 				let data = Expression::GetLocal(Local { var });
@@ -523,7 +1586,7 @@ impl<'a> Factory<'a> {
 					value: self.target.stack.pop().into(),
 				});
 
-				self.target.leak_local_write(var);
+				self.leak_local_write(var);
 				self.target.code.push(data);
 			}
 			Operator::LocalTee { local_index } => {
@@ -534,7 +1597,7 @@ impl<'a> Factory<'a> {
 					value: self.target.stack.pop().into(),
 				});
 
-				self.target.leak_local_write(var);
+				self.leak_local_write(var);
 				self.target.stack.push(get);
 				self.target.code.push(set);
 			}
@@ -551,67 +1614,81 @@ impl<'a> Factory<'a> {
 					value: self.target.stack.pop().into(),
 				});
 
-				self.target.leak_global_write(var);
+				self.leak_global_write(var);
 				self.target.code.push(data);
 			}
-			Operator::I32Load { memarg } => self.target.push_load(LoadType::I32, memarg),
-			Operator::I64Load { memarg } => self.target.push_load(LoadType::I64, memarg),
-			Operator::F32Load { memarg } => self.target.push_load(LoadType::F32, memarg),
-			Operator::F64Load { memarg } => self.target.push_load(LoadType::F64, memarg),
-			Operator::I32Load8S { memarg } => self.target.push_load(LoadType::I32_I8, memarg),
-			Operator::I32Load8U { memarg } => self.target.push_load(LoadType::I32_U8, memarg),
-			Operator::I32Load16S { memarg } => self.target.push_load(LoadType::I32_I16, memarg),
-			Operator::I32Load16U { memarg } => self.target.push_load(LoadType::I32_U16, memarg),
-			Operator::I64Load8S { memarg } => self.target.push_load(LoadType::I64_I8, memarg),
-			Operator::I64Load8U { memarg } => self.target.push_load(LoadType::I64_U8, memarg),
-			Operator::I64Load16S { memarg } => self.target.push_load(LoadType::I64_I16, memarg),
-			Operator::I64Load16U { memarg } => self.target.push_load(LoadType::I64_U16, memarg),
-			Operator::I64Load32S { memarg } => self.target.push_load(LoadType::I64_I32, memarg),
-			Operator::I64Load32U { memarg } => self.target.push_load(LoadType::I64_U32, memarg),
-			Operator::I32Store { memarg } => self.target.add_store(StoreType::I32, memarg),
-			Operator::I64Store { memarg } => self.target.add_store(StoreType::I64, memarg),
-			Operator::F32Store { memarg } => self.target.add_store(StoreType::F32, memarg),
-			Operator::F64Store { memarg } => self.target.add_store(StoreType::F64, memarg),
-			Operator::I32Store8 { memarg } => self.target.add_store(StoreType::I32_N8, memarg),
-			Operator::I32Store16 { memarg } => self.target.add_store(StoreType::I32_N16, memarg),
-			Operator::I64Store8 { memarg } => self.target.add_store(StoreType::I64_N8, memarg),
-			Operator::I64Store16 { memarg } => self.target.add_store(StoreType::I64_N16, memarg),
-			Operator::I64Store32 { memarg } => self.target.add_store(StoreType::I64_N32, memarg),
+			Operator::I32Load { memarg } => self.push_load(LoadType::I32, memarg),
+			Operator::I64Load { memarg } => self.push_load(LoadType::I64, memarg),
+			Operator::F32Load { memarg } => self.push_load(LoadType::F32, memarg),
+			Operator::F64Load { memarg } => self.push_load(LoadType::F64, memarg),
+			Operator::I32Load8S { memarg } => self.push_load(LoadType::I32_I8, memarg),
+			Operator::I32Load8U { memarg } => self.push_load(LoadType::I32_U8, memarg),
+			Operator::I32Load16S { memarg } => self.push_load(LoadType::I32_I16, memarg),
+			Operator::I32Load16U { memarg } => self.push_load(LoadType::I32_U16, memarg),
+			Operator::I64Load8S { memarg } => self.push_load(LoadType::I64_I8, memarg),
+			Operator::I64Load8U { memarg } => self.push_load(LoadType::I64_U8, memarg),
+			Operator::I64Load16S { memarg } => self.push_load(LoadType::I64_I16, memarg),
+			Operator::I64Load16U { memarg } => self.push_load(LoadType::I64_U16, memarg),
+			Operator::I64Load32S { memarg } => self.push_load(LoadType::I64_I32, memarg),
+			Operator::I64Load32U { memarg } => self.push_load(LoadType::I64_U32, memarg),
+			Operator::I32Store { memarg } => self.add_store(StoreType::I32, memarg),
+			Operator::I64Store { memarg } => self.add_store(StoreType::I64, memarg),
+			Operator::F32Store { memarg } => self.add_store(StoreType::F32, memarg),
+			Operator::F64Store { memarg } => self.add_store(StoreType::F64, memarg),
+			Operator::I32Store8 { memarg } => self.add_store(StoreType::I32_N8, memarg),
+			Operator::I32Store16 { memarg } => self.add_store(StoreType::I32_N16, memarg),
+			Operator::I64Store8 { memarg } => self.add_store(StoreType::I64_N8, memarg),
+			Operator::I64Store16 { memarg } => self.add_store(StoreType::I64_N16, memarg),
+			Operator::I64Store32 { memarg } => self.add_store(StoreType::I64_N32, memarg),
 			Operator::MemorySize { mem, .. } => {
 				let memory = mem.try_into().unwrap();
-				let data = Expression::MemorySize(MemorySize { memory });
+				let is_64 = self.type_info.is_memory_64(memory);
+				let data = Expression::MemorySize(MemorySize { memory, is_64 });
 
 				self.target.stack.push(data);
 			}
 			Operator::MemoryGrow { mem, .. } => {
+				let memory = mem.try_into().unwrap();
+				let is_64 = self.type_info.is_memory_64(memory);
 				let size = self.target.stack.pop().into();
 				let result = self.target.stack.push_temporary();
-				let memory = mem.try_into().unwrap();
 
 				let data = Statement::MemoryGrow(MemoryGrow {
 					memory,
+					is_64,
 					result,
 					size,
 				});
 
-				self.target.leak_memory_write(memory);
+				self.leak_memory_write(memory);
 				self.target.code.push(data);
 			}
 			Operator::MemoryCopy { dst_mem, src_mem } => {
-				let size = self.target.stack.pop().into();
+				let dst_mem = dst_mem.try_into().unwrap();
+				let src_mem = src_mem.try_into().unwrap();
+
+				// The length is only an `i64` when both memories are
+				let size = self.target.stack.pop();
+				let size = if self.type_info.is_memory_64(src_mem) {
+					self.to_address(dst_mem, size)
+				} else {
+					size.into()
+				};
 
+				let pointer = self.target.stack.pop();
 				let source = MemoryArgument {
-					memory: src_mem.try_into().unwrap(),
-					pointer: self.target.stack.pop().into(),
+					memory: src_mem,
+					pointer: self.to_address(src_mem, pointer),
 				};
 
+				let pointer = self.target.stack.pop();
 				let destination = MemoryArgument {
-					memory: dst_mem.try_into().unwrap(),
-					pointer: self.target.stack.pop().into(),
+					memory: dst_mem,
+					pointer: self.to_address(dst_mem, pointer),
 				};
 
-				self.target.leak_memory_write(source.memory);
-				self.target.leak_memory_write(destination.memory);
+				self.leak_memory_write(source.memory);
+				self.leak_memory_write(destination.memory);
 
 				let data = Statement::MemoryCopy(MemoryCopy {
 					destination,
@@ -622,15 +1699,18 @@ impl<'a> Factory<'a> {
 				self.target.code.push(data);
 			}
 			Operator::MemoryFill { mem } => {
-				let size = self.target.stack.pop().into();
+				let memory = mem.try_into().unwrap();
+				let size = self.target.stack.pop();
+				let size = self.to_address(memory, size);
 				let value = self.target.stack.pop().into();
 
+				let pointer = self.target.stack.pop();
 				let destination = MemoryArgument {
-					memory: mem.try_into().unwrap(),
-					pointer: self.target.stack.pop().into(),
+					memory,
+					pointer: self.to_address(memory, pointer),
 				};
 
-				self.target.leak_memory_write(destination.memory);
+				self.leak_memory_write(destination.memory);
 
 				let data = Statement::MemoryFill(MemoryFill {
 					destination,
@@ -640,6 +1720,141 @@ impl<'a> Factory<'a> {
 
 				self.target.code.push(data);
 			}
+			Operator::MemoryInit { data_index, mem } => {
+				let memory = mem.try_into().unwrap();
+				let size = self.target.stack.pop().into();
+				let offset = self.target.stack.pop().into();
+
+				let pointer = self.target.stack.pop();
+				let destination = MemoryArgument {
+					memory,
+					pointer: self.to_address(memory, pointer),
+				};
+
+				self.leak_memory_write(destination.memory);
+
+				let data = Statement::MemoryInit(MemoryInit {
+					destination,
+					data: data_index.try_into().unwrap(),
+					offset,
+					size,
+				});
+
+				self.target.code.push(data);
+			}
+			Operator::DataDrop { data_index } => {
+				let data = Statement::DataDrop(DataDrop {
+					data: data_index.try_into().unwrap(),
+				});
+
+				self.target.code.push(data);
+			}
+			Operator::TableInit { elem_index, table } => {
+				let table = table.try_into().unwrap();
+				let data = Statement::TableInit(TableInit {
+					table,
+					element: elem_index.try_into().unwrap(),
+					size: self.target.stack.pop().into(),
+					offset: self.target.stack.pop().into(),
+					destination: self.target.stack.pop().into(),
+				});
+
+				self.leak_table_write(table);
+				self.target.code.push(data);
+			}
+			Operator::ElemDrop { elem_index } => {
+				let data = Statement::ElemDrop(ElemDrop {
+					element: elem_index.try_into().unwrap(),
+				});
+
+				self.target.code.push(data);
+			}
+			Operator::TableGet { table } => {
+				let data = Expression::TableGet(TableGet {
+					table: table.try_into().unwrap(),
+					index: self.target.stack.pop().into(),
+				});
+
+				self.target.stack.push(data);
+			}
+			Operator::TableSet { table } => {
+				let table = table.try_into().unwrap();
+				let data = Statement::TableSet(TableSet {
+					table,
+					value: self.target.stack.pop().into(),
+					index: self.target.stack.pop().into(),
+				});
+
+				self.leak_table_write(table);
+				self.target.code.push(data);
+			}
+			Operator::TableSize { table } => {
+				let table = table.try_into().unwrap();
+				let data = Expression::TableSize(TableSize { table });
+
+				self.target.stack.push(data);
+			}
+			Operator::TableGrow { table } => {
+				let size = self.target.stack.pop().into();
+				let value = self.target.stack.pop().into();
+				let result = self.target.stack.push_temporary();
+				let table = table.try_into().unwrap();
+
+				let data = Statement::TableGrow(TableGrow {
+					table,
+					result,
+					size,
+					value,
+				});
+
+				self.leak_table_write(table);
+				self.target.code.push(data);
+			}
+			Operator::TableCopy {
+				dst_table,
+				src_table,
+			} => {
+				let size = self.target.stack.pop().into();
+
+				let source = TableArgument {
+					table: src_table.try_into().unwrap(),
+					index: self.target.stack.pop().into(),
+				};
+
+				let destination = TableArgument {
+					table: dst_table.try_into().unwrap(),
+					index: self.target.stack.pop().into(),
+				};
+
+				self.leak_table_write(destination.table);
+
+				let data = Statement::TableCopy(TableCopy {
+					destination,
+					source,
+					size,
+				});
+
+				self.target.code.push(data);
+			}
+			Operator::TableFill { table } => {
+				let size = self.target.stack.pop().into();
+				let value = self.target.stack.pop().into();
+
+				let destination = TableArgument {
+					table: table.try_into().unwrap(),
+					index: self.target.stack.pop().into(),
+				};
+
+				self.leak_table_write(destination.table);
+
+				let data = Statement::TableFill(TableFill {
+					destination,
+					size,
+					value,
+				});
+
+				self.target.code.push(data);
+			}
 			Operator::I32Const { value } => self.target.push_constant(value),
 			Operator::I64Const { value } => self.target.push_constant(value),
 			Operator::F32Const { value } => self.target.push_constant(value.bits()),
@@ -648,20 +1863,31 @@ impl<'a> Factory<'a> {
 		}
 	}
 
-	fn build_stat_list(&mut self, list: &[Operator], num_result: usize) -> StatList {
+	fn build_stat_list(
+		&mut self,
+		list: &[Operator],
+		offset_list: &[usize],
+		num_result: usize,
+	) -> StatList {
 		self.target.block_data = BlockData::Forward { num_result };
 		self.nested_unreachable = 0;
 
-		for op in list.iter().take(list.len() - 1) {
+		for (i, op) in list.iter().take(list.len() - 1).enumerate() {
+			self.branch_hint = self.hint_list.get(&i).copied();
+			self.offset = offset_list[i];
+
 			if self.nested_unreachable == 0 {
 				self.add_instruction(op);
 			} else {
 				self.drop_unreachable(op);
 			}
+
+			self.target.fill_offset_list(self.offset);
 		}
 
 		if self.nested_unreachable == 0 {
 			self.target.leak_all();
+			self.target.fill_offset_list(self.offset);
 		}
 
 		std::mem::take(&mut self.target)
@@ -0,0 +1,282 @@
+use wasmparser::{
+	BlockType, ConstExpr, DataKind, ElementItems, ElementKind, GlobalType, HeapType, Operator,
+	RefType, Result, Type, TypeRef, ValType,
+};
+
+use crate::module::{External, Module};
+
+macro_rules! mark_proposal {
+	($set:ident, mvp) => {
+		()
+	};
+	($set:ident, $proposal:ident) => {
+		$set.$proposal = true
+	};
+}
+
+macro_rules! define_load_operator {
+	($(@$proposal:ident $op:ident $({ $($arg:ident: $argty:ty),* })? => $visit:ident)*) => {
+		fn load_operator(&mut self, op: &Operator) {
+			if let Operator::Block { blockty }
+			| Operator::Loop { blockty }
+			| Operator::If { blockty }
+			| Operator::Try { blockty } = op
+			{
+				self.load_block_type(*blockty);
+			}
+
+			match op {
+				$(Operator::$op { .. } => mark_proposal!(self, $proposal),)*
+			}
+		}
+	};
+}
+
+/// The post-MVP proposals a module depends on, as found by [`scan`].
+#[allow(clippy::struct_excessive_bools)]
+#[derive(Default, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct FeatureSet {
+	pub bulk_memory: bool,
+	pub exceptions: bool,
+	pub extended_const: bool,
+	pub function_references: bool,
+	pub gc: bool,
+	pub memory_64: bool,
+	pub memory_control: bool,
+	pub multi_memory: bool,
+	pub multi_value: bool,
+	pub mutable_global: bool,
+	pub reference_types: bool,
+	pub relaxed_simd: bool,
+	pub saturating_float_to_int: bool,
+	pub sign_extension: bool,
+	pub simd: bool,
+	pub tail_call: bool,
+	pub threads: bool,
+}
+
+impl FeatureSet {
+	wasmparser::for_each_operator!(define_load_operator);
+
+	fn load_block_type(&mut self, ty: BlockType) {
+		match ty {
+			BlockType::Empty => {}
+			BlockType::Type(ty) => self.load_val_type(ty),
+			BlockType::FuncType(_) => self.multi_value = true,
+		}
+	}
+
+	fn load_ref_type(&mut self, ty: RefType) {
+		match ty.heap_type() {
+			HeapType::Func | HeapType::Extern if ty.is_nullable() => {}
+			HeapType::Func | HeapType::Extern | HeapType::Indexed(_) => {
+				self.function_references = true;
+			}
+			_ => self.gc = true,
+		}
+	}
+
+	fn load_val_type(&mut self, ty: ValType) {
+		match ty {
+			ValType::I32 | ValType::I64 | ValType::F32 | ValType::F64 => {}
+			ValType::V128 => self.simd = true,
+			ValType::Ref(ty) => {
+				self.reference_types = true;
+				self.load_ref_type(ty);
+			}
+		}
+	}
+
+	fn load_global_type(&mut self, ty: GlobalType, is_import: bool) {
+		self.load_val_type(ty.content_type);
+		self.mutable_global |= is_import && ty.mutable;
+	}
+
+	// Anything beyond a single constant, `global.get`, or reference is
+	// only allowed by extended constant expressions
+	fn load_constant(&mut self, init: &ConstExpr) -> Result<()> {
+		let mut count = 0;
+
+		for op in init.get_operators_reader() {
+			let op = op?;
+
+			count += 1;
+
+			self.load_operator(&op);
+		}
+
+		self.extended_const |= count > 2;
+
+		Ok(())
+	}
+
+	fn load_type_list(&mut self, list: &[Type]) {
+		for ty in list {
+			match ty {
+				Type::Func(ty) => {
+					ty.params()
+						.iter()
+						.chain(ty.results())
+						.for_each(|&v| self.load_val_type(v));
+
+					self.multi_value |= ty.results().len() > 1;
+				}
+				Type::Array(_) => self.gc = true,
+			}
+		}
+	}
+
+	fn load_import_list(&mut self, wasm: &Module) {
+		for import in wasm.import_section() {
+			match import.ty {
+				TypeRef::Global(ty) => self.load_global_type(ty, true),
+				TypeRef::Tag(_) => self.exceptions = true,
+				TypeRef::Func(_) | TypeRef::Table(_) | TypeRef::Memory(_) => {}
+			}
+		}
+	}
+
+	fn load_export_list(&mut self, wasm: &Module) {
+		let offset = wasm.import_count(External::Global);
+
+		for export in wasm.export_section() {
+			let index = usize::try_from(export.index).unwrap();
+
+			match External::from(export.kind) {
+				External::Global if index >= offset => {
+					let ty = wasm.global_section()[index - offset].ty;
+
+					self.mutable_global |= ty.mutable;
+				}
+				External::Tag => self.exceptions = true,
+				_ => {}
+			}
+		}
+	}
+
+	fn load_table_list(&mut self, wasm: &Module) {
+		let import_list = wasm.import_section().iter().filter_map(|v| match v.ty {
+			TypeRef::Table(ty) => Some(ty),
+			_ => None,
+		});
+
+		let list = wasm.table_section().iter().map(|v| v.ty);
+
+		for ty in import_list.chain(list) {
+			if ty.element_type != RefType::FUNCREF {
+				self.reference_types = true;
+				self.load_ref_type(ty.element_type);
+			}
+		}
+
+		self.reference_types |= wasm.table_space() > 1;
+	}
+
+	fn load_memory_list(&mut self, wasm: &Module) {
+		let import_list = wasm.import_section().iter().filter_map(|v| match v.ty {
+			TypeRef::Memory(ty) => Some(ty),
+			_ => None,
+		});
+
+		for ty in import_list.chain(wasm.memory_section().iter().copied()) {
+			self.memory_64 |= ty.memory64;
+			self.threads |= ty.shared;
+		}
+
+		self.multi_memory |= wasm.memory_space() > 1;
+	}
+
+	fn load_global_list(&mut self, wasm: &Module) -> Result<()> {
+		for global in wasm.global_section() {
+			self.load_global_type(global.ty, false);
+			self.load_constant(&global.init_expr)?;
+		}
+
+		Ok(())
+	}
+
+	fn load_element_list(&mut self, wasm: &Module) -> Result<()> {
+		for element in wasm.element_section() {
+			match &element.kind {
+				ElementKind::Passive => self.bulk_memory = true,
+				ElementKind::Declared => self.reference_types = true,
+				ElementKind::Active {
+					table_index,
+					offset_expr,
+				} => {
+					self.reference_types |= table_index.unwrap_or_default() != 0;
+					self.load_constant(offset_expr)?;
+				}
+			}
+
+			if let ElementItems::Expressions(list) = &element.items {
+				self.bulk_memory = true;
+
+				for init in list.clone() {
+					self.load_constant(&init?)?;
+				}
+			}
+
+			if element.ty != RefType::FUNCREF {
+				self.reference_types = true;
+				self.load_ref_type(element.ty);
+			}
+		}
+
+		Ok(())
+	}
+
+	fn load_data_list(&mut self, wasm: &Module) -> Result<()> {
+		for data in wasm.data_section() {
+			match &data.kind {
+				DataKind::Passive => self.bulk_memory = true,
+				DataKind::Active { offset_expr, .. } => self.load_constant(offset_expr)?,
+			}
+		}
+
+		Ok(())
+	}
+
+	fn load_code_list(&mut self, wasm: &Module) -> Result<()> {
+		for body in wasm.code_section() {
+			for local in body.get_locals_reader()? {
+				self.load_val_type(local?.1);
+			}
+
+			let mut reader = body.get_operators_reader()?;
+
+			reader.allow_memarg64(true);
+
+			for op in reader {
+				self.load_operator(&op?);
+			}
+		}
+
+		Ok(())
+	}
+}
+
+/// Walks every section and instruction of the module, reporting which
+/// proposals it needs so embedders can reject it before translation.
+///
+/// # Errors
+///
+/// Returns a `BinaryReaderError` if any function body or constant
+/// expression is malformed.
+pub fn scan(wasm: &Module) -> Result<FeatureSet> {
+	let mut set = FeatureSet::default();
+
+	set.load_type_list(wasm.type_section());
+	set.load_import_list(wasm);
+	set.load_export_list(wasm);
+	set.load_table_list(wasm);
+	set.load_memory_list(wasm);
+	set.load_global_list(wasm)?;
+	set.load_element_list(wasm)?;
+	set.load_data_list(wasm)?;
+	set.load_code_list(wasm)?;
+
+	set.exceptions |= !wasm.tag_section().is_empty();
+
+	Ok(set)
+}
@@ -0,0 +1,402 @@
+use std::collections::HashMap;
+
+use crate::{
+	node::{
+		BinOpType, Block, Br, BrIf, BrTable, Expression, FuncData, LabelType, ResultList, SetLocal,
+		SetTemporary, Statement, Temporary, Try, UnOpType, Value,
+	},
+	visit::{get_block_list, get_child_list, get_expression_list, Driver, Visitor},
+};
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Var {
+	Local(usize),
+	Temporary(usize),
+}
+
+// Expressions are numbered by the operation and the numbers of its operands,
+// so two with the same number give the same value while the variables they
+// read keep theirs
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+enum Key {
+	Local(usize),
+	Temporary(usize),
+	I32(i32),
+	I64(i64),
+	F32(u32),
+	F64(u64),
+	UnOp(UnOpType, usize),
+	BinOp(BinOpType, usize, usize),
+}
+
+// Only arithmetic on variables and constants that can neither trap nor read
+// memory or globals is numbered, as nothing but writing a variable it reads
+// can change its value
+fn is_operand(expr: &Expression) -> bool {
+	match expr {
+		Expression::GetLocal(_) | Expression::GetTemporary(_) => true,
+		Expression::Value(value) => !matches!(value, Value::V128(_)),
+		_ => is_numbered(expr),
+	}
+}
+
+fn is_numbered(expr: &Expression) -> bool {
+	match expr {
+		Expression::UnOp(v) => expr.is_pure() && is_operand(&v.rhs),
+		Expression::BinOp(v) => expr.is_pure() && is_operand(&v.lhs) && is_operand(&v.rhs),
+		_ => false,
+	}
+}
+
+fn get_read_list(expr: &mut Expression, list: &mut Vec<Var>) {
+	match expr {
+		Expression::GetLocal(v) => list.push(Var::Local(v.var)),
+		Expression::GetTemporary(v) => list.push(Var::Temporary(v.var)),
+		expr => {
+			for child in get_child_list(expr) {
+				get_read_list(child, list);
+			}
+		}
+	}
+}
+
+#[derive(Default)]
+struct WriteList {
+	list: Vec<Var>,
+}
+
+impl WriteList {
+	fn add_result_list(&mut self, list: ResultList) {
+		self.list
+			.extend(list.iter().map(|var| Var::Temporary(var.var)));
+	}
+
+	fn add_br(&mut self, br: Br) {
+		if !br.align.is_aligned() {
+			self.add_result_list(br.align.new_range());
+		}
+	}
+}
+
+impl Visitor for WriteList {
+	fn visit_br(&mut self, br: Br) {
+		self.add_br(br);
+	}
+
+	fn visit_br_table(&mut self, table: &BrTable) {
+		for &br in &table.data {
+			self.add_br(br);
+		}
+
+		self.add_br(table.default);
+	}
+
+	fn visit_br_if(&mut self, br_if: &BrIf) {
+		self.add_br(br_if.target);
+	}
+
+	fn visit_try(&mut self, try_: &Try) {
+		for catch in &try_.catch_list {
+			self.add_result_list(catch.result_list);
+		}
+	}
+
+	fn visit_set_temporary(&mut self, set: &SetTemporary) {
+		self.list.push(Var::Temporary(set.var.var));
+	}
+
+	fn visit_set_local(&mut self, set: &SetLocal) {
+		self.list.push(Var::Local(set.var.var));
+	}
+
+	fn visit_statement(&mut self, stat: &Statement) {
+		if let Some(list) = crate::copy::get_result_list(stat) {
+			self.add_result_list(list);
+		}
+	}
+}
+
+struct Group {
+	read_list: Vec<Var>,
+	count: usize,
+}
+
+// Walks the code in order with the groups of values computed before each
+// point on every path to it, which are those of the blocks around it that are
+// not written since, and gives every operation either the group it finds there
+// or a new one that it starts
+#[derive(Default)]
+struct Numbering {
+	number_map: HashMap<Key, usize>,
+	group_list: Vec<Group>,
+	occurrence_list: Vec<(usize, bool)>,
+}
+
+impl Numbering {
+	fn find_number(&mut self, expr: &Expression) -> usize {
+		let key = match expr {
+			Expression::GetLocal(v) => Key::Local(v.var),
+			Expression::GetTemporary(v) => Key::Temporary(v.var),
+			Expression::Value(Value::I32(v)) => Key::I32(*v),
+			Expression::Value(Value::I64(v)) => Key::I64(*v),
+			Expression::Value(Value::F32(v)) => Key::F32(v.to_bits()),
+			Expression::Value(Value::F64(v)) => Key::F64(v.to_bits()),
+			Expression::UnOp(v) => Key::UnOp(v.op_type, self.find_number(&v.rhs)),
+			Expression::BinOp(v) => {
+				let lhs = self.find_number(&v.lhs);
+				let rhs = self.find_number(&v.rhs);
+
+				Key::BinOp(v.op_type, lhs, rhs)
+			}
+			_ => unreachable!(),
+		};
+
+		let next = self.number_map.len();
+
+		*self.number_map.entry(key).or_insert(next)
+	}
+
+	fn visit_expression(&mut self, expr: &mut Expression, available: &mut HashMap<usize, usize>) {
+		if is_numbered(expr) {
+			let number = self.find_number(expr);
+
+			if let Some(&index) = available.get(&number) {
+				self.group_list[index].count += 1;
+				self.occurrence_list.push((index, false));
+
+				return;
+			}
+
+			let mut read_list = Vec::new();
+
+			get_read_list(expr, &mut read_list);
+
+			self.group_list.push(Group {
+				read_list,
+				count: 1,
+			});
+
+			available.insert(number, self.group_list.len() - 1);
+			self.occurrence_list.push((self.group_list.len() - 1, true));
+		}
+
+		for child in get_child_list(expr) {
+			self.visit_expression(child, available);
+		}
+	}
+
+	fn kill(&self, available: &mut HashMap<usize, usize>, write_list: &WriteList) {
+		available.retain(|_, &mut index| {
+			let read_list = &self.group_list[index].read_list;
+
+			!read_list.iter().any(|var| write_list.list.contains(var))
+		});
+	}
+
+	fn visit_block(&mut self, block: &mut Block, mut available: HashMap<usize, usize>) {
+		// Loops are entered again from their end, after anything in them ran
+		if block.label_type == Some(LabelType::Backward) {
+			let mut write_list = WriteList::default();
+
+			block.accept(&mut write_list);
+			self.kill(&mut available, &write_list);
+		}
+
+		for stat in &mut block.code {
+			for expr in get_expression_list(stat) {
+				self.visit_expression(expr, &mut available);
+			}
+
+			for nested in get_block_list(stat) {
+				self.visit_block(nested, available.clone());
+			}
+
+			let mut write_list = WriteList::default();
+
+			stat.accept(&mut write_list);
+			self.kill(&mut available, &write_list);
+		}
+	}
+}
+
+// Groups computed more than once are set to a new temporary where they are
+// first computed, just before the statement, which every one of them reads
+struct Rewriter {
+	occurrence_list: std::vec::IntoIter<(usize, bool)>,
+	temporary_list: Vec<Option<Temporary>>,
+}
+
+impl Rewriter {
+	fn rewrite_expression(&mut self, expr: &mut Expression, set_list: &mut Vec<Statement>) {
+		if !is_numbered(expr) {
+			for child in get_child_list(expr) {
+				self.rewrite_expression(child, set_list);
+			}
+
+			return;
+		}
+
+		let (index, is_first) = self.occurrence_list.next().unwrap();
+
+		if is_first {
+			for child in get_child_list(expr) {
+				self.rewrite_expression(child, set_list);
+			}
+		}
+
+		if let Some(var) = self.temporary_list[index] {
+			let value = std::mem::replace(expr, Expression::GetTemporary(var));
+
+			if is_first {
+				set_list.push(Statement::SetTemporary(SetTemporary {
+					var,
+					value: value.into(),
+				}));
+			}
+		}
+	}
+
+	fn rewrite_block(&mut self, block: &mut Block) {
+		let code = std::mem::take(&mut block.code);
+		let offset_list = std::mem::take(&mut block.offset_list);
+
+		for (mut stat, offset) in code.into_iter().zip(offset_list) {
+			let mut set_list = Vec::new();
+
+			for expr in get_expression_list(&mut stat) {
+				self.rewrite_expression(expr, &mut set_list);
+			}
+
+			for nested in get_block_list(&mut stat) {
+				self.rewrite_block(nested);
+			}
+
+			for set in set_list {
+				block.code.push(set);
+				block.offset_list.push(offset);
+			}
+
+			block.code.push(stat);
+			block.offset_list.push(offset);
+		}
+	}
+}
+
+/// Computes arithmetic on the same variables and constants only once where
+/// every path to it computes it first in a block around it, and nothing has
+/// written the variables since, keeping the value in a new temporary.
+pub fn eliminate(func: &mut FuncData) {
+	let mut numbering = Numbering::default();
+
+	numbering.visit_block(&mut func.code, HashMap::new());
+
+	let temporary_list = numbering
+		.group_list
+		.iter()
+		.map(|group| {
+			(group.count > 1).then(|| {
+				func.num_stack += 1;
+
+				Temporary {
+					var: func.num_stack - 1,
+				}
+			})
+		})
+		.collect();
+
+	let mut rewriter = Rewriter {
+		occurrence_list: numbering.occurrence_list.into_iter(),
+		temporary_list,
+	};
+
+	rewriter.rewrite_block(&mut func.code);
+}
+
+#[cfg(test)]
+mod test {
+	use crate::node::{
+		BinOp, BinOpType, Block, Expression, FuncData, LabelType, Local, SetLocal, Statement, Value,
+	};
+
+	fn set_local(var: usize, value: Expression) -> Statement {
+		Statement::SetLocal(SetLocal {
+			var: Local { var },
+			value: value.into(),
+		})
+	}
+
+	fn add_one() -> Expression {
+		Expression::BinOp(BinOp {
+			op_type: BinOpType::Add_I32,
+			lhs: Expression::GetLocal(Local { var: 0 }).into(),
+			rhs: Expression::Value(Value::I32(1)).into(),
+		})
+	}
+
+	fn new_block(label_type: Option<LabelType>, code: Vec<Statement>) -> Block {
+		Block {
+			label_type,
+			offset_list: vec![0; code.len()],
+			code,
+			last: None,
+		}
+	}
+
+	// Computes `local 0 + 1` before a loop and again at its start, where the
+	// last statement of the loop writes `var`
+	fn eliminate_with_loop_writing(var: usize) -> FuncData {
+		let body = new_block(
+			Some(LabelType::Backward),
+			vec![
+				set_local(2, add_one()),
+				set_local(var, Expression::Value(Value::I32(5))),
+			],
+		);
+
+		let mut func = FuncData {
+			local_data: Vec::new(),
+			param_data: Vec::new(),
+			num_result: 0,
+			num_stack: 0,
+			code: new_block(None, vec![set_local(1, add_one()), Statement::Block(body)]),
+		};
+
+		super::eliminate(&mut func);
+
+		func
+	}
+
+	#[test]
+	fn reuse_in_loop() {
+		let func = eliminate_with_loop_writing(3);
+
+		assert_eq!(func.num_stack, 1);
+
+		let [Statement::SetTemporary(_), _, Statement::Block(body)] = func.code.code.as_slice()
+		else {
+			panic!("the value should be set once before the loop");
+		};
+
+		assert!(matches!(
+			body.code.as_slice(),
+			[Statement::SetLocal(v), _] if matches!(*v.value, Expression::GetTemporary(_))
+		));
+	}
+
+	#[test]
+	fn kill_on_loop_entry() {
+		let func = eliminate_with_loop_writing(0);
+
+		// The value from before the loop is stale once it runs again
+		assert_eq!(func.num_stack, 0);
+
+		let [_, Statement::Block(body)] = func.code.code.as_slice() else {
+			panic!("the loop should be kept");
+		};
+
+		assert!(matches!(
+			body.code.as_slice(),
+			[Statement::SetLocal(v), _] if matches!(*v.value, Expression::BinOp(_))
+		));
+	}
+}
@@ -0,0 +1,249 @@
+use wasmparser::{ExternalKind, TypeRef, ValType};
+
+use crate::{
+	module::{External, Module},
+	node::{
+		Block, Expression, FuncData, GetGlobal, LabelType, Local, SetGlobal, SetLocal, Statement,
+		Terminator,
+	},
+	visit::{get_block_list, get_child_list, get_expression_list, Driver, Visitor},
+};
+
+fn get_terminator_list(last: &mut Terminator) -> Vec<&mut Expression> {
+	match last {
+		Terminator::BrTable(v) => vec![&mut v.condition],
+		Terminator::Throw(v) => v.param_list.iter_mut().collect(),
+		Terminator::Unreachable | Terminator::Br(_) | Terminator::Rethrow(_) => Vec::new(),
+	}
+}
+
+fn for_each_child<F>(expr: &mut Expression, is_looped: bool, func: &mut F)
+where
+	F: FnMut(&mut Expression, bool),
+{
+	func(expr, is_looped);
+
+	for child in get_child_list(expr) {
+		for_each_child(child, is_looped, func);
+	}
+}
+
+// Calls `func` on every expression of the block and those nested in it, along
+// with whether it is within a loop
+fn for_each_expression<F>(block: &mut Block, is_looped: bool, func: &mut F)
+where
+	F: FnMut(&mut Expression, bool),
+{
+	let is_looped = is_looped || block.label_type == Some(LabelType::Backward);
+
+	for stat in &mut block.code {
+		for expr in get_expression_list(stat) {
+			for_each_child(expr, is_looped, func);
+		}
+
+		for nested in get_block_list(stat) {
+			for_each_expression(nested, is_looped, func);
+		}
+	}
+
+	if let Some(last) = block.last.as_deref_mut() {
+		for expr in get_terminator_list(last) {
+			for_each_child(expr, is_looped, func);
+		}
+	}
+}
+
+struct SetGlobalList<'a> {
+	constant_list: &'a mut [Option<ValType>],
+}
+
+impl Visitor for SetGlobalList<'_> {
+	fn visit_set_global(&mut self, set_global: &SetGlobal) {
+		self.constant_list[set_global.var()] = None;
+	}
+}
+
+// Globals that are immutable, or mutable but neither imported, exported, nor
+// ever set, keep the value they are started with
+fn find_constant_list(wasm: &Module, func_list: &[FuncData]) -> Vec<Option<ValType>> {
+	let import_list = wasm.import_section().iter().filter_map(|v| match v.ty {
+		TypeRef::Global(ty) => Some((!ty.mutable).then_some(ty.content_type)),
+		_ => None,
+	});
+
+	let global_list = wasm
+		.global_section()
+		.iter()
+		.map(|v| Some(v.ty.content_type));
+	let mut constant_list: Vec<_> = import_list.chain(global_list).collect();
+
+	let offset = wasm.import_count(External::Global);
+
+	for export in wasm.export_section() {
+		if export.kind != ExternalKind::Global {
+			continue;
+		}
+
+		let index = usize::try_from(export.index).unwrap();
+		let is_mutable = index
+			.checked_sub(offset)
+			.is_some_and(|index| wasm.global_section()[index].ty.mutable);
+
+		if is_mutable {
+			constant_list[index] = None;
+		}
+	}
+
+	let mut visitor = SetGlobalList {
+		constant_list: &mut constant_list,
+	};
+
+	for func in func_list {
+		func.accept(&mut visitor);
+	}
+
+	constant_list
+}
+
+fn hoist_global_list(func: &mut FuncData, constant_list: &[Option<ValType>]) {
+	let mut read_list = vec![0_usize; constant_list.len()];
+
+	for_each_expression(&mut func.code, false, &mut |expr, is_looped| {
+		if let Expression::GetGlobal(v) = expr {
+			read_list[v.var] += if is_looped { 2 } else { 1 };
+		}
+	});
+
+	let mut local_list = vec![None; constant_list.len()];
+	let mut set_list = Vec::new();
+
+	for (var, typ) in constant_list.iter().enumerate() {
+		let Some(typ) = *typ else {
+			continue;
+		};
+
+		if read_list[var] < 2 {
+			continue;
+		}
+
+		let local = Local {
+			var: func.param_data.len() + func.local_data.len(),
+		};
+
+		func.local_data.push(typ);
+		local_list[var] = Some(local);
+		set_list.push(Statement::SetLocal(SetLocal {
+			var: local,
+			value: Expression::GetGlobal(GetGlobal { var }).into(),
+		}));
+	}
+
+	if set_list.is_empty() {
+		return;
+	}
+
+	for_each_expression(&mut func.code, false, &mut |expr, _| {
+		if let Expression::GetGlobal(v) = expr {
+			if let Some(local) = local_list[v.var] {
+				*expr = Expression::GetLocal(local);
+			}
+		}
+	});
+
+	let offset = func.code.offset_list.first().copied().unwrap_or_default();
+	let len = set_list.len();
+
+	func.code.code.splice(0..0, set_list);
+	func.code
+		.offset_list
+		.splice(0..0, std::iter::repeat(offset).take(len));
+}
+
+/// Reads every global that keeps the value it is started with into a new
+/// local at the start of each function that reads it more than once, or
+/// within a loop, which then reads the local in its place.
+pub fn hoist(wasm: &Module, func_list: &mut [FuncData]) {
+	let constant_list = find_constant_list(wasm, func_list);
+
+	for func in func_list {
+		hoist_global_list(func, &constant_list);
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use crate::{
+		factory::Factory,
+		module::{External, Module, TypeInfo},
+		node::{Expression, FuncData, Local, Statement},
+	};
+
+	fn hoist(text: &str) -> Vec<FuncData> {
+		let data = wat::parse_str(text).unwrap();
+		let wasm = Module::try_from_data(&data).unwrap();
+		let type_info = TypeInfo::from_module(&wasm);
+		let offset = wasm.import_count(External::Func);
+		let mut factory = Factory::from_type_info(&type_info);
+		let mut func_list: Vec<_> = wasm
+			.code_section()
+			.iter()
+			.enumerate()
+			.map(|(i, v)| factory.create_indexed(i + offset, v).unwrap())
+			.collect();
+
+		super::hoist(&wasm, &mut func_list);
+
+		func_list
+	}
+
+	#[test]
+	fn read_unchanged_global_into_local() {
+		let func_list = hoist(
+			"(module
+				(global (mut i32) (i32.const 5))
+				(func (result i32) global.get 0 global.get 0 i32.add)
+			)",
+		);
+
+		let [func] = func_list.as_slice() else {
+			panic!("the module should define one function");
+		};
+
+		assert_eq!(func.local_data.len(), 1);
+
+		let [Statement::SetLocal(read), Statement::SetTemporary(sum)] = func.code.code.as_slice()
+		else {
+			panic!("the global should be read once at the start");
+		};
+
+		assert_eq!(read.var.var, 0);
+		assert!(matches!(*read.value, Expression::GetGlobal(_)));
+		assert!(matches!(
+			sum.value.as_ref(),
+			Expression::BinOp(v) if matches!(*v.lhs, Expression::GetLocal(Local { var: 0 }))
+				&& matches!(*v.rhs, Expression::GetLocal(Local { var: 0 }))
+		));
+	}
+
+	#[test]
+	fn keep_reading_global_that_is_set() {
+		let func_list = hoist(
+			"(module
+				(global (mut i32) (i32.const 5))
+				(func (result i32) global.get 0 global.get 0 i32.add)
+				(func i32.const 1 global.set 0)
+			)",
+		);
+
+		let [func, _] = func_list.as_slice() else {
+			panic!("the module should define two functions");
+		};
+
+		assert!(func.local_data.is_empty());
+		assert!(matches!(
+			func.code.code.as_slice(),
+			[Statement::SetTemporary(v)]
+				if matches!(v.value.as_ref(), Expression::BinOp(v) if matches!(*v.lhs, Expression::GetGlobal(_)))
+		));
+	}
+}
@@ -0,0 +1,454 @@
+use wasmparser::ValType;
+
+use crate::{
+	node::{
+		Align, Block, Call, Expression, FuncData, Local, RefNull, ResultList, SetLocal,
+		SetTemporary, Statement, Temporary, Terminator, Value,
+	},
+	visit::{get_child_list, get_expression_list},
+};
+
+// Bodies of at most this many statements and expressions are substituted,
+// through at most this many calls within each other
+const MAX_SIZE: usize = 16;
+const MAX_DEPTH: usize = 4;
+
+fn get_expression_size(expr: &mut Expression) -> usize {
+	get_child_list(expr)
+		.into_iter()
+		.map(get_expression_size)
+		.sum::<usize>()
+		+ 1
+}
+
+fn get_terminator_size(last: &mut Terminator) -> usize {
+	match last {
+		Terminator::BrTable(v) => get_expression_size(&mut v.condition) + 1,
+		Terminator::Throw(v) => {
+			v.param_list
+				.iter_mut()
+				.map(get_expression_size)
+				.sum::<usize>()
+				+ 1
+		}
+		Terminator::Unreachable | Terminator::Br(_) | Terminator::Rethrow(_) => 1,
+	}
+}
+
+fn get_statement_size(stat: &mut Statement, index: usize) -> Option<usize> {
+	let nested = match stat {
+		Statement::Block(v) => get_block_size(v, index)?,
+		Statement::If(v) => {
+			let on_false = match &mut v.on_false {
+				Some(on_false) => get_block_size(on_false, index)?,
+				None => 0,
+			};
+
+			get_block_size(&mut v.on_true, index)? + on_false
+		}
+		Statement::Call(v) if v.function == index => return None,
+		Statement::Try(_) => return None,
+		_ => 0,
+	};
+
+	let size: usize = get_expression_list(stat)
+		.into_iter()
+		.map(get_expression_size)
+		.sum();
+
+	Some(nested + size + 1)
+}
+
+// Handlers are never moved, nor is code that calls back into its function
+fn get_block_size(block: &mut Block, index: usize) -> Option<usize> {
+	let mut size = block.last.as_deref_mut().map_or(0, get_terminator_size);
+
+	for stat in &mut block.code {
+		size += get_statement_size(stat, index)?;
+	}
+
+	Some(size)
+}
+
+fn get_zero(typ: ValType) -> Expression {
+	let value = match typ {
+		ValType::I32 => Value::I32(0),
+		ValType::I64 => Value::I64(0),
+		ValType::F32 => Value::F32(0.0),
+		ValType::F64 => Value::F64(0.0),
+		ValType::V128 => Value::V128(0),
+		ValType::Ref(v) => {
+			return Expression::RefNull(RefNull {
+				heap_type: v.heap_type(),
+			})
+		}
+	};
+
+	Expression::Value(value)
+}
+
+// Gives the locals and temporaries of a body those of the function that it is
+// substituted into, and every statement the offset of the call
+struct Rename {
+	local_list: Vec<usize>,
+	temporary: usize,
+	offset: usize,
+}
+
+impl Rename {
+	const fn result_list(&self, list: &mut ResultList) {
+		list.start += self.temporary;
+		list.end += self.temporary;
+	}
+
+	const fn align(&self, align: &mut Align) {
+		align.new += self.temporary;
+		align.old += self.temporary;
+	}
+
+	fn expression(&self, expr: &mut Expression) {
+		match expr {
+			Expression::GetTemporary(v) => v.var += self.temporary,
+			Expression::GetLocal(v) => v.var = self.local_list[v.var],
+			_ => {}
+		}
+
+		for child in get_child_list(expr) {
+			self.expression(child);
+		}
+	}
+
+	fn statement(&self, stat: &mut Statement) {
+		for expr in get_expression_list(stat) {
+			self.expression(expr);
+		}
+
+		match stat {
+			Statement::Block(v) => self.block(v),
+			Statement::If(v) => {
+				self.block(&mut v.on_true);
+
+				if let Some(on_false) = &mut v.on_false {
+					self.block(on_false);
+				}
+			}
+			Statement::Try(v) => {
+				self.block(&mut v.code);
+
+				for catch in &mut v.catch_list {
+					self.result_list(&mut catch.result_list);
+					self.block(&mut catch.code);
+				}
+			}
+			Statement::BrIf(v) => self.align(&mut v.target.align),
+			Statement::Call(v) => self.result_list(&mut v.result_list),
+			Statement::CallIndirect(v) => self.result_list(&mut v.result_list),
+			Statement::CallRef(v) => self.result_list(&mut v.result_list),
+			Statement::SetTemporary(v) => v.var.var += self.temporary,
+			Statement::SetLocal(v) => v.var.var = self.local_list[v.var.var],
+			Statement::MemoryGrow(v) => v.result.var += self.temporary,
+			Statement::TableGrow(v) => v.result.var += self.temporary,
+			Statement::AtomicWait(v) => v.result.var += self.temporary,
+			Statement::AtomicNotify(v) => v.result.var += self.temporary,
+			_ => {}
+		}
+	}
+
+	fn block(&self, block: &mut Block) {
+		block.offset_list.fill(self.offset);
+
+		for stat in &mut block.code {
+			self.statement(stat);
+		}
+
+		match block.last.as_deref_mut() {
+			Some(Terminator::Br(v)) => self.align(&mut v.align),
+			Some(Terminator::BrTable(v)) => {
+				self.expression(&mut v.condition);
+
+				for target in v.data.iter_mut().chain(std::iter::once(&mut v.default)) {
+					self.align(&mut target.align);
+				}
+			}
+			Some(Terminator::Throw(v)) => {
+				for param in &mut v.param_list {
+					self.expression(param);
+				}
+			}
+			Some(Terminator::Unreachable | Terminator::Rethrow(_)) | None => {}
+		}
+	}
+}
+
+// Locals added for substituted bodies are shared by those that are not
+// nested in each other, as each one sets all of its own before it runs
+struct Inliner<'a> {
+	callee_list: &'a [Option<FuncData>],
+	offset: usize,
+	chain: Vec<usize>,
+	num_local: usize,
+	local_data: Vec<ValType>,
+	is_used_list: Vec<bool>,
+	num_stack: usize,
+}
+
+impl Inliner<'_> {
+	fn is_inlined(&self, call: &Call) -> bool {
+		call.function
+			.checked_sub(self.offset)
+			.is_some_and(|index| self.callee_list[index].is_some())
+			&& self.chain.len() < MAX_DEPTH
+			&& !self.chain.contains(&call.function)
+	}
+
+	fn take_local(&mut self, typ: ValType) -> usize {
+		let index = (0..self.local_data.len())
+			.find(|&i| !self.is_used_list[i] && self.local_data[i] == typ)
+			.unwrap_or_else(|| {
+				self.local_data.push(typ);
+				self.is_used_list.push(false);
+
+				self.local_data.len() - 1
+			});
+
+		self.is_used_list[index] = true;
+
+		index
+	}
+
+	fn substitute(&mut self, call: Call, offset: usize, block: &mut Block) {
+		let callee = self.callee_list[call.function - self.offset]
+			.as_ref()
+			.unwrap();
+		let taken_list: Vec<_> = callee
+			.param_data
+			.iter()
+			.chain(&callee.local_data)
+			.map(|&typ| self.take_local(typ))
+			.collect();
+
+		let rename = Rename {
+			local_list: taken_list.iter().map(|var| var + self.num_local).collect(),
+			temporary: self.num_stack,
+			offset,
+		};
+
+		let mut body = callee.code.clone();
+
+		rename.block(&mut body);
+
+		self.num_stack += callee.num_stack;
+
+		let zero_list = callee.local_data.iter().map(|&typ| get_zero(typ));
+
+		for (value, &var) in call
+			.param_list
+			.into_iter()
+			.chain(zero_list)
+			.zip(&rename.local_list)
+		{
+			block.code.push(Statement::SetLocal(SetLocal {
+				var: Local { var },
+				value: value.into(),
+			}));
+			block.offset_list.push(offset);
+		}
+
+		self.chain.push(call.function);
+		self.block(&mut body);
+		self.chain.pop();
+
+		for index in taken_list {
+			self.is_used_list[index] = false;
+		}
+
+		if body.label_type.is_none() && body.last.is_none() {
+			block.code.append(&mut body.code);
+			block.offset_list.append(&mut body.offset_list);
+		} else {
+			block.code.push(Statement::Block(body));
+			block.offset_list.push(offset);
+		}
+
+		for (i, var) in call.result_list.iter().enumerate() {
+			let value = Expression::GetTemporary(Temporary {
+				var: rename.temporary + i,
+			});
+
+			block.code.push(Statement::SetTemporary(SetTemporary {
+				var,
+				value: value.into(),
+			}));
+			block.offset_list.push(offset);
+		}
+	}
+
+	fn statement(&mut self, stat: &mut Statement) {
+		match stat {
+			Statement::Block(v) => self.block(v),
+			Statement::If(v) => {
+				self.block(&mut v.on_true);
+
+				if let Some(on_false) = &mut v.on_false {
+					self.block(on_false);
+				}
+			}
+			Statement::Try(v) => {
+				self.block(&mut v.code);
+
+				for catch in &mut v.catch_list {
+					self.block(&mut catch.code);
+				}
+			}
+			_ => {}
+		}
+	}
+
+	fn block(&mut self, block: &mut Block) {
+		let code = std::mem::take(&mut block.code);
+		let offset_list = std::mem::take(&mut block.offset_list);
+
+		for (mut stat, offset) in code.into_iter().zip(offset_list) {
+			self.statement(&mut stat);
+
+			match stat {
+				Statement::Call(v) if self.is_inlined(&v) => self.substitute(v, offset, block),
+				stat => {
+					block.code.push(stat);
+					block.offset_list.push(offset);
+				}
+			}
+		}
+	}
+}
+
+/// Substitutes the bodies of small functions that do not call themselves
+/// into every call to them, where `offset` is the index of the first
+/// function in `func_list` after the imports.
+pub fn inline(func_list: &mut [FuncData], offset: usize) {
+	let callee_list: Vec<_> = func_list
+		.iter_mut()
+		.enumerate()
+		.map(|(i, func)| {
+			let size = get_block_size(&mut func.code, i + offset)?;
+
+			(size <= MAX_SIZE).then(|| func.clone())
+		})
+		.collect();
+
+	for (i, func) in func_list.iter_mut().enumerate() {
+		let mut inliner = Inliner {
+			callee_list: &callee_list,
+			offset,
+			chain: vec![i + offset],
+			num_local: func.param_data.len() + func.local_data.len(),
+			local_data: Vec::new(),
+			is_used_list: Vec::new(),
+			num_stack: func.num_stack,
+		};
+
+		inliner.block(&mut func.code);
+
+		func.local_data.append(&mut inliner.local_data);
+		func.num_stack = inliner.num_stack;
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use wasmparser::ValType;
+
+	use crate::node::{
+		Block, Call, Expression, FuncData, Local, ResultList, SetLocal, SetTemporary, Statement,
+		Temporary, Value,
+	};
+
+	fn get_local(var: usize) -> Box<Expression> {
+		Expression::GetLocal(Local { var }).into()
+	}
+
+	fn new_func(param_data: Vec<ValType>, num_stack: usize, code: Vec<Statement>) -> FuncData {
+		FuncData {
+			local_data: vec![ValType::I32],
+			param_data,
+			num_result: 0,
+			num_stack,
+			code: Block {
+				label_type: None,
+				offset_list: vec![0; code.len()],
+				code,
+				last: None,
+			},
+		}
+	}
+
+	#[test]
+	fn rename_local_and_temporary() {
+		// (param i32) (result i32) (local i32)
+		// local.set 1 (local.get 0); local.get 1
+		let mut callee = new_func(
+			vec![ValType::I32],
+			1,
+			vec![
+				Statement::SetLocal(SetLocal {
+					var: Local { var: 1 },
+					value: get_local(0),
+				}),
+				Statement::SetTemporary(SetTemporary {
+					var: Temporary { var: 0 },
+					value: get_local(1),
+				}),
+			],
+		);
+
+		callee.num_result = 1;
+
+		let caller = new_func(
+			vec![ValType::I32],
+			1,
+			vec![Statement::Call(Call {
+				function: 1,
+				param_list: vec![Expression::Value(Value::I32(7))],
+				result_list: ResultList::new(0, 1),
+			})],
+		);
+
+		let mut func_list = [caller, callee];
+
+		super::inline(&mut func_list, 0);
+
+		let [caller, _] = &func_list;
+
+		// The locals of the callee come after the two of the caller, and its
+		// temporary after the one of the caller
+		assert_eq!(caller.local_data.len(), 3);
+		assert_eq!(caller.num_stack, 2);
+
+		let code = caller.code.code.as_slice();
+		let [Statement::SetLocal(param), Statement::SetLocal(zero), Statement::SetLocal(copy), Statement::SetTemporary(inner), Statement::SetTemporary(result)] =
+			code
+		else {
+			panic!("the call should be replaced by the renamed body");
+		};
+
+		assert_eq!(param.var.var, 2);
+		assert!(matches!(*param.value, Expression::Value(Value::I32(7))));
+		assert_eq!(zero.var.var, 3);
+		assert!(matches!(*zero.value, Expression::Value(Value::I32(0))));
+		assert_eq!(copy.var.var, 3);
+		assert!(matches!(
+			*copy.value,
+			Expression::GetLocal(Local { var: 2 })
+		));
+		assert_eq!(inner.var.var, 1);
+		assert!(matches!(
+			*inner.value,
+			Expression::GetLocal(Local { var: 3 })
+		));
+		assert_eq!(result.var.var, 0);
+		assert!(matches!(
+			*result.value,
+			Expression::GetTemporary(Temporary { var: 1 })
+		));
+	}
+}
@@ -0,0 +1,333 @@
+use crate::{
+	node::{
+		Block, CmpOp, CmpOpType, Expression, FuncData, If, LabelType, Statement, Terminator, Value,
+	},
+	visit::get_block_list,
+};
+
+// Calls `f` with every label that the code of a block refers to and how many
+// labels deep within the block the reference is, so that a target equal to
+// the depth is the block itself
+fn for_each_target(block: &mut Block, depth: usize, f: &mut dyn FnMut(&mut usize, usize)) {
+	for stat in &mut block.code {
+		if let Statement::BrIf(v) = stat {
+			f(&mut v.target.target, depth);
+		}
+
+		for nested in get_block_list(stat) {
+			for_each_target(nested, depth + 1, f);
+		}
+	}
+
+	match block.last.as_deref_mut() {
+		Some(Terminator::Br(v)) => f(&mut v.target, depth),
+		Some(Terminator::BrTable(v)) => {
+			for br in &mut v.data {
+				f(&mut br.target, depth);
+			}
+
+			f(&mut v.default.target, depth);
+		}
+		Some(Terminator::Rethrow(v)) => f(&mut v.target, depth),
+		_ => {}
+	}
+}
+
+fn count_reference(block: &mut Block) -> usize {
+	let mut count = 0;
+
+	for_each_target(block, 0, &mut |target, depth| {
+		if *target == depth {
+			count += 1;
+		}
+	});
+
+	count
+}
+
+// Code moved one label deeper still branches to the same labels outside it
+fn deepen(block: &mut Block) {
+	for_each_target(block, 0, &mut |target, depth| {
+		if *target >= depth {
+			*target += 1;
+		}
+	});
+}
+
+// Code moved out of a block that is never branched to, one label shallower
+fn shallow(block: &mut Block) {
+	for_each_target(block, 0, &mut |target, depth| {
+		if *target > depth {
+			*target -= 1;
+		}
+	});
+}
+
+// Blocks that are never branched to and end without a terminator are only a
+// scope, so their code is moved into the block holding them, which exposes
+// the branches in it to that block
+fn flatten(block: &mut Block) {
+	let code = std::mem::take(&mut block.code);
+	let offset_list = std::mem::take(&mut block.offset_list);
+
+	for (stat, offset) in code.into_iter().zip(offset_list) {
+		match stat {
+			Statement::Block(mut v) if v.label_type.is_none() && v.last.is_none() => {
+				shallow(&mut v);
+
+				block.code.extend(v.code);
+				block.offset_list.extend(v.offset_list);
+			}
+			stat => {
+				block.code.push(stat);
+				block.offset_list.push(offset);
+			}
+		}
+	}
+}
+
+fn is_branch_to(block: &Block, target: usize) -> bool {
+	matches!(block.last.as_deref(), Some(Terminator::Br(v)) if v.target == target && v.align.is_aligned())
+}
+
+// Arms that are never branched to end where the `if` does, so code after it
+// that only they reach can be moved to their end
+fn is_extendable(block: &Block) -> bool {
+	block.label_type.is_none() && block.last.is_none()
+}
+
+enum Reference {
+	BrIf,
+	OnTrue,
+	OnFalse,
+}
+
+fn find_reference_list(code: &[Statement]) -> Vec<(usize, Reference)> {
+	code.iter()
+		.enumerate()
+		.filter_map(|(i, stat)| {
+			let reference = match stat {
+				Statement::BrIf(v) if v.target.target == 0 && v.target.align.is_aligned() => {
+					Reference::BrIf
+				}
+				Statement::If(v) => {
+					let on_false = v.on_false.as_deref();
+
+					if is_branch_to(&v.on_true, 1) && on_false.map_or(true, is_extendable) {
+						Reference::OnTrue
+					} else if on_false.is_some_and(|v| is_branch_to(v, 1))
+						&& is_extendable(&v.on_true)
+					{
+						Reference::OnFalse
+					} else {
+						return None;
+					}
+				}
+				_ => return None,
+			};
+
+			Some((i, reference))
+		})
+		.collect()
+}
+
+// The code after a branch to the end of a block runs exactly when the branch
+// is not taken, so it is moved into an `if` on the other outcome
+fn restructure(block: &mut Block, index: usize, reference: Reference) {
+	let mut tail = Block {
+		label_type: None,
+		code: block.code.split_off(index + 1),
+		offset_list: block.offset_list.split_off(index + 1),
+		last: block.last.take(),
+	};
+
+	deepen(&mut tail);
+
+	let stat = match (reference, block.code.pop().unwrap()) {
+		(Reference::BrIf, Statement::BrIf(v)) => {
+			let mut condition = Expression::CmpOp(CmpOp {
+				op_type: CmpOpType::Eq_I32,
+				lhs: v.condition,
+				rhs: Expression::Value(Value::I32(0)).into(),
+			});
+
+			crate::peephole::rewrite_expression(&mut condition);
+
+			Statement::If(If {
+				condition: condition.into(),
+				on_true: tail.into(),
+				on_false: None,
+				hint: None,
+			})
+		}
+		(Reference::OnTrue, Statement::If(mut v)) => {
+			v.on_true.last = None;
+
+			if let Some(on_false) = &mut v.on_false {
+				extend(on_false, tail);
+			} else {
+				v.on_false = Some(tail.into());
+			}
+
+			Statement::If(v)
+		}
+		(Reference::OnFalse, Statement::If(mut v)) => {
+			v.on_false.as_mut().unwrap().last = None;
+
+			extend(&mut v.on_true, tail);
+
+			Statement::If(v)
+		}
+		_ => unreachable!(),
+	};
+
+	block.code.push(stat);
+}
+
+fn extend(block: &mut Block, tail: Block) {
+	block.code.extend(tail.code);
+	block.offset_list.extend(tail.offset_list);
+	block.last = tail.last;
+}
+
+fn simplify_block(block: &mut Block) {
+	for stat in &mut block.code {
+		for nested in get_block_list(stat) {
+			simplify_block(nested);
+		}
+	}
+
+	flatten(block);
+
+	if block.label_type.is_none() {
+		return;
+	}
+
+	let num_reference = count_reference(block);
+
+	if num_reference != 0 {
+		if block.label_type == Some(LabelType::Backward) {
+			return;
+		}
+
+		let reference_list = find_reference_list(&block.code);
+
+		if reference_list.len() != num_reference {
+			return;
+		}
+
+		// The last comes first, so that the code moved never holds another
+		for (index, reference) in reference_list.into_iter().rev() {
+			restructure(block, index, reference);
+		}
+	}
+
+	block.label_type = None;
+}
+
+/// Drops the labels of blocks that are never branched to, moving the code of
+/// those that are only a scope into the block around them. When every branch
+/// to the end of a block is a `br_if` or ends an arm of an `if` in its own
+/// code, the code after each is moved into an `if` on the other outcome so
+/// that the block no longer needs a label.
+pub fn simplify(func: &mut FuncData) {
+	simplify_block(&mut func.code);
+}
+
+#[cfg(test)]
+mod test {
+	use crate::node::{
+		Align, Block, Br, BrIf, Expression, LabelType, Local, SetLocal, Statement, Value,
+	};
+
+	fn get_local(var: usize) -> Box<Expression> {
+		Expression::GetLocal(Local { var }).into()
+	}
+
+	fn set_local(var: usize) -> Statement {
+		Statement::SetLocal(SetLocal {
+			var: Local { var },
+			value: Expression::Value(Value::I32(1)).into(),
+		})
+	}
+
+	fn br_if(target: usize) -> Statement {
+		Statement::BrIf(BrIf {
+			condition: get_local(0),
+			target: Br {
+				target,
+				align: Align {
+					new: 0,
+					old: 0,
+					length: 0,
+				},
+			},
+		})
+	}
+
+	fn wrap(label_type: LabelType, code: Vec<Statement>) -> Block {
+		let inner = Block {
+			label_type: Some(label_type),
+			offset_list: vec![0; code.len()],
+			code,
+			last: None,
+		};
+
+		Block {
+			label_type: None,
+			code: vec![Statement::Block(inner)],
+			offset_list: vec![0],
+			last: None,
+		}
+	}
+
+	#[test]
+	fn restructure_br_if_to_end() {
+		let mut block = wrap(
+			LabelType::Forward,
+			vec![br_if(0), set_local(1), set_local(2)],
+		);
+
+		super::simplify_block(&mut block);
+
+		let [Statement::If(v)] = block.code.as_slice() else {
+			panic!("the block should become a single `if`");
+		};
+
+		assert!(v.on_false.is_none());
+		assert!(v.on_true.label_type.is_none());
+		assert!(matches!(
+			v.on_true.code.as_slice(),
+			[Statement::SetLocal(a), Statement::SetLocal(b)] if a.var.var == 1 && b.var.var == 2
+		));
+	}
+
+	#[test]
+	fn restructure_keeps_outer_target() {
+		let mut block = wrap(LabelType::Forward, vec![br_if(0), br_if(1)]);
+
+		super::simplify_block(&mut block);
+
+		let [Statement::If(v)] = block.code.as_slice() else {
+			panic!("the block should become a single `if`");
+		};
+
+		// The branch moved into the `if` still targets the block around it
+		assert!(matches!(
+			v.on_true.code.as_slice(),
+			[Statement::BrIf(v)] if v.target.target == 1
+		));
+	}
+
+	#[test]
+	fn restructure_skips_loop() {
+		let mut block = wrap(LabelType::Backward, vec![set_local(1), br_if(0)]);
+
+		super::simplify_block(&mut block);
+
+		assert!(matches!(
+			block.code.as_slice(),
+			[Statement::Block(v)] if v.label_type == Some(LabelType::Backward)
+		));
+	}
+}
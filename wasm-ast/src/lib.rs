@@ -1,6 +1,21 @@
+pub mod dump;
 pub mod factory;
+pub mod features;
 pub mod module;
 pub mod node;
+pub mod optimize;
+pub mod shake;
 pub mod visit;
 
+mod color;
+mod copy;
+mod cse;
+mod devirtualize;
+mod gvn;
+mod hoist;
+mod inline;
+mod label;
+mod local;
+mod peephole;
+mod sink;
 mod stack;
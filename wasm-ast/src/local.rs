@@ -0,0 +1,126 @@
+use crate::{
+	node::{Block, Expression, FuncData, Local, SetLocal, Statement, Terminator},
+	visit::{get_block_list, get_child_list, get_expression_list, Driver, Visitor},
+};
+
+struct LocalUse {
+	num_read_list: Vec<usize>,
+	is_set_list: Vec<bool>,
+}
+
+impl LocalUse {
+	fn new(func: &FuncData) -> Self {
+		let num_local = func.param_data.len() + func.local_data.len();
+		let mut local_use = Self {
+			num_read_list: vec![0; num_local],
+			is_set_list: vec![false; num_local],
+		};
+
+		func.accept(&mut local_use);
+
+		local_use
+	}
+}
+
+impl Visitor for LocalUse {
+	fn visit_get_local(&mut self, local: Local) {
+		self.num_read_list[local.var] += 1;
+	}
+
+	fn visit_set_local(&mut self, set_local: &SetLocal) {
+		self.is_set_list[set_local.var.var] = true;
+	}
+}
+
+// Setting a local that is never read only matters for what computing the
+// value does, so those setting it to a pure value are dropped
+fn remove_dead_set(block: &mut Block, num_read_list: &[usize]) -> bool {
+	let code = std::mem::take(&mut block.code);
+	let offset_list = std::mem::take(&mut block.offset_list);
+	let mut is_removed = false;
+
+	for (mut stat, offset) in code.into_iter().zip(offset_list) {
+		if let Statement::SetLocal(v) = &stat {
+			if num_read_list[v.var.var] == 0 && v.value.is_pure() {
+				is_removed = true;
+
+				continue;
+			}
+		}
+
+		for nested in get_block_list(&mut stat) {
+			is_removed |= remove_dead_set(nested, num_read_list);
+		}
+
+		block.code.push(stat);
+		block.offset_list.push(offset);
+	}
+
+	is_removed
+}
+
+fn rename_expression(expr: &mut Expression, var_list: &[usize]) {
+	if let Expression::GetLocal(v) = expr {
+		v.var = var_list[v.var];
+	}
+
+	for child in get_child_list(expr) {
+		rename_expression(child, var_list);
+	}
+}
+
+fn rename_block(block: &mut Block, var_list: &[usize]) {
+	for stat in &mut block.code {
+		for expr in get_expression_list(stat) {
+			rename_expression(expr, var_list);
+		}
+
+		if let Statement::SetLocal(v) = stat {
+			v.var.var = var_list[v.var.var];
+		}
+
+		for nested in get_block_list(stat) {
+			rename_block(nested, var_list);
+		}
+	}
+
+	match block.last.as_deref_mut() {
+		Some(Terminator::BrTable(v)) => rename_expression(&mut v.condition, var_list),
+		Some(Terminator::Throw(v)) => {
+			for param in &mut v.param_list {
+				rename_expression(param, var_list);
+			}
+		}
+		_ => {}
+	}
+}
+
+/// Drops setting locals that are never read to pure values, then the locals
+/// that are no longer read nor set, numbering the rest in their order.
+pub fn compact(func: &mut FuncData) {
+	let mut local_use = LocalUse::new(func);
+
+	while remove_dead_set(&mut func.code, &local_use.num_read_list) {
+		local_use = LocalUse::new(func);
+	}
+
+	let num_param = func.param_data.len();
+	let mut var_list: Vec<usize> = (0..num_param).collect();
+	let mut local_data = Vec::with_capacity(func.local_data.len());
+
+	for (i, &typ) in func.local_data.iter().enumerate() {
+		let var = num_param + i;
+
+		var_list.push(num_param + local_data.len());
+
+		if local_use.num_read_list[var] != 0 || local_use.is_set_list[var] {
+			local_data.push(typ);
+		}
+	}
+
+	if local_data.len() != func.local_data.len() {
+		rename_block(&mut func.code, &var_list);
+
+		func.local_data = local_data;
+	}
+}
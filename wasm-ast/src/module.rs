@@ -1,8 +1,9 @@
 use std::collections::HashMap;
 
 use wasmparser::{
-	BlockType, Data, Element, Export, ExternalKind, FunctionBody, Global, Import, LocalsReader,
-	MemoryType, Name, NameSectionReader, Parser, Payload, Result, Table, Type, TypeRef, ValType,
+	BinaryReader, BlockType, CustomSectionReader, Data, Element, Export, ExternalKind,
+	FunctionBody, Global, Import, LocalsReader, MemoryType, Name, NameSectionReader, Parser,
+	Payload, Result, Table, TagType, Type, TypeRef, ValType,
 };
 
 #[derive(PartialEq, Eq, Clone, Copy)]
@@ -59,13 +60,16 @@ pub struct Module<'a> {
 	func_section: Vec<u32>,
 	table_section: Vec<Table<'a>>,
 	memory_section: Vec<MemoryType>,
+	tag_section: Vec<TagType>,
 	global_section: Vec<Global<'a>>,
 	export_section: Vec<Export<'a>>,
 	element_section: Vec<Element<'a>>,
 	data_section: Vec<Data<'a>>,
 	code_section: Vec<FunctionBody<'a>>,
 
+	custom_section: Vec<CustomSectionReader<'a>>,
 	name_section: HashMap<u32, &'a str>,
+	branch_hint_section: HashMap<u32, HashMap<usize, bool>>,
 
 	start_section: Option<u32>,
 }
@@ -81,12 +85,15 @@ impl<'a> Module<'a> {
 			func_section: Vec::new(),
 			table_section: Vec::new(),
 			memory_section: Vec::new(),
+			tag_section: Vec::new(),
 			global_section: Vec::new(),
 			export_section: Vec::new(),
 			element_section: Vec::new(),
 			data_section: Vec::new(),
 			code_section: Vec::new(),
+			custom_section: Vec::new(),
 			name_section: HashMap::new(),
+			branch_hint_section: HashMap::new(),
 			start_section: None,
 		};
 
@@ -102,6 +109,7 @@ impl<'a> Module<'a> {
 				Payload::FunctionSection(v) => self.func_section = read_checked(v)?,
 				Payload::TableSection(v) => self.table_section = read_checked(v)?,
 				Payload::MemorySection(v) => self.memory_section = read_checked(v)?,
+				Payload::TagSection(v) => self.tag_section = read_checked(v)?,
 				Payload::GlobalSection(v) => self.global_section = read_checked(v)?,
 				Payload::ExportSection(v) => self.export_section = read_checked(v)?,
 				Payload::ElementSection(v) => self.element_section = read_checked(v)?,
@@ -112,15 +120,14 @@ impl<'a> Module<'a> {
 				Payload::StartSection { func, .. } => {
 					self.start_section = Some(func);
 				}
-				Payload::CustomSection(v) if v.name() == "name" => {
-					for name in NameSectionReader::new(v.data(), v.data_offset()) {
-						if let Name::Function(map) = name? {
-							let mut iter = map.into_iter();
-							while let Some(Ok(elem)) = iter.next() {
-								self.name_section.insert(elem.index, elem.name);
-							}
-						}
+				Payload::CustomSection(v) => {
+					match v.name() {
+						"name" => self.load_name_section(&v)?,
+						"metadata.code.branch_hint" => self.load_branch_hint_section(&v)?,
+						_ => {}
 					}
+
+					self.custom_section.push(v);
 				}
 				_ => {}
 			}
@@ -129,6 +136,42 @@ impl<'a> Module<'a> {
 		Ok(())
 	}
 
+	fn load_name_section(&mut self, v: &CustomSectionReader<'a>) -> Result<()> {
+		for name in NameSectionReader::new(v.data(), v.data_offset()) {
+			if let Name::Function(map) = name? {
+				let mut iter = map.into_iter();
+				while let Some(Ok(elem)) = iter.next() {
+					self.name_section.insert(elem.index, elem.name);
+				}
+			}
+		}
+
+		Ok(())
+	}
+
+	// Hints are keyed by function index, then by the byte offset of the
+	// `if` or `br_if` relative to the start of the function body
+	fn load_branch_hint_section(&mut self, v: &CustomSectionReader<'a>) -> Result<()> {
+		let mut reader = BinaryReader::new_with_offset(v.data(), v.data_offset());
+
+		for _ in 0..reader.read_var_u32()? {
+			let func = reader.read_var_u32()?;
+			let mut hint_list = HashMap::new();
+
+			for _ in 0..reader.read_var_u32()? {
+				let offset = reader.read_var_u32()?;
+				let size = reader.read_var_u32()?;
+				let value = reader.read_bytes(size.try_into().unwrap())?;
+
+				hint_list.insert(offset.try_into().unwrap(), value == [1]);
+			}
+
+			self.branch_hint_section.insert(func, hint_list);
+		}
+
+		Ok(())
+	}
+
 	#[must_use]
 	pub fn import_count(&self, ext: External) -> usize {
 		let predicate = |v: &&Import| External::from(v.ty) == ext;
@@ -156,13 +199,18 @@ impl<'a> Module<'a> {
 		self.import_count(External::Global) + self.global_section.len()
 	}
 
+	#[must_use]
+	pub fn tag_space(&self) -> usize {
+		self.import_count(External::Tag) + self.tag_section.len()
+	}
+
 	#[must_use]
 	pub fn type_section(&self) -> &[Type] {
 		&self.type_section
 	}
 
 	#[must_use]
-	pub fn import_section(&self) -> &[Import] {
+	pub fn import_section(&self) -> &[Import<'_>] {
 		&self.import_section
 	}
 
@@ -172,7 +220,7 @@ impl<'a> Module<'a> {
 	}
 
 	#[must_use]
-	pub fn table_section(&self) -> &[Table] {
+	pub fn table_section(&self) -> &[Table<'_>] {
 		&self.table_section
 	}
 
@@ -182,35 +230,52 @@ impl<'a> Module<'a> {
 	}
 
 	#[must_use]
-	pub fn global_section(&self) -> &[Global] {
+	pub fn tag_section(&self) -> &[TagType] {
+		&self.tag_section
+	}
+
+	#[must_use]
+	pub fn global_section(&self) -> &[Global<'_>] {
 		&self.global_section
 	}
 
 	#[must_use]
-	pub fn export_section(&self) -> &[Export] {
+	pub fn export_section(&self) -> &[Export<'_>] {
 		&self.export_section
 	}
 
 	#[must_use]
-	pub fn element_section(&self) -> &[Element] {
+	pub fn element_section(&self) -> &[Element<'_>] {
 		&self.element_section
 	}
 
 	#[must_use]
-	pub fn data_section(&self) -> &[Data] {
+	pub fn data_section(&self) -> &[Data<'_>] {
 		&self.data_section
 	}
 
 	#[must_use]
-	pub fn code_section(&self) -> &[FunctionBody] {
+	pub fn code_section(&self) -> &[FunctionBody<'_>] {
 		&self.code_section
 	}
 
+	/// Every custom section in the order it appeared, including the `name`
+	/// section which is also decoded into `name_section`.
+	#[must_use]
+	pub fn custom_section(&self) -> &[CustomSectionReader<'a>] {
+		&self.custom_section
+	}
+
 	#[must_use]
 	pub const fn name_section(&self) -> &HashMap<u32, &'a str> {
 		&self.name_section
 	}
 
+	#[must_use]
+	pub const fn branch_hint_section(&self) -> &HashMap<u32, HashMap<usize, bool>> {
+		&self.branch_hint_section
+	}
+
 	#[must_use]
 	pub const fn start_section(&self) -> Option<u32> {
 		self.start_section
@@ -220,6 +285,9 @@ impl<'a> Module<'a> {
 pub struct TypeInfo<'a> {
 	type_list: &'a [Type],
 	func_list: Vec<usize>,
+	memory_list: Vec<MemoryType>,
+	tag_list: Vec<usize>,
+	branch_hint_list: &'a HashMap<u32, HashMap<usize, bool>>,
 }
 
 impl<'a> TypeInfo<'a> {
@@ -228,10 +296,15 @@ impl<'a> TypeInfo<'a> {
 		let mut temp = Self {
 			type_list: &wasm.type_section,
 			func_list: Vec::new(),
+			memory_list: Vec::new(),
+			tag_list: Vec::new(),
+			branch_hint_list: &wasm.branch_hint_section,
 		};
 
 		temp.load_import_list(&wasm.import_section);
 		temp.load_func_list(&wasm.func_section);
+		temp.memory_list.extend_from_slice(&wasm.memory_section);
+		temp.load_tag_list(&wasm.tag_section);
 		temp
 	}
 
@@ -246,6 +319,23 @@ impl<'a> TypeInfo<'a> {
 			.map(|v| usize::try_from(v).unwrap());
 
 		self.func_list.extend(iter);
+
+		let iter = list.iter().filter_map(|v| match v.ty {
+			TypeRef::Memory(v) => Some(v),
+			_ => None,
+		});
+
+		self.memory_list.extend(iter);
+
+		let iter = list
+			.iter()
+			.filter_map(|v| match v.ty {
+				TypeRef::Tag(v) => Some(v.func_type_idx),
+				_ => None,
+			})
+			.map(|v| usize::try_from(v).unwrap());
+
+		self.tag_list.extend(iter);
 	}
 
 	fn load_func_list(&mut self, list: &[u32]) {
@@ -254,6 +344,14 @@ impl<'a> TypeInfo<'a> {
 		self.func_list.extend(iter);
 	}
 
+	fn load_tag_list(&mut self, list: &[TagType]) {
+		let iter = list
+			.iter()
+			.map(|v| usize::try_from(v.func_type_idx).unwrap());
+
+		self.tag_list.extend(iter);
+	}
+
 	pub(crate) fn by_type_index(&self, index: usize) -> (usize, usize) {
 		let Type::Func(ty) = &self.type_list[index] else {
 			unreachable!("type at func index must be a func type");
@@ -268,6 +366,29 @@ impl<'a> TypeInfo<'a> {
 		self.by_type_index(adjusted)
 	}
 
+	pub(crate) fn by_func_index_params(&self, index: usize) -> &[ValType] {
+		let Type::Func(ty) = &self.type_list[self.func_list[index]] else {
+			unreachable!("type at func index must be a func type");
+		};
+
+		ty.params()
+	}
+
+	pub(crate) fn by_tag_index(&self, index: usize) -> usize {
+		let adjusted = self.tag_list[index];
+
+		self.by_type_index(adjusted).0
+	}
+
+	pub(crate) fn by_branch_hint(&self, index: usize) -> Option<&HashMap<usize, bool>> {
+		self.branch_hint_list.get(&index.try_into().unwrap())
+	}
+
+	#[must_use]
+	pub fn is_memory_64(&self, index: usize) -> bool {
+		self.memory_list[index].memory64
+	}
+
 	pub(crate) fn by_block_type(&self, ty: BlockType) -> (usize, usize) {
 		match ty {
 			BlockType::Empty => (0, 0),
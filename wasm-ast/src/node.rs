@@ -1,7 +1,7 @@
-use wasmparser::{Operator, ValType};
+use wasmparser::{HeapType, Operator, ValType};
 
 #[allow(non_camel_case_types)]
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum LoadType {
 	I32,
 	I64,
@@ -17,6 +17,7 @@ pub enum LoadType {
 	I64_U16,
 	I64_I32,
 	I64_U32,
+	V128,
 }
 
 impl TryFrom<&Operator<'_>> for LoadType {
@@ -38,6 +39,7 @@ impl TryFrom<&Operator<'_>> for LoadType {
 			Operator::I64Load16U { .. } => Self::I64_U16,
 			Operator::I64Load32S { .. } => Self::I64_I32,
 			Operator::I64Load32U { .. } => Self::I64_U32,
+			Operator::V128Load { .. } => Self::V128,
 			_ => return Err(()),
 		};
 
@@ -46,7 +48,7 @@ impl TryFrom<&Operator<'_>> for LoadType {
 }
 
 #[allow(non_camel_case_types)]
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug)]
 pub enum StoreType {
 	I32,
 	I64,
@@ -57,6 +59,7 @@ pub enum StoreType {
 	I64_N8,
 	I64_N16,
 	I64_N32,
+	V128,
 }
 
 impl TryFrom<&Operator<'_>> for StoreType {
@@ -73,6 +76,7 @@ impl TryFrom<&Operator<'_>> for StoreType {
 			Operator::I64Store8 { .. } => Self::I64_N8,
 			Operator::I64Store16 { .. } => Self::I64_N16,
 			Operator::I64Store32 { .. } => Self::I64_N32,
+			Operator::V128Store { .. } => Self::V128,
 			_ => return Err(()),
 		};
 
@@ -83,7 +87,7 @@ impl TryFrom<&Operator<'_>> for StoreType {
 // Order of mnemonics is:
 // operation_result_parameter
 #[allow(non_camel_case_types)]
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum UnOpType {
 	Clz_I32,
 	Ctz_I32,
@@ -216,7 +220,7 @@ impl TryFrom<&Operator<'_>> for UnOpType {
 }
 
 #[allow(non_camel_case_types)]
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum BinOpType {
 	Add_I32,
 	Sub_I32,
@@ -323,7 +327,7 @@ impl TryFrom<&Operator<'_>> for BinOpType {
 }
 
 #[allow(non_camel_case_types)]
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug)]
 pub enum CmpOpType {
 	Eq_I32,
 	Ne_I32,
@@ -405,13 +409,276 @@ impl TryFrom<&Operator<'_>> for CmpOpType {
 	}
 }
 
+macro_rules! impl_simd_op_type {
+	($($name:ident => $text:literal, $num_param:literal;)*) => {
+		#[derive(Clone, Copy)]
+		pub enum SimdOpType {
+			$($name,)*
+		}
+
+		impl SimdOpType {
+			#[must_use]
+			pub const fn name(self) -> &'static str {
+				match self {
+					$(Self::$name => $text,)*
+				}
+			}
+
+			#[must_use]
+			pub const fn num_param(self) -> usize {
+				match self {
+					$(Self::$name => $num_param,)*
+				}
+			}
+		}
+
+		impl TryFrom<&Operator<'_>> for SimdOpType {
+			type Error = ();
+
+			fn try_from(inst: &Operator) -> Result<Self, Self::Error> {
+				let result = match inst {
+					$(Operator::$name { .. } => Self::$name,)*
+					_ => return Err(()),
+				};
+
+				Ok(result)
+			}
+		}
+	};
+}
+
+// Vector operations are emulated by the runtime, so each one only needs
+// a name and the number of operands it takes off the stack
+impl_simd_op_type! {
+	I8x16Shuffle => "i8x16_shuffle", 2;
+	I8x16ExtractLaneS => "i8x16_extract_lane_s", 1;
+	I8x16ExtractLaneU => "i8x16_extract_lane_u", 1;
+	I8x16ReplaceLane => "i8x16_replace_lane", 2;
+	I16x8ExtractLaneS => "i16x8_extract_lane_s", 1;
+	I16x8ExtractLaneU => "i16x8_extract_lane_u", 1;
+	I16x8ReplaceLane => "i16x8_replace_lane", 2;
+	I32x4ExtractLane => "i32x4_extract_lane", 1;
+	I32x4ReplaceLane => "i32x4_replace_lane", 2;
+	I64x2ExtractLane => "i64x2_extract_lane", 1;
+	I64x2ReplaceLane => "i64x2_replace_lane", 2;
+	F32x4ExtractLane => "f32x4_extract_lane", 1;
+	F32x4ReplaceLane => "f32x4_replace_lane", 2;
+	F64x2ExtractLane => "f64x2_extract_lane", 1;
+	F64x2ReplaceLane => "f64x2_replace_lane", 2;
+	I8x16Swizzle => "i8x16_swizzle", 2;
+	I8x16Splat => "i8x16_splat", 1;
+	I16x8Splat => "i16x8_splat", 1;
+	I32x4Splat => "i32x4_splat", 1;
+	I64x2Splat => "i64x2_splat", 1;
+	F32x4Splat => "f32x4_splat", 1;
+	F64x2Splat => "f64x2_splat", 1;
+	I8x16Eq => "i8x16_eq", 2;
+	I8x16Ne => "i8x16_ne", 2;
+	I8x16LtS => "i8x16_lt_s", 2;
+	I8x16LtU => "i8x16_lt_u", 2;
+	I8x16GtS => "i8x16_gt_s", 2;
+	I8x16GtU => "i8x16_gt_u", 2;
+	I8x16LeS => "i8x16_le_s", 2;
+	I8x16LeU => "i8x16_le_u", 2;
+	I8x16GeS => "i8x16_ge_s", 2;
+	I8x16GeU => "i8x16_ge_u", 2;
+	I16x8Eq => "i16x8_eq", 2;
+	I16x8Ne => "i16x8_ne", 2;
+	I16x8LtS => "i16x8_lt_s", 2;
+	I16x8LtU => "i16x8_lt_u", 2;
+	I16x8GtS => "i16x8_gt_s", 2;
+	I16x8GtU => "i16x8_gt_u", 2;
+	I16x8LeS => "i16x8_le_s", 2;
+	I16x8LeU => "i16x8_le_u", 2;
+	I16x8GeS => "i16x8_ge_s", 2;
+	I16x8GeU => "i16x8_ge_u", 2;
+	I32x4Eq => "i32x4_eq", 2;
+	I32x4Ne => "i32x4_ne", 2;
+	I32x4LtS => "i32x4_lt_s", 2;
+	I32x4LtU => "i32x4_lt_u", 2;
+	I32x4GtS => "i32x4_gt_s", 2;
+	I32x4GtU => "i32x4_gt_u", 2;
+	I32x4LeS => "i32x4_le_s", 2;
+	I32x4LeU => "i32x4_le_u", 2;
+	I32x4GeS => "i32x4_ge_s", 2;
+	I32x4GeU => "i32x4_ge_u", 2;
+	I64x2Eq => "i64x2_eq", 2;
+	I64x2Ne => "i64x2_ne", 2;
+	I64x2LtS => "i64x2_lt_s", 2;
+	I64x2GtS => "i64x2_gt_s", 2;
+	I64x2LeS => "i64x2_le_s", 2;
+	I64x2GeS => "i64x2_ge_s", 2;
+	F32x4Eq => "f32x4_eq", 2;
+	F32x4Ne => "f32x4_ne", 2;
+	F32x4Lt => "f32x4_lt", 2;
+	F32x4Gt => "f32x4_gt", 2;
+	F32x4Le => "f32x4_le", 2;
+	F32x4Ge => "f32x4_ge", 2;
+	F64x2Eq => "f64x2_eq", 2;
+	F64x2Ne => "f64x2_ne", 2;
+	F64x2Lt => "f64x2_lt", 2;
+	F64x2Gt => "f64x2_gt", 2;
+	F64x2Le => "f64x2_le", 2;
+	F64x2Ge => "f64x2_ge", 2;
+	V128Not => "v128_not", 1;
+	V128And => "v128_and", 2;
+	V128AndNot => "v128_andnot", 2;
+	V128Or => "v128_or", 2;
+	V128Xor => "v128_xor", 2;
+	V128Bitselect => "v128_bitselect", 3;
+	V128AnyTrue => "v128_any_true", 1;
+	I8x16Abs => "i8x16_abs", 1;
+	I8x16Neg => "i8x16_neg", 1;
+	I8x16Popcnt => "i8x16_popcnt", 1;
+	I8x16AllTrue => "i8x16_all_true", 1;
+	I8x16Bitmask => "i8x16_bitmask", 1;
+	I8x16NarrowI16x8S => "i8x16_narrow_i16x8_s", 2;
+	I8x16NarrowI16x8U => "i8x16_narrow_i16x8_u", 2;
+	I8x16Shl => "i8x16_shl", 2;
+	I8x16ShrS => "i8x16_shr_s", 2;
+	I8x16ShrU => "i8x16_shr_u", 2;
+	I8x16Add => "i8x16_add", 2;
+	I8x16AddSatS => "i8x16_add_sat_s", 2;
+	I8x16AddSatU => "i8x16_add_sat_u", 2;
+	I8x16Sub => "i8x16_sub", 2;
+	I8x16SubSatS => "i8x16_sub_sat_s", 2;
+	I8x16SubSatU => "i8x16_sub_sat_u", 2;
+	I8x16MinS => "i8x16_min_s", 2;
+	I8x16MinU => "i8x16_min_u", 2;
+	I8x16MaxS => "i8x16_max_s", 2;
+	I8x16MaxU => "i8x16_max_u", 2;
+	I8x16AvgrU => "i8x16_avgr_u", 2;
+	I16x8ExtAddPairwiseI8x16S => "i16x8_extadd_pairwise_i8x16_s", 1;
+	I16x8ExtAddPairwiseI8x16U => "i16x8_extadd_pairwise_i8x16_u", 1;
+	I16x8Abs => "i16x8_abs", 1;
+	I16x8Neg => "i16x8_neg", 1;
+	I16x8Q15MulrSatS => "i16x8_q15mulr_sat_s", 2;
+	I16x8AllTrue => "i16x8_all_true", 1;
+	I16x8Bitmask => "i16x8_bitmask", 1;
+	I16x8NarrowI32x4S => "i16x8_narrow_i32x4_s", 2;
+	I16x8NarrowI32x4U => "i16x8_narrow_i32x4_u", 2;
+	I16x8ExtendLowI8x16S => "i16x8_extend_low_i8x16_s", 1;
+	I16x8ExtendHighI8x16S => "i16x8_extend_high_i8x16_s", 1;
+	I16x8ExtendLowI8x16U => "i16x8_extend_low_i8x16_u", 1;
+	I16x8ExtendHighI8x16U => "i16x8_extend_high_i8x16_u", 1;
+	I16x8Shl => "i16x8_shl", 2;
+	I16x8ShrS => "i16x8_shr_s", 2;
+	I16x8ShrU => "i16x8_shr_u", 2;
+	I16x8Add => "i16x8_add", 2;
+	I16x8AddSatS => "i16x8_add_sat_s", 2;
+	I16x8AddSatU => "i16x8_add_sat_u", 2;
+	I16x8Sub => "i16x8_sub", 2;
+	I16x8SubSatS => "i16x8_sub_sat_s", 2;
+	I16x8SubSatU => "i16x8_sub_sat_u", 2;
+	I16x8Mul => "i16x8_mul", 2;
+	I16x8MinS => "i16x8_min_s", 2;
+	I16x8MinU => "i16x8_min_u", 2;
+	I16x8MaxS => "i16x8_max_s", 2;
+	I16x8MaxU => "i16x8_max_u", 2;
+	I16x8AvgrU => "i16x8_avgr_u", 2;
+	I16x8ExtMulLowI8x16S => "i16x8_extmul_low_i8x16_s", 2;
+	I16x8ExtMulHighI8x16S => "i16x8_extmul_high_i8x16_s", 2;
+	I16x8ExtMulLowI8x16U => "i16x8_extmul_low_i8x16_u", 2;
+	I16x8ExtMulHighI8x16U => "i16x8_extmul_high_i8x16_u", 2;
+	I32x4ExtAddPairwiseI16x8S => "i32x4_extadd_pairwise_i16x8_s", 1;
+	I32x4ExtAddPairwiseI16x8U => "i32x4_extadd_pairwise_i16x8_u", 1;
+	I32x4Abs => "i32x4_abs", 1;
+	I32x4Neg => "i32x4_neg", 1;
+	I32x4AllTrue => "i32x4_all_true", 1;
+	I32x4Bitmask => "i32x4_bitmask", 1;
+	I32x4ExtendLowI16x8S => "i32x4_extend_low_i16x8_s", 1;
+	I32x4ExtendHighI16x8S => "i32x4_extend_high_i16x8_s", 1;
+	I32x4ExtendLowI16x8U => "i32x4_extend_low_i16x8_u", 1;
+	I32x4ExtendHighI16x8U => "i32x4_extend_high_i16x8_u", 1;
+	I32x4Shl => "i32x4_shl", 2;
+	I32x4ShrS => "i32x4_shr_s", 2;
+	I32x4ShrU => "i32x4_shr_u", 2;
+	I32x4Add => "i32x4_add", 2;
+	I32x4Sub => "i32x4_sub", 2;
+	I32x4Mul => "i32x4_mul", 2;
+	I32x4MinS => "i32x4_min_s", 2;
+	I32x4MinU => "i32x4_min_u", 2;
+	I32x4MaxS => "i32x4_max_s", 2;
+	I32x4MaxU => "i32x4_max_u", 2;
+	I32x4DotI16x8S => "i32x4_dot_i16x8_s", 2;
+	I32x4ExtMulLowI16x8S => "i32x4_extmul_low_i16x8_s", 2;
+	I32x4ExtMulHighI16x8S => "i32x4_extmul_high_i16x8_s", 2;
+	I32x4ExtMulLowI16x8U => "i32x4_extmul_low_i16x8_u", 2;
+	I32x4ExtMulHighI16x8U => "i32x4_extmul_high_i16x8_u", 2;
+	I64x2Abs => "i64x2_abs", 1;
+	I64x2Neg => "i64x2_neg", 1;
+	I64x2AllTrue => "i64x2_all_true", 1;
+	I64x2Bitmask => "i64x2_bitmask", 1;
+	I64x2ExtendLowI32x4S => "i64x2_extend_low_i32x4_s", 1;
+	I64x2ExtendHighI32x4S => "i64x2_extend_high_i32x4_s", 1;
+	I64x2ExtendLowI32x4U => "i64x2_extend_low_i32x4_u", 1;
+	I64x2ExtendHighI32x4U => "i64x2_extend_high_i32x4_u", 1;
+	I64x2Shl => "i64x2_shl", 2;
+	I64x2ShrS => "i64x2_shr_s", 2;
+	I64x2ShrU => "i64x2_shr_u", 2;
+	I64x2Add => "i64x2_add", 2;
+	I64x2Sub => "i64x2_sub", 2;
+	I64x2Mul => "i64x2_mul", 2;
+	I64x2ExtMulLowI32x4S => "i64x2_extmul_low_i32x4_s", 2;
+	I64x2ExtMulHighI32x4S => "i64x2_extmul_high_i32x4_s", 2;
+	I64x2ExtMulLowI32x4U => "i64x2_extmul_low_i32x4_u", 2;
+	I64x2ExtMulHighI32x4U => "i64x2_extmul_high_i32x4_u", 2;
+	F32x4Ceil => "f32x4_ceil", 1;
+	F32x4Floor => "f32x4_floor", 1;
+	F32x4Trunc => "f32x4_trunc", 1;
+	F32x4Nearest => "f32x4_nearest", 1;
+	F32x4Abs => "f32x4_abs", 1;
+	F32x4Neg => "f32x4_neg", 1;
+	F32x4Sqrt => "f32x4_sqrt", 1;
+	F32x4Add => "f32x4_add", 2;
+	F32x4Sub => "f32x4_sub", 2;
+	F32x4Mul => "f32x4_mul", 2;
+	F32x4Div => "f32x4_div", 2;
+	F32x4Min => "f32x4_min", 2;
+	F32x4Max => "f32x4_max", 2;
+	F32x4PMin => "f32x4_pmin", 2;
+	F32x4PMax => "f32x4_pmax", 2;
+	F64x2Ceil => "f64x2_ceil", 1;
+	F64x2Floor => "f64x2_floor", 1;
+	F64x2Trunc => "f64x2_trunc", 1;
+	F64x2Nearest => "f64x2_nearest", 1;
+	F64x2Abs => "f64x2_abs", 1;
+	F64x2Neg => "f64x2_neg", 1;
+	F64x2Sqrt => "f64x2_sqrt", 1;
+	F64x2Add => "f64x2_add", 2;
+	F64x2Sub => "f64x2_sub", 2;
+	F64x2Mul => "f64x2_mul", 2;
+	F64x2Div => "f64x2_div", 2;
+	F64x2Min => "f64x2_min", 2;
+	F64x2Max => "f64x2_max", 2;
+	F64x2PMin => "f64x2_pmin", 2;
+	F64x2PMax => "f64x2_pmax", 2;
+	I32x4TruncSatF32x4S => "i32x4_trunc_sat_f32x4_s", 1;
+	I32x4TruncSatF32x4U => "i32x4_trunc_sat_f32x4_u", 1;
+	F32x4ConvertI32x4S => "f32x4_convert_i32x4_s", 1;
+	F32x4ConvertI32x4U => "f32x4_convert_i32x4_u", 1;
+	I32x4TruncSatF64x2SZero => "i32x4_trunc_sat_f64x2_s_zero", 1;
+	I32x4TruncSatF64x2UZero => "i32x4_trunc_sat_f64x2_u_zero", 1;
+	F64x2ConvertLowI32x4S => "f64x2_convert_low_i32x4_s", 1;
+	F64x2ConvertLowI32x4U => "f64x2_convert_low_i32x4_u", 1;
+	F32x4DemoteF64x2Zero => "f32x4_demote_f64x2_zero", 1;
+	F64x2PromoteLowF32x4 => "f64x2_promote_low_f32x4", 1;
+}
+
+#[derive(Clone)]
 pub struct Select {
+	pub(crate) ty: Option<ValType>,
 	pub(crate) condition: Box<Expression>,
 	pub(crate) on_true: Box<Expression>,
 	pub(crate) on_false: Box<Expression>,
 }
 
 impl Select {
+	#[must_use]
+	pub const fn ty(&self) -> Option<ValType> {
+		self.ty
+	}
+
 	#[must_use]
 	pub const fn condition(&self) -> &Expression {
 		&self.condition
@@ -464,10 +731,11 @@ impl GetGlobal {
 	}
 }
 
+#[derive(Clone)]
 pub struct LoadAt {
 	pub(crate) load_type: LoadType,
 	pub(crate) memory: usize,
-	pub(crate) offset: u32,
+	pub(crate) offset: u64,
 	pub(crate) pointer: Box<Expression>,
 }
 
@@ -483,7 +751,7 @@ impl LoadAt {
 	}
 
 	#[must_use]
-	pub const fn offset(&self) -> u32 {
+	pub const fn offset(&self) -> u64 {
 		self.offset
 	}
 
@@ -496,6 +764,7 @@ impl LoadAt {
 #[derive(Clone, Copy)]
 pub struct MemorySize {
 	pub(crate) memory: usize,
+	pub(crate) is_64: bool,
 }
 
 impl MemorySize {
@@ -503,6 +772,43 @@ impl MemorySize {
 	pub const fn memory(&self) -> usize {
 		self.memory
 	}
+
+	/// Whether the memory is indexed by `i64`, which counts its pages in
+	/// `i64` too.
+	#[must_use]
+	pub const fn is_64(&self) -> bool {
+		self.is_64
+	}
+}
+
+#[derive(Clone)]
+pub struct TableGet {
+	pub(crate) table: usize,
+	pub(crate) index: Box<Expression>,
+}
+
+impl TableGet {
+	#[must_use]
+	pub const fn table(&self) -> usize {
+		self.table
+	}
+
+	#[must_use]
+	pub const fn index(&self) -> &Expression {
+		&self.index
+	}
+}
+
+#[derive(Clone)]
+pub struct TableSize {
+	pub(crate) table: usize,
+}
+
+impl TableSize {
+	#[must_use]
+	pub const fn table(&self) -> usize {
+		self.table
+	}
 }
 
 #[derive(Clone, Copy)]
@@ -511,6 +817,7 @@ pub enum Value {
 	I64(i64),
 	F32(f32),
 	F64(f64),
+	V128(u128),
 }
 
 impl From<i32> for Value {
@@ -537,6 +844,79 @@ impl From<u64> for Value {
 	}
 }
 
+impl From<u128> for Value {
+	fn from(value: u128) -> Self {
+		Self::V128(value)
+	}
+}
+
+#[derive(Clone, Copy)]
+pub struct RefNull {
+	pub(crate) heap_type: HeapType,
+}
+
+impl RefNull {
+	#[must_use]
+	pub const fn heap_type(self) -> HeapType {
+		self.heap_type
+	}
+}
+
+#[derive(Clone, Copy)]
+pub struct RefFunc {
+	pub(crate) function: usize,
+}
+
+impl RefFunc {
+	#[must_use]
+	pub const fn function(self) -> usize {
+		self.function
+	}
+}
+
+#[derive(Clone)]
+pub struct RefAsNonNull {
+	pub(crate) reference: Box<Expression>,
+}
+
+impl RefAsNonNull {
+	#[must_use]
+	pub const fn reference(&self) -> &Expression {
+		&self.reference
+	}
+}
+
+#[derive(Clone)]
+pub struct RefIsNull {
+	pub(crate) reference: Box<Expression>,
+}
+
+impl RefIsNull {
+	#[must_use]
+	pub const fn reference(&self) -> &Expression {
+		&self.reference
+	}
+}
+
+#[derive(Clone)]
+pub struct SimdOp {
+	pub(crate) op_type: SimdOpType,
+	pub(crate) param_list: Vec<Expression>,
+}
+
+impl SimdOp {
+	#[must_use]
+	pub const fn op_type(&self) -> SimdOpType {
+		self.op_type
+	}
+
+	#[must_use]
+	pub fn param_list(&self) -> &[Expression] {
+		&self.param_list
+	}
+}
+
+#[derive(Clone)]
 pub struct UnOp {
 	pub(crate) op_type: UnOpType,
 	pub(crate) rhs: Box<Expression>,
@@ -554,6 +934,7 @@ impl UnOp {
 	}
 }
 
+#[derive(Clone)]
 pub struct BinOp {
 	pub(crate) op_type: BinOpType,
 	pub(crate) lhs: Box<Expression>,
@@ -577,6 +958,7 @@ impl BinOp {
 	}
 }
 
+#[derive(Clone)]
 pub struct CmpOp {
 	pub(crate) op_type: CmpOpType,
 	pub(crate) lhs: Box<Expression>,
@@ -600,6 +982,7 @@ impl CmpOp {
 	}
 }
 
+#[derive(Clone)]
 pub enum Expression {
 	Select(Select),
 	GetTemporary(Temporary),
@@ -607,16 +990,73 @@ pub enum Expression {
 	GetGlobal(GetGlobal),
 	LoadAt(LoadAt),
 	MemorySize(MemorySize),
+	TableGet(TableGet),
+	TableSize(TableSize),
 	Value(Value),
+	RefNull(RefNull),
+	RefFunc(RefFunc),
+	RefIsNull(RefIsNull),
+	RefAsNonNull(RefAsNonNull),
 	UnOp(UnOp),
 	BinOp(BinOp),
 	CmpOp(CmpOp),
+	SimdOp(SimdOp),
+}
+
+impl Expression {
+	/// Returns whether the expression can neither trap nor have an effect,
+	/// so that it can be dropped or its evaluation skipped.
+	#[must_use]
+	pub fn is_pure(&self) -> bool {
+		match self {
+			Self::GetTemporary(_)
+			| Self::GetLocal(_)
+			| Self::GetGlobal(_)
+			| Self::MemorySize(_)
+			| Self::TableSize(_)
+			| Self::Value(_)
+			| Self::RefNull(_)
+			| Self::RefFunc(_) => true,
+			Self::Select(v) => v.condition.is_pure() && v.on_true.is_pure() && v.on_false.is_pure(),
+			Self::RefIsNull(v) => v.reference.is_pure(),
+			Self::UnOp(v) => {
+				let is_trapping = matches!(
+					v.op_type,
+					UnOpType::Truncate_I32_F32
+						| UnOpType::Truncate_I32_F64
+						| UnOpType::Truncate_U32_F32
+						| UnOpType::Truncate_U32_F64
+						| UnOpType::Truncate_I64_F32
+						| UnOpType::Truncate_I64_F64
+						| UnOpType::Truncate_U64_F32
+						| UnOpType::Truncate_U64_F64
+				);
+
+				!is_trapping && v.rhs.is_pure()
+			}
+			Self::BinOp(v) => {
+				let is_trapping =
+					matches!(
+						v.op_type,
+						BinOpType::DivS_I32
+							| BinOpType::DivU_I32 | BinOpType::RemS_I32
+							| BinOpType::RemU_I32 | BinOpType::DivS_I64
+							| BinOpType::DivU_I64 | BinOpType::RemS_I64
+							| BinOpType::RemU_I64
+					);
+
+				!is_trapping && v.lhs.is_pure() && v.rhs.is_pure()
+			}
+			Self::CmpOp(v) => v.lhs.is_pure() && v.rhs.is_pure(),
+			Self::LoadAt(_) | Self::TableGet(_) | Self::RefAsNonNull(_) | Self::SimdOp(_) => false,
+		}
+	}
 }
 
 #[derive(Clone, Copy)]
 pub struct ResultList {
-	start: usize,
-	end: usize,
+	pub(crate) start: usize,
+	pub(crate) end: usize,
 }
 
 impl ResultList {
@@ -677,6 +1117,7 @@ impl Br {
 	}
 }
 
+#[derive(Clone)]
 pub struct BrTable {
 	pub(crate) condition: Box<Expression>,
 	pub(crate) data: Vec<Br>,
@@ -700,22 +1141,56 @@ impl BrTable {
 	}
 }
 
+#[derive(Clone)]
+pub struct Throw {
+	pub(crate) tag: usize,
+	pub(crate) param_list: Vec<Expression>,
+}
+
+impl Throw {
+	#[must_use]
+	pub const fn tag(&self) -> usize {
+		self.tag
+	}
+
+	#[must_use]
+	pub fn param_list(&self) -> &[Expression] {
+		&self.param_list
+	}
+}
+
+#[derive(Clone, Copy)]
+pub struct Rethrow {
+	pub(crate) target: usize,
+}
+
+impl Rethrow {
+	#[must_use]
+	pub const fn target(self) -> usize {
+		self.target
+	}
+}
+
 #[derive(PartialEq, Eq, Clone, Copy)]
 pub enum LabelType {
 	Forward,
 	Backward,
 }
 
+#[derive(Clone)]
 pub enum Terminator {
 	Unreachable,
 	Br(Br),
 	BrTable(BrTable),
+	Throw(Throw),
+	Rethrow(Rethrow),
 }
 
-#[derive(Default)]
+#[derive(Clone, Default)]
 pub struct Block {
 	pub(crate) label_type: Option<LabelType>,
 	pub(crate) code: Vec<Statement>,
+	pub(crate) offset_list: Vec<usize>,
 	pub(crate) last: Option<Box<Terminator>>,
 }
 
@@ -730,12 +1205,20 @@ impl Block {
 		&self.code
 	}
 
+	/// Returns the offset in the module of the instruction that each
+	/// statement in [`Block::code`] was built from.
+	#[must_use]
+	pub fn offset_list(&self) -> &[usize] {
+		&self.offset_list
+	}
+
 	#[must_use]
 	pub fn last(&self) -> Option<&Terminator> {
 		self.last.as_deref()
 	}
 }
 
+#[derive(Clone)]
 pub struct BrIf {
 	pub(crate) condition: Box<Expression>,
 	pub(crate) target: Br,
@@ -753,10 +1236,12 @@ impl BrIf {
 	}
 }
 
+#[derive(Clone)]
 pub struct If {
 	pub(crate) condition: Box<Expression>,
 	pub(crate) on_true: Box<Block>,
 	pub(crate) on_false: Option<Box<Block>>,
+	pub(crate) hint: Option<bool>,
 }
 
 impl If {
@@ -774,8 +1259,66 @@ impl If {
 	pub fn on_false(&self) -> Option<&Block> {
 		self.on_false.as_deref()
 	}
+
+	/// Whether the branch hinting section marked the true arm as likely,
+	/// or `None` if there was no hint for this `if`.
+	#[must_use]
+	pub const fn hint(&self) -> Option<bool> {
+		self.hint
+	}
+}
+
+#[derive(Clone)]
+pub struct Catch {
+	pub(crate) tag: Option<usize>,
+	pub(crate) result_list: ResultList,
+	pub(crate) code: Block,
+}
+
+impl Catch {
+	#[must_use]
+	pub const fn tag(&self) -> Option<usize> {
+		self.tag
+	}
+
+	#[must_use]
+	pub const fn result_list(&self) -> ResultList {
+		self.result_list
+	}
+
+	#[must_use]
+	pub const fn code(&self) -> &Block {
+		&self.code
+	}
+}
+
+#[derive(Clone)]
+pub struct Try {
+	pub(crate) code: Block,
+	pub(crate) catch_list: Vec<Catch>,
+	pub(crate) delegate: Option<usize>,
+}
+
+impl Try {
+	#[must_use]
+	pub const fn code(&self) -> &Block {
+		&self.code
+	}
+
+	#[must_use]
+	pub fn catch_list(&self) -> &[Catch] {
+		&self.catch_list
+	}
+
+	// The number of enclosing `Try` handlers an uncaught exception
+	// passes through before it can be caught again
+	#[must_use]
+	pub const fn delegate(&self) -> Option<usize> {
+		self.delegate
+	}
 }
 
+#[derive(Clone)]
 pub struct Call {
 	pub(crate) function: usize,
 	pub(crate) param_list: Vec<Expression>,
@@ -799,7 +1342,9 @@ impl Call {
 	}
 }
 
+#[derive(Clone)]
 pub struct CallIndirect {
+	pub(crate) ty: usize,
 	pub(crate) table: usize,
 	pub(crate) index: Box<Expression>,
 	pub(crate) param_list: Vec<Expression>,
@@ -807,6 +1352,11 @@ pub struct CallIndirect {
 }
 
 impl CallIndirect {
+	#[must_use]
+	pub const fn ty(&self) -> usize {
+		self.ty
+	}
+
 	#[must_use]
 	pub const fn table(&self) -> usize {
 		self.table
@@ -828,6 +1378,31 @@ impl CallIndirect {
 	}
 }
 
+#[derive(Clone)]
+pub struct CallRef {
+	pub(crate) function: Box<Expression>,
+	pub(crate) param_list: Vec<Expression>,
+	pub(crate) result_list: ResultList,
+}
+
+impl CallRef {
+	#[must_use]
+	pub const fn function(&self) -> &Expression {
+		&self.function
+	}
+
+	#[must_use]
+	pub fn param_list(&self) -> &[Expression] {
+		&self.param_list
+	}
+
+	#[must_use]
+	pub const fn result_list(&self) -> ResultList {
+		self.result_list
+	}
+}
+
+#[derive(Clone)]
 pub struct SetTemporary {
 	pub(crate) var: Temporary,
 	pub(crate) value: Box<Expression>,
@@ -845,6 +1420,7 @@ impl SetTemporary {
 	}
 }
 
+#[derive(Clone)]
 pub struct SetLocal {
 	pub(crate) var: Local,
 	pub(crate) value: Box<Expression>,
@@ -862,6 +1438,7 @@ impl SetLocal {
 	}
 }
 
+#[derive(Clone)]
 pub struct SetGlobal {
 	pub(crate) var: usize,
 	pub(crate) value: Box<Expression>,
@@ -879,10 +1456,11 @@ impl SetGlobal {
 	}
 }
 
+#[derive(Clone)]
 pub struct StoreAt {
 	pub(crate) store_type: StoreType,
 	pub(crate) memory: usize,
-	pub(crate) offset: u32,
+	pub(crate) offset: u64,
 	pub(crate) pointer: Box<Expression>,
 	pub(crate) value: Box<Expression>,
 }
@@ -899,7 +1477,7 @@ impl StoreAt {
 	}
 
 	#[must_use]
-	pub const fn offset(&self) -> u32 {
+	pub const fn offset(&self) -> u64 {
 		self.offset
 	}
 
@@ -914,8 +1492,10 @@ impl StoreAt {
 	}
 }
 
+#[derive(Clone)]
 pub struct MemoryGrow {
 	pub(crate) memory: usize,
+	pub(crate) is_64: bool,
 	pub(crate) result: Temporary,
 	pub(crate) size: Box<Expression>,
 }
@@ -926,6 +1506,13 @@ impl MemoryGrow {
 		self.memory
 	}
 
+	/// Whether the memory is indexed by `i64`, which takes and gives back
+	/// page counts in `i64` too.
+	#[must_use]
+	pub const fn is_64(&self) -> bool {
+		self.is_64
+	}
+
 	#[must_use]
 	pub const fn result(&self) -> Temporary {
 		self.result
@@ -937,6 +1524,7 @@ impl MemoryGrow {
 	}
 }
 
+#[derive(Clone)]
 pub struct MemoryArgument {
 	pub(crate) memory: usize,
 	pub(crate) pointer: Box<Expression>,
@@ -954,6 +1542,7 @@ impl MemoryArgument {
 	}
 }
 
+#[derive(Clone)]
 pub struct MemoryCopy {
 	pub(crate) destination: MemoryArgument,
 	pub(crate) source: MemoryArgument,
@@ -977,6 +1566,7 @@ impl MemoryCopy {
 	}
 }
 
+#[derive(Clone)]
 pub struct MemoryFill {
 	pub(crate) destination: MemoryArgument,
 	pub(crate) size: Box<Expression>,
@@ -1000,12 +1590,285 @@ impl MemoryFill {
 	}
 }
 
+#[derive(Clone)]
+pub struct MemoryInit {
+	pub(crate) destination: MemoryArgument,
+	pub(crate) data: usize,
+	pub(crate) offset: Box<Expression>,
+	pub(crate) size: Box<Expression>,
+}
+
+impl MemoryInit {
+	#[must_use]
+	pub const fn destination(&self) -> &MemoryArgument {
+		&self.destination
+	}
+
+	#[must_use]
+	pub const fn data(&self) -> usize {
+		self.data
+	}
+
+	#[must_use]
+	pub const fn offset(&self) -> &Expression {
+		&self.offset
+	}
+
+	#[must_use]
+	pub const fn size(&self) -> &Expression {
+		&self.size
+	}
+}
+
+#[derive(Clone, Copy)]
+pub struct DataDrop {
+	pub(crate) data: usize,
+}
+
+impl DataDrop {
+	#[must_use]
+	pub const fn data(&self) -> usize {
+		self.data
+	}
+}
+
+#[derive(Clone)]
+pub struct TableInit {
+	pub(crate) table: usize,
+	pub(crate) element: usize,
+	pub(crate) destination: Box<Expression>,
+	pub(crate) offset: Box<Expression>,
+	pub(crate) size: Box<Expression>,
+}
+
+impl TableInit {
+	#[must_use]
+	pub const fn table(&self) -> usize {
+		self.table
+	}
+
+	#[must_use]
+	pub const fn element(&self) -> usize {
+		self.element
+	}
+
+	#[must_use]
+	pub const fn destination(&self) -> &Expression {
+		&self.destination
+	}
+
+	#[must_use]
+	pub const fn offset(&self) -> &Expression {
+		&self.offset
+	}
+
+	#[must_use]
+	pub const fn size(&self) -> &Expression {
+		&self.size
+	}
+}
+
+#[derive(Clone, Copy)]
+pub struct ElemDrop {
+	pub(crate) element: usize,
+}
+
+impl ElemDrop {
+	#[must_use]
+	pub const fn element(&self) -> usize {
+		self.element
+	}
+}
+
+#[derive(Clone)]
+pub struct TableSet {
+	pub(crate) table: usize,
+	pub(crate) index: Box<Expression>,
+	pub(crate) value: Box<Expression>,
+}
+
+impl TableSet {
+	#[must_use]
+	pub const fn table(&self) -> usize {
+		self.table
+	}
+
+	#[must_use]
+	pub const fn index(&self) -> &Expression {
+		&self.index
+	}
+
+	#[must_use]
+	pub const fn value(&self) -> &Expression {
+		&self.value
+	}
+}
+
+#[derive(Clone)]
+pub struct TableGrow {
+	pub(crate) table: usize,
+	pub(crate) result: Temporary,
+	pub(crate) size: Box<Expression>,
+	pub(crate) value: Box<Expression>,
+}
+
+impl TableGrow {
+	#[must_use]
+	pub const fn table(&self) -> usize {
+		self.table
+	}
+
+	#[must_use]
+	pub const fn result(&self) -> Temporary {
+		self.result
+	}
+
+	#[must_use]
+	pub const fn size(&self) -> &Expression {
+		&self.size
+	}
+
+	#[must_use]
+	pub const fn value(&self) -> &Expression {
+		&self.value
+	}
+}
+
+#[derive(Clone)]
+pub struct TableArgument {
+	pub(crate) table: usize,
+	pub(crate) index: Box<Expression>,
+}
+
+impl TableArgument {
+	#[must_use]
+	pub const fn table(&self) -> usize {
+		self.table
+	}
+
+	#[must_use]
+	pub const fn index(&self) -> &Expression {
+		&self.index
+	}
+}
+
+#[derive(Clone)]
+pub struct TableCopy {
+	pub(crate) destination: TableArgument,
+	pub(crate) source: TableArgument,
+	pub(crate) size: Box<Expression>,
+}
+
+impl TableCopy {
+	#[must_use]
+	pub const fn destination(&self) -> &TableArgument {
+		&self.destination
+	}
+
+	#[must_use]
+	pub const fn source(&self) -> &TableArgument {
+		&self.source
+	}
+
+	#[must_use]
+	pub const fn size(&self) -> &Expression {
+		&self.size
+	}
+}
+
+#[derive(Clone)]
+pub struct TableFill {
+	pub(crate) destination: TableArgument,
+	pub(crate) size: Box<Expression>,
+	pub(crate) value: Box<Expression>,
+}
+
+impl TableFill {
+	#[must_use]
+	pub const fn destination(&self) -> &TableArgument {
+		&self.destination
+	}
+
+	#[must_use]
+	pub const fn size(&self) -> &Expression {
+		&self.size
+	}
+
+	#[must_use]
+	pub const fn value(&self) -> &Expression {
+		&self.value
+	}
+}
+
+#[derive(Clone)]
+pub struct AtomicWait {
+	pub(crate) result: Temporary,
+	pub(crate) value: Box<Expression>,
+	pub(crate) expected: Box<Expression>,
+	pub(crate) timeout: Box<Expression>,
+}
+
+impl AtomicWait {
+	#[must_use]
+	pub const fn result(&self) -> Temporary {
+		self.result
+	}
+
+	#[must_use]
+	pub const fn value(&self) -> &Expression {
+		&self.value
+	}
+
+	#[must_use]
+	pub const fn expected(&self) -> &Expression {
+		&self.expected
+	}
+
+	#[must_use]
+	pub const fn timeout(&self) -> &Expression {
+		&self.timeout
+	}
+}
+
+#[derive(Clone)]
+pub struct AtomicNotify {
+	pub(crate) memory: usize,
+	pub(crate) result: Temporary,
+	pub(crate) pointer: Box<Expression>,
+	pub(crate) count: Box<Expression>,
+}
+
+impl AtomicNotify {
+	#[must_use]
+	pub const fn memory(&self) -> usize {
+		self.memory
+	}
+
+	#[must_use]
+	pub const fn result(&self) -> Temporary {
+		self.result
+	}
+
+	#[must_use]
+	pub const fn pointer(&self) -> &Expression {
+		&self.pointer
+	}
+
+	#[must_use]
+	pub const fn count(&self) -> &Expression {
+		&self.count
+	}
+}
+
+#[derive(Clone)]
 pub enum Statement {
 	Block(Block),
 	BrIf(BrIf),
 	If(If),
+	Try(Try),
 	Call(Call),
 	CallIndirect(CallIndirect),
+	CallRef(CallRef),
 	SetTemporary(SetTemporary),
 	SetLocal(SetLocal),
 	SetGlobal(SetGlobal),
@@ -1013,12 +1876,23 @@ pub enum Statement {
 	MemoryGrow(MemoryGrow),
 	MemoryCopy(MemoryCopy),
 	MemoryFill(MemoryFill),
-}
-
+	MemoryInit(MemoryInit),
+	DataDrop(DataDrop),
+	TableInit(TableInit),
+	ElemDrop(ElemDrop),
+	TableSet(TableSet),
+	TableGrow(TableGrow),
+	TableCopy(TableCopy),
+	TableFill(TableFill),
+	AtomicWait(AtomicWait),
+	AtomicNotify(AtomicNotify),
+}
+
+#[derive(Clone)]
 pub struct FuncData {
 	pub(crate) local_data: Vec<ValType>,
+	pub(crate) param_data: Vec<ValType>,
 	pub(crate) num_result: usize,
-	pub(crate) num_param: usize,
 	pub(crate) num_stack: usize,
 	pub(crate) code: Block,
 }
@@ -1029,14 +1903,19 @@ impl FuncData {
 		&self.local_data
 	}
 
+	#[must_use]
+	pub fn param_data(&self) -> &[ValType] {
+		&self.param_data
+	}
+
 	#[must_use]
 	pub const fn num_result(&self) -> usize {
 		self.num_result
 	}
 
 	#[must_use]
-	pub const fn num_param(&self) -> usize {
-		self.num_param
+	pub fn num_param(&self) -> usize {
+		self.param_data.len()
 	}
 
 	#[must_use]
@@ -0,0 +1,741 @@
+use crate::{
+	module::Module,
+	node::{
+		BinOp, BinOpType, Block, Br, BrTable, CmpOpType, Expression, FuncData, LabelType, Select,
+		Statement, Terminator, UnOpType, Value,
+	},
+	visit::{get_block_list, get_child_list, get_expression_list},
+};
+
+/// How much work goes into a function after it is built, trading the time
+/// spent translating for the quality of the output.
+#[derive(Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+	/// The code is lowered as it was built.
+	#[default]
+	Direct,
+	/// Arithmetic, comparisons, and conversions on constants are folded,
+	/// multiplying, and dividing or taking the remainder unsigned, by a power
+	/// of two become shifts and masks, shapes such as `eqz` of a comparison,
+	/// negating twice, or wrapping an extended value are simplified, copies of
+	/// locals and temporaries are read from what they copy, the statements that
+	/// can never run, such as a `br_if` on zero or the code after a block that
+	/// always branches away, are dropped, the code after each branch to the
+	/// end of a block is nested in an `if` on the other outcome when that
+	/// leaves the block without a label, temporaries that only the statement
+	/// after them reads are replaced by their value there, and temporaries
+	/// that are never alive at once share a place.
+	Cleanup,
+	/// On top of cleanups, every `if`, `br_if`, `br_table`, and `select` on a
+	/// constant is resolved to the arm that is taken, and a global or memory
+	/// read again before anything can change it is only read once, as is
+	/// arithmetic that the code before it or a block around it already
+	/// computed on variables that still hold the same values. Calls to small
+	/// functions that never call themselves are replaced by their body.
+	/// Globals that never change are read into locals by the functions reading them often,
+	/// and indirect calls to a constant entry of a table that never changes
+	/// call the function placed there directly.
+	Full,
+}
+
+const fn get_value(expr: &Expression) -> Option<Value> {
+	if let Expression::Value(value) = expr {
+		Some(*value)
+	} else {
+		None
+	}
+}
+
+// Runtimes do not all keep the payload of a NaN, so those are left to be made
+// at run time the same way as when unoptimized
+fn is_nan(value: Value) -> bool {
+	match value {
+		Value::F32(v) => v.is_nan(),
+		Value::F64(v) => v.is_nan(),
+		_ => false,
+	}
+}
+
+// Truncations trap when the result does not fit, so only those that do are folded
+fn truncate_within(num: f64, min: f64, max: f64) -> Option<f64> {
+	let num = num.trunc();
+
+	(num >= min && num < max).then_some(num)
+}
+
+fn truncate_i32(num: f64) -> Option<i32> {
+	truncate_within(num, -2_147_483_648.0, 2_147_483_648.0).map(|v| v as i32)
+}
+
+fn truncate_u32(num: f64) -> Option<i32> {
+	truncate_within(num, 0.0, 4_294_967_296.0).map(|v| v as u32 as i32)
+}
+
+fn truncate_i64(num: f64) -> Option<i64> {
+	truncate_within(
+		num,
+		-9_223_372_036_854_775_808.0,
+		9_223_372_036_854_775_808.0,
+	)
+	.map(|v| v as i64)
+}
+
+fn truncate_u64(num: f64) -> Option<i64> {
+	truncate_within(num, 0.0, 18_446_744_073_709_551_616.0).map(|v| v as u64 as i64)
+}
+
+fn fold_un_op(op_type: UnOpType, rhs: Value) -> Option<Value> {
+	let result = match (op_type, rhs) {
+		(UnOpType::Clz_I32, Value::I32(v)) => Value::I32(v.leading_zeros() as i32),
+		(UnOpType::Ctz_I32, Value::I32(v)) => Value::I32(v.trailing_zeros() as i32),
+		(UnOpType::Popcnt_I32, Value::I32(v)) => Value::I32(v.count_ones() as i32),
+		(UnOpType::Clz_I64, Value::I64(v)) => Value::I64(v.leading_zeros().into()),
+		(UnOpType::Ctz_I64, Value::I64(v)) => Value::I64(v.trailing_zeros().into()),
+		(UnOpType::Popcnt_I64, Value::I64(v)) => Value::I64(v.count_ones().into()),
+		(UnOpType::Wrap_I32_I64, Value::I64(v)) => Value::I32(v as i32),
+		(UnOpType::Extend_I32_N8, Value::I32(v)) => Value::I32((v as i8).into()),
+		(UnOpType::Extend_I32_N16, Value::I32(v)) => Value::I32((v as i16).into()),
+		(UnOpType::Extend_I64_N8, Value::I64(v)) => Value::I64((v as i8).into()),
+		(UnOpType::Extend_I64_N16, Value::I64(v)) => Value::I64((v as i16).into()),
+		(UnOpType::Extend_I64_N32, Value::I64(v)) => Value::I64((v as i32).into()),
+		(UnOpType::Extend_I64_I32, Value::I32(v)) => Value::I64(v.into()),
+		(UnOpType::Extend_I64_U32, Value::I32(v)) => Value::I64((v as u32).into()),
+		(UnOpType::Abs_F32, Value::F32(v)) => Value::F32(v.abs()),
+		(UnOpType::Neg_F32, Value::F32(v)) => Value::F32(-v),
+		(UnOpType::Ceil_F32, Value::F32(v)) => Value::F32(v.ceil()),
+		(UnOpType::Floor_F32, Value::F32(v)) => Value::F32(v.floor()),
+		(UnOpType::Truncate_F32, Value::F32(v)) => Value::F32(v.trunc()),
+		(UnOpType::Nearest_F32, Value::F32(v)) => Value::F32(v.round_ties_even()),
+		(UnOpType::Sqrt_F32, Value::F32(v)) => Value::F32(v.sqrt()),
+		(UnOpType::Abs_F64, Value::F64(v)) => Value::F64(v.abs()),
+		(UnOpType::Neg_F64, Value::F64(v)) => Value::F64(-v),
+		(UnOpType::Ceil_F64, Value::F64(v)) => Value::F64(v.ceil()),
+		(UnOpType::Floor_F64, Value::F64(v)) => Value::F64(v.floor()),
+		(UnOpType::Truncate_F64, Value::F64(v)) => Value::F64(v.trunc()),
+		(UnOpType::Nearest_F64, Value::F64(v)) => Value::F64(v.round_ties_even()),
+		(UnOpType::Sqrt_F64, Value::F64(v)) => Value::F64(v.sqrt()),
+		(UnOpType::Truncate_I32_F32, Value::F32(v)) => Value::I32(truncate_i32(v.into())?),
+		(UnOpType::Truncate_I32_F64, Value::F64(v)) => Value::I32(truncate_i32(v)?),
+		(UnOpType::Truncate_U32_F32, Value::F32(v)) => Value::I32(truncate_u32(v.into())?),
+		(UnOpType::Truncate_U32_F64, Value::F64(v)) => Value::I32(truncate_u32(v)?),
+		(UnOpType::Truncate_I64_F32, Value::F32(v)) => Value::I64(truncate_i64(v.into())?),
+		(UnOpType::Truncate_I64_F64, Value::F64(v)) => Value::I64(truncate_i64(v)?),
+		(UnOpType::Truncate_U64_F32, Value::F32(v)) => Value::I64(truncate_u64(v.into())?),
+		(UnOpType::Truncate_U64_F64, Value::F64(v)) => Value::I64(truncate_u64(v)?),
+		(UnOpType::Saturate_I32_F32, Value::F32(v)) => Value::I32(v as i32),
+		(UnOpType::Saturate_I32_F64, Value::F64(v)) => Value::I32(v as i32),
+		(UnOpType::Saturate_U32_F32, Value::F32(v)) => Value::I32(v as u32 as i32),
+		(UnOpType::Saturate_U32_F64, Value::F64(v)) => Value::I32(v as u32 as i32),
+		(UnOpType::Saturate_I64_F32, Value::F32(v)) => Value::I64(v as i64),
+		(UnOpType::Saturate_I64_F64, Value::F64(v)) => Value::I64(v as i64),
+		(UnOpType::Saturate_U64_F32, Value::F32(v)) => Value::I64(v as u64 as i64),
+		(UnOpType::Saturate_U64_F64, Value::F64(v)) => Value::I64(v as u64 as i64),
+		(UnOpType::Convert_F32_I32, Value::I32(v)) => Value::F32(v as f32),
+		(UnOpType::Convert_F32_U32, Value::I32(v)) => Value::F32(v as u32 as f32),
+		(UnOpType::Convert_F32_I64, Value::I64(v)) => Value::F32(v as f32),
+		(UnOpType::Convert_F32_U64, Value::I64(v)) => Value::F32(v as u64 as f32),
+		(UnOpType::Demote_F32_F64, Value::F64(v)) => Value::F32(v as f32),
+		(UnOpType::Convert_F64_I32, Value::I32(v)) => Value::F64(v.into()),
+		(UnOpType::Convert_F64_U32, Value::I32(v)) => Value::F64((v as u32).into()),
+		(UnOpType::Convert_F64_I64, Value::I64(v)) => Value::F64(v as f64),
+		(UnOpType::Convert_F64_U64, Value::I64(v)) => Value::F64(v as u64 as f64),
+		(UnOpType::Promote_F64_F32, Value::F32(v)) => Value::F64(v.into()),
+		(UnOpType::Reinterpret_I32_F32, Value::F32(v)) => Value::I32(v.to_bits() as i32),
+		(UnOpType::Reinterpret_I64_F64, Value::F64(v)) => Value::I64(v.to_bits() as i64),
+		(UnOpType::Reinterpret_F32_I32, Value::I32(v)) => Value::F32(f32::from_bits(v as u32)),
+		(UnOpType::Reinterpret_F64_I64, Value::I64(v)) => Value::F64(f64::from_bits(v as u64)),
+		_ => return None,
+	};
+
+	Some(result)
+}
+
+// Division by zero and signed overflow trap, so those are left to run
+fn fold_bin_op_i32(op_type: BinOpType, lhs: i32, rhs: i32) -> Option<i32> {
+	let result = match op_type {
+		BinOpType::Add_I32 => lhs.wrapping_add(rhs),
+		BinOpType::Sub_I32 => lhs.wrapping_sub(rhs),
+		BinOpType::Mul_I32 => lhs.wrapping_mul(rhs),
+		BinOpType::DivS_I32 => lhs.checked_div(rhs)?,
+		BinOpType::DivU_I32 => (lhs as u32).checked_div(rhs as u32)? as i32,
+		BinOpType::RemS_I32 if rhs != 0 => lhs.wrapping_rem(rhs),
+		BinOpType::RemU_I32 => (lhs as u32).checked_rem(rhs as u32)? as i32,
+		BinOpType::And_I32 => lhs & rhs,
+		BinOpType::Or_I32 => lhs | rhs,
+		BinOpType::Xor_I32 => lhs ^ rhs,
+		BinOpType::Shl_I32 => lhs.wrapping_shl(rhs as u32),
+		BinOpType::ShrS_I32 => lhs.wrapping_shr(rhs as u32),
+		BinOpType::ShrU_I32 => (lhs as u32).wrapping_shr(rhs as u32) as i32,
+		BinOpType::Rotl_I32 => (lhs as u32).rotate_left(rhs as u32 % 32) as i32,
+		BinOpType::Rotr_I32 => (lhs as u32).rotate_right(rhs as u32 % 32) as i32,
+		_ => return None,
+	};
+
+	Some(result)
+}
+
+fn fold_bin_op_i64(op_type: BinOpType, lhs: i64, rhs: i64) -> Option<i64> {
+	let result = match op_type {
+		BinOpType::Add_I64 => lhs.wrapping_add(rhs),
+		BinOpType::Sub_I64 => lhs.wrapping_sub(rhs),
+		BinOpType::Mul_I64 => lhs.wrapping_mul(rhs),
+		BinOpType::DivS_I64 => lhs.checked_div(rhs)?,
+		BinOpType::DivU_I64 => (lhs as u64).checked_div(rhs as u64)? as i64,
+		BinOpType::RemS_I64 if rhs != 0 => lhs.wrapping_rem(rhs),
+		BinOpType::RemU_I64 => (lhs as u64).checked_rem(rhs as u64)? as i64,
+		BinOpType::And_I64 => lhs & rhs,
+		BinOpType::Or_I64 => lhs | rhs,
+		BinOpType::Xor_I64 => lhs ^ rhs,
+		BinOpType::Shl_I64 => lhs.wrapping_shl(rhs as u32),
+		BinOpType::ShrS_I64 => lhs.wrapping_shr(rhs as u32),
+		BinOpType::ShrU_I64 => (lhs as u64).wrapping_shr(rhs as u32) as i64,
+		BinOpType::Rotl_I64 => (lhs as u64).rotate_left((rhs % 64) as u32) as i64,
+		BinOpType::Rotr_I64 => (lhs as u64).rotate_right((rhs % 64) as u32) as i64,
+		_ => return None,
+	};
+
+	Some(result)
+}
+
+// The minimum and maximum of zeros of either sign is the one of the sign asked
+// for, and NaN operands are never folded so they are not considered here
+macro_rules! impl_fold_bin_op_float {
+	($name:tt, $numeric:ty, $add:tt, $sub:tt, $mul:tt, $div:tt, $min:tt, $max:tt, $copysign:tt) => {
+		fn $name(op_type: BinOpType, lhs: $numeric, rhs: $numeric) -> Option<$numeric> {
+			if lhs.is_nan() || rhs.is_nan() {
+				return None;
+			}
+
+			let result = match op_type {
+				BinOpType::$add => lhs + rhs,
+				BinOpType::$sub => lhs - rhs,
+				BinOpType::$mul => lhs * rhs,
+				BinOpType::$div => lhs / rhs,
+				BinOpType::$min if lhs == rhs => {
+					<$numeric>::from_bits(lhs.to_bits() | rhs.to_bits())
+				}
+				BinOpType::$max if lhs == rhs => {
+					<$numeric>::from_bits(lhs.to_bits() & rhs.to_bits())
+				}
+				BinOpType::$min => lhs.min(rhs),
+				BinOpType::$max => lhs.max(rhs),
+				BinOpType::$copysign => lhs.copysign(rhs),
+				_ => return None,
+			};
+
+			Some(result)
+		}
+	};
+}
+
+impl_fold_bin_op_float!(
+	fold_bin_op_f32,
+	f32,
+	Add_F32,
+	Sub_F32,
+	Mul_F32,
+	Div_F32,
+	Min_F32,
+	Max_F32,
+	Copysign_F32
+);
+impl_fold_bin_op_float!(
+	fold_bin_op_f64,
+	f64,
+	Add_F64,
+	Sub_F64,
+	Mul_F64,
+	Div_F64,
+	Min_F64,
+	Max_F64,
+	Copysign_F64
+);
+
+fn fold_bin_op(op_type: BinOpType, lhs: Value, rhs: Value) -> Option<Value> {
+	match (lhs, rhs) {
+		(Value::I32(lhs), Value::I32(rhs)) => fold_bin_op_i32(op_type, lhs, rhs).map(Value::I32),
+		(Value::I64(lhs), Value::I64(rhs)) => fold_bin_op_i64(op_type, lhs, rhs).map(Value::I64),
+		(Value::F32(lhs), Value::F32(rhs)) => fold_bin_op_f32(op_type, lhs, rhs).map(Value::F32),
+		(Value::F64(lhs), Value::F64(rhs)) => fold_bin_op_f64(op_type, lhs, rhs).map(Value::F64),
+		_ => None,
+	}
+}
+
+// Every `f32` is exactly an `f64`, so both are compared as the latter
+fn fold_cmp_op_float(op_type: CmpOpType, lhs: f64, rhs: f64) -> Option<Value> {
+	let result = match op_type {
+		CmpOpType::Eq_F32 | CmpOpType::Eq_F64 => lhs == rhs,
+		CmpOpType::Ne_F32 | CmpOpType::Ne_F64 => lhs != rhs,
+		CmpOpType::Lt_F32 | CmpOpType::Lt_F64 => lhs < rhs,
+		CmpOpType::Gt_F32 | CmpOpType::Gt_F64 => lhs > rhs,
+		CmpOpType::Le_F32 | CmpOpType::Le_F64 => lhs <= rhs,
+		CmpOpType::Ge_F32 | CmpOpType::Ge_F64 => lhs >= rhs,
+		_ => return None,
+	};
+
+	Some(Value::I32(result.into()))
+}
+
+fn fold_cmp_op(op_type: CmpOpType, lhs: Value, rhs: Value) -> Option<Value> {
+	let (signed, unsigned) = match (lhs, rhs) {
+		(Value::I32(lhs), Value::I32(rhs)) => (lhs.cmp(&rhs), (lhs as u32).cmp(&(rhs as u32))),
+		(Value::I64(lhs), Value::I64(rhs)) => (lhs.cmp(&rhs), (lhs as u64).cmp(&(rhs as u64))),
+		(Value::F32(lhs), Value::F32(rhs)) => {
+			return fold_cmp_op_float(op_type, lhs.into(), rhs.into())
+		}
+		(Value::F64(lhs), Value::F64(rhs)) => return fold_cmp_op_float(op_type, lhs, rhs),
+		_ => return None,
+	};
+
+	let result = match op_type {
+		CmpOpType::Eq_I32 | CmpOpType::Eq_I64 => signed.is_eq(),
+		CmpOpType::Ne_I32 | CmpOpType::Ne_I64 => signed.is_ne(),
+		CmpOpType::LtS_I32 | CmpOpType::LtS_I64 => signed.is_lt(),
+		CmpOpType::LtU_I32 | CmpOpType::LtU_I64 => unsigned.is_lt(),
+		CmpOpType::GtS_I32 | CmpOpType::GtS_I64 => signed.is_gt(),
+		CmpOpType::GtU_I32 | CmpOpType::GtU_I64 => unsigned.is_gt(),
+		CmpOpType::LeS_I32 | CmpOpType::LeS_I64 => signed.is_le(),
+		CmpOpType::LeU_I32 | CmpOpType::LeU_I64 => unsigned.is_le(),
+		CmpOpType::GeS_I32 | CmpOpType::GeS_I64 => signed.is_ge(),
+		CmpOpType::GeU_I32 | CmpOpType::GeU_I64 => unsigned.is_ge(),
+		_ => return None,
+	};
+
+	Some(Value::I32(result.into()))
+}
+
+// Multiplying, and dividing or taking the remainder unsigned, by a power of two
+// are done with a shift or a mask, which costs runtimes much less
+fn reduce_bin_op(bin_op: &mut BinOp) {
+	if matches!(bin_op.op_type, BinOpType::Mul_I32 | BinOpType::Mul_I64)
+		&& get_value(&bin_op.lhs).is_some()
+	{
+		std::mem::swap(&mut bin_op.lhs, &mut bin_op.rhs);
+	}
+
+	let Some(rhs) = get_value(&bin_op.rhs) else {
+		return;
+	};
+
+	let (op_type, rhs) = match (bin_op.op_type, rhs) {
+		(BinOpType::Mul_I32, Value::I32(v)) if v.count_ones() == 1 => {
+			(BinOpType::Shl_I32, Value::I32(v.trailing_zeros() as i32))
+		}
+		(BinOpType::DivU_I32, Value::I32(v)) if v.count_ones() == 1 => {
+			(BinOpType::ShrU_I32, Value::I32(v.trailing_zeros() as i32))
+		}
+		(BinOpType::RemU_I32, Value::I32(v)) if v.count_ones() == 1 => {
+			(BinOpType::And_I32, Value::I32(v.wrapping_sub(1)))
+		}
+		(BinOpType::Mul_I64, Value::I64(v)) if v.count_ones() == 1 => {
+			(BinOpType::Shl_I64, Value::I64(v.trailing_zeros().into()))
+		}
+		(BinOpType::DivU_I64, Value::I64(v)) if v.count_ones() == 1 => {
+			(BinOpType::ShrU_I64, Value::I64(v.trailing_zeros().into()))
+		}
+		(BinOpType::RemU_I64, Value::I64(v)) if v.count_ones() == 1 => {
+			(BinOpType::And_I64, Value::I64(v.wrapping_sub(1)))
+		}
+		_ => return,
+	};
+
+	bin_op.op_type = op_type;
+	*bin_op.rhs = Expression::Value(rhs);
+}
+
+// A constant added to an address is taken into the offset of the access, which
+// assumes that the addition never wraps around, as compilers do not emit any.
+// Where it does, wasm wraps the address to a valid one while the runtimes would
+// index past 4 GiB, so this is only done when asked for
+fn merge_offset(pointer: &mut Box<Expression>, offset: &mut u64) {
+	while let Expression::BinOp(v) = pointer.as_mut() {
+		if !matches!(v.op_type, BinOpType::Add_I32) {
+			return;
+		}
+
+		let (base, constant) = match (get_value(&v.lhs), get_value(&v.rhs)) {
+			(_, Some(Value::I32(constant))) => (&mut v.lhs, constant),
+			(Some(Value::I32(constant)), _) => (&mut v.rhs, constant),
+			_ => return,
+		};
+
+		let Some(sum) = u32::try_from(constant)
+			.ok()
+			.and_then(|constant| u32::try_from(*offset).ok()?.checked_add(constant))
+		else {
+			return;
+		};
+
+		let base = std::mem::replace(base, Expression::Value(Value::I32(0)).into());
+
+		*offset = sum.into();
+		*pointer = base;
+	}
+}
+
+// The arm that is not taken is still evaluated, so it must be pure to drop
+fn take_select_arm(select: &mut Select) -> Option<Expression> {
+	let Some(Value::I32(condition)) = get_value(&select.condition) else {
+		return None;
+	};
+
+	let (kept, dropped) = if condition == 0 {
+		(&mut select.on_false, &select.on_true)
+	} else {
+		(&mut select.on_true, &select.on_false)
+	};
+
+	dropped
+		.is_pure()
+		.then(|| std::mem::replace(kept.as_mut(), Expression::Value(Value::I32(0))))
+}
+
+fn optimize_expression(expr: &mut Expression, level: Level) {
+	match expr {
+		Expression::Select(v) => {
+			optimize_expression(&mut v.condition, level);
+			optimize_expression(&mut v.on_true, level);
+			optimize_expression(&mut v.on_false, level);
+		}
+		Expression::LoadAt(v) => optimize_expression(&mut v.pointer, level),
+		Expression::TableGet(v) => optimize_expression(&mut v.index, level),
+		Expression::RefIsNull(v) => optimize_expression(&mut v.reference, level),
+		Expression::RefAsNonNull(v) => optimize_expression(&mut v.reference, level),
+		Expression::UnOp(v) => optimize_expression(&mut v.rhs, level),
+		Expression::BinOp(v) => {
+			optimize_expression(&mut v.lhs, level);
+			optimize_expression(&mut v.rhs, level);
+		}
+		Expression::CmpOp(v) => {
+			optimize_expression(&mut v.lhs, level);
+			optimize_expression(&mut v.rhs, level);
+		}
+		Expression::SimdOp(v) => {
+			for param in &mut v.param_list {
+				optimize_expression(param, level);
+			}
+		}
+		_ => {}
+	}
+
+	let value = match expr {
+		Expression::UnOp(v) => get_value(&v.rhs).and_then(|rhs| fold_un_op(v.op_type, rhs)),
+		Expression::BinOp(v) => get_value(&v.lhs)
+			.zip(get_value(&v.rhs))
+			.and_then(|(lhs, rhs)| fold_bin_op(v.op_type, lhs, rhs)),
+		Expression::CmpOp(v) => get_value(&v.lhs)
+			.zip(get_value(&v.rhs))
+			.and_then(|(lhs, rhs)| fold_cmp_op(v.op_type, lhs, rhs)),
+		Expression::Select(v) if level >= Level::Full => {
+			if let Some(kept) = take_select_arm(v) {
+				*expr = kept;
+			}
+
+			return;
+		}
+		_ => None,
+	};
+
+	if let Some(value) = value.filter(|value| !is_nan(*value)) {
+		*expr = Expression::Value(value);
+	} else if let Expression::BinOp(v) = expr {
+		reduce_bin_op(v);
+	} else {
+		crate::peephole::rewrite_expression(expr);
+	}
+}
+
+fn optimize_expression_list(list: &mut [Expression], level: Level) {
+	for expr in list {
+		optimize_expression(expr, level);
+	}
+}
+
+fn optimize_statement(stat: &mut Statement, level: Level) {
+	match stat {
+		Statement::Block(v) => optimize_block(v, level),
+		Statement::BrIf(v) => optimize_expression(&mut v.condition, level),
+		Statement::If(v) => {
+			optimize_expression(&mut v.condition, level);
+			optimize_block(&mut v.on_true, level);
+
+			if let Some(on_false) = &mut v.on_false {
+				optimize_block(on_false, level);
+			}
+		}
+		Statement::Try(v) => {
+			optimize_block(&mut v.code, level);
+
+			for catch in &mut v.catch_list {
+				optimize_block(&mut catch.code, level);
+			}
+		}
+		Statement::Call(v) => optimize_expression_list(&mut v.param_list, level),
+		Statement::CallIndirect(v) => {
+			optimize_expression(&mut v.index, level);
+			optimize_expression_list(&mut v.param_list, level);
+		}
+		Statement::CallRef(v) => {
+			optimize_expression(&mut v.function, level);
+			optimize_expression_list(&mut v.param_list, level);
+		}
+		Statement::SetTemporary(v) => optimize_expression(&mut v.value, level),
+		Statement::SetLocal(v) => optimize_expression(&mut v.value, level),
+		Statement::SetGlobal(v) => optimize_expression(&mut v.value, level),
+		Statement::StoreAt(v) => {
+			optimize_expression(&mut v.pointer, level);
+			optimize_expression(&mut v.value, level);
+		}
+		Statement::MemoryGrow(v) => optimize_expression(&mut v.size, level),
+		Statement::MemoryCopy(v) => {
+			optimize_expression(&mut v.destination.pointer, level);
+			optimize_expression(&mut v.source.pointer, level);
+			optimize_expression(&mut v.size, level);
+		}
+		Statement::MemoryFill(v) => {
+			optimize_expression(&mut v.destination.pointer, level);
+			optimize_expression(&mut v.size, level);
+			optimize_expression(&mut v.value, level);
+		}
+		Statement::MemoryInit(v) => {
+			optimize_expression(&mut v.destination.pointer, level);
+			optimize_expression(&mut v.offset, level);
+			optimize_expression(&mut v.size, level);
+		}
+		Statement::TableInit(v) => {
+			optimize_expression(&mut v.destination, level);
+			optimize_expression(&mut v.offset, level);
+			optimize_expression(&mut v.size, level);
+		}
+		Statement::TableSet(v) => {
+			optimize_expression(&mut v.index, level);
+			optimize_expression(&mut v.value, level);
+		}
+		Statement::TableGrow(v) => {
+			optimize_expression(&mut v.size, level);
+			optimize_expression(&mut v.value, level);
+		}
+		Statement::TableCopy(v) => {
+			optimize_expression(&mut v.destination.index, level);
+			optimize_expression(&mut v.source.index, level);
+			optimize_expression(&mut v.size, level);
+		}
+		Statement::TableFill(v) => {
+			optimize_expression(&mut v.destination.index, level);
+			optimize_expression(&mut v.size, level);
+			optimize_expression(&mut v.value, level);
+		}
+		Statement::AtomicWait(v) => {
+			optimize_expression(&mut v.value, level);
+			optimize_expression(&mut v.expected, level);
+			optimize_expression(&mut v.timeout, level);
+		}
+		Statement::AtomicNotify(v) => {
+			optimize_expression(&mut v.pointer, level);
+			optimize_expression(&mut v.count, level);
+		}
+		Statement::DataDrop(_) | Statement::ElemDrop(_) => {}
+	}
+
+	crate::peephole::rewrite_statement(stat);
+}
+
+enum Resolved {
+	Removed,
+	Kept(Statement),
+	Taken(Br),
+}
+
+// An `if` keeps its label when resolved, as a block with the arm as its code
+fn resolve_statement(stat: Statement, level: Level) -> Resolved {
+	match stat {
+		Statement::Block(v) if v.code.is_empty() && v.last.is_none() => Resolved::Removed,
+		Statement::BrIf(v) => match get_value(&v.condition) {
+			Some(Value::I32(0)) => Resolved::Removed,
+			Some(Value::I32(_)) if level >= Level::Full => Resolved::Taken(v.target),
+			_ => Resolved::Kept(Statement::BrIf(v)),
+		},
+		Statement::If(v) if level >= Level::Full => match get_value(&v.condition) {
+			Some(Value::I32(0)) => v.on_false.map_or(Resolved::Removed, |on_false| {
+				Resolved::Kept(Statement::Block(*on_false))
+			}),
+			Some(Value::I32(_)) => Resolved::Kept(Statement::Block(*v.on_true)),
+			_ => Resolved::Kept(Statement::If(v)),
+		},
+		stat => Resolved::Kept(stat),
+	}
+}
+
+fn resolve_terminator(last: Terminator, level: Level) -> Terminator {
+	match last {
+		Terminator::BrTable(mut v) => {
+			optimize_expression(&mut v.condition, level);
+
+			match get_value(&v.condition) {
+				Some(Value::I32(index)) if level >= Level::Full => {
+					let BrTable { data, default, .. } = v;
+					let index = usize::try_from(index as u32).unwrap();
+
+					Terminator::Br(data.get(index).copied().unwrap_or(default))
+				}
+				_ => Terminator::BrTable(v),
+			}
+		}
+		Terminator::Throw(mut v) => {
+			optimize_expression_list(&mut v.param_list, level);
+
+			Terminator::Throw(v)
+		}
+		last => last,
+	}
+}
+
+// Only labels that are branched to are kept, so a block without one can
+// only be left by reaching its end
+fn is_block_open(block: &Block) -> bool {
+	block.last.is_none() && block.code.last().map_or(true, is_falling_through)
+}
+
+// Whether the code after a statement can run, which a `Try` is always
+// assumed to allow since its handlers are entered from anywhere within
+fn is_falling_through(stat: &Statement) -> bool {
+	match stat {
+		Statement::Block(v) => v.label_type == Some(LabelType::Forward) || is_block_open(v),
+		Statement::If(v) => {
+			let on_false = v.on_false.as_deref();
+
+			v.on_true.label_type.is_some()
+				|| on_false.is_some_and(|v| v.label_type.is_some())
+				|| is_block_open(&v.on_true)
+				|| on_false.map_or(true, is_block_open)
+		}
+		_ => true,
+	}
+}
+
+// Statements after a `br_if` that is always taken never run, so the branch
+// becomes the end of the block in their place, and neither do those after a
+// statement that is never left through its end
+fn optimize_block(block: &mut Block, level: Level) {
+	let code = std::mem::take(&mut block.code);
+	let offset_list = std::mem::take(&mut block.offset_list);
+
+	for (mut stat, offset) in code.into_iter().zip(offset_list) {
+		optimize_statement(&mut stat, level);
+
+		match resolve_statement(stat, level) {
+			Resolved::Removed => {}
+			Resolved::Kept(stat) => {
+				let is_open = is_falling_through(&stat);
+
+				block.code.push(stat);
+				block.offset_list.push(offset);
+
+				if !is_open {
+					return;
+				}
+			}
+			Resolved::Taken(target) => {
+				block.last = Some(Terminator::Br(target).into());
+
+				return;
+			}
+		}
+	}
+
+	block.last = block
+		.last
+		.take()
+		.map(|last| resolve_terminator(*last, level).into());
+}
+
+/// Optimizes the code of a function as far as `level` allows.
+pub fn optimize(func: &mut FuncData, level: Level) {
+	if level == Level::Direct {
+		return;
+	}
+
+	optimize_block(&mut func.code, level);
+
+	if level >= Level::Full {
+		crate::cse::eliminate(func);
+		crate::gvn::eliminate(func);
+	}
+
+	crate::copy::propagate(func);
+	crate::local::compact(func);
+	crate::label::simplify(func);
+	crate::sink::sink(func);
+	crate::color::recolor(func);
+}
+
+/// Moves constants added to the addresses of loads and stores into their
+/// offset, assuming that the addition never wraps around past 4 GiB. Unlike
+/// the other passes this is not part of any level, as a module that relies on
+/// the wrapping would read or write out of bounds instead.
+pub fn fold_offsets(func: &mut FuncData) {
+	fold_offset_block(&mut func.code);
+}
+
+fn fold_offset_expression(expr: &mut Expression) {
+	for child in get_child_list(expr) {
+		fold_offset_expression(child);
+	}
+
+	if let Expression::LoadAt(v) = expr {
+		merge_offset(&mut v.pointer, &mut v.offset);
+	}
+}
+
+fn fold_offset_block(block: &mut Block) {
+	for stat in &mut block.code {
+		for nested in get_block_list(stat) {
+			fold_offset_block(nested);
+		}
+
+		for expr in get_expression_list(stat) {
+			fold_offset_expression(expr);
+		}
+
+		if let Statement::StoreAt(v) = stat {
+			merge_offset(&mut v.pointer, &mut v.offset);
+		}
+	}
+
+	match block.last.as_deref_mut() {
+		Some(Terminator::BrTable(v)) => fold_offset_expression(&mut v.condition),
+		Some(Terminator::Throw(v)) => {
+			for param in &mut v.param_list {
+				fold_offset_expression(param);
+			}
+		}
+		_ => {}
+	}
+}
+
+/// Replaces each `call_indirect` on a constant index into a table that never
+/// changes with a direct call when `level` allows, before functions are
+/// inlined so that the calls it makes direct can be too.
+pub fn devirtualize(wasm: &Module, func_list: &mut [FuncData], level: Level) {
+	if level >= Level::Full {
+		crate::devirtualize::devirtualize(wasm, func_list);
+	}
+}
+
+/// Replaces calls to small functions with their body when `level` allows,
+/// before the functions are optimized, where `offset` is the index in the
+/// module of the first function in `func_list`.
+pub fn inline(func_list: &mut [FuncData], offset: usize, level: Level) {
+	if level >= Level::Full {
+		crate::inline::inline(func_list, offset);
+	}
+}
+
+/// Reads each global that keeps the value it is started with into a local at
+/// the start of the functions that read it often when `level` allows, before
+/// the functions are optimized.
+pub fn hoist(wasm: &Module, func_list: &mut [FuncData], level: Level) {
+	if level >= Level::Full {
+		crate::hoist::hoist(wasm, func_list);
+	}
+}
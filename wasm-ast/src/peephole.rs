@@ -0,0 +1,645 @@
+use crate::node::{Block, CmpOpType, Expression, Statement, UnOpType, Value};
+
+/// Rewrites an expression in place when it has the shape that the rule
+/// matches, returning whether it did.
+type ExpressionRule = fn(&mut Expression) -> bool;
+
+/// Rewrites a statement in place when it has the shape that the rule
+/// matches, returning whether it did.
+type StatementRule = fn(&mut Statement) -> bool;
+
+const EXPRESSION_RULE_LIST: [ExpressionRule; 6] = [
+	move_constant_right,
+	invert_equal_zero,
+	remove_not_equal_zero,
+	remove_double_negation,
+	remove_wrap_of_extend,
+	remove_repeated_extend,
+];
+
+const STATEMENT_RULE_LIST: [StatementRule; 2] = [remove_condition_test, remove_empty_else];
+
+fn take(expr: &mut Expression) -> Expression {
+	std::mem::replace(expr, Expression::Value(Value::I32(0)))
+}
+
+const fn is_zero(expr: &Expression) -> bool {
+	matches!(expr, Expression::Value(Value::I32(0) | Value::I64(0)))
+}
+
+const fn get_mirrored(op_type: CmpOpType) -> CmpOpType {
+	match op_type {
+		CmpOpType::LtS_I32 => CmpOpType::GtS_I32,
+		CmpOpType::LtU_I32 => CmpOpType::GtU_I32,
+		CmpOpType::GtS_I32 => CmpOpType::LtS_I32,
+		CmpOpType::GtU_I32 => CmpOpType::LtU_I32,
+		CmpOpType::LeS_I32 => CmpOpType::GeS_I32,
+		CmpOpType::LeU_I32 => CmpOpType::GeU_I32,
+		CmpOpType::GeS_I32 => CmpOpType::LeS_I32,
+		CmpOpType::GeU_I32 => CmpOpType::LeU_I32,
+		CmpOpType::LtS_I64 => CmpOpType::GtS_I64,
+		CmpOpType::LtU_I64 => CmpOpType::GtU_I64,
+		CmpOpType::GtS_I64 => CmpOpType::LtS_I64,
+		CmpOpType::GtU_I64 => CmpOpType::LtU_I64,
+		CmpOpType::LeS_I64 => CmpOpType::GeS_I64,
+		CmpOpType::LeU_I64 => CmpOpType::GeU_I64,
+		CmpOpType::GeS_I64 => CmpOpType::LeS_I64,
+		CmpOpType::GeU_I64 => CmpOpType::LeU_I64,
+		CmpOpType::Lt_F32 => CmpOpType::Gt_F32,
+		CmpOpType::Gt_F32 => CmpOpType::Lt_F32,
+		CmpOpType::Le_F32 => CmpOpType::Ge_F32,
+		CmpOpType::Ge_F32 => CmpOpType::Le_F32,
+		CmpOpType::Lt_F64 => CmpOpType::Gt_F64,
+		CmpOpType::Gt_F64 => CmpOpType::Lt_F64,
+		CmpOpType::Le_F64 => CmpOpType::Ge_F64,
+		CmpOpType::Ge_F64 => CmpOpType::Le_F64,
+		op_type => op_type,
+	}
+}
+
+// Floats only have an inverse for equality, as every ordering of a NaN is false
+const fn get_inverted(op_type: CmpOpType) -> Option<CmpOpType> {
+	let result = match op_type {
+		CmpOpType::Eq_I32 => CmpOpType::Ne_I32,
+		CmpOpType::Ne_I32 => CmpOpType::Eq_I32,
+		CmpOpType::LtS_I32 => CmpOpType::GeS_I32,
+		CmpOpType::LtU_I32 => CmpOpType::GeU_I32,
+		CmpOpType::GtS_I32 => CmpOpType::LeS_I32,
+		CmpOpType::GtU_I32 => CmpOpType::LeU_I32,
+		CmpOpType::LeS_I32 => CmpOpType::GtS_I32,
+		CmpOpType::LeU_I32 => CmpOpType::GtU_I32,
+		CmpOpType::GeS_I32 => CmpOpType::LtS_I32,
+		CmpOpType::GeU_I32 => CmpOpType::LtU_I32,
+		CmpOpType::Eq_I64 => CmpOpType::Ne_I64,
+		CmpOpType::Ne_I64 => CmpOpType::Eq_I64,
+		CmpOpType::LtS_I64 => CmpOpType::GeS_I64,
+		CmpOpType::LtU_I64 => CmpOpType::GeU_I64,
+		CmpOpType::GtS_I64 => CmpOpType::LeS_I64,
+		CmpOpType::GtU_I64 => CmpOpType::LeU_I64,
+		CmpOpType::LeS_I64 => CmpOpType::GtS_I64,
+		CmpOpType::LeU_I64 => CmpOpType::GtU_I64,
+		CmpOpType::GeS_I64 => CmpOpType::LtS_I64,
+		CmpOpType::GeU_I64 => CmpOpType::LtU_I64,
+		CmpOpType::Eq_F32 => CmpOpType::Ne_F32,
+		CmpOpType::Ne_F32 => CmpOpType::Eq_F32,
+		CmpOpType::Eq_F64 => CmpOpType::Ne_F64,
+		CmpOpType::Ne_F64 => CmpOpType::Eq_F64,
+		_ => return None,
+	};
+
+	Some(result)
+}
+
+// Constants are moved to the right of comparisons, where the other rules look
+// for them, which is safe to do as they have no effect to reorder
+fn move_constant_right(expr: &mut Expression) -> bool {
+	let Expression::CmpOp(v) = expr else {
+		return false;
+	};
+
+	let is_constant = |expr: &Expression| matches!(expr, Expression::Value(_));
+
+	if !is_constant(&v.lhs) || is_constant(&v.rhs) {
+		return false;
+	}
+
+	v.op_type = get_mirrored(v.op_type);
+	std::mem::swap(&mut v.lhs, &mut v.rhs);
+
+	true
+}
+
+// `eqz` of a comparison is its inverse, which also undoes a double `eqz`
+fn invert_equal_zero(expr: &mut Expression) -> bool {
+	let Expression::CmpOp(v) = expr else {
+		return false;
+	};
+
+	if !matches!(v.op_type, CmpOpType::Eq_I32) || !is_zero(&v.rhs) {
+		return false;
+	}
+
+	let Expression::CmpOp(inner) = v.lhs.as_mut() else {
+		return false;
+	};
+
+	let Some(op_type) = get_inverted(inner.op_type) else {
+		return false;
+	};
+
+	inner.op_type = op_type;
+
+	let inner = take(&mut v.lhs);
+
+	*expr = inner;
+
+	true
+}
+
+// Comparisons are only ever `0` or `1`, so testing one against zero is itself
+fn remove_not_equal_zero(expr: &mut Expression) -> bool {
+	let Expression::CmpOp(v) = expr else {
+		return false;
+	};
+
+	let is_comparison = matches!(v.lhs.as_ref(), Expression::CmpOp(_));
+
+	if !matches!(v.op_type, CmpOpType::Ne_I32) || !is_zero(&v.rhs) || !is_comparison {
+		return false;
+	}
+
+	let inner = take(&mut v.lhs);
+
+	*expr = inner;
+
+	true
+}
+
+// Negating flips the sign bit alone, so doing it twice is the operand
+fn remove_double_negation(expr: &mut Expression) -> bool {
+	let Expression::UnOp(v) = expr else {
+		return false;
+	};
+
+	let Expression::UnOp(inner) = v.rhs.as_mut() else {
+		return false;
+	};
+
+	if !matches!(
+		(v.op_type, inner.op_type),
+		(UnOpType::Neg_F32, UnOpType::Neg_F32) | (UnOpType::Neg_F64, UnOpType::Neg_F64)
+	) {
+		return false;
+	}
+
+	let operand = take(&mut inner.rhs);
+
+	*expr = operand;
+
+	true
+}
+
+// Wrapping an extended `i32` gives back the `i32`
+fn remove_wrap_of_extend(expr: &mut Expression) -> bool {
+	let Expression::UnOp(v) = expr else {
+		return false;
+	};
+
+	let Expression::UnOp(inner) = v.rhs.as_mut() else {
+		return false;
+	};
+
+	if !matches!(v.op_type, UnOpType::Wrap_I32_I64)
+		|| !matches!(
+			inner.op_type,
+			UnOpType::Extend_I64_I32 | UnOpType::Extend_I64_U32
+		) {
+		return false;
+	}
+
+	let operand = take(&mut inner.rhs);
+
+	*expr = operand;
+
+	true
+}
+
+// Returns the type and the number of low bits that a sign extension keeps
+const fn get_extend_width(op_type: UnOpType) -> Option<(bool, u32)> {
+	let result = match op_type {
+		UnOpType::Extend_I32_N8 => (false, 8),
+		UnOpType::Extend_I32_N16 => (false, 16),
+		UnOpType::Extend_I64_N8 => (true, 8),
+		UnOpType::Extend_I64_N16 => (true, 16),
+		UnOpType::Extend_I64_N32 | UnOpType::Extend_I64_I32 => (true, 32),
+		_ => return None,
+	};
+
+	Some(result)
+}
+
+// A value that is sign extended from some bits already is from any more, and
+// extending it from fewer ignores the ones extended before
+fn remove_repeated_extend(expr: &mut Expression) -> bool {
+	let Expression::UnOp(v) = expr else {
+		return false;
+	};
+
+	let Expression::UnOp(inner) = v.rhs.as_mut() else {
+		return false;
+	};
+
+	let Some((outer_type, outer_width)) = get_extend_width(v.op_type) else {
+		return false;
+	};
+
+	let Some((inner_type, inner_width)) = get_extend_width(inner.op_type) else {
+		return false;
+	};
+
+	if outer_type != inner_type {
+		return false;
+	}
+
+	if outer_width >= inner_width {
+		let inner = take(&mut v.rhs);
+
+		*expr = inner;
+	} else if matches!(inner.op_type, UnOpType::Extend_I64_I32) {
+		// Its operand is an `i32`, which the outer one cannot take
+		return false;
+	} else {
+		let operand = take(&mut inner.rhs);
+
+		*v.rhs = operand;
+	}
+
+	true
+}
+
+// Conditions are tested against zero when they are branched on anyway
+fn remove_condition_test(stat: &mut Statement) -> bool {
+	let condition = match stat {
+		Statement::BrIf(v) => &mut v.condition,
+		Statement::If(v) => &mut v.condition,
+		_ => return false,
+	};
+
+	let Expression::CmpOp(v) = condition.as_mut() else {
+		return false;
+	};
+
+	if !matches!(v.op_type, CmpOpType::Ne_I32) || !is_zero(&v.rhs) {
+		return false;
+	}
+
+	let operand = take(&mut v.lhs);
+
+	**condition = operand;
+
+	true
+}
+
+fn is_empty(block: &Block) -> bool {
+	block.code.is_empty() && block.last.is_none()
+}
+
+fn remove_empty_else(stat: &mut Statement) -> bool {
+	let Statement::If(v) = stat else {
+		return false;
+	};
+
+	if !v.on_false.as_deref().is_some_and(is_empty) {
+		return false;
+	}
+
+	v.on_false = None;
+
+	true
+}
+
+/// Applies the expression rules to an expression until none of them match,
+/// after its operands have been.
+pub fn rewrite_expression(expr: &mut Expression) {
+	while EXPRESSION_RULE_LIST.iter().any(|rule| rule(expr)) {}
+}
+
+/// Applies the statement rules to a statement until none of them match,
+/// after its expressions have been.
+pub fn rewrite_statement(stat: &mut Statement) {
+	while STATEMENT_RULE_LIST.iter().any(|rule| rule(stat)) {}
+}
+
+#[cfg(test)]
+mod test {
+	use crate::node::{
+		Block, CmpOp, CmpOpType, Expression, If, Local, Statement, Terminator, UnOp, UnOpType,
+		Value,
+	};
+
+	fn get_local(var: usize) -> Box<Expression> {
+		Expression::GetLocal(Local { var }).into()
+	}
+
+	fn is_local(expr: &Expression, var: usize) -> bool {
+		matches!(expr, Expression::GetLocal(v) if v.var == var)
+	}
+
+	fn cmp_op(op_type: CmpOpType, lhs: Box<Expression>, rhs: Box<Expression>) -> Expression {
+		Expression::CmpOp(CmpOp { op_type, lhs, rhs })
+	}
+
+	fn un_op(op_type: UnOpType, rhs: Expression) -> Expression {
+		Expression::UnOp(UnOp {
+			op_type,
+			rhs: rhs.into(),
+		})
+	}
+
+	fn equal_zero(expr: Expression) -> Expression {
+		cmp_op(
+			CmpOpType::Eq_I32,
+			expr.into(),
+			Expression::Value(Value::I32(0)).into(),
+		)
+	}
+
+	fn not_equal_zero(expr: Expression) -> Expression {
+		cmp_op(
+			CmpOpType::Ne_I32,
+			expr.into(),
+			Expression::Value(Value::I32(0)).into(),
+		)
+	}
+
+	fn rewrite(mut expr: Expression) -> Expression {
+		super::rewrite_expression(&mut expr);
+
+		expr
+	}
+
+	fn if_statement(condition: Expression, on_false: Option<Block>) -> Statement {
+		Statement::If(If {
+			condition: condition.into(),
+			on_true: Block::default().into(),
+			on_false: on_false.map(Box::new),
+			hint: None,
+		})
+	}
+
+	#[test]
+	fn invert_comparison_under_eqz() {
+		let expr = rewrite(equal_zero(cmp_op(
+			CmpOpType::LtS_I32,
+			get_local(0),
+			get_local(1),
+		)));
+
+		assert!(matches!(
+			expr,
+			Expression::CmpOp(CmpOp { op_type: CmpOpType::GeS_I32, lhs, rhs })
+				if is_local(&lhs, 0) && is_local(&rhs, 1)
+		));
+	}
+
+	#[test]
+	fn invert_float_equality_under_eqz() {
+		let expr = rewrite(equal_zero(cmp_op(
+			CmpOpType::Eq_F64,
+			get_local(0),
+			get_local(1),
+		)));
+
+		assert!(matches!(
+			expr,
+			Expression::CmpOp(CmpOp { op_type: CmpOpType::Ne_F64, lhs, rhs })
+				if is_local(&lhs, 0) && is_local(&rhs, 1)
+		));
+	}
+
+	// `!(a < b)` is true for a NaN, but `a >= b` is not
+	#[test]
+	fn keep_eqz_of_float_ordering() {
+		for op_type in [
+			CmpOpType::Lt_F32,
+			CmpOpType::Gt_F32,
+			CmpOpType::Le_F64,
+			CmpOpType::Ge_F64,
+		] {
+			let expr = rewrite(equal_zero(cmp_op(op_type, get_local(0), get_local(1))));
+
+			let Expression::CmpOp(CmpOp {
+				op_type: CmpOpType::Eq_I32,
+				lhs,
+				..
+			}) = expr
+			else {
+				panic!("the `eqz` should be kept");
+			};
+
+			let Expression::CmpOp(inner) = *lhs else {
+				panic!("the comparison should be kept");
+			};
+
+			assert_eq!(
+				std::mem::discriminant(&inner.op_type),
+				std::mem::discriminant(&op_type)
+			);
+		}
+	}
+
+	#[test]
+	fn keep_eqz_of_local() {
+		let expr = rewrite(equal_zero(*get_local(0)));
+
+		assert!(matches!(
+			expr,
+			Expression::CmpOp(CmpOp { op_type: CmpOpType::Eq_I32, lhs, .. })
+				if is_local(&lhs, 0)
+		));
+	}
+
+	#[test]
+	fn remove_double_eqz() {
+		let expr = rewrite(equal_zero(equal_zero(*get_local(0))));
+
+		assert!(matches!(
+			expr,
+			Expression::CmpOp(CmpOp { op_type: CmpOpType::Ne_I32, lhs, rhs })
+				if is_local(&lhs, 0) && matches!(*rhs, Expression::Value(Value::I32(0)))
+		));
+	}
+
+	#[test]
+	fn move_constant_right() {
+		let expr = rewrite(cmp_op(
+			CmpOpType::Lt_F64,
+			Expression::Value(Value::F64(1.0)).into(),
+			get_local(0),
+		));
+
+		assert!(matches!(
+			expr,
+			Expression::CmpOp(CmpOp { op_type: CmpOpType::Gt_F64, lhs, rhs })
+				if is_local(&lhs, 0) && matches!(*rhs, Expression::Value(Value::F64(_)))
+		));
+	}
+
+	#[test]
+	fn keep_constant_left_of_constant() {
+		let expr = rewrite(cmp_op(
+			CmpOpType::LtU_I32,
+			Expression::Value(Value::I32(1)).into(),
+			Expression::Value(Value::I32(2)).into(),
+		));
+
+		assert!(matches!(
+			expr,
+			Expression::CmpOp(CmpOp { op_type: CmpOpType::LtU_I32, lhs, .. })
+				if matches!(*lhs, Expression::Value(Value::I32(1)))
+		));
+	}
+
+	#[test]
+	fn remove_not_equal_zero_of_comparison() {
+		let expr = rewrite(not_equal_zero(cmp_op(
+			CmpOpType::Ne_F32,
+			get_local(0),
+			get_local(1),
+		)));
+
+		assert!(matches!(
+			expr,
+			Expression::CmpOp(CmpOp { op_type: CmpOpType::Ne_F32, lhs, rhs })
+				if is_local(&lhs, 0) && is_local(&rhs, 1)
+		));
+	}
+
+	#[test]
+	fn keep_not_equal_zero_of_local() {
+		let expr = rewrite(not_equal_zero(*get_local(0)));
+
+		assert!(matches!(
+			expr,
+			Expression::CmpOp(CmpOp { op_type: CmpOpType::Ne_I32, lhs, .. })
+				if is_local(&lhs, 0)
+		));
+	}
+
+	#[test]
+	fn remove_double_negation() {
+		let expr = rewrite(un_op(
+			UnOpType::Neg_F64,
+			un_op(UnOpType::Neg_F64, *get_local(0)),
+		));
+
+		assert!(is_local(&expr, 0));
+	}
+
+	// `-abs(x)` is not `x`, so only negations cancel out
+	#[test]
+	fn keep_negation_of_absolute() {
+		let expr = rewrite(un_op(
+			UnOpType::Neg_F32,
+			un_op(UnOpType::Abs_F32, *get_local(0)),
+		));
+
+		assert!(matches!(
+			expr,
+			Expression::UnOp(UnOp { op_type: UnOpType::Neg_F32, rhs })
+				if matches!(*rhs, Expression::UnOp(UnOp { op_type: UnOpType::Abs_F32, .. }))
+		));
+	}
+
+	#[test]
+	fn remove_wrap_of_extend() {
+		for op_type in [UnOpType::Extend_I64_I32, UnOpType::Extend_I64_U32] {
+			let expr = rewrite(un_op(UnOpType::Wrap_I32_I64, un_op(op_type, *get_local(0))));
+
+			assert!(is_local(&expr, 0));
+		}
+	}
+
+	#[test]
+	fn keep_extend_of_wrap() {
+		let expr = rewrite(un_op(
+			UnOpType::Extend_I64_I32,
+			un_op(UnOpType::Wrap_I32_I64, *get_local(0)),
+		));
+
+		assert!(matches!(
+			expr,
+			Expression::UnOp(UnOp { op_type: UnOpType::Extend_I64_I32, rhs })
+				if matches!(*rhs, Expression::UnOp(UnOp { op_type: UnOpType::Wrap_I32_I64, .. }))
+		));
+	}
+
+	#[test]
+	fn remove_wider_extend() {
+		let expr = rewrite(un_op(
+			UnOpType::Extend_I64_N32,
+			un_op(UnOpType::Extend_I64_N8, *get_local(0)),
+		));
+
+		assert!(matches!(
+			expr,
+			Expression::UnOp(UnOp { op_type: UnOpType::Extend_I64_N8, rhs })
+				if is_local(&rhs, 0)
+		));
+	}
+
+	#[test]
+	fn remove_narrower_extend() {
+		let expr = rewrite(un_op(
+			UnOpType::Extend_I32_N8,
+			un_op(UnOpType::Extend_I32_N16, *get_local(0)),
+		));
+
+		assert!(matches!(
+			expr,
+			Expression::UnOp(UnOp { op_type: UnOpType::Extend_I32_N8, rhs })
+				if is_local(&rhs, 0)
+		));
+	}
+
+	#[test]
+	fn keep_narrower_extend_of_i32() {
+		let expr = rewrite(un_op(
+			UnOpType::Extend_I64_N8,
+			un_op(UnOpType::Extend_I64_I32, *get_local(0)),
+		));
+
+		assert!(matches!(
+			expr,
+			Expression::UnOp(UnOp { op_type: UnOpType::Extend_I64_N8, rhs })
+				if matches!(*rhs, Expression::UnOp(UnOp { op_type: UnOpType::Extend_I64_I32, .. }))
+		));
+	}
+
+	#[test]
+	fn remove_condition_test() {
+		let mut stat = if_statement(not_equal_zero(*get_local(0)), None);
+
+		super::rewrite_statement(&mut stat);
+
+		assert!(matches!(stat, Statement::If(v) if is_local(&v.condition, 0)));
+	}
+
+	#[test]
+	fn keep_condition_equal_zero() {
+		let mut stat = if_statement(equal_zero(*get_local(0)), None);
+
+		super::rewrite_statement(&mut stat);
+
+		assert!(matches!(
+			stat,
+			Statement::If(v) if matches!(
+				*v.condition,
+				Expression::CmpOp(CmpOp { op_type: CmpOpType::Eq_I32, .. })
+			)
+		));
+	}
+
+	#[test]
+	fn remove_empty_else() {
+		let mut stat = if_statement(*get_local(0), Some(Block::default()));
+
+		super::rewrite_statement(&mut stat);
+
+		assert!(matches!(stat, Statement::If(v) if v.on_false.is_none()));
+	}
+
+	#[test]
+	fn keep_else_with_code() {
+		let on_false = Block {
+			last: Some(Terminator::Unreachable.into()),
+			..Block::default()
+		};
+
+		let mut stat = if_statement(*get_local(0), Some(on_false));
+
+		super::rewrite_statement(&mut stat);
+
+		assert!(matches!(stat, Statement::If(v) if v.on_false.is_some()));
+	}
+}
@@ -0,0 +1,364 @@
+use wasmparser::{ConstExpr, ElementItems, ElementKind, ExternalKind, Operator};
+
+use crate::{
+	module::{External, Module},
+	node::{Call, CallIndirect, FuncData, RefFunc, TableCopy, TableGet, TableInit},
+	visit::{Driver, Visitor},
+};
+
+#[derive(Default)]
+struct UseList {
+	function_list: Vec<usize>,
+	table_list: Vec<usize>,
+	element_list: Vec<usize>,
+}
+
+impl Visitor for UseList {
+	fn visit_ref_func(&mut self, ref_func: RefFunc) {
+		self.function_list.push(ref_func.function);
+	}
+
+	fn visit_table_get(&mut self, table_get: &TableGet) {
+		self.table_list.push(table_get.table);
+	}
+
+	fn visit_call(&mut self, call: &Call) {
+		self.function_list.push(call.function);
+	}
+
+	fn visit_call_indirect(&mut self, call_indirect: &CallIndirect) {
+		self.table_list.push(call_indirect.table);
+	}
+
+	fn visit_table_init(&mut self, table_init: &TableInit) {
+		self.element_list.push(table_init.element);
+	}
+
+	fn visit_table_copy(&mut self, table_copy: &TableCopy) {
+		self.table_list.push(table_copy.source.table);
+	}
+}
+
+fn get_constant_function_list(init: &ConstExpr) -> Vec<usize> {
+	init.get_operators_reader()
+		.into_iter()
+		.filter_map(|v| match v.unwrap() {
+			Operator::RefFunc { function_index } => usize::try_from(function_index).ok(),
+			_ => None,
+		})
+		.collect()
+}
+
+fn get_element_function_list(items: &ElementItems) -> Vec<usize> {
+	match items.clone() {
+		ElementItems::Functions(list) => list
+			.into_iter()
+			.map(|v| usize::try_from(v.unwrap()).unwrap())
+			.collect(),
+		ElementItems::Expressions(list) => list
+			.into_iter()
+			.flat_map(|v| get_constant_function_list(&v.unwrap()))
+			.collect(),
+	}
+}
+
+/// Which functions and element segments of a module can ever be reached from
+/// its exports, its start function, and the tables that anything can read.
+pub struct Reachable {
+	is_function_used: Vec<bool>,
+	is_table_used: Vec<bool>,
+	is_element_used: Vec<bool>,
+	pending: Vec<usize>,
+}
+
+impl Reachable {
+	fn use_function(&mut self, function: usize) {
+		if !self.is_function_used[function] {
+			self.is_function_used[function] = true;
+			self.pending.push(function);
+		}
+	}
+
+	fn use_element(&mut self, wasm: &Module, element: usize) {
+		if self.is_element_used[element] {
+			return;
+		}
+
+		self.is_element_used[element] = true;
+
+		for function in get_element_function_list(&wasm.element_section()[element].items) {
+			self.use_function(function);
+		}
+	}
+
+	// Active segments only matter once their table can be read, as writing
+	// them never traps
+	fn use_table(&mut self, wasm: &Module, table: usize) {
+		if self.is_table_used[table] {
+			return;
+		}
+
+		self.is_table_used[table] = true;
+
+		for (i, element) in wasm.element_section().iter().enumerate() {
+			if let ElementKind::Active { table_index, .. } = element.kind {
+				if usize::try_from(table_index.unwrap_or(0)).unwrap() == table {
+					self.use_element(wasm, i);
+				}
+			}
+		}
+	}
+
+	fn use_root_list(&mut self, wasm: &Module) {
+		let num_import = wasm.import_count(External::Func);
+
+		for function in 0..num_import {
+			self.is_function_used[function] = true;
+		}
+
+		for table in 0..wasm.import_count(External::Table) {
+			self.use_table(wasm, table);
+		}
+
+		for export in wasm.export_section() {
+			let index = usize::try_from(export.index).unwrap();
+
+			match export.kind {
+				ExternalKind::Func => self.use_function(index),
+				ExternalKind::Table => self.use_table(wasm, index),
+				_ => {}
+			}
+		}
+
+		if let Some(start) = wasm.start_section() {
+			self.use_function(usize::try_from(start).unwrap());
+		}
+
+		for global in wasm.global_section() {
+			for function in get_constant_function_list(&global.init_expr) {
+				self.use_function(function);
+			}
+		}
+	}
+
+	/// Finds what is reachable in the module with the functions it defines
+	/// in `func_list`, following what each of them calls, references, reads
+	/// from tables, and initializes tables from.
+	#[must_use]
+	pub fn from_module(wasm: &Module, func_list: &[FuncData]) -> Self {
+		let offset = wasm.import_count(External::Func);
+		let mut reachable = Self {
+			is_function_used: vec![false; wasm.function_space()],
+			is_table_used: vec![false; wasm.table_space()],
+			is_element_used: vec![false; wasm.element_section().len()],
+			pending: Vec::new(),
+		};
+
+		reachable.use_root_list(wasm);
+
+		while let Some(function) = reachable.pending.pop() {
+			let mut use_list = UseList::default();
+
+			func_list[function - offset].accept(&mut use_list);
+
+			for function in use_list.function_list {
+				reachable.use_function(function);
+			}
+
+			for table in use_list.table_list {
+				reachable.use_table(wasm, table);
+			}
+
+			for element in use_list.element_list {
+				reachable.use_element(wasm, element);
+			}
+		}
+
+		reachable
+	}
+
+	/// Treats everything in the module as reachable.
+	#[must_use]
+	pub fn all(wasm: &Module) -> Self {
+		Self {
+			is_function_used: vec![true; wasm.function_space()],
+			is_table_used: vec![true; wasm.table_space()],
+			is_element_used: vec![true; wasm.element_section().len()],
+			pending: Vec::new(),
+		}
+	}
+
+	#[must_use]
+	pub fn is_function_used(&self, function: usize) -> bool {
+		self.is_function_used[function]
+	}
+
+	#[must_use]
+	pub fn is_element_used(&self, element: usize) -> bool {
+		self.is_element_used[element]
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use crate::{
+		factory::Factory,
+		module::{External, Module, TypeInfo},
+	};
+
+	use super::Reachable;
+
+	// Returns whether each function of the module is reachable
+	fn find_used_list(text: &str) -> Vec<bool> {
+		let data = wat::parse_str(text).unwrap();
+		let wasm = Module::try_from_data(&data).unwrap();
+		let type_info = TypeInfo::from_module(&wasm);
+		let offset = wasm.import_count(External::Func);
+		let mut factory = Factory::from_type_info(&type_info);
+		let func_list: Vec<_> = wasm
+			.code_section()
+			.iter()
+			.enumerate()
+			.map(|(i, v)| factory.create_indexed(i + offset, v).unwrap())
+			.collect();
+
+		let reachable = Reachable::from_module(&wasm, &func_list);
+
+		(0..wasm.function_space())
+			.map(|v| reachable.is_function_used(v))
+			.collect()
+	}
+
+	#[test]
+	fn keep_callee_of_export() {
+		let used_list = find_used_list(
+			"(module
+				(func $helper)
+				(func (export \"main\") call $helper)
+			)",
+		);
+
+		assert_eq!(used_list, [true, true]);
+	}
+
+	#[test]
+	fn drop_unreferenced() {
+		let used_list = find_used_list(
+			"(module
+				(func $unused call $other)
+				(func $other)
+				(func (export \"main\"))
+			)",
+		);
+
+		assert_eq!(used_list, [false, false, true]);
+	}
+
+	#[test]
+	fn keep_start() {
+		let used_list = find_used_list(
+			"(module
+				(func $helper)
+				(func $start call $helper)
+				(start $start)
+			)",
+		);
+
+		assert_eq!(used_list, [true, true]);
+	}
+
+	#[test]
+	fn keep_element_of_exported_table() {
+		let used_list = find_used_list(
+			"(module
+				(table (export \"table\") 1 funcref)
+				(func $entry)
+				(elem (i32.const 0) $entry)
+			)",
+		);
+
+		assert_eq!(used_list, [true]);
+	}
+
+	#[test]
+	fn keep_element_of_called_table() {
+		let used_list = find_used_list(
+			"(module
+				(table 1 funcref)
+				(func $entry)
+				(func (export \"main\") i32.const 0 call_indirect)
+				(elem (i32.const 0) $entry)
+			)",
+		);
+
+		assert_eq!(used_list, [true, true]);
+	}
+
+	#[test]
+	fn drop_element_of_unread_table() {
+		let used_list = find_used_list(
+			"(module
+				(table 1 funcref)
+				(func $entry)
+				(func (export \"main\"))
+				(elem (i32.const 0) $entry)
+			)",
+		);
+
+		assert_eq!(used_list, [false, true]);
+	}
+
+	#[test]
+	fn keep_element_of_table_init() {
+		let used_list = find_used_list(
+			"(module
+				(table 1 funcref)
+				(func $entry)
+				(func (export \"main\") i32.const 0 i32.const 0 i32.const 1 table.init $passive)
+				(elem $passive func $entry)
+			)",
+		);
+
+		assert_eq!(used_list, [true, true]);
+	}
+
+	#[test]
+	fn keep_ref_func_in_code() {
+		let used_list = find_used_list(
+			"(module
+				(func $target)
+				(func (export \"main\") (result funcref) ref.func $target)
+				(elem declare func $target)
+			)",
+		);
+
+		assert_eq!(used_list, [true, true]);
+	}
+
+	#[test]
+	fn keep_ref_func_in_global() {
+		let used_list = find_used_list(
+			"(module
+				(func $target)
+				(global funcref (ref.func $target))
+			)",
+		);
+
+		assert_eq!(used_list, [true]);
+	}
+
+	// Declaring a function only allows `ref.func` of it, which then has to be
+	// reached itself
+	#[test]
+	fn drop_declared_only() {
+		let used_list = find_used_list(
+			"(module
+				(func $target)
+				(func $unused (result funcref) ref.func $target)
+				(elem declare func $target)
+			)",
+		);
+
+		assert_eq!(used_list, [false, false]);
+	}
+}
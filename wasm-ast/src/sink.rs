@@ -0,0 +1,400 @@
+use crate::{
+	node::{Align, Block, Expression, FuncData, LabelType, Statement, Terminator, Try},
+	visit::{get_child_list, get_expression_list},
+};
+
+fn union(live: &mut [bool], other: &[bool]) -> bool {
+	let mut is_changed = false;
+
+	for (is_live, &is_other) in live.iter_mut().zip(other) {
+		is_changed |= is_other && !*is_live;
+		*is_live |= is_other;
+	}
+
+	is_changed
+}
+
+fn mark_read(expr: &mut Expression, live: &mut [bool]) {
+	if let Expression::GetTemporary(v) = expr {
+		live[v.var] = true;
+
+		return;
+	}
+
+	for child in get_child_list(expr) {
+		mark_read(child, live);
+	}
+}
+
+fn count_read(expr: &mut Expression, var: usize) -> usize {
+	if let Expression::GetTemporary(v) = expr {
+		return usize::from(v.var == var);
+	}
+
+	get_child_list(expr)
+		.into_iter()
+		.map(|child| count_read(child, var))
+		.sum()
+}
+
+// Values that can trap are only moved where nothing evaluated beside them
+// can trap, so that which one does first never changes, and never into an arm
+// of a `select`, which may be skipped
+fn is_movable_within(list: &mut [&mut Expression], var: usize, is_select: bool) -> bool {
+	list.iter_mut().enumerate().all(|(i, expr)| {
+		if count_read(expr, var) == 0 {
+			return expr.is_pure();
+		}
+
+		if is_select && i != 0 {
+			return false;
+		}
+
+		if let Expression::GetTemporary(_) = expr {
+			return true;
+		}
+
+		let is_select = matches!(expr, Expression::Select(_));
+
+		is_movable_within(&mut get_child_list(expr), var, is_select)
+	})
+}
+
+fn replace(expr: &mut Expression, var: usize, value: &mut Option<Expression>) {
+	if matches!(expr, Expression::GetTemporary(v) if v.var == var) {
+		*expr = value.take().unwrap();
+
+		return;
+	}
+
+	for child in get_child_list(expr) {
+		replace(child, var, value);
+	}
+}
+
+fn get_terminator_expression_list(last: &mut Terminator) -> Vec<&mut Expression> {
+	match last {
+		Terminator::BrTable(v) => vec![&mut v.condition],
+		Terminator::Throw(v) => v.param_list.iter_mut().collect(),
+		Terminator::Unreachable | Terminator::Br(_) | Terminator::Rethrow(_) => Vec::new(),
+	}
+}
+
+// Temporaries that may still be read at each point of the code, found by
+// walking it backwards from where the function ends. The start of each loop
+// has a guess that only ever grows, so walking the function again until none
+// of them do leaves every guess covering what is read after branching there
+struct Liveness {
+	label_list: Vec<Vec<bool>>,
+	loop_list: Vec<Vec<bool>>,
+	num_loop: usize,
+	handler: Vec<bool>,
+	is_changed: bool,
+	is_sinking: bool,
+}
+
+impl Liveness {
+	fn new(num_stack: usize) -> Self {
+		Self {
+			label_list: Vec::new(),
+			loop_list: Vec::new(),
+			num_loop: 0,
+			handler: vec![false; num_stack],
+			is_changed: false,
+			is_sinking: false,
+		}
+	}
+
+	// Branches that move values read the old range and write the new one
+	// before reaching their target
+	fn get_branch_live(&self, target: usize, align: Align) -> Vec<bool> {
+		let mut live = self.label_list[self.label_list.len() - 1 - target].clone();
+
+		if !align.is_aligned() {
+			for var in align.new_range().iter() {
+				live[var.var] = false;
+			}
+
+			for var in align.old_range().iter() {
+				live[var.var] = true;
+			}
+		}
+
+		live
+	}
+
+	fn get_terminator_live(&self, last: &Terminator) -> Vec<bool> {
+		match last {
+			Terminator::Br(v) => self.get_branch_live(v.target, v.align),
+			Terminator::BrTable(v) => {
+				let mut live = self.get_branch_live(v.default.target, v.default.align);
+
+				for br in &v.data {
+					union(&mut live, &self.get_branch_live(br.target, br.align));
+				}
+
+				live
+			}
+			Terminator::Unreachable | Terminator::Throw(_) | Terminator::Rethrow(_) => {
+				vec![false; self.handler.len()]
+			}
+		}
+	}
+
+	// Anything in the code of a `try` may throw into one of its handlers, so
+	// what they read is live throughout it, which is kept apart from what is
+	// found in the code and checked on its own
+	fn visit_try(&mut self, try_: &mut Try, live: &mut Vec<bool>) {
+		let mut handler = vec![false; live.len()];
+
+		for catch in &mut try_.catch_list {
+			let mut catch_live = self.visit_block(&mut catch.code, live.clone());
+
+			for var in catch.result_list.iter() {
+				catch_live[var.var] = false;
+			}
+
+			union(&mut handler, &catch_live);
+		}
+
+		let outer = self.handler.clone();
+
+		union(&mut self.handler, &handler);
+
+		*live = self.visit_block(&mut try_.code, std::mem::take(live));
+
+		self.handler = outer;
+
+		union(live, &handler);
+	}
+
+	// Turns what is live after a statement into what is live once its own
+	// expressions have been evaluated, before it does anything
+	fn visit_statement(&mut self, stat: &mut Statement, live: &mut Vec<bool>) {
+		match stat {
+			Statement::Block(v) => *live = self.visit_block(v, std::mem::take(live)),
+			Statement::If(v) => {
+				let on_false = match &mut v.on_false {
+					Some(on_false) => self.visit_block(on_false, live.clone()),
+					None => live.clone(),
+				};
+
+				*live = self.visit_block(&mut v.on_true, std::mem::take(live));
+
+				union(live, &on_false);
+			}
+			Statement::Try(v) => self.visit_try(v, live),
+			Statement::BrIf(v) => {
+				union(live, &self.get_branch_live(v.target.target, v.target.align));
+			}
+			Statement::SetTemporary(v) => live[v.var.var] = false,
+			stat => {
+				if let Some(list) = crate::copy::get_result_list(stat) {
+					for var in list.iter() {
+						live[var.var] = false;
+					}
+				}
+			}
+		}
+	}
+
+	// Nothing runs between setting a temporary and evaluating the statement
+	// after it, so when that reads it once and nothing can read it later the
+	// value is moved there
+	fn sink_last(
+		&self,
+		code: &mut Vec<Statement>,
+		offset_list: &mut Vec<usize>,
+		mut list: Vec<&mut Expression>,
+		live: &[bool],
+	) -> bool {
+		let Some(Statement::SetTemporary(v)) = code.last() else {
+			return false;
+		};
+
+		let var = v.var.var;
+
+		if live[var] || self.handler[var] {
+			return false;
+		}
+
+		let num_read: usize = list.iter_mut().map(|expr| count_read(expr, var)).sum();
+
+		if num_read != 1 || (!v.value.is_pure() && !is_movable_within(&mut list, var, false)) {
+			return false;
+		}
+
+		let Some(Statement::SetTemporary(v)) = code.pop() else {
+			unreachable!()
+		};
+
+		let mut value = Some(*v.value);
+
+		offset_list.pop();
+
+		for expr in list {
+			replace(expr, var, &mut value);
+		}
+
+		true
+	}
+
+	fn visit_block(&mut self, block: &mut Block, mut live: Vec<bool>) -> Vec<bool> {
+		let index = (block.label_type == Some(LabelType::Backward)).then(|| {
+			self.num_loop += 1;
+
+			if self.loop_list.len() < self.num_loop {
+				self.loop_list.push(vec![false; live.len()]);
+			}
+
+			self.num_loop - 1
+		});
+
+		let label = index.map_or_else(|| live.clone(), |i| self.loop_list[i].clone());
+
+		self.label_list.push(label);
+
+		if let Some(last) = block.last.as_deref_mut() {
+			live = self.get_terminator_live(last);
+
+			if self.is_sinking {
+				while self.sink_last(
+					&mut block.code,
+					&mut block.offset_list,
+					get_terminator_expression_list(last),
+					&live,
+				) {}
+			}
+
+			for expr in get_terminator_expression_list(last) {
+				mark_read(expr, &mut live);
+			}
+		}
+
+		let mut code = std::mem::take(&mut block.code);
+		let mut offset_list = std::mem::take(&mut block.offset_list);
+		let mut visited = Vec::with_capacity(code.len());
+
+		while let (Some(mut stat), Some(offset)) = (code.pop(), offset_list.pop()) {
+			self.visit_statement(&mut stat, &mut live);
+
+			if self.is_sinking {
+				while self.sink_last(
+					&mut code,
+					&mut offset_list,
+					get_expression_list(&mut stat),
+					&live,
+				) {}
+			}
+
+			for expr in get_expression_list(&mut stat) {
+				mark_read(expr, &mut live);
+			}
+
+			visited.push((stat, offset));
+		}
+
+		for (stat, offset) in visited.into_iter().rev() {
+			block.code.push(stat);
+			block.offset_list.push(offset);
+		}
+
+		self.label_list.pop();
+
+		if let Some(i) = index {
+			self.is_changed |= union(&mut self.loop_list[i], &live);
+		}
+
+		live
+	}
+
+	fn run(&mut self, func: &mut FuncData) {
+		let mut live = vec![false; func.num_stack];
+
+		live[..func.num_result.min(func.num_stack)].fill(true);
+
+		self.num_loop = 0;
+		self.is_changed = false;
+		self.visit_block(&mut func.code, live);
+	}
+}
+
+/// Moves the value of each temporary into the statement right after setting
+/// it, when that is the only one to read it before it is set again and doing
+/// so never changes what traps first, and drops the temporary.
+pub fn sink(func: &mut FuncData) {
+	let mut liveness = Liveness::new(func.num_stack);
+
+	liveness.run(func);
+
+	while liveness.is_changed {
+		liveness.run(func);
+	}
+
+	liveness.is_sinking = true;
+	liveness.run(func);
+}
+
+#[cfg(test)]
+mod test {
+	use crate::node::{BinOp, BinOpType, Expression, Local, Select, Temporary};
+
+	fn get_temporary(var: usize) -> Box<Expression> {
+		Expression::GetTemporary(Temporary { var }).into()
+	}
+
+	fn get_local(var: usize) -> Box<Expression> {
+		Expression::GetLocal(Local { var }).into()
+	}
+
+	fn bin_op(op_type: BinOpType, lhs: Box<Expression>, rhs: Box<Expression>) -> Expression {
+		Expression::BinOp(BinOp { op_type, lhs, rhs })
+	}
+
+	fn select(condition: Box<Expression>, on_true: Box<Expression>) -> Expression {
+		Expression::Select(Select {
+			ty: None,
+			condition,
+			on_true,
+			on_false: get_local(1),
+		})
+	}
+
+	#[test]
+	fn movable_beside_pure() {
+		let mut lhs = bin_op(BinOpType::Add_I32, get_temporary(0), get_local(0));
+		let mut rhs = *get_local(1);
+
+		assert!(super::is_movable_within(
+			&mut [&mut lhs, &mut rhs],
+			0,
+			false
+		));
+	}
+
+	#[test]
+	fn not_movable_beside_trap() {
+		let mut lhs = *get_temporary(0);
+		let mut rhs = bin_op(BinOpType::DivS_I32, get_local(0), get_local(1));
+
+		assert!(!super::is_movable_within(
+			&mut [&mut lhs, &mut rhs],
+			0,
+			false
+		));
+	}
+
+	#[test]
+	fn movable_into_select_condition() {
+		let mut expr = select(get_temporary(0), get_local(0));
+
+		assert!(super::is_movable_within(&mut [&mut expr], 0, false));
+	}
+
+	#[test]
+	fn not_movable_into_select_arm() {
+		let mut expr = select(get_local(0), get_temporary(0));
+
+		assert!(!super::is_movable_within(&mut [&mut expr], 0, false));
+	}
+}
@@ -1,28 +1,38 @@
 use crate::{
 	node::{
-		Align, Expression, GetGlobal, LoadAt, Local, ResultList, SetTemporary, Statement, Temporary,
+		Align, Expression, GetGlobal, LoadAt, Local, MemorySize, ResultList, SetTemporary,
+		Statement, TableGet, TableSize, Temporary,
 	},
 	visit::{Driver, Visitor},
 };
 
-pub struct ReadGet<A, B, C> {
+pub struct ReadGet<A, B, C, D> {
 	has_local: A,
 	has_global: B,
 	has_memory: C,
+	has_table: D,
 	result: bool,
 }
 
-impl<A, B, C> ReadGet<A, B, C>
+impl<A, B, C, D> ReadGet<A, B, C, D>
 where
 	A: Fn(Local) -> bool,
 	B: Fn(GetGlobal) -> bool,
-	C: Fn(&LoadAt) -> bool,
+	C: Fn(usize) -> bool,
+	D: Fn(usize) -> bool,
 {
-	pub fn run<D: Driver<Self>>(node: &D, has_local: A, has_global: B, has_memory: C) -> bool {
+	pub fn run<E: Driver<Self>>(
+		node: &E,
+		has_local: A,
+		has_global: B,
+		has_memory: C,
+		has_table: D,
+	) -> bool {
 		let mut visitor = Self {
 			has_local,
 			has_global,
 			has_memory,
+			has_table,
 			result: false,
 		};
 
@@ -32,18 +42,31 @@ where
 	}
 }
 
-impl<A, B, C> Visitor for ReadGet<A, B, C>
+impl<A, B, C, D> Visitor for ReadGet<A, B, C, D>
 where
 	A: Fn(Local) -> bool,
 	B: Fn(GetGlobal) -> bool,
-	C: Fn(&LoadAt) -> bool,
+	C: Fn(usize) -> bool,
+	D: Fn(usize) -> bool,
 {
 	fn visit_get_global(&mut self, get_global: GetGlobal) {
 		self.result |= (self.has_global)(get_global);
 	}
 
 	fn visit_load_at(&mut self, load_at: &LoadAt) {
-		self.result |= (self.has_memory)(load_at);
+		self.result |= (self.has_memory)(load_at.memory());
+	}
+
+	fn visit_memory_size(&mut self, memory_size: &MemorySize) {
+		self.result |= (self.has_memory)(memory_size.memory());
+	}
+
+	fn visit_table_get(&mut self, table_get: &TableGet) {
+		self.result |= (self.has_table)(table_get.table());
+	}
+
+	fn visit_table_size(&mut self, table_size: &TableSize) {
+		self.result |= (self.has_table)(table_size.table());
 	}
 
 	fn visit_get_local(&mut self, local: Local) {
@@ -51,6 +74,27 @@ where
 	}
 }
 
+struct ReadTemporary {
+	result: bool,
+}
+
+impl Visitor for ReadTemporary {
+	fn visit_get_temporary(&mut self, _: Temporary) {
+		self.result = true;
+	}
+}
+
+// Values that cannot trap can be evaluated as late as the next write to what
+// they read, which leaks them first, except for temporaries which nested
+// blocks reuse without leaking anything
+pub fn is_deferrable(node: &Expression) -> bool {
+	let mut visitor = ReadTemporary { result: false };
+
+	node.accept(&mut visitor);
+
+	node.is_pure() && !visitor.result
+}
+
 #[derive(Default)]
 pub struct Stack {
 	var_list: Vec<Expression>,
@@ -63,14 +107,17 @@ impl Stack {
 		self.var_list.len()
 	}
 
+	// Split off the `num_param` inputs of a new block, reserving enough slots
+	// past the block's base for either its inputs or its results
 	pub fn split_last(&mut self, num_param: usize, num_result: usize) -> Self {
 		let desired = self.len() - num_param;
 		let var_list = self.var_list.split_off(desired);
+		let previous = self.previous + desired;
 
 		Self {
 			var_list,
-			capacity: self.capacity.max(desired + num_result),
-			previous: self.previous + desired,
+			capacity: self.capacity.max(previous + num_param.max(num_result)),
+			previous,
 		}
 	}
 
@@ -123,14 +170,14 @@ impl Stack {
 	// adjusting the capacity and old index accordingly
 	pub fn leak_into<P>(&mut self, code: &mut Vec<Statement>, predicate: P)
 	where
-		P: Fn(&Expression) -> bool,
+		P: Fn(usize, &Expression) -> bool,
 	{
 		for (i, old) in self.var_list.iter_mut().enumerate() {
 			let var = self.previous + i;
 			let is_temporary =
 				matches!(old, Expression::GetTemporary(temporary) if temporary.var() == var);
 
-			if is_temporary || !predicate(old) {
+			if is_temporary || !predicate(i, old) {
 				continue;
 			}
 
@@ -1,7 +1,9 @@
 use crate::node::{
-	BinOp, Block, Br, BrIf, BrTable, Call, CallIndirect, CmpOp, Expression, FuncData, GetGlobal,
-	If, LoadAt, Local, MemoryCopy, MemoryFill, MemoryGrow, MemorySize, Select, SetGlobal, SetLocal,
-	SetTemporary, Statement, StoreAt, Temporary, Terminator, UnOp, Value,
+	AtomicNotify, AtomicWait, BinOp, Block, Br, BrIf, BrTable, Call, CallIndirect, CallRef, CmpOp,
+	DataDrop, ElemDrop, Expression, FuncData, GetGlobal, If, LoadAt, Local, MemoryCopy, MemoryFill,
+	MemoryGrow, MemoryInit, MemorySize, RefAsNonNull, RefFunc, RefIsNull, RefNull, Rethrow, Select,
+	SetGlobal, SetLocal, SetTemporary, SimdOp, Statement, StoreAt, TableCopy, TableFill, TableGet,
+	TableGrow, TableInit, TableSet, TableSize, Temporary, Terminator, Throw, Try, UnOp, Value,
 };
 
 pub trait Visitor {
@@ -17,14 +19,28 @@ pub trait Visitor {
 
 	fn visit_memory_size(&mut self, _: &MemorySize) {}
 
+	fn visit_table_get(&mut self, _: &TableGet) {}
+
+	fn visit_table_size(&mut self, _: &TableSize) {}
+
 	fn visit_value(&mut self, _: Value) {}
 
+	fn visit_ref_null(&mut self, _: RefNull) {}
+
+	fn visit_ref_func(&mut self, _: RefFunc) {}
+
+	fn visit_ref_is_null(&mut self, _: &RefIsNull) {}
+
+	fn visit_ref_as_non_null(&mut self, _: &RefAsNonNull) {}
+
 	fn visit_un_op(&mut self, _: &UnOp) {}
 
 	fn visit_bin_op(&mut self, _: &BinOp) {}
 
 	fn visit_cmp_op(&mut self, _: &CmpOp) {}
 
+	fn visit_simd_op(&mut self, _: &SimdOp) {}
+
 	fn visit_expression(&mut self, _: &Expression) {}
 
 	fn visit_unreachable(&mut self) {}
@@ -33,6 +49,10 @@ pub trait Visitor {
 
 	fn visit_br_table(&mut self, _: &BrTable) {}
 
+	fn visit_throw(&mut self, _: &Throw) {}
+
+	fn visit_rethrow(&mut self, _: Rethrow) {}
+
 	fn visit_terminator(&mut self, _: &Terminator) {}
 
 	fn visit_block(&mut self, _: &Block) {}
@@ -41,10 +61,14 @@ pub trait Visitor {
 
 	fn visit_if(&mut self, _: &If) {}
 
+	fn visit_try(&mut self, _: &Try) {}
+
 	fn visit_call(&mut self, _: &Call) {}
 
 	fn visit_call_indirect(&mut self, _: &CallIndirect) {}
 
+	fn visit_call_ref(&mut self, _: &CallRef) {}
+
 	fn visit_set_temporary(&mut self, _: &SetTemporary) {}
 
 	fn visit_set_local(&mut self, _: &SetLocal) {}
@@ -59,6 +83,26 @@ pub trait Visitor {
 
 	fn visit_memory_fill(&mut self, _: &MemoryFill) {}
 
+	fn visit_memory_init(&mut self, _: &MemoryInit) {}
+
+	fn visit_data_drop(&mut self, _: &DataDrop) {}
+
+	fn visit_table_init(&mut self, _: &TableInit) {}
+
+	fn visit_elem_drop(&mut self, _: &ElemDrop) {}
+
+	fn visit_table_set(&mut self, _: &TableSet) {}
+
+	fn visit_table_grow(&mut self, _: &TableGrow) {}
+
+	fn visit_table_copy(&mut self, _: &TableCopy) {}
+
+	fn visit_table_fill(&mut self, _: &TableFill) {}
+
+	fn visit_atomic_wait(&mut self, _: &AtomicWait) {}
+
+	fn visit_atomic_notify(&mut self, _: &AtomicNotify) {}
+
 	fn visit_statement(&mut self, _: &Statement) {}
 }
 
@@ -108,6 +152,20 @@ impl<T: Visitor> Driver<T> for MemorySize {
 	}
 }
 
+impl<T: Visitor> Driver<T> for TableGet {
+	fn accept(&self, visitor: &mut T) {
+		self.index().accept(visitor);
+
+		visitor.visit_table_get(self);
+	}
+}
+
+impl<T: Visitor> Driver<T> for TableSize {
+	fn accept(&self, visitor: &mut T) {
+		visitor.visit_table_size(self);
+	}
+}
+
 impl<T: Visitor> Driver<T> for MemoryCopy {
 	fn accept(&self, visitor: &mut T) {
 		self.destination().pointer().accept(visitor);
@@ -128,12 +186,129 @@ impl<T: Visitor> Driver<T> for MemoryFill {
 	}
 }
 
+impl<T: Visitor> Driver<T> for MemoryInit {
+	fn accept(&self, visitor: &mut T) {
+		self.destination().pointer().accept(visitor);
+		self.offset().accept(visitor);
+		self.size().accept(visitor);
+
+		visitor.visit_memory_init(self);
+	}
+}
+
+impl<T: Visitor> Driver<T> for DataDrop {
+	fn accept(&self, visitor: &mut T) {
+		visitor.visit_data_drop(self);
+	}
+}
+
+impl<T: Visitor> Driver<T> for TableInit {
+	fn accept(&self, visitor: &mut T) {
+		self.destination().accept(visitor);
+		self.offset().accept(visitor);
+		self.size().accept(visitor);
+
+		visitor.visit_table_init(self);
+	}
+}
+
+impl<T: Visitor> Driver<T> for ElemDrop {
+	fn accept(&self, visitor: &mut T) {
+		visitor.visit_elem_drop(self);
+	}
+}
+
+impl<T: Visitor> Driver<T> for TableSet {
+	fn accept(&self, visitor: &mut T) {
+		self.index().accept(visitor);
+		self.value().accept(visitor);
+
+		visitor.visit_table_set(self);
+	}
+}
+
+impl<T: Visitor> Driver<T> for TableGrow {
+	fn accept(&self, visitor: &mut T) {
+		self.value().accept(visitor);
+		self.size().accept(visitor);
+
+		visitor.visit_table_grow(self);
+	}
+}
+
+impl<T: Visitor> Driver<T> for TableCopy {
+	fn accept(&self, visitor: &mut T) {
+		self.destination().index().accept(visitor);
+		self.source().index().accept(visitor);
+		self.size().accept(visitor);
+
+		visitor.visit_table_copy(self);
+	}
+}
+
+impl<T: Visitor> Driver<T> for TableFill {
+	fn accept(&self, visitor: &mut T) {
+		self.destination().index().accept(visitor);
+		self.value().accept(visitor);
+		self.size().accept(visitor);
+
+		visitor.visit_table_fill(self);
+	}
+}
+
+impl<T: Visitor> Driver<T> for AtomicWait {
+	fn accept(&self, visitor: &mut T) {
+		self.value().accept(visitor);
+		self.expected().accept(visitor);
+		self.timeout().accept(visitor);
+
+		visitor.visit_atomic_wait(self);
+	}
+}
+
+impl<T: Visitor> Driver<T> for AtomicNotify {
+	fn accept(&self, visitor: &mut T) {
+		self.pointer().accept(visitor);
+		self.count().accept(visitor);
+
+		visitor.visit_atomic_notify(self);
+	}
+}
+
 impl<T: Visitor> Driver<T> for Value {
 	fn accept(&self, visitor: &mut T) {
 		visitor.visit_value(*self);
 	}
 }
 
+impl<T: Visitor> Driver<T> for RefNull {
+	fn accept(&self, visitor: &mut T) {
+		visitor.visit_ref_null(*self);
+	}
+}
+
+impl<T: Visitor> Driver<T> for RefFunc {
+	fn accept(&self, visitor: &mut T) {
+		visitor.visit_ref_func(*self);
+	}
+}
+
+impl<T: Visitor> Driver<T> for RefIsNull {
+	fn accept(&self, visitor: &mut T) {
+		self.reference().accept(visitor);
+
+		visitor.visit_ref_is_null(self);
+	}
+}
+
+impl<T: Visitor> Driver<T> for RefAsNonNull {
+	fn accept(&self, visitor: &mut T) {
+		self.reference().accept(visitor);
+
+		visitor.visit_ref_as_non_null(self);
+	}
+}
+
 impl<T: Visitor> Driver<T> for UnOp {
 	fn accept(&self, visitor: &mut T) {
 		self.rhs().accept(visitor);
@@ -160,6 +335,16 @@ impl<T: Visitor> Driver<T> for CmpOp {
 	}
 }
 
+impl<T: Visitor> Driver<T> for SimdOp {
+	fn accept(&self, visitor: &mut T) {
+		for v in self.param_list() {
+			v.accept(visitor);
+		}
+
+		visitor.visit_simd_op(self);
+	}
+}
+
 impl<T: Visitor> Driver<T> for Expression {
 	fn accept(&self, visitor: &mut T) {
 		match self {
@@ -169,10 +354,17 @@ impl<T: Visitor> Driver<T> for Expression {
 			Self::GetGlobal(v) => v.accept(visitor),
 			Self::LoadAt(v) => v.accept(visitor),
 			Self::MemorySize(v) => v.accept(visitor),
+			Self::TableGet(v) => v.accept(visitor),
+			Self::TableSize(v) => v.accept(visitor),
 			Self::Value(v) => v.accept(visitor),
+			Self::RefNull(v) => v.accept(visitor),
+			Self::RefFunc(v) => v.accept(visitor),
+			Self::RefIsNull(v) => v.accept(visitor),
+			Self::RefAsNonNull(v) => v.accept(visitor),
 			Self::UnOp(v) => v.accept(visitor),
 			Self::BinOp(v) => v.accept(visitor),
 			Self::CmpOp(v) => v.accept(visitor),
+			Self::SimdOp(v) => v.accept(visitor),
 		}
 
 		visitor.visit_expression(self);
@@ -193,12 +385,30 @@ impl<T: Visitor> Driver<T> for BrTable {
 	}
 }
 
+impl<T: Visitor> Driver<T> for Throw {
+	fn accept(&self, visitor: &mut T) {
+		for v in self.param_list() {
+			v.accept(visitor);
+		}
+
+		visitor.visit_throw(self);
+	}
+}
+
+impl<T: Visitor> Driver<T> for Rethrow {
+	fn accept(&self, visitor: &mut T) {
+		visitor.visit_rethrow(*self);
+	}
+}
+
 impl<T: Visitor> Driver<T> for Terminator {
 	fn accept(&self, visitor: &mut T) {
 		match self {
 			Self::Unreachable => visitor.visit_unreachable(),
 			Self::Br(v) => v.accept(visitor),
 			Self::BrTable(v) => v.accept(visitor),
+			Self::Throw(v) => v.accept(visitor),
+			Self::Rethrow(v) => v.accept(visitor),
 		}
 
 		visitor.visit_terminator(self);
@@ -240,6 +450,18 @@ impl<T: Visitor> Driver<T> for If {
 	}
 }
 
+impl<T: Visitor> Driver<T> for Try {
+	fn accept(&self, visitor: &mut T) {
+		self.code().accept(visitor);
+
+		for v in self.catch_list() {
+			v.code().accept(visitor);
+		}
+
+		visitor.visit_try(self);
+	}
+}
+
 impl<T: Visitor> Driver<T> for Call {
 	fn accept(&self, visitor: &mut T) {
 		for v in self.param_list() {
@@ -262,6 +484,18 @@ impl<T: Visitor> Driver<T> for CallIndirect {
 	}
 }
 
+impl<T: Visitor> Driver<T> for CallRef {
+	fn accept(&self, visitor: &mut T) {
+		self.function().accept(visitor);
+
+		for v in self.param_list() {
+			v.accept(visitor);
+		}
+
+		visitor.visit_call_ref(self);
+	}
+}
+
 impl<T: Visitor> Driver<T> for SetTemporary {
 	fn accept(&self, visitor: &mut T) {
 		self.value().accept(visitor);
@@ -309,8 +543,10 @@ impl<T: Visitor> Driver<T> for Statement {
 			Self::Block(v) => v.accept(visitor),
 			Self::BrIf(v) => v.accept(visitor),
 			Self::If(v) => v.accept(visitor),
+			Self::Try(v) => v.accept(visitor),
 			Self::Call(v) => v.accept(visitor),
 			Self::CallIndirect(v) => v.accept(visitor),
+			Self::CallRef(v) => v.accept(visitor),
 			Self::SetTemporary(v) => v.accept(visitor),
 			Self::SetLocal(v) => v.accept(visitor),
 			Self::SetGlobal(v) => v.accept(visitor),
@@ -318,6 +554,16 @@ impl<T: Visitor> Driver<T> for Statement {
 			Self::MemoryGrow(v) => v.accept(visitor),
 			Self::MemoryCopy(v) => v.accept(visitor),
 			Self::MemoryFill(v) => v.accept(visitor),
+			Self::MemoryInit(v) => v.accept(visitor),
+			Self::DataDrop(v) => v.accept(visitor),
+			Self::TableInit(v) => v.accept(visitor),
+			Self::ElemDrop(v) => v.accept(visitor),
+			Self::TableSet(v) => v.accept(visitor),
+			Self::TableGrow(v) => v.accept(visitor),
+			Self::TableCopy(v) => v.accept(visitor),
+			Self::TableFill(v) => v.accept(visitor),
+			Self::AtomicWait(v) => v.accept(visitor),
+			Self::AtomicNotify(v) => v.accept(visitor),
 		}
 
 		visitor.visit_statement(self);
@@ -329,3 +575,79 @@ impl<T: Visitor> Driver<T> for FuncData {
 		self.code().accept(visitor);
 	}
 }
+
+// The expressions that a statement evaluates itself, leaving out the code of
+// any nested blocks
+pub(crate) fn get_expression_list(stat: &mut Statement) -> Vec<&mut Expression> {
+	match stat {
+		Statement::Block(_)
+		| Statement::Try(_)
+		| Statement::DataDrop(_)
+		| Statement::ElemDrop(_) => Vec::new(),
+		Statement::BrIf(v) => vec![&mut v.condition],
+		Statement::If(v) => vec![&mut v.condition],
+		Statement::Call(v) => v.param_list.iter_mut().collect(),
+		Statement::CallIndirect(v) => std::iter::once(v.index.as_mut())
+			.chain(&mut v.param_list)
+			.collect(),
+		Statement::CallRef(v) => std::iter::once(v.function.as_mut())
+			.chain(&mut v.param_list)
+			.collect(),
+		Statement::SetTemporary(v) => vec![&mut v.value],
+		Statement::SetLocal(v) => vec![&mut v.value],
+		Statement::SetGlobal(v) => vec![&mut v.value],
+		Statement::StoreAt(v) => vec![&mut v.pointer, &mut v.value],
+		Statement::MemoryGrow(v) => vec![&mut v.size],
+		Statement::MemoryCopy(v) => vec![
+			&mut v.destination.pointer,
+			&mut v.source.pointer,
+			&mut v.size,
+		],
+		Statement::MemoryFill(v) => vec![&mut v.destination.pointer, &mut v.size, &mut v.value],
+		Statement::MemoryInit(v) => vec![&mut v.destination.pointer, &mut v.offset, &mut v.size],
+		Statement::TableInit(v) => vec![&mut v.destination, &mut v.offset, &mut v.size],
+		Statement::TableSet(v) => vec![&mut v.index, &mut v.value],
+		Statement::TableGrow(v) => vec![&mut v.size, &mut v.value],
+		Statement::TableCopy(v) => vec![&mut v.destination.index, &mut v.source.index, &mut v.size],
+		Statement::TableFill(v) => vec![&mut v.destination.index, &mut v.size, &mut v.value],
+		Statement::AtomicWait(v) => vec![&mut v.value, &mut v.expected, &mut v.timeout],
+		Statement::AtomicNotify(v) => vec![&mut v.pointer, &mut v.count],
+	}
+}
+
+// The operands of an expression in the order they are evaluated
+pub(crate) fn get_child_list(expr: &mut Expression) -> Vec<&mut Expression> {
+	match expr {
+		Expression::Select(v) => vec![&mut v.condition, &mut v.on_true, &mut v.on_false],
+		Expression::LoadAt(v) => vec![&mut v.pointer],
+		Expression::TableGet(v) => vec![&mut v.index],
+		Expression::RefIsNull(v) => vec![&mut v.reference],
+		Expression::RefAsNonNull(v) => vec![&mut v.reference],
+		Expression::UnOp(v) => vec![&mut v.rhs],
+		Expression::BinOp(v) => vec![&mut v.lhs, &mut v.rhs],
+		Expression::CmpOp(v) => vec![&mut v.lhs, &mut v.rhs],
+		Expression::SimdOp(v) => v.param_list.iter_mut().collect(),
+		Expression::GetTemporary(_)
+		| Expression::GetLocal(_)
+		| Expression::GetGlobal(_)
+		| Expression::MemorySize(_)
+		| Expression::TableSize(_)
+		| Expression::Value(_)
+		| Expression::RefNull(_)
+		| Expression::RefFunc(_) => Vec::new(),
+	}
+}
+
+// The blocks that a statement runs nested within it
+pub(crate) fn get_block_list(stat: &mut Statement) -> Vec<&mut Block> {
+	match stat {
+		Statement::Block(v) => vec![v],
+		Statement::If(v) => std::iter::once(v.on_true.as_mut())
+			.chain(v.on_false.as_deref_mut())
+			.collect(),
+		Statement::Try(v) => std::iter::once(&mut v.code)
+			.chain(v.catch_list.iter_mut().map(|v| &mut v.code))
+			.collect(),
+		_ => Vec::new(),
+	}
+}
@@ -0,0 +1,1439 @@
+#[cfg(feature = "run")]
+mod run;
+#[cfg(feature = "verify")]
+mod verify;
+mod watch;
+
+#[cfg(feature = "wat")]
+use std::io::Error;
+use std::{
+	borrow::Cow,
+	collections::BTreeSet,
+	ffi::OsStr,
+	fs::File,
+	io::{BufWriter, ErrorKind, Read, Result, Write},
+	path::Path,
+};
+
+use codegen_core::{Mapping, Split};
+use codegen_luajit::LuaJIT;
+use wasm_ast::{
+	factory::Factory,
+	module::{External, Module, TypeInfo},
+	optimize::Level,
+};
+
+const USAGE: &str =
+	"usage: wasynth [build] [<file> | -] --target <name> [-o <file>] [--custom <name>]... [--no-goto] [--shorten] [--minify] [--split <bytes>] [--source-map <file>] [--runtime <expr>] [--memory <layout>] [--i64 <repr>] [--fuel <budget>] [--yield-every <iterations>] [--max-depth <calls>] [--debug-names] [--trace] [--profile] [--coverage] [--async] [--compact-data] [--tree-shake] [--fold-offsets] [-O0 | -O1 | -O2]
+       wasynth [build] [<file> | -] --target luajit --run [--import <file>] [--invoke <name>]
+       wasynth [build] <file>... --target <name> --out-dir <dir> [--inline-runtime] [options]
+       wasynth watch <file>... --target <name> (-o <file> | --out-dir <dir> | --run) [options]
+       wasynth verify [<file> | -] [--target luajit] [--count <calls>] [--seed <number>] [options]
+       wasynth dump [<file> | -] [-o <file>] [--function <index>] [-O0 | -O1 | -O2]
+       wasynth selftest --target <name> [-o <file>] [--memory <layout>] [--i64 <repr>]
+
+the module is read from stdin when no file or `-` is given, and the output is
+written to stdout when no `-o` is given
+
+with `--out-dir`, every file is translated to its own script in the directory,
+all of which load one shared `runtime` script unless `--inline-runtime` embeds
+it in each of them, as a single output always does
+
+with `--runtime`, the runtime is loaded with the given Lua expression, such
+as `require(script.Parent.Runtime)`, instead of being embedded or required
+from next to the script, and it must return the runtime as `--out-dir` writes
+it
+
+with `--split`, functions are moved out to chunks of about that many bytes,
+written next to the script that loads them
+
+with `--source-map`, a JSON file is written mapping lines of the output back
+to the function index and instruction offset in the module they came from
+
+with `--run`, the output is run in an embedded LuaJIT instead of written, with
+the imports taken from the table that the `--import` Lua file returns, and the
+`--invoke` export or else `_start` is called with its results printed
+
+with `watch`, the files are translated again every time they change until
+the command is stopped, and with `--run` the output is run again
+
+with `verify`, every export taking and returning numbers is called with the
+same generated arguments under wasmtime and the translation, `--count` times
+each, and any difference in results or traps is reported
+
+with `dump`, the code that functions are built into is written in a readable
+form instead of being translated, for `--function` alone when it is given
+
+with `selftest`, a script is written that checks the runtime of the target
+against edge cases such as dividing the least integer by -1, the minimum of a
+NaN, and shifting by 32 or more, printing each that fails on the host it is
+run on
+
+with `--fuel`, functions count down a budget on entry and on every loop
+iteration, and trap once it is spent unless the `handler` of the `fuel` table
+on the instance returns more
+
+with `--yield-every`, loops count their iterations and yield the running
+coroutine once every that many, or call the `handler` of the `yield` table on
+the instance instead when it is set
+
+with `--max-depth`, calls nested deeper than the limit trap as the call stack
+being exhausted, and exported functions are guarded so that the count is put
+back when a call from the host fails
+
+with `--debug-names`, every function is defined as a local named after it in
+the name section, or after its index without one, so that Luau tracebacks
+show those names instead of anonymous functions
+
+with `--memory words`, the `lua51`, `glua`, and `cct` targets lay memory out
+as a table of 32 bit words instead of the default `bytes`, which is faster for
+code that mostly accesses whole words
+
+with `--trace`, the `luau`, `lua51`, `glua`, `cct`, and `fengari` targets
+pass the loads, stores, and globals that the `trace` table of the instance is
+told to watch to its `hook`, which prints them unless replaced
+
+with `--profile`, every function counts its calls and the time spent in it
+outside of the calls it makes, which `profile.dump()` on the instance lists
+with the slowest first
+
+with `--coverage`, every block of every function counts how often it is
+entered, which `coverage.dump()` on the instance lists by function and block
+
+with `--async`, `instance:call_async(name, ...)` calls an export in its own
+coroutine, which an import suspends by yielding a function that is given
+another to resume it with the results of the import
+
+with `--compact-data`, data segments are written as text in base 85 that the
+runtime decodes as the module starts, which is far smaller than escaping every
+byte that is not printable
+
+with `--tree-shake`, functions that no export, start function, or readable
+table reaches are left out, along with element segments filling tables that
+nothing reads
+
+with `--fold-offsets`, constants added to addresses are moved into the offset
+of the load or store, which is only correct when no address wraps around past
+4 GiB, as compilers never make one do
+
+with `--i64 pair`, the `luau` target keeps every `i64` as a table of its two
+32 bit halves instead of packed in a `Vector3`, which is faster for code heavy
+on 64 bit arithmetic
+
+`-O0` lowers functions as they are built, `-O1` folds constants, turns
+multiplying and unsigned dividing by powers of two into shifts, simplifies
+tests of comparisons, double negations and redundant extensions, reads copies
+of locals and temporaries from what they copy, drops code that never runs and
+locals that are never read, nests the code after branches to the end of a
+block in an `if` so that the block needs no label, moves temporaries read only
+by the next statement into it, and shares temporaries that are never alive at
+once, and `-O2` also inlines small functions, resolves branches on constants,
+reads repeated loads and globals once, reuses arithmetic already computed
+before it or in an enclosing block, reads globals that never change into locals and calls constant
+entries of tables that never change directly
+
+targets: luajit, luau, lua51, lua54, glua, teal, cct, openresty, love, fengari";
+
+const RUNTIME_NAME: &str = "runtime";
+
+#[cfg(feature = "verify")]
+const VERIFY_COUNT: usize = 100;
+#[cfg(feature = "verify")]
+const VERIFY_SEED: u64 = 1;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Command {
+	Build,
+	Watch,
+	Verify,
+	Dump,
+	SelfTest,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Layout {
+	Bytes,
+	Words,
+}
+
+impl Layout {
+	fn from_name(name: &str) -> Option<Self> {
+		match name {
+			"bytes" => Some(Self::Bytes),
+			"words" => Some(Self::Words),
+			_ => None,
+		}
+	}
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Repr {
+	Vector,
+	Pair,
+}
+
+impl Repr {
+	fn from_name(name: &str) -> Option<Self> {
+		match name {
+			"vector" => Some(Self::Vector),
+			"pair" => Some(Self::Pair),
+			_ => None,
+		}
+	}
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Target {
+	LuaJIT,
+	Luau,
+	Lua51,
+	Lua54,
+	GLua,
+	Teal,
+	CCTweaked,
+	OpenResty,
+	Love,
+	Fengari,
+}
+
+impl Target {
+	fn from_name(name: &str) -> Option<Self> {
+		let result = match name {
+			"luajit" => Self::LuaJIT,
+			"luau" => Self::Luau,
+			"lua51" => Self::Lua51,
+			"lua54" => Self::Lua54,
+			"glua" => Self::GLua,
+			"teal" => Self::Teal,
+			"cct" => Self::CCTweaked,
+			"openresty" => Self::OpenResty,
+			"love" => Self::Love,
+			"fengari" => Self::Fengari,
+			_ => return None,
+		};
+
+		Some(result)
+	}
+
+	const fn runtime(self) -> &'static str {
+		match self {
+			Self::LuaJIT => codegen_luajit::RUNTIME,
+			Self::Luau => codegen_luau::RUNTIME,
+			Self::Lua51 => codegen_lua51::RUNTIME,
+			Self::Lua54 => codegen_lua54::RUNTIME,
+			Self::GLua => codegen_glua::RUNTIME,
+			Self::Teal => codegen_teal::RUNTIME,
+			Self::CCTweaked => codegen_cct::RUNTIME,
+			Self::OpenResty => codegen_openresty::RUNTIME,
+			Self::Love => codegen_love::RUNTIME,
+			Self::Fengari => codegen_fengari::RUNTIME,
+		}
+	}
+
+	// Only the runtimes built on the Lua 5.1 `buffer` have another layout, and
+	// only Luau has another `i64` representation
+	const fn runtime_in(self, layout: Layout, repr: Repr) -> &'static str {
+		match (self, layout, repr) {
+			(Self::Lua51, Layout::Words, _) => codegen_lua51::RUNTIME_WORDS,
+			(Self::GLua, Layout::Words, _) => codegen_glua::RUNTIME_WORDS,
+			(Self::CCTweaked, Layout::Words, _) => codegen_cct::RUNTIME_WORDS,
+			(Self::Luau, _, Repr::Pair) => codegen_luau::RUNTIME_PAIR,
+			_ => self.runtime(),
+		}
+	}
+
+	// Tracing rebinds the loads and stores of the runtime it is appended to,
+	// which enclosed runtimes keep out of its reach
+	const fn trace(self) -> Option<&'static str> {
+		match self {
+			Self::Luau => Some(codegen_luau::TRACE),
+			Self::Lua51 | Self::GLua | Self::CCTweaked | Self::Fengari => {
+				Some(codegen_lua51::TRACE)
+			}
+			_ => None,
+		}
+	}
+
+	const fn has_layout(self) -> bool {
+		matches!(self, Self::Lua51 | Self::GLua | Self::CCTweaked)
+	}
+
+	// Enclosed runtimes return a table of their functions, the others are
+	// made of top level locals the translated code names directly
+	const fn is_enclosed(self) -> bool {
+		matches!(
+			self,
+			Self::LuaJIT | Self::Lua54 | Self::Teal | Self::OpenResty | Self::Love
+		)
+	}
+
+	const fn extension(self) -> &'static str {
+		match self {
+			Self::Luau => "luau",
+			Self::Teal => "tl",
+			_ => "lua",
+		}
+	}
+}
+
+struct Options {
+	input_list: Vec<String>,
+	output: Option<String>,
+	out_dir: Option<String>,
+	inline_runtime: bool,
+	runtime: Option<String>,
+	layout: Layout,
+	repr: Repr,
+	target: Target,
+	custom_list: Vec<String>,
+	no_goto: bool,
+	shorten: bool,
+	minify: bool,
+	split: Option<usize>,
+	source_map: Option<String>,
+	level: Level,
+	fuel: Option<u64>,
+	yield_every: Option<u32>,
+	max_depth: Option<u32>,
+	debug_names: bool,
+	trace: bool,
+	profile: bool,
+	coverage: bool,
+	call_async: bool,
+	compact_data: bool,
+	tree_shake: bool,
+	fold_offsets: bool,
+	command: Command,
+	function: Option<usize>,
+	#[cfg(feature = "run")]
+	run: bool,
+	#[cfg(feature = "run")]
+	import: Option<String>,
+	#[cfg(feature = "run")]
+	invoke: Option<String>,
+	#[cfg(feature = "verify")]
+	count: usize,
+	#[cfg(feature = "verify")]
+	seed: u64,
+}
+
+impl Options {
+	const fn translation(&self) -> codegen_core::Options {
+		codegen_core::Options {
+			level: self.level,
+			fuel: self.fuel,
+			yield_every: self.yield_every,
+			max_depth: self.max_depth,
+			debug_names: self.debug_names,
+			trace: self.trace,
+			profile: self.profile,
+			coverage: self.coverage,
+			call_async: self.call_async,
+			compact_data: self.compact_data,
+			tree_shake: self.tree_shake,
+			fold_offsets: self.fold_offsets,
+		}
+	}
+
+	// Tracing is appended to whichever runtime the layout and representation
+	// pick rather than being another build of each
+	fn runtime(&self) -> Cow<'static, str> {
+		let runtime = self.target.runtime_in(self.layout, self.repr);
+
+		match self.target.trace() {
+			Some(trace) if self.trace => Cow::Owned(format!("{runtime}{trace}")),
+			_ => Cow::Borrowed(runtime),
+		}
+	}
+}
+
+fn fail_usage(message: &str) -> std::io::Error {
+	eprintln!("{message}\n\n{USAGE}\n");
+
+	ErrorKind::InvalidInput.into()
+}
+
+fn load_arg_options() -> Result<Options> {
+	let mut arguments = std::env::args().skip(1).peekable();
+
+	let command = match arguments.peek().map(String::as_str) {
+		Some("watch") => Command::Watch,
+		Some("verify") => Command::Verify,
+		Some("dump") => Command::Dump,
+		Some("selftest") => Command::SelfTest,
+		_ => Command::Build,
+	};
+
+	if command != Command::Build || arguments.peek().is_some_and(|v| v == "build") {
+		arguments.next();
+	}
+
+	let mut input_list = Vec::new();
+	let mut output = None;
+	let mut out_dir = None;
+	let mut inline_runtime = false;
+	let mut runtime = None;
+	let mut layout = Layout::Bytes;
+	let mut repr = Repr::Vector;
+	let mut target = None;
+	let mut custom_list = Vec::new();
+	let mut no_goto = false;
+	let mut shorten = false;
+	let mut minify = false;
+	let mut split = None;
+	let mut source_map = None;
+	let mut level = Level::Direct;
+	let mut fuel = None;
+	let mut yield_every = None;
+	let mut max_depth = None;
+	let mut debug_names = false;
+	let mut trace = false;
+	let mut profile = false;
+	let mut coverage = false;
+	let mut call_async = false;
+	let mut compact_data = false;
+	let mut tree_shake = false;
+	let mut fold_offsets = false;
+	let mut run = false;
+	let mut import = None;
+	let mut invoke = None;
+	let mut count: Option<usize> = None;
+	let mut seed: Option<u64> = None;
+	let mut function = None;
+
+	while let Some(argument) = arguments.next() {
+		match argument.as_str() {
+			"--target" | "-t" => {
+				let name = arguments.next().unwrap_or_default();
+				let result = Target::from_name(&name)
+					.ok_or_else(|| fail_usage(&format!("unknown target `{name}`")))?;
+
+				target = Some(result);
+			}
+			"--output" | "-o" => {
+				output = Some(
+					arguments
+						.next()
+						.ok_or_else(|| fail_usage("expected a file"))?,
+				);
+			}
+			"--out-dir" => {
+				out_dir = Some(
+					arguments
+						.next()
+						.ok_or_else(|| fail_usage("expected a directory"))?,
+				);
+			}
+			"--inline-runtime" => inline_runtime = true,
+			"--memory" => {
+				let name = arguments.next().unwrap_or_default();
+
+				layout = Layout::from_name(&name)
+					.ok_or_else(|| fail_usage(&format!("unknown memory layout `{name}`")))?;
+			}
+			"--i64" => {
+				let name = arguments.next().unwrap_or_default();
+
+				repr = Repr::from_name(&name)
+					.ok_or_else(|| fail_usage(&format!("unknown i64 representation `{name}`")))?;
+			}
+			"--runtime" => {
+				runtime = Some(
+					arguments
+						.next()
+						.ok_or_else(|| fail_usage("expected a Lua expression"))?,
+				);
+			}
+			"--custom" => {
+				let name = arguments
+					.next()
+					.ok_or_else(|| fail_usage("expected a section name"))?;
+
+				custom_list.push(name);
+			}
+			"--run" => run = true,
+			"--import" => {
+				import = Some(
+					arguments
+						.next()
+						.ok_or_else(|| fail_usage("expected a file"))?,
+				);
+			}
+			"--invoke" => {
+				invoke = Some(
+					arguments
+						.next()
+						.ok_or_else(|| fail_usage("expected an export name"))?,
+				);
+			}
+			"--count" => {
+				let calls = arguments
+					.next()
+					.and_then(|v| v.parse().ok())
+					.ok_or_else(|| fail_usage("expected a number of calls"))?;
+
+				count = Some(calls);
+			}
+			"--seed" => {
+				let number = arguments
+					.next()
+					.and_then(|v| v.parse().ok())
+					.ok_or_else(|| fail_usage("expected a number"))?;
+
+				seed = Some(number);
+			}
+			"--function" => {
+				let index = arguments
+					.next()
+					.and_then(|v| v.parse().ok())
+					.ok_or_else(|| fail_usage("expected a function index"))?;
+
+				function = Some(index);
+			}
+			"-O0" => level = Level::Direct,
+			"-O1" => level = Level::Cleanup,
+			"-O2" => level = Level::Full,
+			"--fuel" => {
+				let budget = arguments
+					.next()
+					.and_then(|v| v.parse().ok())
+					.ok_or_else(|| fail_usage("expected a fuel budget"))?;
+
+				fuel = Some(budget);
+			}
+			"--yield-every" => {
+				let every = arguments
+					.next()
+					.and_then(|v| v.parse().ok())
+					.filter(|&v| v != 0)
+					.ok_or_else(|| fail_usage("expected a number of iterations"))?;
+
+				yield_every = Some(every);
+			}
+			"--max-depth" => {
+				let limit = arguments
+					.next()
+					.and_then(|v| v.parse().ok())
+					.ok_or_else(|| fail_usage("expected a call depth"))?;
+
+				max_depth = Some(limit);
+			}
+			"--debug-names" => debug_names = true,
+			"--trace" => trace = true,
+			"--profile" => profile = true,
+			"--coverage" => coverage = true,
+			"--async" => call_async = true,
+			"--compact-data" => compact_data = true,
+			"--tree-shake" => tree_shake = true,
+			"--fold-offsets" => fold_offsets = true,
+			"--no-goto" => no_goto = true,
+			"--shorten" => shorten = true,
+			"--minify" => minify = true,
+			"--split" => {
+				let limit = arguments
+					.next()
+					.and_then(|v| v.parse().ok())
+					.ok_or_else(|| fail_usage("expected a size in bytes"))?;
+
+				split = Some(limit);
+			}
+			"--source-map" => {
+				source_map = Some(
+					arguments
+						.next()
+						.ok_or_else(|| fail_usage("expected a file"))?,
+				);
+			}
+			_ if argument == "-" || !argument.starts_with('-') => input_list.push(argument),
+			_ => return Err(fail_usage(&format!("unexpected argument `{argument}`"))),
+		}
+	}
+
+	// Only LuaJIT can be run, so `verify` does not need to be told the target
+	let target = match target {
+		Some(target) => target,
+		None if command == Command::Verify => Target::LuaJIT,
+		// The code is dumped before any target lowers it
+		None if command == Command::Dump => Target::LuaJIT,
+		None if command == Command::SelfTest => {
+			return Err(fail_usage("`selftest` expects a target"));
+		}
+		None => return Err(fail_usage("expected a target")),
+	};
+
+	if out_dir.is_some() {
+		if input_list.is_empty() || input_list.iter().any(|v| v == "-") {
+			return Err(fail_usage("`--out-dir` expects input files"));
+		}
+
+		if output.is_some() {
+			return Err(fail_usage("`-o` does not apply with `--out-dir`"));
+		}
+	} else if input_list.len() > 1 {
+		return Err(fail_usage("several input files need `--out-dir`"));
+	} else if inline_runtime {
+		return Err(fail_usage(
+			"`--inline-runtime` needs `--out-dir`, as a single output embeds the runtime",
+		));
+	}
+
+	if no_goto && target != Target::LuaJIT {
+		return Err(fail_usage(
+			"`--no-goto` only applies to the `luajit` target",
+		));
+	}
+
+	if shorten && target == Target::Teal {
+		return Err(fail_usage(
+			"`--shorten` does not apply to the `teal` target",
+		));
+	}
+
+	if trace && target.trace().is_none() {
+		return Err(fail_usage(
+			"`--trace` only applies to the `luau`, `lua51`, `glua`, `cct`, and `fengari` targets",
+		));
+	}
+
+	if shorten && debug_names {
+		return Err(fail_usage(
+			"`--shorten` renames the functions that `--debug-names` names",
+		));
+	}
+
+	if split.is_some() {
+		if output.is_none() && out_dir.is_none() {
+			return Err(fail_usage("`--split` needs `-o` or `--out-dir`"));
+		}
+
+		if target == Target::Teal {
+			return Err(fail_usage("`--split` does not apply to the `teal` target"));
+		}
+	}
+
+	if layout != Layout::Bytes && !target.has_layout() {
+		return Err(fail_usage(
+			"`--memory` only applies to the `lua51`, `glua`, and `cct` targets",
+		));
+	}
+
+	if repr != Repr::Vector && target != Target::Luau {
+		return Err(fail_usage("`--i64` only applies to the `luau` target"));
+	}
+
+	if inline_runtime && runtime.is_some() {
+		return Err(fail_usage(
+			"`--inline-runtime` does not apply with `--runtime`",
+		));
+	}
+
+	// Minifying joins lines and the other two write several scripts, so the
+	// lines would no longer line up with the map
+	if source_map.is_some() && (minify || split.is_some() || out_dir.is_some() || runtime.is_some())
+	{
+		return Err(fail_usage(
+			"`--source-map` does not apply with `--minify`, `--split`, `--out-dir`, or `--runtime`",
+		));
+	}
+
+	if command == Command::Watch {
+		if input_list.is_empty() || input_list.iter().any(|v| v == "-") {
+			return Err(fail_usage("`watch` expects input files"));
+		}
+
+		if output.is_none() && out_dir.is_none() && !run {
+			return Err(fail_usage("`watch` needs `-o`, `--out-dir`, or `--run`"));
+		}
+	}
+
+	if command == Command::Verify {
+		if cfg!(not(feature = "verify")) {
+			return Err(fail_usage(
+				"`verify` needs wasynth built with the `verify` feature",
+			));
+		}
+
+		if target != Target::LuaJIT {
+			return Err(fail_usage("`verify` only applies to the `luajit` target"));
+		}
+
+		if output.is_some()
+			|| out_dir.is_some()
+			|| split.is_some()
+			|| source_map.is_some()
+			|| runtime.is_some()
+			|| run
+		{
+			return Err(fail_usage(
+				"`verify` does not write files, so it does not apply with `-o`, `--out-dir`, `--split`, `--source-map`, `--runtime`, or `--run`",
+			));
+		}
+	} else if count.is_some() || seed.is_some() {
+		return Err(fail_usage("`--count` and `--seed` need `verify`"));
+	}
+
+	if command == Command::Dump {
+		if out_dir.is_some()
+			|| !custom_list.is_empty()
+			|| no_goto
+			|| shorten
+			|| minify || split.is_some()
+			|| source_map.is_some()
+			|| runtime.is_some()
+			|| fuel.is_some()
+			|| yield_every.is_some()
+			|| max_depth.is_some()
+			|| debug_names
+			|| trace || profile
+			|| coverage
+			|| call_async
+			|| compact_data
+			|| tree_shake
+			|| run
+		{
+			return Err(fail_usage(
+				"`dump` does not translate, so it only takes `-o`, `--function`, `--fold-offsets`, and `-O`",
+			));
+		}
+	} else if function.is_some() {
+		return Err(fail_usage("`--function` needs `dump`"));
+	}
+
+	if command == Command::SelfTest
+		&& (!input_list.is_empty()
+			|| out_dir.is_some()
+			|| !custom_list.is_empty()
+			|| no_goto
+			|| shorten
+			|| minify || split.is_some()
+			|| source_map.is_some()
+			|| runtime.is_some()
+			|| level != Level::Direct
+			|| fuel.is_some()
+			|| yield_every.is_some()
+			|| max_depth.is_some()
+			|| debug_names
+			|| trace || profile
+			|| coverage
+			|| call_async
+			|| compact_data
+			|| tree_shake
+			|| fold_offsets
+			|| run)
+	{
+		return Err(fail_usage(
+			"`selftest` does not translate a module, so it only takes `--target`, `-o`, `--memory`, and `--i64`",
+		));
+	}
+
+	if !run && (import.is_some() || invoke.is_some()) {
+		return Err(fail_usage("`--import` and `--invoke` need `--run`"));
+	}
+
+	if run {
+		if cfg!(not(feature = "run")) {
+			return Err(fail_usage(
+				"`--run` needs wasynth built with the `run` feature",
+			));
+		}
+
+		if target != Target::LuaJIT {
+			return Err(fail_usage("`--run` only applies to the `luajit` target"));
+		}
+
+		if output.is_some()
+			|| out_dir.is_some()
+			|| split.is_some()
+			|| source_map.is_some()
+			|| runtime.is_some()
+		{
+			return Err(fail_usage(
+				"`--run` does not write files, so it does not apply with `-o`, `--out-dir`, `--split`, `--source-map`, or `--runtime`",
+			));
+		}
+	}
+
+	Ok(Options {
+		input_list,
+		output,
+		out_dir,
+		inline_runtime,
+		runtime,
+		layout,
+		repr,
+		target,
+		custom_list,
+		no_goto,
+		shorten,
+		minify,
+		split,
+		source_map,
+		level,
+		fuel,
+		yield_every,
+		max_depth,
+		debug_names,
+		trace,
+		profile,
+		coverage,
+		call_async,
+		compact_data,
+		tree_shake,
+		fold_offsets,
+		command,
+		function,
+		#[cfg(feature = "run")]
+		run,
+		#[cfg(feature = "run")]
+		import,
+		#[cfg(feature = "run")]
+		invoke,
+		#[cfg(feature = "verify")]
+		count: count.unwrap_or(VERIFY_COUNT),
+		#[cfg(feature = "verify")]
+		seed: seed.unwrap_or(VERIFY_SEED),
+	})
+}
+
+fn read_input(path: Option<&str>) -> Result<Vec<u8>> {
+	let Some(path) = path else {
+		let mut data = Vec::new();
+
+		std::io::stdin().lock().read_to_end(&mut data)?;
+
+		return Ok(data);
+	};
+
+	std::fs::read(path)
+}
+
+// Text modules are only understood when built with the `wat` feature,
+// binary modules are passed through either way
+#[cfg(feature = "wat")]
+fn read_source(path: Option<&str>) -> Result<Vec<u8>> {
+	let data = read_input(path)?;
+
+	wat::parse_bytes(&data)
+		.map(std::borrow::Cow::into_owned)
+		.map_err(|e| Error::new(ErrorKind::InvalidData, e))
+}
+
+#[cfg(not(feature = "wat"))]
+fn read_source(path: Option<&str>) -> Result<Vec<u8>> {
+	read_input(path)
+}
+
+fn do_enclosed_runtime(start: &str, runtime: &str, lock: &mut dyn Write) -> Result<()> {
+	writeln!(lock, "{start}")?;
+	writeln!(lock, "{runtime}")?;
+	writeln!(lock, "end)()")
+}
+
+fn do_runtime(options: &Options, lock: &mut dyn Write) -> Result<()> {
+	let target = options.target;
+	let runtime = options.runtime();
+
+	match target {
+		Target::Luau => {
+			writeln!(lock, "--!optimize 2")?;
+			writeln!(lock, "{runtime}")
+		}
+		Target::Teal => do_enclosed_runtime("local rt: any = (function(): any", &runtime, lock),
+		_ if target.is_enclosed() => do_enclosed_runtime("local rt = (function()", &runtime, lock),
+		_ => writeln!(lock, "{runtime}"),
+	}
+}
+
+// The shared runtime is loaded on its own, so enclosed runtimes are written as
+// they are and the others return a table of everything they declare
+fn do_shared_runtime(options: &Options, lock: &mut dyn Write) -> Result<()> {
+	let target = options.target;
+	let runtime = options.runtime();
+
+	if target == Target::Luau {
+		writeln!(lock, "--!optimize 2")?;
+	}
+
+	if target.is_enclosed() {
+		writeln!(lock, "{runtime}")
+	} else {
+		codegen_core::write_runtime_table(&runtime, lock)
+	}
+}
+
+// Loads the script called `name` from the same place as the one loading it
+fn build_import(target: Target, name: &str) -> String {
+	match target {
+		Target::Luau => format!("require(script.Parent.{name})"),
+		Target::GLua => format!("include(\"{name}.lua\")"),
+		_ => format!("require(\"{name}\")"),
+	}
+}
+
+// The runtime is loaded with the expression given by `--runtime`, or else
+// from the shared script
+fn build_runtime_import(options: &Options) -> String {
+	options
+		.runtime
+		.clone()
+		.unwrap_or_else(|| build_import(options.target, RUNTIME_NAME))
+}
+
+// Chunks take the parts of a runtime loaded on its own from the module
+fn build_shared_name_list(options: &Options) -> BTreeSet<&'static str> {
+	let target = options.target;
+
+	if target.is_enclosed() {
+		return BTreeSet::from(["rt"]);
+	}
+
+	let mut name_list = codegen_core::build_runtime_name_list(target.runtime());
+
+	if let Some(trace) = target.trace().filter(|_| options.trace) {
+		name_list.extend(codegen_core::build_runtime_name_list(trace));
+	}
+
+	name_list
+}
+
+fn do_shared_import(
+	options: &Options,
+	import: &str,
+	data: &[u8],
+	lock: &mut dyn Write,
+) -> Result<()> {
+	let target = options.target;
+
+	match target {
+		Target::Luau => writeln!(lock, "--!optimize 2")?,
+		Target::Teal => writeln!(lock, "local rt: any = {import}")?,
+		_ if target.is_enclosed() => writeln!(lock, "local rt = {import}")?,
+		_ => {}
+	}
+
+	if target.is_enclosed() {
+		lock.write_all(data)
+	} else {
+		codegen_core::write_runtime_binding(&options.runtime(), import, data, lock)
+	}
+}
+
+fn do_module(options: &Options, wasm: &Module, lock: &mut dyn Write) -> Result<()> {
+	let type_info = TypeInfo::from_module(wasm);
+	let name_list: Vec<_> = options.custom_list.iter().map(String::as_str).collect();
+	let name_list = &name_list[..];
+	let translation = &options.translation();
+
+	match options.target {
+		Target::LuaJIT if options.no_goto => {
+			let backend = LuaJIT { no_goto: true };
+
+			codegen_core::from_module_custom(
+				&backend,
+				wasm,
+				&type_info,
+				name_list,
+				translation,
+				lock,
+			)
+		}
+		Target::LuaJIT => {
+			codegen_luajit::from_module_custom(wasm, &type_info, name_list, translation, lock)
+		}
+		Target::Luau => {
+			codegen_luau::from_module_custom(wasm, &type_info, name_list, translation, lock)
+		}
+		Target::Lua51 => {
+			codegen_lua51::from_module_custom(wasm, &type_info, name_list, translation, lock)
+		}
+		Target::Lua54 => {
+			codegen_lua54::from_module_custom(wasm, &type_info, name_list, translation, lock)
+		}
+		Target::GLua => {
+			codegen_glua::from_module_custom(wasm, &type_info, name_list, translation, lock)
+		}
+		Target::Teal => {
+			codegen_teal::from_module_custom(wasm, &type_info, name_list, translation, lock)
+		}
+		Target::CCTweaked => {
+			codegen_cct::from_module_custom(wasm, &type_info, name_list, translation, lock)
+		}
+		Target::OpenResty => {
+			codegen_openresty::from_module_custom(wasm, &type_info, name_list, translation, lock)
+		}
+		Target::Love => {
+			codegen_love::from_module_custom(wasm, &type_info, name_list, translation, lock)
+		}
+		Target::Fengari => {
+			codegen_fengari::from_module_custom(wasm, &type_info, name_list, translation, lock)
+		}
+	}
+}
+
+fn do_module_split(
+	options: &Options,
+	wasm: &Module,
+	split: &Split,
+	lock: &mut dyn Write,
+) -> Result<Vec<Vec<u8>>> {
+	let type_info = TypeInfo::from_module(wasm);
+	let name_list: Vec<_> = options.custom_list.iter().map(String::as_str).collect();
+	let name_list = &name_list[..];
+	let translation = &options.translation();
+
+	match options.target {
+		Target::LuaJIT if options.no_goto => {
+			let backend = LuaJIT { no_goto: true };
+
+			codegen_core::from_module_split(
+				&backend,
+				wasm,
+				&type_info,
+				name_list,
+				translation,
+				split,
+				lock,
+			)
+		}
+		Target::LuaJIT => {
+			codegen_luajit::from_module_split(wasm, &type_info, name_list, translation, split, lock)
+		}
+		Target::Luau => {
+			codegen_luau::from_module_split(wasm, &type_info, name_list, translation, split, lock)
+		}
+		Target::Lua51 => {
+			codegen_lua51::from_module_split(wasm, &type_info, name_list, translation, split, lock)
+		}
+		Target::Lua54 => {
+			codegen_lua54::from_module_split(wasm, &type_info, name_list, translation, split, lock)
+		}
+		Target::GLua => {
+			codegen_glua::from_module_split(wasm, &type_info, name_list, translation, split, lock)
+		}
+		Target::Teal => unreachable!("`--split` is rejected for Teal"),
+		Target::CCTweaked => {
+			codegen_cct::from_module_split(wasm, &type_info, name_list, translation, split, lock)
+		}
+		Target::OpenResty => codegen_openresty::from_module_split(
+			wasm,
+			&type_info,
+			name_list,
+			translation,
+			split,
+			lock,
+		),
+		Target::Love => {
+			codegen_love::from_module_split(wasm, &type_info, name_list, translation, split, lock)
+		}
+		Target::Fengari => codegen_fengari::from_module_split(
+			wasm,
+			&type_info,
+			name_list,
+			translation,
+			split,
+			lock,
+		),
+	}
+}
+
+fn do_module_mapped(
+	options: &Options,
+	wasm: &Module,
+	lock: &mut dyn Write,
+) -> Result<Vec<Mapping>> {
+	let type_info = TypeInfo::from_module(wasm);
+	let name_list: Vec<_> = options.custom_list.iter().map(String::as_str).collect();
+	let name_list = &name_list[..];
+	let translation = &options.translation();
+
+	match options.target {
+		Target::LuaJIT if options.no_goto => {
+			let backend = LuaJIT { no_goto: true };
+
+			codegen_core::from_module_mapped(
+				&backend,
+				wasm,
+				&type_info,
+				name_list,
+				translation,
+				lock,
+			)
+		}
+		Target::LuaJIT => {
+			codegen_luajit::from_module_mapped(wasm, &type_info, name_list, translation, lock)
+		}
+		Target::Luau => {
+			codegen_luau::from_module_mapped(wasm, &type_info, name_list, translation, lock)
+		}
+		Target::Lua51 => {
+			codegen_lua51::from_module_mapped(wasm, &type_info, name_list, translation, lock)
+		}
+		Target::Lua54 => {
+			codegen_lua54::from_module_mapped(wasm, &type_info, name_list, translation, lock)
+		}
+		Target::GLua => {
+			codegen_glua::from_module_mapped(wasm, &type_info, name_list, translation, lock)
+		}
+		Target::Teal => {
+			codegen_teal::from_module_mapped(wasm, &type_info, name_list, translation, lock)
+		}
+		Target::CCTweaked => {
+			codegen_cct::from_module_mapped(wasm, &type_info, name_list, translation, lock)
+		}
+		Target::OpenResty => {
+			codegen_openresty::from_module_mapped(wasm, &type_info, name_list, translation, lock)
+		}
+		Target::Love => {
+			codegen_love::from_module_mapped(wasm, &type_info, name_list, translation, lock)
+		}
+		Target::Fengari => {
+			codegen_fengari::from_module_mapped(wasm, &type_info, name_list, translation, lock)
+		}
+	}
+}
+
+fn do_translate(options: &Options, data: &[u8], lock: &mut dyn Write) -> Result<()> {
+	let wasm = Module::try_from_data(data).unwrap();
+
+	do_module(options, &wasm, lock)
+}
+
+// The map is written to `path` with its lines counted from after `runtime`
+fn do_translate_mapped(
+	options: &Options,
+	data: &[u8],
+	runtime: &[u8],
+	path: &Path,
+	lock: &mut dyn Write,
+) -> Result<()> {
+	let wasm = Module::try_from_data(data).unwrap();
+	let mapping_list = do_module_mapped(options, &wasm, lock)?;
+	let line_offset = runtime.iter().filter(|&&v| v == b'\n').count();
+	let file = &mut BufWriter::new(File::create(path)?);
+
+	codegen_core::write_source_map(&mapping_list, line_offset, file)?;
+
+	file.flush()
+}
+
+// Chunks are written next to the script at `path`, named after it
+fn do_translate_split(
+	options: &Options,
+	data: &[u8],
+	name_list: BTreeSet<&str>,
+	path: &Path,
+	lock: &mut dyn Write,
+) -> Result<()> {
+	let stem = path.file_stem().and_then(OsStr::to_str).unwrap_or_default();
+	let extension = path
+		.extension()
+		.unwrap_or_else(|| options.target.extension().as_ref());
+
+	let chunk_name = |index: usize| format!("{stem}_{}", index + 1);
+	let import = |index| build_import(options.target, &chunk_name(index));
+	let split = Split {
+		limit: options.split.unwrap_or_default(),
+		name_list,
+		import: &import,
+	};
+
+	let wasm = Module::try_from_data(data).unwrap();
+	let chunk_list = do_module_split(options, &wasm, &split, lock)?;
+
+	for (i, chunk) in chunk_list.into_iter().enumerate() {
+		let mut result = Vec::new();
+
+		if options.target == Target::Luau {
+			writeln!(result, "--!optimize 2")?;
+		}
+
+		result.extend(chunk);
+
+		let path = path.with_file_name(chunk_name(i)).with_extension(extension);
+
+		std::fs::write(path, do_post_process(options, Vec::new(), result))?;
+	}
+
+	Ok(())
+}
+
+fn do_post_process(options: &Options, mut runtime: Vec<u8>, mut module: Vec<u8>) -> Vec<u8> {
+	let mut buffer = if options.shorten {
+		codegen_core::shorten(&runtime, &module)
+	} else {
+		runtime.append(&mut module);
+		runtime
+	};
+
+	if options.minify {
+		buffer = codegen_core::minify(&buffer);
+	}
+
+	buffer
+}
+
+// Shortening and minifying need the whole output, so it is buffered before
+// being written
+fn do_output(options: &Options, data: &[u8], lock: &mut dyn Write) -> Result<()> {
+	if !options.shorten
+		&& !options.minify
+		&& options.split.is_none()
+		&& options.source_map.is_none()
+		&& options.runtime.is_none()
+	{
+		do_runtime(options, lock)?;
+
+		return do_translate(options, data, lock);
+	}
+
+	let mut runtime = Vec::new();
+	let mut module = Vec::new();
+
+	if options.runtime.is_none() {
+		do_runtime(options, &mut runtime)?;
+	}
+
+	if let (Some(_), Some(path)) = (options.split, &options.output) {
+		let runtime = String::from_utf8_lossy(&runtime);
+		let name_list = if options.runtime.is_some() {
+			build_shared_name_list(options)
+		} else {
+			codegen_core::build_runtime_name_list(&runtime)
+		};
+
+		do_translate_split(options, data, name_list, Path::new(path), &mut module)?;
+	} else if let Some(path) = &options.source_map {
+		do_translate_mapped(options, data, &runtime, Path::new(path), &mut module)?;
+	} else {
+		do_translate(options, data, &mut module)?;
+	}
+
+	if let Some(import) = &options.runtime {
+		let mut result = Vec::new();
+
+		do_shared_import(options, import, &module, &mut result)?;
+		module = result;
+	}
+
+	lock.write_all(&do_post_process(options, runtime, module))
+}
+
+// Scripts either load the shared runtime written next to them or carry their
+// own copy, as a single output does
+fn do_batch(options: &Options, dir: &Path) -> Result<()> {
+	let extension = options.target.extension();
+
+	std::fs::create_dir_all(dir)?;
+
+	if !options.inline_runtime {
+		let mut runtime = Vec::new();
+
+		// Teal can load the runtime as plain Lua, so it is not typed
+		let runtime_path = match options.target {
+			Target::Teal => dir.join(RUNTIME_NAME).with_extension("lua"),
+			_ => dir.join(RUNTIME_NAME).with_extension(extension),
+		};
+
+		do_shared_runtime(options, &mut runtime)?;
+		std::fs::write(runtime_path, do_post_process(options, runtime, Vec::new()))?;
+	}
+
+	for input in &options.input_list {
+		let name = Path::new(input).file_stem().unwrap_or_default();
+
+		if name == RUNTIME_NAME && !options.inline_runtime {
+			return Err(fail_usage(&format!("`{input}` would replace the runtime")));
+		}
+
+		let data = read_source(Some(input))?;
+		let mut runtime = Vec::new();
+		let mut module = Vec::new();
+
+		let path = dir.join(name).with_extension(extension);
+
+		if options.inline_runtime {
+			do_runtime(options, &mut runtime)?;
+		}
+
+		if options.split.is_some() {
+			let runtime = String::from_utf8_lossy(&runtime);
+			let name_list = if options.inline_runtime {
+				codegen_core::build_runtime_name_list(&runtime)
+			} else {
+				build_shared_name_list(options)
+			};
+
+			do_translate_split(options, &data, name_list, &path, &mut module)?;
+		} else {
+			do_translate(options, &data, &mut module)?;
+		}
+
+		let result = if options.inline_runtime {
+			module
+		} else {
+			let mut result = Vec::new();
+
+			do_shared_import(
+				options,
+				&build_runtime_import(options),
+				&module,
+				&mut result,
+			)?;
+			result
+		};
+
+		std::fs::write(path, do_post_process(options, runtime, result))?;
+	}
+
+	Ok(())
+}
+
+#[cfg(feature = "run")]
+fn do_run(options: &Options, data: &[u8]) -> Result<()> {
+	let mut source = Vec::new();
+
+	do_output(options, data, &mut source)?;
+
+	run::run(
+		&source,
+		options.import.as_deref(),
+		options.invoke.as_deref(),
+	)
+}
+
+#[cfg(feature = "verify")]
+fn do_verify(options: &Options, data: &[u8]) -> Result<()> {
+	let mut runtime = Vec::new();
+	let mut module = Vec::new();
+
+	do_runtime(options, &mut runtime)?;
+	write!(runtime, "{}", verify::BOUNDS_CHECK)?;
+	do_translate(options, data, &mut module)?;
+
+	let source = do_post_process(options, runtime, module);
+
+	verify::verify(data, &source, options.count, options.seed)
+}
+
+fn do_dump(options: &Options, data: &[u8], lock: &mut dyn Write) -> Result<()> {
+	let wasm = Module::try_from_data(data).unwrap();
+	let type_info = TypeInfo::from_module(&wasm);
+	let offset = wasm.import_count(External::Func);
+
+	if let Some(index) = options.function {
+		if index < offset || index >= wasm.function_space() {
+			return Err(fail_usage(&format!(
+				"function {index} is not defined in the module"
+			)));
+		}
+	}
+
+	let mut builder = Factory::from_type_info(&type_info);
+	let mut func_list: Vec<_> = wasm
+		.code_section()
+		.iter()
+		.enumerate()
+		.map(|(i, body)| builder.create_indexed(i + offset, body).unwrap())
+		.collect();
+
+	wasm_ast::optimize::devirtualize(&wasm, &mut func_list, options.level);
+	wasm_ast::optimize::inline(&mut func_list, offset, options.level);
+	wasm_ast::optimize::hoist(&wasm, &mut func_list, options.level);
+
+	for (i, func) in func_list.iter_mut().enumerate() {
+		let index = i + offset;
+
+		if options.function.is_some_and(|v| v != index) {
+			continue;
+		}
+
+		wasm_ast::optimize::optimize(func, options.level);
+
+		if options.fold_offsets {
+			wasm_ast::optimize::fold_offsets(func);
+		}
+		wasm_ast::dump::write_func_data(index, func, lock)?;
+	}
+
+	Ok(())
+}
+
+// Teal loads the runtime as plain Lua, so it is checked as Lua 5.4 is
+fn do_self_test(options: &Options, lock: &mut dyn Write) -> Result<()> {
+	let target = options.target;
+
+	if target == Target::Teal {
+		do_enclosed_runtime("local rt = (function()", &options.runtime(), lock)?;
+	} else {
+		do_runtime(options, lock)?;
+	}
+
+	match target {
+		Target::LuaJIT => codegen_luajit::write_self_test(lock),
+		Target::Luau => codegen_luau::write_self_test(lock),
+		Target::Lua51 => codegen_lua51::write_self_test(lock),
+		Target::Lua54 | Target::Teal => codegen_lua54::write_self_test(lock),
+		Target::GLua => codegen_glua::write_self_test(lock),
+		Target::CCTweaked => codegen_cct::write_self_test(lock),
+		Target::OpenResty => codegen_openresty::write_self_test(lock),
+		Target::Love => codegen_love::write_self_test(lock),
+		Target::Fengari => codegen_fengari::write_self_test(lock),
+	}
+}
+
+fn do_build(options: &Options) -> Result<()> {
+	if let Some(dir) = &options.out_dir {
+		return do_batch(options, Path::new(dir));
+	}
+
+	if options.command == Command::SelfTest {
+		let file: Box<dyn Write> = match &options.output {
+			Some(path) => Box::new(File::create(path)?),
+			None => Box::new(std::io::stdout().lock()),
+		};
+
+		let lock = &mut BufWriter::new(file);
+
+		do_self_test(options, lock)?;
+
+		return lock.flush();
+	}
+
+	let input = options.input_list.first().filter(|v| *v != "-");
+	let data = read_source(input.map(String::as_str))?;
+
+	#[cfg(feature = "run")]
+	if options.run {
+		return do_run(options, &data);
+	}
+
+	#[cfg(feature = "verify")]
+	if options.command == Command::Verify {
+		return do_verify(options, &data);
+	}
+
+	let file: Box<dyn Write> = match &options.output {
+		Some(path) => Box::new(File::create(path)?),
+		None => Box::new(std::io::stdout().lock()),
+	};
+
+	let lock = &mut BufWriter::new(file);
+
+	if options.command == Command::Dump {
+		do_dump(options, &data, lock)?;
+	} else {
+		do_output(options, &data, lock)?;
+	}
+
+	lock.flush()
+}
+
+fn main() -> Result<()> {
+	let options = load_arg_options()?;
+
+	if options.command == Command::Watch {
+		watch::watch(&options.input_list, || do_build(&options));
+	}
+
+	do_build(&options)
+}
@@ -0,0 +1,75 @@
+use std::io::{Error, Result};
+
+use mlua::{Function, Lua, LuaOptions, MultiValue, StdLib, Table};
+
+fn load_import_list<'lua>(lua: &'lua Lua, path: Option<&str>) -> mlua::Result<Table<'lua>> {
+	let Some(path) = path else {
+		return lua.create_table();
+	};
+
+	let source = std::fs::read(path)?;
+
+	lua.load(source).set_name(path).eval()
+}
+
+fn print_result_list(lua: &Lua, list: MultiValue) -> mlua::Result<()> {
+	let to_string: Function = lua.globals().get("tostring")?;
+	let list = list
+		.into_iter()
+		.map(|v| to_string.call(v))
+		.collect::<mlua::Result<Vec<String>>>()?;
+
+	if !list.is_empty() {
+		println!("{}", list.join("\t"));
+	}
+
+	Ok(())
+}
+
+/// Creates a LuaJIT state with the libraries that translations use.
+pub fn new_state() -> Lua {
+	// The LuaJIT runtime keeps memories in buffers made through `ffi`, which
+	// is only opened for an unsafe state
+	unsafe { Lua::unsafe_new_with(StdLib::ALL_SAFE | StdLib::FFI, LuaOptions::new()) }
+}
+
+/// Loads the translated `source` and instantiates it with `import`, returning
+/// the table of its exports.
+pub fn load_instance<'lua>(
+	lua: &'lua Lua,
+	source: &[u8],
+	import: Table<'lua>,
+) -> mlua::Result<Table<'lua>> {
+	let new: Function = lua.load(source).set_name("module").eval()?;
+
+	new.call(import)
+}
+
+fn run_source(source: &[u8], import: Option<&str>, invoke: Option<&str>) -> mlua::Result<()> {
+	let lua = new_state();
+	let instance = load_instance(&lua, source, load_import_list(&lua, import)?)?;
+	let func_list: Table = instance.get("func_list")?;
+
+	// Without a named export, `_start` is run as WASI commands expect if the
+	// module has one, and otherwise instantiating it is all there is to do
+	let func = match invoke {
+		Some(name) => func_list
+			.get::<_, Option<Function>>(name)?
+			.ok_or_else(|| mlua::Error::runtime(format!("no exported function `{name}`")))?,
+		None => match func_list.get::<_, Option<Function>>("_start")? {
+			Some(func) => func,
+			None => return Ok(()),
+		},
+	};
+
+	let result = func.call(())?;
+
+	print_result_list(&lua, result)
+}
+
+/// Runs the translated `source` in an embedded LuaJIT, instantiating it with
+/// the table of imports returned by the Lua file at `import` and calling the
+/// export named `invoke`.
+pub fn run(source: &[u8], import: Option<&str>, invoke: Option<&str>) -> Result<()> {
+	run_source(source, import, invoke).map_err(|e| Error::other(e.to_string()))
+}
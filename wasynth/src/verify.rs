@@ -0,0 +1,460 @@
+use std::{
+	fmt::{Display, Formatter},
+	io::{Error, Result},
+};
+
+use mlua::{Function, Lua, Table, Variadic};
+use wasmtime::{Engine, ExternType, FuncType, Instance, Module, Store, Val, ValType};
+
+use crate::run;
+
+// Integers of 64 bits are `int64_t` in the LuaJIT runtime, so they are made
+// from halves that fit in a number. Results come back as whether they were
+// numbers and the halves of their bits, since `mlua` takes any number within
+// an epsilon of an integer to be that integer and would lose small floats
+const CONVERT: &str = r#"
+local ffi = require("ffi")
+local bit = require("bit")
+
+local bits_t = ffi.typeof("union { double f64; uint64_t u64; }")
+
+local function to_i64(high, low)
+	local value = bit.bor(bit.lshift(ffi.cast("uint64_t", high), 32), low)
+
+	return ffi.cast("int64_t", value)
+end
+
+local function to_halves(value)
+	if type(value) == "number" then
+		value = bits_t(value).u64
+	end
+
+	value = ffi.cast("uint64_t", value)
+
+	return tonumber(bit.rshift(value, 32)), tonumber(bit.band(value, 0xFFFFFFFFULL))
+end
+
+local function call(func, len, ...)
+	local list = { func(...) }
+	local result = {}
+
+	for i = 1, len do
+		local value = list[i]
+
+		if value == nil then
+			error("missing result " .. i)
+		end
+
+		result[i * 3 - 2] = type(value) == "number" and 1 or 0
+		result[i * 3 - 1], result[i * 3] = to_halves(value)
+	end
+
+	return unpack(result, 1, len * 3)
+end
+
+return to_i64, call
+"#;
+
+/// Wraps the memory accesses of the runtime `rt` to trap out of bounds, as
+/// LuaJIT reaches memory through `ffi` without checks and would read or write
+/// past its end where wasmtime traps. It goes between the runtime and module.
+pub const BOUNDS_CHECK: &str = r#"
+do
+	local function check(memory, addr, len)
+		if addr < 0 or addr + len > memory.min * 65536 then
+			rt.trap.raise("out_of_bounds_memory_access", "out of bounds memory access")
+		end
+	end
+
+	for name, func in pairs(rt.load) do
+		local bits = string.match(name, "%d+$")
+
+		if bits then
+			local len = tonumber(bits) / 8
+
+			rt.load[name] = function(memory, addr)
+				check(memory, addr, len)
+
+				return func(memory, addr)
+			end
+		end
+	end
+
+	for name, func in pairs(rt.store) do
+		local bits = string.match(name, "%d+$")
+
+		if bits then
+			local len = tonumber(bits) / 8
+
+			rt.store[name] = function(memory, addr, value)
+				check(memory, addr, len)
+
+				return func(memory, addr, value)
+			end
+		end
+	end
+
+	local load_string = rt.load.string
+	local store_string = rt.store.string
+	local store_copy = rt.store.copy
+	local store_fill = rt.store.fill
+
+	function rt.load.string(memory, addr, len)
+		check(memory, addr, len)
+
+		return load_string(memory, addr, len)
+	end
+
+	function rt.store.string(memory, addr, data, len)
+		check(memory, addr, len or #data)
+
+		return store_string(memory, addr, data, len)
+	end
+
+	function rt.store.copy(memory_1, addr_1, memory_2, addr_2, len)
+		check(memory_1, addr_1, len)
+		check(memory_2, addr_2, len)
+
+		return store_copy(memory_1, addr_1, memory_2, addr_2, len)
+	end
+
+	function rt.store.fill(memory, addr, len, value)
+		check(memory, addr, len)
+
+		return store_fill(memory, addr, len, value)
+	end
+end
+"#;
+
+const I32_EDGE_LIST: [i32; 5] = [0, 1, -1, i32::MIN, i32::MAX];
+const I64_EDGE_LIST: [i64; 7] = [
+	0,
+	1,
+	-1,
+	i64::MIN,
+	i64::MAX,
+	i32::MIN as i64,
+	u32::MAX as i64,
+];
+const F32_EDGE_LIST: [f32; 7] = [
+	0.0,
+	-0.0,
+	1.0,
+	-1.0,
+	f32::INFINITY,
+	f32::NEG_INFINITY,
+	f32::NAN,
+];
+const F64_EDGE_LIST: [f64; 7] = [
+	0.0,
+	-0.0,
+	1.0,
+	-1.0,
+	f64::INFINITY,
+	f64::NEG_INFINITY,
+	f64::NAN,
+];
+
+#[derive(Clone, Copy)]
+enum Value {
+	I32(i32),
+	I64(i64),
+	F32(f32),
+	F64(f64),
+}
+
+impl Value {
+	fn from_val(val: &Val) -> Self {
+		match *val {
+			Val::I32(v) => Self::I32(v),
+			Val::I64(v) => Self::I64(v),
+			Val::F32(v) => Self::F32(f32::from_bits(v)),
+			Val::F64(v) => Self::F64(f64::from_bits(v)),
+			_ => unreachable!("only numbers are generated"),
+		}
+	}
+
+	fn to_val(self) -> Val {
+		match self {
+			Self::I32(v) => Val::I32(v),
+			Self::I64(v) => Val::I64(v),
+			Self::F32(v) => Val::F32(v.to_bits()),
+			Self::F64(v) => Val::F64(v.to_bits()),
+		}
+	}
+
+	// Lua does not keep the payload of a NaN, so any two of them agree
+	fn is_same(self, other: Self) -> bool {
+		match (self, other) {
+			(Self::I32(a), Self::I32(b)) => a == b,
+			(Self::I64(a), Self::I64(b)) => a == b,
+			(Self::F32(a), Self::F32(b)) => a.to_bits() == b.to_bits() || a.is_nan() && b.is_nan(),
+			(Self::F64(a), Self::F64(b)) => a.to_bits() == b.to_bits() || a.is_nan() && b.is_nan(),
+			_ => false,
+		}
+	}
+}
+
+impl Display for Value {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::I32(v) => write!(f, "{v}"),
+			Self::I64(v) => write!(f, "{v}"),
+			Self::F32(v) => write!(f, "{v:?}"),
+			Self::F64(v) => write!(f, "{v:?}"),
+		}
+	}
+}
+
+struct ValueList<'a>(&'a [Value]);
+
+impl Display for ValueList<'_> {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		write!(f, "(")?;
+
+		for (i, v) in self.0.iter().enumerate() {
+			if i != 0 {
+				write!(f, ", ")?;
+			}
+
+			write!(f, "{v}")?;
+		}
+
+		write!(f, ")")
+	}
+}
+
+// A call either returns its results or fails with a message
+type Outcome = std::result::Result<Vec<Value>, String>;
+
+fn is_same_outcome(a: &Outcome, b: &Outcome) -> bool {
+	match (a, b) {
+		(Ok(a), Ok(b)) => a.len() == b.len() && a.iter().zip(b).all(|(a, b)| a.is_same(*b)),
+		(Err(_), Err(_)) => true,
+		_ => false,
+	}
+}
+
+struct OutcomeText<'a>(&'a Outcome);
+
+impl Display for OutcomeText<'_> {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		match self.0 {
+			Ok(list) => write!(f, "returned {}", ValueList(list)),
+			Err(message) => {
+				let line = message.lines().next().unwrap_or_default();
+
+				write!(f, "failed with `{line}`")
+			}
+		}
+	}
+}
+
+// Arguments are drawn from a xorshift generator so that a seed always gives
+// the same calls
+struct Generator {
+	state: u64,
+}
+
+impl Generator {
+	fn next(&mut self) -> u64 {
+		self.state ^= self.state << 13;
+		self.state ^= self.state >> 7;
+		self.state ^= self.state << 17;
+		self.state
+	}
+
+	fn pick<T: Copy>(&mut self, list: &[T]) -> T {
+		list[(self.next() % list.len() as u64) as usize]
+	}
+
+	// One value in four sits at the edge of its type and one in four is
+	// small, as those are where translations tend to go wrong
+	fn next_value(&mut self, kind: &ValType) -> Value {
+		let choice = self.next() % 4;
+		let bits = self.next();
+		let small = (bits % 65) as i32 - 32;
+
+		match (kind, choice) {
+			(ValType::I32, 0) => Value::I32(self.pick(&I32_EDGE_LIST)),
+			(ValType::I32, 1) => Value::I32(small),
+			(ValType::I32, _) => Value::I32(bits as i32),
+			(ValType::I64, 0) => Value::I64(self.pick(&I64_EDGE_LIST)),
+			(ValType::I64, 1) => Value::I64(small.into()),
+			(ValType::I64, _) => Value::I64(bits as i64),
+			(ValType::F32, 0) => Value::F32(self.pick(&F32_EDGE_LIST)),
+			(ValType::F32, 1) => Value::F32(small as f32),
+			(ValType::F32, _) => Value::F32(f32::from_bits(bits as u32)),
+			(ValType::F64, 0) => Value::F64(self.pick(&F64_EDGE_LIST)),
+			(ValType::F64, 1) => Value::F64(small.into()),
+			(ValType::F64, _) => Value::F64(f64::from_bits(bits)),
+			_ => unreachable!("only numbers are generated"),
+		}
+	}
+}
+
+struct Bridge<'lua> {
+	to_i64: Function<'lua>,
+	call: Function<'lua>,
+}
+
+impl<'lua> Bridge<'lua> {
+	fn new(lua: &'lua Lua) -> mlua::Result<Self> {
+		let (to_i64, call) = lua.load(CONVERT).set_name("verify").eval()?;
+
+		Ok(Self { to_i64, call })
+	}
+
+	fn write_value(&self, value: Value) -> mlua::Result<mlua::Value<'lua>> {
+		match value {
+			Value::I32(v) => Ok(mlua::Value::Number(v.into())),
+			Value::I64(v) => {
+				let v = v as u64;
+
+				self.to_i64.call((v >> 32, v & 0xFFFF_FFFF))
+			}
+			Value::F32(v) => Ok(mlua::Value::Number(v.into())),
+			Value::F64(v) => Ok(mlua::Value::Number(v)),
+		}
+	}
+
+	// Numbers are converted from the float they hold and anything else, which
+	// can only be an `int64_t`, from its bits
+	fn read_value(kind: &ValType, is_number: bool, bits: u64) -> Value {
+		let float = f64::from_bits(bits);
+
+		match kind {
+			ValType::I32 if is_number => Value::I32(float as i64 as i32),
+			ValType::I32 => Value::I32(bits as i32),
+			ValType::I64 if is_number => Value::I64(float as i64),
+			ValType::I64 => Value::I64(bits as i64),
+			ValType::F32 => Value::F32(float as f32),
+			ValType::F64 => Value::F64(float),
+			_ => unreachable!("only numbers are generated"),
+		}
+	}
+
+	fn call(&self, func: &Function<'lua>, kind: &FuncType, param_list: &[Value]) -> Outcome {
+		let result = param_list
+			.iter()
+			.map(|v| self.write_value(*v))
+			.collect::<mlua::Result<Variadic<_>>>()
+			.and_then(|list| {
+				let len = kind.results().len();
+
+				self.call
+					.call::<_, Variadic<u32>>((func.clone(), len, list))
+			})
+			.map(|list| {
+				kind.results()
+					.zip(list.chunks(3))
+					.map(|(kind, v)| {
+						let bits = u64::from(v[1]) << 32 | u64::from(v[2]);
+
+						Self::read_value(&kind, v[0] != 0, bits)
+					})
+					.collect()
+			});
+
+		result.map_err(|e| e.to_string())
+	}
+}
+
+fn call_wasmtime(
+	store: &mut Store<()>,
+	instance: &Instance,
+	name: &str,
+	kind: &FuncType,
+	param_list: &[Value],
+) -> Outcome {
+	let func = instance.get_func(&mut *store, name).unwrap();
+	let param_list: Vec<_> = param_list.iter().map(|v| v.to_val()).collect();
+	let mut result_list = vec![Val::I32(0); kind.results().len()];
+
+	func.call(store, &param_list, &mut result_list)
+		.map_err(|e| e.root_cause().to_string())?;
+
+	Ok(result_list.iter().map(Value::from_val).collect())
+}
+
+fn is_number(kind: &ValType) -> bool {
+	matches!(
+		kind,
+		ValType::I32 | ValType::I64 | ValType::F32 | ValType::F64
+	)
+}
+
+fn to_error(error: impl Display) -> Error {
+	Error::other(error.to_string())
+}
+
+/// Calls every export of the module in `data` that takes and returns only
+/// numbers `count` times under both wasmtime and the LuaJIT translation in
+/// `source`, with arguments generated from `seed`, and reports the first call
+/// of each where the two disagree.
+pub fn verify(data: &[u8], source: &[u8], count: usize, seed: u64) -> Result<()> {
+	let engine = Engine::default();
+	let module = Module::new(&engine, data).map_err(to_error)?;
+
+	if module.imports().len() != 0 {
+		return Err(Error::other(
+			"`verify` only applies to modules without imports",
+		));
+	}
+
+	let mut store = Store::new(&engine, ());
+	let instance = Instance::new(&mut store, &module, &[]).map_err(to_error)?;
+
+	let lua = run::new_state();
+	let bridge = Bridge::new(&lua).map_err(to_error)?;
+	let func_list: Table = lua
+		.create_table()
+		.and_then(|import| run::load_instance(&lua, source, import))
+		.and_then(|instance| instance.get("func_list"))
+		.map_err(to_error)?;
+
+	let mut generator = Generator { state: seed.max(1) };
+	let mut failed = 0;
+
+	for export in module.exports() {
+		let ExternType::Func(kind) = export.ty() else {
+			continue;
+		};
+
+		let name = export.name();
+
+		if !kind.params().chain(kind.results()).all(|v| is_number(&v)) {
+			println!("`{name}`: skipped, as it takes or returns more than numbers");
+
+			continue;
+		}
+
+		let func: Function = func_list.get(name).map_err(to_error)?;
+		let mismatch = (0..count).find_map(|_| {
+			let param_list: Vec<_> = kind.params().map(|v| generator.next_value(&v)).collect();
+			let wanted = call_wasmtime(&mut store, &instance, name, &kind, &param_list);
+			let result = bridge.call(&func, &kind, &param_list);
+
+			(!is_same_outcome(&wanted, &result)).then_some((param_list, wanted, result))
+		});
+
+		if let Some((param_list, wanted, result)) = mismatch {
+			println!(
+				"`{name}`: disagrees when called with {}",
+				ValueList(&param_list)
+			);
+			println!("\twasmtime {}", OutcomeText(&wanted));
+			println!("\ttranslation {}", OutcomeText(&result));
+
+			failed += 1;
+		} else {
+			println!("`{name}`: {count} calls agree");
+		}
+	}
+
+	if failed != 0 {
+		return Err(Error::other(format!("{failed} exports disagree")));
+	}
+
+	Ok(())
+}
@@ -0,0 +1,45 @@
+use std::{
+	io::Result,
+	panic::AssertUnwindSafe,
+	thread,
+	time::{Duration, SystemTime},
+};
+
+const INTERVAL: Duration = Duration::from_millis(250);
+
+fn load_time_list(path_list: &[String]) -> Vec<Option<SystemTime>> {
+	path_list
+		.iter()
+		.map(|path| std::fs::metadata(path).and_then(|v| v.modified()).ok())
+		.collect()
+}
+
+/// Calls `build` once and then again every time one of the files in
+/// `path_list` changes, reporting failures and carrying on. Files are polled
+/// for their modification time, so no platform specific watcher is needed.
+pub fn watch(path_list: &[String], mut build: impl FnMut() -> Result<()>) -> ! {
+	let mut last = Vec::new();
+	let mut built = Vec::new();
+
+	loop {
+		let now = load_time_list(path_list);
+
+		// Files still being written keep changing or go missing, so a build
+		// waits for all of them to exist and settle for one interval
+		if now == last && now != built && now.iter().all(Option::is_some) {
+			built.clone_from(&now);
+
+			// Panics have already been reported by the hook when they unwind
+			if let Ok(result) = std::panic::catch_unwind(AssertUnwindSafe(&mut build)) {
+				match result {
+					Ok(()) => eprintln!("translated {}", path_list.join(", ")),
+					Err(e) => eprintln!("failed to translate: {e}"),
+				}
+			}
+		}
+
+		last = now;
+
+		thread::sleep(INTERVAL);
+	}
+}